@@ -1,8 +1,11 @@
 //! Command-line argument definitions using clap derive macros.
 
 use clap::{Parser, ValueEnum};
+use std::ops::Range;
 use std::path::PathBuf;
 
+use sdif_rs::schemas;
+
 /// Convert MATLAB/Octave .mat files to SDIF format.
 ///
 /// mat2sdif reads numeric arrays from MAT files and writes them as
@@ -13,7 +16,7 @@ use std::path::PathBuf;
 #[command(author, version, about, long_about = None)]
 #[command(after_help = EXAMPLES)]
 pub struct Args {
-    /// Input .mat file
+    /// Input .mat file (use `-` to read from standard input)
     #[arg(value_name = "INPUT")]
     pub input: PathBuf,
 
@@ -28,6 +31,10 @@ pub struct Args {
     #[arg(short, long)]
     pub list: bool,
 
+    /// Number of rows of each variable to show in --list mode
+    #[arg(long, value_name = "N", default_value = "0")]
+    pub preview: usize,
+
     /// Validate conversion without writing output
     #[arg(long)]
     pub dry_run: bool,
@@ -100,6 +107,42 @@ pub struct Args {
     #[arg(long, value_enum, default_value = "magnitude")]
     pub complex_mode: ComplexModeArg,
 
+    /// Lowest level written by --complex-mode db, in dB
+    #[arg(
+        long,
+        value_name = "DB",
+        default_value = "-200",
+        allow_negative_numbers = true
+    )]
+    pub db_floor: f64,
+
+    /// Unwrap phases so they are continuous across frames
+    ///
+    /// Applies to the Phase column, or to the phases of --complex-mode
+    /// mag-phase.
+    #[arg(long)]
+    pub unwrap_phase: bool,
+
+    /// Expected value range of a column (repeatable)
+    ///
+    /// Values outside MIN..MAX are reported with their frame index, which
+    /// catches unit mistakes such as frequencies in kHz. Either bound may
+    /// be omitted, e.g. `Amplitude=0..`.
+    #[arg(long = "range", value_name = "NAME=MIN..MAX", value_parser = parse_column_range)]
+    pub ranges: Vec<(String, Range<f64>)>,
+
+    /// Fail instead of warning when a value is out of range
+    #[arg(long)]
+    pub strict_ranges: bool,
+
+    /// How to handle frames with the same time as the previous one
+    #[arg(long, value_enum, default_value = "keep")]
+    pub duplicate_times: DuplicateTimesArg,
+
+    /// Seconds between nudged frames with --duplicate-times offset
+    #[arg(long, value_name = "SECONDS", default_value = "0.000001")]
+    pub duplicate_offset: f64,
+
     // ========================================================================
     // Output Control
     // ========================================================================
@@ -127,6 +170,21 @@ pub enum ComplexModeArg {
     MagPhase,
     /// Output real and imaginary as separate columns
     ReIm,
+    /// Convert to magnitude in dB (see --db-floor)
+    Db,
+}
+
+/// How to handle frames with duplicate times.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DuplicateTimesArg {
+    /// Write every frame as is
+    Keep,
+    /// Merge the rows of frames at the same time into one frame
+    Merge,
+    /// Move duplicates slightly later (see --duplicate-offset)
+    Offset,
+    /// Fail the conversion
+    Error,
 }
 
 impl Args {
@@ -158,7 +216,7 @@ impl Args {
         }
 
         // Check input file exists
-        if !self.input.exists() {
+        if !self.input_is_stdin() && !self.input.exists() {
             return Err(format!(
                 "Input file not found: {}",
                 self.input.display()
@@ -175,6 +233,13 @@ impl Args {
             }
         }
 
+        if self.duplicate_offset.is_nan() || self.duplicate_offset <= 0.0 {
+            return Err(format!(
+                "Duplicate offset must be positive, got {}",
+                self.duplicate_offset
+            ));
+        }
+
         // Quiet and verbose are mutually exclusive
         if self.quiet && self.verbose {
             return Err("Cannot use both --quiet and --verbose".to_string());
@@ -183,6 +248,20 @@ impl Args {
         Ok(())
     }
 
+    /// Check whether the input should be read from standard input.
+    pub fn input_is_stdin(&self) -> bool {
+        self.input.as_os_str() == "-"
+    }
+
+    /// Get the input name for messages.
+    pub fn input_name(&self) -> String {
+        if self.input_is_stdin() {
+            "<stdin>".to_string()
+        } else {
+            self.input.display().to_string()
+        }
+    }
+
     /// Get default column names based on frame type.
     pub fn get_columns(&self) -> Vec<String> {
         if let Some(ref cols) = self.columns {
@@ -190,21 +269,9 @@ impl Args {
         }
 
         // Defaults based on frame type
-        match self.frame_type.as_str() {
-            "1TRC" | "1HRM" => vec![
-                "Index".to_string(),
-                "Frequency".to_string(),
-                "Amplitude".to_string(),
-                "Phase".to_string(),
-            ],
-            "1FQ0" => vec!["Frequency".to_string(), "Confidence".to_string()],
-            "1RES" => vec![
-                "Frequency".to_string(),
-                "Amplitude".to_string(),
-                "DecayRate".to_string(),
-                "Phase".to_string(),
-            ],
-            _ => vec![
+        match schemas::standard_columns(&self.frame_type) {
+            Some(cols) => cols.iter().map(|c| c.to_string()).collect(),
+            None => vec![
                 "Col1".to_string(),
                 "Col2".to_string(),
                 "Col3".to_string(),
@@ -214,12 +281,41 @@ impl Args {
     }
 }
 
+/// Parse a `NAME=MIN..MAX` column range, with open bounds when omitted.
+fn parse_column_range(text: &str) -> Result<(String, Range<f64>), String> {
+    let (name, range) = text
+        .split_once('=')
+        .ok_or_else(|| format!("Expected NAME=MIN..MAX, got '{}'", text))?;
+    let (min, max) = range
+        .split_once("..")
+        .ok_or_else(|| format!("Expected MIN..MAX after '{}=', got '{}'", name, range))?;
+
+    let bound = |value: &str, open: f64| -> Result<f64, String> {
+        if value.trim().is_empty() {
+            Ok(open)
+        } else {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid range bound '{}'", value))
+        }
+    };
+
+    Ok((
+        name.trim().to_string(),
+        bound(min, f64::NEG_INFINITY)?..bound(max, f64::INFINITY)?,
+    ))
+}
+
 /// Example usage shown in --help.
 const EXAMPLES: &str = r#"
 EXAMPLES:
     # List variables in a MAT file
     mat2sdif --list analysis.mat
 
+    # List variables with their first 5 rows
+    mat2sdif --list --preview 5 analysis.mat
+
     # Basic conversion with auto-detection
     mat2sdif analysis.mat output.sdif
 
@@ -229,6 +325,9 @@ EXAMPLES:
     # Convert with custom column names
     mat2sdif analysis.mat output.sdif -c "Index,Freq,Amp,Phase"
 
+    # Read the MAT file from a pipeline
+    fetch-analysis | mat2sdif - output.sdif
+
     # Convert F0 data
     mat2sdif pitch.mat f0.sdif -f 1FQ0 -m 1FQ0 -c "Frequency,Confidence"
 
@@ -240,6 +339,12 @@ EXAMPLES:
 
     # Legacy Max compatibility (256 partial limit)
     mat2sdif --max-partials 256 analysis.mat output.sdif
+
+    # Merge frames that share a timestamp
+    mat2sdif --duplicate-times merge analysis.mat output.sdif
+
+    # Reject frequencies above Nyquist and negative amplitudes
+    mat2sdif --strict-ranges --range Frequency=0..22050 --range Amplitude=0.. analysis.mat output.sdif
 "#;
 
 #[cfg(test)]
@@ -252,6 +357,7 @@ mod tests {
             input: PathBuf::from("test.mat"),
             output: Some(PathBuf::from("test.sdif")),
             list: false,
+            preview: 0,
             dry_run: false,
             time_var: None,
             data_var: None,
@@ -263,6 +369,12 @@ mod tests {
             max_compat: false,
             transpose: false,
             complex_mode: ComplexModeArg::Magnitude,
+            db_floor: -200.0,
+            unwrap_phase: false,
+            ranges: Vec::new(),
+            strict_ranges: false,
+            duplicate_times: DuplicateTimesArg::Keep,
+            duplicate_offset: 1e-6,
             verbose: false,
             quiet: false,
             force: false,
@@ -279,6 +391,7 @@ mod tests {
             input: PathBuf::from("test.mat"),
             output: Some(PathBuf::from("test.sdif")),
             list: false,
+            preview: 0,
             dry_run: false,
             time_var: None,
             data_var: None,
@@ -290,6 +403,12 @@ mod tests {
             max_compat: false,
             transpose: false,
             complex_mode: ComplexModeArg::Magnitude,
+            db_floor: -200.0,
+            unwrap_phase: false,
+            ranges: Vec::new(),
+            strict_ranges: false,
+            duplicate_times: DuplicateTimesArg::Keep,
+            duplicate_offset: 1e-6,
             verbose: false,
             quiet: false,
             force: false,
@@ -299,4 +418,19 @@ mod tests {
         assert_eq!(cols.len(), 2);
         assert_eq!(cols[0], "Frequency");
     }
+
+    #[test]
+    fn test_parse_column_range() {
+        assert_eq!(
+            parse_column_range("Frequency=20..20000"),
+            Ok(("Frequency".to_string(), 20.0..20000.0))
+        );
+        assert_eq!(
+            parse_column_range("Amplitude=0.."),
+            Ok(("Amplitude".to_string(), 0.0..f64::INFINITY))
+        );
+        assert!(parse_column_range("Frequency").is_err());
+        assert!(parse_column_range("Frequency=20-20000").is_err());
+        assert!(parse_column_range("Frequency=low..high").is_err());
+    }
 }
@@ -5,11 +5,15 @@ use std::time::Instant;
 use anyhow::{Context, Result, bail};
 use colored::Colorize;
 
-use sdif_rs::{MatFile, MatToSdifConfig, MatToSdifConverter, ComplexMode, SdifFile};
+use sdif_rs::{MatToSdifConfig, MatToSdifConverter, ComplexMode, DuplicateTimes, SdifFile};
 
-use crate::cli::{Args, ComplexModeArg};
+use crate::cli::{Args, ComplexModeArg, DuplicateTimesArg};
 use crate::max_compat;
-use crate::output::{self, ProgressReporter};
+use crate::output;
+use crate::progress::FrameProgress;
+
+/// Most out-of-range values reported individually.
+const MAX_RANGE_WARNINGS: usize = 10;
 
 /// Run the convert command.
 pub fn run(args: &Args) -> Result<()> {
@@ -19,13 +23,12 @@ pub fn run(args: &Args) -> Result<()> {
     let output_path = args.output.as_ref().unwrap();
 
     output::print_verbose(
-        &format!("Opening MAT file: {}", args.input.display()),
+        &format!("Opening MAT file: {}", args.input_name()),
         args.verbose,
     );
 
     // Load MAT file
-    let mat = MatFile::open(&args.input)
-        .with_context(|| format!("Failed to open MAT file: {}", args.input.display()))?;
+    let mat = super::open_input(args)?;
 
     if mat.is_empty() {
         bail!("No numeric variables found in MAT file");
@@ -57,28 +60,40 @@ pub fn run(args: &Args) -> Result<()> {
         max_compat::validate_config(args, &converter)?;
     }
 
+    // Column range checks (an error with --strict-ranges)
+    let range_warnings = converter.check_ranges()
+        .context("Column range check failed")?;
+    for warning in range_warnings.iter().take(MAX_RANGE_WARNINGS) {
+        output::print_warning(warning);
+    }
+    if range_warnings.len() > MAX_RANGE_WARNINGS {
+        output::print_warning(&format!(
+            "{} more values out of range",
+            range_warnings.len() - MAX_RANGE_WARNINGS
+        ));
+    }
+
     // Create SDIF writer
     let columns_strings = args.get_columns();
     let columns: Vec<&str> = columns_strings.iter().map(|s| s.as_str()).collect();
     let component = format!("{} Data", args.matrix_type);
 
+    let source = args.input_name();
     let mut writer = SdifFile::builder()
         .create(output_path)
         .with_context(|| format!("Failed to create output file: {}", output_path.display()))?
         .add_nvt([
             ("creator", "mat2sdif"),
-            ("source", args.input.to_str().unwrap_or("unknown")),
+            ("source", source.as_str()),
         ])?
         .add_matrix_type(&args.matrix_type, &columns)?
         .add_frame_type(&args.frame_type, &[&component])?
         .build()
         .context("Failed to initialize SDIF file")?;
 
-    // Progress reporter
-    let progress = ProgressReporter::new(num_frames, args.verbose);
-
     // Write frames
-    converter.write_to(&mut writer)
+    let progress = FrameProgress::new(num_frames, args.quiet);
+    converter.write_to_with_progress(&mut writer, |done| progress.set(done))
         .context("Failed to write frames")?;
 
     progress.finish();
@@ -104,7 +119,12 @@ pub(crate) fn build_config(args: &Args) -> Result<MatToSdifConfig> {
         .matrix_type(&args.matrix_type)
         .columns(&args.get_columns().iter().map(|s| s.as_str()).collect::<Vec<_>>())
         .stream_id(args.stream_id)
-        .transpose(args.transpose);
+        .transpose(args.transpose)
+        .strict_ranges(args.strict_ranges);
+
+    for (column, range) in &args.ranges {
+        config = config.column_range(column, range.clone());
+    }
 
     // Set max partials (0 = no limit)
     if args.max_partials > 0 {
@@ -129,6 +149,17 @@ pub(crate) fn build_config(args: &Args) -> Result<MatToSdifConfig> {
         ComplexModeArg::Magnitude => ComplexMode::Magnitude,
         ComplexModeArg::MagPhase => ComplexMode::MagnitudePhase,
         ComplexModeArg::ReIm => ComplexMode::RealImag,
+        ComplexModeArg::Db => ComplexMode::Db,
+    });
+    config = config.db_floor(args.db_floor);
+    config = config.unwrap_phase(args.unwrap_phase);
+
+    // Set duplicate time handling
+    config = config.duplicate_times(match args.duplicate_times {
+        DuplicateTimesArg::Keep => DuplicateTimes::Keep,
+        DuplicateTimesArg::Merge => DuplicateTimes::Merge,
+        DuplicateTimesArg::Offset => DuplicateTimes::Offset(args.duplicate_offset),
+        DuplicateTimesArg::Error => DuplicateTimes::Error,
     });
 
     Ok(config)
@@ -139,7 +170,7 @@ fn print_summary(args: &Args, frames: usize, duration: f64, elapsed: std::time::
     println!();
     output::print_success(
         &format!("Converted {} to {}",
-            args.input.display(),
+            args.input_name(),
             args.output.as_ref().unwrap().display()
         ),
         false,
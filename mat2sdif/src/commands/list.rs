@@ -1,22 +1,19 @@
 //! List variables command (--list mode).
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use colored::Colorize;
 
-use sdif_rs::MatFile;
-
 use crate::cli::Args;
 use crate::output;
 
 /// Run the list command.
 pub fn run(args: &Args) -> Result<()> {
     output::print_verbose(
-        &format!("Opening MAT file: {}", args.input.display()),
+        &format!("Opening MAT file: {}", args.input_name()),
         args.verbose,
     );
 
-    let mat = MatFile::open(&args.input)
-        .with_context(|| format!("Failed to open MAT file: {}", args.input.display()))?;
+    let mat = super::open_input(args)?;
 
     if mat.is_empty() {
         output::print_warning("No numeric variables found in MAT file");
@@ -26,7 +23,7 @@ pub fn run(args: &Args) -> Result<()> {
     }
 
     // Print header
-    println!("{}", format!("Variables in '{}':", args.input.display()).bold());
+    println!("{}", format!("Variables in '{}':", args.input_name()).bold());
     println!();
 
     // Collect and sort variable names
@@ -38,18 +35,16 @@ pub fn run(args: &Args) -> Result<()> {
 
     // Print header row
     println!(
-        "  {:<width$}  {:>14}  {:>10}  {}",
-        "Name", "Shape", "Type", "Notes",
-        width = max_name
+        "  Name{}           Shape        Type         Min         Max        Mean    NaN    Inf  Notes",
+        " ".repeat(max_name - 4)
     );
     println!(
-        "  {:-<width$}  {:->14}  {:->10}  -----",
-        "", "", "",
-        width = max_name
+        "  {}  --------------  ----------  ----------  ----------  ----------  -----  -----  -----",
+        "-".repeat(max_name)
     );
 
     // Print each variable
-    for (name, data) in vars {
+    for &(name, data) in &vars {
         let shape = format!("{:?}", data.shape());
         let dtype = if data.is_complex() {
             format!("{} (complex)", data.dtype())
@@ -73,16 +68,48 @@ pub fn run(args: &Args) -> Result<()> {
             }
         }
 
+        let stats = data.stats();
+        // Pad before coloring, as escape codes would count toward the width
+        let highlight = |count: usize| {
+            let padded = format!("{:>5}", count);
+            if count > 0 {
+                padded.yellow().to_string()
+            } else {
+                padded
+            }
+        };
+
         println!(
-            "  {:<width$}  {:>14}  {:>10}  {}",
+            "  {:<width$}  {:>14}  {:>10}  {:>10}  {:>10}  {:>10}  {}  {}  {}",
             name,
             shape,
             dtype,
+            output::format_value(stats.min),
+            output::format_value(stats.max),
+            output::format_value(stats.mean),
+            highlight(stats.nan_count),
+            highlight(stats.inf_count),
             notes.join(", "),
             width = max_name
         );
     }
 
+    // Print the first rows of each variable
+    if args.preview > 0 {
+        for &(name, data) in &vars {
+            // Arrays with more than two dimensions have no rows to show
+            let Ok(head) = data.head(args.preview) else {
+                continue;
+            };
+            println!();
+            println!("  {}", name.bold());
+            for (i, row) in head.rows().into_iter().enumerate() {
+                let values: Vec<String> = row.iter().map(|&v| output::format_value(v)).collect();
+                println!("    {} {}", format!("[{}]", i).dimmed(), values.join("  "));
+            }
+        }
+    }
+
     // Print summary
     println!();
     println!("{} numeric variables found", mat.len());
@@ -3,3 +3,22 @@
 pub mod convert;
 pub mod list;
 pub mod validate;
+
+use std::io;
+
+use anyhow::{Context, Result};
+
+use sdif_rs::MatFile;
+
+use crate::cli::Args;
+
+/// Load the input MAT file, reading standard input for `-`.
+pub fn open_input(args: &Args) -> Result<MatFile> {
+    let mat = if args.input_is_stdin() {
+        MatFile::from_reader(io::stdin().lock(), args.input_name())
+    } else {
+        MatFile::open(&args.input)
+    };
+
+    mat.with_context(|| format!("Failed to open MAT file: {}", args.input_name()))
+}
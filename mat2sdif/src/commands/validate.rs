@@ -3,7 +3,7 @@
 use anyhow::{Context, Result, bail};
 use colored::Colorize;
 
-use sdif_rs::{MatFile, MatToSdifConfig, MatToSdifConverter};
+use sdif_rs::MatToSdifConverter;
 
 use crate::cli::Args;
 use crate::max_compat;
@@ -18,12 +18,11 @@ pub fn run(args: &Args) -> Result<()> {
 
     // Load MAT file
     output::print_verbose(
-        &format!("Opening MAT file: {}", args.input.display()),
+        &format!("Opening MAT file: {}", args.input_name()),
         args.verbose,
     );
 
-    let mat = MatFile::open(&args.input)
-        .with_context(|| format!("Failed to open MAT file: {}", args.input.display()))?;
+    let mat = super::open_input(args)?;
 
     if mat.is_empty() {
         bail!("No numeric variables found in MAT file");
@@ -31,7 +30,7 @@ pub fn run(args: &Args) -> Result<()> {
 
     println!("{}", "MAT File Analysis".bold().underline());
     println!();
-    output::print_kv("File", &args.input.display().to_string(), 2);
+    output::print_kv("File", &args.input_name(), 2);
     output::print_kv("Variables", &mat.len().to_string(), 2);
 
     // Build configuration
@@ -54,6 +53,11 @@ pub fn run(args: &Args) -> Result<()> {
     output::print_kv("Duration", &output::format_duration(time_end - time_start), 2);
     output::print_kv("Columns per frame", &cols_per_frame.to_string(), 2);
 
+    let times = converter.validate_times();
+    if let Some(hop) = times.median_hop {
+        output::print_kv("Median hop", &format!("{:.2}ms", hop * 1000.0), 2);
+    }
+
     println!();
     println!("{}", "SDIF Output".bold().underline());
     println!();
@@ -73,7 +77,9 @@ pub fn run(args: &Args) -> Result<()> {
     println!("{}", "Compatibility Checks".bold().underline());
     println!();
 
-    let warnings = max_compat::check_all(args, &converter);
+    let mut warnings = max_compat::check_all(args, &converter);
+    warnings.extend(converter.check_ranges().context("Column range check failed")?);
+    warnings.extend(times.issues().into_iter().map(|issue| format!("Time vector: {}", issue)));
 
     if warnings.is_empty() {
         println!("  {} All checks passed", "✓".green());
@@ -7,6 +7,7 @@ mod cli;
 mod commands;
 mod max_compat;
 mod output;
+mod progress;
 
 use anyhow::Result;
 use clap::Parser;
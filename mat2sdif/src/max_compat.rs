@@ -5,7 +5,7 @@
 
 use colored::Colorize;
 
-use sdif_rs::MatToSdifConverter;
+use sdif_rs::{schemas, MatToSdifConverter};
 
 use crate::cli::Args;
 use crate::output;
@@ -105,12 +105,8 @@ fn check_partial_limit(limit: usize, _cols: usize) -> Option<String> {
 
 /// Check column count matches expected for frame type.
 fn check_column_count(frame_type: &str, columns: &[String]) -> Option<String> {
-    let expected = match frame_type {
-        "1TRC" | "1HRM" => 4, // Index, Frequency, Amplitude, Phase
-        "1FQ0" => 2,          // Frequency, Confidence
-        "1RES" => 4,          // Frequency, Amplitude, DecayRate, Phase
-        _ => return None,     // Unknown type, skip check
-    };
+    // Unknown types are not checked
+    let expected = schemas::standard_columns(frame_type)?.len();
 
     if columns.len() != expected {
         Some(format!(
@@ -1,7 +1,6 @@
 //! Terminal output formatting utilities.
 
 use colored::Colorize;
-use std::io::{self, Write};
 
 /// Print an error message to stderr.
 pub fn print_error(err: &anyhow::Error) {
@@ -83,6 +82,17 @@ pub fn format_duration(seconds: f64) -> String {
     }
 }
 
+/// Format a data value compactly, using scientific notation for very
+/// large or small magnitudes.
+pub fn format_value(value: f64) -> String {
+    let magnitude = value.abs();
+    if value.is_finite() && value != 0.0 && !(1e-3..1e6).contains(&magnitude) {
+        format!("{:.3e}", value)
+    } else {
+        format!("{:.4}", value)
+    }
+}
+
 /// Format file size in human-readable form.
 pub fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -100,57 +110,6 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
-/// Create a simple progress reporter for verbose mode.
-pub struct ProgressReporter {
-    total: usize,
-    current: usize,
-    last_percent: usize,
-    verbose: bool,
-}
-
-impl ProgressReporter {
-    pub fn new(total: usize, verbose: bool) -> Self {
-        ProgressReporter {
-            total,
-            current: 0,
-            last_percent: 0,
-            verbose,
-        }
-    }
-
-    pub fn increment(&mut self) {
-        self.current += 1;
-
-        if self.verbose && self.total > 0 {
-            let percent = (self.current * 100) / self.total;
-
-            // Only print at 10% intervals
-            if percent >= self.last_percent + 10 {
-                self.last_percent = percent;
-                eprint!(
-                    "\r{}: {}% ({}/{} frames)",
-                    "progress".blue(),
-                    percent,
-                    self.current,
-                    self.total
-                );
-                io::stderr().flush().ok();
-            }
-        }
-    }
-
-    pub fn finish(&self) {
-        if self.verbose && self.total > 0 {
-            eprintln!(
-                "\r{}: 100% ({}/{} frames)",
-                "progress".blue(),
-                self.total,
-                self.total
-            );
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,4 +128,12 @@ mod tests {
         assert_eq!(format_duration(1.5), "1.50s");
         assert_eq!(format_duration(90.0), "1m 30.0s");
     }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(440.0), "440.0000");
+        assert_eq!(format_value(-0.5), "-0.5000");
+        assert_eq!(format_value(0.00002), "2.000e-5");
+        assert_eq!(format_value(f64::NAN), "NaN");
+    }
 }
@@ -0,0 +1,56 @@
+//! Progress bars for conversions.
+//!
+//! With the `progress` feature, on by default, a conversion shows a bar on
+//! stderr with the frames written, the frame rate and the time left. The
+//! bar is hidden in quiet mode and when stderr is not a terminal, so
+//! scripts and pipes get the same plain output as builds without the
+//! feature.
+
+#[cfg(feature = "progress")]
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Layout of the frame progress bar.
+#[cfg(feature = "progress")]
+const FRAME_TEMPLATE: &str =
+    "{spinner:.blue} [{elapsed_precise}] {wide_bar:.cyan/blue} {pos}/{len} frames \
+     ({per_sec}, ETA {eta})";
+
+/// Progress of writing the frames of one file.
+pub struct FrameProgress {
+    #[cfg(feature = "progress")]
+    bar: ProgressBar,
+}
+
+impl FrameProgress {
+    /// Start a bar for `total` frames, or a hidden one when `quiet`.
+    #[cfg(feature = "progress")]
+    pub fn new(total: usize, quiet: bool) -> Self {
+        let bar = if quiet {
+            ProgressBar::hidden()
+        } else {
+            ProgressBar::new(total as u64)
+        };
+        let style = ProgressStyle::with_template(FRAME_TEMPLATE)
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> ");
+        bar.set_style(style);
+        FrameProgress { bar }
+    }
+
+    #[cfg(not(feature = "progress"))]
+    pub fn new(_total: usize, _quiet: bool) -> Self {
+        FrameProgress {}
+    }
+
+    /// Set the number of frames done.
+    pub fn set(&self, _done: usize) {
+        #[cfg(feature = "progress")]
+        self.bar.set_position(_done as u64);
+    }
+
+    /// Remove the bar, leaving the terminal to the summary.
+    pub fn finish(&self) {
+        #[cfg(feature = "progress")]
+        self.bar.finish_and_clear();
+    }
+}
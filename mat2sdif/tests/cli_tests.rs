@@ -84,6 +84,17 @@ fn test_list_missing_file() {
         .failure();
 }
 
+#[test]
+fn test_list_invalid_stdin() {
+    mat2sdif()
+        .arg("--list")
+        .arg("-")
+        .write_stdin("not a mat file")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<stdin>"));
+}
+
 // ============================================================================
 // Dry Run Tests
 // ============================================================================
@@ -121,6 +132,26 @@ fn test_list_simple_mat() {
         .stdout(predicate::str::contains("Variables in"));
 }
 
+#[test]
+#[ignore = "Requires test fixture: simple.mat"]
+fn test_list_simple_mat_from_stdin() {
+    let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/simple.mat");
+
+    if !fixture.exists() {
+        eprintln!("Skipping: fixture not found");
+        return;
+    }
+
+    mat2sdif()
+        .arg("--list")
+        .arg("-")
+        .write_stdin(fs::read(&fixture).unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Variables in '<stdin>'"));
+}
+
 #[test]
 #[ignore = "Requires test fixture: simple.mat"]
 fn test_convert_simple_mat() {
@@ -0,0 +1,304 @@
+//! Command-line argument definitions using clap derive macros.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+
+/// Inspect and edit SDIF files.
+#[derive(Parser, Debug)]
+#[command(name = "sdif")]
+#[command(author, version, about, long_about = None)]
+#[command(after_help = EXAMPLES)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Available subcommands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Summarize an SDIF file: header, NVTs, types, frame counts and times
+    Info(InfoArgs),
+
+    /// Write an SDIF file as text for diffing and review, or as JSON
+    Dump(DumpArgs),
+
+    /// Print the matrix and frame types declared in an SDIF file
+    Schema(SchemaArgs),
+
+    /// Rebuild an SDIF file from the output of `sdif dump`
+    Undump(UndumpArgs),
+
+    /// Print the first frames of an SDIF file
+    Head(PeekArgs),
+
+    /// Print the last frames of an SDIF file
+    Tail(PeekArgs),
+
+    /// Copy the frames in a time range to a new SDIF file
+    Extract(ExtractArgs),
+
+    /// Copy the first frames of an SDIF file, up to a frame or size limit
+    Truncate(TruncateArgs),
+
+    /// Salvage the readable frames of a damaged SDIF file
+    Repair(RepairArgs),
+
+    /// Split an SDIF file into one file per stream or frame signature
+    Split(SplitArgs),
+
+    /// Resynthesize the 1TRC/1HRM partials of an SDIF file to a WAV file
+    Render(RenderArgs),
+}
+
+/// Arguments of `sdif info`.
+#[derive(clap::Args, Debug)]
+pub struct InfoArgs {
+    /// Input .sdif file (use `-` to read from standard input)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+}
+
+/// Arguments of `sdif dump`.
+#[derive(clap::Args, Debug)]
+pub struct DumpArgs {
+    /// Input .sdif file (use `-` to read from standard input)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Write the dump to a file instead of standard output
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: DumpFormat,
+}
+
+/// Output formats of `sdif dump`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// Line-oriented text that `sdif undump` reads back
+    Text,
+    /// JSON with NVTs, types and frames, for web and scripting tools
+    Json,
+}
+
+/// Arguments of `sdif schema`.
+#[derive(clap::Args, Debug)]
+pub struct SchemaArgs {
+    /// Input .sdif file (use `-` to read from standard input)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: SchemaFormat,
+}
+
+/// Output formats of `sdif schema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaFormat {
+    /// A readable list of the types
+    Text,
+    /// JSON that `SdifFileBuilder::from_schema` reads back
+    Json,
+}
+
+/// Arguments of `sdif undump`.
+#[derive(clap::Args, Debug)]
+pub struct UndumpArgs {
+    /// Input dump (use `-` to read from standard input)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output .sdif file
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Force overwrite of existing output file
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments of `sdif head` and `sdif tail`.
+#[derive(clap::Args, Debug)]
+pub struct PeekArgs {
+    /// Input .sdif file (use `-` to read from standard input)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Number of frames to print
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 10)]
+    pub frames: usize,
+}
+
+/// Arguments of `sdif extract`.
+#[derive(clap::Args, Debug)]
+pub struct ExtractArgs {
+    /// Input .sdif file
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output .sdif file for the extracted frames
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Time of the first frame to keep, in seconds
+    #[arg(long, value_name = "SECONDS", allow_negative_numbers = true)]
+    pub start: Option<f64>,
+
+    /// Keep frames before this time, in seconds
+    #[arg(long, value_name = "SECONDS", allow_negative_numbers = true)]
+    pub end: Option<f64>,
+
+    /// Force overwrite of existing output file
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments of `sdif truncate`.
+#[derive(clap::Args, Debug)]
+#[command(group(clap::ArgGroup::new("limit").required(true).multiple(true)))]
+pub struct TruncateArgs {
+    /// Input .sdif file
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output .sdif file for the kept frames
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Keep at most this many frames
+    #[arg(long, value_name = "N", group = "limit")]
+    pub max_frames: Option<usize>,
+
+    /// Keep the output at or below this many bytes
+    #[arg(long, value_name = "BYTES", group = "limit")]
+    pub max_bytes: Option<u64>,
+
+    /// Force overwrite of existing output file
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments of `sdif repair`.
+#[derive(clap::Args, Debug)]
+pub struct RepairArgs {
+    /// Damaged .sdif file
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output .sdif file for the recovered frames
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Force overwrite of existing output file
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments of `sdif split`.
+#[derive(clap::Args, Debug)]
+pub struct SplitArgs {
+    /// Input .sdif file
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Directory for the output files (default: the input's directory)
+    #[arg(short, long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// What to split on
+    #[arg(short, long, value_enum, default_value = "signature")]
+    pub by: SplitKey,
+}
+
+/// What `sdif split` makes one file per.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SplitKey {
+    /// One file per frame signature, e.g. `input.1TRC.sdif`
+    Signature,
+    /// One file per stream, named after the stream or its ID
+    Stream,
+}
+
+/// Arguments of `sdif render`.
+#[derive(clap::Args, Debug)]
+pub struct RenderArgs {
+    /// Input .sdif file with 1TRC or 1HRM partials
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output .wav file
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Sample rate of the output, in Hz
+    #[arg(short = 'r', long, value_name = "HZ", default_value_t = 44100)]
+    pub sample_rate: u32,
+
+    /// Fade in and out around each partial, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.01)]
+    pub fade: f64,
+
+    /// Factor applied to the partial amplitudes
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0)]
+    pub gain: f64,
+
+    /// Scale the output so its loudest sample is at this level instead
+    #[arg(long, value_name = "PEAK", conflicts_with = "gain")]
+    pub normalize: Option<f64>,
+
+    /// Force overwrite of existing output file
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Check whether a path argument stands for a standard stream.
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Example usage shown in --help.
+const EXAMPLES: &str = r#"
+EXAMPLES:
+    # Summarize an SDIF file
+    sdif info analysis.sdif
+
+    # Show an SDIF file as text
+    sdif dump analysis.sdif
+
+    # Keep a reviewable copy next to the binary file
+    sdif dump analysis.sdif -o analysis.sdif.txt
+
+    # Rebuild the binary file after editing the text
+    sdif undump analysis.sdif.txt analysis.sdif --force
+
+    # Export the whole file as JSON
+    sdif dump --format json analysis.sdif -o analysis.json
+
+    # List the types a file declares, as JSON
+    sdif schema --format json unknown-tool.sdif
+
+    # Check the first and last frames of an analysis
+    sdif head -n 5 analysis.sdif
+    sdif tail -n 5 analysis.sdif
+
+    # Keep the frames from 1.5 to 3 seconds
+    sdif extract analysis.sdif section.sdif --start 1.5 --end 3
+
+    # Make a small test file from the start of an analysis
+    sdif truncate analysis.sdif sample.sdif --max-frames 100
+
+    # Recover what can be read from a damaged file
+    sdif repair damaged.sdif repaired.sdif
+
+    # Write the 1TRC and 1FQ0 frames to separate files
+    sdif split analysis.sdif -o out
+
+    # Listen to an analysis
+    sdif render analysis.sdif analysis.wav --normalize 0.9
+
+    # Compare two analyses
+    diff <(sdif dump before.sdif) <(sdif dump after.sdif)
+"#;
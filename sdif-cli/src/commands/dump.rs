@@ -0,0 +1,38 @@
+//! Text and JSON dump command (`sdif dump`).
+
+use std::fs;
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+
+use sdif_rs::dump::Dump;
+use sdif_rs::SdifFile;
+
+use crate::cli::{self, DumpArgs, DumpFormat};
+
+/// Run the dump command.
+pub fn run(args: &DumpArgs) -> Result<()> {
+    let file = if cli::is_stdio(&args.input) {
+        SdifFile::from_reader(io::stdin().lock())
+    } else {
+        SdifFile::open(&args.input)
+    }
+    .with_context(|| format!("Failed to open SDIF file: {}", args.input.display()))?;
+
+    let text = match args.format {
+        DumpFormat::Text => Dump::from_file(&file).map(|dump| dump.to_string()),
+        DumpFormat::Json => file.to_json().map(|json| json + "\n"),
+    }
+    .with_context(|| format!("Failed to read SDIF file: {}", args.input.display()))?;
+
+    match &args.output {
+        Some(output) => fs::write(output, text)
+            .with_context(|| format!("Failed to write dump: {}", output.display())),
+        None => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(text.as_bytes())?;
+            stdout.flush()?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Time range extraction command (`sdif extract`).
+
+use anyhow::{bail, Context, Result};
+
+use sdif_rs::copy::{self, CopyOptions};
+
+use crate::cli::ExtractArgs;
+
+/// Run the extract command.
+pub fn run(args: &ExtractArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        bail!(
+            "Output file already exists: {} (use --force to overwrite)",
+            args.output.display()
+        );
+    }
+
+    let start = args.start.unwrap_or(f64::NEG_INFINITY);
+    let end = args.end.unwrap_or(f64::INFINITY);
+    if start >= end {
+        bail!("Start time {} is not before end time {}", start, end);
+    }
+
+    let options = CopyOptions::new().time_range(start..end);
+    let summary = copy::copy_file(&args.input, &args.output, &options)
+        .with_context(|| format!("Failed to extract from {}", args.input.display()))?;
+
+    println!(
+        "extracted {} frames ({} bytes)",
+        summary.frames_copied, summary.bytes_written
+    );
+    if summary.frames_skipped > 0 {
+        println!("skipped {} frames without matrices", summary.frames_skipped);
+    }
+
+    Ok(())
+}
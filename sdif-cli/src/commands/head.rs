@@ -0,0 +1,91 @@
+//! First frames command (`sdif head`).
+//!
+//! Frames are printed like in `sdif dump`, but with only the first row of
+//! each matrix, followed by `...` when there are more.
+
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+
+use sdif_rs::{DataType, Frame, SdifFile};
+
+use crate::cli::{self, PeekArgs};
+
+/// Run the head command.
+pub fn run(args: &PeekArgs) -> Result<()> {
+    let file = open(args)?;
+    print_frames(args, file.frames().take(args.frames))
+}
+
+/// Open the input file.
+pub(crate) fn open(args: &PeekArgs) -> Result<SdifFile> {
+    if cli::is_stdio(&args.input) {
+        SdifFile::from_reader(io::stdin().lock())
+    } else {
+        SdifFile::open(&args.input)
+    }
+    .with_context(|| format!("Failed to open SDIF file: {}", args.input.display()))
+}
+
+/// Print frames to standard output.
+pub(crate) fn print_frames<'a>(
+    args: &PeekArgs,
+    frames: impl Iterator<Item = sdif_rs::Result<Frame<'a>>>,
+) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    for frame in frames {
+        let frame =
+            frame.with_context(|| format!("Failed to read SDIF file: {}", args.input.display()))?;
+        print_frame(&mut stdout, frame)
+            .with_context(|| format!("Failed to read SDIF file: {}", args.input.display()))?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Print a frame header and the shape and first row of its matrices.
+fn print_frame(out: &mut impl Write, mut frame: Frame<'_>) -> Result<()> {
+    writeln!(
+        out,
+        "frame {} {} {}",
+        frame.signature(),
+        frame.stream_id(),
+        frame.time()
+    )?;
+
+    for matrix in frame.matrices() {
+        let matrix = matrix?;
+        let data_type = matrix.data_type();
+        writeln!(
+            out,
+            "  matrix {} {} {}x{}",
+            matrix.signature(),
+            data_type,
+            matrix.rows(),
+            matrix.cols()
+        )?;
+
+        // Text matrices have no numbers to show
+        if !(data_type.is_float() || data_type.is_integer()) {
+            continue;
+        }
+        let matrix = matrix.into_owned()?;
+        if let Some(row) = matrix.row(0) {
+            let values: Vec<_> = row.iter().map(|&v| format_value(v, data_type)).collect();
+            writeln!(out, "    {}", values.join(" "))?;
+        }
+        if matrix.rows > 1 {
+            writeln!(out, "    ...")?;
+        }
+    }
+    Ok(())
+}
+
+/// Format a value in the shortest form for its data type.
+fn format_value(value: f64, data_type: DataType) -> String {
+    if data_type == DataType::Float4 {
+        (value as f32).to_string()
+    } else {
+        value.to_string()
+    }
+}
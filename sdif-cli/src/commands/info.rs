@@ -0,0 +1,67 @@
+//! File summary command (`sdif info`).
+
+use std::io;
+
+use anyhow::{Context, Result};
+
+use sdif_rs::corpus::FileSummary;
+use sdif_rs::document::FileMetadata;
+use sdif_rs::SdifFile;
+
+use crate::cli::{self, InfoArgs};
+
+/// Run the info command.
+pub fn run(args: &InfoArgs) -> Result<()> {
+    let file = if cli::is_stdio(&args.input) {
+        SdifFile::from_reader(io::stdin().lock())
+    } else {
+        SdifFile::open(&args.input)
+    }
+    .with_context(|| format!("Failed to open SDIF file: {}", args.input.display()))?;
+
+    let read_context = || format!("Failed to read SDIF file: {}", args.input.display());
+    let header = file.header().with_context(read_context)?;
+    let metadata = FileMetadata::from_file(&file).with_context(read_context)?;
+    let summary = FileSummary::from_file(&file, &args.input).with_context(read_context)?;
+
+    println!("file {}", args.input.display());
+    println!(
+        "version {} (types version {})",
+        header.format_version, header.types_version
+    );
+
+    for (i, nvt) in metadata.nvts.iter().enumerate() {
+        println!("nvt {}", i);
+        for (name, value) in nvt {
+            println!("  {} = {}", name, value);
+        }
+    }
+    for matrix_type in &metadata.matrix_types {
+        println!(
+            "mtype {} {}",
+            matrix_type.signature,
+            matrix_type.columns.join(", ")
+        );
+    }
+    for frame_type in &metadata.frame_types {
+        let components: Vec<String> = frame_type
+            .components
+            .iter()
+            .map(|c| format!("{} {}", c.signature, c.name))
+            .collect();
+        println!("ftype {} {}", frame_type.signature, components.join(", "));
+    }
+
+    println!("frames {}", summary.num_frames);
+    for (signature, count) in &summary.frames_per_signature {
+        println!("  {} {}", signature, count);
+    }
+    if let (Some(start), Some(end)) = (summary.start_time, summary.end_time) {
+        println!("time {} to {} ({}s)", start, end, summary.duration());
+    }
+    if let Some(rate) = summary.frame_rate {
+        println!("frame rate {:.3} Hz", rate);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+//! Command implementations.
+
+pub mod dump;
+pub mod extract;
+pub mod head;
+pub mod info;
+pub mod render;
+pub mod repair;
+pub mod schema;
+pub mod split;
+pub mod tail;
+pub mod truncate;
+pub mod undump;
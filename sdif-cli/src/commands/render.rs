@@ -0,0 +1,48 @@
+//! Additive resynthesis command (`sdif render`).
+
+use anyhow::{bail, Context, Result};
+
+use sdif_rs::synth::{self, SynthOptions};
+use sdif_rs::tracks::TrackSet;
+use sdif_rs::SdifFile;
+
+use crate::cli::RenderArgs;
+
+/// Run the render command.
+pub fn run(args: &RenderArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        bail!(
+            "Output file already exists: {} (use --force to overwrite)",
+            args.output.display()
+        );
+    }
+
+    let file = SdifFile::open(&args.input)
+        .with_context(|| format!("Failed to open {}", args.input.display()))?;
+    let tracks = TrackSet::from_partials(&file)
+        .with_context(|| format!("Failed to read partials from {}", args.input.display()))?;
+    if tracks.is_empty() {
+        bail!("No 1TRC or 1HRM partials in {}", args.input.display());
+    }
+
+    let mut options = SynthOptions::new()
+        .sample_rate(args.sample_rate)
+        .fade(args.fade)
+        .gain(args.gain);
+    if let Some(peak) = args.normalize {
+        options = options.normalize(peak);
+    }
+
+    let samples = synth::render(&tracks, &options).context("Failed to render partials")?;
+    synth::write_wav(&args.output, &samples, &options)
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    println!(
+        "rendered {} partials ({:.3}s at {} Hz)",
+        tracks.len(),
+        samples.len() as f64 / f64::from(args.sample_rate),
+        args.sample_rate
+    );
+
+    Ok(())
+}
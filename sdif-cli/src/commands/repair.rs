@@ -0,0 +1,39 @@
+//! Damaged file recovery command (`sdif repair`).
+
+use anyhow::{bail, Context, Result};
+
+use sdif_rs::repair;
+
+use crate::cli::RepairArgs;
+
+/// Run the repair command.
+pub fn run(args: &RepairArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        bail!(
+            "Output file already exists: {} (use --force to overwrite)",
+            args.output.display()
+        );
+    }
+
+    let summary = repair::repair_file(&args.input, &args.output)
+        .with_context(|| format!("Failed to repair {}", args.input.display()))?;
+
+    println!(
+        "recovered {} frames ({} bytes)",
+        summary.frames_recovered, summary.bytes_recovered
+    );
+    if summary.is_intact() {
+        println!("no damage found");
+        return Ok(());
+    }
+
+    println!(
+        "dropped {} frames ({} bytes)",
+        summary.frames_dropped, summary.bytes_dropped
+    );
+    for range in &summary.damaged {
+        println!("  damaged bytes {}..{}", range.start, range.end);
+    }
+
+    Ok(())
+}
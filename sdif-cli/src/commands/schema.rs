@@ -0,0 +1,30 @@
+//! Type declaration command (`sdif schema`).
+
+use std::io;
+
+use anyhow::{Context, Result};
+
+use sdif_rs::SdifFile;
+
+use crate::cli::{self, SchemaArgs, SchemaFormat};
+
+/// Run the schema command.
+pub fn run(args: &SchemaArgs) -> Result<()> {
+    let file = if cli::is_stdio(&args.input) {
+        SdifFile::from_reader(io::stdin().lock())
+    } else {
+        SdifFile::open(&args.input)
+    }
+    .with_context(|| format!("Failed to open SDIF file: {}", args.input.display()))?;
+
+    let schema = file
+        .describe_types()
+        .with_context(|| format!("Failed to read types of {}", args.input.display()))?;
+
+    match args.format {
+        SchemaFormat::Text => print!("{}", schema),
+        SchemaFormat::Json => println!("{}", schema.to_json()?),
+    }
+
+    Ok(())
+}
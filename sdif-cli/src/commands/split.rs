@@ -0,0 +1,30 @@
+//! File splitting command (`sdif split`).
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use sdif_rs::split::{self, SplitBy};
+
+use crate::cli::{SplitArgs, SplitKey};
+
+/// Run the split command.
+pub fn run(args: &SplitArgs) -> Result<()> {
+    let output_dir = match &args.output_dir {
+        Some(dir) => dir.as_path(),
+        None => args.input.parent().unwrap_or(Path::new("")),
+    };
+    let by = match args.by {
+        SplitKey::Signature => SplitBy::Signature,
+        SplitKey::Stream => SplitBy::Stream,
+    };
+
+    let outputs = split::split_file(&args.input, output_dir, by)
+        .with_context(|| format!("Failed to split {}", args.input.display()))?;
+
+    for output in &outputs {
+        println!("{}: {} frames", output.path.display(), output.frames);
+    }
+
+    Ok(())
+}
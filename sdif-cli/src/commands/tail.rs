@@ -0,0 +1,25 @@
+//! Last frames command (`sdif tail`).
+
+use anyhow::{Context, Result};
+
+use crate::cli::PeekArgs;
+use crate::commands::head;
+
+/// Run the tail command.
+///
+/// The file's index, loaded from its sidecar or built with one pass over
+/// the frame headers, gives the position of the first frame to print.
+pub fn run(args: &PeekArgs) -> Result<()> {
+    let file = head::open(args)?;
+
+    let count = file
+        .scan_index()
+        .with_context(|| format!("Failed to index SDIF file: {}", args.input.display()))?
+        .len();
+    let first = count.saturating_sub(args.frames);
+    if first < count {
+        file.seek_to_frame(first)?;
+    }
+
+    head::print_frames(args, file.frames().take(args.frames))
+}
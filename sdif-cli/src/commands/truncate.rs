@@ -0,0 +1,38 @@
+//! Frame and size limit command (`sdif truncate`).
+
+use anyhow::{bail, Context, Result};
+
+use sdif_rs::copy::{self, CopyOptions};
+
+use crate::cli::TruncateArgs;
+
+/// Run the truncate command.
+pub fn run(args: &TruncateArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        bail!(
+            "Output file already exists: {} (use --force to overwrite)",
+            args.output.display()
+        );
+    }
+
+    let mut options = CopyOptions::new();
+    if let Some(frames) = args.max_frames {
+        options = options.max_frames(frames);
+    }
+    if let Some(bytes) = args.max_bytes {
+        options = options.max_bytes(bytes);
+    }
+
+    let summary = copy::copy_file(&args.input, &args.output, &options)
+        .with_context(|| format!("Failed to truncate {}", args.input.display()))?;
+
+    println!(
+        "kept {} frames ({} bytes)",
+        summary.frames_copied, summary.bytes_written
+    );
+    if summary.is_truncated() {
+        println!("dropped {} frames", summary.frames_dropped);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! Dump re-import command (`sdif undump`).
+
+use std::fs;
+use std::io::{self, Read};
+
+use anyhow::{bail, Context, Result};
+
+use sdif_rs::dump::Dump;
+
+use crate::cli::{self, UndumpArgs};
+
+/// Run the undump command.
+pub fn run(args: &UndumpArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        bail!(
+            "Output file already exists: {} (use --force to overwrite)",
+            args.output.display()
+        );
+    }
+
+    let text = if cli::is_stdio(&args.input) {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        fs::read_to_string(&args.input)
+            .with_context(|| format!("Failed to read dump: {}", args.input.display()))?
+    };
+
+    let dump =
+        Dump::parse(&text).with_context(|| format!("Invalid dump: {}", args.input.display()))?;
+    dump.write_sdif(&args.output)
+        .with_context(|| format!("Failed to write SDIF file: {}", args.output.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! sdif - Command-line tools for SDIF files.
+//!
+//! Each subcommand wraps a piece of the `sdif-rs` library so it can be used
+//! from scripts and the shell.
+
+mod cli;
+mod commands;
+
+use anyhow::Result;
+use clap::Parser;
+
+use cli::{Args, Command};
+
+fn main() {
+    let args = Args::parse();
+
+    if let Err(e) = run(args) {
+        eprintln!("error: {}", e);
+        for cause in e.chain().skip(1) {
+            eprintln!("  caused by: {}", cause);
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Dispatch to the selected subcommand.
+fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Info(args) => commands::info::run(&args),
+        Command::Dump(args) => commands::dump::run(&args),
+        Command::Schema(args) => commands::schema::run(&args),
+        Command::Undump(args) => commands::undump::run(&args),
+        Command::Head(args) => commands::head::run(&args),
+        Command::Tail(args) => commands::tail::run(&args),
+        Command::Extract(args) => commands::extract::run(&args),
+        Command::Truncate(args) => commands::truncate::run(&args),
+        Command::Repair(args) => commands::repair::run(&args),
+        Command::Split(args) => commands::split::run(&args),
+        Command::Render(args) => commands::render::run(&args),
+    }
+}
@@ -0,0 +1,373 @@
+//! Integration tests for the sdif CLI.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+/// Get the sdif command.
+fn sdif() -> Command {
+    Command::cargo_bin("sdif").unwrap()
+}
+
+/// Path of the dump fixture.
+const PARTIALS_DUMP: &str = "tests/fixtures/partials.txt";
+
+#[test]
+fn test_help() {
+    sdif()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("dump"))
+        .stdout(predicate::str::contains("undump"))
+        .stdout(predicate::str::contains("EXAMPLES"));
+}
+
+#[test]
+fn test_missing_command() {
+    sdif().assert().failure();
+}
+
+#[test]
+fn test_dump_nonexistent_input() {
+    sdif()
+        .args(["dump", "/nonexistent/file.sdif"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to open SDIF file"));
+}
+
+#[test]
+fn test_undump_dump_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let sdif_path = dir.path().join("partials.sdif");
+    let dump_path = dir.path().join("partials.txt");
+
+    sdif()
+        .arg("undump")
+        .arg(PARTIALS_DUMP)
+        .arg(&sdif_path)
+        .assert()
+        .success();
+
+    sdif()
+        .arg("dump")
+        .arg(&sdif_path)
+        .arg("-o")
+        .arg(&dump_path)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&dump_path).unwrap(),
+        fs::read_to_string(PARTIALS_DUMP).unwrap()
+    );
+
+    // Dumping to standard output gives the same text
+    sdif()
+        .arg("dump")
+        .arg(&sdif_path)
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(PARTIALS_DUMP).unwrap());
+}
+
+#[test]
+fn test_dump_json() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+
+    sdif()
+        .args(["dump", "--format", "json"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{"))
+        .stdout(predicate::str::contains(r#""signature": "1TRC""#))
+        .stdout(predicate::str::contains(r#""time": 0.02"#))
+        .stdout(predicate::str::contains("881.0"));
+}
+
+#[test]
+fn test_undump_refuses_overwrite() {
+    let dir = TempDir::new().unwrap();
+    let sdif_path = dir.path().join("existing.sdif");
+    fs::write(&sdif_path, b"").unwrap();
+
+    sdif()
+        .arg("undump")
+        .arg(PARTIALS_DUMP)
+        .arg(&sdif_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+}
+
+#[test]
+fn test_undump_invalid_dump() {
+    let dir = TempDir::new().unwrap();
+    let dump_path = dir.path().join("bad.txt");
+    fs::write(&dump_path, "SDIF-DUMP 1\nframe 1FQ0 0\n").unwrap();
+
+    sdif()
+        .arg("undump")
+        .arg(&dump_path)
+        .arg(dir.path().join("out.sdif"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Line 2"));
+}
+
+/// Build the partials fixture as an SDIF file in `dir`.
+fn partials_sdif(dir: &TempDir) -> std::path::PathBuf {
+    let path = dir.path().join("partials.sdif");
+    sdif()
+        .arg("undump")
+        .arg(PARTIALS_DUMP)
+        .arg(&path)
+        .assert()
+        .success();
+    path
+}
+
+#[test]
+fn test_head() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+
+    sdif()
+        .args(["head", "-n", "2"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(concat!(
+            "frame 1TRC 0 0\n",
+            "  matrix 1TRC float32 2x4\n",
+            "    1 440 0.5 0\n",
+            "    ...\n",
+            "frame 1TRC 0 0.01\n",
+            "  matrix 1TRC float32 2x4\n",
+            "    1 440.5 0.5 0.1\n",
+            "    ...\n",
+        ));
+}
+
+#[test]
+fn test_tail() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+
+    sdif()
+        .args(["tail", "-n", "1"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(concat!(
+            "frame 1TRC 0 0.02\n",
+            "  matrix 1TRC float64 2x4\n",
+            "    1 441 0.5 0.30000000000000004\n",
+            "    ...\n",
+        ));
+
+    // Asking for more frames than the file has prints all of them
+    sdif()
+        .arg("tail")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("frame 1TRC 0 0\n"))
+        .stdout(predicate::str::contains("frame 1TRC 0 0.02\n"));
+}
+
+#[test]
+fn test_repair() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+    let repaired = dir.path().join("repaired.sdif");
+
+    // Cut the last frame short
+    let mut bytes = std::fs::read(&path).unwrap();
+    let len = bytes.len();
+    bytes.truncate(len - 10);
+    std::fs::write(&path, &bytes).unwrap();
+
+    sdif()
+        .arg("repair")
+        .arg(&path)
+        .arg(&repaired)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("recovered 2 frames"))
+        .stdout(predicate::str::contains("dropped 1 frames"));
+
+    sdif()
+        .args(["tail", "-n", "1"])
+        .arg(&repaired)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("frame 1TRC 0 0.01\n"));
+}
+
+#[test]
+fn test_info() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+
+    sdif()
+        .arg("info")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("version 3"))
+        .stdout(predicate::str::contains(
+            "mtype 1TRC Index, Frequency, Amplitude, Phase\n",
+        ))
+        .stdout(predicate::str::contains("ftype 1TRC 1TRC SinusoidalTracks\n"))
+        .stdout(predicate::str::contains("frames 3\n  1TRC 3\n"))
+        .stdout(predicate::str::contains("time 0 to 0.02"));
+}
+
+#[test]
+fn test_extract() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+    let extracted = dir.path().join("extracted.sdif");
+
+    sdif()
+        .arg("extract")
+        .arg(&path)
+        .arg(&extracted)
+        .args(["--start", "0.005"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("extracted 2 frames"));
+
+    sdif()
+        .arg("head")
+        .arg(&extracted)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("frame 1TRC 0 0.01\n"));
+
+    // The output now exists
+    sdif()
+        .arg("extract")
+        .arg(&path)
+        .arg(&extracted)
+        .args(["--end", "0.015"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+}
+
+#[test]
+fn test_render() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+    let wav = dir.path().join("partials.wav");
+
+    sdif()
+        .arg("render")
+        .arg(&path)
+        .arg(&wav)
+        .args(["--sample-rate", "8000", "--normalize", "0.5"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rendered 2 partials (0.030s at 8000 Hz)"));
+
+    // RIFF header followed by 240 16-bit samples
+    let bytes = std::fs::read(&wav).unwrap();
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(bytes.len(), 44 + 240 * 2);
+
+    sdif()
+        .arg("render")
+        .arg(&path)
+        .arg(&wav)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+}
+
+#[test]
+fn test_split() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+
+    sdif()
+        .arg("split")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("partials.1TRC.sdif: 3 frames"));
+    assert!(dir.path().join("partials.1TRC.sdif").exists());
+
+    let out = dir.path().join("out");
+    fs::create_dir(&out).unwrap();
+    sdif()
+        .arg("split")
+        .arg(&path)
+        .args(["--by", "stream", "-o"])
+        .arg(&out)
+        .assert()
+        .success();
+    assert!(out.join("partials.0.sdif").exists());
+}
+
+#[test]
+fn test_truncate() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+    let truncated = dir.path().join("truncated.sdif");
+
+    sdif()
+        .arg("truncate")
+        .arg(&path)
+        .arg(&truncated)
+        .args(["--max-frames", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("kept 2 frames"))
+        .stdout(predicate::str::contains("dropped 1 frames"));
+
+    sdif()
+        .arg("tail")
+        .arg(&truncated)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("frame 1TRC 0 0.01\n"))
+        .stdout(predicate::str::contains("0.02").not());
+
+    // A limit is required
+    sdif()
+        .arg("truncate")
+        .arg(&path)
+        .arg(dir.path().join("other.sdif"))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_schema() {
+    let dir = TempDir::new().unwrap();
+    let path = partials_sdif(&dir);
+
+    sdif()
+        .arg("schema")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Matrix types:\n  1TRC: Index, Frequency, Amplitude, Phase\n",
+        ))
+        .stdout(predicate::str::contains("    1TRC SinusoidalTracks"));
+
+    sdif()
+        .args(["schema", "--format", "json"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"signature\": \"1TRC\""))
+        .stdout(predicate::str::contains("\"Frequency\""));
+}
@@ -0,0 +1,47 @@
+//! Extract the fundamental frequency of an analysis to CSV.
+//!
+//! Writes every 1FQ0 row with its time, ready for
+//! `pandas.read_csv()`, and prints how much of the file is voiced.
+//!
+//! ```text
+//! cargo run --example f0_to_csv --features bundled -- voice.f0.sdif voice.f0.csv
+//! ```
+
+use std::env;
+use std::fs;
+use std::process;
+
+use sdif_rs::csv::{self, CsvExportOptions};
+use sdif_rs::f0::{self, F0Track};
+use sdif_rs::SdifFile;
+
+/// Confidence needed for an estimate to count as voiced.
+const VOICING_THRESHOLD: f64 = 0.5;
+
+/// Shortest voiced or unvoiced segment, in seconds.
+const MIN_SEGMENT: f64 = 0.05;
+
+fn main() -> sdif_rs::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [input, output] = args.as_slice() else {
+        eprintln!("usage: f0_to_csv <input.sdif> <output.csv>");
+        process::exit(2);
+    };
+
+    let options = CsvExportOptions::new("1FQ0").stream_column(true);
+    let text = csv::to_csv(&SdifFile::open(input)?, &options)?;
+    fs::write(output, &text)?;
+    println!("wrote {} F0 rows to {}", text.lines().count() - 1, output);
+
+    let track = F0Track::from_file(&SdifFile::open(input)?)?;
+    let segments = f0::segment_voicing(&track, VOICING_THRESHOLD, MIN_SEGMENT);
+    let total: f64 = segments.iter().map(|segment| segment.duration()).sum();
+    let voiced: f64 = segments
+        .iter()
+        .filter(|segment| segment.voiced)
+        .map(|segment| segment.duration())
+        .sum();
+    println!("voiced {:.3}s of {:.3}s", voiced, total);
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Convert partials stored in a MAT file to SDIF that SPEAR can open.
+//!
+//! The MAT file needs a time vector and a matrix with one row per frame,
+//! holding `Index, Frequency, Amplitude, Phase` for each partial. SPEAR
+//! reads 1TRC frames of 32-bit floats with strictly increasing times, so
+//! frames at the same time are merged into one.
+//!
+//! ```text
+//! cargo run --example mat_to_spear --features bundled,mat -- \
+//!     partials.mat partials.sdif [time-var] [data-var]
+//! ```
+
+use std::env;
+use std::process;
+
+use sdif_rs::{
+    schemas, DataType, DuplicateTimes, MatFile, MatToSdifConfig, MatToSdifConverter, Result,
+    SdifFile,
+};
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !(2..=4).contains(&args.len()) {
+        eprintln!("usage: mat_to_spear <input.mat> <output.sdif> [time-var] [data-var]");
+        process::exit(2);
+    }
+    let time_var = args.get(2).map_or("time", String::as_str);
+    let data_var = args.get(3).map_or("partials", String::as_str);
+
+    let mat = MatFile::open(&args[0])?;
+    let config = MatToSdifConfig::new()
+        .time_var(time_var)
+        .data_var(data_var)
+        .frame_type("1TRC")
+        .matrix_type("1TRC")
+        .columns(schemas::TRC_COLUMNS)
+        .duplicate_times(DuplicateTimes::Merge);
+    let converter = MatToSdifConverter::new(&mat, config)?;
+    for warning in converter.check_ranges()? {
+        eprintln!("warning: {}", warning);
+    }
+
+    let mut writer = SdifFile::builder()
+        .create(&args[1])?
+        .add_nvt([("creator", "mat_to_spear")])?
+        .add_matrix_type("1TRC", schemas::TRC_COLUMNS)?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .default_data_type("1TRC", DataType::Float4)?
+        .build()?;
+    converter.write_to(&mut writer)?;
+    writer.close()?;
+
+    let (start, end) = converter.time_range();
+    println!(
+        "wrote {} frames ({:.3}s to {:.3}s) to {}",
+        converter.num_frames(),
+        start,
+        end,
+        args[1]
+    );
+    Ok(())
+}
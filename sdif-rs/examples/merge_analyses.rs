@@ -0,0 +1,80 @@
+//! Merge two analyses of the same sound into one file.
+//!
+//! Frames of both files are interleaved in time order. The second file's
+//! streams are renumbered after the first file's, so for example partials
+//! from one analysis and F0 from another end up as separate streams. Type
+//! declarations are combined, keeping the first file's when both declare
+//! a signature, and the NVTs of both files are kept.
+//!
+//! ```text
+//! cargo run --example merge_analyses --features bundled -- \
+//!     voice.partials.sdif voice.f0.sdif voice.sdif
+//! ```
+
+use std::env;
+use std::process;
+
+use sdif_rs::document::FileMetadata;
+use sdif_rs::{FrameData, Result, SdifFile};
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [first, second, output] = args.as_slice() else {
+        eprintln!("usage: merge_analyses <first.sdif> <second.sdif> <output.sdif>");
+        process::exit(2);
+    };
+
+    let (mut metadata, first_frames) = read(first)?;
+    let (other, mut second_frames) = read(second)?;
+
+    for matrix_type in other.matrix_types {
+        if !metadata.matrix_types.iter().any(|t| t.signature == matrix_type.signature) {
+            metadata.matrix_types.push(matrix_type);
+        }
+    }
+    for frame_type in other.frame_types {
+        if !metadata.frame_types.iter().any(|t| t.signature == frame_type.signature) {
+            metadata.frame_types.push(frame_type);
+        }
+    }
+    metadata.nvts.extend(other.nvts);
+
+    let offset = first_frames.iter().map(|f| f.stream_id + 1).max().unwrap_or(0);
+    for frame in &mut second_frames {
+        frame.stream_id += offset;
+    }
+
+    let mut writer = metadata.create(output)?;
+    let mut a = first_frames.into_iter().peekable();
+    let mut b = second_frames.into_iter().peekable();
+    let mut count = 0;
+    loop {
+        // Take from the first file on ties, so its frames come first
+        let frame = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if y.time < x.time => b.next(),
+            (Some(_), _) => a.next(),
+            (None, _) => b.next(),
+        };
+        let Some(frame) = frame else { break };
+        writer.write_frame_data(&frame)?;
+        count += 1;
+    }
+    writer.close()?;
+
+    println!(
+        "merged {} frames into {}, second file's streams start at {}",
+        count, output, offset
+    );
+    Ok(())
+}
+
+/// Read the metadata and all frames of a file.
+fn read(path: &str) -> Result<(FileMetadata, Vec<FrameData>)> {
+    let file = SdifFile::open(path)?;
+    let metadata = FileMetadata::from_file(&file)?;
+    let frames = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<_>>()?;
+    Ok((metadata, frames))
+}
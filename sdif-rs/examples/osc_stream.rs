@@ -0,0 +1,87 @@
+//! Stream the frames of an SDIF file over OSC in real time.
+//!
+//! Each matrix row is sent as one UDP message to
+//! `/sdif/<frame signature>/<matrix signature>`, with the stream ID, the
+//! frame time and the row's values as arguments, at the time the frame is
+//! due. A Max or Pure Data patch listening on the port can resynthesize
+//! the analysis as it plays.
+//!
+//! ```text
+//! cargo run --example osc_stream --features bundled -- \
+//!     analysis.sdif 127.0.0.1:9000 [frame-signature]
+//! ```
+
+use std::env;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::process;
+
+use sdif_rs::player::Player;
+use sdif_rs::{FrameFilter, Result, SdifFile};
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !(2..=3).contains(&args.len()) {
+        eprintln!("usage: osc_stream <input.sdif> <host:port> [frame-signature]");
+        process::exit(2);
+    }
+
+    let file = SdifFile::open(&args[0])?;
+    let mut filter = FrameFilter::new();
+    if let Some(signature) = args.get(2) {
+        filter = filter.signature(signature);
+    }
+
+    let target = args[1].to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown address: {}", args[1]))
+    })?;
+    // Not connected, so that messages sent before the receiver is up don't
+    // make later ones fail
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+
+    let mut messages = 0;
+    for frame in Player::with_filter(&file, filter, 0.0)? {
+        let mut frame = frame?;
+        let (time, stream_id) = (frame.time(), frame.stream_id());
+        let frame_signature = frame.signature();
+
+        for matrix in frame.matrices() {
+            let matrix = matrix?;
+            let address = format!("/sdif/{}/{}", frame_signature, matrix.signature());
+            let cols = matrix.cols().max(1);
+            for row in matrix.data_f64()?.chunks(cols) {
+                send(&socket, target, &address, stream_id, time, row)?;
+                messages += 1;
+            }
+        }
+    }
+
+    println!("sent {} messages to {}", messages, args[1]);
+    Ok(())
+}
+
+/// Send one OSC message with an int32 stream ID and float32 time and values.
+fn send(
+    socket: &UdpSocket,
+    target: SocketAddr,
+    address: &str,
+    stream_id: u32,
+    time: f64,
+    row: &[f64],
+) -> io::Result<()> {
+    let mut packet = Vec::with_capacity(64 + row.len() * 4);
+    push_string(&mut packet, address);
+    push_string(&mut packet, &format!(",if{}", "f".repeat(row.len())));
+    packet.extend_from_slice(&(stream_id as i32).to_be_bytes());
+    packet.extend_from_slice(&(time as f32).to_be_bytes());
+    for &value in row {
+        packet.extend_from_slice(&(value as f32).to_be_bytes());
+    }
+    socket.send_to(&packet, target).map(|_| ())
+}
+
+/// Append an OSC string: null-terminated and padded to 4 bytes.
+fn push_string(packet: &mut Vec<u8>, text: &str) {
+    packet.extend_from_slice(text.as_bytes());
+    packet.resize(packet.len() + 4 - text.len() % 4, 0);
+}
@@ -0,0 +1,407 @@
+//! Analysis of frame timing and content over time.
+//!
+//! Capture pipelines sometimes drop frames or deliver them late.
+//! [`find_gaps()`] compares the spacing of consecutive frames against the
+//! expected hop size and reports where frames are missing or irregular.
+//! Each stream and frame signature is checked on its own, so interleaved
+//! streams don't hide each other's gaps.
+//!
+//! Missing frames can be filled in while copying a file with
+//! [`CopyOptions::fill_gaps()`](crate::copy::CopyOptions::fill_gaps).
+//!
+//! [`windowed_stats()`] groups frames into fixed time windows and computes
+//! an aggregate per window, turning a file into a regular time series of
+//! features.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{analysis, SdifFile};
+//!
+//! let file = SdifFile::open("capture.sdif")?;
+//! for gap in analysis::find_gaps(&file, 0.01)? {
+//!     println!("{} stream {}: {:.3}s-{:.3}s, {} missing",
+//!         gap.signature, gap.stream_id, gap.start, gap.end, gap.missing_frames());
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::data_type::DataType;
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame::Frame;
+use crate::frame_data::{FrameData, MatrixData, OwnedFrame};
+use crate::signature::Signature;
+
+/// Relative deviation from the expected hop that counts as irregular.
+const IRREGULAR_TOLERANCE: f64 = 0.1;
+
+/// Longest run of empty windows that [`windowed_stats()`] reports one by one.
+pub const MAX_EMPTY_WINDOWS: usize = 1024;
+
+/// What is wrong with the spacing between two frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GapKind {
+    /// One or more frames are missing.
+    Missing {
+        /// Number of frames that would fit in the gap at the expected hop.
+        frames: usize,
+    },
+
+    /// The spacing is off by more than 10% of the hop, but no whole
+    /// frame is missing.
+    Irregular,
+}
+
+/// A span between two consecutive frames of a stream that doesn't match
+/// the expected hop.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Gap {
+    /// Frame signature of the stream.
+    pub signature: String,
+
+    /// Stream ID.
+    pub stream_id: u32,
+
+    /// Time of the frame before the gap.
+    pub start: f64,
+
+    /// Time of the frame after the gap.
+    pub end: f64,
+
+    /// Kind of gap.
+    pub kind: GapKind,
+}
+
+impl Gap {
+    /// Get the time between the frames around the gap.
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// Get the number of missing frames (0 for irregular spacing).
+    pub fn missing_frames(&self) -> usize {
+        match self.kind {
+            GapKind::Missing { frames } => frames,
+            GapKind::Irregular => 0,
+        }
+    }
+}
+
+/// Find spans where frames are missing or irregularly spaced.
+///
+/// Frames of each (stream ID, signature) pair are expected every
+/// `expected_hop` seconds. A spacing that fits one or more extra frames
+/// is reported as [`GapKind::Missing`]; a spacing that deviates from the
+/// hop by more than 10% otherwise is reported as [`GapKind::Irregular`].
+///
+/// The file is read from its current position to the end; matrix data
+/// is skipped.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if `expected_hop` is not positive
+/// - Any error from reading the frames
+pub fn find_gaps(file: &SdifFile, expected_hop: f64) -> Result<Vec<Gap>> {
+    let mut gaps = Vec::new();
+    scan_gaps(file, expected_hop, |gap, _| {
+        gaps.push(gap);
+        Ok(())
+    })?;
+    Ok(gaps)
+}
+
+/// Scan a file for gaps, passing each gap with the frame that ends it.
+pub(crate) fn scan_gaps<F>(file: &SdifFile, expected_hop: f64, mut visit: F) -> Result<()>
+where
+    F: FnMut(Gap, Frame<'_>) -> Result<()>,
+{
+    if !(expected_hop > 0.0 && expected_hop.is_finite()) {
+        return Err(Error::invalid_format(format!(
+            "Expected hop must be positive, got {}",
+            expected_hop
+        )));
+    }
+
+    let mut last_times: HashMap<(u32, Signature), f64> = HashMap::new();
+
+    for frame in file.frames() {
+        let frame = frame?;
+        let time = frame.time();
+        let key = (frame.stream_id(), frame.canonical_signature_raw());
+
+        let Some(start) = last_times.insert(key, time) else {
+            continue;
+        };
+
+        if let Some(kind) = classify(time - start, expected_hop) {
+            let gap = Gap {
+                signature: frame.signature(),
+                stream_id: frame.stream_id(),
+                start,
+                end: time,
+                kind,
+            };
+            visit(gap, frame)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Frames falling into one time window.
+///
+/// Passed to the aggregate function of [`windowed_stats()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Window<'a> {
+    /// Start of the window (inclusive), in seconds.
+    pub start: f64,
+
+    /// End of the window (exclusive), in seconds.
+    pub end: f64,
+
+    /// Frames in the window, in file order.
+    pub frames: &'a [FrameData],
+}
+
+impl<'a> Window<'a> {
+    /// Iterate over the matrices with the given signature in all frames.
+    pub fn matrices(&self, signature: &'a str) -> impl Iterator<Item = &'a MatrixData> + 'a {
+        self.frames
+            .iter()
+            .flat_map(|f| &f.matrices)
+            .filter(move |m| m.signature == signature)
+    }
+
+    /// Iterate over one column of all matrices with the given signature.
+    ///
+    /// Matrices with fewer columns are skipped.
+    pub fn column(&self, signature: &'a str, column: usize) -> impl Iterator<Item = f64> + 'a {
+        self.matrices(signature)
+            .flat_map(move |m| m.rows().filter_map(move |row| row.get(column).copied()))
+    }
+
+    /// Count the rows of all matrices with the given signature.
+    ///
+    /// For 1TRC or 1HRM data this is the number of partials in the window.
+    pub fn row_count(&self, signature: &'a str) -> usize {
+        self.matrices(signature).map(|m| m.rows).sum()
+    }
+
+    /// Get the mean of a column over all matrices with the given signature.
+    ///
+    /// Returns `None` if the window has no such values.
+    pub fn mean(&self, signature: &'a str, column: usize) -> Option<f64> {
+        let (sum, count) = self
+            .column(signature, column)
+            .fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+        (count > 0).then(|| sum / count as f64)
+    }
+
+    /// Get the sum of squares of a column over all matrices with the
+    /// given signature.
+    ///
+    /// Applied to an amplitude column this is the total energy in the window.
+    pub fn sum_of_squares(&self, signature: &'a str, column: usize) -> f64 {
+        self.column(signature, column).map(|v| v * v).sum()
+    }
+}
+
+/// The aggregate computed for one window.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WindowValue<T> {
+    /// Start of the window, in seconds.
+    pub start: f64,
+
+    /// End of the window, in seconds.
+    pub end: f64,
+
+    /// Value returned by the aggregate function.
+    pub value: T,
+}
+
+/// Compute an aggregate over consecutive time windows.
+///
+/// Windows are `window` seconds long and aligned to multiples of
+/// `window`, starting with the window holding the first frame and ending
+/// with the window holding the last. Windows without frames are passed to
+/// `aggregate` as well, so the result is a regular time series; only runs
+/// of more than [`MAX_EMPTY_WINDOWS`] empty windows are passed as a single
+/// window spanning the whole run, so a jump in time can't exhaust memory.
+///
+/// The file is read from its current position to the end. Only `Float4`
+/// and `Float8` matrices are decoded; other matrices are left out of the
+/// windows.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if `window` is not positive
+/// - Any error from reading the frames
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::{analysis, SdifFile};
+///
+/// let file = SdifFile::open("analysis.sdif")?;
+/// let series = analysis::windowed_stats(&file, 0.1, |w| {
+///     (
+///         w.mean("1FQ0", 0),              // mean F0
+///         w.row_count("1TRC"),            // partial count
+///         w.sum_of_squares("1TRC", 2),    // total energy
+///     )
+/// })?;
+///
+/// for point in &series {
+///     println!("{:.1}s: {:?}", point.start, point.value);
+/// }
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+pub fn windowed_stats<T, F>(
+    file: &SdifFile,
+    window: f64,
+    mut aggregate: F,
+) -> Result<Vec<WindowValue<T>>>
+where
+    F: FnMut(&Window<'_>) -> T,
+{
+    if !(window > 0.0 && window.is_finite()) {
+        return Err(Error::invalid_format(format!(
+            "Window length must be positive, got {}",
+            window
+        )));
+    }
+
+    let mut series = Vec::new();
+    let mut frames: Vec<FrameData> = Vec::new();
+    let mut index: Option<i64> = None;
+
+    // Aggregate the windows from index `first` up to but not including `last`
+    let mut flush = |first: i64, last: i64, frames: &[FrameData]| {
+        let start = first as f64 * window;
+        let end = last as f64 * window;
+        let value = aggregate(&Window { start, end, frames });
+        series.push(WindowValue { start, end, value });
+    };
+
+    for frame in file.frames() {
+        let frame = float_matrices(frame?.into_owned()?);
+        let frame_index = (frame.time / window).floor() as i64;
+
+        if let Some(current) = index {
+            if frame_index > current {
+                flush(current, current + 1, &frames);
+                frames.clear();
+                if frame_index - current - 1 > MAX_EMPTY_WINDOWS as i64 {
+                    flush(current + 1, frame_index, &[]);
+                } else {
+                    for empty in current + 1..frame_index {
+                        flush(empty, empty + 1, &[]);
+                    }
+                }
+            }
+        }
+        if index.map_or(true, |current| frame_index > current) {
+            index = Some(frame_index);
+        }
+        frames.push(frame);
+    }
+
+    if let Some(current) = index {
+        flush(current, current + 1, &frames);
+    }
+
+    Ok(series)
+}
+
+/// Decode the `Float4` and `Float8` matrices of a frame, dropping the rest.
+fn float_matrices(frame: OwnedFrame) -> FrameData {
+    let mut data = FrameData::new(frame.signature, frame.time, frame.stream_id);
+    data.matrices = frame
+        .matrices
+        .into_iter()
+        .filter(|m| matches!(m.data_type, DataType::Float4 | DataType::Float8))
+        .map(MatrixData::from)
+        .collect();
+    data
+}
+
+/// Classify the spacing between two frames, or `None` if it is regular.
+fn classify(delta: f64, hop: f64) -> Option<GapKind> {
+    let steps = (delta / hop).round();
+
+    if steps >= 2.0 {
+        Some(GapKind::Missing {
+            frames: steps as usize - 1,
+        })
+    } else if (delta - hop).abs() > hop * IRREGULAR_TOLERANCE {
+        Some(GapKind::Irregular)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(0.01, 0.01), None);
+        assert_eq!(classify(0.0105, 0.01), None);
+        assert_eq!(classify(0.0089, 0.01), Some(GapKind::Irregular));
+        assert_eq!(classify(0.0, 0.01), Some(GapKind::Irregular));
+        assert_eq!(classify(0.014, 0.01), Some(GapKind::Irregular));
+        assert_eq!(classify(0.02, 0.01), Some(GapKind::Missing { frames: 1 }));
+        assert_eq!(classify(0.051, 0.01), Some(GapKind::Missing { frames: 4 }));
+    }
+
+    #[test]
+    fn test_window_aggregates() {
+        let mut a = FrameData::new("1TRC", 0.0, 0);
+        a.matrices.push(MatrixData::new(
+            "1TRC",
+            2,
+            3,
+            vec![1.0, 100.0, 0.5, 2.0, 200.0, 0.25],
+        ));
+        let mut b = FrameData::new("1FQ0", 0.01, 0);
+        b.matrices
+            .push(MatrixData::new("1FQ0", 1, 2, vec![100.0, 0.9]));
+        let mut c = FrameData::new("1TRC", 0.02, 0);
+        c.matrices
+            .push(MatrixData::new("1TRC", 1, 3, vec![1.0, 110.0, 1.0]));
+
+        let frames = [a, b, c];
+        let window = Window {
+            start: 0.0,
+            end: 0.1,
+            frames: &frames,
+        };
+
+        assert_eq!(window.row_count("1TRC"), 3);
+        assert_eq!(window.mean("1FQ0", 0), Some(100.0));
+        assert_eq!(window.mean("1TRC", 1), Some(410.0 / 3.0));
+        assert_eq!(window.mean("1HRM", 0), None);
+        assert_eq!(window.sum_of_squares("1TRC", 2), 0.25 + 0.0625 + 1.0);
+        assert_eq!(window.column("1TRC", 5).count(), 0);
+    }
+
+    #[test]
+    fn test_gap_accessors() {
+        let gap = Gap {
+            signature: "1FQ0".to_string(),
+            stream_id: 0,
+            start: 1.0,
+            end: 1.5,
+            kind: GapKind::Missing { frames: 4 },
+        };
+        assert_eq!(gap.duration(), 0.5);
+        assert_eq!(gap.missing_frames(), 4);
+    }
+}
@@ -0,0 +1,240 @@
+//! Break-point export for tools that don't read SDIF.
+//!
+//! Many computer-music tools take control data as break-point functions:
+//! a list of `(time, value)` pairs with straight lines in between. This
+//! module extracts them from pitch (1FQ0) and partial (1TRC/1HRM) data and
+//! writes them in two widely read formats:
+//!
+//! - [`Breakpoints::to_text()`]: plain `time value` lines, as read by the
+//!   CDP programs and most other break-point file readers
+//! - [`Breakpoints::to_csound_table()`]: a Csound score `f` statement
+//!   using GEN27, which builds a table from break-points
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{breakpoints, SdifFile};
+//!
+//! let file = SdifFile::open("voice.f0.sdif")?;
+//! let pitch = breakpoints::pitch(&file)?;
+//! std::fs::write("voice.brk", pitch.to_text())?;
+//! std::fs::write("voice.sco", pitch.to_csound_table(1, 4096)?)?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame_data::MatrixData;
+
+/// Signature of pitch matrices.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Signatures of partial matrices.
+const PARTIAL_SIGNATURES: &[&str] = &["1TRC", "1HRM"];
+
+/// One point of a break-point function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Breakpoint {
+    /// Time in seconds.
+    pub time: f64,
+
+    /// Value at that time.
+    pub value: f64,
+}
+
+/// A break-point function: values at increasing times.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Breakpoints {
+    /// Points in time order.
+    pub points: Vec<Breakpoint>,
+}
+
+impl Breakpoints {
+    /// Create an empty function.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a point.
+    ///
+    /// Break-point formats need strictly increasing times, so a point at
+    /// or before the last point's time is ignored.
+    pub fn push(&mut self, time: f64, value: f64) {
+        if self.points.last().map_or(true, |last| time > last.time) {
+            self.points.push(Breakpoint { time, value });
+        }
+    }
+
+    /// Get the time between the first and last point, in seconds.
+    pub fn duration(&self) -> f64 {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// Format as a text break-point file, one `time value` pair per line.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for point in &self.points {
+            let _ = writeln!(text, "{} {}", point.time, point.value);
+        }
+        text
+    }
+
+    /// Format as a Csound score `f` statement building table `table` of
+    /// `size` points with GEN27.
+    ///
+    /// The function's time span is stretched over the table, so the first
+    /// point lands on index 0 and the last on index `size`, the guard
+    /// point. Points that would share an index with the previous one are
+    /// dropped. The GEN number is negated to keep the values unnormalized.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if there are fewer than two points or
+    ///   `size` is 0
+    pub fn to_csound_table(&self, table: u32, size: usize) -> Result<String> {
+        if self.points.len() < 2 || size == 0 {
+            return Err(Error::invalid_format(
+                "A Csound table needs at least two points and a non-zero size",
+            ));
+        }
+
+        let start = self.points[0].time;
+        let duration = self.duration();
+        let mut statement = format!("f {} 0 {} -27", table, size + 1);
+        let mut last_index = None;
+
+        for point in &self.points {
+            let index = ((point.time - start) / duration * size as f64).round() as usize;
+            if last_index.is_some_and(|last| index <= last) {
+                continue;
+            }
+            let _ = write!(statement, " {} {}", index, point.value);
+            last_index = Some(index);
+        }
+
+        statement.push('\n');
+        Ok(statement)
+    }
+}
+
+/// Extract the fundamental frequency over time from 1FQ0 matrices.
+///
+/// Each 1FQ0 matrix contributes its first row's `Frequency` value.
+///
+/// The file is read from its current position to the end.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if a 1FQ0 matrix has no `Frequency` column
+/// - Any error from reading the frames
+pub fn pitch(file: &SdifFile) -> Result<Breakpoints> {
+    let mut breakpoints = Breakpoints::new();
+
+    for frame in file.frames() {
+        let frame = frame?.into_data()?;
+        for matrix in frame.matrices.iter().filter(|m| m.signature == PITCH_SIGNATURE) {
+            let frequency = column(matrix, "Frequency")?;
+            if let Some(value) = matrix.get(0, frequency) {
+                breakpoints.push(frame.time, value);
+            }
+        }
+    }
+
+    Ok(breakpoints)
+}
+
+/// Extract one column of each partial over time from 1TRC/1HRM matrices.
+///
+/// Partials are told apart by their `Index` column, and the result maps
+/// each index to the function of its `column_name` values, for example
+/// `"Frequency"` or `"Amplitude"`.
+///
+/// The file is read from its current position to the end.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if a partial matrix lacks the `Index` column
+///   or `column_name`
+/// - Any error from reading the frames
+pub fn partials(file: &SdifFile, column_name: &str) -> Result<BTreeMap<u32, Breakpoints>> {
+    let mut partials: BTreeMap<u32, Breakpoints> = BTreeMap::new();
+
+    for frame in file.frames() {
+        let frame = frame?.into_data()?;
+        for matrix in frame
+            .matrices
+            .iter()
+            .filter(|m| PARTIAL_SIGNATURES.contains(&m.signature.as_str()))
+        {
+            let index = column(matrix, "Index")?;
+            let value = column(matrix, column_name)?;
+            for row in matrix.rows() {
+                partials
+                    .entry(row[index] as u32)
+                    .or_default()
+                    .push(frame.time, row[value]);
+            }
+        }
+    }
+
+    Ok(partials)
+}
+
+/// Find a column of a matrix, or fail with a format error.
+fn column(matrix: &MatrixData, name: &str) -> Result<usize> {
+    matrix.find_column(name).ok_or_else(|| {
+        Error::invalid_format(format!("{} matrix has no {} column", matrix.signature, name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(points: &[(f64, f64)]) -> Breakpoints {
+        let mut breakpoints = Breakpoints::new();
+        for &(time, value) in points {
+            breakpoints.push(time, value);
+        }
+        breakpoints
+    }
+
+    #[test]
+    fn test_push_keeps_times_increasing() {
+        let breakpoints = function(&[(0.0, 1.0), (0.5, 2.0), (0.5, 3.0), (0.25, 4.0), (1.0, 5.0)]);
+        let times: Vec<f64> = breakpoints.points.iter().map(|p| p.time).collect();
+        assert_eq!(times, [0.0, 0.5, 1.0]);
+        assert_eq!(breakpoints.duration(), 1.0);
+    }
+
+    #[test]
+    fn test_to_text() {
+        let breakpoints = function(&[(0.0, 220.0), (0.01, 221.5)]);
+        assert_eq!(breakpoints.to_text(), "0 220\n0.01 221.5\n");
+    }
+
+    #[test]
+    fn test_to_csound_table() {
+        let breakpoints = function(&[(1.0, 220.0), (1.001, 230.0), (1.5, 440.0), (2.0, 110.0)]);
+        assert_eq!(
+            breakpoints.to_csound_table(3, 8).unwrap(),
+            "f 3 0 9 -27 0 220 4 440 8 110\n"
+        );
+        assert!(function(&[(0.0, 1.0)]).to_csound_table(1, 8).is_err());
+    }
+
+    #[test]
+    fn test_column_fallback() {
+        let matrix = MatrixData::new("1TRC", 1, 4, vec![1.0, 440.0, 0.5, 0.0]);
+        assert_eq!(column(&matrix, "Amplitude").unwrap(), 2);
+        assert!(column(&matrix, "Confidence").is_err());
+    }
+}
@@ -8,6 +8,8 @@
 //!
 //! ```text
 //! New → (create) → Config → (build) → SdifWriter
+//! New → (create_temporary) → Config → (build) → SdifWriter
+//! New → (append) → SdifWriter
 //! ```
 //!
 //! # Example
@@ -32,13 +34,24 @@ use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
 
+use indexmap::IndexMap;
 use sdif_sys::{
-    SdifFOpen, SdifFClose, SdifFileT, SdifFileModeET_eWriteFile,
-    SdifFWriteGeneralHeader, SdifFWriteAllASCIIChunks,
+    SdifFOpen, SdifFClose, SdifFileT, SdifFileModeET_eReadWriteFile, SdifFileModeET_eWriteFile,
+    SdifFReadAllASCIIChunks, SdifFReadGeneralHeader, SdifFSetPos, SdifFWriteGeneralHeader,
+    SdifFWriteAllASCIIChunks, SdiffPosT,
 };
 
+use crate::columns::ColumnDef;
+use crate::data_type::DataType;
 use crate::error::{Error, Result};
-use crate::init::ensure_initialized;
+use crate::file::{self, ReadOptions, SdifFile};
+use crate::history::{self, HistoryEntry};
+use crate::init::initialize;
+use crate::metadata;
+use crate::schema::Schema;
+use crate::signature::{string_to_signature, Signature};
+use crate::source::SourceAudio;
+use crate::streams;
 use crate::writer::SdifWriter;
 
 // ============================================================================
@@ -61,7 +74,10 @@ pub struct Config;
 #[derive(Debug, Default, Clone)]
 pub(crate) struct NvtConfig {
     /// List of NVT tables, each being a map of key-value pairs.
-    pub tables: Vec<HashMap<String, String>>,
+    ///
+    /// Entries keep their insertion order so the written file is
+    /// byte-for-byte reproducible.
+    pub tables: Vec<IndexMap<String, String>>,
 }
 
 /// Stores a matrix type definition.
@@ -91,6 +107,25 @@ pub(crate) struct BuilderConfig {
     pub matrix_types: Vec<MatrixTypeDef>,
     /// Frame type definitions.
     pub frame_types: Vec<FrameTypeDef>,
+    /// Data types used for f64 data, keyed by matrix signature.
+    pub data_types: HashMap<Signature, DataType>,
+    /// Omit run-dependent metadata (timestamps, library versions).
+    pub deterministic: bool,
+    /// Store matrix data column by column.
+    pub column_major: bool,
+    /// Index of the NVT table holding stream names declared on this builder.
+    pub stream_table: Option<usize>,
+    /// Index of the NVT table holding conventional metadata keys.
+    pub metadata_table: Option<usize>,
+    /// Leave out the NVT entry naming this crate as the writer.
+    pub omit_writer: bool,
+    /// Record the build time as the file's date.
+    #[cfg(feature = "chrono")]
+    pub stamp_date: bool,
+    /// Estimated size of the frames to preallocate, in bytes.
+    pub reserve_bytes: Option<u64>,
+    /// Write to a temporary file that is removed with the writer.
+    pub temporary: bool,
 }
 
 // ============================================================================
@@ -167,6 +202,149 @@ impl SdifFileBuilder<New> {
             _state: PhantomData,
         })
     }
+
+    /// Write the file to a temporary file instead of a chosen path, and
+    /// transition to Config state.
+    ///
+    /// For data that is sent elsewhere rather than kept, such as a network
+    /// response: it stays in the temporary file until it is collected with
+    /// [`SdifWriter::into_bytes()`](crate::SdifWriter::into_bytes) or
+    /// [`SdifWriter::finish_into()`](crate::SdifWriter::finish_into). The
+    /// temporary file is removed when the writer is dropped, so data not
+    /// collected by then is lost.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let mut writer = SdifFile::builder()
+    ///     .create_temporary()?
+    ///     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    ///     .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+    ///     .build()?;
+    /// writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    /// let bytes: Vec<u8> = writer.into_bytes()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn create_temporary(self) -> Result<SdifFileBuilder<Config>> {
+        let mut builder = self.create(file::temp_sdif_path())?;
+        builder.config.temporary = true;
+        Ok(builder)
+    }
+
+    /// Open an existing SDIF file to write more frames at its end.
+    ///
+    /// The file's header, NVTs and type declarations are kept as they are,
+    /// and its frames are scanned once to find the time of the last one:
+    /// frames written to the returned writer must not be earlier. Stream
+    /// names declared in the file can be used with
+    /// [`SdifWriter::named_stream()`](crate::SdifWriter::named_stream).
+    ///
+    /// Data is written row by row with the default data types, since the
+    /// options of the writer that created the file aren't recorded in it.
+    ///
+    /// # Errors
+    ///
+    /// - Same as [`SdifFile::open()`](crate::SdifFile::open) if the file
+    ///   can't be read
+    /// - [`Error::OpenFailed`] if the file can't be opened for writing
+    /// - [`Error::Io`] if the end of the file can't be reached
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let mut writer = SdifFile::builder().append("analysis.sdif")?;
+    /// writer.write_frame_one_matrix("1TRC", 12.5, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    /// writer.close()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn append(self, path: impl AsRef<Path>) -> Result<SdifWriter> {
+        let path = path.as_ref();
+
+        let (last_time, streams) = {
+            let file = SdifFile::open_with_options(path, ReadOptions::new().sidecar_index(false))?;
+            let mut last_time = None;
+            for frame in file.frames() {
+                last_time = Some(frame?.time());
+            }
+            (last_time, streams::from_entries(file.nvts().iter().flatten()))
+        };
+        let file_len = std::fs::metadata(path)?.len();
+
+        let path_str = path.to_str().ok_or_else(|| {
+            Error::invalid_format("Path contains invalid UTF-8")
+        })?;
+        let c_path = CString::new(path_str)?;
+
+        let handle = unsafe { SdifFOpen(c_path.as_ptr(), SdifFileModeET_eReadWriteFile) };
+        let handle = NonNull::new(handle).ok_or_else(|| Error::open_failed(path))?;
+
+        // Read the header and ASCII chunks to register the file's types,
+        // then move past the last frame
+        let mut end = file_len as SdiffPosT;
+        let positioned = unsafe {
+            SdifFReadGeneralHeader(handle.as_ptr()) != 0 && {
+                SdifFReadAllASCIIChunks(handle.as_ptr());
+                SdifFSetPos(handle.as_ptr(), &mut end) == 0
+            }
+        };
+        if !positioned {
+            unsafe { SdifFClose(handle.as_ptr()) };
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Failed to move to the end of the SDIF file",
+            )));
+        }
+
+        let mut writer =
+            SdifWriter::new(handle, path.to_path_buf(), HashMap::new(), false, streams);
+        writer.resume_after(last_time);
+        Ok(writer)
+    }
+}
+
+impl SdifFileBuilder<New> {
+    /// Create a builder preconfigured from a schema document.
+    ///
+    /// The document's NVT defaults, matrix types and frame types are
+    /// applied as if added with [`add_nvt()`](SdifFileBuilder::add_nvt),
+    /// [`add_matrix_type()`](SdifFileBuilder::add_matrix_type) and
+    /// [`add_frame_type()`](SdifFileBuilder::add_frame_type). The format is
+    /// chosen from the extension (see [`Schema::from_file()`]).
+    ///
+    /// Requires the `json` or `toml` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFileBuilder;
+    ///
+    /// let mut writer = SdifFileBuilder::from_schema("pipeline-schema.toml")?
+    ///     .create("output.sdif")?
+    ///     .add_nvt([("source", "take-03.wav")])?
+    ///     .build()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    #[cfg(any(feature = "json", feature = "toml"))]
+    pub fn from_schema(path: impl AsRef<Path>) -> Result<Self> {
+        let schema = Schema::from_file(path)?;
+
+        let configured = SdifFileBuilder::<Config> {
+            path: None,
+            config: BuilderConfig::default(),
+            _state: PhantomData,
+        }
+        .with_schema(&schema)?;
+
+        Ok(SdifFileBuilder {
+            path: None,
+            config: configured.config,
+            _state: PhantomData,
+        })
+    }
 }
 
 impl Default for SdifFileBuilder<New> {
@@ -203,7 +381,7 @@ impl SdifFileBuilder<Config> {
         mut self,
         entries: impl IntoIterator<Item = (&'a str, &'a str)>,
     ) -> Result<Self> {
-        let mut nvt = HashMap::new();
+        let mut nvt = IndexMap::new();
         for (key, value) in entries {
             // Validate no embedded nulls
             if key.contains('\0') || value.contains('\0') {
@@ -219,6 +397,290 @@ impl SdifFileBuilder<Config> {
         Ok(self)
     }
 
+    /// Record a processing step in the file's history.
+    ///
+    /// The step is stored as NVT entries following the convention in
+    /// [`crate::history`] and numbered after any steps already added, for
+    /// example history copied from an input file. Read it back with
+    /// [`SdifFile::history()`](crate::SdifFile::history).
+    ///
+    /// NVTs are written before the first frame, so history is recorded on
+    /// the builder rather than the writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - Name of the tool performing the step
+    /// * `version` - Version of the tool
+    /// * `params` - Parameters the tool was run with
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `tool` is empty or an entry contains
+    ///   null bytes
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let builder = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .append_history("my-analyzer", "1.2.0", [("hop", "0.01"), ("window", "hann")])?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn append_history<'a>(
+        self,
+        tool: &str,
+        version: &str,
+        params: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self> {
+        if tool.is_empty() {
+            return Err(Error::invalid_format("History tool name cannot be empty"));
+        }
+
+        let entry = params
+            .into_iter()
+            .fold(HistoryEntry::new(tool, version), |entry, (name, value)| {
+                entry.param(name, value)
+            });
+
+        let step = self
+            .config
+            .nvts
+            .tables
+            .iter()
+            .flat_map(|table| table.keys())
+            .filter_map(|key| history::step_of(key))
+            .max()
+            .map_or(0, |last| last + 1);
+
+        let entries = entry.to_nvt_entries(step);
+        self.add_nvt(entries.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
+    /// Declare a named stream and allocate its stream ID.
+    ///
+    /// The name and ID are recorded in an NVT entry (see
+    /// [`crate::streams`]), so code writing and reading the file can refer
+    /// to the stream by name instead of a magic number. Get the ID with
+    /// [`SdifWriter::named_stream()`](crate::SdifWriter::named_stream) after
+    /// building, or [`SdifFile::stream_named()`](crate::SdifFile::stream_named)
+    /// when reading.
+    ///
+    /// IDs are allocated from 1 upwards, after any named streams already
+    /// in the NVTs (for example copied from an input file), leaving stream
+    /// 0 for frames written without an explicit stream. Declaring a name
+    /// twice keeps its first ID.
+    ///
+    /// NVTs are written before the first frame, so streams are named on
+    /// the builder rather than the writer.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `name` is empty or contains whitespace
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let mut writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    ///     .add_frame_type("1FQ0", &["1FQ0 Pitch"])?
+    ///     .named_stream("f0")?
+    ///     .named_stream("f0-smoothed")?
+    ///     .build()?;
+    ///
+    /// let smoothed = writer.named_stream("f0-smoothed")?;
+    /// writer.new_frame("1FQ0", 0.0, smoothed)?
+    ///     .add_matrix("1FQ0", 1, 2, &[220.0, 0.9])?
+    ///     .finish()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn named_stream(mut self, name: &str) -> Result<Self> {
+        streams::validate_name(name)?;
+
+        let existing = self.stream_ids();
+        if existing.contains_key(name) {
+            return Ok(self);
+        }
+        let id = existing.values().max().map_or(1, |last| last + 1);
+
+        let tables = &mut self.config.nvts.tables;
+        let table = *self.config.stream_table.get_or_insert_with(|| {
+            tables.push(IndexMap::new());
+            tables.len() - 1
+        });
+        tables[table].insert(streams::key(name), id.to_string());
+
+        Ok(self)
+    }
+
+    /// Record the sample rate of the analysed sound, in Hz.
+    ///
+    /// Stored under the conventional [`SampleRate`](crate::metadata::SAMPLE_RATE)
+    /// key and read back with
+    /// [`SdifFile::sample_rate()`](crate::SdifFile::sample_rate).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `rate` is not positive and finite
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let builder = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .sample_rate(44100.0)?
+    ///     .creator("my-analyzer")?
+    ///     .soundfile("voice.wav")?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn sample_rate(self, rate: f64) -> Result<Self> {
+        let value = metadata::format_sample_rate(rate)?;
+        self.set_metadata(metadata::SAMPLE_RATE, &value)
+    }
+
+    /// Record the name of the program writing the file.
+    ///
+    /// Stored under the conventional [`Creator`](crate::metadata::CREATOR) key.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `creator` contains null bytes
+    pub fn creator(self, creator: &str) -> Result<Self> {
+        self.set_metadata(metadata::CREATOR, creator)
+    }
+
+    /// Record the date the file was written.
+    ///
+    /// Stored as given under the conventional [`Date`](crate::metadata::DATE)
+    /// key. Prefer RFC 3339 (`2024-03-01T12:00:00Z`), which every reader
+    /// understands.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `date` contains null bytes
+    pub fn date(self, date: &str) -> Result<Self> {
+        self.set_metadata(metadata::DATE, date)
+    }
+
+    /// Record the date the file was written.
+    ///
+    /// Like [`date()`](Self::date), formatted as ISO 8601 in UTC
+    /// (`2024-03-01T12:00:00Z`).
+    #[cfg(feature = "chrono")]
+    pub fn date_time(self, date: chrono::DateTime<chrono::Utc>) -> Result<Self> {
+        self.set_metadata(metadata::DATE, &metadata::format_date(date))
+    }
+
+    /// Record the time the file is built as its date.
+    ///
+    /// When [`build()`](Self::build) is called, the current time is stored
+    /// under the conventional [`Date`](crate::metadata::DATE) key in ISO
+    /// 8601, unless a date was already set with [`date()`](Self::date) or
+    /// [`date_time()`](Self::date_time). Read it back with
+    /// [`SdifFile::date_time()`](crate::SdifFile::date_time).
+    ///
+    /// The timestamp is left out in [`deterministic()`](Self::deterministic)
+    /// mode, since it would differ between runs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .creator("my-analyzer")?
+    ///     .stamp_date()
+    ///     .build()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn stamp_date(mut self) -> Self {
+        self.config.stamp_date = true;
+        self
+    }
+
+    /// Leave out the NVT entry naming this crate as the file's writer.
+    ///
+    /// By default, [`build()`](Self::build) records `sdif-rs` and its
+    /// version under the [`Writer`](crate::metadata::WRITER) key, which
+    /// [`SdifFile::written_by_sdif_rs()`](crate::SdifFile::written_by_sdif_rs)
+    /// reads back. The entry is also left out in
+    /// [`deterministic()`](Self::deterministic) mode, since the version
+    /// changes between releases.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .omit_writer()
+    ///     .build()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn omit_writer(mut self) -> Self {
+        self.config.omit_writer = true;
+        self
+    }
+
+    /// Record the path of the analysed sound file.
+    ///
+    /// Stored under the conventional [`Soundfile`](crate::metadata::SOUNDFILE)
+    /// key.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `path` contains null bytes
+    pub fn soundfile(self, path: &str) -> Result<Self> {
+        self.set_metadata(metadata::SOUNDFILE, path)
+    }
+
+    /// Record the sound file the analysis was computed from.
+    ///
+    /// The file's name and a hash of its contents are stored in NVT
+    /// entries (see [`crate::source`]), along with its duration and sample
+    /// rate for WAV files when the `wav` feature is enabled. The name is
+    /// also recorded under the conventional
+    /// [`Soundfile`](crate::metadata::SOUNDFILE) key. Read it back with
+    /// [`SdifFile::source_audio()`](crate::SdifFile::source_audio).
+    ///
+    /// The whole file is read to compute the hash.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the path has no file name, or a WAV
+    ///   file has an invalid header
+    /// - [`Error::Io`] if the file can't be read
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let builder = SdifFile::builder()
+    ///     .create("voice.sdif")?
+    ///     .set_source_audio("sounds/voice.wav")?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn set_source_audio(self, path: impl AsRef<Path>) -> Result<Self> {
+        let source = SourceAudio::from_path(path)?;
+
+        let mut builder = self.soundfile(&source.file_name)?;
+        let table = builder.metadata_table();
+        for (key, value) in source.to_nvt_entries() {
+            table.insert(key, value);
+        }
+        Ok(builder)
+    }
+
     /// Define a matrix type with column names.
     ///
     /// Matrix types define the structure of data matrices. Common types include:
@@ -268,6 +730,50 @@ impl SdifFileBuilder<Config> {
         Ok(self)
     }
 
+    /// Define a matrix type with column units and descriptions.
+    ///
+    /// Like [`add_matrix_type()`](Self::add_matrix_type), and records the
+    /// unit and description of each column in NVT entries following the
+    /// convention in [`crate::columns`]. Read them back with
+    /// [`SdifFile::column_defs()`](crate::SdifFile::column_defs).
+    ///
+    /// # Errors
+    ///
+    /// - Same as [`add_matrix_type()`](Self::add_matrix_type)
+    /// - [`Error::InvalidFormat`] if an annotated column name contains
+    ///   whitespace, or an annotation contains null bytes
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::columns::ColumnDef;
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let builder = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .add_matrix_type_with_defs(
+    ///         "XLOU",
+    ///         &[ColumnDef::new("Loudness").unit("sone"), "Sharpness".into()],
+    ///     )?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn add_matrix_type_with_defs(
+        mut self,
+        signature: &str,
+        columns: &[ColumnDef],
+    ) -> Result<Self> {
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        self = self.add_matrix_type(signature, &names)?;
+
+        for column in columns {
+            for (key, value) in column.to_nvt_entries(signature)? {
+                self = self.set_metadata(&key, &value)?;
+            }
+        }
+
+        Ok(self)
+    }
+
     /// Define a frame type with its component matrices.
     ///
     /// Frame types define what matrices can appear in a frame.
@@ -306,6 +812,176 @@ impl SdifFileBuilder<Config> {
         Ok(self)
     }
 
+    /// Apply the NVT defaults and type declarations of a [`Schema`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let template = SdifFile::open("reference.sdif")?.describe_types()?;
+    ///
+    /// let writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .with_schema(&template)?
+    ///     .build()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn with_schema(mut self, schema: &Schema) -> Result<Self> {
+        self = self.add_nvt(schema.nvt.iter().map(|(k, v)| (k.as_str(), v.as_str())))?;
+
+        for mtype in &schema.matrix_types {
+            let columns: Vec<&str> = mtype.columns.iter().map(|s| s.as_str()).collect();
+            self = self.add_matrix_type(&mtype.signature, &columns)?;
+        }
+
+        for ftype in &schema.frame_types {
+            let components: Vec<String> = ftype
+                .components
+                .iter()
+                .map(|c| format!("{} {}", c.signature, c.name))
+                .collect();
+            let components: Vec<&str> = components.iter().map(|s| s.as_str()).collect();
+            self = self.add_frame_type(&ftype.signature, &components)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Set the data type written for matrices with a given signature.
+    ///
+    /// The convenience methods that take `f64` data,
+    /// [`SdifWriter::write_frame_one_matrix()`] and
+    /// [`FrameBuilder::add_matrix()`](crate::FrameBuilder::add_matrix)
+    /// (and their ndarray variants), convert the data to this type. This
+    /// makes mixed-precision files, such as f32 spectra next to f64
+    /// markers, possible without calling the `_f32` methods everywhere.
+    /// The explicit `_f32` methods are not affected.
+    ///
+    /// Matrices without a default are written as `Float8`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if the signature is invalid
+    /// - [`Error::DataTypeMismatch`] if `data_type` is not `Float4` or `Float8`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::{DataType, SdifFile};
+    ///
+    /// let mut writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .default_data_type("1STF", DataType::Float4)?
+    ///     .build()?;
+    ///
+    /// // Written as Float4
+    /// writer.write_frame_one_matrix("1STF", 0.0, "1STF", 2, 1, &[0.5, 0.25])?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn default_data_type(mut self, signature: &str, data_type: DataType) -> Result<Self> {
+        let sig = string_to_signature(signature)?;
+
+        if !matches!(data_type, DataType::Float4 | DataType::Float8) {
+            return Err(Error::type_mismatch("float32 or float64", data_type.to_string()));
+        }
+
+        self.config.data_types.insert(sig, data_type);
+        Ok(self)
+    }
+
+    /// Enable deterministic output.
+    ///
+    /// Writing the same frames with the same configuration then produces
+    /// byte-identical files, which keeps diffs and build caches stable.
+    /// NVT entries are always written in insertion order; in deterministic
+    /// mode the builder additionally never adds metadata that varies between
+    /// runs, such as creation timestamps or the writing library's version.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .deterministic()
+    ///     .add_nvt([("creator", "my-app")])?
+    ///     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    ///     .add_frame_type("1FQ0", &["1FQ0 Pitch"])?
+    ///     .build()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn deterministic(mut self) -> Self {
+        self.config.deterministic = true;
+        self
+    }
+
+    /// Store matrix data in column-major order.
+    ///
+    /// SDIF matrices are row-major: the values of row 0 come first, then
+    /// row 1, and so on. Some consumers (notably certain MATLAB SDIF
+    /// readers) fill matrices column by column instead. With this option
+    /// the writer keeps the matrix header as given (`rows` x `cols`) but
+    /// stores the values column by column, so those consumers see the
+    /// matrix that was written. Data is still passed to the writer in
+    /// row-major order; it is reordered on write.
+    ///
+    /// Readers that follow the standard see the values in the wrong
+    /// order. Read such files back with
+    /// [`MatrixData::interpret_column_major()`](crate::MatrixData::interpret_column_major).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let mut writer = SdifFile::builder()
+    ///     .create("for-matlab.sdif")?
+    ///     .column_major()
+    ///     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    ///     .add_frame_type("1FQ0", &["1FQ0 Pitch"])?
+    ///     .build()?;
+    ///
+    /// // Stored as [220.0, 221.0, 0.9, 0.8]
+    /// writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 2, 2, &[220.0, 0.9, 221.0, 0.8])?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn column_major(mut self) -> Self {
+        self.config.column_major = true;
+        self
+    }
+
+    /// Preallocate space for the frames that will be written.
+    ///
+    /// Writing a multi-gigabyte file in small appends can leave it badly
+    /// fragmented. With this option the file is grown to its estimated
+    /// final size up front, assuming `frames` frames of one matrix with
+    /// `avg_matrix_bytes` bytes of data each, and truncated to the size
+    /// actually written when the writer is closed or dropped.
+    ///
+    /// Preallocation is best effort: where the platform or filesystem
+    /// doesn't support it, the file is written as usual.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// // About 10 minutes of 1024-bin spectra at a 10 ms hop
+    /// let writer = SdifFile::builder()
+    ///     .create("spectra.sdif")?
+    ///     .reserve_frames(60_000, 1024 * 2 * 4)
+    ///     .add_matrix_type("1STF", &["Real", "Imaginary"])?
+    ///     .add_frame_type("1STF", &["1STF Spectrum"])?
+    ///     .build()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn reserve_frames(mut self, frames: usize, avg_matrix_bytes: usize) -> Self {
+        self.config.reserve_bytes = Some(estimate_frame_bytes(frames, avg_matrix_bytes));
+        self
+    }
+
     /// Finalize configuration and create the writer.
     ///
     /// This opens the file, writes the general header and ASCII chunks
@@ -318,7 +994,9 @@ impl SdifFileBuilder<Config> {
     ///
     /// # Errors
     ///
+    /// - [`Error::LibraryUnavailable`] if the SDIF library couldn't be loaded
     /// - [`Error::InitFailed`] if the SDIF library couldn't be initialized
+    /// - [`Error::InitPoisoned`] if the SDIF library couldn't load its types
     /// - [`Error::OpenFailed`] if the file couldn't be created
     /// - [`Error::Io`] if writing headers fails
     ///
@@ -336,11 +1014,13 @@ impl SdifFileBuilder<Config> {
     /// // writer is ready to write frames
     /// # Ok::<(), sdif_rs::Error>(())
     /// ```
-    pub fn build(self) -> Result<SdifWriter> {
+    pub fn build(mut self) -> Result<SdifWriter> {
         // Ensure library is initialized
-        if !ensure_initialized() {
-            return Err(Error::InitFailed);
-        }
+        initialize()?;
+
+        self.apply_writer_stamp();
+        #[cfg(feature = "chrono")]
+        self.apply_date_stamp(std::time::SystemTime::now().into());
 
         let path = self.path.as_ref().expect("Path should be set in Config state");
 
@@ -382,7 +1062,87 @@ impl SdifFileBuilder<Config> {
             )));
         }
 
-        Ok(SdifWriter::new(handle, path.clone()))
+        let mut writer = SdifWriter::new(
+            handle,
+            path.clone(),
+            self.config.data_types.clone(),
+            self.config.column_major,
+            self.stream_ids(),
+        );
+        if let Some(bytes) = self.config.reserve_bytes {
+            writer.preallocate(bytes);
+        }
+        if self.config.temporary {
+            writer.remove_on_drop();
+        }
+        Ok(writer)
+    }
+
+    /// Set a conventional metadata key in the builder's metadata table.
+    fn set_metadata(mut self, key: &str, value: &str) -> Result<Self> {
+        if value.contains('\0') {
+            return Err(Error::invalid_format("NVT key/value cannot contain null bytes"));
+        }
+
+        self.metadata_table().insert(key.to_string(), value.to_string());
+        Ok(self)
+    }
+
+    /// Get the builder's metadata table, adding it if needed.
+    fn metadata_table(&mut self) -> &mut IndexMap<String, String> {
+        let tables = &mut self.config.nvts.tables;
+        let table = *self.config.metadata_table.get_or_insert_with(|| {
+            tables.push(IndexMap::new());
+            tables.len() - 1
+        });
+        &mut tables[table]
+    }
+
+    /// Record this crate and its version as the file's writer, unless
+    /// disabled with [`omit_writer()`](Self::omit_writer).
+    ///
+    /// A writer copied from another file, for example by
+    /// [`crate::copy`], is replaced in place rather than repeated.
+    fn apply_writer_stamp(&mut self) {
+        if self.config.omit_writer || self.config.deterministic {
+            return;
+        }
+        let writer = metadata::format_writer();
+        match self.existing_entry(metadata::WRITER) {
+            Some(existing) => *existing = writer,
+            None => {
+                self.metadata_table()
+                    .insert(metadata::WRITER.to_string(), writer);
+            }
+        }
+    }
+
+    /// Store `now` as the file's date if requested with
+    /// [`stamp_date()`](Self::stamp_date).
+    #[cfg(feature = "chrono")]
+    fn apply_date_stamp(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        if !self.config.stamp_date || self.config.deterministic {
+            return;
+        }
+        if self.existing_entry(metadata::DATE).is_some() {
+            return;
+        }
+        self.metadata_table()
+            .insert(metadata::DATE.to_string(), metadata::format_date(now));
+    }
+
+    /// Find an entry already in one of the NVTs.
+    fn existing_entry(&mut self, key: &str) -> Option<&mut String> {
+        self.config
+            .nvts
+            .tables
+            .iter_mut()
+            .find_map(|table| table.get_mut(key))
+    }
+
+    /// Get the named streams declared in the NVTs so far.
+    fn stream_ids(&self) -> HashMap<String, u32> {
+        streams::from_entries(self.config.nvts.tables.iter().flatten())
     }
 
     /// Write NVT and type definitions to the file handle.
@@ -409,7 +1169,7 @@ impl SdifFileBuilder<Config> {
     }
 
     /// Add a single NVT to the file.
-    fn add_nvt_to_file(handle: *mut SdifFileT, nvt: &HashMap<String, String>) -> Result<()> {
+    fn add_nvt_to_file(handle: *mut SdifFileT, nvt: &IndexMap<String, String>) -> Result<()> {
         use sdif_sys::{SdifFNameValueList, SdifNameValuesLNewTable, SdifNameValuesLPutCurrNVT};
 
         unsafe {
@@ -520,6 +1280,13 @@ impl SdifFileBuilder<Config> {
     }
 }
 
+/// Estimate the size of `frames` frames holding one matrix each.
+fn estimate_frame_bytes(frames: usize, avg_matrix_bytes: usize) -> u64 {
+    // Frame header (24) and matrix header (16), with data padded to 8 bytes
+    let padded = (avg_matrix_bytes as u64 + 7) / 8 * 8;
+    frames as u64 * (24 + 16 + padded)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -559,4 +1326,208 @@ mod tests {
         let result = builder.add_matrix_type("1TRC", &[]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_nvt_preserves_insertion_order() {
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap()
+            .add_nvt([("zeta", "1"), ("alpha", "2"), ("mid", "3")])
+            .unwrap();
+
+        let keys: Vec<_> = builder.config.nvts.tables[0].keys().cloned().collect();
+        assert_eq!(keys, ["zeta", "alpha", "mid"]);
+    }
+
+    #[test]
+    fn test_append_history_numbers_steps() {
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap()
+            .add_nvt([("history.4.tool", "upstream")])
+            .unwrap()
+            .append_history("resampler", "0.3", [("rate", "100")])
+            .unwrap();
+
+        let table = builder.config.nvts.tables.last().unwrap();
+        assert_eq!(table["history.5.tool"], "resampler");
+        assert_eq!(table["history.5.version"], "0.3");
+        assert_eq!(table["history.5.param.rate"], "100");
+
+        let builder = builder.append_history("", "1.0", []);
+        assert!(matches!(builder, Err(Error::InvalidFormat { .. })));
+    }
+
+    #[test]
+    fn test_named_stream_ids() {
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap()
+            .add_nvt([("stream.partials", "3")])
+            .unwrap()
+            .named_stream("f0")
+            .unwrap()
+            .add_nvt([("creator", "tests")])
+            .unwrap()
+            .named_stream("noise")
+            .unwrap()
+            .named_stream("partials")
+            .unwrap();
+
+        let streams = builder.stream_ids();
+        assert_eq!(streams.len(), 3);
+        assert_eq!(streams["partials"], 3);
+        assert_eq!(streams["f0"], 4);
+        assert_eq!(streams["noise"], 5);
+
+        // New names share one table
+        let table = &builder.config.nvts.tables[1];
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_metadata_setters() {
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap()
+            .creator("tests")
+            .unwrap()
+            .add_nvt([("history.0.tool", "tests")])
+            .unwrap()
+            .sample_rate(44100.0)
+            .unwrap()
+            .date("2024-03-01T12:00:00Z")
+            .unwrap()
+            .creator("tests 2")
+            .unwrap();
+
+        // Metadata keys share one table, and setting a key twice replaces it
+        let table = &builder.config.nvts.tables[0];
+        assert_eq!(table.len(), 3);
+        assert_eq!(table[metadata::CREATOR], "tests 2");
+        assert_eq!(table[metadata::SAMPLE_RATE], "44100");
+        assert_eq!(table[metadata::DATE], "2024-03-01T12:00:00Z");
+
+        let builder = SdifFileBuilder::<New>::new().create("/tmp/test.sdif").unwrap();
+        assert!(builder.sample_rate(0.0).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_date_stamp() {
+        use chrono::{TimeZone, Utc};
+
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let builder = || SdifFileBuilder::<New>::new().create("/tmp/test.sdif").unwrap();
+
+        // Only stamped on request
+        let mut plain = builder();
+        plain.apply_date_stamp(now);
+        assert!(plain.config.nvts.tables.is_empty());
+
+        let mut stamped = builder().stamp_date();
+        stamped.apply_date_stamp(now);
+        assert_eq!(
+            stamped.config.nvts.tables[0][metadata::DATE],
+            "2024-03-01T12:00:00Z"
+        );
+
+        // An explicit date wins
+        let mut dated = builder().date("1999-12-31").unwrap().stamp_date();
+        dated.apply_date_stamp(now);
+        assert_eq!(dated.config.nvts.tables[0][metadata::DATE], "1999-12-31");
+
+        // Deterministic output has no timestamp
+        let mut deterministic = builder().stamp_date().deterministic();
+        deterministic.apply_date_stamp(now);
+        assert!(deterministic.config.nvts.tables.is_empty());
+    }
+
+    #[test]
+    fn test_set_source_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voice.raw");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap()
+            .set_source_audio(&path)
+            .unwrap();
+
+        let table = &builder.config.nvts.tables[0];
+        assert_eq!(table[metadata::SOUNDFILE], "voice.raw");
+        assert_eq!(table["source.file"], "voice.raw");
+        assert_eq!(table["source.sha256"].len(), 64);
+
+        let builder = SdifFileBuilder::<New>::new().create("/tmp/test.sdif").unwrap();
+        assert!(builder.set_source_audio(dir.path().join("missing.wav")).is_err());
+    }
+
+    #[test]
+    fn test_with_schema() {
+        use crate::schema::{ComponentSchema, FrameTypeSchema, MatrixTypeSchema};
+
+        let schema = Schema {
+            nvt: [("creator".to_string(), "tests".to_string())].into_iter().collect(),
+            matrix_types: vec![MatrixTypeSchema {
+                signature: "XAMP".to_string(),
+                columns: vec!["Gain".to_string()],
+            }],
+            frame_types: vec![FrameTypeSchema {
+                signature: "XAMP".to_string(),
+                components: vec![ComponentSchema {
+                    signature: "XAMP".to_string(),
+                    name: "Levels".to_string(),
+                }],
+            }],
+        };
+
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap()
+            .with_schema(&schema)
+            .unwrap();
+
+        assert_eq!(builder.config.nvts.tables[0]["creator"], "tests");
+        assert_eq!(builder.config.matrix_types[0].column_names, ["Gain"]);
+        assert_eq!(builder.config.frame_types[0].components, ["XAMP Levels"]);
+    }
+
+    #[test]
+    fn test_estimate_frame_bytes() {
+        assert_eq!(estimate_frame_bytes(0, 4096), 0);
+        assert_eq!(estimate_frame_bytes(1, 8), 48);
+        // 12 data bytes are padded to 16
+        assert_eq!(estimate_frame_bytes(10, 12), 560);
+    }
+
+    #[test]
+    fn test_writer_stamp() {
+        let builder = || SdifFileBuilder::<New>::new().create("/tmp/test.sdif").unwrap();
+
+        let mut stamped = builder();
+        stamped.apply_writer_stamp();
+        assert_eq!(
+            metadata::parse_writer(&stamped.config.nvts.tables[0][metadata::WRITER]),
+            Some(metadata::Version::current())
+        );
+
+        let mut omitted = builder().omit_writer();
+        omitted.apply_writer_stamp();
+        assert!(omitted.config.nvts.tables.is_empty());
+
+        let mut deterministic = builder().deterministic();
+        deterministic.apply_writer_stamp();
+        assert!(deterministic.config.nvts.tables.is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_flag() {
+        let builder = SdifFileBuilder::<New>::new()
+            .create("/tmp/test.sdif")
+            .unwrap();
+        assert!(!builder.config.deterministic);
+        assert!(builder.deterministic().config.deterministic);
+    }
 }
@@ -0,0 +1,166 @@
+//! Units and descriptions of matrix columns, recorded in NVT entries.
+//!
+//! SDIF type declarations only name their columns. Custom descriptor
+//! files can say what the values mean with one NVT entry per annotation,
+//!
+//! ```text
+//! column.<signature>.<name>.unit           unit of the values, e.g. Hz
+//! column.<signature>.<name>.description    free text
+//! ```
+//!
+//! declared with
+//! [`SdifFileBuilder::add_matrix_type_with_defs()`](crate::SdifFileBuilder::add_matrix_type_with_defs)
+//! and read back with [`SdifFile::column_defs()`](crate::SdifFile::column_defs).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::columns::ColumnDef;
+//! use sdif_rs::SdifFile;
+//!
+//! let writer = SdifFile::builder()
+//!     .create("descriptors.sdif")?
+//!     .add_matrix_type_with_defs(
+//!         "XDSC",
+//!         &[
+//!             ColumnDef::new("Centroid").unit("Hz"),
+//!             ColumnDef::new("Flatness").description("Geometric over arithmetic mean"),
+//!         ],
+//!     )?
+//!     .add_frame_type("XDSC", &["XDSC Descriptors"])?
+//!     .build()?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+
+/// Prefix of all column annotation NVT keys.
+const PREFIX: &str = "column.";
+
+/// A matrix column with its optional unit and description.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnDef {
+    /// Column name.
+    pub name: String,
+
+    /// Unit of the values, if recorded.
+    pub unit: Option<String>,
+
+    /// Description of the values, if recorded.
+    pub description: Option<String>,
+}
+
+impl ColumnDef {
+    /// Create a column without unit or description.
+    pub fn new(name: impl Into<String>) -> Self {
+        ColumnDef {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the unit of the values.
+    pub fn unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Set the description of the values.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Convert the unit and description to NVT entries.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the column is annotated and its name
+    ///   contains whitespace, which can't be stored in an NVT key
+    pub(crate) fn to_nvt_entries(&self, signature: &str) -> Result<Vec<(String, String)>> {
+        let entries: Vec<_> = [("unit", &self.unit), ("description", &self.description)]
+            .into_iter()
+            .filter_map(|(field, value)| Some((key(signature, &self.name, field), value.clone()?)))
+            .collect();
+
+        if !entries.is_empty() && self.name.chars().any(char::is_whitespace) {
+            return Err(Error::invalid_format(format!(
+                "Annotated column names cannot contain whitespace: {:?}",
+                self.name
+            )));
+        }
+        Ok(entries)
+    }
+}
+
+impl From<&str> for ColumnDef {
+    fn from(name: &str) -> Self {
+        ColumnDef::new(name)
+    }
+}
+
+/// Get the NVT key of annotation `field` of a column.
+fn key(signature: &str, column: &str, field: &str) -> String {
+    format!("{}{}.{}.{}", PREFIX, signature, column, field)
+}
+
+/// Annotate the columns of matrix type `signature` from a file's NVTs.
+pub(crate) fn from_nvts(
+    nvts: &[HashMap<String, String>],
+    signature: &str,
+    columns: Vec<String>,
+) -> Vec<ColumnDef> {
+    let get = |column: &str, field: &str| {
+        let key = key(signature, column, field);
+        nvts.iter().find_map(|nvt| nvt.get(&key)).cloned()
+    };
+
+    columns
+        .into_iter()
+        .map(|name| ColumnDef {
+            unit: get(&name, "unit"),
+            description: get(&name, "description"),
+            name,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nvt_round_trip() {
+        let columns = [
+            ColumnDef::new("Centroid")
+                .unit("Hz")
+                .description("Spectral centroid"),
+            ColumnDef::new("Flatness"),
+        ];
+
+        let nvt: HashMap<_, _> = columns
+            .iter()
+            .flat_map(|c| c.to_nvt_entries("XDSC").unwrap())
+            .collect();
+        assert_eq!(nvt.len(), 2);
+        assert_eq!(nvt["column.XDSC.Centroid.unit"], "Hz");
+
+        let names = vec!["Centroid".to_string(), "Flatness".to_string()];
+        assert_eq!(from_nvts(&[nvt], "XDSC", names), columns);
+    }
+
+    #[test]
+    fn test_annotated_names_without_whitespace() {
+        assert!(ColumnDef::new("Two words")
+            .to_nvt_entries("XDSC")
+            .unwrap()
+            .is_empty());
+        assert!(ColumnDef::new("Two words")
+            .unit("s")
+            .to_nvt_entries("XDSC")
+            .is_err());
+    }
+}
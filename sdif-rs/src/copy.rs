@@ -0,0 +1,526 @@
+//! Copying SDIF files frame by frame, with limits on the output size.
+//!
+//! [`copy_file()`] reads every frame of an input file and writes it to a
+//! new file that carries the same NVTs and type declarations. A
+//! [`CopyOptions`] budget on the number of frames or bytes turns the copy
+//! into a truncation, which is how oversized analyses are cut down to fit
+//! devices with limited storage (e.g., sample content for hardware synths).
+//!
+//! Frames are copied whole and in order: the copy stops before the first
+//! frame that would exceed a limit, so the output is always a valid SDIF
+//! file holding a prefix of the input.
+//!
+//! With [`CopyOptions::time_range()`], only the frames in a time range are
+//! copied, which extracts a section of an analysis.
+//!
+//! With [`CopyOptions::fill_gaps()`], frames missing from a stream (as
+//! reported by [`find_gaps()`](crate::analysis::find_gaps)) are filled in
+//! with empty or interpolated frames, so downstream tools that expect a
+//! constant hop see one. Filled frames count toward the limits, and a gap
+//! of more than [`MAX_GAP_FILLS`] frames, usually a corrupt time, is
+//! reported as an error rather than filled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::copy::{self, CopyOptions};
+//!
+//! let options = CopyOptions::new().max_bytes(64 * 1024);
+//! let summary = copy::copy_file("analysis.sdif", "analysis-small.sdif", &options)?;
+//!
+//! println!("Kept {} frames ({} bytes), dropped {}",
+//!     summary.frames_copied,
+//!     summary.bytes_written,
+//!     summary.frames_dropped
+//! );
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::analysis::{self, Gap, GapKind};
+use crate::builder::{Config, SdifFileBuilder};
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame_data::{FrameData, MatrixData};
+use crate::metrics::{self, ConversionTimer};
+use crate::schema::Schema;
+
+/// Largest number of frames filled into a single gap.
+pub const MAX_GAP_FILLS: usize = 1_000_000;
+
+/// How missing frames are filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FillMode {
+    /// Matrices with the same signatures and columns as the frame after
+    /// the gap, but no rows.
+    Empty,
+
+    /// Values interpolated linearly between the frames around the gap.
+    ///
+    /// Falls back to [`FillMode::Empty`] for matrices whose shape differs
+    /// on either side of the gap.
+    Interpolate,
+}
+
+/// Gap filling applied while copying.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GapFill {
+    /// Expected time between frames of a stream, in seconds.
+    pub hop: f64,
+
+    /// How to fill the missing frames.
+    pub mode: FillMode,
+}
+
+/// Limits, time range and gap filling applied while copying frames.
+///
+/// All limits are off by default, which copies the whole file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CopyOptions {
+    /// Maximum number of frames to copy.
+    pub max_frames: Option<usize>,
+
+    /// Maximum size of the output file in bytes, including its header.
+    pub max_bytes: Option<u64>,
+
+    /// Fill in missing frames.
+    pub fill_gaps: Option<GapFill>,
+
+    /// Only copy frames whose time is in this range, in seconds.
+    pub time_range: Option<Range<f64>>,
+}
+
+impl CopyOptions {
+    /// Create options that copy everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy at most `frames` frames.
+    pub fn max_frames(mut self, frames: usize) -> Self {
+        self.max_frames = Some(frames);
+        self
+    }
+
+    /// Keep the output file at or below `bytes` bytes.
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Fill in frames missing at the expected `hop`.
+    pub fn fill_gaps(mut self, hop: f64, mode: FillMode) -> Self {
+        self.fill_gaps = Some(GapFill { hop, mode });
+        self
+    }
+
+    /// Only copy frames whose time is in `range`. Filled frames outside
+    /// the range are left out too.
+    pub fn time_range(mut self, range: Range<f64>) -> Self {
+        self.time_range = Some(range);
+        self
+    }
+
+    /// Check whether a frame at `time` is in the time range.
+    fn in_range(&self, time: f64) -> bool {
+        self.time_range
+            .as_ref()
+            .map_or(true, |range| range.contains(&time))
+    }
+}
+
+/// Outcome of a copy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CopySummary {
+    /// Number of frames written to the output, not counting filled frames.
+    pub frames_copied: usize,
+
+    /// Number of frames written to fill gaps.
+    pub frames_filled: usize,
+
+    /// Number of input frames left out because a limit was reached.
+    pub frames_dropped: usize,
+
+    /// Number of input frames without matrices, which can't be written.
+    pub frames_skipped: usize,
+
+    /// Number of input frames outside the time range.
+    pub frames_out_of_range: usize,
+
+    /// Size of the output file in bytes.
+    pub bytes_written: u64,
+}
+
+impl CopySummary {
+    /// Check whether a limit cut the copy short.
+    pub fn is_truncated(&self) -> bool {
+        self.frames_dropped > 0
+    }
+}
+
+/// Copy an SDIF file, stopping at the limits in `options`.
+///
+/// The output gets the input's NVTs and type declarations, followed by
+/// as many whole frames as fit within the limits. When gap filling is
+/// enabled, the input is read twice: once to find the gaps and once to
+/// copy it.
+///
+/// # Errors
+///
+/// - Any error from opening or reading the input
+/// - Any error from creating or writing the output
+/// - [`Error::DataTypeMismatch`](crate::Error::DataTypeMismatch) if the input has
+///   matrices that are not `Float4` or `Float8`
+/// - [`Error::InvalidFormat`](crate::Error::InvalidFormat) if the gap fill hop
+///   is not positive, or a gap misses more than [`MAX_GAP_FILLS`] frames
+pub fn copy_file(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &CopyOptions,
+) -> Result<CopySummary> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_COPY);
+    let input = input.as_ref();
+    let mut fills = match &options.fill_gaps {
+        Some(fill) => plan_fills(&SdifFile::open(input)?, fill)?,
+        None => Fills::default(),
+    };
+
+    let file = SdifFile::open(input)?;
+    let schema = file.describe_types()?;
+    let mut writer = copy_header(&file, &schema, SdifFile::builder().create(output)?)?.build()?;
+
+    let mut summary = CopySummary::default();
+    let mut position = writer.position()?;
+    let mut full = false;
+
+    // Last frame of each (stream ID, signature) series, for interpolation
+    let mut previous: HashMap<(u32, String), FrameData> = HashMap::new();
+
+    for frame in file.frames() {
+        let frame = frame?;
+
+        if !options.in_range(frame.time()) {
+            summary.frames_out_of_range += 1;
+            continue;
+        }
+        if full {
+            summary.frames_dropped += 1;
+            continue;
+        }
+
+        let frame = frame.into_data()?;
+        if frame.matrices.is_empty() {
+            summary.frames_skipped += 1;
+            continue;
+        }
+
+        // Fills are built one at a time, so the limits stop them too
+        while let Some((gap, number)) = fills.next_until(frame.time) {
+            if !options.in_range(gap.time(number)) {
+                continue;
+            }
+            let key = (gap.end.stream_id, gap.end.signature.clone());
+            let filled = gap.frame(number, previous.get(&key));
+            let frame_bytes = filled.byte_size();
+
+            let over_frames = options
+                .max_frames
+                .is_some_and(|max| summary.frames_copied + summary.frames_filled >= max);
+            let over_bytes = options
+                .max_bytes
+                .is_some_and(|max| position + frame_bytes > max);
+            if over_frames || over_bytes {
+                full = true;
+                break;
+            }
+
+            writer.write_frame_data(&filled)?;
+            position += frame_bytes;
+            summary.frames_filled += 1;
+        }
+
+        if full
+            || options
+                .max_frames
+                .is_some_and(|max| summary.frames_copied + summary.frames_filled >= max)
+        {
+            full = true;
+            summary.frames_dropped += 1;
+            continue;
+        }
+
+        let frame_bytes = frame.byte_size();
+        if options
+            .max_bytes
+            .is_some_and(|max| position + frame_bytes > max)
+        {
+            full = true;
+            summary.frames_dropped += 1;
+            continue;
+        }
+
+        writer.write_frame_data(&frame)?;
+        position += frame_bytes;
+        summary.frames_copied += 1;
+        previous.insert((frame.stream_id, frame.signature.clone()), frame);
+    }
+
+    summary.bytes_written = writer.position()?;
+    writer.close()?;
+
+    timer.finish();
+    Ok(summary)
+}
+
+/// Frames to be inserted into a gap.
+struct PlannedGap {
+    /// Time of the frame before the gap.
+    start: f64,
+
+    /// Time between the filled frames.
+    step: f64,
+
+    /// Number of frames to fill.
+    frames: usize,
+
+    /// Frame after the gap.
+    end: FrameData,
+
+    mode: FillMode,
+}
+
+impl PlannedGap {
+    /// Get the time of filled frame `number`, counted from 1.
+    fn time(&self, number: usize) -> f64 {
+        self.start + self.step * number as f64
+    }
+
+    /// Build filled frame `number`, given the frame before the gap.
+    fn frame(&self, number: usize, start: Option<&FrameData>) -> FrameData {
+        let fraction = number as f64 / (self.frames + 1) as f64;
+        let mut frame = FrameData::new(
+            self.end.signature.clone(),
+            self.time(number),
+            self.end.stream_id,
+        );
+
+        frame.matrices = self
+            .end
+            .matrices
+            .iter()
+            .map(|end| {
+                let start = match self.mode {
+                    FillMode::Interpolate => start.and_then(|s| s.matrix(&end.signature)),
+                    FillMode::Empty => None,
+                };
+                match start {
+                    Some(start) if start.rows == end.rows && start.cols == end.cols => {
+                        interpolate(start, end, fraction)
+                    }
+                    _ => MatrixData {
+                        rows: 0,
+                        data: Vec::new(),
+                        ..end.clone()
+                    },
+                }
+            })
+            .collect();
+
+        frame
+    }
+}
+
+/// The gaps of a file, handing out their filled frames in time order.
+#[derive(Default)]
+struct Fills {
+    /// Gaps ordered by the time of their first filled frame.
+    gaps: Vec<PlannedGap>,
+
+    /// Number of gaps whose frames have started.
+    started: usize,
+
+    /// Started gaps with frames left, and the number of their next frame.
+    active: Vec<(usize, usize)>,
+}
+
+impl Fills {
+    /// Get the next filled frame at or before `time`, as its gap and number.
+    fn next_until(&mut self, time: f64) -> Option<(&PlannedGap, usize)> {
+        while self.started < self.gaps.len() && self.gaps[self.started].time(1) <= time {
+            self.active.push((self.started, 1));
+            self.started += 1;
+        }
+
+        let gaps = &self.gaps;
+        let slot = self
+            .active
+            .iter()
+            .enumerate()
+            .map(|(slot, &(gap, number))| (slot, gaps[gap].time(number)))
+            .filter(|&(_, fill_time)| fill_time <= time)
+            .min_by(|a, b| a.1.total_cmp(&b.1))?
+            .0;
+
+        let (gap, number) = self.active[slot];
+        if number == gaps[gap].frames {
+            self.active.remove(slot);
+        } else {
+            self.active[slot].1 += 1;
+        }
+        Some((&gaps[gap], number))
+    }
+}
+
+/// Interpolate linearly between two matrices of the same shape.
+fn interpolate(start: &MatrixData, end: &MatrixData, fraction: f64) -> MatrixData {
+    let data = start
+        .data
+        .iter()
+        .zip(&end.data)
+        .map(|(a, b)| a + (b - a) * fraction)
+        .collect();
+
+    MatrixData {
+        data,
+        ..end.clone()
+    }
+}
+
+/// Find the gaps of `file` to fill.
+fn plan_fills(file: &SdifFile, fill: &GapFill) -> Result<Fills> {
+    let mut gaps = Vec::new();
+
+    analysis::scan_gaps(file, fill.hop, |gap: Gap, frame| {
+        let GapKind::Missing { frames } = gap.kind else {
+            return Ok(());
+        };
+        if frames > MAX_GAP_FILLS {
+            return Err(Error::invalid_format(format!(
+                "{} stream {} misses {} frames between {}s and {}s, more than the {} that can be filled",
+                gap.signature, gap.stream_id, frames, gap.start, gap.end, MAX_GAP_FILLS
+            )));
+        }
+
+        let end = frame.into_data()?;
+        if end.matrices.is_empty() {
+            return Ok(());
+        }
+
+        // Spread the frames evenly over the gap, which may not be an exact
+        // multiple of the hop
+        gaps.push(PlannedGap {
+            start: gap.start,
+            step: gap.duration() / (frames + 1) as f64,
+            frames,
+            end,
+            mode: fill.mode,
+        });
+        Ok(())
+    })?;
+
+    gaps.sort_by(|a, b| a.time(1).total_cmp(&b.time(1)));
+    Ok(Fills {
+        gaps,
+        ..Fills::default()
+    })
+}
+
+/// Configure a builder with the NVTs of `file` and the types in `schema`.
+pub(crate) fn copy_header(
+    file: &SdifFile,
+    schema: &Schema,
+    mut builder: SdifFileBuilder<Config>,
+) -> Result<SdifFileBuilder<Config>> {
+    for nvt in file.nvts() {
+        // HashMap order varies between runs; sort for stable output
+        let mut entries: Vec<(&str, &str)> =
+            nvt.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        entries.sort();
+        builder = builder.add_nvt(entries)?;
+    }
+
+    builder.with_schema(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_builder() {
+        let options = CopyOptions::new()
+            .max_frames(10)
+            .max_bytes(4096)
+            .fill_gaps(0.01, FillMode::Empty)
+            .time_range(1.0..2.0);
+        assert_eq!(options.max_frames, Some(10));
+        assert!(options.in_range(1.0) && !options.in_range(2.0));
+        assert_eq!(options.max_bytes, Some(4096));
+        assert_eq!(options.fill_gaps.unwrap().mode, FillMode::Empty);
+        assert_eq!(CopyOptions::new(), CopyOptions::default());
+    }
+
+    #[test]
+    fn test_summary_truncated() {
+        let mut summary = CopySummary::default();
+        assert!(!summary.is_truncated());
+        summary.frames_dropped = 1;
+        assert!(summary.is_truncated());
+    }
+
+    #[test]
+    fn test_planned_fill_frame() {
+        let mut start = FrameData::new("1FQ0", 0.0, 0);
+        start.matrices.push(MatrixData::new("1FQ0", 1, 2, vec![100.0, 0.5]));
+        let mut end = FrameData::new("1FQ0", 0.04, 0);
+        end.matrices.push(MatrixData::new("1FQ0", 1, 2, vec![200.0, 1.0]));
+
+        let mut gap = PlannedGap {
+            start: 0.0,
+            step: 0.01,
+            frames: 3,
+            end,
+            mode: FillMode::Interpolate,
+        };
+
+        let frame = gap.frame(1, Some(&start));
+        assert_eq!(frame.time, 0.01);
+        assert_eq!(frame.matrices[0].data, vec![125.0, 0.625]);
+
+        // Without a frame before the gap, interpolation falls back to empty
+        let frame = gap.frame(1, None);
+        assert_eq!((frame.matrices[0].rows, frame.matrices[0].cols), (0, 2));
+
+        gap.mode = FillMode::Empty;
+        let frame = gap.frame(1, Some(&start));
+        assert!(frame.matrices[0].data.is_empty());
+    }
+
+    #[test]
+    fn test_fills_in_time_order() {
+        let gap = |stream_id, start: f64, frames| PlannedGap {
+            start,
+            step: 0.01,
+            frames,
+            end: FrameData::new("1FQ0", start + 0.01 * (frames + 1) as f64, stream_id),
+            mode: FillMode::Empty,
+        };
+        let mut fills = Fills {
+            gaps: vec![gap(0, 0.0, 3), gap(1, 0.015, 1)],
+            ..Fills::default()
+        };
+
+        let mut order = Vec::new();
+        while let Some((gap, number)) = fills.next_until(0.1) {
+            order.push((gap.end.stream_id, number));
+        }
+        assert_eq!(order, [(0, 1), (0, 2), (1, 1), (0, 3)]);
+        assert!(fills.next_until(1.0).is_none());
+    }
+}
@@ -0,0 +1,494 @@
+//! Cross-file statistics for collections of SDIF files.
+//!
+//! Researchers often manage hundreds of analysis files produced by
+//! different tools and settings. [`summarize()`] scans each file once and
+//! produces a [`CorpusSummary`] with per-file statistics (duration, frame
+//! rate, partial counts, pitch range) and corpus-wide aggregates.
+//!
+//! Files that fail to open or read are recorded in
+//! [`CorpusSummary::failures`] instead of aborting the whole scan.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::corpus;
+//!
+//! let summary = corpus::summarize(["a.sdif", "b.sdif", "c.sdif"]);
+//!
+//! println!("{} files, {:.1}s total",
+//!     summary.aggregate.num_files,
+//!     summary.aggregate.total_duration
+//! );
+//!
+//! for failure in &summary.failures {
+//!     eprintln!("{}: {}", failure.path.display(), failure.message);
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::data_type::DataType;
+use crate::error::Result;
+use crate::file::SdifFile;
+use crate::signature::Signature;
+
+/// Frame/matrix signatures whose rows are counted as partials.
+const PARTIAL_SIGNATURES: &[&str] = &["1TRC", "1HRM"];
+
+/// Frame/matrix signature holding fundamental frequency estimates.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Statistics about the number of partials (rows) per matrix.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PartialStats {
+    /// Smallest number of partials in a single matrix.
+    pub min: usize,
+
+    /// Largest number of partials in a single matrix.
+    pub max: usize,
+
+    /// Mean number of partials per matrix.
+    pub mean: f64,
+
+    /// Number of matrices the statistics were computed from.
+    pub matrices: usize,
+}
+
+impl PartialStats {
+    /// Combine two sets of statistics, weighting the mean by matrix count.
+    fn merge(&self, other: &PartialStats) -> PartialStats {
+        let matrices = self.matrices + other.matrices;
+        let mean = if matrices == 0 {
+            0.0
+        } else {
+            (self.mean * self.matrices as f64 + other.mean * other.matrices as f64)
+                / matrices as f64
+        };
+
+        PartialStats {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            mean,
+            matrices,
+        }
+    }
+}
+
+/// A frequency range in Hz.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PitchRange {
+    /// Lowest frequency in Hz.
+    pub min: f64,
+
+    /// Highest frequency in Hz.
+    pub max: f64,
+}
+
+impl PitchRange {
+    /// Widen the range to include another range.
+    fn merge(&self, other: &PitchRange) -> PitchRange {
+        PitchRange {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+/// Statistics for a single SDIF file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FileSummary {
+    /// Path of the summarized file.
+    pub path: PathBuf,
+
+    /// Total number of frames.
+    pub num_frames: usize,
+
+    /// Time of the first frame in seconds.
+    pub start_time: Option<f64>,
+
+    /// Time of the last frame in seconds.
+    pub end_time: Option<f64>,
+
+    /// Frame rate in frames per second.
+    ///
+    /// Each (stream ID, signature) pair is measured over its own time
+    /// span, so interleaved streams don't add up; this is the mean of
+    /// their rates. `None` if no stream has two distinct frame times.
+    pub frame_rate: Option<f64>,
+
+    /// Number of frames for each frame signature.
+    pub frames_per_signature: BTreeMap<String, usize>,
+
+    /// Partial counts from 1TRC/1HRM matrices, if any.
+    pub partials: Option<PartialStats>,
+
+    /// Range of non-zero fundamental frequencies from float 1FQ0 matrices,
+    /// if any.
+    pub pitch_range: Option<PitchRange>,
+}
+
+impl FileSummary {
+    /// Get the time span covered by the file's frames, in seconds.
+    pub fn duration(&self) -> f64 {
+        match (self.start_time, self.end_time) {
+            (Some(start), Some(end)) => end - start,
+            _ => 0.0,
+        }
+    }
+
+    /// Summarize an open file, reading all its frames.
+    ///
+    /// `path` is only recorded in the summary. The file must not be in
+    /// the middle of a frame iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading the frames.
+    pub fn from_file(file: &SdifFile, path: impl Into<PathBuf>) -> Result<Self> {
+        let mut num_frames = 0;
+        let mut start_time: Option<f64> = None;
+        let mut end_time: Option<f64> = None;
+        let mut frames_per_signature = BTreeMap::new();
+        let mut partials: Option<PartialStats> = None;
+        let mut pitch_range: Option<PitchRange> = None;
+        // Frame count and first and last time of each stream
+        let mut streams: HashMap<(u32, Signature), (usize, f64, f64)> = HashMap::new();
+
+        for frame in file.frames() {
+            let mut frame = frame?;
+            let time = frame.time();
+
+            let key = (frame.stream_id(), frame.canonical_signature_raw());
+            let stream = streams.entry(key).or_insert((0, time, time));
+            *stream = (stream.0 + 1, stream.1.min(time), stream.2.max(time));
+
+            num_frames += 1;
+            start_time = Some(start_time.map_or(time, |t| t.min(time)));
+            end_time = Some(end_time.map_or(time, |t| t.max(time)));
+            *frames_per_signature.entry(frame.signature()).or_insert(0) += 1;
+
+            for matrix in frame.matrices() {
+                let matrix = matrix?;
+                let signature = matrix.signature();
+
+                if PARTIAL_SIGNATURES.contains(&signature.as_str()) {
+                    let rows = matrix.rows();
+                    let stats = PartialStats {
+                        min: rows,
+                        max: rows,
+                        mean: rows as f64,
+                        matrices: 1,
+                    };
+                    partials = Some(match partials {
+                        Some(ref acc) => acc.merge(&stats),
+                        None => stats,
+                    });
+                } else if signature == PITCH_SIGNATURE
+                    && !matrix.is_empty()
+                    && matches!(matrix.data_type(), DataType::Float4 | DataType::Float8)
+                {
+                    let cols = matrix.cols();
+                    let data = matrix.data_f64()?;
+
+                    // First column holds the fundamental frequency; 0 means unvoiced
+                    for &f0 in data.iter().step_by(cols) {
+                        if f0 > 0.0 && f0.is_finite() {
+                            let r = PitchRange { min: f0, max: f0 };
+                            pitch_range = Some(match pitch_range {
+                                Some(ref acc) => acc.merge(&r),
+                                None => r,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let rates: Vec<f64> = streams
+            .values()
+            .filter(|&&(frames, start, end)| end > start && frames > 1)
+            .map(|&(frames, start, end)| (frames - 1) as f64 / (end - start))
+            .collect();
+        let frame_rate =
+            (!rates.is_empty()).then(|| rates.iter().sum::<f64>() / rates.len() as f64);
+
+        Ok(FileSummary {
+            path: path.into(),
+            num_frames,
+            start_time,
+            end_time,
+            frame_rate,
+            frames_per_signature,
+            partials,
+            pitch_range,
+        })
+    }
+}
+
+/// A file that could not be summarized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FileFailure {
+    /// Path of the file.
+    pub path: PathBuf,
+
+    /// Description of the error.
+    pub message: String,
+}
+
+/// Statistics aggregated over all successfully summarized files.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AggregateStats {
+    /// Number of successfully summarized files.
+    pub num_files: usize,
+
+    /// Total number of frames across all files.
+    pub total_frames: usize,
+
+    /// Sum of all file durations in seconds.
+    pub total_duration: f64,
+
+    /// Shortest file duration in seconds.
+    pub min_duration: Option<f64>,
+
+    /// Longest file duration in seconds.
+    pub max_duration: Option<f64>,
+
+    /// Mean of the per-file frame rates.
+    pub mean_frame_rate: Option<f64>,
+
+    /// Number of frames for each frame signature across all files.
+    pub frames_per_signature: BTreeMap<String, usize>,
+
+    /// Partial counts across all files.
+    pub partials: Option<PartialStats>,
+
+    /// Pitch range across all files.
+    pub pitch_range: Option<PitchRange>,
+}
+
+impl AggregateStats {
+    /// Compute aggregate statistics from per-file summaries.
+    pub fn from_files(files: &[FileSummary]) -> Self {
+        let mut stats = AggregateStats {
+            num_files: files.len(),
+            ..Default::default()
+        };
+
+        let mut rate_sum = 0.0;
+        let mut rate_count = 0usize;
+
+        for file in files {
+            let duration = file.duration();
+
+            stats.total_frames += file.num_frames;
+            stats.total_duration += duration;
+            stats.min_duration = Some(stats.min_duration.map_or(duration, |d| d.min(duration)));
+            stats.max_duration = Some(stats.max_duration.map_or(duration, |d| d.max(duration)));
+
+            if let Some(rate) = file.frame_rate {
+                rate_sum += rate;
+                rate_count += 1;
+            }
+
+            for (sig, count) in &file.frames_per_signature {
+                *stats.frames_per_signature.entry(sig.clone()).or_insert(0) += count;
+            }
+
+            if let Some(ref p) = file.partials {
+                stats.partials = Some(match stats.partials {
+                    Some(ref acc) => acc.merge(p),
+                    None => p.clone(),
+                });
+            }
+
+            if let Some(ref r) = file.pitch_range {
+                stats.pitch_range = Some(match stats.pitch_range {
+                    Some(ref acc) => acc.merge(r),
+                    None => *r,
+                });
+            }
+        }
+
+        if rate_count > 0 {
+            stats.mean_frame_rate = Some(rate_sum / rate_count as f64);
+        }
+
+        stats
+    }
+}
+
+/// Summary of a whole corpus of SDIF files.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CorpusSummary {
+    /// Per-file statistics, in input order.
+    pub files: Vec<FileSummary>,
+
+    /// Files that could not be read.
+    pub failures: Vec<FileFailure>,
+
+    /// Statistics aggregated over `files`.
+    pub aggregate: AggregateStats,
+}
+
+impl CorpusSummary {
+    /// Serialize the summary as pretty-printed JSON.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Summarize a collection of SDIF files.
+///
+/// Each file is read once. Files that fail to open or read are listed in
+/// [`CorpusSummary::failures`] and excluded from the aggregate.
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::corpus;
+///
+/// let summary = corpus::summarize(["a.sdif", "b.sdif"]);
+/// if let Some(range) = summary.aggregate.pitch_range {
+///     println!("Pitch range: {:.1}-{:.1} Hz", range.min, range.max);
+/// }
+/// ```
+pub fn summarize<I, P>(paths: I) -> CorpusSummary
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut files = Vec::new();
+    let mut failures = Vec::new();
+
+    for path in paths {
+        let path = path.as_ref();
+        match summarize_file(path) {
+            Ok(summary) => files.push(summary),
+            Err(e) => failures.push(FileFailure {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    let aggregate = AggregateStats::from_files(&files);
+
+    CorpusSummary {
+        files,
+        failures,
+        aggregate,
+    }
+}
+
+/// Summarize a single SDIF file.
+///
+/// # Errors
+///
+/// Returns any error from opening the file or reading its frames.
+pub fn summarize_file(path: impl AsRef<Path>) -> Result<FileSummary> {
+    let path = path.as_ref();
+    FileSummary::from_file(&SdifFile::open(path)?, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_summary(name: &str, frames: usize, start: f64, end: f64) -> FileSummary {
+        FileSummary {
+            path: PathBuf::from(name),
+            num_frames: frames,
+            start_time: Some(start),
+            end_time: Some(end),
+            frame_rate: Some((frames - 1) as f64 / (end - start)),
+            frames_per_signature: [("1TRC".to_string(), frames)].into_iter().collect(),
+            partials: None,
+            pitch_range: None,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_basic() {
+        let a = file_summary("a.sdif", 101, 0.0, 1.0);
+        let b = file_summary("b.sdif", 201, 0.0, 4.0);
+
+        let stats = AggregateStats::from_files(&[a, b]);
+
+        assert_eq!(stats.num_files, 2);
+        assert_eq!(stats.total_frames, 302);
+        assert_eq!(stats.total_duration, 5.0);
+        assert_eq!(stats.min_duration, Some(1.0));
+        assert_eq!(stats.max_duration, Some(4.0));
+        assert_eq!(stats.mean_frame_rate, Some(75.0));
+        assert_eq!(stats.frames_per_signature["1TRC"], 302);
+    }
+
+    #[test]
+    fn test_aggregate_partials_and_pitch() {
+        let mut a = file_summary("a.sdif", 3, 0.0, 1.0);
+        a.partials = Some(PartialStats {
+            min: 2,
+            max: 10,
+            mean: 5.0,
+            matrices: 2,
+        });
+        a.pitch_range = Some(PitchRange {
+            min: 100.0,
+            max: 200.0,
+        });
+
+        let mut b = file_summary("b.sdif", 3, 0.0, 1.0);
+        b.partials = Some(PartialStats {
+            min: 1,
+            max: 4,
+            mean: 2.0,
+            matrices: 1,
+        });
+        b.pitch_range = Some(PitchRange {
+            min: 80.0,
+            max: 150.0,
+        });
+
+        let stats = AggregateStats::from_files(&[a, b]);
+
+        let partials = stats.partials.unwrap();
+        assert_eq!(partials.min, 1);
+        assert_eq!(partials.max, 10);
+        assert_eq!(partials.matrices, 3);
+        assert!((partials.mean - 4.0).abs() < 1e-12);
+
+        assert_eq!(
+            stats.pitch_range,
+            Some(PitchRange {
+                min: 80.0,
+                max: 200.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_aggregate_empty() {
+        let stats = AggregateStats::from_files(&[]);
+        assert_eq!(stats.num_files, 0);
+        assert_eq!(stats.min_duration, None);
+        assert_eq!(stats.mean_frame_rate, None);
+    }
+
+    #[test]
+    fn test_summarize_records_failures() {
+        let summary = summarize(["/nonexistent/corpus/file.sdif"]);
+        assert!(summary.files.is_empty());
+        assert_eq!(summary.failures.len(), 1);
+        assert_eq!(summary.aggregate.num_files, 0);
+    }
+}
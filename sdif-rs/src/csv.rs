@@ -0,0 +1,602 @@
+//! CSV export and import of SDIF matrices.
+//!
+//! CSV is what spreadsheets and pandas read. An export holds the matrices
+//! of one signature, one matrix row per line, with the frame time first:
+//!
+//! ```text
+//! time,Index,Frequency,Amplitude,Phase
+//! 0,1,440,0.5,0
+//! 0,2,880,0.25,0
+//! 0.01,1,440.5,0.5,0.1
+//! ```
+//!
+//! The header names the matrix columns as declared in the file. A `stream`
+//! column after `time` can be added with
+//! [`CsvExportOptions::stream_column()`]. Matrices narrower than the widest
+//! one leave their last fields empty, and frames with empty matrices have
+//! no lines.
+//!
+//! Importing goes the other way: consecutive lines with the same time and
+//! stream become the rows of one frame's matrix. CSV columns are picked
+//! by header name, and can be renamed on the way with
+//! [`CsvImportOptions::map_column()`]; by default every column apart from
+//! time and stream is kept under its own name. Empty fields are read as
+//! NaN.
+//!
+//! Standard types keep the columns predefined by the SDIF library, so a
+//! selection of 1TRC columns, say, is best imported under a custom
+//! signature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::csv::{self, CsvExportOptions, CsvImportOptions};
+//!
+//! let rows = csv::export("analysis.sdif", "tracks.csv", &CsvExportOptions::new("1TRC"))?;
+//! println!("Exported {} rows", rows);
+//!
+//! // Edited in pandas with `df.to_csv("edited.csv", index=False)`
+//! let options = CsvImportOptions::new("XPRT")
+//!     .map_column("partial", "Index")
+//!     .map_column("freq_hz", "Frequency");
+//! csv::import("edited.csv", "edited.sdif", &options)?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame::FrameFilter;
+use crate::frame_data::{FrameData, MatrixData};
+use crate::metrics::{self, ConversionTimer};
+use crate::sonic_visualiser::split_fields;
+
+/// Header of the time column.
+pub const TIME_COLUMN: &str = "time";
+
+/// Header of the stream ID column.
+pub const STREAM_COLUMN: &str = "stream";
+
+/// Frame selection and layout options for [`to_csv()`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CsvExportOptions {
+    /// Signature of the matrices to export.
+    pub matrix_signature: String,
+
+    /// Only export matrices from frames of this signature, if set.
+    pub frame_signature: Option<String>,
+
+    /// Only export frames of this stream ID, if set.
+    pub stream_id: Option<u32>,
+
+    /// Add a [`STREAM_COLUMN`] after the time column.
+    pub stream_column: bool,
+
+    /// Field delimiter.
+    pub delimiter: char,
+}
+
+impl CsvExportOptions {
+    /// Create options exporting every matrix with the given signature.
+    pub fn new(matrix_signature: impl Into<String>) -> Self {
+        Self {
+            matrix_signature: matrix_signature.into(),
+            frame_signature: None,
+            stream_id: None,
+            stream_column: false,
+            delimiter: ',',
+        }
+    }
+
+    /// Only export matrices from frames of this signature.
+    pub fn frame_signature(mut self, signature: impl Into<String>) -> Self {
+        self.frame_signature = Some(signature.into());
+        self
+    }
+
+    /// Only export frames of this stream ID.
+    pub fn stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = Some(stream_id);
+        self
+    }
+
+    /// Set whether to add a stream ID column.
+    pub fn stream_column(mut self, stream_column: bool) -> Self {
+        self.stream_column = stream_column;
+        self
+    }
+
+    /// Set the field delimiter.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+}
+
+/// Column mapping and output options for [`parse_csv()`] and
+/// [`write_sdif()`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CsvImportOptions {
+    /// Signature of the frames to write.
+    pub frame_signature: String,
+
+    /// Signature of the matrices to write.
+    pub matrix_signature: String,
+
+    /// Name of the frame component declared for the matrix.
+    pub component: String,
+
+    /// Header of the column holding frame times, in seconds.
+    pub time_column: String,
+
+    /// Header of the column holding stream IDs, if any.
+    ///
+    /// Defaults to [`STREAM_COLUMN`], which may be missing from the CSV
+    /// header; other names must be present. Without a stream column,
+    /// frames are written to [`stream_id`](Self::stream_id).
+    pub stream_column: Option<String>,
+
+    /// Stream ID of the frames when there is no stream column.
+    pub stream_id: u32,
+
+    /// CSV columns to keep, as pairs of CSV header and matrix column name,
+    /// in matrix column order.
+    ///
+    /// Empty to keep every column apart from time and stream, in CSV order
+    /// and under its CSV name.
+    pub columns: Vec<(String, String)>,
+
+    /// Field delimiter.
+    pub delimiter: char,
+}
+
+impl CsvImportOptions {
+    /// Create options writing frames and matrices of the same signature.
+    pub fn new(signature: impl Into<String>) -> Self {
+        let signature = signature.into();
+        Self {
+            frame_signature: signature.clone(),
+            matrix_signature: signature,
+            component: "Data".to_string(),
+            time_column: TIME_COLUMN.to_string(),
+            stream_column: Some(STREAM_COLUMN.to_string()),
+            stream_id: 0,
+            columns: Vec::new(),
+            delimiter: ',',
+        }
+    }
+
+    /// Set the signature of the matrices, when it differs from the frames'.
+    pub fn matrix_signature(mut self, signature: impl Into<String>) -> Self {
+        self.matrix_signature = signature.into();
+        self
+    }
+
+    /// Set the name of the frame component.
+    pub fn component(mut self, name: impl Into<String>) -> Self {
+        self.component = name.into();
+        self
+    }
+
+    /// Set the header of the time column.
+    pub fn time_column(mut self, header: impl Into<String>) -> Self {
+        self.time_column = header.into();
+        self
+    }
+
+    /// Set the header of the stream column.
+    pub fn stream_column(mut self, header: Option<String>) -> Self {
+        self.stream_column = header;
+        self
+    }
+
+    /// Set the stream ID used when there is no stream column.
+    pub fn stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = stream_id;
+        self
+    }
+
+    /// Keep the CSV column `header` as the next matrix column, named
+    /// `name`.
+    pub fn map_column(mut self, header: impl Into<String>, name: impl Into<String>) -> Self {
+        self.columns.push((header.into(), name.into()));
+        self
+    }
+
+    /// Set the field delimiter.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+}
+
+/// Frames and matrix column names read from a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvTable {
+    /// Names of the matrix columns.
+    pub columns: Vec<String>,
+
+    /// Frames in time order, each with one matrix.
+    pub frames: Vec<FrameData>,
+}
+
+/// Write the matrices selected by `options` as CSV text.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the delimiter is a quote
+/// - Any error from reading the frames
+pub fn to_csv(file: &SdifFile, options: &CsvExportOptions) -> Result<String> {
+    if options.delimiter == '"' {
+        return Err(Error::invalid_format("CSV delimiter cannot be a quote"));
+    }
+
+    let mut filter = FrameFilter::new();
+    if let Some(signature) = &options.frame_signature {
+        filter = filter.signature(signature);
+    }
+    if let Some(stream_id) = options.stream_id {
+        filter = filter.stream_id(stream_id);
+    }
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut lines: Vec<Vec<String>> = Vec::new();
+    let mut width = 0;
+
+    for frame in file.frames_filtered(filter) {
+        let mut frame = frame?;
+        let mut prefix = vec![frame.time().to_string()];
+        if options.stream_column {
+            prefix.push(frame.stream_id().to_string());
+        }
+
+        for matrix in frame.matrices() {
+            let matrix = matrix?;
+            if matrix.signature() != options.matrix_signature || matrix.is_empty() {
+                continue;
+            }
+            if columns.is_empty() {
+                columns = matrix.columns();
+            }
+            let cols = matrix.cols();
+            width = width.max(cols);
+
+            for row in matrix.data_f64()?.chunks(cols) {
+                let mut line = prefix.clone();
+                line.extend(row.iter().map(f64::to_string));
+                lines.push(line);
+            }
+        }
+    }
+
+    // Predefined types may declare more columns than files use, and
+    // columns beyond the declared ones get numbered names
+    columns.truncate(width);
+    for i in columns.len()..width {
+        columns.push(format!("Column{}", i + 1));
+    }
+
+    let mut header = vec![TIME_COLUMN.to_string()];
+    if options.stream_column {
+        header.push(STREAM_COLUMN.to_string());
+    }
+    header.extend(columns);
+
+    let fields = header.len();
+    let mut text = String::new();
+    for mut line in std::iter::once(header).chain(lines) {
+        line.resize(fields, String::new());
+        let quoted: Vec<String> = line
+            .iter()
+            .map(|field| quote_field(field, options.delimiter))
+            .collect();
+        text.push_str(&quoted.join(&options.delimiter.to_string()));
+        text.push('\n');
+    }
+
+    Ok(text)
+}
+
+/// Export the matrices selected by `options` to a CSV file.
+///
+/// Returns the number of matrix rows written.
+///
+/// # Errors
+///
+/// - [`Error::Io`] if the output can't be written
+/// - Any error from opening the input or from [`to_csv()`]
+pub fn export(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &CsvExportOptions,
+) -> Result<usize> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_CSV_EXPORT);
+    let text = to_csv(&SdifFile::open(input)?, options)?;
+    fs::write(output, &text)?;
+    timer.finish();
+    Ok(text.lines().count() - 1)
+}
+
+/// Parse CSV text into frames.
+///
+/// The first line must be a header. Blank lines are skipped. Rows with
+/// the same time and stream make one frame, even when other rows come
+/// between them.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the text has no header, a mapped column
+///   is missing from it, a line has too few fields, a field is not a
+///   valid number, or a time is empty or not finite
+pub fn parse_csv(text: &str, options: &CsvImportOptions) -> Result<CsvTable> {
+    if options.delimiter == '"' {
+        return Err(Error::invalid_format("CSV delimiter cannot be a quote"));
+    }
+
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| Error::invalid_format("CSV file has no header"))?;
+    let header = split_fields(header, options.delimiter);
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| Error::invalid_format(format!("CSV header has no column {:?}", name)))
+    };
+
+    let time_column = find(&options.time_column)?;
+    // A missing stream column is only an error if it was asked for by name
+    let stream_column = match &options.stream_column {
+        Some(name) if name == STREAM_COLUMN => find(name).ok(),
+        Some(name) => Some(find(name)?),
+        None => None,
+    };
+
+    let (indices, columns): (Vec<usize>, Vec<String>) = if options.columns.is_empty() {
+        header
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != time_column && Some(i) != stream_column)
+            .map(|(i, name)| (i, name.trim().to_string()))
+            .unzip()
+    } else {
+        options
+            .columns
+            .iter()
+            .map(|(csv_name, name)| Ok((find(csv_name)?, name.clone())))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip()
+    };
+    if columns.is_empty() {
+        return Err(Error::invalid_format("CSV file has no matrix columns"));
+    }
+
+    let mut frames: Vec<FrameData> = Vec::new();
+    // Frame of each time and stream, with -0.0 keyed as 0.0
+    let mut frame_indices: HashMap<(u64, u32), usize> = HashMap::new();
+    for (index, line) in lines {
+        let fields = split_fields(line, options.delimiter);
+        let number = |column: usize| {
+            let text = fields.get(column).ok_or_else(|| {
+                Error::invalid_format(format!("Line {} has no column {}", index + 1, column))
+            })?;
+            if text.trim().is_empty() {
+                return Ok(f64::NAN);
+            }
+            text.trim().parse::<f64>().map_err(|_| {
+                Error::invalid_format(format!(
+                    "Line {} column {}: invalid number {:?}",
+                    index + 1,
+                    column,
+                    text
+                ))
+            })
+        };
+
+        let time = number(time_column)?;
+        if !time.is_finite() {
+            return Err(Error::invalid_format(format!(
+                "Line {}: invalid time {:?}",
+                index + 1,
+                fields[time_column]
+            )));
+        }
+        let stream_id = match stream_column {
+            Some(column) => {
+                let id = number(column)?;
+                if !(id >= 0.0 && id <= u32::MAX as f64 && id.fract() == 0.0) {
+                    return Err(Error::invalid_format(format!(
+                        "Line {}: invalid stream ID {}",
+                        index + 1,
+                        id
+                    )));
+                }
+                id as u32
+            }
+            None => options.stream_id,
+        };
+        let row = indices.iter().map(|&i| number(i)).collect::<Result<Vec<_>>>()?;
+
+        let key = ((time + 0.0).to_bits(), stream_id);
+        match frame_indices.get(&key) {
+            Some(&i) => {
+                let matrix = &mut frames[i].matrices[0];
+                matrix.rows += 1;
+                matrix.data.extend(row);
+            }
+            None => {
+                frame_indices.insert(key, frames.len());
+                let mut frame = FrameData::new(&options.frame_signature, time, stream_id);
+                frame.matrices.push(MatrixData::new(
+                    &options.matrix_signature,
+                    1,
+                    columns.len(),
+                    row,
+                ));
+                frames.push(frame);
+            }
+        }
+    }
+
+    frames.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(CsvTable { columns, frames })
+}
+
+/// Read a CSV file into frames.
+///
+/// # Errors
+///
+/// - [`Error::Io`] if the file can't be read
+/// - Any error from [`parse_csv()`]
+pub fn read_csv(path: impl AsRef<Path>, options: &CsvImportOptions) -> Result<CsvTable> {
+    parse_csv(&fs::read_to_string(path)?, options)
+}
+
+/// Write frames read from CSV to a new SDIF file.
+///
+/// The file declares the matrix type with the table's column names, and
+/// the frame type with one component holding it; see the
+/// [module documentation](self) for standard types. Returns the number of
+/// frames written.
+///
+/// # Errors
+///
+/// - Any error from creating or writing the file
+pub fn write_sdif(
+    table: &CsvTable,
+    path: impl AsRef<Path>,
+    options: &CsvImportOptions,
+) -> Result<usize> {
+    let columns: Vec<&str> = table.columns.iter().map(String::as_str).collect();
+    let component = format!("{} {}", options.matrix_signature, options.component);
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type(&options.matrix_signature, &columns)?
+        .add_frame_type(&options.frame_signature, &[component.as_str()])?
+        .build()?;
+
+    for frame in &table.frames {
+        writer.write_frame_data(frame)?;
+    }
+    writer.close()?;
+
+    Ok(table.frames.len())
+}
+
+/// Convert a CSV file to SDIF.
+///
+/// Returns the number of frames written.
+///
+/// # Errors
+///
+/// - Any error from [`read_csv()`] or [`write_sdif()`]
+pub fn import(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &CsvImportOptions,
+) -> Result<usize> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_CSV_IMPORT);
+    let table = read_csv(input, options)?;
+    let frames = write_sdif(&table, output, options)?;
+    timer.finish();
+    Ok(frames)
+}
+
+/// Quote a field if it holds the delimiter, a quote or a line break.
+fn quote_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_field() {
+        assert_eq!(quote_field("440", ','), "440");
+        assert_eq!(quote_field("a,b", ','), "\"a,b\"");
+        assert_eq!(quote_field("say \"hi\"", ';'), "\"say \"\"hi\"\"\"");
+        assert_eq!(split_fields(&quote_field("x, \"y\"", ','), ','), ["x, \"y\""]);
+    }
+
+    #[test]
+    fn test_parse_groups_rows() {
+        let text = "time,Index,Frequency\n0,1,440\n0,2,880\n\n0.01,1,441\n";
+        let table = parse_csv(text, &CsvImportOptions::new("1TRC")).unwrap();
+        assert_eq!(table.columns, ["Index", "Frequency"]);
+        assert_eq!(table.frames.len(), 2);
+
+        let matrix = &table.frames[0].matrices[0];
+        assert_eq!((matrix.rows, matrix.cols), (2, 2));
+        assert_eq!(matrix.data, [1.0, 440.0, 2.0, 880.0]);
+        assert_eq!(table.frames[1].time, 0.01);
+    }
+
+    #[test]
+    fn test_parse_merges_interleaved_rows() {
+        let text = "time,stream,Index\n0,1,1\n0,2,1\n-0,1,2\n0.01,1,1\n0,2,2\n";
+        let table = parse_csv(text, &CsvImportOptions::new("1TRC")).unwrap();
+        assert_eq!(table.frames.len(), 3);
+
+        let frame = &table.frames[0];
+        assert_eq!((frame.time, frame.stream_id), (0.0, 1));
+        assert_eq!(frame.matrices[0].data, [1.0, 2.0]);
+        assert_eq!(table.frames[1].stream_id, 2);
+        assert_eq!(table.frames[1].matrices[0].data, [1.0, 2.0]);
+        assert_eq!(table.frames[2].time, 0.01);
+    }
+
+    #[test]
+    fn test_parse_mapping_and_streams() {
+        let text = "freq,t,stream,amp\n440,0,1,0.5\n220,0,2,\n";
+        let options = CsvImportOptions::new("1TRC")
+            .time_column("t")
+            .map_column("amp", "Amplitude")
+            .map_column("freq", "Frequency");
+        let table = parse_csv(text, &options).unwrap();
+
+        assert_eq!(table.columns, ["Amplitude", "Frequency"]);
+        assert_eq!(table.frames.len(), 2);
+        assert_eq!(table.frames[0].stream_id, 1);
+        assert_eq!(table.frames[0].matrices[0].data, [0.5, 440.0]);
+        assert!(table.frames[1].matrices[0].data[0].is_nan());
+
+        // Without a stream column, all rows at a time make one frame
+        let options = options.stream_column(None).stream_id(3);
+        let table = parse_csv(text, &options).unwrap();
+        assert_eq!(table.frames.len(), 1);
+        assert_eq!(table.frames[0].stream_id, 3);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let options = CsvImportOptions::new("1TRC");
+        assert!(parse_csv("", &options).is_err());
+        assert!(parse_csv("Index,Frequency\n1,440\n", &options).is_err());
+        assert!(parse_csv("time\n0\n", &options).is_err());
+
+        let err = parse_csv("time,Frequency\n0,abc\n", &options).unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+
+        let err = parse_csv("time,Frequency\n,440\n", &options).unwrap_err();
+        assert!(err.to_string().contains("Line 2: invalid time"));
+        let err = parse_csv("time,Frequency\n0,440\ninf,441\n", &options).unwrap_err();
+        assert!(err.to_string().contains("Line 3: invalid time"));
+        assert!(parse_csv("time,Frequency\nNaN,440\n", &options).is_err());
+
+        let options = options.map_column("freq", "Frequency");
+        let err = parse_csv("time,Frequency\n0,440\n", &options).unwrap_err();
+        assert!(err.to_string().contains("\"freq\""));
+    }
+}
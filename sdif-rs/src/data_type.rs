@@ -4,12 +4,16 @@
 //! The most common are `Float4` (f32) and `Float8` (f64).
 
 use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
 
 /// SDIF matrix data types.
 ///
 /// SDIF supports various numeric data types for matrix storage.
 /// In practice, most audio analysis data uses `Float4` or `Float8`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum DataType {
     /// 32-bit floating point (f32)
@@ -125,12 +129,84 @@ impl fmt::Display for DataType {
     }
 }
 
+impl FromStr for DataType {
+    type Err = Error;
+
+    /// Parse a data type from its [`Display`](fmt::Display) name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "float32" => Ok(DataType::Float4),
+            "float64" => Ok(DataType::Float8),
+            "int8" => Ok(DataType::Int1),
+            "int16" => Ok(DataType::Int2),
+            "int32" => Ok(DataType::Int4),
+            "uint8" => Ok(DataType::UInt1),
+            "uint16" => Ok(DataType::UInt2),
+            "uint32" => Ok(DataType::UInt4),
+            "text" => Ok(DataType::Text),
+            _ => Err(Error::invalid_format(format!("Unknown data type: {}", s))),
+        }
+    }
+}
+
 impl Default for DataType {
     fn default() -> Self {
         DataType::Float8 // Most common for audio data
     }
 }
 
+/// A number type that matrix data can be read as with
+/// [`Matrix::data_as()`](crate::Matrix::data_as).
+///
+/// Conversions are lossless: a type can read a data type only if it holds
+/// every value of that type exactly. `i32` reads `Int1`, `Int2`, `Int4`,
+/// `UInt1` and `UInt2`, but not `UInt4` or floats; `f64` reads every
+/// numeric type.
+pub trait MatrixValue: Copy + sealed::Sealed {
+    /// Check whether every value of `data_type` converts exactly.
+    fn holds(data_type: DataType) -> bool;
+
+    /// Convert a value already known to fit.
+    #[doc(hidden)]
+    fn from_f64(value: f64) -> Self;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! matrix_value {
+    ($($ty:ty => [$($data_type:ident),*]),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $ty {}
+            impl MatrixValue for $ty {
+                fn holds(data_type: DataType) -> bool {
+                    matches!(data_type, $(DataType::$data_type)|*)
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $ty
+                }
+            }
+        )*
+    };
+}
+
+// Every numeric type converts to f64 exactly, so values are decoded to
+// f64 first
+matrix_value! {
+    f64 => [Float4, Float8, Int1, Int2, Int4, UInt1, UInt2, UInt4],
+    f32 => [Float4, Int1, Int2, UInt1, UInt2],
+    i64 => [Int1, Int2, Int4, UInt1, UInt2, UInt4],
+    i32 => [Int1, Int2, Int4, UInt1, UInt2],
+    i16 => [Int1, Int2, UInt1],
+    i8 => [Int1],
+    u64 => [UInt1, UInt2, UInt4],
+    u32 => [UInt1, UInt2, UInt4],
+    u16 => [UInt1, UInt2],
+    u8 => [UInt1],
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,9 +236,35 @@ mod tests {
         assert!(!DataType::UInt4.is_signed());
     }
 
+    #[test]
+    fn test_matrix_value_is_lossless() {
+        assert!(i32::holds(DataType::UInt2));
+        assert!(!i32::holds(DataType::UInt4));
+        assert!(!i32::holds(DataType::Float4));
+        assert!(u32::holds(DataType::UInt4));
+        assert!(!u32::holds(DataType::Int1));
+        assert!(f32::holds(DataType::Int2));
+        assert!(!f32::holds(DataType::Int4));
+        assert!(!f64::holds(DataType::Text));
+    }
+
     #[test]
     fn test_display() {
         assert_eq!(format!("{}", DataType::Float4), "float32");
         assert_eq!(format!("{}", DataType::Float8), "float64");
     }
+
+    #[test]
+    fn test_from_str() {
+        for dt in [
+            DataType::Float4,
+            DataType::Float8,
+            DataType::Int2,
+            DataType::UInt4,
+            DataType::Text,
+        ] {
+            assert_eq!(dt.to_string().parse::<DataType>().unwrap(), dt);
+        }
+        assert!("unknown".parse::<DataType>().is_err());
+    }
 }
@@ -0,0 +1,564 @@
+//! Spectral shape descriptors of partial and STFT frames.
+//!
+//! [`Descriptors`] summarize the shape of a spectrum in three numbers:
+//! the centroid (the amplitude-weighted mean frequency), the spread
+//! around it, and the flatness (how noise-like the spectrum is). They can
+//! be computed from partials (1TRC, 1HRM) or from short-time Fourier
+//! transform bins (1STF).
+//!
+//! [`append_descriptors()`] copies a file and adds a stream of 1DSC frames
+//! holding the descriptors of each source frame, following the IRCAM
+//! convention of storing descriptors next to the data they describe.
+//!
+//! Other descriptors plug into the same pipeline by implementing
+//! [`DescriptorExtractor`]; [`augment()`] runs any number of extractors
+//! while copying a file, each writing a stream of its own.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::descriptors::{self, DescriptorOptions};
+//!
+//! let options = DescriptorOptions::new().bin_hz(44100.0 / 2048.0);
+//! let written = descriptors::append_descriptors("in.sdif", "out.sdif", &options)?;
+//! println!("Added {} descriptor frames", written);
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::copy::copy_header;
+use crate::error::Result;
+use crate::file::SdifFile;
+use crate::frame_data::{FrameData, MatrixData, TimeSlice};
+use crate::schema::{ComponentSchema, FrameTypeSchema, MatrixTypeSchema, Schema};
+use crate::schemas;
+
+/// Signature of descriptor frames and matrices.
+pub const DESCRIPTOR_SIGNATURE: &str = "1DSC";
+
+/// Columns of descriptor matrices.
+pub const DESCRIPTOR_COLUMNS: &[&str] = &["Centroid", "Spread", "Flatness"];
+
+/// Frame/matrix signatures holding partials.
+const PARTIAL_SIGNATURES: &[&str] = &["1TRC", "1HRM"];
+
+/// Frame/matrix signature of STFT bins.
+const STFT_SIGNATURE: &str = "1STF";
+
+/// Largest time difference between frames considered simultaneous.
+const TIME_TOLERANCE: f64 = 1e-9;
+
+/// Spectral shape of one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Descriptors {
+    /// Amplitude-weighted mean frequency.
+    pub centroid: f64,
+
+    /// Amplitude-weighted standard deviation of the frequency around the
+    /// centroid.
+    pub spread: f64,
+
+    /// Ratio of the geometric to the arithmetic mean of the power, from 0
+    /// (a single peak) to 1 (flat, noise-like).
+    pub flatness: f64,
+}
+
+impl Descriptors {
+    /// Compute descriptors from (frequency, amplitude) pairs.
+    ///
+    /// Returns `None` if there are no pairs or all amplitudes are zero.
+    pub fn from_spectrum(bins: impl IntoIterator<Item = (f64, f64)>) -> Option<Self> {
+        let bins: Vec<(f64, f64)> = bins.into_iter().map(|(f, a)| (f, a.abs())).collect();
+
+        let total: f64 = bins.iter().map(|&(_, a)| a).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let centroid = bins.iter().map(|&(f, a)| f * a).sum::<f64>() / total;
+        let variance = bins
+            .iter()
+            .map(|&(f, a)| (f - centroid).powi(2) * a)
+            .sum::<f64>()
+            / total;
+
+        let n = bins.len() as f64;
+        let power_mean = bins.iter().map(|&(_, a)| a * a).sum::<f64>() / n;
+        let flatness = if bins.iter().any(|&(_, a)| a == 0.0) {
+            0.0
+        } else {
+            let log_mean = bins.iter().map(|&(_, a)| (a * a).ln()).sum::<f64>() / n;
+            log_mean.exp() / power_mean
+        };
+
+        Some(Descriptors {
+            centroid,
+            spread: variance.sqrt(),
+            flatness,
+        })
+    }
+
+    /// Compute descriptors of a frame.
+    ///
+    /// Partial matrices are used if the frame has any, with the frequency
+    /// and amplitude columns of the standard layout. Otherwise 1STF
+    /// matrices are used, taking the magnitude of each bin and placing
+    /// bin `k` at `k * bin_hz`. Returns `None` for frames with neither, or
+    /// without energy.
+    pub fn from_frame(frame: &FrameData, bin_hz: f64) -> Option<Self> {
+        let frequency = column(schemas::TRC_COLUMNS, "Frequency");
+        let amplitude = column(schemas::TRC_COLUMNS, "Amplitude");
+
+        let partials: Vec<&MatrixData> = frame
+            .matrices
+            .iter()
+            .filter(|m| PARTIAL_SIGNATURES.contains(&m.signature.as_str()))
+            .collect();
+        if !partials.is_empty() {
+            return Self::from_spectrum(
+                partials
+                    .into_iter()
+                    .flat_map(|m| m.rows())
+                    .filter_map(|row| Some((*row.get(frequency)?, *row.get(amplitude)?))),
+            );
+        }
+
+        let real = column(schemas::STF_COLUMNS, "Real");
+        let imaginary = column(schemas::STF_COLUMNS, "Imaginary");
+
+        let stft = frame.matrix(STFT_SIGNATURE)?;
+        Self::from_spectrum(stft.rows().enumerate().filter_map(|(k, row)| {
+            let magnitude = row.get(real)?.hypot(*row.get(imaginary)?);
+            Some((k as f64 * bin_hz, magnitude))
+        }))
+    }
+
+    /// Get the descriptors as a 1x3 [`DESCRIPTOR_SIGNATURE`] matrix.
+    pub fn to_matrix(&self) -> MatrixData {
+        MatrixData::new(
+            DESCRIPTOR_SIGNATURE,
+            1,
+            DESCRIPTOR_COLUMNS.len(),
+            vec![self.centroid, self.spread, self.flatness],
+        )
+    }
+}
+
+/// Computes descriptor matrices from the frames at one instant.
+///
+/// Extractors plug into [`augment()`], which copies a file and writes the
+/// matrices returned by each extractor as a frame of its own stream.
+/// Third-party crates add descriptor streams by implementing this trait
+/// and, optionally, registering a factory in a [`DescriptorRegistry`].
+///
+/// # Example
+///
+/// ```
+/// use sdif_rs::descriptors::DescriptorExtractor;
+/// use sdif_rs::{MatrixData, MatrixTypeSchema, Schema, TimeSlice};
+///
+/// /// Counts the partials at each instant.
+/// struct PartialCount;
+///
+/// impl DescriptorExtractor for PartialCount {
+///     fn signature(&self) -> &str {
+///         "XCNT"
+///     }
+///
+///     fn schema(&self) -> Schema {
+///         let mut schema = Schema::default();
+///         schema.matrix_types.push(MatrixTypeSchema {
+///             signature: "XCNT".to_string(),
+///             columns: vec!["Count".to_string()],
+///         });
+///         schema
+///     }
+///
+///     fn process(&mut self, slice: &TimeSlice) -> Vec<MatrixData> {
+///         match slice.matrix("1TRC") {
+///             Some(m) => vec![MatrixData::new("XCNT", 1, 1, vec![m.rows as f64])],
+///             None => Vec::new(),
+///         }
+///     }
+/// }
+/// ```
+pub trait DescriptorExtractor {
+    /// Get the frame signature of the descriptor frames.
+    fn signature(&self) -> &str;
+
+    /// Get the types to declare for the descriptor frames and matrices.
+    ///
+    /// A frame type for [`signature()`](Self::signature) is declared
+    /// automatically if the schema doesn't include one, with a component
+    /// for each matrix type.
+    fn schema(&self) -> Schema;
+
+    /// Compute the descriptor matrices for the frames at one instant.
+    ///
+    /// Returning no matrices writes no frame for this instant.
+    fn process(&mut self, slice: &TimeSlice) -> Vec<MatrixData>;
+}
+
+/// Extractor of spectral centroid, spread and flatness.
+///
+/// Writes one [`DESCRIPTOR_SIGNATURE`] frame per instant with
+/// [`Descriptors`], computed from the first frame that has any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralShape {
+    /// Width of a 1STF bin in Hz.
+    pub bin_hz: f64,
+}
+
+impl DescriptorExtractor for SpectralShape {
+    fn signature(&self) -> &str {
+        DESCRIPTOR_SIGNATURE
+    }
+
+    fn schema(&self) -> Schema {
+        let mut schema = Schema::default();
+        schema.matrix_types.push(MatrixTypeSchema {
+            signature: DESCRIPTOR_SIGNATURE.to_string(),
+            columns: DESCRIPTOR_COLUMNS.iter().map(|c| c.to_string()).collect(),
+        });
+        schema
+    }
+
+    fn process(&mut self, slice: &TimeSlice) -> Vec<MatrixData> {
+        slice
+            .frames
+            .iter()
+            .find_map(|frame| Descriptors::from_frame(frame, self.bin_hz))
+            .map(|d| d.to_matrix())
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Creates an extractor configured by [`DescriptorOptions`].
+pub type ExtractorFactory = Box<dyn Fn(&DescriptorOptions) -> Box<dyn DescriptorExtractor>>;
+
+/// Extractors available by name.
+///
+/// [`DescriptorRegistry::new()`] starts with the built-in extractors:
+///
+/// | Name             | Extractor           |
+/// |------------------|---------------------|
+/// | `spectral-shape` | [`SpectralShape`]   |
+///
+/// # Example
+///
+/// ```
+/// use sdif_rs::descriptors::{DescriptorOptions, DescriptorRegistry, SpectralShape};
+///
+/// let mut registry = DescriptorRegistry::new();
+/// registry.register("coarse-shape", |_| Box::new(SpectralShape { bin_hz: 100.0 }));
+///
+/// let extractor = registry.create("coarse-shape", &DescriptorOptions::new());
+/// assert_eq!(extractor.map(|e| e.signature().to_string()), Some("1DSC".to_string()));
+/// ```
+pub struct DescriptorRegistry {
+    factories: BTreeMap<String, ExtractorFactory>,
+}
+
+impl DescriptorRegistry {
+    /// Create a registry with the built-in extractors.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register("spectral-shape", |options| {
+            Box::new(SpectralShape {
+                bin_hz: options.bin_hz.unwrap_or(1.0),
+            })
+        });
+        registry
+    }
+
+    /// Create a registry without any extractors.
+    pub fn empty() -> Self {
+        DescriptorRegistry {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Register an extractor, replacing any registered under the same name.
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(&DescriptorOptions) -> Box<dyn DescriptorExtractor> + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    /// Create the extractor registered as `name`.
+    pub fn create(
+        &self,
+        name: &str,
+        options: &DescriptorOptions,
+    ) -> Option<Box<dyn DescriptorExtractor>> {
+        self.factories.get(name).map(|factory| factory(options))
+    }
+
+    /// Iterate over the registered names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+}
+
+impl Default for DescriptorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for DescriptorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Options for descriptor extraction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DescriptorOptions {
+    /// Width of a 1STF bin in Hz. Without it, frequencies of STFT
+    /// descriptors are in bins.
+    pub bin_hz: Option<f64>,
+
+    /// Stream ID of the first descriptor stream; further extractors use
+    /// the following IDs. Defaults to one more than the largest stream ID
+    /// in the input.
+    pub stream_id: Option<u32>,
+}
+
+impl DescriptorOptions {
+    /// Create default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the width of a 1STF bin in Hz.
+    pub fn bin_hz(mut self, hz: f64) -> Self {
+        self.bin_hz = Some(hz);
+        self
+    }
+
+    /// Write descriptor frames starting at stream `id`.
+    pub fn stream_id(mut self, id: u32) -> Self {
+        self.stream_id = Some(id);
+        self
+    }
+}
+
+/// Copy a file, adding a stream of spectral shape descriptors.
+///
+/// Shorthand for [`augment()`] with a single [`SpectralShape`] extractor.
+/// Returns the number of descriptor frames written.
+///
+/// # Errors
+///
+/// - Any error from opening or reading the input
+/// - Any error from creating or writing the output
+pub fn append_descriptors(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &DescriptorOptions,
+) -> Result<usize> {
+    let mut extractors: Vec<Box<dyn DescriptorExtractor>> = vec![Box::new(SpectralShape {
+        bin_hz: options.bin_hz.unwrap_or(1.0),
+    })];
+    let written = augment(input, output, &mut extractors, options)?;
+    Ok(written[0])
+}
+
+/// Copy a file, adding one stream of descriptor frames per extractor.
+///
+/// The input's frames are grouped by time. Each group is copied, followed
+/// by one frame per extractor that returns matrices for it, at the time
+/// of the group's last frame. Extractor `i` writes to stream
+/// `options.stream_id + i`. The output declares the extractors' types in
+/// addition to the input's.
+///
+/// Returns the number of frames written by each extractor.
+///
+/// # Errors
+///
+/// - Any error from opening or reading the input
+/// - Any error from creating or writing the output, including invalid
+///   matrices returned by an extractor
+pub fn augment(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    extractors: &mut [Box<dyn DescriptorExtractor>],
+    options: &DescriptorOptions,
+) -> Result<Vec<usize>> {
+    let input = input.as_ref();
+    let first_stream = match options.stream_id {
+        Some(id) => id,
+        None => next_stream_id(&SdifFile::open(input)?)?,
+    };
+
+    let file = SdifFile::open(input)?;
+    let schema = file.describe_types()?;
+
+    let mut builder = copy_header(&file, &schema, SdifFile::builder().create(output)?)?;
+    for extractor in extractors.iter() {
+        builder = builder.with_schema(&new_types(&schema, extractor.as_ref()))?;
+    }
+    let mut writer = builder.build()?;
+
+    let mut written = vec![0; extractors.len()];
+    for slice in file.frames().group_by_time(TIME_TOLERANCE) {
+        let slice = slice?;
+
+        let mut time = slice.time;
+        for frame in slice.frames.iter().filter(|f| !f.matrices.is_empty()) {
+            writer.write_frame_data(frame)?;
+            time = frame.time;
+        }
+
+        for (i, extractor) in extractors.iter_mut().enumerate() {
+            let matrices = extractor.process(&slice);
+            if matrices.is_empty() {
+                continue;
+            }
+
+            let mut frame = FrameData::new(extractor.signature(), time, first_stream + i as u32);
+            frame.matrices = matrices;
+            writer.write_frame_data(&frame)?;
+            written[i] += 1;
+        }
+    }
+
+    writer.close()?;
+    Ok(written)
+}
+
+/// Get the types an extractor needs that `existing` doesn't declare.
+fn new_types(existing: &Schema, extractor: &dyn DescriptorExtractor) -> Schema {
+    let mut types = extractor.schema();
+    types.nvt.clear();
+
+    let signature = extractor.signature();
+    if types.frame_type(signature).is_none() {
+        types.frame_types.push(FrameTypeSchema {
+            signature: signature.to_string(),
+            components: types
+                .matrix_types
+                .iter()
+                .map(|m| ComponentSchema {
+                    signature: m.signature.clone(),
+                    name: m.signature.clone(),
+                })
+                .collect(),
+        });
+    }
+
+    types
+        .matrix_types
+        .retain(|m| existing.matrix_type(&m.signature).is_none());
+    types
+        .frame_types
+        .retain(|f| existing.frame_type(&f.signature).is_none());
+    types
+}
+
+/// Get one more than the largest stream ID in `file`.
+fn next_stream_id(file: &SdifFile) -> Result<u32> {
+    let mut max = None;
+    for frame in file.frames() {
+        let id = frame?.stream_id();
+        max = Some(max.map_or(id, |m: u32| m.max(id)));
+    }
+    Ok(max.map_or(0, |m| m + 1))
+}
+
+/// Find a column in a standard layout.
+fn column(columns: &[&str], name: &str) -> usize {
+    columns
+        .iter()
+        .position(|&c| c == name)
+        .expect("standard layouts have the column")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_peak() {
+        let d = Descriptors::from_spectrum([(440.0, 1.0)]).unwrap();
+        assert_eq!(d.centroid, 440.0);
+        assert_eq!(d.spread, 0.0);
+        assert_eq!(d.flatness, 1.0);
+
+        let d = Descriptors::from_spectrum([(100.0, 1.0), (200.0, 0.0)]).unwrap();
+        assert_eq!(d.centroid, 100.0);
+        assert_eq!(d.flatness, 0.0);
+    }
+
+    #[test]
+    fn test_two_peaks() {
+        let d = Descriptors::from_spectrum([(100.0, 1.0), (300.0, 1.0)]).unwrap();
+        assert_eq!(d.centroid, 200.0);
+        assert_eq!(d.spread, 100.0);
+        assert!((d.flatness - 1.0).abs() < 1e-12);
+
+        let d = Descriptors::from_spectrum([(100.0, 3.0), (300.0, 1.0)]).unwrap();
+        assert_eq!(d.centroid, 150.0);
+        assert!(d.flatness < 1.0);
+    }
+
+    #[test]
+    fn test_empty_spectrum() {
+        assert!(Descriptors::from_spectrum([]).is_none());
+        assert!(Descriptors::from_spectrum([(100.0, 0.0)]).is_none());
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = DescriptorRegistry::new();
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["spectral-shape"]);
+        assert!(registry
+            .create("missing", &DescriptorOptions::new())
+            .is_none());
+        assert!(DescriptorRegistry::empty().names().next().is_none());
+    }
+
+    #[test]
+    fn test_new_types() {
+        let extractor = SpectralShape { bin_hz: 1.0 };
+
+        let types = new_types(&Schema::default(), &extractor);
+        assert_eq!(types.matrix_types.len(), 1);
+        let frame_type = types.frame_type("1DSC").unwrap();
+        assert_eq!(frame_type.components[0].signature, "1DSC");
+
+        // Types the input already declares are not declared again
+        assert!(new_types(&types, &extractor).is_empty());
+    }
+
+    #[test]
+    fn test_from_frame() {
+        let mut stft = FrameData::new("1STF", 0.0, 0);
+        // Bins 0..3 with magnitudes 0, 5, 0
+        stft.matrices.push(MatrixData::new(
+            "1STF",
+            3,
+            2,
+            vec![0.0, 0.0, 3.0, 4.0, 0.0, 0.0],
+        ));
+        let d = Descriptors::from_frame(&stft, 10.0).unwrap();
+        assert_eq!(d.centroid, 10.0);
+
+        let mut trc = FrameData::new("1TRC", 0.0, 0);
+        trc.matrices.push(MatrixData::new(
+            "1TRC",
+            2,
+            4,
+            vec![1.0, 100.0, 1.0, 0.0, 2.0, 300.0, 1.0, 0.0],
+        ));
+        assert_eq!(Descriptors::from_frame(&trc, 1.0).unwrap().centroid, 200.0);
+
+        assert!(Descriptors::from_frame(&FrameData::new("1FQ0", 0.0, 0), 1.0).is_none());
+    }
+}
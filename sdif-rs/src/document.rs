@@ -0,0 +1,139 @@
+//! Whole SDIF files as in-memory documents.
+//!
+//! An [`SdifDocument`] holds a file's metadata and all its frames as owned
+//! values. With the `serde` feature, it can be serialized to any format
+//! serde supports, such as JSON, YAML or MessagePack, and written back to
+//! an SDIF file after deserializing, which makes SDIF data easy to pass
+//! through web APIs and configuration-driven pipelines.
+//!
+//! Matrices are serialized like [`MatrixData`](crate::MatrixData), with
+//! their data type and their values as f64, so integer data round-trips
+//! exactly.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "json")]
+//! # fn example() -> sdif_rs::Result<()> {
+//! use sdif_rs::document::SdifDocument;
+//! use sdif_rs::SdifFile;
+//!
+//! let document = SdifFile::open("analysis.sdif")?.to_document()?;
+//! let json = serde_json::to_string(&document)?;
+//!
+//! let document: SdifDocument = serde_json::from_str(&json)?;
+//! document.write_sdif("restored.sdif")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use crate::error::Result;
+use crate::file::SdifFile;
+use crate::frame_data::OwnedFrame;
+use crate::schema::{FrameTypeSchema, MatrixTypeSchema, Schema};
+use crate::writer::SdifWriter;
+
+/// Everything in an SDIF file apart from its frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileMetadata {
+    /// NVT tables in file order.
+    pub nvts: Vec<IndexMap<String, String>>,
+
+    /// Matrix type declarations.
+    pub matrix_types: Vec<MatrixTypeSchema>,
+
+    /// Frame type declarations.
+    pub frame_types: Vec<FrameTypeSchema>,
+}
+
+impl FileMetadata {
+    /// Read the NVTs and type declarations of a file.
+    ///
+    /// NVT entries are sorted by key, since their order is not kept when
+    /// reading.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SdifFile::describe_types()`].
+    pub fn from_file(file: &SdifFile) -> Result<Self> {
+        let nvts = file
+            .nvts()
+            .iter()
+            .map(|nvt| {
+                let mut table: IndexMap<String, String> =
+                    nvt.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                table.sort_keys();
+                table
+            })
+            .collect();
+
+        let schema = file.describe_types()?;
+        Ok(FileMetadata {
+            nvts,
+            matrix_types: schema.matrix_types,
+            frame_types: schema.frame_types,
+        })
+    }
+
+    /// Create a new SDIF file with exactly these NVTs and types, ready for
+    /// frames.
+    ///
+    /// No writer entry is added to the NVTs, so reading the file back
+    /// gives the same metadata.
+    ///
+    /// # Errors
+    ///
+    /// Any error from creating the file or declaring its types.
+    pub fn create(&self, path: impl AsRef<Path>) -> Result<SdifWriter> {
+        let mut builder = SdifFile::builder().create(path)?.omit_writer();
+        for nvt in &self.nvts {
+            builder = builder.add_nvt(nvt.iter().map(|(k, v)| (k.as_str(), v.as_str())))?;
+        }
+
+        let schema = Schema {
+            nvt: IndexMap::new(),
+            matrix_types: self.matrix_types.clone(),
+            frame_types: self.frame_types.clone(),
+        };
+        builder.with_schema(&schema)?.build()
+    }
+}
+
+/// The full contents of an SDIF file.
+///
+/// Created by [`SdifFile::to_document()`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SdifDocument {
+    /// NVTs and type declarations.
+    pub metadata: FileMetadata,
+
+    /// Frames in file order.
+    pub frames: Vec<OwnedFrame>,
+}
+
+impl SdifDocument {
+    /// Write the document to a new SDIF file.
+    ///
+    /// Returns the number of frames written.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`](crate::Error::InvalidState) if a frame has
+    ///   no matrices
+    /// - Any error from creating or writing the file
+    pub fn write_sdif(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let mut writer = self.metadata.create(path)?;
+        for frame in &self.frames {
+            writer.write_frame_data(&frame.to_data())?;
+        }
+        writer.close()?;
+
+        Ok(self.frames.len())
+    }
+}
@@ -0,0 +1,628 @@
+//! Line-oriented text dumps of SDIF files.
+//!
+//! A dump holds everything a file carries (NVTs, type declarations and
+//! frames) as plain text with one matrix row per line, so SDIF files can
+//! be diffed, reviewed and kept in version control. Loading a dump with
+//! [`Dump::parse()`] and writing it with [`Dump::write_sdif()`] gives back
+//! the same file.
+//!
+//! ```text
+//! SDIF-DUMP 1
+//! mtype 1TRC Index Frequency Amplitude Phase
+//! ftype 1TRC 1TRC SinusoidalTracks
+//! frame 1TRC 0 0.01
+//!   matrix 1TRC float64 2x4
+//!     1 440 0.5 0
+//!     2 880 0.25 1.5707963267948966
+//! ```
+//!
+//! - `nvt` starts a table; its entries follow, indented, with a tab
+//!   between key and value. Backslashes, tabs and line breaks in entries
+//!   are escaped as `\\`, `\t`, `\n` and `\r`, and leading and trailing
+//!   spaces as `\s`.
+//! - `mtype` declares a matrix type with its column names.
+//! - `ftype` declares a frame type with pairs of component signature and
+//!   name.
+//! - `frame` gives signature, stream ID and time, followed by its
+//!   matrices. Each `matrix` line gives signature, data type and shape,
+//!   followed by its rows. Text matrices have a single line instead: `|`
+//!   and the text, escaped like NVT entries, with NUL as `\0`.
+//!
+//! Numbers are written in the shortest form that parses back to the same
+//! value in the matrix's data type, so round trips are exact. Blank lines
+//! and lines starting with `#` are ignored.
+//!
+//! Frames without matrices can't be written back and are rejected when
+//! dumping a file.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{dump::Dump, SdifFile};
+//!
+//! let dump = Dump::from_file(&SdifFile::open("analysis.sdif")?)?;
+//! std::fs::write("analysis.sdif.txt", dump.to_string())?;
+//!
+//! let text = std::fs::read_to_string("analysis.sdif.txt")?;
+//! Dump::parse(&text)?.write_sdif("restored.sdif")?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use crate::data_type::DataType;
+use crate::document::FileMetadata;
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame::Frame;
+use crate::frame_data::{FrameData, MatrixData};
+use crate::schema::{ComponentSchema, FrameTypeSchema, MatrixTypeSchema};
+
+/// First line of every dump, naming the format version.
+pub const HEADER: &str = "SDIF-DUMP 1";
+
+/// The full contents of an SDIF file, as written to and read from text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dump {
+    /// NVT tables in file order.
+    pub nvts: Vec<IndexMap<String, String>>,
+
+    /// Matrix type declarations.
+    pub matrix_types: Vec<MatrixTypeSchema>,
+
+    /// Frame type declarations.
+    pub frame_types: Vec<FrameTypeSchema>,
+
+    /// Frames in file order.
+    pub frames: Vec<FrameData>,
+}
+
+impl Dump {
+    /// Read the contents of a file.
+    ///
+    /// NVT entries are sorted by key, since their order is not kept when
+    /// reading. Frames are read from the current position to the end.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if a frame has no matrices, since SDIF
+    ///   writers can't write such frames back
+    /// - [`Error::DataTypeMismatch`] if a matrix's data type is unknown
+    /// - Any error from reading the type declarations or frames
+    pub fn from_file(file: &SdifFile) -> Result<Self> {
+        let metadata = FileMetadata::from_file(file)?;
+        let frames = file
+            .frames()
+            .map(|frame| read_frame(frame?))
+            .collect::<Result<_>>()?;
+
+        Ok(Dump {
+            nvts: metadata.nvts,
+            matrix_types: metadata.matrix_types,
+            frame_types: metadata.frame_types,
+            frames,
+        })
+    }
+
+    /// Parse a dump.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] with the line number if the text is not a
+    ///   dump or a line is malformed
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            // An NVT entry with an empty key and value is just a tab
+            .filter(|(_, line)| !line.trim_matches(' ').is_empty() && !line.starts_with('#'));
+
+        match lines.next() {
+            Some((_, line)) if line.trim_end() == HEADER => {}
+            _ => {
+                return Err(Error::invalid_format(format!(
+                    "Not an SDIF dump: expected {:?} on the first line",
+                    HEADER
+                )))
+            }
+        }
+
+        let mut dump = Dump::default();
+        let mut block = Block::None;
+
+        for (number, line) in lines {
+            let at_line =
+                |message: String| Error::invalid_format(format!("Line {}: {}", number, message));
+
+            if line.starts_with(char::is_whitespace) {
+                dump.parse_block_line(&mut block, line)
+                    .map_err(|e| at_line(e.to_string()))?;
+                continue;
+            }
+
+            if let Block::Frame = block {
+                dump.check_last_matrix()?;
+            }
+
+            let mut tokens = line.split_whitespace();
+            block = match tokens.next() {
+                Some("nvt") => {
+                    dump.nvts.push(IndexMap::new());
+                    Block::Nvt
+                }
+                Some("mtype") => {
+                    let signature =
+                        parse_signature(tokens.next()).map_err(|e| at_line(e.to_string()))?;
+                    dump.matrix_types.push(MatrixTypeSchema {
+                        signature,
+                        columns: tokens.map(str::to_string).collect(),
+                    });
+                    Block::None
+                }
+                Some("ftype") => {
+                    let signature =
+                        parse_signature(tokens.next()).map_err(|e| at_line(e.to_string()))?;
+                    let tokens: Vec<&str> = tokens.collect();
+                    if tokens.len() % 2 != 0 {
+                        return Err(at_line(
+                            "frame type components must be signature and name pairs".to_string(),
+                        ));
+                    }
+                    let components = tokens
+                        .chunks(2)
+                        .map(|pair| {
+                            Ok(ComponentSchema {
+                                signature: parse_signature(Some(pair[0]))?,
+                                name: pair[1].to_string(),
+                            })
+                        })
+                        .collect::<Result<_>>()
+                        .map_err(|e| at_line(e.to_string()))?;
+                    dump.frame_types.push(FrameTypeSchema {
+                        signature,
+                        components,
+                    });
+                    Block::None
+                }
+                Some("frame") => {
+                    let frame = parse_frame(tokens).map_err(|e| at_line(e.to_string()))?;
+                    dump.frames.push(frame);
+                    Block::Frame
+                }
+                _ => return Err(at_line(format!("unexpected line {:?}", line))),
+            };
+        }
+
+        dump.check_last_matrix()?;
+
+        Ok(dump)
+    }
+
+    /// Write the contents to a new SDIF file.
+    ///
+    /// Returns the number of frames written. The file gets exactly the NVTs
+    /// in the dump, without a writer entry of its own, so dumping it again
+    /// gives the same text.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if a frame has no matrices
+    /// - [`Error::DataTypeMismatch`] if a matrix's data type is unknown
+    /// - Any error from creating or writing the file
+    pub fn write_sdif(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let metadata = FileMetadata {
+            nvts: self.nvts.clone(),
+            matrix_types: self.matrix_types.clone(),
+            frame_types: self.frame_types.clone(),
+        };
+        let mut writer = metadata.create(path)?;
+
+        for frame in &self.frames {
+            writer.write_frame_data(frame)?;
+        }
+        writer.close()?;
+
+        Ok(self.frames.len())
+    }
+
+    /// Handle an indented line belonging to the current block.
+    fn parse_block_line(&mut self, block: &mut Block, line: &str) -> Result<()> {
+        match block {
+            Block::None => Err(Error::invalid_format("indented line outside a block")),
+            Block::Nvt => {
+                // Only the indentation goes: the tab and any spaces around
+                // it belong to the entry
+                let line = line.trim_start_matches(' ');
+                let (key, value) = line.split_once('\t').ok_or_else(|| {
+                    Error::invalid_format("NVT entry needs a tab between key and value")
+                })?;
+                let table = self.nvts.last_mut().expect("NVT block has a table");
+                table.insert(unescape(key)?, unescape(value)?);
+                Ok(())
+            }
+            Block::Frame => {
+                let line = line.trim();
+                let frame = self.frames.last_mut().expect("frame block has a frame");
+                if let Some(header) = line.strip_prefix("matrix ") {
+                    if let Some(last) = frame.matrices.last() {
+                        check_rows(last)?;
+                    }
+                    frame.matrices.push(parse_matrix(header)?);
+                    return Ok(());
+                }
+
+                let matrix = frame
+                    .matrices
+                    .last_mut()
+                    .ok_or_else(|| Error::invalid_format("matrix row before any matrix"))?;
+                if matrix.data_type == DataType::Text {
+                    return parse_text(matrix, line);
+                }
+                let row = line
+                    .split_whitespace()
+                    .map(|value| parse_value(value, matrix.data_type))
+                    .collect::<Result<Vec<_>>>()?;
+                if row.len() != matrix.cols {
+                    return Err(Error::invalid_format(format!(
+                        "row has {} values, matrix has {} columns",
+                        row.len(),
+                        matrix.cols
+                    )));
+                }
+                if matrix.data.len() >= matrix.rows * matrix.cols {
+                    return Err(Error::invalid_format(format!(
+                        "matrix has more than {} rows",
+                        matrix.rows
+                    )));
+                }
+                matrix.data.extend(row);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that the final matrix of the dump got all its rows.
+    fn check_last_matrix(&self) -> Result<()> {
+        match self.frames.last().and_then(|f| f.matrices.last()) {
+            Some(matrix) => check_rows(matrix),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Dump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+
+        for nvt in &self.nvts {
+            writeln!(f, "nvt")?;
+            for (key, value) in nvt {
+                writeln!(f, "  {}\t{}", escape(key), escape(value))?;
+            }
+        }
+
+        for mtype in &self.matrix_types {
+            write!(f, "mtype {}", mtype.signature)?;
+            for column in &mtype.columns {
+                write!(f, " {}", column)?;
+            }
+            writeln!(f)?;
+        }
+
+        for ftype in &self.frame_types {
+            write!(f, "ftype {}", ftype.signature)?;
+            for component in &ftype.components {
+                write!(f, " {} {}", component.signature, component.name)?;
+            }
+            writeln!(f)?;
+        }
+
+        for frame in &self.frames {
+            writeln!(
+                f,
+                "frame {} {} {}",
+                frame.signature, frame.stream_id, frame.time
+            )?;
+            for matrix in &frame.matrices {
+                writeln!(
+                    f,
+                    "  matrix {} {} {}x{}",
+                    matrix.signature, matrix.data_type, matrix.rows, matrix.cols
+                )?;
+                if matrix.data_type == DataType::Text {
+                    writeln!(f, "    |{}", escape(&text_of(matrix)))?;
+                    continue;
+                }
+                for row in matrix.rows() {
+                    write!(f, "   ")?;
+                    for &value in row {
+                        // Widened f32 values print exactly as f32
+                        if matrix.data_type == DataType::Float4 {
+                            write!(f, " {}", value as f32)?;
+                        } else {
+                            write!(f, " {}", value)?;
+                        }
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a frame, keeping integer and text values exact.
+fn read_frame(mut frame: Frame<'_>) -> Result<FrameData> {
+    if frame.num_matrices() == 0 {
+        return Err(Error::invalid_format(format!(
+            "{} frame at {}s has no matrices and can't be dumped",
+            frame.signature(),
+            frame.time()
+        )));
+    }
+
+    let mut data = FrameData::new(frame.signature(), frame.time(), frame.stream_id());
+    for matrix in frame.matrices() {
+        let matrix = matrix?;
+        let (rows, cols) = matrix.shape();
+        let mut matrix_data = MatrixData::new(matrix.signature(), rows, cols, Vec::new());
+        matrix_data.data_type = matrix.data_type();
+        matrix_data.columns = matrix.columns();
+
+        matrix_data.data = match matrix_data.data_type {
+            DataType::Float4 | DataType::Float8 => matrix.data_f64()?,
+            DataType::Text => {
+                // Trailing NUL padding was removed; put it back
+                let mut bytes = matrix.data_text()?.into_bytes();
+                bytes.resize(rows * cols, 0);
+                bytes.into_iter().map(f64::from).collect()
+            }
+            _ => matrix.data_as::<f64>()?,
+        };
+        data.matrices.push(matrix_data);
+    }
+    Ok(data)
+}
+
+/// Get the string held by a text matrix, without trailing NUL padding.
+fn text_of(matrix: &MatrixData) -> String {
+    let bytes: Vec<u8> = matrix.data.iter().map(|&value| value as u8).collect();
+    let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |last| last + 1);
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Parse the `|` line holding the string of a text matrix.
+fn parse_text(matrix: &mut MatrixData, line: &str) -> Result<()> {
+    let text = line
+        .strip_prefix('|')
+        .ok_or_else(|| Error::invalid_format("text matrix needs a line starting with |"))?;
+    if !matrix.data.is_empty() {
+        return Err(Error::invalid_format("text matrix has more than one line"));
+    }
+
+    let mut bytes = unescape(text)?.into_bytes();
+    let len = matrix.rows * matrix.cols;
+    if bytes.len() > len {
+        return Err(Error::invalid_format(format!(
+            "text has {} bytes, matrix holds {}",
+            bytes.len(),
+            len
+        )));
+    }
+    bytes.resize(len, 0);
+    matrix.data = bytes.into_iter().map(f64::from).collect();
+    Ok(())
+}
+
+/// The kind of block indented lines belong to while parsing.
+enum Block {
+    None,
+    Nvt,
+    Frame,
+}
+
+/// Parse the fields of a `frame` line after the keyword.
+fn parse_frame<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<FrameData> {
+    let signature = parse_signature(tokens.next())?;
+    let stream_id = tokens
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| Error::invalid_format("frame needs a stream ID"))?;
+    let time = tokens
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| Error::invalid_format("frame needs a time"))?;
+    if tokens.next().is_some() {
+        return Err(Error::invalid_format("unexpected text after frame time"));
+    }
+    Ok(FrameData::new(signature, time, stream_id))
+}
+
+/// Parse the fields of a `matrix` line after the keyword.
+fn parse_matrix(header: &str) -> Result<MatrixData> {
+    let tokens: Vec<&str> = header.split_whitespace().collect();
+    let [sig, data_type, shape] = tokens[..] else {
+        return Err(Error::invalid_format(
+            "matrix needs a signature, data type and shape",
+        ));
+    };
+    let (rows, cols) = shape
+        .split_once('x')
+        .and_then(|(r, c)| Some((r.parse().ok()?, c.parse().ok()?)))
+        .ok_or_else(|| Error::invalid_format(format!("invalid matrix shape {:?}", shape)))?;
+
+    let mut matrix = MatrixData::new(parse_signature(Some(sig))?, rows, cols, Vec::new());
+    matrix.data_type = data_type.parse()?;
+    Ok(matrix)
+}
+
+/// Parse one matrix value, rounding it to `data_type`.
+fn parse_value(text: &str, data_type: DataType) -> Result<f64> {
+    let invalid = || Error::invalid_format(format!("invalid {} value {:?}", data_type, text));
+    if data_type == DataType::Float4 {
+        text.parse::<f32>().map(f64::from).map_err(|_| invalid())
+    } else {
+        text.parse::<f64>().map_err(|_| invalid())
+    }
+}
+
+/// Check that a matrix got as many rows as its shape says.
+fn check_rows(matrix: &MatrixData) -> Result<()> {
+    let found = matrix.data.len() / matrix.cols.max(1);
+    if matrix.cols > 0 && found != matrix.rows {
+        return Err(Error::invalid_format(format!(
+            "{} matrix has {} rows, expected {}",
+            matrix.signature, found, matrix.rows
+        )));
+    }
+    Ok(())
+}
+
+/// Check a signature token.
+fn parse_signature(token: Option<&str>) -> Result<String> {
+    match token {
+        Some(sig) if sig.len() == 4 && sig.is_ascii() => Ok(sig.to_string()),
+        Some(sig) => Err(Error::invalid_format(format!(
+            "invalid signature {:?}",
+            sig
+        ))),
+        None => Err(Error::invalid_format("missing signature")),
+    }
+}
+
+/// Escape an NVT key or value, or the text of a matrix, for a single line.
+///
+/// Leading and trailing spaces are escaped too, so that neither the
+/// indentation nor editors stripping trailing whitespace change them.
+fn escape(text: &str) -> String {
+    let body = text.trim_matches(' ');
+    let leading = text.len() - text.trim_start_matches(' ').len();
+    let trailing = text.len() - leading - body.len();
+
+    let mut escaped = String::with_capacity(text.len());
+    escaped.push_str(&"\\s".repeat(leading));
+    for c in body.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push_str(&"\\s".repeat(trailing));
+    escaped
+}
+
+/// Undo [`escape()`].
+fn unescape(text: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('s') => unescaped.push(' '),
+            Some('0') => unescaped.push('\0'),
+            other => {
+                return Err(Error::invalid_format(format!(
+                    "invalid escape \\{}",
+                    other.map(String::from).unwrap_or_default()
+                )))
+            }
+        }
+    }
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Dump {
+        let mut nvt = IndexMap::new();
+        nvt.insert("Creator".to_string(), "sdif-rs".to_string());
+        nvt.insert("Notes".to_string(), "tab\there\nback\\slash".to_string());
+        nvt.insert("Empty".to_string(), String::new());
+        nvt.insert(" Padded ".to_string(), "  two spaces  ".to_string());
+
+        let mut frame = FrameData::new("1TRC", 0.01, 3);
+        frame.matrices.push(MatrixData::new(
+            "1TRC",
+            2,
+            4,
+            vec![1.0, 440.0, 0.5, 0.0, 2.0, 880.0, -0.0, std::f64::consts::PI],
+        ));
+        let mut f32_matrix = MatrixData::new("1FQ0", 1, 2, vec![f64::from(0.1f32), 1.0]);
+        f32_matrix.data_type = DataType::Float4;
+        frame.matrices.push(f32_matrix);
+
+        Dump {
+            nvts: vec![nvt],
+            matrix_types: vec![MatrixTypeSchema {
+                signature: "1TRC".to_string(),
+                columns: vec!["Index".to_string(), "Frequency".to_string()],
+            }],
+            frame_types: vec![FrameTypeSchema {
+                signature: "1TRC".to_string(),
+                components: vec![ComponentSchema {
+                    signature: "1TRC".to_string(),
+                    name: "SinusoidalTracks".to_string(),
+                }],
+            }],
+            frames: vec![frame, FrameData::new("1FQ0", 1.0 / 3.0, 0)],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dump = sample();
+        let text = dump.to_string();
+        assert!(text.contains("\n    0.1 1\n"));
+        assert!(text.contains("tab\\there\\nback\\\\slash"));
+        assert!(text.contains("\n  Empty\t\n"));
+        assert!(text.contains("\n  \\sPadded\\s\t\\s\\stwo spaces\\s\\s\n"));
+
+        let parsed = Dump::parse(&text).unwrap();
+        assert_eq!(parsed, dump);
+        assert!(parsed.frames[0].matrices[0].data[6].is_sign_negative());
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn test_parse_ignores_comments() {
+        let text =
+            "SDIF-DUMP 1\n# a comment\n\nframe 1FQ0 0 0.5\n  matrix 1FQ0 float64 1x2\n    220 1\n";
+        let dump = Dump::parse(text).unwrap();
+        assert_eq!(dump.frames[0].matrices[0].data, vec![220.0, 1.0]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Dump::parse("frame 1FQ0 0 0").is_err());
+
+        let err = Dump::parse("SDIF-DUMP 1\nframe 1FQ0 0 0\n  matrix 1FQ0 float64 2x2\n    1 2\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("1 rows, expected 2"));
+
+        let err = Dump::parse("SDIF-DUMP 1\nframe 1FQ0 0 0\n  matrix 1FQ0 float64 1x2\n    1\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("Line 4"));
+
+        assert!(Dump::parse("SDIF-DUMP 1\nmtype TOOLONG A\n").is_err());
+        assert!(Dump::parse("SDIF-DUMP 1\nnvt\n  no-tab\n").is_err());
+        assert!(Dump::parse("SDIF-DUMP 1\nnvt\n  bad\\q\tx\n").is_err());
+    }
+}
@@ -11,6 +11,26 @@ use thiserror::Error;
 /// Result type alias for SDIF operations.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Broad classes of [`Error`]s, returned by [`Error::category()`].
+///
+/// Applications can decide how to react to a failure by its category
+/// rather than by matching every variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The operating system or file system failed (missing file, full
+    /// disk, permissions). Retrying may succeed.
+    Io,
+
+    /// The data in the file is malformed or truncated.
+    Format,
+
+    /// The API was called incorrectly or with invalid arguments.
+    Usage,
+
+    /// The SDIF library or a required resource is unavailable.
+    Resource,
+}
+
 /// Errors that can occur during SDIF operations.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -22,6 +42,25 @@ pub enum Error {
     #[error("Failed to initialize SDIF library")]
     InitFailed,
 
+    /// The SDIF library was initialized without the types it was asked to
+    /// load, see [`init::poison_reason()`].
+    ///
+    /// [`init::poison_reason()`]: crate::init::poison_reason
+    #[error("SDIF library initialization is poisoned: {reason}")]
+    InitPoisoned {
+        /// What went wrong and how to fix it.
+        reason: String,
+    },
+
+    /// The SDIF library couldn't be loaded, see [`init::is_available()`].
+    ///
+    /// [`init::is_available()`]: crate::init::is_available
+    #[error("SDIF library not available: {reason}")]
+    LibraryUnavailable {
+        /// Why the library couldn't be loaded.
+        reason: String,
+    },
+
     /// The SDIF file could not be opened.
     #[error("Failed to open SDIF file: {path}")]
     OpenFailed {
@@ -71,10 +110,20 @@ pub enum Error {
     },
 
     /// Failed to read data from the file.
-    #[error("Read error: {message}")]
+    ///
+    /// Errors raised while iterating frames carry the location of the
+    /// failure, so that reports about corrupt files point at the frame.
+    #[error("Read error: {message}{}", location(.byte_offset, .frame_index, .signature))]
     ReadError {
         /// Description of the read error.
         message: String,
+        /// Position in the file where reading failed, if known.
+        byte_offset: Option<u64>,
+        /// Index of the frame being read, counted from where frame
+        /// iteration started, if known.
+        frame_index: Option<usize>,
+        /// Signature of the frame being read, if known.
+        signature: Option<String>,
     },
 
     /// End of file reached unexpectedly.
@@ -106,6 +155,55 @@ pub enum Error {
         /// Previous time value.
         previous: f64,
     },
+
+    /// A frame time is too far from the nearest point of the time grid.
+    #[error("Frame time {time} deviates from grid point {nearest} by more than {tolerance}")]
+    TimeOffGrid {
+        /// Requested frame time.
+        time: f64,
+        /// Nearest grid point.
+        nearest: f64,
+        /// Maximum allowed deviation.
+        tolerance: f64,
+    },
+
+    /// A frame repeats the time, stream and signature of a frame already
+    /// written.
+    #[error("Duplicate {signature} frame at {time}s in stream {stream_id}")]
+    DuplicateFrame {
+        /// Frame time.
+        time: f64,
+        /// Stream ID of the frame.
+        stream_id: u32,
+        /// Frame signature.
+        signature: String,
+    },
+
+    /// A stream name was not declared before the file was built.
+    #[error("Unknown stream name: {name}")]
+    UnknownStream {
+        /// Requested stream name.
+        name: String,
+    },
+
+    /// A frame position is past the last frame of the file.
+    #[error("Frame {index} out of range, the file has {len} frames")]
+    FrameOutOfRange {
+        /// Requested frame position.
+        index: usize,
+        /// Number of frames in the file.
+        len: usize,
+    },
+
+    /// JSON serialization error.
+    #[cfg(feature = "json")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// TOML parse error.
+    #[cfg(feature = "toml")]
+    #[error("TOML error: {0}")]
+    Toml(#[from] toml::de::Error),
 }
 
 impl Error {
@@ -142,9 +240,111 @@ impl Error {
         }
     }
 
-    /// Create a ReadError.
+    /// Create a ReadError without location.
     pub fn read_error(message: impl Into<String>) -> Self {
-        Self::ReadError { message: message.into() }
+        Self::ReadError {
+            message: message.into(),
+            byte_offset: None,
+            frame_index: None,
+            signature: None,
+        }
+    }
+
+    /// Add location details to a ReadError.
+    ///
+    /// Only details the error doesn't have yet are set; other errors are
+    /// returned unchanged.
+    pub fn with_location(
+        mut self,
+        byte_offset: Option<u64>,
+        frame_index: Option<usize>,
+        signature: Option<String>,
+    ) -> Self {
+        if let Self::ReadError {
+            byte_offset: offset,
+            frame_index: index,
+            signature: sig,
+            ..
+        } = &mut self
+        {
+            *offset = offset.or(byte_offset);
+            *index = index.or(frame_index);
+            if sig.is_none() {
+                *sig = signature;
+            }
+        }
+        self
+    }
+
+    /// Get the broad category of this error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sdif_rs::{Error, ErrorCategory};
+    ///
+    /// let err = Error::invalid_signature("TOOLONG");
+    /// assert_eq!(err.category(), ErrorCategory::Usage);
+    /// ```
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::Io(_) | Self::OpenFailed { .. } => ErrorCategory::Io,
+
+            Self::InvalidFormat { .. } | Self::ReadError { .. } | Self::UnexpectedEof => {
+                ErrorCategory::Format
+            }
+            #[cfg(feature = "json")]
+            Self::Json(_) => ErrorCategory::Format,
+            #[cfg(feature = "toml")]
+            Self::Toml(_) => ErrorCategory::Format,
+
+            Self::InvalidSignature { .. }
+            | Self::InvalidState { .. }
+            | Self::CString(_)
+            | Self::DataTypeMismatch { .. }
+            | Self::InvalidDimensions { .. }
+            | Self::FileClosed
+            | Self::EmptyFrame
+            | Self::TimeNotIncreasing { .. }
+            | Self::TimeOffGrid { .. }
+            | Self::DuplicateFrame { .. }
+            | Self::UnknownStream { .. }
+            | Self::FrameOutOfRange { .. } => ErrorCategory::Usage,
+
+            Self::InitFailed
+            | Self::InitPoisoned { .. }
+            | Self::LibraryUnavailable { .. }
+            | Self::NullPointer { .. } => ErrorCategory::Resource,
+        }
+    }
+
+    /// Check whether the failed operation might succeed if retried.
+    ///
+    /// True for I/O errors of a transient kind, such as interrupted
+    /// system calls or timeouts.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Self::Io(e) => matches!(
+                e.kind(),
+                io::ErrorKind::Interrupted
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+            ),
+            _ => false,
+        }
+    }
+
+    /// Check whether the error means the file is malformed or truncated.
+    ///
+    /// Such errors are not fixed by retrying; a batch job would typically
+    /// skip the file and move on.
+    pub fn is_corruption(&self) -> bool {
+        self.category() == ErrorCategory::Format
+    }
+
+    /// Check whether the error is caused by misuse of the API.
+    pub fn is_usage(&self) -> bool {
+        self.category() == ErrorCategory::Usage
     }
 
     /// Create a TimeNotIncreasing error.
@@ -153,6 +353,41 @@ impl Error {
     }
 }
 
+impl From<sdif_sys::write::WriteError> for Error {
+    fn from(e: sdif_sys::write::WriteError) -> Self {
+        match e {
+            sdif_sys::write::WriteError::Failed(_) => {
+                Error::Io(io::Error::new(io::ErrorKind::Other, e.to_string()))
+            }
+            _ => Error::invalid_format(e.to_string()),
+        }
+    }
+}
+
+/// Describe where a read error happened, e.g. " (frame 3 '1TRC', byte 1024)".
+fn location(
+    byte_offset: &Option<u64>,
+    frame_index: &Option<usize>,
+    signature: &Option<String>,
+) -> String {
+    let mut parts = Vec::new();
+    match (frame_index, signature) {
+        (Some(index), Some(sig)) => parts.push(format!("frame {} '{}'", index, sig)),
+        (Some(index), None) => parts.push(format!("frame {}", index)),
+        (None, Some(sig)) => parts.push(format!("frame '{}'", sig)),
+        (None, None) => {}
+    }
+    if let Some(offset) = byte_offset {
+        parts.push(format!("byte {}", offset));
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", parts.join(", "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +401,52 @@ mod tests {
         assert!(err.to_string().contains("cannot read matrix"));
     }
 
+    #[test]
+    fn test_read_error_location() {
+        let err = Error::read_error("Failed to read matrix row");
+        assert_eq!(err.to_string(), "Read error: Failed to read matrix row");
+
+        let err = err.with_location(Some(1024), Some(3), Some("1TRC".to_string()));
+        assert_eq!(
+            err.to_string(),
+            "Read error: Failed to read matrix row (frame 3 '1TRC', byte 1024)"
+        );
+
+        // Existing details are kept
+        let err = err.with_location(Some(0), None, None);
+        assert!(matches!(err, Error::ReadError { byte_offset: Some(1024), .. }));
+
+        let err = Error::read_error("Seek failed").with_location(Some(8), None, None);
+        assert_eq!(err.to_string(), "Read error: Seek failed (byte 8)");
+
+        let err = Error::invalid_state("closed").with_location(Some(8), None, None);
+        assert!(matches!(err, Error::InvalidState { .. }));
+    }
+
+    #[test]
+    fn test_error_categories() {
+        assert_eq!(Error::open_failed("x.sdif").category(), ErrorCategory::Io);
+        assert_eq!(Error::InitFailed.category(), ErrorCategory::Resource);
+        let err = Error::LibraryUnavailable {
+            reason: "libsdif.so not found".to_string(),
+        };
+        assert_eq!(err.category(), ErrorCategory::Resource);
+        assert_eq!(err.to_string(), "SDIF library not available: libsdif.so not found");
+
+        let err = Error::read_error("Failed to read frame header");
+        assert!(err.is_corruption());
+        assert!(!err.is_recoverable());
+
+        let err = Error::invalid_state("Writer has been closed");
+        assert!(err.is_usage());
+        assert!(!err.is_corruption());
+
+        let err = Error::from(io::Error::new(io::ErrorKind::Interrupted, "signal"));
+        assert!(err.is_recoverable());
+        let err = Error::from(io::Error::new(io::ErrorKind::NotFound, "missing"));
+        assert!(!err.is_recoverable());
+    }
+
     #[test]
     fn test_error_from_io() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file not found");
@@ -0,0 +1,284 @@
+//! Fundamental frequency tracks and voicing segmentation.
+//!
+//! An [`F0Track`] holds the frequency and confidence of each 1FQ0 frame.
+//! [`segment_voicing()`] splits it into alternating voiced and unvoiced
+//! [`VoicingSegment`]s, the usual first step of speech and singing
+//! analysis, and [`write_markers()`] saves them as 1MRK markers that
+//! annotation tools can display next to the audio.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{f0, SdifFile};
+//!
+//! let file = SdifFile::open("voice.f0.sdif")?;
+//! let track = f0::F0Track::from_file(&file)?;
+//! let segments = f0::segment_voicing(&track, 0.5, 0.05);
+//! for segment in segments.iter().filter(|s| s.voiced) {
+//!     println!("voiced {:.3}s - {:.3}s", segment.start, segment.end);
+//! }
+//! f0::write_markers(&segments, "voice.voicing.sdif")?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::sonic_visualiser::{self, Annotation, CsvImportOptions};
+
+/// Signature of pitch matrices.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Marker labels of voiced and unvoiced segments.
+const VOICED_LABEL: &str = "voiced";
+const UNVOICED_LABEL: &str = "unvoiced";
+
+/// One fundamental frequency estimate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct F0Point {
+    /// Time in seconds.
+    pub time: f64,
+
+    /// Fundamental frequency in Hz, 0 or less when there is none.
+    pub frequency: f64,
+
+    /// Confidence of the estimate, usually between 0 and 1.
+    pub confidence: f64,
+}
+
+impl F0Point {
+    /// Check whether the point has a pitch with at least `threshold`
+    /// confidence.
+    pub fn is_voiced(&self, threshold: f64) -> bool {
+        self.frequency > 0.0 && self.confidence >= threshold
+    }
+}
+
+/// Fundamental frequency estimates in time order.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct F0Track {
+    /// Estimates in time order.
+    pub points: Vec<F0Point>,
+}
+
+impl F0Track {
+    /// Create an empty track.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the first row of every 1FQ0 matrix.
+    ///
+    /// Matrices without a `Confidence` column count as fully confident.
+    /// The file is read from its current position to the end.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if a 1FQ0 matrix has no `Frequency`
+    ///   column
+    /// - Any error from reading the frames
+    pub fn from_file(file: &SdifFile) -> Result<Self> {
+        let mut track = F0Track::new();
+
+        for frame in file.frames() {
+            let frame = frame?.into_data()?;
+            for matrix in frame
+                .matrices
+                .iter()
+                .filter(|m| m.signature == PITCH_SIGNATURE)
+            {
+                let frequency = matrix
+                    .find_column("Frequency")
+                    .ok_or_else(|| Error::invalid_format("1FQ0 matrix has no Frequency column"))?;
+                let confidence = matrix.find_column("Confidence");
+                if let Some(row) = matrix.row(0) {
+                    track.points.push(F0Point {
+                        time: frame.time,
+                        frequency: row[frequency],
+                        confidence: confidence.map_or(1.0, |c| row[c]),
+                    });
+                }
+            }
+        }
+
+        Ok(track)
+    }
+}
+
+/// A stretch of time that is either voiced or unvoiced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoicingSegment {
+    /// Start time in seconds.
+    pub start: f64,
+
+    /// End time in seconds.
+    pub end: f64,
+
+    /// Whether the segment has a pitch.
+    pub voiced: bool,
+}
+
+impl VoicingSegment {
+    /// Get the length of the segment in seconds.
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// Split a track into voiced and unvoiced segments.
+///
+/// A point is voiced when it has a positive frequency and a confidence of
+/// at least `threshold`. Each point lasts until the next one, and the last
+/// point as long as the one before it. Segments shorter than
+/// `min_duration` are absorbed by their neighbours: unvoiced gaps inside
+/// voiced stretches are bridged first, then remaining short voiced
+/// segments are dropped.
+///
+/// The segments cover the track without gaps, alternating between voiced
+/// and unvoiced.
+pub fn segment_voicing(track: &F0Track, threshold: f64, min_duration: f64) -> Vec<VoicingSegment> {
+    let points = &track.points;
+    let mut segments: Vec<VoicingSegment> = Vec::with_capacity(points.len());
+
+    for (i, point) in points.iter().enumerate() {
+        let end = match (points.get(i + 1), i.checked_sub(1)) {
+            (Some(next), _) => next.time,
+            (None, Some(previous)) => point.time + (point.time - points[previous].time),
+            (None, None) => point.time,
+        };
+        segments.push(VoicingSegment {
+            start: point.time,
+            end,
+            voiced: point.is_voiced(threshold),
+        });
+    }
+    merge(&mut segments);
+
+    let last = segments.len().saturating_sub(1);
+    for (i, segment) in segments.iter_mut().enumerate() {
+        if !segment.voiced && i > 0 && i < last && segment.duration() < min_duration {
+            segment.voiced = true;
+        }
+    }
+    merge(&mut segments);
+
+    for segment in &mut segments {
+        if segment.voiced && segment.duration() < min_duration {
+            segment.voiced = false;
+        }
+    }
+    merge(&mut segments);
+
+    segments
+}
+
+/// Write segments as 1MRK markers labelled `voiced` or `unvoiced`.
+///
+/// Each segment becomes a marker at its start with its duration, and a
+/// `Value` of 1 when voiced and 0 otherwise. See
+/// [`sonic_visualiser`](crate::sonic_visualiser) for the marker layout.
+///
+/// Returns the number of frames written.
+///
+/// # Errors
+///
+/// - Any error from creating or writing the file
+pub fn write_markers(segments: &[VoicingSegment], path: impl AsRef<Path>) -> Result<usize> {
+    let markers: Vec<Annotation> = segments
+        .iter()
+        .map(|segment| {
+            let (value, label) = if segment.voiced {
+                (1.0, VOICED_LABEL)
+            } else {
+                (0.0, UNVOICED_LABEL)
+            };
+            Annotation {
+                time: segment.start,
+                duration: segment.duration(),
+                value: Some(value),
+                label: Some(label.to_string()),
+            }
+        })
+        .collect();
+
+    sonic_visualiser::write_sdif(&markers, path, &CsvImportOptions::default())
+}
+
+/// Join neighbouring segments with the same voicing.
+fn merge(segments: &mut Vec<VoicingSegment>) {
+    segments.dedup_by(|next, previous| {
+        let same = next.voiced == previous.voiced;
+        if same {
+            previous.end = next.end;
+        }
+        same
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(confidences: &[f64]) -> F0Track {
+        F0Track {
+            points: confidences
+                .iter()
+                .enumerate()
+                .map(|(i, &confidence)| F0Point {
+                    time: i as f64 * 0.01,
+                    frequency: if confidence > 0.0 { 220.0 } else { 0.0 },
+                    confidence,
+                })
+                .collect(),
+        }
+    }
+
+    fn spans(segments: &[VoicingSegment]) -> Vec<(bool, i64, i64)> {
+        segments
+            .iter()
+            .map(|s| {
+                (
+                    s.voiced,
+                    (s.start * 100.0).round() as i64,
+                    (s.end * 100.0).round() as i64,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_segment_voicing() {
+        let track = track(&[0.0, 0.9, 0.8, 0.2, 0.9, 0.9, 0.0, 0.0]);
+        let segments = segment_voicing(&track, 0.5, 0.0);
+        assert_eq!(
+            spans(&segments),
+            [
+                (false, 0, 1),
+                (true, 1, 3),
+                (false, 3, 4),
+                (true, 4, 6),
+                (false, 6, 8)
+            ]
+        );
+        assert!((segments[1].duration() - 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_segment_voicing_min_duration() {
+        // The one-point gap is bridged, the lone voiced point dropped
+        let track = track(&[0.9, 0.9, 0.0, 0.9, 0.9, 0.0, 0.0, 0.9, 0.0, 0.0]);
+        let segments = segment_voicing(&track, 0.5, 0.015);
+        assert_eq!(spans(&segments), [(true, 0, 5), (false, 5, 10)]);
+    }
+
+    #[test]
+    fn test_segment_voicing_empty() {
+        assert!(segment_voicing(&F0Track::new(), 0.5, 0.1).is_empty());
+        let single = segment_voicing(&track(&[0.9]), 0.5, 0.0);
+        assert_eq!(spans(&single), [(true, 0, 0)]);
+    }
+}
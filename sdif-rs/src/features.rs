@@ -0,0 +1,280 @@
+//! Derived descriptors computed from analysis data.
+//!
+//! Feature extractors read a file once and produce one value per frame as
+//! a [`FeatureTrack`], which can be inspected directly or written to a new
+//! SDIF file as a stream of its own.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{features, SdifFile};
+//!
+//! let file = SdifFile::open("partials.sdif")?;
+//! let energy = features::energy_track(&file)?;
+//!
+//! for point in &energy.points {
+//!     println!("{:.3}s: {:.4}", point.time, point.value);
+//! }
+//! energy.write("energy.sdif")?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::schemas;
+
+/// Frame/matrix signatures holding partials.
+const PARTIAL_SIGNATURES: &[&str] = &["1TRC", "1HRM"];
+
+/// Signature of energy frames and matrices.
+pub const ENERGY_SIGNATURE: &str = "1NRG";
+
+/// Columns of energy matrices.
+pub const ENERGY_COLUMNS: &[&str] = &["Energy"];
+
+/// Signature of inharmonicity frames and matrices.
+pub const INHARMONICITY_SIGNATURE: &str = "1INH";
+
+/// Columns of inharmonicity matrices.
+pub const INHARMONICITY_COLUMNS: &[&str] = &["Inharmonicity"];
+
+/// Frame/matrix signature of harmonic partials.
+const HARMONIC_SIGNATURE: &str = "1HRM";
+
+/// Frame/matrix signature of fundamental frequency estimates.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Largest time difference between frames considered simultaneous.
+const TIME_TOLERANCE: f64 = 1e-9;
+
+/// One value of a feature track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeaturePoint {
+    /// Time of the frame the value was computed from.
+    pub time: f64,
+
+    /// Stream ID of the frame the value was computed from.
+    pub stream_id: u32,
+
+    /// Feature value.
+    pub value: f64,
+}
+
+/// A time series of one feature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureTrack {
+    /// Frame and matrix signature used when writing the track.
+    pub signature: String,
+
+    /// Column name used when writing the track.
+    pub column: String,
+
+    /// Values in file order.
+    pub points: Vec<FeaturePoint>,
+}
+
+impl FeatureTrack {
+    /// Create an empty track.
+    pub fn new(signature: impl Into<String>, column: impl Into<String>) -> Self {
+        FeatureTrack {
+            signature: signature.into(),
+            column: column.into(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Write the track to a new SDIF file.
+    ///
+    /// Each point becomes a frame holding a 1x1 matrix, with the point's
+    /// time and stream ID. The file declares the track's signature as a
+    /// matrix type with one column and a frame type with one component.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if the track's signature is invalid
+    /// - Any error from creating or writing the file
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let component = format!("{} {}", self.signature, self.column);
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type(&self.signature, &[self.column.as_str()])?
+            .add_frame_type(&self.signature, &[component.as_str()])?
+            .build()?;
+
+        for point in &self.points {
+            writer
+                .new_frame(&self.signature, point.time, point.stream_id)?
+                .add_matrix(&self.signature, 1, 1, &[point.value])?
+                .finish()?;
+        }
+
+        writer.close()
+    }
+}
+
+/// Compute the energy of each frame of partials.
+///
+/// The energy of a frame is the sum of squared amplitudes over all rows of
+/// its 1TRC and 1HRM matrices. Frames without partial matrices are left
+/// out. The track is written with the signature
+/// [`ENERGY_SIGNATURE`] and column [`ENERGY_COLUMNS`].
+///
+/// The file is read from its current position to the end.
+///
+/// # Errors
+///
+/// - Any error from reading the frames
+pub fn energy_track(file: &SdifFile) -> Result<FeatureTrack> {
+    let mut track = FeatureTrack::new(ENERGY_SIGNATURE, ENERGY_COLUMNS[0]);
+    let amplitude = column_index(schemas::TRC_COLUMNS, "Amplitude")?;
+
+    for frame in file.frames() {
+        let frame = frame?.into_data()?;
+
+        let mut partials = frame
+            .matrices
+            .iter()
+            .filter(|m| PARTIAL_SIGNATURES.contains(&m.signature.as_str()))
+            .peekable();
+        if partials.peek().is_none() {
+            continue;
+        }
+
+        let value = partials
+            .flat_map(|m| m.rows())
+            .filter_map(|row| row.get(amplitude))
+            .map(|a| a * a)
+            .sum();
+
+        track.points.push(FeaturePoint {
+            time: frame.time,
+            stream_id: frame.stream_id,
+            value,
+        });
+    }
+
+    Ok(track)
+}
+
+/// Estimate how far harmonic partials deviate from multiples of F0.
+///
+/// Each 1HRM frame is paired with the F0 of a 1FQ0 frame at the same time
+/// or, if there is none, the most recent F0 before it. Every partial is
+/// assigned the nearest harmonic number `n = round(f / f0)` (at least 1),
+/// and the frame's value is the mean relative deviation `|f - n * f0| /
+/// (n * f0)` over its partials: 0 for a perfectly harmonic frame.
+///
+/// Frames without a positive F0 to pair with, and frames without
+/// partials, are left out. The track is written with the signature
+/// [`INHARMONICITY_SIGNATURE`] and column [`INHARMONICITY_COLUMNS`].
+///
+/// The file is read from its current position to the end.
+///
+/// # Errors
+///
+/// - Any error from reading the frames
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::{features, SdifFile};
+///
+/// let file = SdifFile::open("piano.sdif")?;
+/// let track = features::inharmonicity(&file)?;
+/// let mean = track.points.iter().map(|p| p.value).sum::<f64>() / track.points.len() as f64;
+/// println!("Mean inharmonicity: {:.5}", mean);
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+pub fn inharmonicity(file: &SdifFile) -> Result<FeatureTrack> {
+    let mut track = FeatureTrack::new(INHARMONICITY_SIGNATURE, INHARMONICITY_COLUMNS[0]);
+    let frequency = column_index(schemas::HRM_COLUMNS, "Frequency")?;
+    let f0_column = column_index(schemas::FQ0_COLUMNS, "Frequency")?;
+
+    let mut f0: Option<f64> = None;
+
+    for slice in file.frames().group_by_time(TIME_TOLERANCE) {
+        let slice = slice?;
+
+        if let Some(value) = slice
+            .matrix(PITCH_SIGNATURE)
+            .and_then(|m| m.get(0, f0_column))
+        {
+            f0 = Some(value);
+        }
+        let Some(f0) = f0.filter(|&f0| f0 > 0.0) else {
+            continue;
+        };
+
+        for frame in slice
+            .frames
+            .iter()
+            .filter(|f| f.signature == HARMONIC_SIGNATURE)
+        {
+            let deviations: Vec<f64> = frame
+                .matrices
+                .iter()
+                .filter(|m| m.signature == HARMONIC_SIGNATURE)
+                .flat_map(|m| m.rows())
+                .filter_map(|row| row.get(frequency))
+                .map(|&f| harmonic_deviation(f, f0))
+                .collect();
+
+            if deviations.is_empty() {
+                continue;
+            }
+
+            track.points.push(FeaturePoint {
+                time: frame.time,
+                stream_id: frame.stream_id,
+                value: deviations.iter().sum::<f64>() / deviations.len() as f64,
+            });
+        }
+    }
+
+    Ok(track)
+}
+
+/// Get the relative deviation of `frequency` from the nearest harmonic of `f0`.
+fn harmonic_deviation(frequency: f64, f0: f64) -> f64 {
+    let harmonic = (frequency / f0).round().max(1.0) * f0;
+    (frequency - harmonic).abs() / harmonic
+}
+
+/// Find a column in a standard layout.
+fn column_index(columns: &[&str], name: &str) -> Result<usize> {
+    columns
+        .iter()
+        .position(|&c| c == name)
+        .ok_or_else(|| Error::invalid_format(format!("No {} column", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(column_index(schemas::TRC_COLUMNS, "Amplitude").unwrap(), 2);
+        assert_eq!(column_index(schemas::HRM_COLUMNS, "Amplitude").unwrap(), 2);
+        assert!(column_index(schemas::FQ0_COLUMNS, "Amplitude").is_err());
+    }
+
+    #[test]
+    fn test_harmonic_deviation() {
+        assert_eq!(harmonic_deviation(440.0, 220.0), 0.0);
+        assert!((harmonic_deviation(666.0, 220.0) - 6.0 / 660.0).abs() < 1e-12);
+        // Partials below F0 are compared with the first harmonic
+        assert!((harmonic_deviation(55.0, 220.0) - 0.75).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_new_track() {
+        let track = FeatureTrack::new(ENERGY_SIGNATURE, "Energy");
+        assert_eq!(track.signature, "1NRG");
+        assert!(track.points.is_empty());
+    }
+}
@@ -12,21 +12,211 @@
 //! # Ok::<(), sdif_rs::Error>(())
 //! ```
 
-use std::cell::Cell;
-use std::collections::HashMap;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
+use std::os::raw::c_int;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::marker::PhantomData;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use sdif_sys::{
-    SdifFClose, SdifFOpen, SdifFReadAllASCIIChunks, SdifFReadGeneralHeader,
-    SdifFileT, SdifFileModeET_eReadFile,
+    SdifCreateFrameType, SdifCreateHashTableIterator, SdifCreateMatrixType,
+    SdifDisableErrorOutput, SdifEnableErrorOutput, SdifFClose, SdifFColumnIsSelected,
+    SdifFCurrDataType, SdifFCurrFrameIsSelected, SdifFCurrMatrixIsSelected, SdifFCurrNbCol,
+    SdifFCurrOneRow, SdifFRowIsSelected, SdifFCurrSignature, SdifFGetFrameTypesTable,
+    SdifFGetMatrixTypesTable, SdifFGetPos, SdifFGetSignature, SdifFNameValueList, SdifFOpen,
+    SdifFSetPos, SdiffPosT, SdifFReadAllASCIIChunks, SdifFReadGeneralHeader, SdifGetMatrixType,
+    SdifHashTableIteratorGetNext, SdifHashTableIteratorIsNext, SdifKillHashTableIterator,
+    SdifListGetNext, SdifListInitLoop, SdifListIsNext, SdifMatrixTypeInsertTailColumnDef,
+    SdifNameValueT, SdifNameValueTableT, SdifPutFrameType, SdifPutMatrixType, SdifReInitOneRow,
+    SdifTestFrameType, SdifFileT, SdifFileModeET_eReadFile,
 };
+use sdif_sys::write;
 
+use crate::columns::{self, ColumnDef};
+use crate::document::{FileMetadata, SdifDocument};
 use crate::error::{Error, Result};
-use crate::frame::FrameIterator;
-use crate::init::ensure_initialized;
+use crate::frame::{
+    FilteredFrames, Frame, FrameFilter, FrameIterator, ReverseFrames, FRAME_SIZE_PREFIX,
+};
+use crate::history::{self, HistoryEntry};
+use crate::index::{Index, Rounding};
+use crate::init::initialize;
+use crate::metadata::{self, Version};
+use crate::schema::{c_str_or_empty, Schema};
+use crate::schemas;
+use crate::selection::Selection;
+use crate::signature::{signature_to_string, string_to_signature, Signature};
+use crate::source::{self, SourceAudio};
+use crate::streams;
+
+/// Options controlling how an SDIF file is read.
+///
+/// By default the reader is lenient: frames and matrices whose types are
+/// not declared in the file fall back to the library's predefined types,
+/// or to a generic type if no predefined one exists. Each fallback is
+/// recorded once in [`SdifFile::warnings()`].
+///
+/// A fresh `.sdifx` sidecar index next to the file is loaded automatically;
+/// see [`Index`].
+///
+/// Signatures used by non-standard writers can be mapped to standard ones
+/// with [`alias()`](Self::alias).
+///
+/// Reading can be restricted to part of the file with
+/// [`selection()`](Self::selection).
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::{ReadOptions, SdifFile};
+///
+/// // Reject files that use undeclared types
+/// let file = SdifFile::open_with_options("input.sdif", ReadOptions::new().strict(true))?;
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// Require every frame and matrix type to be declared in the file.
+    pub strict: bool,
+
+    /// Load the `.sdifx` sidecar index if it is present and up to date.
+    pub sidecar_index: bool,
+
+    /// Frame and matrix signatures read as other signatures, see
+    /// [`alias()`](Self::alias).
+    pub aliases: HashMap<String, String>,
+
+    /// Check that the padding after each matrix is zero, see
+    /// [`verify_padding()`](Self::verify_padding).
+    pub verify_padding: bool,
+
+    /// Part of the file to read, see [`selection()`](Self::selection).
+    pub selection: Option<Selection>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            strict: false,
+            sidecar_index: true,
+            aliases: HashMap::new(),
+            verify_padding: false,
+            selection: None,
+        }
+    }
+}
+
+impl ReadOptions {
+    /// Create options with default (lenient) values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require type declarations for every frame and matrix type used.
+    ///
+    /// In strict mode, reading a frame or matrix whose type is not declared
+    /// in the file's `1TYP` chunk returns [`Error::InvalidFormat`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Enable or disable automatic loading of the sidecar index.
+    pub fn sidecar_index(mut self, enabled: bool) -> Self {
+        self.sidecar_index = enabled;
+        self
+    }
+
+    /// Check that the padding bytes after each matrix are zero.
+    ///
+    /// The format requires zero padding and some readers reject anything
+    /// else, so this helps to vet files before passing them on. Each frame
+    /// is checked as it is read, including frames skipped without reading
+    /// their matrices. An offending frame is reported in
+    /// [`SdifFile::warnings()`], or as an [`Error::InvalidFormat`] in
+    /// strict mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::{ReadOptions, SdifFile};
+    ///
+    /// let options = ReadOptions::new().verify_padding(true);
+    /// let file = SdifFile::open_with_options("suspect.sdif", options)?;
+    /// let frames = file.frames().count();
+    /// for warning in file.warnings() {
+    ///     println!("{}", warning);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn verify_padding(mut self, enabled: bool) -> Self {
+        self.verify_padding = enabled;
+        self
+    }
+
+    /// Read frames and matrices with signature `from` as `to`.
+    ///
+    /// Meant for writers that use their own signatures for standard data,
+    /// such as `ETRC` for sinusoidal tracks. [`Frame::signature()`] and
+    /// [`Matrix::signature()`](crate::Matrix::signature) report `to`, so
+    /// typed access such as [`Matrix::rows_as()`](crate::Matrix::rows_as)
+    /// and code matching on standard signatures accept the data. An
+    /// aliased type that the file doesn't declare is accepted in strict
+    /// mode, and its columns are named after those of `to`.
+    ///
+    /// The signatures are checked when the file is opened. The
+    /// [`Index`] records signatures as stored in the file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::rows::TrcRow;
+    /// use sdif_rs::{ReadOptions, SdifFile};
+    ///
+    /// let options = ReadOptions::new().alias("ETRC", "1TRC");
+    /// let file = SdifFile::open_with_options("vendor.sdif", options)?;
+    /// for frame in file.frames() {
+    ///     for matrix in frame?.matrices() {
+    ///         let partials: Vec<TrcRow> = matrix?.rows_as()?.collect();
+    ///         println!("{} partials", partials.len());
+    ///     }
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn alias(mut self, from: &str, to: &str) -> Self {
+        self.aliases.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Read only the streams, types, times, rows and columns in
+    /// `selection`.
+    ///
+    /// See [`selection`](crate::selection) for how frames and matrices
+    /// are filtered.
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = Some(selection);
+        self
+    }
+}
+
+/// The general header at the start of an SDIF file.
+///
+/// Returned by [`SdifFile::header()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileHeader {
+    /// Version of the SDIF format, 3 for files written by current tools.
+    pub format_version: u32,
+
+    /// Version of the standard types the file was written with.
+    pub types_version: u32,
+}
 
 /// An SDIF file opened for reading.
 ///
@@ -61,10 +251,52 @@ pub struct SdifFile {
     /// Cached NVT (Name-Value Table) entries read from the file.
     nvts: Vec<HashMap<String, String>>,
 
+    /// Options the file was opened with.
+    options: ReadOptions,
+
+    /// Signature aliases from the options.
+    aliases: HashMap<Signature, Signature>,
+
+    /// Frame types declared in the file or already resolved by fallback.
+    known_frame_types: RefCell<HashSet<Signature>>,
+
+    /// Matrix types declared in the file or already resolved by fallback.
+    known_matrix_types: RefCell<HashSet<Signature>>,
+
+    /// Column names of the matrix types looked up so far.
+    matrix_columns: RefCell<HashMap<Signature, Vec<String>>>,
+
+    /// Warnings collected while reading.
+    warnings: RefCell<Vec<String>>,
+
     /// Track whether we're currently iterating frames.
     /// Prevents multiple simultaneous iterators.
     iterating: Cell<bool>,
 
+    /// Whether the signature of the next frame has been read.
+    ///
+    /// The C library reads each frame's signature separately from its
+    /// header, so this is false while a frame is being read.
+    signature_pending: Cell<bool>,
+
+    /// Frame index loaded from a sidecar file or built by
+    /// [`scan_index()`](Self::scan_index).
+    index: OnceCell<Index>,
+
+    /// Path the file was opened from, used to scan it for an index.
+    path: PathBuf,
+
+    /// Byte offsets where frames can start: from the first frame after
+    /// the ASCII chunks up to the end of the file.
+    frame_offsets: Range<u64>,
+
+    /// Copy of the data when the file was read from a stream or memory,
+    /// released after the handle is closed.
+    spool: Option<Spool>,
+
+    /// Second handle on the file, opened to verify padding.
+    raw_file: RefCell<Option<File>>,
+
     /// Marker to make SdifFile !Send and !Sync.
     /// The C library uses global state and isn't thread-safe.
     _not_send_sync: PhantomData<*const ()>,
@@ -76,6 +308,9 @@ impl SdifFile {
     /// This reads the general header and all ASCII chunks (NVT, type definitions).
     /// After opening, use [`frames()`](Self::frames) to iterate over data frames.
     ///
+    /// The file is read with the default, lenient [`ReadOptions`]; use
+    /// [`open_with_options()`](Self::open_with_options) to change them.
+    ///
     /// # Arguments
     ///
     /// * `path` - Path to the SDIF file.
@@ -86,7 +321,9 @@ impl SdifFile {
     ///
     /// # Errors
     ///
+    /// - [`Error::LibraryUnavailable`] if the SDIF library couldn't be loaded
     /// - [`Error::InitFailed`] if the SDIF library couldn't be initialized
+    /// - [`Error::InitPoisoned`] if the SDIF library couldn't load its types
     /// - [`Error::OpenFailed`] if the file doesn't exist or isn't readable
     /// - [`Error::InvalidFormat`] if the file isn't a valid SDIF file
     ///
@@ -99,18 +336,52 @@ impl SdifFile {
     /// # Ok::<(), sdif_rs::Error>(())
     /// ```
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_options(path, ReadOptions::default())
+    }
+
+    /// Open an SDIF file for reading with the given options.
+    ///
+    /// # Errors
+    ///
+    /// - Same as [`open()`](Self::open)
+    /// - [`Error::InvalidSignature`] if an alias is not a valid signature
+    /// - Same as [`open_with_selection()`](Self::open_with_selection) with a
+    ///   selection
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::{ReadOptions, SdifFile};
+    ///
+    /// let file = SdifFile::open_with_options("analysis.sdif", ReadOptions::new().strict(true))?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn open_with_options(path: impl AsRef<Path>, options: ReadOptions) -> Result<Self> {
         let path = path.as_ref();
 
-        // Ensure library is initialized
-        if !ensure_initialized() {
-            return Err(Error::InitFailed);
+        let aliases = options
+            .aliases
+            .iter()
+            .map(|(from, to)| Ok((string_to_signature(from)?, string_to_signature(to)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+        if let Some(selection) = &options.selection {
+            selection.validate()?;
+            if !sdif_sys::supports_selections() {
+                return Err(Error::invalid_state("SDIF library has no selection functions"));
+            }
         }
 
-        // Convert path to C string
+        // Ensure library is initialized
+        initialize()?;
+
+        // Convert path to C string, with the selection the library parses
         let path_str = path.to_str().ok_or_else(|| {
             Error::invalid_format("Path contains invalid UTF-8")
         })?;
-        let c_path = CString::new(path_str)?;
+        let c_path = match &options.selection {
+            Some(selection) => CString::new(format!("{}{}", path_str, selection))?,
+            None => CString::new(path_str)?,
+        };
 
         // Open the file
         let handle = unsafe {
@@ -129,154 +400,1389 @@ impl SdifFile {
             return Err(Error::invalid_format("Failed to read SDIF header"));
         }
 
-        // Read ASCII chunks (NVT, type definitions)
-        let ascii_bytes = unsafe { SdifFReadAllASCIIChunks(handle.as_ptr()) };
-        if ascii_bytes < 0 {
+        // Read ASCII chunks (NVT, type definitions). This also reads the
+        // signature of the first frame, or hits the end of the file.
+        unsafe { SdifFReadAllASCIIChunks(handle.as_ptr()) };
+
+        // Remember where the first frame starts, before its signature
+        let mut position: SdiffPosT = 0;
+        if unsafe { SdifFGetPos(handle.as_ptr(), &mut position) } != 0 {
             unsafe { SdifFClose(handle.as_ptr()) };
-            return Err(Error::invalid_format("Failed to read ASCII chunks"));
+            return Err(Error::read_error("Failed to get file position"));
+        }
+        if unsafe { SdifFCurrSignature(handle.as_ptr()) } != 0 {
+            position -= 4;
         }
+        let file_len = match fs::metadata(path) {
+            Ok(meta) => meta.len(),
+            Err(e) => {
+                unsafe { SdifFClose(handle.as_ptr()) };
+                return Err(e.into());
+            }
+        };
+        let frame_offsets = position as u64..file_len;
 
         // Parse NVTs
         let nvts = Self::read_nvts(handle.as_ptr());
 
+        // Remember which types the file declares itself
+        let declared = match Schema::from_handle(handle.as_ptr()) {
+            Ok(schema) => schema,
+            Err(e) => {
+                unsafe { SdifFClose(handle.as_ptr()) };
+                return Err(e);
+            }
+        };
+        let known_frame_types = declared
+            .frame_types
+            .iter()
+            .filter_map(|f| crate::signature::string_to_signature(&f.signature).ok())
+            .collect();
+        let matrix_columns: HashMap<Signature, Vec<String>> = declared
+            .matrix_types
+            .into_iter()
+            .filter_map(|m| {
+                let signature = crate::signature::string_to_signature(&m.signature).ok()?;
+                Some((signature, m.columns))
+            })
+            .collect();
+        let known_matrix_types = matrix_columns.keys().copied().collect();
+
+        let index = OnceCell::new();
+        if options.sidecar_index {
+            if let Some(sidecar) = Index::load_fresh_sidecar(path) {
+                let _ = index.set(sidecar);
+            }
+        }
+
         Ok(SdifFile {
             handle,
             nvts,
+            options,
+            aliases,
+            known_frame_types: RefCell::new(known_frame_types),
+            known_matrix_types: RefCell::new(known_matrix_types),
+            matrix_columns: RefCell::new(matrix_columns),
+            warnings: RefCell::new(Vec::new()),
             iterating: Cell::new(false),
+            signature_pending: Cell::new(true),
+            index,
+            path: path.to_path_buf(),
+            frame_offsets,
+            spool: None,
+            raw_file: RefCell::new(None),
             _not_send_sync: PhantomData,
         })
     }
 
-    /// Get the Name-Value Tables (NVT) from the file.
+    /// Open an SDIF file for reading part of it.
     ///
-    /// NVTs contain metadata about the file, such as creator, date,
-    /// sample rate, and other application-specific information.
+    /// Only the streams, types, times, rows and columns in `selection` are
+    /// read, see [`selection`](crate::selection). Same as
+    /// [`open_with_options()`](Self::open_with_options) with
+    /// [`ReadOptions::selection()`].
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// A slice of hash maps, where each map represents one NVT.
-    /// Most files have a single NVT.
+    /// - Same as [`open()`](Self::open)
+    /// - [`Error::InvalidSignature`] or [`Error::InvalidFormat`] if the
+    ///   selection is invalid, see [`Selection::validate()`]
+    /// - [`Error::InvalidState`] if the SDIF library has no selection
+    ///   functions
     ///
     /// # Example
     ///
     /// ```no_run
+    /// use sdif_rs::selection::Selection;
     /// use sdif_rs::SdifFile;
     ///
-    /// let file = SdifFile::open("input.sdif")?;
-    /// for nvt in file.nvts() {
-    ///     if let Some(creator) = nvt.get("creator") {
-    ///         println!("Created by: {}", creator);
-    ///     }
-    /// }
+    /// let selection = Selection::new().stream(1).time_range(0.5..=1.5);
+    /// let file = SdifFile::open_with_selection("analysis.sdif", &selection)?;
     /// # Ok::<(), sdif_rs::Error>(())
     /// ```
-    pub fn nvts(&self) -> &[HashMap<String, String>] {
-        &self.nvts
+    pub fn open_with_selection(path: impl AsRef<Path>, selection: &Selection) -> Result<Self> {
+        Self::open_with_options(path, ReadOptions::new().selection(selection.clone()))
     }
 
-    /// Get a specific value from the first NVT.
+    /// Read an SDIF file from a stream, such as standard input.
     ///
-    /// This is a convenience method for accessing common metadata.
+    /// The C library needs a seekable file, so the stream is first copied
+    /// to an anonymous in-memory file on Linux, and to a temporary file
+    /// elsewhere. The copy is released when the returned `SdifFile` is
+    /// dropped.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `key` - The NVT key to look up.
+    /// - [`Error::Io`] if the stream or the copy can't be used
+    /// - Same as [`open()`](Self::open) otherwise
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// The value if found, or `None`.
-    pub fn nvt_get(&self, key: &str) -> Option<&str> {
-        self.nvts.first()?.get(key).map(|s| s.as_str())
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::from_reader(std::io::stdin().lock())?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        Self::from_reader_with_options(reader, ReadOptions::default())
     }
 
-    /// Create an iterator over all frames in the file.
-    ///
-    /// Frames are read sequentially from the current file position.
-    /// Each frame contains one or more matrices of data.
+    /// Read an SDIF file from a stream with the given options.
     ///
-    /// # Returns
+    /// See [`from_reader()`](Self::from_reader).
+    pub fn from_reader_with_options(mut reader: impl Read, options: ReadOptions) -> Result<Self> {
+        let spool = Spool::new(&mut reader)?;
+        let mut file = Self::open_with_options(spool.path(), options)?;
+        file.spool = Some(spool);
+        Ok(file)
+    }
+
+    /// Read an SDIF file held in memory, such as a blob received over the
+    /// network.
     ///
-    /// A [`FrameIterator`] that yields [`Frame`] objects.
+    /// Like [`from_reader()`](Self::from_reader), the data is copied to an
+    /// anonymous in-memory file on Linux, and to a temporary file
+    /// elsewhere.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if called while another frame iterator is active.
-    /// Only one iterator can be active at a time because the
-    /// underlying C library maintains file position state.
+    /// Same as [`from_reader()`](Self::from_reader).
     ///
     /// # Example
     ///
     /// ```no_run
     /// use sdif_rs::SdifFile;
     ///
-    /// let file = SdifFile::open("input.sdif")?;
-    /// for frame_result in file.frames() {
-    ///     let frame = frame_result?;
-    ///     println!("Time: {:.3}, Sig: {}", frame.time(), frame.signature());
-    /// }
+    /// # let body: Vec<u8> = Vec::new();
+    /// let file = SdifFile::from_bytes(&body)?;
+    /// println!("{} frames", file.frames().count());
     /// # Ok::<(), sdif_rs::Error>(())
     /// ```
-    pub fn frames(&self) -> FrameIterator<'_> {
-        if self.iterating.get() {
-            panic!("Cannot create multiple frame iterators simultaneously");
-        }
-        self.iterating.set(true);
-        FrameIterator::new(self)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::from_reader(bytes)
     }
 
-    /// Get the raw C file handle.
-    ///
-    /// # Safety
+    /// Read an SDIF file held in memory with the given options.
     ///
-    /// This is for internal use. The caller must not close the handle
-    /// or use it after the `SdifFile` is dropped.
-    pub(crate) fn handle(&self) -> *mut SdifFileT {
-        self.handle.as_ptr()
+    /// See [`from_bytes()`](Self::from_bytes).
+    pub fn from_bytes_with_options(bytes: &[u8], options: ReadOptions) -> Result<Self> {
+        Self::from_reader_with_options(bytes, options)
     }
 
-    /// Mark that frame iteration has ended.
-    pub(crate) fn end_iteration(&self) {
-        self.iterating.set(false);
+    /// Get the options the file was opened with.
+    pub fn options(&self) -> &ReadOptions {
+        &self.options
     }
 
-    /// Read NVT entries from the file.
-    fn read_nvts(handle: *mut SdifFileT) -> Vec<HashMap<String, String>> {
-        // TODO: Implement NVT reading using SDIF C API
-        // For now, return empty vec - will implement with proper C API calls
-        // The C API provides SdifFGetAllNVT, SdifNameValueTableGetNbData, etc.
+    /// Read the general header of the file.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be read again
+    /// - [`Error::InvalidFormat`] if the header is too short
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// println!("SDIF version {}", file.header()?.format_version);
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn header(&self) -> Result<FileHeader> {
+        let mut bytes = [0u8; 16];
+        File::open(&self.path)?.read_exact(&mut bytes).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => Error::invalid_format("SDIF header is truncated"),
+            _ => e.into(),
+        })?;
 
-        // Placeholder - actual implementation requires walking the NVT structures
-        Vec::new()
+        let word = |pos: usize| u32::from_be_bytes([
+            bytes[pos],
+            bytes[pos + 1],
+            bytes[pos + 2],
+            bytes[pos + 3],
+        ]);
+        Ok(FileHeader {
+            format_version: word(8),
+            types_version: word(12),
+        })
     }
-}
 
-impl Drop for SdifFile {
-    fn drop(&mut self) {
-        // SAFETY: We own the handle and it's valid (NonNull).
-        // After this, the handle is invalid, but we're being dropped anyway.
-        unsafe {
-            SdifFClose(self.handle.as_ptr());
-        }
+    /// Get the frame index loaded from the file's `.sdifx` sidecar.
+    ///
+    /// Returns `None` if there is no sidecar, if it is older than the
+    /// file, or if sidecar loading was disabled in [`ReadOptions`], unless
+    /// [`scan_index()`](Self::scan_index) has already built one.
+    pub fn index(&self) -> Option<&Index> {
+        self.index.get()
     }
-}
 
-// PhantomData<*const ()> makes SdifFile !Send and !Sync automatically
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get the frame index, scanning the file to build it if needed.
+    ///
+    /// The scan reads every frame header once through a separate handle,
+    /// so it doesn't move the reader. The result is kept for later calls
+    /// and for [`seek_to_frame()`](Self::seek_to_frame) and
+    /// [`frames_from()`](Self::frames_from).
+    ///
+    /// # Errors
+    ///
+    /// - Same as [`Index::build()`] if the file can't be scanned
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// for frame in file.scan_index()?.frames() {
+    ///     println!("{} at {:.3}s, offset {}", frame.signature_str(), frame.time, frame.byte_offset);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn scan_index(&self) -> Result<&Index> {
+        if let Some(index) = self.index.get() {
+            return Ok(index);
+        }
+        let index = Index::build(&self.path)?;
+        Ok(self.index.get_or_init(|| index))
+    }
 
-    #[test]
-    fn test_open_nonexistent() {
-        let result = SdifFile::open("/nonexistent/path/to/file.sdif");
-        assert!(result.is_err());
+    /// Position the reader at the frame with position `idx` in the index.
+    ///
+    /// The next call to [`frames()`](Self::frames) starts iterating at that
+    /// frame. The index is built with [`scan_index()`](Self::scan_index)
+    /// if the file doesn't have one yet.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::FrameOutOfRange`] if `idx` is past the last frame
+    /// - Same as [`seek_to_offset()`](Self::seek_to_offset) otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// file.seek_to_frame(100)?;
+    /// let frame = file.frames().next().unwrap()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn seek_to_frame(&self, idx: usize) -> Result<()> {
+        let index = self.scan_index()?;
+        let frame = index.frames().get(idx).ok_or(Error::FrameOutOfRange {
+            index: idx,
+            len: index.len(),
+        })?;
+        self.seek_to_offset(frame.byte_offset)
+    }
 
-        match result.unwrap_err() {
-            Error::OpenFailed { path } => {
-                assert!(path.to_str().unwrap().contains("nonexistent"));
-            }
-            other => panic!("Expected OpenFailed, got {:?}", other),
+    /// Iterate over the frames starting at the first one at or after `time`.
+    ///
+    /// Uses the index to jump straight to the frame, building it with
+    /// [`scan_index()`](Self::scan_index) if needed. The iterator is empty
+    /// if every frame is earlier than `time`.
+    ///
+    /// # Errors
+    ///
+    /// - Same as [`seek_to_offset()`](Self::seek_to_offset)
+    ///
+    /// # Panics
+    ///
+    /// Same as [`frames()`](Self::frames).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("long-analysis.sdif")?;
+    /// for frame in file.frames_from(3600.0)? {
+    ///     let frame = frame?;
+    ///     if frame.time() >= 3660.0 {
+    ///         break;
+    ///     }
+    ///     println!("Frame at {:.3}s", frame.time());
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn frames_from(&self, time: f64) -> Result<FrameIterator<'_>> {
+        let index = self.scan_index()?;
+        match index.first_at_or_after(time) {
+            Some(frame) => self.seek_to_offset(frame.byte_offset)?,
+            None => self.seek_to_end()?,
         }
+        Ok(self.frames())
+    }
+
+    /// Read the frame at `time`, or the one picked by `rounding` if no
+    /// frame starts exactly at `time`.
+    ///
+    /// The frame is found by binary search in the index, built with
+    /// [`scan_index()`](Self::scan_index) if needed, so this is cheap to
+    /// call for every audio block when aligning control data to a playback
+    /// position. The returned flag is `true` when the frame time equals
+    /// `time` exactly. Returns `None` if there is no such frame, e.g. with
+    /// [`Rounding::Before`] and a time before the first frame.
+    ///
+    /// Afterwards, [`frames()`](Self::frames) continues with the frame
+    /// following the returned one.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if a frame iterator is active or a frame
+    ///   is still being read
+    /// - Same as [`scan_index()`](Self::scan_index) if the index can't be built
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::{Rounding, SdifFile};
+    ///
+    /// let file = SdifFile::open("partials.sdif")?;
+    /// let playback_position = 12.5;
+    /// if let Some((frame, exact)) = file.frame_at(playback_position, Rounding::Before)? {
+    ///     println!("Using frame at {:.3}s (exact: {})", frame.time(), exact);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn frame_at(&self, time: f64, rounding: Rounding) -> Result<Option<(Frame<'_>, bool)>> {
+        if !self.signature_pending() {
+            return Err(Error::invalid_state(
+                "Previous frame must be dropped before reading the next one",
+            ));
+        }
+        let Some(pos) = self.scan_index()?.position_near(time, rounding) else {
+            return Ok(None);
+        };
+        self.seek_to_frame(pos)?;
+
+        match self.frames().next() {
+            Some(frame) => {
+                let frame = frame?;
+                let exact = frame.time() == time;
+                Ok(Some((frame, exact)))
+            }
+            None => Err(Error::invalid_format(format!(
+                "Indexed frame {} is missing from the file",
+                pos
+            ))),
+        }
+    }
+
+    /// Get the warnings collected while reading so far.
+    ///
+    /// In lenient mode, each undeclared frame or matrix type produces one
+    /// warning the first time it is encountered.
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Get the Name-Value Tables (NVT) from the file.
+    ///
+    /// NVTs contain metadata about the file, such as creator, date,
+    /// sample rate, and other application-specific information.
+    ///
+    /// # Returns
+    ///
+    /// A slice of hash maps, where each map represents one NVT.
+    /// Most files have a single NVT.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// for nvt in file.nvts() {
+    ///     if let Some(creator) = nvt.get("creator") {
+    ///         println!("Created by: {}", creator);
+    ///     }
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn nvts(&self) -> &[HashMap<String, String>] {
+        &self.nvts
+    }
+
+    /// Get a specific value from the first NVT.
+    ///
+    /// This is a convenience method for accessing common metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The NVT key to look up.
+    ///
+    /// # Returns
+    ///
+    /// The value if found, or `None`.
+    pub fn nvt_get(&self, key: &str) -> Option<&str> {
+        self.nvts.first()?.get(key).map(|s| s.as_str())
+    }
+
+    /// Get the processing history recorded in the file's NVTs.
+    ///
+    /// Steps are returned in the order they were recorded. See
+    /// [`crate::history`] for the NVT convention.
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        history::from_nvts(&self.nvts)
+    }
+
+    /// Get the sound file the analysis was computed from.
+    ///
+    /// Returns `None` unless the file records its source audio; see
+    /// [`crate::source`] for the NVT convention.
+    pub fn source_audio(&self) -> Option<SourceAudio> {
+        source::from_nvts(&self.nvts)
+    }
+
+    /// Get the columns of a matrix type with their units and descriptions.
+    ///
+    /// Column names come from the type declarations, as for
+    /// [`Matrix::columns()`](crate::Matrix::columns), and annotations from
+    /// the NVTs; see [`crate::columns`] for the convention. The list is
+    /// empty if the type isn't known.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if `signature` is not 4 characters
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("descriptors.sdif")?;
+    /// for column in file.column_defs("XDSC")? {
+    ///     println!("{} [{}]", column.name, column.unit.as_deref().unwrap_or("-"));
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn column_defs(&self, signature: &str) -> Result<Vec<ColumnDef>> {
+        let names = self.matrix_columns(string_to_signature(signature)?);
+        Ok(columns::from_nvts(&self.nvts, signature, names))
+    }
+
+    /// Look up the ID of a named stream.
+    ///
+    /// Returns `None` if the file doesn't name a stream `name`. See
+    /// [`crate::streams`] for how names are recorded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// if let Some(partials) = file.stream_named("partials") {
+    ///     for frame in file.frames() {
+    ///         let frame = frame?;
+    ///         if frame.stream_id() == partials {
+    ///             println!("{:.3}s", frame.time());
+    ///         }
+    ///     }
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn stream_named(&self, name: &str) -> Option<u32> {
+        streams::from_entries(self.nvts.iter().flatten())
+            .get(name)
+            .copied()
+    }
+
+    /// Get the sample rate recorded in the NVTs, in Hz.
+    ///
+    /// Reads the conventional [`SampleRate`](crate::metadata::SAMPLE_RATE)
+    /// key. Returns `None` if it is missing or not a positive number.
+    pub fn sample_rate(&self) -> Option<f64> {
+        metadata::lookup(&self.nvts, metadata::SAMPLE_RATE).and_then(metadata::parse_sample_rate)
+    }
+
+    /// Get the name of the program that wrote the file.
+    ///
+    /// Reads the conventional [`Creator`](crate::metadata::CREATOR) key.
+    pub fn creator(&self) -> Option<&str> {
+        metadata::lookup(&self.nvts, metadata::CREATOR)
+    }
+
+    /// Get the date the file was written, as recorded.
+    ///
+    /// Reads the conventional [`Date`](crate::metadata::DATE) key. Tools
+    /// don't agree on a date format, so the value is returned as text; see
+    /// [`date_time()`](Self::date_time) for a parsed date.
+    pub fn date(&self) -> Option<&str> {
+        metadata::lookup(&self.nvts, metadata::DATE)
+    }
+
+    /// Get the date the file was written, parsed.
+    ///
+    /// Accepts ISO 8601 dates as written by
+    /// [`SdifFileBuilder::stamp_date()`](crate::SdifFileBuilder::stamp_date)
+    /// and other RFC 3339 dates, as well as `YYYY-MM-DD HH:MM:SS` and
+    /// `YYYY-MM-DD`, which are taken to be in UTC. Returns `None` if the
+    /// date is missing or in another format.
+    #[cfg(feature = "chrono")]
+    pub fn date_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.date().and_then(metadata::parse_date)
+    }
+
+    /// Get the path of the analysed sound file.
+    ///
+    /// Reads the conventional [`Soundfile`](crate::metadata::SOUNDFILE) key.
+    pub fn soundfile(&self) -> Option<&str> {
+        metadata::lookup(&self.nvts, metadata::SOUNDFILE)
+    }
+
+    /// Get the version of this crate that wrote the file.
+    ///
+    /// Reads the [`Writer`](crate::metadata::WRITER) entry that
+    /// [`SdifFileBuilder`](crate::SdifFileBuilder) adds by default. Returns
+    /// `None` for files from other writers, and for files written by this
+    /// crate with [`omit_writer()`](crate::SdifFileBuilder::omit_writer) or
+    /// in [`deterministic()`](crate::SdifFileBuilder::deterministic) mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// match file.written_by_sdif_rs() {
+    ///     Some(version) => println!("Written by sdif-rs {}", version),
+    ///     None => println!("Written by another tool"),
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn written_by_sdif_rs(&self) -> Option<Version> {
+        metadata::lookup(&self.nvts, metadata::WRITER).and_then(metadata::parse_writer)
+    }
+
+    /// Describe the matrix and frame types declared in the file.
+    ///
+    /// Only types declared in the file's own `1TYP` chunk are listed;
+    /// predefined library types that the file merely uses are not.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NullPointer`] if the C library's type tables
+    /// are unavailable.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// for mtype in &file.describe_types()?.matrix_types {
+    ///     println!("{}: {:?}", mtype.signature, mtype.columns);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn describe_types(&self) -> Result<Schema> {
+        Schema::from_handle(self.handle.as_ptr())
+    }
+
+    /// Read the metadata and all remaining frames into a document.
+    ///
+    /// Frames are read from the current position to the end. With the
+    /// `serde` feature, the document can be serialized and later written
+    /// back with [`SdifDocument::write_sdif()`].
+    ///
+    /// # Errors
+    ///
+    /// - Any error from reading the type declarations or frames
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let document = SdifFile::open("input.sdif")?.to_document()?;
+    /// println!("{} NVTs, {} frames", document.metadata.nvts.len(), document.frames.len());
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn to_document(&self) -> Result<SdifDocument> {
+        let metadata = FileMetadata::from_file(self)?;
+        let frames = self
+            .frames()
+            .map(|frame| frame?.into_owned())
+            .collect::<Result<_>>()?;
+
+        Ok(SdifDocument { metadata, frames })
+    }
+
+    /// Read the metadata and all remaining frames as pretty-printed JSON.
+    ///
+    /// The JSON is the serialized [`SdifDocument`], with NVTs, type
+    /// declarations, and each frame's time, stream ID and matrices. Matrix
+    /// values are arrays of numbers in row-major order, with NaN and
+    /// infinite values written as the strings `"NaN"`, `"inf"` and
+    /// `"-inf"` so that they read back unchanged. Requires the `json`
+    /// feature.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Json`] if serialization fails
+    /// - Any error from [`to_document()`](Self::to_document)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let json = SdifFile::open("analysis.sdif")?.to_json()?;
+    /// std::fs::write("analysis.json", json)?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.to_document()?)?)
+    }
+
+    /// Create an iterator over all frames in the file.
+    ///
+    /// Frames are read sequentially from the current file position.
+    /// Each frame contains one or more matrices of data.
+    ///
+    /// # Returns
+    ///
+    /// A [`FrameIterator`] that yields [`Frame`] objects.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called while another frame iterator is active.
+    /// Only one iterator can be active at a time because the
+    /// underlying C library maintains file position state.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// for frame_result in file.frames() {
+    ///     let frame = frame_result?;
+    ///     println!("Time: {:.3}, Sig: {}", frame.time(), frame.signature());
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn frames(&self) -> FrameIterator<'_> {
+        if self.iterating.get() {
+            panic!("Cannot create multiple frame iterators simultaneously");
+        }
+        self.iterating.set(true);
+        FrameIterator::new(self)
+    }
+
+    /// Iterate over the frames that pass `filter`.
+    ///
+    /// Frames that don't match are skipped without reading their
+    /// matrices. When the file has an [`index()`](Self::index) whose frame
+    /// times never decrease, iteration ends at the first frame past the
+    /// end of the filter's time range; otherwise every remaining frame is
+    /// checked, as files are not guaranteed to be in time order. To also
+    /// avoid reading the headers of frames before the range, position the
+    /// file with
+    /// [`frames_from()`](Self::frames_from) or
+    /// [`seek_to_offset()`](Self::seek_to_offset) first.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`frames()`](Self::frames).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::{FrameFilter, SdifFile};
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// let filter = FrameFilter::new().signature("1TRC").time_range(1.0..2.0);
+    /// for frame in file.frames_filtered(filter) {
+    ///     let frame = frame?;
+    ///     println!("{:.3}s: {} matrices", frame.time(), frame.num_matrices());
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn frames_filtered(&self, filter: FrameFilter) -> FilteredFrames<'_> {
+        FilteredFrames::new(self.frames(), filter)
+    }
+
+    /// Iterate over the frames from the last to the first.
+    ///
+    /// Each frame is found through the index, which is built with
+    /// [`scan_index()`](Self::scan_index) if the file doesn't have one
+    /// yet, so only the frames actually visited are read. This makes it
+    /// cheap to show the latest data first, or to search backwards from
+    /// the end.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`scan_index()`](Self::scan_index).
+    ///
+    /// # Panics
+    ///
+    /// Same as [`frames()`](Self::frames).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// // Find the last 1FQ0 frame before 10 seconds
+    /// let file = SdifFile::open("voice.sdif")?;
+    /// for frame in file.frames_rev()? {
+    ///     let frame = frame?;
+    ///     if frame.time() < 10.0 && frame.signature() == "1FQ0" {
+    ///         println!("Last pitch estimate at {:.3}s", frame.time());
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn frames_rev(&self) -> Result<ReverseFrames<'_>> {
+        let index = self.scan_index()?;
+        Ok(ReverseFrames::new(self.frames(), index))
+    }
+
+    /// Position the reader at the frame starting at `byte_offset`.
+    ///
+    /// The next call to [`frames()`](Self::frames) starts iterating at that
+    /// frame. Offsets come from [`Frame::byte_offset()`](crate::Frame::byte_offset),
+    /// typically recorded in an index by an earlier pass over the file.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if a frame iterator is active
+    /// - [`Error::InvalidFormat`] if no frame starts at `byte_offset`,
+    ///   including offsets in the header or ASCII chunks and offsets at or
+    ///   past the end of the file; the reader position is left unchanged
+    /// - [`Error::ReadError`] if the file can't be repositioned
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("input.sdif")?;
+    /// let offset = file.frames().nth(100).unwrap()?.byte_offset();
+    ///
+    /// // Later: jump straight back to frame 100
+    /// file.seek_to_offset(offset)?;
+    /// let frame = file.frames().next().unwrap()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn seek_to_offset(&self, byte_offset: u64) -> Result<()> {
+        if self.iterating.get() {
+            return Err(Error::invalid_state("Cannot seek while iterating frames"));
+        }
+        self.move_to_offset(byte_offset)
+    }
+
+    /// Position the reader at a frame, even while iterating.
+    ///
+    /// The caller must make sure no frame is being read.
+    pub(crate) fn move_to_offset(&self, byte_offset: u64) -> Result<()> {
+        let no_frame =
+            || Error::invalid_format(format!("No frame starts at byte offset {}", byte_offset));
+        if !self.frame_offsets.contains(&byte_offset) {
+            return Err(no_frame());
+        }
+        let handle = self.handle();
+        let mut target = SdiffPosT::try_from(byte_offset)
+            .map_err(|_| Error::invalid_format(format!("Invalid byte offset {}", byte_offset)))?;
+
+        // Remember where we are, to restore it if the offset is invalid
+        let mut saved: SdiffPosT = 0;
+        let saved_signature = unsafe { SdifFCurrSignature(handle) };
+        if unsafe { SdifFGetPos(handle, &mut saved) } != 0 {
+            return Err(Error::read_error("Failed to get file position"));
+        }
+
+        if unsafe { SdifFSetPos(handle, &mut target) } != 0 {
+            return Err(Error::read_error("Failed to seek").with_location(
+                Some(byte_offset),
+                None,
+                None,
+            ));
+        }
+        let signature = self.read_signature();
+
+        if !signature.is_some_and(is_frame_signature) {
+            // Go back to the pending signature, or to the end of file
+            let mut restore = if saved_signature != 0 { saved - 4 } else { saved };
+            unsafe { SdifFSetPos(handle, &mut restore) };
+            self.read_signature();
+
+            return Err(no_frame());
+        }
+
+        self.signature_pending.set(true);
+        Ok(())
+    }
+
+    /// Position the reader at the end of the file, so that
+    /// [`frames()`](Self::frames) yields nothing.
+    fn seek_to_end(&self) -> Result<()> {
+        if self.iterating.get() {
+            return Err(Error::invalid_state("Cannot seek while iterating frames"));
+        }
+        let mut target = SdiffPosT::try_from(self.frame_offsets.end)
+            .map_err(|_| Error::read_error("Failed to seek"))?;
+        if unsafe { SdifFSetPos(self.handle(), &mut target) } != 0 {
+            return Err(Error::read_error("Failed to seek"));
+        }
+        self.read_signature();
+        self.signature_pending.set(true);
+        Ok(())
+    }
+
+    /// Read the signature at the current position, or `None` at end of file.
+    fn read_signature(&self) -> Option<Signature> {
+        let mut bytes_read = 0;
+        unsafe {
+            SdifFGetSignature(self.handle(), &mut bytes_read);
+            (bytes_read > 0).then(|| SdifFCurrSignature(self.handle()))
+        }
+    }
+
+    /// Get the raw C file handle.
+    ///
+    /// # Safety
+    ///
+    /// This is for internal use. The caller must not close the handle
+    /// or use it after the `SdifFile` is dropped.
+    pub(crate) fn handle(&self) -> *mut SdifFileT {
+        self.handle.as_ptr()
+    }
+
+    /// Mark that frame iteration has ended.
+    pub(crate) fn end_iteration(&self) {
+        self.iterating.set(false);
+    }
+
+    /// Check whether the next frame's signature has been read.
+    pub(crate) fn signature_pending(&self) -> bool {
+        self.signature_pending.get()
+    }
+
+    /// Record whether the next frame's signature has been read.
+    pub(crate) fn set_signature_pending(&self, pending: bool) {
+        self.signature_pending.set(pending);
+    }
+
+    /// Check whether reading runs in lenient mode.
+    pub(crate) fn is_lenient(&self) -> bool {
+        !self.options.strict
+    }
+
+    /// Check whether frames and matrices are read through a selection.
+    pub(crate) fn has_selection(&self) -> bool {
+        self.options.selection.is_some()
+    }
+
+    /// Check whether the frame whose header was just read is selected.
+    pub(crate) fn frame_is_selected(&self) -> bool {
+        self.options.selection.is_none()
+            || unsafe { SdifFCurrFrameIsSelected(self.handle()) } != 0
+    }
+
+    /// Check whether the matrix whose header was just read is selected.
+    pub(crate) fn matrix_is_selected(&self) -> bool {
+        self.options.selection.is_none()
+            || unsafe { SdifFCurrMatrixIsSelected(self.handle()) } != 0
+    }
+
+    /// Get the selected rows and columns of a matrix with `rows` rows and
+    /// `cols` columns, counted from 0, or `None` if all are selected.
+    pub(crate) fn selected_cells(&self, rows: u32, cols: u32) -> Option<(Vec<u32>, Vec<u32>)> {
+        if !self.options.selection.as_ref().is_some_and(Selection::selects_cells) {
+            return None;
+        }
+
+        // The library counts rows and columns from 1
+        let handle = self.handle();
+        let rows = (0..rows)
+            .filter(|&row| unsafe { SdifFRowIsSelected(handle, row as c_int + 1) } != 0)
+            .collect();
+        let cols = (0..cols)
+            .filter(|&col| unsafe { SdifFColumnIsSelected(handle, col as c_int + 1) } != 0)
+            .collect();
+        Some((rows, cols))
+    }
+
+    /// Get the signature a frame or matrix type is read as.
+    pub(crate) fn canonical_signature(&self, signature: Signature) -> Signature {
+        self.aliases.get(&signature).copied().unwrap_or(signature)
+    }
+
+    /// Record a warning.
+    fn warn(&self, message: String) {
+        self.warnings.borrow_mut().push(message);
+    }
+
+    /// Check the padding after the matrices of `frame`, if enabled in the
+    /// options.
+    ///
+    /// The frame is read again through a separate handle, so the C
+    /// library's position is left alone.
+    pub(crate) fn check_padding(&self, frame: &Frame<'_>) -> Result<()> {
+        if !self.options.verify_padding {
+            return Ok(());
+        }
+
+        let mut raw_file = self.raw_file.borrow_mut();
+        let raw_file = match raw_file.as_mut() {
+            Some(raw_file) => raw_file,
+            None => raw_file.insert(File::open(&self.path)?),
+        };
+        let Some((matrix, signature)) =
+            find_nonzero_padding(raw_file, frame.byte_offset(), frame.num_matrices())?
+        else {
+            return Ok(());
+        };
+
+        let message = format!(
+            "Non-zero padding after matrix {} ('{}') of frame '{}' at {:.6}s (offset {})",
+            matrix,
+            signature_to_string(signature),
+            frame.signature(),
+            frame.time(),
+            frame.byte_offset()
+        );
+        if self.options.strict {
+            return Err(Error::invalid_format(message));
+        }
+        self.warn(message);
+        Ok(())
+    }
+
+    /// Make sure the type of the frame about to be read is usable.
+    ///
+    /// Must be called before the C library reads the frame header, so that
+    /// a fallback type is in place when it looks the type up.
+    pub(crate) fn resolve_frame_type(&self, signature: Signature) -> Result<()> {
+        if self.known_frame_types.borrow().contains(&signature) {
+            return Ok(());
+        }
+
+        let name = signature_to_string(signature);
+        if self.options.strict && !self.aliases.contains_key(&signature) {
+            return Err(Error::invalid_format(format!(
+                "Frame type '{}' is not declared in the file",
+                name
+            )));
+        }
+
+        // Look up a predefined type quietly; the library reports failures itself
+        let predefined = unsafe {
+            SdifDisableErrorOutput();
+            let ftype = SdifTestFrameType(self.handle(), signature);
+            SdifEnableErrorOutput();
+            !ftype.is_null()
+        };
+
+        if predefined {
+            self.warn(format!(
+                "Frame type '{}' is not declared; using predefined type",
+                name
+            ));
+        } else {
+            unsafe {
+                let ftypes = SdifFGetFrameTypesTable(self.handle());
+                let ftype = SdifCreateFrameType(signature, std::ptr::null_mut());
+                if ftypes.is_null() || ftype.is_null() {
+                    return Err(Error::null_pointer("Frame type"));
+                }
+                SdifPutFrameType(ftypes, ftype);
+            }
+            self.warn(format!(
+                "Frame type '{}' is not declared or predefined; using a generic type",
+                name
+            ));
+        }
+
+        self.known_frame_types.borrow_mut().insert(signature);
+        Ok(())
+    }
+
+    /// Get the column names of a matrix type.
+    ///
+    /// Types that aren't declared or predefined have no columns until a
+    /// matrix of that type has been read in lenient mode.
+    pub(crate) fn matrix_columns(&self, signature: Signature) -> Vec<String> {
+        if let Some(columns) = self.matrix_columns.borrow().get(&signature) {
+            return columns.clone();
+        }
+
+        let columns = unsafe {
+            let mtypes = SdifFGetMatrixTypesTable(self.handle());
+            let mtype = if mtypes.is_null() {
+                std::ptr::null_mut()
+            } else {
+                SdifGetMatrixType(mtypes, signature)
+            };
+            if mtype.is_null() {
+                return Vec::new();
+            }
+            Schema::describe_matrix_type(mtype).columns
+        };
+
+        self.matrix_columns
+            .borrow_mut()
+            .insert(signature, columns.clone());
+        columns
+    }
+
+    /// Make sure the type of the matrix whose header was just read is usable.
+    ///
+    /// If the type is unknown, a generic type with one column per matrix
+    /// column is registered and the row buffer is sized for it.
+    pub(crate) fn resolve_matrix_type(&self, signature: Signature) -> Result<()> {
+        if self.known_matrix_types.borrow().contains(&signature) {
+            return Ok(());
+        }
+
+        let name = signature_to_string(signature);
+        if self.options.strict && !self.aliases.contains_key(&signature) {
+            return Err(Error::invalid_format(format!(
+                "Matrix type '{}' is not declared in the file",
+                name
+            )));
+        }
+
+        let handle = self.handle();
+        unsafe {
+            let mtypes = SdifFGetMatrixTypesTable(handle);
+            if mtypes.is_null() {
+                return Err(Error::null_pointer("Matrix types table"));
+            }
+
+            // Reading the header already copied a predefined type into the
+            // file's table if one exists
+            if !SdifGetMatrixType(mtypes, signature).is_null() {
+                self.warn(format!(
+                    "Matrix type '{}' is not declared; using predefined type",
+                    name
+                ));
+            } else {
+                let mtype = SdifCreateMatrixType(signature, std::ptr::null_mut());
+                if mtype.is_null() {
+                    return Err(Error::null_pointer("Matrix type"));
+                }
+
+                let names = self.alias_columns(signature);
+                let cols = SdifFCurrNbCol(handle);
+                for col in 1..=cols {
+                    let name = names.get(col as usize - 1).cloned();
+                    let c_name = CString::new(name.unwrap_or_else(|| format!("Column{}", col)))?;
+                    SdifMatrixTypeInsertTailColumnDef(mtype, c_name.as_ptr());
+                }
+                SdifPutMatrixType(mtypes, mtype);
+
+                // The header test failed, so the row buffer wasn't resized
+                SdifReInitOneRow(SdifFCurrOneRow(handle), SdifFCurrDataType(handle), cols);
+
+                self.warn(format!(
+                    "Matrix type '{}' is not declared or predefined; using a generic type",
+                    name
+                ));
+            }
+        }
+
+        self.known_matrix_types.borrow_mut().insert(signature);
+        Ok(())
+    }
+
+    /// Get the column names of the type a matrix type is aliased to.
+    ///
+    /// Empty if the type has no alias, or the alias has no known columns.
+    fn alias_columns(&self, signature: Signature) -> Vec<String> {
+        let Some(&target) = self.aliases.get(&signature) else {
+            return Vec::new();
+        };
+        let columns = self.matrix_columns(target);
+        if !columns.is_empty() {
+            return columns;
+        }
+        schemas::standard_columns(&signature_to_string(target))
+            .map(|columns| columns.iter().map(|c| c.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Read NVT entries from the file.
+    ///
+    /// The library keeps the tables it read from the ASCII chunks in a list,
+    /// each one a hash table of name-value entries, so the order of the
+    /// entries within a table is not preserved.
+    fn read_nvts(handle: *mut SdifFileT) -> Vec<HashMap<String, String>> {
+        let mut nvts = Vec::new();
+
+        // SAFETY: the handle is open and its ASCII chunks have been read;
+        // the list, tables and entries stay owned by the library and are
+        // only read here.
+        unsafe {
+            let values = SdifFNameValueList(handle);
+            if values.is_null() || (*values).NVTList.is_null() {
+                return nvts;
+            }
+
+            let list = (*values).NVTList;
+            SdifListInitLoop(list);
+            while SdifListIsNext(list) != 0 {
+                let table = SdifListGetNext(list) as *mut SdifNameValueTableT;
+                if table.is_null() || (*table).NVHT.is_null() {
+                    continue;
+                }
+
+                let mut nvt = HashMap::new();
+                let iter = SdifCreateHashTableIterator((*table).NVHT);
+                while SdifHashTableIteratorIsNext(iter) != 0 {
+                    let entry = SdifHashTableIteratorGetNext(iter) as *mut SdifNameValueT;
+                    if !entry.is_null() {
+                        nvt.insert(
+                            c_str_or_empty((*entry).Name),
+                            c_str_or_empty((*entry).Value),
+                        );
+                    }
+                }
+                SdifKillHashTableIterator(iter);
+
+                nvts.push(nvt);
+            }
+        }
+
+        nvts
+    }
+}
+
+/// Signatures of the ASCII chunks between the header and the first frame.
+const CHUNK_SIGNATURES: [[u8; 4]; 3] = [*b"1NVT", *b"1TYP", *b"1IDS"];
+
+/// Check whether a signature looks like a frame signature.
+///
+/// Frame signatures consist of ASCII letters and digits, which rules out
+/// offsets that point into the middle of a frame. The signatures of the
+/// ASCII chunks look the same, so they are ruled out by name.
+fn is_frame_signature(signature: Signature) -> bool {
+    let bytes = signature.to_be_bytes();
+    bytes.iter().all(|b| b.is_ascii_alphanumeric()) && !CHUNK_SIGNATURES.contains(&bytes)
+}
+
+/// Find the first matrix followed by non-zero padding in the frame
+/// starting at `frame_offset`.
+///
+/// Returns the position of the matrix in the frame and its signature.
+/// Matrices after one of unknown data type can't be located and are not
+/// checked.
+fn find_nonzero_padding(
+    reader: &mut (impl Read + Seek),
+    frame_offset: u64,
+    num_matrices: usize,
+) -> io::Result<Option<(usize, Signature)>> {
+    let be_u32 = |bytes: &[u8]| u32::from_be_bytes(bytes.try_into().expect("4 bytes"));
+
+    let header_size = FRAME_SIZE_PREFIX + u64::from(write::FRAME_HEADER_SIZE);
+    reader.seek(SeekFrom::Start(frame_offset + header_size))?;
+    for matrix in 0..num_matrices {
+        let mut header = [0u8; write::MATRIX_HEADER_SIZE as usize];
+        reader.read_exact(&mut header)?;
+
+        let signature = be_u32(&header[0..4]);
+        let Some(element) = write::data_type_size(be_u32(&header[4..8])) else {
+            return Ok(None);
+        };
+        let (rows, cols) = (be_u32(&header[8..12]), be_u32(&header[12..16]));
+        let data = u64::from(rows) * u64::from(cols) * element as u64;
+
+        let mut padding = vec![0u8; write::padding(data as usize)];
+        reader.seek(SeekFrom::Current(data as i64))?;
+        reader.read_exact(&mut padding)?;
+        if padding.iter().any(|&b| b != 0) {
+            return Ok(Some((matrix, signature)));
+        }
+    }
+    Ok(None)
+}
+
+/// Get a new, unique path for a temporary SDIF file.
+pub(crate) fn temp_sdif_path() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    std::env::temp_dir().join(format!(
+        "sdif-rs-{}-{}.sdif",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Copy of data read from a stream or memory, which the C library can open
+/// by path.
+#[derive(Debug)]
+enum Spool {
+    /// Anonymous in-memory file, opened through `/proc/self/fd`.
+    #[cfg(target_os = "linux")]
+    Memory(File),
+
+    /// Temporary file, removed when the [`SdifFile`] is dropped.
+    TempFile(PathBuf),
+}
+
+impl Spool {
+    /// Copy a stream, in memory if the system allows it.
+    fn new(reader: &mut impl Read) -> Result<Self> {
+        #[cfg(target_os = "linux")]
+        if let Some(mut file) = memory_file() {
+            io::copy(reader, &mut file)?;
+            return Ok(Spool::Memory(file));
+        }
+
+        let path = temp_sdif_path();
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+        if let Err(e) = io::copy(reader, &mut file) {
+            drop(file);
+            let _ = fs::remove_file(&path);
+            return Err(e.into());
+        }
+        Ok(Spool::TempFile(path))
+    }
+
+    /// Get the path the copy can be opened from.
+    fn path(&self) -> PathBuf {
+        match self {
+            #[cfg(target_os = "linux")]
+            Spool::Memory(file) => fd_path(file),
+            Spool::TempFile(path) => path.clone(),
+        }
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        if let Spool::TempFile(path) = self {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Create an anonymous in-memory file that can be opened by path.
+///
+/// Returns `None` if the kernel has no `memfd_create` or `/proc` isn't
+/// mounted.
+#[cfg(target_os = "linux")]
+fn memory_file() -> Option<File> {
+    use std::ffi::{c_char, c_int, c_uint};
+    use std::os::fd::FromRawFd;
+
+    const MFD_CLOEXEC: c_uint = 0x0001;
+
+    extern "C" {
+        fn memfd_create(name: *const c_char, flags: c_uint) -> c_int;
+    }
+
+    // SAFETY: The name is a valid C string, and a non-negative result is
+    // a new file descriptor that nothing else owns.
+    let file = unsafe {
+        let fd = memfd_create(b"sdif-rs\0".as_ptr().cast(), MFD_CLOEXEC);
+        if fd < 0 {
+            return None;
+        }
+        File::from_raw_fd(fd)
+    };
+
+    fd_path(&file).exists().then_some(file)
+}
+
+/// Get the path of an open file under `/proc/self/fd`.
+#[cfg(target_os = "linux")]
+fn fd_path(file: &File) -> PathBuf {
+    use std::os::fd::AsRawFd;
+
+    PathBuf::from(format!("/proc/self/fd/{}", file.as_raw_fd()))
+}
+
+impl Drop for SdifFile {
+    fn drop(&mut self) {
+        // SAFETY: We own the handle and it's valid (NonNull).
+        // After this, the handle is invalid, but we're being dropped anyway.
+        unsafe {
+            SdifFClose(self.handle.as_ptr());
+        }
+    }
+}
+
+// PhantomData<*const ()> makes SdifFile !Send and !Sync automatically
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_options_default_is_lenient() {
+        assert!(!ReadOptions::default().strict);
+        assert!(ReadOptions::default().sidecar_index);
+        assert!(ReadOptions::new().strict(true).strict);
+        assert!(ReadOptions::default().aliases.is_empty());
+        assert!(!ReadOptions::default().verify_padding);
+    }
+
+    #[test]
+    fn test_find_nonzero_padding() {
+        // Frame at offset 0 with two 1x3 f32 matrices, 4 padding bytes each
+        let mut bytes = vec![0u8; 24];
+        for signature in [b"1FQ0", b"XPAD"] {
+            bytes.extend_from_slice(signature);
+            for value in [0x0004u32, 1, 3] {
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+            bytes.extend_from_slice(&[0x3f; 12]);
+            bytes.extend_from_slice(&[0; 4]);
+        }
+
+        let mut reader = io::Cursor::new(bytes.clone());
+        assert_eq!(find_nonzero_padding(&mut reader, 0, 2).unwrap(), None);
+
+        let last = bytes.len() - 1;
+        bytes[last] = b' ';
+        let mut reader = io::Cursor::new(bytes);
+        assert_eq!(
+            find_nonzero_padding(&mut reader, 0, 2).unwrap(),
+            Some((1, u32::from_be_bytes(*b"XPAD")))
+        );
+    }
+
+    #[test]
+    fn test_invalid_alias() {
+        let options = ReadOptions::new().alias("ETRC", "TRC");
+        let result = SdifFile::open_with_options("/nonexistent/path/to/file.sdif", options);
+        assert!(matches!(result, Err(Error::InvalidSignature { .. })));
+    }
+
+    #[test]
+    fn test_open_nonexistent() {
+        let result = SdifFile::open("/nonexistent/path/to/file.sdif");
+        assert!(result.is_err());
+
+        match result.unwrap_err() {
+            Error::OpenFailed { path } => {
+                assert!(path.to_str().unwrap().contains("nonexistent"));
+            }
+            other => panic!("Expected OpenFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_frame_signature() {
+        assert!(is_frame_signature(u32::from_be_bytes(*b"1TRC")));
+        assert!(!is_frame_signature(0x0000_0001));
+        assert!(!is_frame_signature(u32::from_be_bytes(*b"1TR ")));
+    }
+
+    #[test]
+    fn test_from_reader_invalid() {
+        let result = SdifFile::from_reader(&b"not an sdif file"[..]);
+        assert!(result.is_err());
     }
 
     // Additional tests require test fixtures - see integration tests
@@ -0,0 +1,369 @@
+//! Filter coefficients for resonance (1RES) data.
+//!
+//! Each row of a 1RES matrix describes a mode `(f, a, d, φ)` ringing as
+//!
+//! ```text
+//! a · exp(-d · t) · cos(2π · f · t + φ)
+//! ```
+//!
+//! A second-order filter has exactly this impulse response, so a bank of
+//! them driven by an excitation resynthesizes the resonance. With
+//! `r = exp(-d / fs)` and `θ = 2π · f / fs`, mode filters have the
+//! coefficients
+//!
+//! ```text
+//! b0 = a · cos(φ)               a1 = -2 · r · cos(θ)
+//! b1 = -a · r · cos(θ - φ)      a2 = r²
+//! b2 = 0
+//! ```
+//!
+//! [`FilterBank`] computes them for a sample rate `fs` and writes them as
+//! CSV, JSON, or a Faust program, ready to drop into a synthesis engine.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{filters::FilterBank, SdifFile};
+//!
+//! let file = SdifFile::open("bell.res.sdif")?;
+//! let bank = FilterBank::from_file(&file, 48000.0)?;
+//! std::fs::write("bell.dsp", bank.to_faust())?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame_data::MatrixData;
+
+/// Matrix signature of resonances.
+const RESONANCE_SIGNATURE: &str = "1RES";
+
+/// Coefficients of a second-order filter, normalized so that `a0 = 1`.
+///
+/// The filter computes
+/// `y[n] = b0·x[n] + b1·x[n-1] + b2·x[n-2] - a1·y[n-1] - a2·y[n-2]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Biquad {
+    /// Feed-forward coefficient of `x[n]`.
+    pub b0: f64,
+    /// Feed-forward coefficient of `x[n-1]`.
+    pub b1: f64,
+    /// Feed-forward coefficient of `x[n-2]`.
+    pub b2: f64,
+    /// Feedback coefficient of `y[n-1]`.
+    pub a1: f64,
+    /// Feedback coefficient of `y[n-2]`.
+    pub a2: f64,
+}
+
+impl Biquad {
+    /// Compute the filter whose impulse response is one resonance mode.
+    ///
+    /// `decay_rate` is in 1/s and `phase` in radians.
+    pub fn resonator(
+        frequency: f64,
+        amplitude: f64,
+        decay_rate: f64,
+        phase: f64,
+        sample_rate: f64,
+    ) -> Self {
+        let r = (-decay_rate / sample_rate).exp();
+        let theta = 2.0 * PI * frequency / sample_rate;
+
+        Biquad {
+            b0: amplitude * phase.cos(),
+            b1: -amplitude * r * (theta - phase).cos(),
+            b2: 0.0,
+            a1: -2.0 * r * theta.cos(),
+            a2: r * r,
+        }
+    }
+
+    /// Run the filter over `input`, starting from rest.
+    pub fn process(&self, input: &[f64]) -> Vec<f64> {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        input
+            .iter()
+            .map(|&x| {
+                let y = self.b0 * x + self.b1 * x1 + self.b2 * x2 - self.a1 * y1 - self.a2 * y2;
+                (x2, x1, y2, y1) = (x1, x, y1, y);
+                y
+            })
+            .collect()
+    }
+}
+
+/// The filter of one resonance mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModeFilter {
+    /// Frequency of the mode in Hz.
+    pub frequency: f64,
+
+    /// Amplitude of the mode.
+    pub amplitude: f64,
+
+    /// Decay rate of the mode in 1/s.
+    pub decay_rate: f64,
+
+    /// Phase of the mode in radians.
+    pub phase: f64,
+
+    /// Filter coefficients.
+    pub coefficients: Biquad,
+}
+
+/// Filters for all modes of a resonance, at one sample rate.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterBank {
+    /// Sample rate the coefficients are computed for, in Hz.
+    pub sample_rate: f64,
+
+    /// One filter per mode, in matrix row order.
+    pub filters: Vec<ModeFilter>,
+}
+
+impl FilterBank {
+    /// Compute the filters for the rows of a 1RES matrix.
+    ///
+    /// Columns are found by name when the matrix carries column names,
+    /// and by their standard position otherwise. Modes at or above the
+    /// Nyquist frequency are left out, since they can't be represented at
+    /// `sample_rate`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the matrix is not a 1RES matrix, lacks
+    ///   a column, or `sample_rate` is not positive
+    pub fn from_matrix(matrix: &MatrixData, sample_rate: f64) -> Result<Self> {
+        if matrix.signature != RESONANCE_SIGNATURE {
+            return Err(Error::invalid_format(format!(
+                "Expected a 1RES matrix, got {}",
+                matrix.signature
+            )));
+        }
+        if !(sample_rate > 0.0 && sample_rate.is_finite()) {
+            return Err(Error::invalid_format(format!(
+                "Sample rate must be positive, got {}",
+                sample_rate
+            )));
+        }
+
+        let column = |name: &str| {
+            matrix
+                .find_column(name)
+                .ok_or_else(|| Error::invalid_format(format!("1RES matrix has no {} column", name)))
+        };
+        let frequency_col = column("Frequency")?;
+        let amplitude_col = column("Amplitude")?;
+        let decay_col = column("DecayRate")?;
+        // Phase is optional; modes without one start in cosine phase
+        let phase_col = column("Phase").ok();
+
+        let filters = matrix
+            .rows()
+            .map(|row| {
+                let frequency = row[frequency_col];
+                let amplitude = row[amplitude_col];
+                let decay_rate = row[decay_col];
+                let phase = phase_col.map_or(0.0, |i| row[i]);
+                ModeFilter {
+                    frequency,
+                    amplitude,
+                    decay_rate,
+                    phase,
+                    coefficients: Biquad::resonator(
+                        frequency,
+                        amplitude,
+                        decay_rate,
+                        phase,
+                        sample_rate,
+                    ),
+                }
+            })
+            .filter(|f| f.frequency > 0.0 && f.frequency < sample_rate / 2.0)
+            .collect();
+
+        Ok(FilterBank {
+            sample_rate,
+            filters,
+        })
+    }
+
+    /// Compute the filters for the first 1RES matrix in a file.
+    ///
+    /// The file is read from its current position until a 1RES matrix is
+    /// found.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the file has no 1RES matrix
+    /// - Any error from reading the frames or [`from_matrix()`](Self::from_matrix)
+    pub fn from_file(file: &SdifFile, sample_rate: f64) -> Result<Self> {
+        for frame in file.frames() {
+            let frame = frame?.into_data()?;
+            if let Some(matrix) = frame
+                .matrices
+                .iter()
+                .find(|m| m.signature == RESONANCE_SIGNATURE)
+            {
+                return Self::from_matrix(matrix, sample_rate);
+            }
+        }
+        Err(Error::invalid_format("File has no 1RES matrix"))
+    }
+
+    /// Run all filters over `input` and sum their outputs.
+    pub fn process(&self, input: &[f64]) -> Vec<f64> {
+        let mut output = vec![0.0; input.len()];
+        for filter in &self.filters {
+            for (out, y) in output.iter_mut().zip(filter.coefficients.process(input)) {
+                *out += y;
+            }
+        }
+        output
+    }
+
+    /// Format the filters as CSV, one mode per line after a header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("frequency,amplitude,decay_rate,phase,b0,b1,b2,a1,a2\n");
+        for f in &self.filters {
+            let c = &f.coefficients;
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{}",
+                f.frequency, f.amplitude, f.decay_rate, f.phase, c.b0, c.b1, c.b2, c.a1, c.a2
+            );
+        }
+        csv
+    }
+
+    /// Format the filters as a Faust program.
+    ///
+    /// The program runs its input through all filters in parallel with
+    /// `fi.tf2` and sums them. The coefficients are only valid at
+    /// [`sample_rate`](Self::sample_rate), which the program notes in a
+    /// comment.
+    pub fn to_faust(&self) -> String {
+        let mut dsp = String::new();
+        let _ = writeln!(dsp, "// {} resonance modes", self.filters.len());
+        let _ = writeln!(
+            dsp,
+            "// Coefficients computed for a sample rate of {} Hz",
+            self.sample_rate
+        );
+        dsp.push_str("import(\"stdfaust.lib\");\n\n");
+
+        if self.filters.is_empty() {
+            dsp.push_str("process = _ : !, 0;\n");
+            return dsp;
+        }
+
+        dsp.push_str("process = _ <: (\n");
+        let last = self.filters.len() - 1;
+        for (i, f) in self.filters.iter().enumerate() {
+            let c = &f.coefficients;
+            let _ = writeln!(
+                dsp,
+                "    fi.tf2({:e}, {:e}, {:e}, {:e}, {:e}){} // {} Hz",
+                c.b0,
+                c.b1,
+                c.b2,
+                c.a1,
+                c.a2,
+                if i == last { "" } else { "," },
+                f.frequency
+            );
+        }
+        dsp.push_str(") :> _;\n");
+        dsp
+    }
+
+    /// Serialize the filters as pretty-printed JSON.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resonances() -> MatrixData {
+        MatrixData::new(
+            "1RES",
+            2,
+            4,
+            vec![440.0, 0.5, 3.0, 0.3, 30000.0, 1.0, 1.0, 0.0],
+        )
+    }
+
+    #[test]
+    fn test_resonator_impulse_response() {
+        let (frequency, amplitude, decay, phase, rate) = (440.0, 0.5, 3.0, 0.3, 8000.0);
+        let filter = Biquad::resonator(frequency, amplitude, decay, phase, rate);
+
+        let mut impulse = vec![0.0; 400];
+        impulse[0] = 1.0;
+        for (n, y) in filter.process(&impulse).into_iter().enumerate() {
+            let t = n as f64 / rate;
+            let expected = amplitude * (-decay * t).exp() * (2.0 * PI * frequency * t + phase).cos();
+            assert!((y - expected).abs() < 1e-9, "sample {}: {} != {}", n, y, expected);
+        }
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        let bank = FilterBank::from_matrix(&resonances(), 44100.0).unwrap();
+
+        // The 30 kHz mode is above Nyquist
+        assert_eq!(bank.filters.len(), 1);
+        assert_eq!(bank.filters[0].frequency, 440.0);
+        assert_eq!(bank.filters[0].phase, 0.3);
+
+        let wrong = MatrixData::new("1TRC", 1, 4, vec![0.0; 4]);
+        assert!(FilterBank::from_matrix(&wrong, 44100.0).is_err());
+        assert!(FilterBank::from_matrix(&resonances(), 0.0).is_err());
+    }
+
+    #[test]
+    fn test_from_matrix_uses_column_names() {
+        let mut matrix = MatrixData::new("1RES", 1, 3, vec![2.0, 220.0, 0.5]);
+        matrix.columns = vec![
+            "DecayRate".to_string(),
+            "Frequency".to_string(),
+            "Amplitude".to_string(),
+        ];
+
+        let bank = FilterBank::from_matrix(&matrix, 44100.0).unwrap();
+        let mode = &bank.filters[0];
+        assert_eq!(
+            (mode.frequency, mode.amplitude, mode.decay_rate, mode.phase),
+            (220.0, 0.5, 2.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let bank = FilterBank::from_matrix(&resonances(), 44100.0).unwrap();
+
+        let csv = bank.to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("frequency,amplitude"));
+        assert_eq!(lines[1].split(',').count(), 9);
+
+        let dsp = bank.to_faust();
+        assert!(dsp.contains("import(\"stdfaust.lib\");"));
+        assert_eq!(dsp.matches("fi.tf2(").count(), 1);
+        assert!(dsp.contains("44100 Hz"));
+    }
+}
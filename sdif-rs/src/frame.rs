@@ -4,18 +4,28 @@
 //! Frames are the primary unit of data organization in SDIF files.
 
 use std::marker::PhantomData;
+use std::ops::Range;
 
 use sdif_sys::{
-    SdifFCurrFrameSignature, SdifFCurrID, SdifFCurrNbMatrix, SdifFCurrTime,
-    SdifFReadFrameHeader, SdifFSkipFrameData,
-    SdifFileT,
+    SdifDisableErrorOutput, SdifEnableErrorOutput, SdifFCurrFrameSignature, SdifFCurrID,
+    SdifFCurrNbMatrix, SdifFCurrSignature, SdifFCurrTime, SdifFGetPos, SdifFGetSignature,
+    SdifFReadFrameHeader, SdifFSkipFrameData, SdifFSkipMatrix, SdifFileT, SdiffPosT,
 };
 
 use crate::error::{Error, Result};
 use crate::file::SdifFile;
-use crate::matrix::MatrixIterator;
+use crate::frame_data::{FrameData, MatrixData, OwnedFrame, TimeSlice};
+use crate::index::Index;
+use crate::matrix::{Matrix, MatrixIterator};
+use crate::metrics;
 use crate::signature::{signature_to_string, Signature};
 
+/// Frame size value used by writers that don't know the size in advance.
+const UNKNOWN_FRAME_SIZE: u32 = 0xffff_ffff;
+
+/// Bytes before the counted part of a frame: signature (4) + size (4).
+pub(crate) const FRAME_SIZE_PREFIX: u64 = 8;
+
 /// A single frame from an SDIF file.
 ///
 /// A frame represents a snapshot of data at a specific point in time.
@@ -56,6 +66,15 @@ pub struct Frame<'a> {
     /// Number of matrices in this frame.
     num_matrices: u32,
 
+    /// Position of the frame signature in the file.
+    byte_offset: u64,
+
+    /// Index of the frame, counted from where iteration started.
+    index: usize,
+
+    /// Total size of the frame on disk, if the writer recorded it.
+    byte_size: Option<u64>,
+
     /// Current matrix index during iteration.
     current_matrix: u32,
 
@@ -70,7 +89,7 @@ impl<'a> Frame<'a> {
     /// Create a new Frame from the current file state.
     ///
     /// This should only be called after SdifFReadFrameHeader succeeds.
-    pub(crate) fn from_current(file: &'a SdifFile) -> Self {
+    pub(crate) fn from_current(file: &'a SdifFile, byte_offset: u64, index: usize) -> Self {
         let handle = file.handle();
 
         let time = unsafe { SdifFCurrTime(handle) };
@@ -78,12 +97,26 @@ impl<'a> Frame<'a> {
         let stream_id = unsafe { SdifFCurrID(handle) }; // Get the stream ID from current frame
         let num_matrices = unsafe { SdifFCurrNbMatrix(handle) };
 
+        // SAFETY: the frame header was just read, so CurrFramH is valid
+        let size = unsafe {
+            let header = (*handle).CurrFramH;
+            if header.is_null() {
+                UNKNOWN_FRAME_SIZE
+            } else {
+                (*header).Size
+            }
+        };
+        let byte_size = (size != UNKNOWN_FRAME_SIZE).then(|| u64::from(size) + FRAME_SIZE_PREFIX);
+
         Frame {
             file,
             time,
             signature,
             stream_id,
             num_matrices,
+            byte_offset,
+            index,
+            byte_size,
             current_matrix: 0,
             finished: false,
             _phantom: PhantomData,
@@ -109,6 +142,9 @@ impl<'a> Frame<'a> {
 
     /// Get the frame type signature as a string (e.g., "1TRC").
     ///
+    /// Signatures aliased with [`ReadOptions::alias()`](crate::ReadOptions::alias)
+    /// are reported as their alias.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -121,14 +157,21 @@ impl<'a> Frame<'a> {
     /// # Ok::<(), sdif_rs::Error>(())
     /// ```
     pub fn signature(&self) -> String {
-        signature_to_string(self.signature)
+        signature_to_string(self.canonical_signature_raw())
     }
 
-    /// Get the frame type signature as a raw u32.
+    /// Get the frame type signature as a raw u32, as stored in the file.
+    ///
+    /// Unlike [`signature()`](Self::signature), aliases are not applied.
     pub fn signature_raw(&self) -> Signature {
         self.signature
     }
 
+    /// Get the frame type signature as a raw u32, with aliases applied.
+    pub fn canonical_signature_raw(&self) -> Signature {
+        self.file.canonical_signature(self.signature)
+    }
+
     /// Get the stream ID for this frame.
     ///
     /// Stream IDs allow multiple parallel streams in one SDIF file.
@@ -137,6 +180,36 @@ impl<'a> Frame<'a> {
         self.stream_id
     }
 
+    /// Get the position of this frame in the file, in bytes.
+    ///
+    /// The offset points at the frame signature and can be passed to
+    /// [`SdifFile::seek_to_offset()`] to resume reading at this frame,
+    /// for example from an index saved by a previous run.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// let file = SdifFile::open("input.sdif")?;
+    /// let offsets: Vec<(f64, u64)> = file
+    ///     .frames()
+    ///     .map(|f| f.map(|f| (f.time(), f.byte_offset())))
+    ///     .collect::<Result<_, _>>()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn byte_offset(&self) -> u64 {
+        self.byte_offset
+    }
+
+    /// Get the size of this frame in the file, in bytes.
+    ///
+    /// This covers the whole frame, from its signature to the padding
+    /// after its last matrix. Returns `None` if the writer left the frame
+    /// size unspecified, as streaming writers may do.
+    pub fn byte_size(&self) -> Option<u64> {
+        self.byte_size
+    }
+
     /// Get the number of matrices in this frame.
     ///
     /// Most frames contain a single matrix, but some frame types
@@ -156,7 +229,7 @@ impl<'a> Frame<'a> {
     /// # use sdif_rs::SdifFile;
     /// # let file = SdifFile::open("input.sdif")?;
     /// for frame in file.frames() {
-    ///     let frame = frame?;
+    ///     let mut frame = frame?;
     ///     for matrix in frame.matrices() {
     ///         let matrix = matrix?;
     ///         println!("  Matrix '{}': {}x{}",
@@ -172,6 +245,78 @@ impl<'a> Frame<'a> {
         MatrixIterator::new(self)
     }
 
+    /// Read all matrices of the frame into an owned [`FrameData`].
+    ///
+    /// Matrix values are widened to f64; each [`MatrixData`] records the
+    /// data type it was stored as.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::DataTypeMismatch`] if a matrix is not `Float4` or `Float8`
+    /// - Any error from reading the matrices
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// let file = SdifFile::open("input.sdif")?;
+    /// for frame in file.frames() {
+    ///     let frame = frame?.into_data()?;
+    ///     println!("{} matrices at {:.3}s", frame.matrices.len(), frame.time);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn into_data(mut self) -> Result<FrameData> {
+        let mut data = FrameData::new(self.signature(), self.time, self.stream_id);
+        data.matrices.reserve(self.num_matrices());
+
+        for matrix in self.matrices() {
+            data.matrices.push(matrix_data(matrix?)?);
+        }
+
+        Ok(data)
+    }
+
+    /// Read all matrices of the frame into an owned [`OwnedFrame`].
+    ///
+    /// Matrix payloads are copied as raw bytes and decoded only when
+    /// accessed, which keeps loading cheap when most values are never
+    /// looked at. Use [`into_data()`](Self::into_data) to decode eagerly.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::DataTypeMismatch`] if a matrix's data type is unknown
+    /// - Any error from reading the matrices
+    pub fn into_owned(mut self) -> Result<OwnedFrame> {
+        let mut owned = OwnedFrame {
+            time: self.time,
+            signature: self.signature(),
+            stream_id: self.stream_id,
+            matrices: Vec::with_capacity(self.num_matrices()),
+        };
+
+        for matrix in self.matrices() {
+            owned.matrices.push(matrix?.into_owned()?);
+        }
+
+        Ok(owned)
+    }
+
+    /// Create a ReadError located at the current position in this frame.
+    pub(crate) fn read_error(&self, message: impl Into<String>) -> Error {
+        let mut pos: SdiffPosT = 0;
+        let byte_offset = if unsafe { SdifFGetPos(self.handle(), &mut pos) } == 0 {
+            pos as u64
+        } else {
+            self.byte_offset
+        };
+        Error::read_error(message).with_location(
+            Some(byte_offset),
+            Some(self.index),
+            Some(self.signature()),
+        )
+    }
+
     /// Get the file handle for matrix reading.
     pub(crate) fn handle(&self) -> *mut SdifFileT {
         self.file.handle()
@@ -197,17 +342,42 @@ impl<'a> Frame<'a> {
         self.finished = true;
     }
 
-    /// Skip remaining matrices in this frame.
+    /// Get the parent file.
+    pub(crate) fn file(&self) -> &'a SdifFile {
+        self.file
+    }
+
+    /// Skip remaining matrices in this frame and read the next signature.
     ///
-    /// Called when the frame is dropped without reading all matrices.
+    /// Called when the frame is dropped, whether or not all matrices
+    /// were read.
     fn skip_remaining(&mut self) {
-        if !self.finished && self.current_matrix < self.num_matrices {
-            // Skip remaining frame data
+        let handle = self.file.handle();
+
+        if self.current_matrix == 0 {
+            // Nothing read yet: skip the whole frame body at once
             unsafe {
-                SdifFSkipFrameData(self.file.handle());
+                SdifFSkipFrameData(handle);
+            }
+        } else {
+            // Part of the frame was read: skip the rest matrix by matrix
+            while self.current_matrix < self.num_matrices {
+                unsafe {
+                    SdifDisableErrorOutput();
+                    SdifFSkipMatrix(handle);
+                    SdifEnableErrorOutput();
+                }
+                self.current_matrix += 1;
             }
         }
         self.finished = true;
+
+        // Load the next frame's signature (empty at end of file)
+        let mut bytes_read = 0;
+        unsafe {
+            SdifFGetSignature(handle, &mut bytes_read);
+        }
+        self.file.set_signature_pending(true);
     }
 }
 
@@ -223,6 +393,8 @@ impl Drop for Frame<'_> {
 pub struct FrameIterator<'a> {
     file: &'a SdifFile,
     finished: bool,
+    /// Number of frames read so far.
+    index: usize,
 }
 
 impl<'a> FrameIterator<'a> {
@@ -230,6 +402,75 @@ impl<'a> FrameIterator<'a> {
         FrameIterator {
             file,
             finished: false,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> FrameIterator<'a> {
+    /// Group frames that share the same time into [`TimeSlice`]s.
+    ///
+    /// A slice starts at the next unread frame and takes every following
+    /// frame whose time is at most `tolerance` seconds later. This is how
+    /// synthesis engines consume files that store F0, partials and
+    /// envelopes as separate frames at the same instant.
+    ///
+    /// Frames are read into owned [`FrameData`], so matrices must be
+    /// `Float4` or `Float8`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("voice.sdif")?;
+    /// for slice in file.frames().group_by_time(1e-9) {
+    ///     let slice = slice?;
+    ///     let f0 = slice.matrix("1FQ0").and_then(|m| m.get(0, 0));
+    ///     let partials = slice.matrix("1TRC").map_or(0, |m| m.rows);
+    ///     println!("{:.3}s: F0 {:?}, {} partials", slice.time, f0, partials);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn group_by_time(self, tolerance: f64) -> TimeSlices<'a> {
+        TimeSlices {
+            frames: self,
+            tolerance,
+            pending: None,
+        }
+    }
+
+    /// Keep only frames whose `signature` matrix satisfies `predicate`.
+    ///
+    /// The predicate gets the matrix rows, widened to f64. Matrices are
+    /// read in order until the one being tested, and only decoded once it
+    /// passes; when it fails, the rest of the frame is skipped. Frames
+    /// without a `signature` matrix are skipped too. Kept frames are read
+    /// into owned [`FrameData`], with every value widened to f64.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// // Voiced frames only: F0 confidence above 0.5
+    /// let file = SdifFile::open("voice.f0.sdif")?;
+    /// for frame in file.frames().filter_matrix_data("1FQ0", |rows| {
+    ///     rows.first().is_some_and(|r| r[1] > 0.5)
+    /// }) {
+    ///     let frame = frame?;
+    ///     println!("{:.3}s: {:?}", frame.time, frame.matrix("1FQ0").and_then(|m| m.get(0, 0)));
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn filter_matrix_data<F>(self, signature: &str, predicate: F) -> MatrixDataFilter<'a, F>
+    where
+        F: FnMut(&[&[f64]]) -> bool,
+    {
+        MatrixDataFilter {
+            frames: self,
+            signature: signature.to_string(),
+            predicate,
         }
     }
 }
@@ -238,29 +479,78 @@ impl<'a> Iterator for FrameIterator<'a> {
     type Item = Result<Frame<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.read_frame()? {
+                // Frames outside the selection are skipped when dropped
+                Ok(_) if !self.file.frame_is_selected() => continue,
+                Ok(frame) => {
+                    metrics::frame_read(frame.signature, frame.byte_size());
+                    return Some(Ok(frame));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl<'a> FrameIterator<'a> {
+    /// Read the next frame header, whether or not the frame is selected.
+    fn read_frame(&mut self) -> Option<Result<Frame<'a>>> {
         if self.finished {
             return None;
         }
 
-        let handle = self.file.handle();
+        if !self.file.signature_pending() {
+            return Some(Err(Error::invalid_state(
+                "Previous frame must be dropped before reading the next one",
+            )));
+        }
 
-        // Try to read the next frame header
-        let bytes_read = unsafe { SdifFReadFrameHeader(handle) };
+        let handle = self.file.handle();
 
-        if bytes_read == 0 {
-            // End of file or error
+        // The signature was read after the previous frame (or the ASCII
+        // chunks); an empty signature means end of file.
+        let signature = unsafe { SdifFCurrSignature(handle) };
+        if signature == 0 {
             self.finished = true;
             return None;
         }
 
-        if bytes_read < 0 {
-            // Read error
+        if let Err(e) = self.file.resolve_frame_type(signature) {
+            self.finished = true;
+            return Some(Err(e));
+        }
+
+        // The stream is positioned right after the signature
+        let mut pos: SdiffPosT = 0;
+        if unsafe { SdifFGetPos(handle, &mut pos) } != 0 {
             self.finished = true;
-            return Some(Err(Error::read_error("Failed to read frame header")));
+            return Some(Err(Error::read_error("Failed to get frame position").with_location(
+                None,
+                Some(self.index),
+                Some(signature_to_string(signature)),
+            )));
         }
+        let byte_offset = (pos as u64).saturating_sub(4);
 
-        // Successfully read a frame header
-        Some(Ok(Frame::from_current(self.file)))
+        // Read the rest of the frame header
+        let bytes_read = unsafe { SdifFReadFrameHeader(handle) };
+        if bytes_read == 0 {
+            self.finished = true;
+            return Some(Err(Error::read_error("Failed to read frame header").with_location(
+                Some(byte_offset),
+                Some(self.index),
+                Some(signature_to_string(signature)),
+            )));
+        }
+
+        self.file.set_signature_pending(false);
+        let frame = Frame::from_current(self.file, byte_offset, self.index);
+        self.index += 1;
+        if let Err(e) = self.file.check_padding(&frame) {
+            return Some(Err(e));
+        }
+        Some(Ok(frame))
     }
 }
 
@@ -270,6 +560,314 @@ impl Drop for FrameIterator<'_> {
     }
 }
 
+/// Iterator over the frames of a file from the last to the first.
+///
+/// Created by [`SdifFile::frames_rev()`].
+pub struct ReverseFrames<'a> {
+    frames: FrameIterator<'a>,
+    index: &'a Index,
+    /// Number of frames not returned yet; the next one is the last of them.
+    remaining: usize,
+}
+
+impl<'a> ReverseFrames<'a> {
+    pub(crate) fn new(frames: FrameIterator<'a>, index: &'a Index) -> Self {
+        ReverseFrames {
+            frames,
+            index,
+            remaining: index.len(),
+        }
+    }
+}
+
+impl<'a> Iterator for ReverseFrames<'a> {
+    type Item = Result<Frame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            // Moving away from a frame still being read would corrupt it
+            if !self.frames.file.signature_pending() {
+                return Some(Err(Error::invalid_state(
+                    "Previous frame must be dropped before reading the next one",
+                )));
+            }
+
+            self.remaining -= 1;
+            let offset = self.index.frames()[self.remaining].byte_offset;
+            if let Err(e) = self.frames.file.move_to_offset(offset) {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+
+            self.frames.finished = false;
+            self.frames.index = self.remaining;
+            // The index covers every frame; those outside the selection
+            // are skipped when dropped
+            match self.frames.read_frame()? {
+                Ok(_) if !self.frames.file.frame_is_selected() => continue,
+                Ok(frame) => {
+                    metrics::frame_read(frame.signature, frame.byte_size());
+                    return Some(Ok(frame));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Frames outside a selection are skipped, so fewer may remain
+        let lower = if self.frames.file.has_selection() { 0 } else { self.remaining };
+        (lower, Some(self.remaining))
+    }
+}
+
+/// Selects frames by signature, stream and time.
+///
+/// Used with [`SdifFile::frames_filtered()`]. Each criterion left empty
+/// matches every frame.
+///
+/// # Example
+///
+/// ```
+/// use sdif_rs::FrameFilter;
+///
+/// let filter = FrameFilter::new()
+///     .signature("1TRC")
+///     .stream_id(0)
+///     .time_range(1.0..2.5);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameFilter {
+    /// Frame signatures to keep, or all if empty.
+    pub signatures: Vec<String>,
+
+    /// Stream IDs to keep, or all if empty.
+    pub stream_ids: Vec<u32>,
+
+    /// Times to keep in seconds, including the start but not the end.
+    pub time_range: Option<Range<f64>>,
+}
+
+impl FrameFilter {
+    /// Create a filter that keeps every frame.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep frames with signature `signature`, in addition to any
+    /// signatures already selected.
+    pub fn signature(mut self, signature: &str) -> Self {
+        self.signatures.push(signature.to_string());
+        self
+    }
+
+    /// Keep frames of stream `stream_id`, in addition to any streams
+    /// already selected.
+    pub fn stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_ids.push(stream_id);
+        self
+    }
+
+    /// Keep frames whose time is in `range`.
+    pub fn time_range(mut self, range: Range<f64>) -> Self {
+        self.time_range = Some(range);
+        self
+    }
+
+    /// Check whether a frame passes the filter.
+    pub fn matches(&self, frame: &Frame<'_>) -> bool {
+        (self.signatures.is_empty() || self.signatures.contains(&frame.signature()))
+            && (self.stream_ids.is_empty() || self.stream_ids.contains(&frame.stream_id()))
+            && self
+                .time_range
+                .as_ref()
+                .map_or(true, |range| range.contains(&frame.time()))
+    }
+
+    /// Check whether no frame at or after `time` can pass the filter.
+    fn is_past(&self, time: f64) -> bool {
+        self.time_range.as_ref().is_some_and(|range| time >= range.end)
+    }
+}
+
+/// Iterator over the frames that pass a [`FrameFilter`].
+///
+/// Created by [`SdifFile::frames_filtered()`].
+pub struct FilteredFrames<'a> {
+    frames: FrameIterator<'a>,
+    filter: FrameFilter,
+    /// Whether the file's index shows that frame times never decrease.
+    time_ordered: bool,
+}
+
+impl<'a> FilteredFrames<'a> {
+    pub(crate) fn new(frames: FrameIterator<'a>, filter: FrameFilter) -> Self {
+        let time_ordered = frames.file.index().is_some_and(Index::is_time_ordered);
+        FilteredFrames {
+            frames,
+            filter,
+            time_ordered,
+        }
+    }
+}
+
+impl<'a> Iterator for FilteredFrames<'a> {
+    type Item = Result<Frame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = match self.frames.next()? {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if self.filter.matches(&frame) {
+                return Some(Ok(frame));
+            }
+            // Nothing later is in range when frame times never decrease
+            if self.time_ordered && self.filter.is_past(frame.time()) {
+                self.frames.finished = true;
+                return None;
+            }
+            // Dropping the unread frame skips its data
+        }
+    }
+}
+
+/// Iterator over groups of frames with the same time.
+///
+/// Created by [`FrameIterator::group_by_time()`].
+pub struct TimeSlices<'a> {
+    frames: FrameIterator<'a>,
+    tolerance: f64,
+    /// First frame of the next slice, read while closing the previous one.
+    pending: Option<FrameData>,
+}
+
+impl TimeSlices<'_> {
+    /// Read the next frame into owned data.
+    fn next_frame(&mut self) -> Option<Result<FrameData>> {
+        self.frames.next().map(|frame| frame.and_then(Frame::into_data))
+    }
+}
+
+impl Iterator for TimeSlices<'_> {
+    type Item = Result<TimeSlice>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = match self.pending.take() {
+            Some(frame) => frame,
+            None => match self.next_frame()? {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(e)),
+            },
+        };
+
+        let mut slice = TimeSlice {
+            time: first.time,
+            frames: vec![first],
+        };
+
+        while let Some(frame) = self.next_frame() {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if frame.time - slice.time <= self.tolerance {
+                slice.frames.push(frame);
+            } else {
+                self.pending = Some(frame);
+                break;
+            }
+        }
+
+        Some(Ok(slice))
+    }
+}
+
+/// Iterator over the frames whose matrix passes a test.
+///
+/// Created by [`FrameIterator::filter_matrix_data()`].
+pub struct MatrixDataFilter<'a, F> {
+    frames: FrameIterator<'a>,
+    signature: String,
+    predicate: F,
+}
+
+impl<F> MatrixDataFilter<'_, F>
+where
+    F: FnMut(&[&[f64]]) -> bool,
+{
+    /// Read a frame up to its tested matrix, and the rest if it passes.
+    fn read_if_matching(&mut self, mut frame: Frame<'_>) -> Result<Option<FrameData>> {
+        let mut data = FrameData::new(frame.signature(), frame.time(), frame.stream_id());
+        // Matrices before the tested one, kept undecoded until it passes
+        let mut before = Vec::new();
+        let mut tested = false;
+
+        for matrix in frame.matrices() {
+            let matrix = matrix?;
+            if tested {
+                data.matrices.push(matrix.into_owned()?.into());
+            } else if matrix.signature() == self.signature {
+                let matrix = matrix.into_owned()?;
+                let rows: Vec<&[f64]> = (0..matrix.rows).filter_map(|row| matrix.row(row)).collect();
+                if !(self.predicate)(&rows) {
+                    return Ok(None);
+                }
+                tested = true;
+                data.matrices.extend(before.drain(..).map(MatrixData::from));
+                data.matrices.push(matrix.into());
+            } else {
+                before.push(matrix.into_owned()?);
+            }
+        }
+
+        Ok(tested.then_some(data))
+    }
+}
+
+impl<F> Iterator for MatrixDataFilter<'_, F>
+where
+    F: FnMut(&[&[f64]]) -> bool,
+{
+    type Item = Result<FrameData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = match self.frames.next()? {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(e)),
+            };
+
+            match self.read_if_matching(frame) {
+                Ok(Some(data)) => return Some(Ok(data)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Read a matrix into owned data, widening its values to f64.
+fn matrix_data(matrix: Matrix<'_>) -> Result<MatrixData> {
+    let signature = matrix.signature();
+    let (rows, cols) = matrix.shape();
+    let data_type = matrix.data_type();
+    let columns = matrix.columns();
+
+    Ok(MatrixData {
+        signature,
+        rows,
+        cols,
+        data_type,
+        columns,
+        data: matrix.data_f64()?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     // Tests require test fixtures - see integration tests
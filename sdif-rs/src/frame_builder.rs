@@ -2,17 +2,29 @@
 //!
 //! `FrameBuilder` provides a way to add multiple matrices to a single frame
 //! before writing it to the file. Use `SdifWriter::new_frame()` to create one.
+//!
+//! The matrix list lives inline for the usual one or two matrices.
+//! [`FrameBuilder::add_matrix_ref()`] and
+//! [`SdifWriter::write_frame_data()`] borrow matrix data instead of
+//! copying it, so writing a frame allocates nothing per frame unless data
+//! has to be converted.
+
+use std::borrow::Cow;
 
-use sdif_sys::{
-    SdifFSetCurrFrameHeader, SdifFSetCurrMatrixHeader,
-    SdifFWriteFrameHeader, SdifFWriteMatrixHeader, SdifFWriteMatrixData,
-    SdifFWritePadding, SdifDataTypeET_eFloat4, SdifDataTypeET_eFloat8,
-};
+use smallvec::SmallVec;
 
+use sdif_sys::write::{self, MatrixElement};
+use sdif_sys::SdifDataTypeET;
+
+use crate::data_type::DataType;
 use crate::error::{Error, Result};
+use crate::frame_data::{transpose, MatrixData};
 use crate::signature::string_to_signature;
 use crate::writer::SdifWriter;
 
+/// Number of matrices stored inline before spilling to the heap.
+const INLINE_MATRICES: usize = 2;
+
 /// Builder for frames with multiple matrices.
 ///
 /// Created by [`SdifWriter::new_frame()`]. Matrices are added with
@@ -57,24 +69,137 @@ pub struct FrameBuilder<'a> {
     stream_id: u32,
 
     /// Matrices to write (collected before writing frame header).
-    matrices: Vec<MatrixData>,
+    matrices: SmallVec<[PendingMatrix<'a>; INLINE_MATRICES]>,
 
     /// Whether finish() was called.
     finished: bool,
 }
 
 /// Internal storage for a matrix's data.
-struct MatrixData {
+pub(crate) struct PendingMatrix<'a> {
     signature: u32,
     rows: u32,
     cols: u32,
-    data: MatrixDataType,
+    data: MatrixDataType<'a>,
+}
+
+impl<'a> PendingMatrix<'a> {
+    /// Validate an owned matrix and convert it to its written data type.
+    ///
+    /// `Float8` data is borrowed; `Float4`, integer and text data is
+    /// converted into a copy.
+    pub(crate) fn from_data(matrix: &'a MatrixData) -> Result<Self> {
+        let signature = string_to_signature(&matrix.signature)?;
+        let (rows, cols) = (matrix.rows, matrix.cols);
+
+        if matrix.data.len() != rows * cols {
+            return Err(Error::InvalidDimensions { rows, cols });
+        }
+
+        let data = match matrix.data_type {
+            DataType::Float4 => MatrixDataType::narrow(&matrix.data),
+            DataType::Float8 => MatrixDataType::Float64(Cow::Borrowed(&matrix.data)),
+            DataType::Int1 => MatrixDataType::convert::<i8>(&matrix.data),
+            DataType::Int2 => MatrixDataType::convert::<i16>(&matrix.data),
+            DataType::Int4 => MatrixDataType::convert::<i32>(&matrix.data),
+            DataType::UInt1 => MatrixDataType::convert::<u8>(&matrix.data),
+            DataType::UInt2 => MatrixDataType::convert::<u16>(&matrix.data),
+            DataType::UInt4 => MatrixDataType::convert::<u32>(&matrix.data),
+            DataType::Text => {
+                let bytes = matrix.data.iter().map(|&v| u8::from_f64(v)).collect();
+                MatrixDataType::Other(DataType::Text as SdifDataTypeET, bytes)
+            }
+            other => return Err(Error::type_mismatch("numeric or text", other.to_string())),
+        };
+
+        Ok(PendingMatrix {
+            signature,
+            rows: rows as u32,
+            cols: cols as u32,
+            data,
+        })
+    }
+
+    /// Copy borrowed data so the matrix no longer borrows its source.
+    fn into_owned(self) -> PendingMatrix<'static> {
+        PendingMatrix {
+            signature: self.signature,
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.into_owned(),
+        }
+    }
 }
 
-/// Matrix data can be f32 or f64.
-enum MatrixDataType {
-    Float32(Vec<f32>),
-    Float64(Vec<f64>),
+/// Matrix data can be f32 or f64, borrowed from the caller or converted.
+///
+/// Integer and text data is stored as the bytes to write.
+enum MatrixDataType<'a> {
+    Float32(Cow<'a, [f32]>),
+    Float64(Cow<'a, [f64]>),
+    Other(SdifDataTypeET, Vec<u8>),
+}
+
+impl<'a> MatrixDataType<'a> {
+    /// Convert f64 data to f32 for a `Float4` matrix.
+    fn narrow(data: &[f64]) -> Self {
+        MatrixDataType::Float32(data.iter().map(|&v| v as f32).collect())
+    }
+
+    /// Copy elements of another type as the bytes to write.
+    fn copy_of<T: MatrixElement>(data: &[T]) -> Self {
+        MatrixDataType::Other(T::DATA_TYPE, write::bytes_of(data).to_vec())
+    }
+
+    /// Convert f64 data to an integer type, saturating at its bounds.
+    fn convert<T: MatrixElement + FromF64>(data: &[f64]) -> Self {
+        let data: Vec<T> = data.iter().map(|&v| T::from_f64(v)).collect();
+        Self::copy_of(&data)
+    }
+
+    /// Copy borrowed data; owned data is moved.
+    fn into_owned(self) -> MatrixDataType<'static> {
+        match self {
+            MatrixDataType::Float32(v) => MatrixDataType::Float32(Cow::Owned(v.into_owned())),
+            MatrixDataType::Float64(v) => MatrixDataType::Float64(Cow::Owned(v.into_owned())),
+            MatrixDataType::Other(data_type, v) => MatrixDataType::Other(data_type, v),
+        }
+    }
+
+    /// Get the SDIF data type code of the data.
+    fn sdif_type(&self) -> SdifDataTypeET {
+        match self {
+            MatrixDataType::Float32(_) => f32::DATA_TYPE,
+            MatrixDataType::Float64(_) => f64::DATA_TYPE,
+            MatrixDataType::Other(data_type, _) => *data_type,
+        }
+    }
+
+    /// Get the data as the bytes to write.
+    fn bytes(&self) -> &[u8] {
+        match self {
+            MatrixDataType::Float32(v) => write::bytes_of(v),
+            MatrixDataType::Float64(v) => write::bytes_of(v),
+            MatrixDataType::Other(_, v) => v,
+        }
+    }
+
+    /// Reorder row-major data of a `rows` x `cols` matrix to column-major.
+    fn to_column_major(&self, rows: usize, cols: usize) -> Self {
+        match self {
+            MatrixDataType::Float32(v) => {
+                MatrixDataType::Float32(Cow::Owned(transpose(v, rows, cols)))
+            }
+            MatrixDataType::Float64(v) => {
+                MatrixDataType::Float64(Cow::Owned(transpose(v, rows, cols)))
+            }
+            MatrixDataType::Other(data_type, v) => {
+                let size = write::data_type_size(*data_type).unwrap_or(1);
+                let elements: Vec<&[u8]> = v.chunks_exact(size).collect();
+                MatrixDataType::Other(*data_type, transpose(&elements, rows, cols).concat())
+            }
+        }
+    }
 }
 
 impl<'a> FrameBuilder<'a> {
@@ -90,13 +215,16 @@ impl<'a> FrameBuilder<'a> {
             signature,
             time,
             stream_id,
-            matrices: Vec::new(),
+            matrices: SmallVec::new(),
             finished: false,
         }
     }
 
     /// Add a matrix with f64 data to the frame.
     ///
+    /// The data is written as `Float4` if that is the default data type
+    /// set for the matrix signature on the builder.
+    ///
     /// # Arguments
     ///
     /// * `signature` - Matrix type signature (e.g., "1TRC")
@@ -113,12 +241,68 @@ impl<'a> FrameBuilder<'a> {
     /// - [`Error::InvalidSignature`] if the signature is invalid
     /// - [`Error::InvalidDimensions`] if data length doesn't match rows*cols
     pub fn add_matrix(
-        mut self,
+        self,
         signature: &str,
         rows: usize,
         cols: usize,
         data: &[f64],
     ) -> Result<Self> {
+        let matrix = self.pending_f64(signature, rows, cols, Cow::Borrowed(data))?;
+        Ok(self.push_matrix(matrix.into_owned()))
+    }
+
+    /// Add a matrix with borrowed f64 data to the frame.
+    ///
+    /// Like [`add_matrix()`](Self::add_matrix), but the data is borrowed
+    /// until the frame is written instead of being copied, which saves an
+    /// allocation and a copy for large matrices. Data converted to the
+    /// signature's default type `Float4` is still copied.
+    ///
+    /// The data must outlive the builder. Because the builder checks on
+    /// drop that [`finish()`](Self::finish) was called, the data must be
+    /// bound to a variable declared before the builder; a temporary such
+    /// as `&[1.0, 2.0]` is rejected by the borrow checker.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if the signature is invalid
+    /// - [`Error::InvalidDimensions`] if data length doesn't match rows*cols
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let mut writer = SdifFile::builder()
+    /// #     .create("output.sdif")?
+    /// #     .add_matrix_type("1STF", &["Real", "Imaginary"])?
+    /// #     .add_frame_type("1STF", &["1STF Spectrum"])?
+    /// #     .build()?;
+    /// let bins = vec![0.0; 2 * 4096];
+    ///
+    /// writer.new_frame("1STF", 0.0, 0)?
+    ///     .add_matrix_ref("1STF", 4096, 2, &bins)?
+    ///     .finish()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn add_matrix_ref(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &'a [f64],
+    ) -> Result<Self> {
+        let matrix = self.pending_f64(signature, rows, cols, Cow::Borrowed(data))?;
+        Ok(self.push_matrix(matrix))
+    }
+
+    /// Validate f64 data, converting it to the default type for its signature.
+    fn pending_f64<'d>(
+        &self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: Cow<'d, [f64]>,
+    ) -> Result<PendingMatrix<'d>> {
         let sig = string_to_signature(signature)?;
 
         let expected_len = rows * cols;
@@ -126,43 +310,205 @@ impl<'a> FrameBuilder<'a> {
             return Err(Error::InvalidDimensions { rows, cols });
         }
 
-        self.matrices.push(MatrixData {
+        let data = match self.writer.default_data_type(sig) {
+            DataType::Float4 => MatrixDataType::narrow(&data),
+            _ => MatrixDataType::Float64(data),
+        };
+
+        Ok(PendingMatrix {
             signature: sig,
             rows: rows as u32,
             cols: cols as u32,
-            data: MatrixDataType::Float64(data.to_vec()),
-        });
-
-        Ok(self)
+            data,
+        })
     }
 
     /// Add a matrix with f32 data to the frame.
     ///
     /// Similar to [`add_matrix()`](Self::add_matrix) but for 32-bit floats.
     pub fn add_matrix_f32(
-        mut self,
+        self,
         signature: &str,
         rows: usize,
         cols: usize,
         data: &[f32],
     ) -> Result<Self> {
-        let sig = string_to_signature(signature)?;
+        let matrix = pending_f32(signature, rows, cols, Cow::Borrowed(data))?;
+        Ok(self.push_matrix(matrix.into_owned()))
+    }
 
-        let expected_len = rows * cols;
-        if data.len() != expected_len {
+    /// Add a matrix with i8 data to the frame, stored as `Int1`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if the signature is invalid
+    /// - [`Error::InvalidDimensions`] if data length doesn't match rows*cols
+    pub fn add_matrix_i8(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[i8],
+    ) -> Result<Self> {
+        self.add_matrix_of(signature, rows, cols, data)
+    }
+
+    /// Add a matrix with i16 data to the frame, stored as `Int2`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`add_matrix_i8()`](Self::add_matrix_i8).
+    pub fn add_matrix_i16(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[i16],
+    ) -> Result<Self> {
+        self.add_matrix_of(signature, rows, cols, data)
+    }
+
+    /// Add a matrix with i32 data to the frame, stored as `Int4`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`add_matrix_i8()`](Self::add_matrix_i8).
+    pub fn add_matrix_i32(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[i32],
+    ) -> Result<Self> {
+        self.add_matrix_of(signature, rows, cols, data)
+    }
+
+    /// Add a matrix with u8 data to the frame, stored as `UInt1`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`add_matrix_i8()`](Self::add_matrix_i8).
+    pub fn add_matrix_u8(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[u8],
+    ) -> Result<Self> {
+        self.add_matrix_of(signature, rows, cols, data)
+    }
+
+    /// Add a matrix with u16 data to the frame, stored as `UInt2`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`add_matrix_i8()`](Self::add_matrix_i8).
+    pub fn add_matrix_u16(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[u16],
+    ) -> Result<Self> {
+        self.add_matrix_of(signature, rows, cols, data)
+    }
+
+    /// Add a matrix with u32 data to the frame, stored as `UInt4`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`add_matrix_i8()`](Self::add_matrix_i8).
+    pub fn add_matrix_u32(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[u32],
+    ) -> Result<Self> {
+        self.add_matrix_of(signature, rows, cols, data)
+    }
+
+    /// Add a text matrix to the frame.
+    ///
+    /// The text is stored as UTF-8 in a single column of type `Text`,
+    /// one byte per row, as labels and markers usually are.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if the signature is invalid
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let mut writer = SdifFile::builder()
+    /// #     .create("output.sdif")?
+    /// #     .add_matrix_type("1LAB", &["Label"])?
+    /// #     .add_frame_type("1LAB", &["1LAB Label"])?
+    /// #     .build()?;
+    /// writer.new_frame("1LAB", 1.5, 0)?
+    ///     .add_matrix_text("1LAB", "chorus")?
+    ///     .finish()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn add_matrix_text(self, signature: &str, text: &str) -> Result<Self> {
+        let matrix = PendingMatrix {
+            signature: string_to_signature(signature)?,
+            rows: text.len() as u32,
+            cols: 1,
+            data: MatrixDataType::Other(
+                DataType::Text as SdifDataTypeET,
+                text.as_bytes().to_vec(),
+            ),
+        };
+        Ok(self.push_matrix(matrix))
+    }
+
+    /// Validate and add a matrix of integer data.
+    fn add_matrix_of<T: MatrixElement>(
+        self,
+        signature: &str,
+        rows: usize,
+        cols: usize,
+        data: &[T],
+    ) -> Result<Self> {
+        let sig = string_to_signature(signature)?;
+        if data.len() != rows * cols {
             return Err(Error::InvalidDimensions { rows, cols });
         }
 
-        self.matrices.push(MatrixData {
+        let matrix = PendingMatrix {
             signature: sig,
             rows: rows as u32,
             cols: cols as u32,
-            data: MatrixDataType::Float32(data.to_vec()),
-        });
+            data: MatrixDataType::copy_of(data),
+        };
+        Ok(self.push_matrix(matrix))
+    }
 
+    /// Add an owned matrix to the frame.
+    ///
+    /// The matrix is written with its own data type, regardless of the
+    /// builder's default for its signature. Values of integer and text
+    /// matrices are rounded towards zero and saturate at the bounds of the
+    /// type; the values of a text matrix are its bytes.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if the signature is invalid
+    /// - [`Error::InvalidDimensions`] if data length doesn't match rows*cols
+    /// - [`Error::DataTypeMismatch`] if the data type is unknown
+    pub fn add_matrix_data(mut self, matrix: &MatrixData) -> Result<Self> {
+        self.matrices.push(PendingMatrix::from_data(matrix)?.into_owned());
         Ok(self)
     }
 
+    /// Add a matrix that was already validated.
+    pub(crate) fn push_matrix(mut self, matrix: PendingMatrix<'a>) -> Self {
+        self.matrices.push(matrix);
+        self
+    }
+
     /// Finalize and write the frame to the file.
     ///
     /// This writes the frame header followed by all matrices.
@@ -183,124 +529,96 @@ impl<'a> FrameBuilder<'a> {
 
     /// Internal method to write the frame.
     fn write_frame(&mut self) -> Result<()> {
+        if self.writer.is_column_major() {
+            for matrix in &mut self.matrices {
+                matrix.data = matrix
+                    .data
+                    .to_column_major(matrix.rows as usize, matrix.cols as usize);
+            }
+        }
+
         let handle = self.writer.handle();
         let num_matrices = self.matrices.len() as u32;
 
         // Calculate total data size for frame header
-        let data_size = self.calculate_frame_size();
+        let data_size = write::frame_size(
+            self.matrices
+                .iter()
+                .map(|m| (m.data.sdif_type(), m.rows, m.cols)),
+        )?;
 
         unsafe {
-            // Set and write frame header
-            SdifFSetCurrFrameHeader(
+            write::write_frame_header(
                 handle,
                 self.signature,
-                data_size,
-                num_matrices,
                 self.stream_id,
                 self.time,
-            );
-
-            let header_bytes = SdifFWriteFrameHeader(handle);
-            if header_bytes == 0 {
-                return Err(Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to write frame header",
-                )));
-            }
+                num_matrices,
+                data_size,
+            )?;
 
             // Write each matrix
             for matrix in &self.matrices {
-                self.write_matrix(handle, matrix)?;
+                write::write_matrix(
+                    handle,
+                    matrix.signature,
+                    matrix.data.sdif_type(),
+                    matrix.rows,
+                    matrix.cols,
+                    matrix.data.bytes(),
+                )?;
             }
         }
 
-        self.writer.record_frame_written(self.time);
+        let rows = self.matrices.iter().map(|m| m.rows as usize).sum();
+        self.writer.record_frame_written(self.time, self.signature, self.stream_id, rows);
 
         Ok(())
     }
+}
 
-    /// Calculate the total size of frame data.
-    fn calculate_frame_size(&self) -> u32 {
-        let mut size = 0u32;
-
-        for matrix in &self.matrices {
-            // Matrix header size (signature + type + rows + cols = 16 bytes)
-            size += 16;
-
-            // Matrix data size
-            let element_size = match &matrix.data {
-                MatrixDataType::Float32(_) => 4,
-                MatrixDataType::Float64(_) => 8,
-            };
-            let data_bytes = matrix.rows * matrix.cols * element_size;
-            size += data_bytes;
-
-            // Padding to 8-byte boundary
-            let padding = (8 - (data_bytes % 8)) % 8;
-            size += padding;
-        }
-
-        size
+/// Validate f32 data.
+fn pending_f32<'d>(
+    signature: &str,
+    rows: usize,
+    cols: usize,
+    data: Cow<'d, [f32]>,
+) -> Result<PendingMatrix<'d>> {
+    let sig = string_to_signature(signature)?;
+
+    let expected_len = rows * cols;
+    if data.len() != expected_len {
+        return Err(Error::InvalidDimensions { rows, cols });
     }
 
-    /// Write a single matrix.
-    unsafe fn write_matrix(&self, handle: *mut sdif_sys::SdifFileT, matrix: &MatrixData) -> Result<()> {
-        let (data_type, data_ptr, _element_size) = match &matrix.data {
-            MatrixDataType::Float32(v) => (
-                SdifDataTypeET_eFloat4,
-                v.as_ptr() as *const libc::c_void,
-                4u32,
-            ),
-            MatrixDataType::Float64(v) => (
-                SdifDataTypeET_eFloat8,
-                v.as_ptr() as *const libc::c_void,
-                8u32,
-            ),
-        };
-
-        // Set and write matrix header
-        SdifFSetCurrMatrixHeader(
-            handle,
-            matrix.signature,
-            data_type,
-            matrix.rows,
-            matrix.cols,
-        );
-
-        let header_bytes = SdifFWriteMatrixHeader(handle);
-        if header_bytes == 0 {
-            return Err(Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to write matrix header",
-            )));
-        }
-
-        // Write matrix data
-        let data_bytes = SdifFWriteMatrixData(handle, data_ptr as *mut libc::c_void);
-        if data_bytes == 0 {
-            return Err(Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to write matrix data",
-            )));
-        }
-
-        // Write padding
-        SdifFWritePadding(handle, calculate_padding(data_bytes) as usize);
+    Ok(PendingMatrix {
+        signature: sig,
+        rows: rows as u32,
+        cols: cols as u32,
+        data: MatrixDataType::Float32(data),
+    })
+}
 
-        Ok(())
-    }
+/// Integer element types that f64 values can be converted to.
+trait FromF64 {
+    fn from_f64(value: f64) -> Self;
 }
 
-/// Calculate padding needed to reach 8-byte alignment.
-fn calculate_padding(bytes_written: usize) -> u32 {
-    let remainder = bytes_written % 8;
-    if remainder == 0 {
-        0
-    } else {
-        (8 - remainder) as u32
-    }
+macro_rules! from_f64 {
+    ($($ty:ty),*) => {
+        $(
+            impl FromF64 for $ty {
+                fn from_f64(value: f64) -> Self {
+                    // `as` saturates, and maps NaN to 0
+                    value as $ty
+                }
+            }
+        )*
+    };
 }
 
+from_f64!(i8, i16, i32, u8, u16, u32);
+
 impl Drop for FrameBuilder<'_> {
     fn drop(&mut self) {
         if !self.finished && !self.matrices.is_empty() {
@@ -340,19 +658,8 @@ impl<'a> FrameBuilder<'a> {
         data: &Array2<f64>,
     ) -> Result<Self> {
         let (rows, cols) = data.dim();
-
-        // Convert to row-major (C order) if needed
-        let data_vec: Vec<f64> = if data.is_standard_layout() {
-            data.iter().copied().collect()
-        } else {
-            let mut vec = Vec::with_capacity(rows * cols);
-            for row in data.rows() {
-                vec.extend(row.iter().copied());
-            }
-            vec
-        };
-
-        self.add_matrix(signature, rows, cols, &data_vec)
+        let matrix = self.pending_f64(signature, rows, cols, row_major(data))?;
+        Ok(self.push_matrix(matrix.into_owned()))
     }
 
     /// Add a matrix from an ndarray Array2<f32>.
@@ -362,18 +669,17 @@ impl<'a> FrameBuilder<'a> {
         data: &Array2<f32>,
     ) -> Result<Self> {
         let (rows, cols) = data.dim();
+        let matrix = pending_f32(signature, rows, cols, row_major(data))?;
+        Ok(self.push_matrix(matrix.into_owned()))
+    }
+}
 
-        let data_vec: Vec<f32> = if data.is_standard_layout() {
-            data.iter().copied().collect()
-        } else {
-            let mut vec = Vec::with_capacity(rows * cols);
-            for row in data.rows() {
-                vec.extend(row.iter().copied());
-            }
-            vec
-        };
-
-        self.add_matrix_f32(signature, rows, cols, &data_vec)
+/// Get the elements of an array in row-major (C) order.
+#[cfg(feature = "ndarray")]
+fn row_major<T: Copy>(data: &Array2<T>) -> Cow<'_, [T]> {
+    match data.as_slice() {
+        Some(slice) => Cow::Borrowed(slice),
+        None => Cow::Owned(data.iter().copied().collect()),
     }
 }
 
@@ -0,0 +1,900 @@
+//! Owned copies of frame and matrix data.
+//!
+//! [`Frame`](crate::Frame) and [`Matrix`](crate::Matrix) borrow the file
+//! and must be consumed in order. [`FrameData`] and [`MatrixData`] hold a
+//! fully read frame instead, so frames can be collected, grouped, and
+//! passed around freely, or written to another file with
+//! [`SdifWriter::write_frame_data()`](crate::SdifWriter::write_frame_data).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("input.sdif")?;
+//! let frames = file
+//!     .frames()
+//!     .map(|frame| frame?.into_data())
+//!     .collect::<Result<Vec<_>, _>>()?;
+//!
+//! for frame in &frames {
+//!     if let Some(f0) = frame.matrix("1FQ0") {
+//!         println!("{:.3}s: {:?}", frame.time, f0.row(0));
+//!     }
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+//!
+//! [`OwnedFrame`] and [`OwnedMatrix`] are a lighter alternative for large
+//! files: matrix payloads are kept as the raw bytes read from the file and
+//! only decoded to f64 when accessed. Reading the structure of a file with
+//! [`Frame::into_owned()`](crate::Frame::into_owned) then costs a copy of
+//! the bytes, and matrices that are never looked at are never converted.
+
+use std::cell::OnceCell;
+
+use indexmap::IndexMap;
+
+use crate::data_type::DataType;
+use crate::error::{Error, Result};
+use crate::rows::{MatrixRow, RowLayout};
+use crate::schemas;
+
+/// An owned matrix with its data in row-major order.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatrixData {
+    /// Matrix type signature (e.g., "1TRC").
+    pub signature: String,
+
+    /// Number of rows.
+    pub rows: usize,
+
+    /// Number of columns.
+    pub cols: usize,
+
+    /// Data type the matrix was read as or should be written as.
+    pub data_type: DataType,
+
+    /// Column names declared for the matrix type, if known.
+    ///
+    /// Filled in when a matrix is read from a file, so that exported data
+    /// describes itself. Not used when writing.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub columns: Vec<String>,
+
+    /// Matrix values in row-major order, widened to f64.
+    ///
+    /// Human-readable formats such as JSON store NaN and infinite values
+    /// as the strings `"NaN"`, `"inf"` and `"-inf"`.
+    #[cfg_attr(feature = "serde", serde(with = "values"))]
+    pub data: Vec<f64>,
+}
+
+impl MatrixData {
+    /// Create a `Float8` matrix.
+    pub fn new(signature: impl Into<String>, rows: usize, cols: usize, data: Vec<f64>) -> Self {
+        MatrixData {
+            signature: signature.into(),
+            rows,
+            cols,
+            data_type: DataType::Float8,
+            columns: Vec::new(),
+            data,
+        }
+    }
+
+    /// Find a column by its declared name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// Find a column by its declared name, or by its position in the
+    /// standard layout if the matrix has no column names.
+    pub(crate) fn find_column(&self, name: &str) -> Option<usize> {
+        let index = if self.columns.is_empty() {
+            schemas::standard_columns(&self.signature)?
+                .iter()
+                .position(|&c| c == name)
+        } else {
+            self.column_index(name)
+        };
+        index.filter(|&i| i < self.cols)
+    }
+
+    /// Get one row of the matrix.
+    pub fn row(&self, row: usize) -> Option<&[f64]> {
+        if row >= self.rows {
+            return None;
+        }
+        self.data.get(row * self.cols..(row + 1) * self.cols)
+    }
+
+    /// Iterate over the rows of the matrix.
+    pub fn rows(&self) -> impl Iterator<Item = &[f64]> {
+        // chunks() panics on 0, and a matrix without columns has no values
+        self.data.chunks(self.cols.max(1)).take(self.rows)
+    }
+
+    /// Get a single value.
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        if col >= self.cols {
+            return None;
+        }
+        self.row(row).map(|r| r[col])
+    }
+
+    /// Iterate over the rows of a standard matrix type as structs.
+    ///
+    /// Columns are found by name, see [`rows`](crate::rows).
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidFormat`] if the matrix is not of type `T` or lacks a
+    /// required column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sdif_rs::rows::TrcRow;
+    /// use sdif_rs::MatrixData;
+    ///
+    /// let matrix = MatrixData::new("1TRC", 1, 4, vec![1.0, 440.0, 0.5, 0.0]);
+    /// let partial = matrix.rows_as::<TrcRow>()?.next().unwrap();
+    /// assert_eq!((partial.index, partial.frequency), (1, 440.0));
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn rows_as<'a, T: MatrixRow + 'a>(&'a self) -> Result<impl Iterator<Item = T> + 'a> {
+        let mut layout = RowLayout::<T>::new(&self.signature, &self.columns, self.cols)?;
+        Ok(self.rows().map(move |row| layout.read(row)))
+    }
+
+    /// Split the matrix into one vector per column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sdif_rs::MatrixData;
+    ///
+    /// let matrix = MatrixData::new("1TRC", 2, 2, vec![1.0, 440.0, 2.0, 880.0]);
+    /// let columns = matrix.into_columns();
+    /// assert_eq!(columns[1], vec![440.0, 880.0]);
+    /// ```
+    pub fn into_columns(self) -> Vec<Vec<f64>> {
+        split_columns(&self.data, self.rows, self.cols)
+    }
+
+    /// Split the matrix into columns keyed by name, in column order.
+    ///
+    /// Names are the declared [`columns`](Self::columns), or the standard
+    /// layout if the matrix has none. Returns `None` if not every column
+    /// has a name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sdif_rs::MatrixData;
+    ///
+    /// let matrix = MatrixData::new("1FQ0", 1, 2, vec![220.0, 0.9]);
+    /// let columns = matrix.into_named_columns().unwrap();
+    /// assert_eq!(columns["Frequency"], vec![220.0]);
+    /// assert_eq!(columns["Confidence"], vec![0.9]);
+    /// ```
+    pub fn into_named_columns(self) -> Option<IndexMap<String, Vec<f64>>> {
+        let names = column_names(self.columns.clone(), &self.signature, self.cols)?;
+        Some(names.into_iter().zip(self.into_columns()).collect())
+    }
+
+    /// Reinterpret data stored in column-major order.
+    ///
+    /// Files written with
+    /// [`SdifFileBuilder::column_major()`](crate::SdifFileBuilder::column_major)
+    /// store each matrix column by column. Reading such a matrix gives its
+    /// values in that order; this returns a copy with the values reordered
+    /// to row-major, so that [`row()`](Self::row) and [`get()`](Self::get)
+    /// see the matrix that was written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sdif_rs::MatrixData;
+    ///
+    /// // [[1, 2, 3], [4, 5, 6]] stored column by column
+    /// let stored = MatrixData::new("1TRC", 2, 3, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    ///
+    /// let matrix = stored.interpret_column_major();
+    /// assert_eq!(matrix.row(0), Some(&[1.0, 2.0, 3.0][..]));
+    /// ```
+    pub fn interpret_column_major(&self) -> MatrixData {
+        MatrixData {
+            data: transpose(&self.data, self.cols, self.rows),
+            ..self.clone()
+        }
+    }
+
+    /// Get the size of the matrix in a file, including its header and padding.
+    pub(crate) fn byte_size(&self) -> u64 {
+        let element_size = match self.data_type {
+            DataType::Float4 => 4,
+            _ => 8,
+        };
+        let data_bytes = (self.rows * self.cols * element_size) as u64;
+        16 + (data_bytes + 7) / 8 * 8
+    }
+}
+
+/// An owned frame with all its matrices.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameData {
+    /// Frame timestamp in seconds.
+    pub time: f64,
+
+    /// Frame type signature (e.g., "1TRC").
+    pub signature: String,
+
+    /// Stream ID of the frame.
+    pub stream_id: u32,
+
+    /// Matrices in file order.
+    pub matrices: Vec<MatrixData>,
+}
+
+impl FrameData {
+    /// Create a frame without matrices.
+    pub fn new(signature: impl Into<String>, time: f64, stream_id: u32) -> Self {
+        FrameData {
+            time,
+            signature: signature.into(),
+            stream_id,
+            matrices: Vec::new(),
+        }
+    }
+
+    /// Find the first matrix with the given signature.
+    pub fn matrix(&self, signature: &str) -> Option<&MatrixData> {
+        self.matrices.iter().find(|m| m.signature == signature)
+    }
+
+    /// Get the size of the frame in a file, including all headers and padding.
+    pub(crate) fn byte_size(&self) -> u64 {
+        // Signature, size, time, stream ID and matrix count
+        24 + self.matrices.iter().map(MatrixData::byte_size).sum::<u64>()
+    }
+}
+
+/// An owned matrix whose values are decoded on first access.
+///
+/// The payload is kept as raw bytes in the matrix's data type. The first
+/// call to [`data()`](Self::data) (or any accessor built on it) converts
+/// the values to f64 and caches them, so later accesses are free.
+///
+/// # Example
+///
+/// ```no_run
+/// # use sdif_rs::SdifFile;
+/// let file = SdifFile::open("input.sdif")?;
+/// for frame in file.frames() {
+///     let frame = frame?.into_owned()?;
+///     if let Some(f0) = frame.matrix("1FQ0") {
+///         // Only the 1FQ0 matrices are decoded
+///         println!("{:.3}s: {:?}", frame.time, f0.get(0, 0));
+///     }
+/// }
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+///
+/// With the `serde` feature, the matrix is serialized like a
+/// [`MatrixData`], with its values widened to f64. Every data type it can
+/// hold converts back exactly.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "MatrixData", try_from = "MatrixData")
+)]
+pub struct OwnedMatrix {
+    /// Matrix type signature (e.g., "1TRC").
+    pub signature: String,
+
+    /// Number of rows.
+    pub rows: usize,
+
+    /// Number of columns.
+    pub cols: usize,
+
+    /// Data type of the raw values.
+    pub data_type: DataType,
+
+    /// Column names declared for the matrix type, if known.
+    pub columns: Vec<String>,
+
+    /// Values in row-major order, in native byte order.
+    bytes: Vec<u8>,
+
+    /// Values widened to f64, once decoded.
+    decoded: OnceCell<Vec<f64>>,
+}
+
+impl OwnedMatrix {
+    /// Create a matrix from raw values in native byte order.
+    ///
+    /// Text matrices hold one byte per value, and their values are the
+    /// bytes.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::DataTypeMismatch`] if `data_type` is `Unknown`
+    /// - [`Error::InvalidFormat`] if `bytes` doesn't hold `rows * cols`
+    ///   values of `data_type`
+    pub fn from_bytes(
+        signature: impl Into<String>,
+        rows: usize,
+        cols: usize,
+        data_type: DataType,
+        bytes: Vec<u8>,
+    ) -> Result<Self> {
+        if data_type == DataType::Unknown {
+            return Err(Error::type_mismatch("numeric or text", data_type.to_string()));
+        }
+        let expected = rows * cols * element_size(data_type);
+        if bytes.len() != expected {
+            return Err(Error::invalid_format(format!(
+                "Expected {} bytes for a {}x{} {} matrix, got {}",
+                expected,
+                rows,
+                cols,
+                data_type,
+                bytes.len()
+            )));
+        }
+
+        Ok(OwnedMatrix {
+            signature: signature.into(),
+            rows,
+            cols,
+            data_type,
+            columns: Vec::new(),
+            bytes,
+            decoded: OnceCell::new(),
+        })
+    }
+
+    /// Get the raw values in row-major order, in native byte order.
+    pub fn raw_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Check whether the values have been decoded yet.
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+
+    /// Get the values in row-major order, widened to f64.
+    ///
+    /// The values are decoded on the first call and cached.
+    pub fn data(&self) -> &[f64] {
+        self.decoded.get_or_init(|| decode(&self.bytes, self.data_type))
+    }
+
+    /// Get one row of the matrix.
+    pub fn row(&self, row: usize) -> Option<&[f64]> {
+        if row >= self.rows {
+            return None;
+        }
+        self.data().get(row * self.cols..(row + 1) * self.cols)
+    }
+
+    /// Get a single value.
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        if col >= self.cols {
+            return None;
+        }
+        self.row(row).map(|r| r[col])
+    }
+
+    /// Decode the matrix into a [`MatrixData`].
+    pub fn to_data(&self) -> MatrixData {
+        MatrixData {
+            signature: self.signature.clone(),
+            rows: self.rows,
+            cols: self.cols,
+            data_type: self.data_type,
+            columns: self.columns.clone(),
+            data: self.data().to_vec(),
+        }
+    }
+}
+
+impl From<OwnedMatrix> for MatrixData {
+    fn from(matrix: OwnedMatrix) -> Self {
+        matrix.to_data()
+    }
+}
+
+impl TryFrom<MatrixData> for OwnedMatrix {
+    type Error = Error;
+
+    /// Encode the values in the matrix's data type.
+    ///
+    /// Fails with [`Error::InvalidFormat`] if a value can't be stored
+    /// exactly: integer and text values must be whole numbers within the
+    /// bounds of the type, and finite `Float4` values must be within the
+    /// range of f32.
+    fn try_from(matrix: MatrixData) -> Result<Self> {
+        if matrix.data.len() != matrix.rows * matrix.cols {
+            return Err(Error::InvalidDimensions {
+                rows: matrix.rows,
+                cols: matrix.cols,
+            });
+        }
+
+        let bytes = encode(&matrix.data, matrix.data_type)?;
+        let mut owned = OwnedMatrix::from_bytes(
+            matrix.signature,
+            matrix.rows,
+            matrix.cols,
+            matrix.data_type,
+            bytes,
+        )?;
+        owned.columns = matrix.columns;
+        Ok(owned)
+    }
+}
+
+impl PartialEq for OwnedMatrix {
+    fn eq(&self, other: &Self) -> bool {
+        // Whether the values were decoded yet doesn't matter
+        self.signature == other.signature
+            && self.rows == other.rows
+            && self.cols == other.cols
+            && self.data_type == other.data_type
+            && self.columns == other.columns
+            && self.bytes == other.bytes
+    }
+}
+
+/// An owned frame whose matrices are decoded on access.
+///
+/// Produced by [`Frame::into_owned()`](crate::Frame::into_owned).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedFrame {
+    /// Frame timestamp in seconds.
+    pub time: f64,
+
+    /// Frame type signature (e.g., "1TRC").
+    pub signature: String,
+
+    /// Stream ID of the frame.
+    pub stream_id: u32,
+
+    /// Matrices in file order.
+    pub matrices: Vec<OwnedMatrix>,
+}
+
+impl OwnedFrame {
+    /// Find the first matrix with the given signature.
+    pub fn matrix(&self, signature: &str) -> Option<&OwnedMatrix> {
+        self.matrices.iter().find(|m| m.signature == signature)
+    }
+
+    /// Decode all matrices into a [`FrameData`].
+    pub fn to_data(&self) -> FrameData {
+        FrameData {
+            time: self.time,
+            signature: self.signature.clone(),
+            stream_id: self.stream_id,
+            matrices: self.matrices.iter().map(OwnedMatrix::to_data).collect(),
+        }
+    }
+}
+
+/// Frames that share (approximately) the same time.
+///
+/// Produced by [`FrameIterator::group_by_time()`](crate::FrameIterator::group_by_time).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeSlice {
+    /// Time of the first frame in the slice.
+    pub time: f64,
+
+    /// Frames in file order.
+    pub frames: Vec<FrameData>,
+}
+
+impl TimeSlice {
+    /// Find the first frame with the given signature.
+    pub fn frame(&self, signature: &str) -> Option<&FrameData> {
+        self.frames.iter().find(|f| f.signature == signature)
+    }
+
+    /// Find the first matrix with the given signature in any frame.
+    pub fn matrix(&self, signature: &str) -> Option<&MatrixData> {
+        self.frames.iter().find_map(|f| f.matrix(signature))
+    }
+}
+
+/// Split a `rows` x `cols` matrix stored in row-major order into columns.
+pub(crate) fn split_columns(data: &[f64], rows: usize, cols: usize) -> Vec<Vec<f64>> {
+    (0..cols)
+        .map(|col| data.iter().skip(col).step_by(cols).take(rows).copied().collect())
+        .collect()
+}
+
+/// Name the first `cols` columns of a matrix, falling back to the
+/// standard layout when no names are declared.
+pub(crate) fn column_names(
+    declared: Vec<String>,
+    signature: &str,
+    cols: usize,
+) -> Option<Vec<String>> {
+    let mut names = if declared.is_empty() {
+        schemas::standard_columns(signature)?.iter().map(|c| c.to_string()).collect()
+    } else {
+        declared
+    };
+    if names.len() < cols {
+        return None;
+    }
+    names.truncate(cols);
+    Some(names)
+}
+
+/// Transpose a `rows` x `cols` matrix stored in row-major order.
+///
+/// The result is the matrix's data in column-major order, or equivalently
+/// the row-major data of its `cols` x `rows` transpose.
+pub(crate) fn transpose<T: Copy>(data: &[T], rows: usize, cols: usize) -> Vec<T> {
+    let mut out = Vec::with_capacity(data.len());
+    for col in 0..cols {
+        out.extend(data.iter().skip(col).step_by(cols.max(1)).take(rows).copied());
+    }
+    out
+}
+
+/// Decode numeric values in native byte order to f64.
+pub(crate) fn decode(bytes: &[u8], data_type: DataType) -> Vec<f64> {
+    macro_rules! widen {
+        ($t:ty) => {
+            bytes
+                .chunks_exact(std::mem::size_of::<$t>())
+                .map(|b| <$t>::from_ne_bytes(b.try_into().unwrap()) as f64)
+                .collect()
+        };
+    }
+
+    match data_type {
+        DataType::Float4 => widen!(f32),
+        DataType::Float8 => widen!(f64),
+        DataType::Int1 => widen!(i8),
+        DataType::Int2 => widen!(i16),
+        DataType::Int4 => widen!(i32),
+        DataType::UInt1 => widen!(u8),
+        DataType::UInt2 => widen!(u16),
+        DataType::UInt4 => widen!(u32),
+        DataType::Text => bytes.iter().map(|&b| f64::from(b)).collect(),
+        // Rejected when the matrix is created
+        DataType::Unknown => Vec::new(),
+    }
+}
+
+/// Encode f64 values as `data_type` in native byte order.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if a value can't be stored exactly as
+///   `data_type`
+/// - [`Error::DataTypeMismatch`] if `data_type` is `Unknown`
+fn encode(data: &[f64], data_type: DataType) -> Result<Vec<u8>> {
+    let out_of_range = |index: usize, value: f64| {
+        Error::invalid_format(format!(
+            "Value {} at index {} can't be stored as {}",
+            value, index, data_type
+        ))
+    };
+
+    macro_rules! narrow {
+        ($t:ty) => {{
+            let mut bytes = Vec::with_capacity(data.len() * std::mem::size_of::<$t>());
+            for (index, &value) in data.iter().enumerate() {
+                // Round-tripping also rejects NaN and fractions
+                let narrowed = value as $t;
+                if narrowed as f64 != value {
+                    return Err(out_of_range(index, value));
+                }
+                bytes.extend_from_slice(&narrowed.to_ne_bytes());
+            }
+            Ok(bytes)
+        }};
+    }
+
+    match data_type {
+        DataType::Float4 => {
+            let mut bytes = Vec::with_capacity(data.len() * 4);
+            for (index, &value) in data.iter().enumerate() {
+                let narrowed = value as f32;
+                if value.is_finite() && !narrowed.is_finite() {
+                    return Err(out_of_range(index, value));
+                }
+                bytes.extend_from_slice(&narrowed.to_ne_bytes());
+            }
+            Ok(bytes)
+        }
+        DataType::Float8 => Ok(data.iter().flat_map(|v| v.to_ne_bytes()).collect()),
+        DataType::Int1 => narrow!(i8),
+        DataType::Int2 => narrow!(i16),
+        DataType::Int4 => narrow!(i32),
+        DataType::UInt1 | DataType::Text => narrow!(u8),
+        DataType::UInt2 => narrow!(u16),
+        DataType::UInt4 => narrow!(u32),
+        DataType::Unknown => Err(Error::type_mismatch("numeric or text", "unknown")),
+    }
+}
+
+/// Get the size in bytes of one value of `data_type`, one for text.
+fn element_size(data_type: DataType) -> usize {
+    match data_type {
+        DataType::Text => 1,
+        data_type => data_type.size_bytes(),
+    }
+}
+
+/// Serde support for matrix values that keeps NaN and infinity.
+///
+/// JSON has no numbers for them, so human-readable formats write them as
+/// strings, and read `null` as NaN. Other formats store plain f64 values.
+#[cfg(feature = "serde")]
+mod values {
+    use std::fmt;
+
+    use serde::de::{self, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(data: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return data.serialize(serializer);
+        }
+
+        let mut seq = serializer.serialize_seq(Some(data.len()))?;
+        for value in data {
+            if value.is_finite() {
+                seq.serialize_element(value)?;
+            } else {
+                seq.serialize_element(&value.to_string())?;
+            }
+        }
+        seq.end()
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<f64>, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Vec::deserialize(deserializer);
+        }
+        let values = Vec::<Value>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|Value(value)| value).collect())
+    }
+
+    /// A value written as a number, a string, or `null`.
+    struct Value(f64);
+
+    impl<'de> Deserialize<'de> for Value {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(ValueVisitor)
+        }
+    }
+
+    struct ValueVisitor;
+
+    impl<'de> Visitor<'de> for ValueVisitor {
+        type Value = Value;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number, \"NaN\", \"inf\", \"-inf\" or null")
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+            Ok(Value(value))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+            Ok(Value(value as f64))
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+            Ok(Value(value as f64))
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+            value
+                .parse()
+                .map(Value)
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+            Ok(Value(f64::NAN))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_rows() {
+        let m = MatrixData::new("1TRC", 2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        assert_eq!(m.row(1), Some(&[4.0, 5.0, 6.0][..]));
+        assert_eq!(m.row(2), None);
+        assert_eq!(m.get(0, 2), Some(3.0));
+        assert_eq!(m.get(0, 3), None);
+        assert_eq!(m.rows().count(), 2);
+    }
+
+    #[test]
+    fn test_column_index() {
+        let mut m = MatrixData::new("1FQ0", 1, 2, vec![220.0, 0.9]);
+        assert_eq!(m.column_index("Confidence"), None);
+
+        m.columns = vec!["Frequency".to_string(), "Confidence".to_string()];
+        assert_eq!(m.column_index("Confidence"), Some(1));
+        assert_eq!(m.column_index("Amplitude"), None);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let data = [1, 2, 3, 4, 5, 6];
+        assert_eq!(transpose(&data, 2, 3), vec![1, 4, 2, 5, 3, 6]);
+        assert_eq!(transpose(&transpose(&data, 2, 3), 3, 2), data.to_vec());
+        assert!(transpose::<f64>(&[], 0, 4).is_empty());
+    }
+
+    #[test]
+    fn test_interpret_column_major() {
+        let stored = MatrixData::new("1FQ0", 3, 2, vec![1.0, 2.0, 3.0, 0.1, 0.2, 0.3]);
+        let m = stored.interpret_column_major();
+
+        assert_eq!((m.rows, m.cols), (3, 2));
+        assert_eq!(m.row(1), Some(&[2.0, 0.2][..]));
+    }
+
+    #[test]
+    fn test_into_columns() {
+        let m = MatrixData::new("1TRC", 2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(m.clone().into_columns(), vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+
+        let named = m.clone().into_named_columns().unwrap();
+        assert_eq!(named.keys().collect::<Vec<_>>(), ["Index", "Frequency", "Amplitude"]);
+        assert_eq!(named["Amplitude"], vec![3.0, 6.0]);
+
+        let declared = MatrixData {
+            columns: vec!["A".into(), "B".into()],
+            ..m.clone()
+        };
+        assert_eq!(declared.into_named_columns(), None);
+        assert_eq!(MatrixData::new("XABC", 1, 1, vec![1.0]).into_named_columns(), None);
+        assert!(MatrixData::new("1TRC", 0, 0, vec![]).into_columns().is_empty());
+    }
+
+    #[test]
+    fn test_byte_size_includes_padding() {
+        let mut m = MatrixData::new("1FQ0", 1, 3, vec![0.0; 3]);
+        assert_eq!(m.byte_size(), 16 + 24);
+
+        // 12 data bytes padded to 16
+        m.data_type = DataType::Float4;
+        assert_eq!(m.byte_size(), 16 + 16);
+
+        let mut frame = FrameData::new("1FQ0", 0.0, 0);
+        frame.matrices.push(m);
+        assert_eq!(frame.byte_size(), 24 + 32);
+    }
+
+    #[test]
+    fn test_owned_matrix_decodes_lazily() {
+        let bytes: Vec<u8> = [1.0f32, 2.5, -3.0, 4.0]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        let m = OwnedMatrix::from_bytes("1FQ0", 2, 2, DataType::Float4, bytes).unwrap();
+
+        assert!(!m.is_decoded());
+        assert_eq!(m.raw_bytes().len(), 16);
+        assert_eq!(m.get(1, 0), Some(-3.0));
+        assert!(m.is_decoded());
+        assert_eq!(m.row(0), Some(&[1.0, 2.5][..]));
+        assert_eq!(m.get(0, 2), None);
+
+        let data = m.to_data();
+        assert_eq!(data.data_type, DataType::Float4);
+        assert_eq!(data.data, vec![1.0, 2.5, -3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_owned_matrix_integers() {
+        let bytes: Vec<u8> = [-2i16, 7].iter().flat_map(|v| v.to_ne_bytes()).collect();
+        let m = OwnedMatrix::from_bytes("XINT", 1, 2, DataType::Int2, bytes).unwrap();
+        assert_eq!(m.data(), &[-2.0, 7.0]);
+
+        let m = OwnedMatrix::from_bytes("XINT", 1, 3, DataType::UInt1, vec![0, 128, 255]).unwrap();
+        assert_eq!(m.data(), &[0.0, 128.0, 255.0]);
+    }
+
+    #[test]
+    fn test_owned_matrix_from_data() {
+        let mut data = MatrixData::new("XINT", 1, 3, vec![-2.0, 7.0, 32767.0]);
+        data.data_type = DataType::Int2;
+        let m = OwnedMatrix::try_from(data.clone()).unwrap();
+        assert_eq!(m.data(), &[-2.0, 7.0, 32767.0]);
+        assert_eq!(MatrixData::from(m.clone()).data, m.data());
+
+        let data = MatrixData::new("XINT", 2, 2, vec![1.0]);
+        assert!(OwnedMatrix::try_from(data).is_err());
+    }
+
+    #[test]
+    fn test_owned_matrix_from_data_rejects_lossy_values() {
+        let matrix = |data_type, value| MatrixData {
+            data_type,
+            ..MatrixData::new("XINT", 1, 2, vec![1.0, value])
+        };
+
+        for (data_type, value) in [
+            (DataType::Int2, 1e9),
+            (DataType::Int2, f64::NAN),
+            (DataType::Int4, 0.5),
+            (DataType::UInt1, -1.0),
+            (DataType::Text, 256.0),
+            (DataType::Float4, 1e300),
+        ] {
+            let err = OwnedMatrix::try_from(matrix(data_type, value)).unwrap_err();
+            assert!(err.to_string().contains("index 1"), "{}", err);
+        }
+
+        // Float values may lose precision, and keep NaN and infinity
+        let m = OwnedMatrix::try_from(matrix(DataType::Float4, f64::INFINITY)).unwrap();
+        assert_eq!(m.data()[1], f64::INFINITY);
+        assert!(OwnedMatrix::try_from(matrix(DataType::Float4, 0.1)).is_ok());
+        assert!(OwnedMatrix::try_from(matrix(DataType::Float8, f64::NAN)).is_ok());
+    }
+
+    #[test]
+    fn test_owned_matrix_text() {
+        let m = OwnedMatrix::from_bytes("XTXT", 1, 3, DataType::Text, b"hi\0".to_vec()).unwrap();
+        assert_eq!(m.data(), &[104.0, 105.0, 0.0]);
+        assert_eq!(OwnedMatrix::try_from(m.to_data()).unwrap(), m);
+    }
+
+    #[test]
+    fn test_owned_matrix_validation() {
+        assert!(OwnedMatrix::from_bytes("XTXT", 1, 3, DataType::Unknown, vec![0; 3]).is_err());
+        assert!(OwnedMatrix::from_bytes("1FQ0", 1, 2, DataType::Float8, vec![0; 15]).is_err());
+
+        // Equality ignores whether values were decoded
+        let a = OwnedMatrix::from_bytes("1FQ0", 1, 1, DataType::Float8, vec![0; 8]).unwrap();
+        let b = a.clone();
+        a.data();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_time_slice_lookup() {
+        let mut f0 = FrameData::new("1FQ0", 0.5, 1);
+        f0.matrices.push(MatrixData::new("1FQ0", 1, 2, vec![220.0, 0.9]));
+        let trc = FrameData::new("1TRC", 0.5, 0);
+
+        let slice = TimeSlice {
+            time: 0.5,
+            frames: vec![trc, f0],
+        };
+
+        assert_eq!(slice.frame("1FQ0").unwrap().stream_id, 1);
+        assert_eq!(slice.matrix("1FQ0").unwrap().get(0, 0), Some(220.0));
+        assert!(slice.matrix("1TRC").is_none());
+    }
+}
@@ -0,0 +1,176 @@
+//! Processing history recorded in NVT entries.
+//!
+//! Each tool that writes or transforms a file can record a step with
+//! [`SdifFileBuilder::append_history()`](crate::SdifFileBuilder::append_history),
+//! and [`SdifFile::history()`](crate::SdifFile::history) reads the steps
+//! back in order. Because NVTs are copied along with the data, a corpus
+//! keeps an auditable trail of every tool that touched it.
+//!
+//! # Convention
+//!
+//! Step `n` (counting from 0) is stored as NVT entries with the keys
+//!
+//! ```text
+//! history.<n>.tool           name of the tool
+//! history.<n>.version        version of the tool
+//! history.<n>.param.<name>   one entry per parameter
+//! ```
+//!
+//! Each step is written as its own NVT table, but readers accept the
+//! entries spread over any tables.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("partials.sdif")?;
+//! for step in file.history() {
+//!     println!("{} {} {:?}", step.tool, step.version, step.params);
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Prefix of all history NVT keys.
+const PREFIX: &str = "history.";
+
+/// One processing step in a file's history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryEntry {
+    /// Name of the tool that performed the step.
+    pub tool: String,
+
+    /// Version of the tool.
+    pub version: String,
+
+    /// Parameters the tool was run with.
+    pub params: BTreeMap<String, String>,
+}
+
+impl HistoryEntry {
+    /// Create a history entry without parameters.
+    pub fn new(tool: impl Into<String>, version: impl Into<String>) -> Self {
+        HistoryEntry {
+            tool: tool.into(),
+            version: version.into(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// Add a parameter to the entry.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Get the NVT entries recording this entry as step `step`.
+    pub(crate) fn to_nvt_entries(&self, step: usize) -> Vec<(String, String)> {
+        let mut entries = vec![
+            (format!("{}{}.tool", PREFIX, step), self.tool.clone()),
+            (format!("{}{}.version", PREFIX, step), self.version.clone()),
+        ];
+        for (name, value) in &self.params {
+            entries.push((format!("{}{}.param.{}", PREFIX, step, name), value.clone()));
+        }
+        entries
+    }
+}
+
+/// Split a history key into its step number and field name.
+fn parse_key(key: &str) -> Option<(usize, &str)> {
+    let (step, field) = key.strip_prefix(PREFIX)?.split_once('.')?;
+    Some((step.parse().ok()?, field))
+}
+
+/// Get the step number of a history key, if `key` is one.
+pub(crate) fn step_of(key: &str) -> Option<usize> {
+    parse_key(key).map(|(step, _)| step)
+}
+
+/// Collect the history entries from a file's NVT tables, ordered by step.
+///
+/// Steps without a `tool` entry are ignored.
+pub(crate) fn from_nvts(nvts: &[HashMap<String, String>]) -> Vec<HistoryEntry> {
+    let mut steps: BTreeMap<usize, (Option<String>, HistoryEntry)> = BTreeMap::new();
+
+    for (key, value) in nvts.iter().flatten() {
+        let Some((step, field)) = parse_key(key) else {
+            continue;
+        };
+        let (tool, entry) = steps.entry(step).or_default();
+
+        match field {
+            "tool" => *tool = Some(value.clone()),
+            "version" => entry.version = value.clone(),
+            _ => {
+                if let Some(name) = field.strip_prefix("param.") {
+                    entry.params.insert(name.to_string(), value.clone());
+                }
+            }
+        }
+    }
+
+    steps
+        .into_values()
+        .filter_map(|(tool, entry)| tool.map(|tool| HistoryEntry { tool, ..entry }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nvt_entries() {
+        let entry = HistoryEntry::new("mat2sdif", "0.1.0").param("frame-type", "1TRC");
+
+        assert_eq!(
+            entry.to_nvt_entries(2),
+            vec![
+                ("history.2.tool".to_string(), "mat2sdif".to_string()),
+                ("history.2.version".to_string(), "0.1.0".to_string()),
+                ("history.2.param.frame-type".to_string(), "1TRC".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_step_of() {
+        assert_eq!(step_of("history.0.tool"), Some(0));
+        assert_eq!(step_of("history.12.param.x"), Some(12));
+        assert_eq!(step_of("history.x.tool"), None);
+        assert_eq!(step_of("creator"), None);
+    }
+
+    #[test]
+    fn test_roundtrip_across_tables() {
+        let first = HistoryEntry::new("analyzer", "2.0").param("hop", "0.01");
+        let second = HistoryEntry::new("sdif-truncate", "1.0")
+            .param("max-frames", "100")
+            .param("note.with.dots", "ok");
+
+        // Step 10 sorts after step 2 numerically, not as a string
+        let nvts: Vec<HashMap<String, String>> = vec![
+            second.to_nvt_entries(10).into_iter().collect(),
+            first
+                .to_nvt_entries(2)
+                .into_iter()
+                .chain([("creator".to_string(), "analyzer".to_string())])
+                .collect(),
+        ];
+
+        assert_eq!(from_nvts(&nvts), vec![first, second]);
+    }
+
+    #[test]
+    fn test_step_without_tool_ignored() {
+        let nvts = vec![HashMap::from([(
+            "history.0.version".to_string(),
+            "1.0".to_string(),
+        )])];
+        assert!(from_nvts(&nvts).is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+//! Frame indexes and the `.sdifx` sidecar file format.
+//!
+//! An [`Index`] records the position, time, signature and stream of every
+//! frame in an SDIF file. Building it takes one pass over the file; saving
+//! it next to the file as a sidecar lets later runs skip that pass, which
+//! makes random access to very large files immediate.
+//!
+//! [`SdifFile::open()`](crate::SdifFile::open) loads the sidecar
+//! automatically when it exists and is at least as recent as the SDIF file.
+//! Without one, [`SdifFile::scan_index()`](crate::SdifFile::scan_index)
+//! builds the index in memory on first use, and
+//! [`SdifFile::frames_from()`](crate::SdifFile::frames_from) and
+//! [`SdifFile::seek_to_frame()`](crate::SdifFile::seek_to_frame) jump
+//! through it.
+//!
+//! # Sidecar Format
+//!
+//! The sidecar is a small little-endian binary file:
+//!
+//! ```text
+//! magic "SDFX" | version u32 | SDIF file length u64 | frame count u64
+//! per frame: byte offset u64 | byte size u64 (u64::MAX if unknown)
+//!            | time f64 | signature u32 | stream ID u32
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{Index, SdifFile};
+//!
+//! // Once: scan the file and save the index next to it
+//! let index = Index::build("long-analysis.sdif")?;
+//! index.save(Index::sidecar_path("long-analysis.sdif"))?;
+//!
+//! // Later: the index is picked up automatically
+//! let file = SdifFile::open("long-analysis.sdif")?;
+//! if let Some(frame) = file.index().and_then(|i| i.first_at_or_after(3600.0)) {
+//!     file.seek_to_offset(frame.byte_offset)?;
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::file::{ReadOptions, SdifFile};
+use crate::signature::{signature_to_string, Signature};
+
+/// Magic bytes at the start of a sidecar index.
+const MAGIC: &[u8; 4] = b"SDFX";
+
+/// Current sidecar format version.
+const VERSION: u32 = 1;
+
+/// Stored in place of the frame size when the size is unknown.
+const UNKNOWN_SIZE: u64 = u64::MAX;
+
+/// Extension of sidecar index files.
+const SIDECAR_EXTENSION: &str = "sdifx";
+
+/// Which frame to pick when no frame starts exactly at the requested time.
+///
+/// Used by [`Index::frame_near()`] and
+/// [`SdifFile::frame_at()`](crate::SdifFile::frame_at). A frame at exactly
+/// the requested time is always picked, whatever the rounding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// The last frame before the requested time.
+    ///
+    /// This is the frame whose data is current at that time, which is what
+    /// a player following the audio position usually wants.
+    #[default]
+    Before,
+
+    /// The frame closest to the requested time, the earlier one on ties.
+    Nearest,
+
+    /// The first frame after the requested time.
+    After,
+}
+
+/// Location and header summary of one frame in an SDIF file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameRef {
+    /// Frame timestamp in seconds.
+    pub time: f64,
+
+    /// Frame type signature.
+    pub signature: Signature,
+
+    /// Stream ID of the frame.
+    pub stream_id: u32,
+
+    /// Position of the frame signature in the file.
+    pub byte_offset: u64,
+
+    /// Total size of the frame in the file, if known.
+    pub byte_size: Option<u64>,
+}
+
+impl FrameRef {
+    /// Get the frame type signature as a string (e.g., "1TRC").
+    pub fn signature_str(&self) -> String {
+        signature_to_string(self.signature)
+    }
+}
+
+/// Index of all frames in an SDIF file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Index {
+    /// Length of the indexed SDIF file, used to detect stale sidecars.
+    file_len: u64,
+
+    /// Indexed frames in file order.
+    frames: Vec<FrameRef>,
+}
+
+impl Index {
+    /// Scan an SDIF file and index all its frames.
+    ///
+    /// # Errors
+    ///
+    /// Any error from opening the file or reading its frames.
+    pub fn build(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = SdifFile::open_with_options(path, ReadOptions::new().sidecar_index(false))?;
+
+        let mut frames = Vec::new();
+        for frame in file.frames() {
+            let frame = frame?;
+            frames.push(FrameRef {
+                time: frame.time(),
+                signature: frame.signature_raw(),
+                stream_id: frame.stream_id(),
+                byte_offset: frame.byte_offset(),
+                byte_size: frame.byte_size(),
+            });
+        }
+
+        Ok(Index {
+            file_len: fs::metadata(path)?.len(),
+            frames,
+        })
+    }
+
+    /// Get the sidecar path for an SDIF file (`x.sdif` becomes `x.sdifx`).
+    pub fn sidecar_path(sdif_path: impl AsRef<Path>) -> PathBuf {
+        sdif_path.as_ref().with_extension(SIDECAR_EXTENSION)
+    }
+
+    /// Get the indexed frames in file order.
+    pub fn frames(&self) -> &[FrameRef] {
+        &self.frames
+    }
+
+    /// Get the number of indexed frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check if the index contains no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get the length of the SDIF file the index was built from.
+    pub fn file_len(&self) -> u64 {
+        self.file_len
+    }
+
+    /// Check whether frame times never decrease in file order.
+    pub(crate) fn is_time_ordered(&self) -> bool {
+        self.frames.windows(2).all(|pair| pair[0].time <= pair[1].time)
+    }
+
+    /// Find the first frame whose time is at or after `time`.
+    ///
+    /// Frame times in an SDIF file are non-decreasing, so this is a
+    /// binary search.
+    pub fn first_at_or_after(&self, time: f64) -> Option<&FrameRef> {
+        let pos = self.frames.partition_point(|f| f.time < time);
+        self.frames.get(pos)
+    }
+
+    /// Find the frame closest to `time`, the earlier one on ties.
+    ///
+    /// Returns `None` only if the index is empty.
+    pub fn nearest_frame(&self, time: f64) -> Option<&FrameRef> {
+        self.frame_near(time, Rounding::Nearest)
+    }
+
+    /// Find the frame at `time`, or the one picked by `rounding` if no
+    /// frame starts exactly at `time`.
+    ///
+    /// When several frames share a time, the first of them is returned.
+    pub fn frame_near(&self, time: f64, rounding: Rounding) -> Option<&FrameRef> {
+        self.position_near(time, rounding).map(|pos| &self.frames[pos])
+    }
+
+    /// Find the position in [`frames()`](Self::frames) of the frame
+    /// picked by [`frame_near()`](Self::frame_near).
+    pub fn position_near(&self, time: f64, rounding: Rounding) -> Option<usize> {
+        let after = self.frames.partition_point(|f| f.time < time);
+        let before = after.checked_sub(1);
+        if self.frames.get(after).is_some_and(|f| f.time == time) {
+            return Some(after);
+        }
+
+        match rounding {
+            Rounding::Before => before,
+            Rounding::After => (after < self.frames.len()).then_some(after),
+            Rounding::Nearest => match (before, self.frames.get(after)) {
+                (Some(before), Some(next)) => {
+                    let closer = next.time - time < time - self.frames[before].time;
+                    Some(if closer { after } else { before })
+                }
+                (before, None) => before,
+                (None, Some(_)) => Some(after),
+            },
+        }
+    }
+
+    /// Write the index to a sidecar file.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&self.file_len.to_le_bytes())?;
+        out.write_all(&(self.frames.len() as u64).to_le_bytes())?;
+
+        for frame in &self.frames {
+            out.write_all(&frame.byte_offset.to_le_bytes())?;
+            out.write_all(&frame.byte_size.unwrap_or(UNKNOWN_SIZE).to_le_bytes())?;
+            out.write_all(&frame.time.to_le_bytes())?;
+            out.write_all(&frame.signature.to_le_bytes())?;
+            out.write_all(&frame.stream_id.to_le_bytes())?;
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Read an index from a sidecar file.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be read or is truncated
+    /// - [`Error::InvalidFormat`] if the file is not a sidecar index or
+    ///   has an unsupported version
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::invalid_format("Not an SDIF sidecar index"));
+        }
+
+        let version = read_u32(&mut input)?;
+        if version != VERSION {
+            return Err(Error::invalid_format(format!(
+                "Unsupported sidecar index version {}",
+                version
+            )));
+        }
+
+        let file_len = read_u64(&mut input)?;
+        let count = read_u64(&mut input)?;
+
+        let mut frames = Vec::new();
+        for _ in 0..count {
+            let byte_offset = read_u64(&mut input)?;
+            let byte_size = read_u64(&mut input)?;
+            let time = f64::from_bits(read_u64(&mut input)?);
+            let signature = read_u32(&mut input)?;
+            let stream_id = read_u32(&mut input)?;
+
+            frames.push(FrameRef {
+                time,
+                signature,
+                stream_id,
+                byte_offset,
+                byte_size: (byte_size != UNKNOWN_SIZE).then_some(byte_size),
+            });
+        }
+
+        Ok(Index { file_len, frames })
+    }
+
+    /// Load the sidecar of an SDIF file if it is present and up to date.
+    ///
+    /// A sidecar is up to date when it was modified no earlier than the
+    /// SDIF file and records the SDIF file's current length. Unreadable or
+    /// stale sidecars are ignored.
+    pub(crate) fn load_fresh_sidecar(sdif_path: &Path) -> Option<Self> {
+        let sidecar = Self::sidecar_path(sdif_path);
+
+        let sdif_meta = fs::metadata(sdif_path).ok()?;
+        let sidecar_meta = fs::metadata(&sidecar).ok()?;
+        if sidecar_meta.modified().ok()? < sdif_meta.modified().ok()? {
+            return None;
+        }
+
+        let index = Self::load(&sidecar).ok()?;
+        (index.file_len == sdif_meta.len()).then_some(index)
+    }
+}
+
+/// Read a little-endian u32.
+fn read_u32(input: &mut impl Read) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Read a little-endian u64.
+fn read_u64(input: &mut impl Read) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Index {
+        Index {
+            file_len: 1024,
+            frames: vec![
+                FrameRef {
+                    time: 0.0,
+                    signature: u32::from_be_bytes(*b"1TRC"),
+                    stream_id: 0,
+                    byte_offset: 200,
+                    byte_size: Some(88),
+                },
+                FrameRef {
+                    time: 0.5,
+                    signature: u32::from_be_bytes(*b"1FQ0"),
+                    stream_id: 1,
+                    byte_offset: 288,
+                    byte_size: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(Index::sidecar_path("a/b.sdif"), PathBuf::from("a/b.sdifx"));
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let index = sample();
+
+        index.save(temp.path()).unwrap();
+        assert_eq!(Index::load(temp.path()).unwrap(), index);
+    }
+
+    #[test]
+    fn test_load_rejects_other_files() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        fs::write(temp.path(), b"SDIF\0\0\0\0").unwrap();
+
+        assert!(matches!(
+            Index::load(temp.path()),
+            Err(Error::InvalidFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_first_at_or_after() {
+        let index = sample();
+        assert_eq!(index.first_at_or_after(0.0).unwrap().byte_offset, 200);
+        assert_eq!(index.first_at_or_after(0.1).unwrap().signature_str(), "1FQ0");
+        assert!(index.first_at_or_after(1.0).is_none());
+    }
+
+    #[test]
+    fn test_frame_near() {
+        let index = sample();
+        let offset = |time, rounding| index.frame_near(time, rounding).map(|f| f.byte_offset);
+
+        assert_eq!(offset(0.5, Rounding::Before), Some(288));
+        assert_eq!(offset(0.5, Rounding::After), Some(288));
+        assert_eq!(offset(0.2, Rounding::Before), Some(200));
+        assert_eq!(offset(0.2, Rounding::After), Some(288));
+        assert_eq!(offset(0.2, Rounding::Nearest), Some(200));
+        assert_eq!(offset(0.25, Rounding::Nearest), Some(200));
+        assert_eq!(offset(0.3, Rounding::Nearest), Some(288));
+        assert_eq!(offset(-1.0, Rounding::Before), None);
+        assert_eq!(offset(-1.0, Rounding::Nearest), Some(200));
+        assert_eq!(offset(2.0, Rounding::After), None);
+        assert_eq!(index.nearest_frame(2.0).unwrap().byte_offset, 288);
+        assert!(Index::default().nearest_frame(0.0).is_none());
+    }
+}
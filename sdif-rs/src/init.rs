@@ -6,20 +6,78 @@
 //!
 //! Users don't need to call these functions directly - initialization is
 //! handled automatically when opening an SDIF file.
+//!
+//! With the `runtime-loading` feature the library is a shared library
+//! loaded on first use rather than linked, and [`is_available()`] tells
+//! whether it is installed.
+//!
+//! [`backend_info()`] describes the library in use, so callers can adapt to
+//! older system installs that lack optional parts.
+//!
+//! # Poisoned Initialization
+//!
+//! The C library loads its predefined types from the file named by the
+//! [`TYPES_ENV`] environment variable. When that file can't be read, it
+//! prints a remark and carries on with a built-in copy of the types, which
+//! may not match the ones the user meant to load. The initialization is
+//! then recorded as poisoned: opening or creating any file fails with
+//! [`Error::InitPoisoned`], explaining the problem, until
+//! [`ALLOW_MISSING_TYPES_ENV`] is set to accept the built-in types.
+//!
+//! # Embedded Types
+//!
+//! Installed applications often ship without the library's default types
+//! file. With the `embedded-types` feature, when [`TYPES_ENV`] is unset and
+//! there is no `SdifTypes.STYP` in the current directory or at the
+//! library's default path, a copy of the standard types file is written to
+//! the temporary directory and loaded instead, see
+//! [`embedded_types_file()`].
 
+use std::env;
+use std::ffi::{CString, OsStr};
+use std::fs::File;
+#[cfg(feature = "embedded-types")]
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::ptr;
-use std::sync::Once;
+use std::sync::OnceLock;
 
 use sdif_sys::SdifGenInit;
 
-/// Static guard for one-time initialization.
-static INIT: Once = Once::new();
+use crate::error::{Error, Result};
+use crate::metadata::Version;
 
-/// Flag to track if initialization succeeded.
-///
-/// We use a simple atomic bool pattern here. In practice, SdifGenInit
-/// always succeeds, but we track it for safety.
-static mut INIT_SUCCEEDED: bool = false;
+/// Environment variable naming the predefined types file the SDIF library
+/// loads, usually `SdifTypes.STYP`.
+pub const TYPES_ENV: &str = "SDIFTYPES";
+
+/// Environment variable that lets files be opened after a poisoned
+/// initialization, with the library's built-in types. Any value other than
+/// empty or `0` enables it.
+pub const ALLOW_MISSING_TYPES_ENV: &str = "SDIF_ALLOW_MISSING_TYPES";
+
+/// Outcome of the one-time initialization.
+#[derive(Debug)]
+enum InitState {
+    /// The library couldn't be loaded, so it wasn't initialized.
+    Unavailable,
+
+    /// The library is initialized with the requested types.
+    Ready,
+
+    /// The library is initialized, but its types file couldn't be read.
+    Poisoned(String),
+}
+
+/// Set once by [`ensure_initialized()`].
+static STATE: OnceLock<InitState> = OnceLock::new();
+
+/// Name of the types file the library looks for in the current directory.
+#[cfg(feature = "embedded-types")]
+const TYPES_FILE_NAME: &str = "SdifTypes.STYP";
+
+/// Copy of the standard types file loaded at initialization, if any.
+static EMBEDDED_TYPES_FILE: OnceLock<PathBuf> = OnceLock::new();
 
 /// Ensures the SDIF library is initialized.
 ///
@@ -29,6 +87,7 @@ static mut INIT_SUCCEEDED: bool = false;
 /// # Returns
 ///
 /// `true` if the library is (now) initialized, `false` if initialization failed.
+/// A [poisoned](poison_reason) initialization still returns `true`.
 ///
 /// # Example
 ///
@@ -38,35 +97,248 @@ static mut INIT_SUCCEEDED: bool = false;
 /// assert!(ensure_initialized());
 /// ```
 pub fn ensure_initialized() -> bool {
-    INIT.call_once(|| {
-        // SAFETY: SdifGenInit is called exactly once, protected by Once.
-        // Passing null uses the default types file path.
+    let state = STATE.get_or_init(|| {
+        if !sdif_sys::is_available() {
+            return InitState::Unavailable;
+        }
+
+        // Checked first, as the library reads the variable itself
+        let problem = types_file_problem(env::var_os(TYPES_ENV).as_deref());
+        let types_file = fallback_types_file();
+
+        // SAFETY: SdifGenInit is called exactly once, protected by
+        // OnceLock. Passing null uses the default types file path.
         unsafe {
-            SdifGenInit(ptr::null());
-            INIT_SUCCEEDED = true;
+            SdifGenInit(types_file.as_ref().map_or(ptr::null(), |path| path.as_ptr()));
+        }
+
+        match problem {
+            Some(reason) => InitState::Poisoned(reason),
+            None => InitState::Ready,
         }
     });
 
-    // SAFETY: INIT_SUCCEEDED is only written inside call_once,
-    // which guarantees it completes before any read.
-    unsafe { INIT_SUCCEEDED }
+    !matches!(state, InitState::Unavailable)
+}
+
+/// Get why the initialization is poisoned, if it is.
+///
+/// Returns `None` if the library is not initialized yet or was initialized
+/// with the requested types. See the [module documentation](self).
+///
+/// # Example
+///
+/// ```no_run
+/// sdif_rs::init::ensure_initialized();
+/// if let Some(reason) = sdif_rs::init::poison_reason() {
+///     eprintln!("warning: {}", reason);
+/// }
+/// ```
+pub fn poison_reason() -> Option<&'static str> {
+    match STATE.get() {
+        Some(InitState::Poisoned(reason)) => Some(reason),
+        _ => None,
+    }
+}
+
+/// Get the copy of the standard types file the library was initialized
+/// with, if the `embedded-types` feature put one in place.
+///
+/// Returns `None` without the feature, before initialization, when a types
+/// file was found (including the library's
+/// [default](sdif_sys::default_types_file)), or when the copy couldn't be
+/// written; the library then uses its own default. See the [module documentation](self).
+///
+/// # Example
+///
+/// ```no_run
+/// sdif_rs::init::ensure_initialized();
+/// if let Some(path) = sdif_rs::init::embedded_types_file() {
+///     println!("Using the built-in types from {}", path.display());
+/// }
+/// ```
+pub fn embedded_types_file() -> Option<&'static Path> {
+    EMBEDDED_TYPES_FILE.get().map(PathBuf::as_path)
+}
+
+/// Get the types file to initialize the library with, when the library's
+/// default shouldn't be used.
+#[cfg(feature = "embedded-types")]
+fn fallback_types_file() -> Option<CString> {
+    let types_env = env::var_os(TYPES_ENV);
+    let default_file = sdif_sys::default_types_file().is_some_and(|path| Path::new(path).is_file());
+    if types_env.is_some_and(|path| !path.is_empty())
+        || Path::new(TYPES_FILE_NAME).is_file()
+        || default_file
+    {
+        return None;
+    }
+
+    let path = write_standard_types(&env::temp_dir()).ok()?;
+    let c_path = CString::new(path.to_str()?).ok()?;
+    EMBEDDED_TYPES_FILE.get_or_init(|| path);
+    Some(c_path)
+}
+
+/// Without the `embedded-types` feature the library's default is used.
+#[cfg(not(feature = "embedded-types"))]
+fn fallback_types_file() -> Option<CString> {
+    None
+}
+
+/// Write the standard types file to `dir` and return its path.
+///
+/// The name includes the crate version, so processes built against
+/// different releases don't replace each other's copy.
+#[cfg(feature = "embedded-types")]
+fn write_standard_types(dir: &Path) -> io::Result<PathBuf> {
+    let path = dir.join(format!("sdif-rs-{}-{}", env!("CARGO_PKG_VERSION"), TYPES_FILE_NAME));
+
+    // Written to a new file with a random name, then moved into place:
+    // other processes never load a partial file, and a file or link
+    // planted at the path is replaced rather than trusted or followed
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(sdif_sys::STANDARD_TYPES.as_bytes())?;
+    match file.persist(&path) {
+        Ok(_) => Ok(path),
+        // Another user's copy in a shared directory can't be replaced
+        Err(e) => Ok(e.file.into_temp_path().keep()?),
+    }
+}
+
+/// Check whether [`ALLOW_MISSING_TYPES_ENV`] is set.
+fn missing_types_allowed() -> bool {
+    env::var_os(ALLOW_MISSING_TYPES_ENV).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Find why the types file named by [`TYPES_ENV`] can't be loaded.
+///
+/// Like the SDIF library, a file with the same name in the current
+/// directory is used first. Without the variable, the library's default
+/// file is optional, so there is nothing to check.
+fn types_file_problem(types_file: Option<&OsStr>) -> Option<String> {
+    let path = Path::new(types_file.filter(|path| !path.is_empty())?);
+    if path.file_name().is_some_and(|name| Path::new(name).is_file()) {
+        return None;
+    }
+
+    let error = match File::open(path) {
+        Ok(_) if path.is_file() => return None,
+        Ok(_) => "not a file".to_string(),
+        Err(e) => e.to_string(),
+    };
+    Some(format!(
+        "the SDIF types file {}={} can't be read ({}), so the SDIF library fell back \
+         to its built-in types; fix or unset {}, or set {}=1 to use the built-in types",
+        TYPES_ENV,
+        path.display(),
+        error,
+        TYPES_ENV,
+        ALLOW_MISSING_TYPES_ENV
+    ))
+}
+
+/// Check whether the SDIF library can be used.
+///
+/// Always true when the library is linked at build time. With the
+/// `runtime-loading` feature it is false when the shared library isn't
+/// installed, and opening or creating a file fails with
+/// [`Error::LibraryUnavailable`].
+///
+/// # Example
+///
+/// ```no_run
+/// if !sdif_rs::init::is_available() {
+///     eprintln!("Install the SDIF library to read .sdif files");
+/// }
+/// ```
+pub fn is_available() -> bool {
+    sdif_sys::is_available()
+}
+
+/// Description of the SDIF C library behind this crate.
+///
+/// Returned by [`backend_info()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackendInfo {
+    /// Whether the library can be called, see [`is_available()`].
+    pub available: bool,
+
+    /// Whether the library is loaded at runtime rather than linked.
+    pub runtime_loaded: bool,
+
+    /// Version of the library headers the bindings were built from, if
+    /// known.
+    pub version: Option<Version>,
+
+    /// Whether the library has the selection functions.
+    pub selections: bool,
+
+    /// Whether the library can convert between SDIF and text.
+    pub text_conversion: bool,
+}
+
+/// Describe the SDIF C library in use.
+///
+/// # Example
+///
+/// ```no_run
+/// let info = sdif_rs::backend_info();
+/// match info.version {
+///     Some(version) => println!("libsdif {}", version),
+///     None => println!("libsdif, unknown version"),
+/// }
+/// if !info.text_conversion {
+///     eprintln!("Text conversion needs a newer SDIF library");
+/// }
+/// ```
+pub fn backend_info() -> BackendInfo {
+    BackendInfo {
+        available: is_available(),
+        runtime_loaded: sdif_sys::RUNTIME_LOADING,
+        version: sdif_sys::library_version().and_then(Version::parse),
+        selections: sdif_sys::supports_selections(),
+        text_conversion: sdif_sys::supports_text_conversion(),
+    }
+}
+
+/// Initialize the library before opening or creating a file.
+///
+/// # Errors
+///
+/// - [`Error::LibraryUnavailable`] if the library can't be loaded
+/// - [`Error::InitFailed`] if initialization failed
+/// - [`Error::InitPoisoned`] if the types file couldn't be loaded and
+///   [`ALLOW_MISSING_TYPES_ENV`] isn't set
+pub(crate) fn initialize() -> Result<()> {
+    sdif_sys::check_library().map_err(|reason| Error::LibraryUnavailable {
+        reason: reason.to_string(),
+    })?;
+
+    if !ensure_initialized() {
+        return Err(Error::InitFailed);
+    }
+    match poison_reason() {
+        Some(reason) if !missing_types_allowed() => Err(Error::InitPoisoned {
+            reason: reason.to_string(),
+        }),
+        _ => Ok(()),
+    }
 }
 
 /// Check if the library has been initialized.
 ///
 /// Returns `true` if `ensure_initialized()` has been called successfully.
 pub fn is_initialized() -> bool {
-    if INIT.is_completed() {
-        // SAFETY: If Once is completed, INIT_SUCCEEDED has its final value.
-        unsafe { INIT_SUCCEEDED }
-    } else {
-        false
-    }
+    matches!(STATE.get(), Some(InitState::Ready | InitState::Poisoned(_)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "embedded-types")]
+    use std::fs;
 
     #[test]
     #[cfg(not(sdif_stub_bindings))]
@@ -80,5 +352,69 @@ mod tests {
 
         // Should report as initialized
         assert!(is_initialized());
+        assert!(is_available());
+        assert!(initialize().is_ok());
+    }
+
+    #[test]
+    #[cfg(not(sdif_stub_bindings))]
+    fn test_backend_info() {
+        let info = backend_info();
+        assert!(info.available);
+        assert_eq!(info.runtime_loaded, cfg!(feature = "runtime-loading"));
+
+        let version = info.version.expect("headers have sdif_version.h");
+        assert!(version.major >= 3);
+        assert_eq!(
+            sdif_sys::library_version_number(),
+            Some((version.major * 10000 + version.minor * 100 + version.patch) as u32)
+        );
+        assert!(info.selections);
+        assert!(info.text_conversion);
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-types")]
+    fn test_write_standard_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_standard_types(dir.path()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), sdif_sys::STANDARD_TYPES);
+
+        // A damaged copy is replaced, and no partial file is left behind
+        fs::write(&path, "SDIF\n").unwrap();
+        assert_eq!(write_standard_types(dir.path()).unwrap(), path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), sdif_sys::STANDARD_TYPES);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "embedded-types"))]
+    fn test_write_standard_types_replaces_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        fs::write(&target, "keep").unwrap();
+        let name = format!("sdif-rs-{}-{}", env!("CARGO_PKG_VERSION"), TYPES_FILE_NAME);
+        std::os::unix::fs::symlink(&target, dir.path().join(name)).unwrap();
+
+        let path = write_standard_types(dir.path()).unwrap();
+        assert!(!fs::symlink_metadata(&path).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&path).unwrap(), sdif_sys::STANDARD_TYPES);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "keep");
+    }
+
+    #[test]
+    fn test_types_file_problem() {
+        assert_eq!(types_file_problem(None), None);
+        assert_eq!(types_file_problem(Some(OsStr::new(""))), None);
+        let existing = concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs");
+        assert_eq!(types_file_problem(Some(OsStr::new(existing))), None);
+
+        let reason = types_file_problem(Some(OsStr::new("/nonexistent/SdifTypes.STYP")))
+            .expect("missing file");
+        assert!(reason.contains("SDIFTYPES=/nonexistent/SdifTypes.STYP"));
+        assert!(reason.contains(ALLOW_MISSING_TYPES_ENV));
+
+        let reason = types_file_problem(Some(OsStr::new("/"))).expect("not a file");
+        assert!(reason.contains("not a file") || reason.contains("directory"));
     }
 }
@@ -87,49 +87,139 @@
 //! - `mat`: Enable MAT file parsing for MATLAB/Octave file conversion (includes `ndarray`)
 //! - `bundled`: Compile SDIF C library from bundled source
 //! - `static`: Force static linking of SDIF C library
+//! - `runtime-loading`: Load the SDIF shared library when first used instead
+//!   of linking it; see [`init::is_available()`]
+//! - `threaded`: [`SdifFile::open_owned()`] for files that can be moved
+//!   between threads (includes `bundled`)
+//! - `embedded-types`: Load a built-in copy of the standard types file when
+//!   none is installed; see [`init::embedded_types_file()`]
+//! - `serde`: Derive `serde` traits for summary, statistics and schema types,
+//!   and for owned frames and [`document::SdifDocument`]
+//! - `json`: JSON output for summaries and JSON schema documents (includes `serde`)
+//! - `toml`: TOML schema documents (includes `serde`)
+//! - `chrono`: Read and write NVT dates as `chrono` types
+//! - `wav`: Read the duration and sample rate of WAV source audio
+//! - `pitch`: YIN pitch tracking from WAV files to 1FQ0 (includes `wav`)
+//! - `resonance`: Modal resonance fitting from WAV files to 1RES (includes `wav`)
+//! - `synth`: Additive resynthesis of 1TRC/1HRM partials to WAV (includes `wav`)
+//! - `metrics`: Count frames and bytes read and written, and time conversions,
+//!   through the `metrics` facade; see [`metrics`](mod@metrics)
+//!
+//! None are enabled by default. The default build is the reader and writer
+//! alone, without `ndarray`, `matfile` or serde, for plugins and other
+//! embedded uses where build time and dependency count matter.
 //!
 //! ## Thread Safety
 //!
 //! The underlying SDIF C library uses global state and is not thread-safe.
 //! `SdifFile` is marked as `!Send + !Sync` to prevent cross-thread usage.
 //! All SDIF operations should occur on a single thread.
+//!
+//! Services that write from several threads can use [`WriterPool`], which
+//! keeps its writers on a dedicated thread and is itself `Send + Sync`.
+//!
+//! Batch jobs that read one file per thread can enable the `threaded`
+//! feature and open files with [`SdifFile::open_owned()`]; the returned
+//! [`OwnedSdifFile`] is `Send` and locks the library around each use.
 
 #![deny(missing_docs)]
 
+#[cfg(all(feature = "threaded", feature = "runtime-loading"))]
+compile_error!("the `threaded` feature needs the bundled library and can't be used with `runtime-loading`");
+
 // Modules - Reading
+pub mod columns;
 mod data_type;
+pub mod document;
 mod error;
 mod file;
 mod frame;
+mod frame_data;
+pub mod history;
+mod index;
 pub mod init;
 mod matrix;
+pub mod metadata;
+pub mod metrics;
+pub mod player;
+pub mod rows;
+mod schema;
+pub mod schemas;
+pub mod selection;
 mod signature;
+pub mod source;
+pub mod streams;
 
 // Modules - Writing
 pub mod builder;
 mod frame_builder;
 mod writer;
+mod writer_pool;
+#[cfg(feature = "threaded")]
+mod threaded;
+
+// Modules - Analysis
+pub mod analysis;
+pub mod breakpoints;
+pub mod corpus;
+pub mod descriptors;
+pub mod f0;
+pub mod features;
+pub mod filters;
+#[cfg(feature = "pitch")]
+pub mod pitch;
+#[cfg(feature = "resonance")]
+pub mod resonance;
+#[cfg(feature = "synth")]
+pub mod synth;
+pub mod tracks;
+
+// Modules - File operations
+pub mod copy;
+pub mod csv;
+pub mod dump;
+pub mod praat;
+pub mod repair;
+pub mod sonic_visualiser;
+pub mod split;
 
 // Modules - MAT file support (optional)
 #[cfg(feature = "mat")]
 pub mod mat;
 
 // Public exports - Core types
-pub use data_type::DataType;
-pub use error::{Error, Result};
-pub use file::SdifFile;
-pub use frame::Frame;
+pub use data_type::{DataType, MatrixValue};
+pub use error::{Error, ErrorCategory, Result};
+pub use file::{FileHeader, ReadOptions, SdifFile};
+pub use frame::{
+    FilteredFrames, Frame, FrameFilter, FrameIterator, MatrixDataFilter, ReverseFrames, TimeSlices,
+};
+pub use frame_data::{FrameData, MatrixData, OwnedFrame, OwnedMatrix, TimeSlice};
+pub use history::HistoryEntry;
+pub use index::{FrameRef, Index, Rounding};
+pub use init::{BackendInfo, backend_info};
 pub use matrix::Matrix;
+pub use metadata::Version;
+pub use schema::{ComponentSchema, FrameTypeSchema, MatrixTypeSchema, Schema};
 pub use signature::{Signature, signature_to_string, string_to_signature};
+pub use source::SourceAudio;
+#[doc(hidden)]
+pub use signature::sig_const_str;
 
 // Public exports - Writing
 pub use builder::SdifFileBuilder;
 pub use frame_builder::FrameBuilder;
-pub use writer::SdifWriter;
+pub use writer::{DuplicateFrames, SdifWriter, WriterStats};
+pub use writer_pool::WriterPool;
+#[cfg(feature = "threaded")]
+pub use threaded::OwnedSdifFile;
 
-// Public exports - MAT support
+// Public exports - MAT support (types only; helpers stay in `mat`)
 #[cfg(feature = "mat")]
-pub use mat::{MatData, MatFile, MatToSdifConfig, MatToSdifConverter, ComplexMode, TimeStats};
+pub use mat::{
+    ComplexMode, DuplicateTimes, HeuristicDetector, MatData, MatFile, MatToSdifConfig,
+    MatToSdifConverter, TimeStats, TimeValidation, ValueStats, VariableDetector,
+};
 
 // Re-export common signatures for convenience
 pub mod signatures {
@@ -138,19 +228,19 @@ pub mod signatures {
     use super::Signature;
 
     /// 1TRC - Sinusoidal Tracks (most widely supported)
-    pub const TRC: Signature = super::signature::sig_const(b"1TRC");
+    pub const TRC: Signature = crate::sig!("1TRC");
 
     /// 1HRM - Harmonic Partials
-    pub const HRM: Signature = super::signature::sig_const(b"1HRM");
+    pub const HRM: Signature = crate::sig!("1HRM");
 
     /// 1FQ0 - Fundamental Frequency
-    pub const FQ0: Signature = super::signature::sig_const(b"1FQ0");
+    pub const FQ0: Signature = crate::sig!("1FQ0");
 
     /// 1RES - Resonances
-    pub const RES: Signature = super::signature::sig_const(b"1RES");
+    pub const RES: Signature = crate::sig!("1RES");
 
     /// 1STF - Short-Time Fourier Transform
-    pub const STF: Signature = super::signature::sig_const(b"1STF");
+    pub const STF: Signature = crate::sig!("1STF");
 }
 
 // Conditional re-exports
@@ -2,65 +2,130 @@
 //!
 //! MATLAB stores complex numbers as separate real and imaginary arrays.
 //! This module provides utilities for converting to various representations.
+//!
+//! The functions accept owned arrays and views alike, so slices of a large
+//! spectrum can be converted without copying them first. The `_into`
+//! variants write into an existing buffer instead of allocating one.
 
-use ndarray::Array2;
+use ndarray::{Array2, ArrayBase, Data, DataMut, Ix2, Zip};
 
 use crate::error::{Error, Result};
 
 /// Convert complex data to magnitude.
 ///
 /// magnitude = sqrt(real² + imag²)
-pub fn to_magnitude(real: &Array2<f64>, imag: &Array2<f64>) -> Result<Array2<f64>> {
-    if real.dim() != imag.dim() {
-        return Err(Error::invalid_format(format!(
-            "Real and imaginary arrays have different shapes: {:?} vs {:?}",
-            real.dim(),
-            imag.dim()
-        )));
-    }
+pub fn to_magnitude<R, I>(real: &ArrayBase<R, Ix2>, imag: &ArrayBase<I, Ix2>) -> Result<Array2<f64>>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+{
+    let mut magnitude = Array2::zeros(real.dim());
+    magnitude_into(real, imag, &mut magnitude)?;
+    Ok(magnitude)
+}
 
-    Ok((real * real + imag * imag).mapv(f64::sqrt))
+/// Convert complex data to magnitude, writing into `out`.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the arrays don't all have the same shape
+pub fn magnitude_into<R, I, O>(
+    real: &ArrayBase<R, Ix2>,
+    imag: &ArrayBase<I, Ix2>,
+    out: &mut ArrayBase<O, Ix2>,
+) -> Result<()>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+    O: DataMut<Elem = f64>,
+{
+    check_shapes(real, imag, out)?;
+    Zip::from(out)
+        .and(real)
+        .and(imag)
+        .for_each(|m, &r, &i| *m = (r * r + i * i).sqrt());
+    Ok(())
 }
 
 /// Convert complex data to phase.
 ///
 /// phase = atan2(imag, real)
-pub fn to_phase(real: &Array2<f64>, imag: &Array2<f64>) -> Result<Array2<f64>> {
-    if real.dim() != imag.dim() {
-        return Err(Error::invalid_format(format!(
-            "Real and imaginary arrays have different shapes: {:?} vs {:?}",
-            real.dim(),
-            imag.dim()
-        )));
-    }
+pub fn to_phase<R, I>(real: &ArrayBase<R, Ix2>, imag: &ArrayBase<I, Ix2>) -> Result<Array2<f64>>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+{
+    let mut phase = Array2::zeros(real.dim());
+    phase_into(real, imag, &mut phase)?;
+    Ok(phase)
+}
 
-    let (rows, cols) = real.dim();
-    let mut phase = Array2::zeros((rows, cols));
+/// Convert complex data to phase, writing into `out`.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the arrays don't all have the same shape
+pub fn phase_into<R, I, O>(
+    real: &ArrayBase<R, Ix2>,
+    imag: &ArrayBase<I, Ix2>,
+    out: &mut ArrayBase<O, Ix2>,
+) -> Result<()>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+    O: DataMut<Elem = f64>,
+{
+    check_shapes(real, imag, out)?;
+    Zip::from(out)
+        .and(real)
+        .and(imag)
+        .for_each(|p, &r, &i| *p = i.atan2(r));
+    Ok(())
+}
 
-    for ((r, i), p) in real.iter().zip(imag.iter()).zip(phase.iter_mut()) {
-        *p = i.atan2(*r);
-    }
+/// Lowest level returned by [`to_db()`], in dB.
+pub const DEFAULT_DB_FLOOR: f64 = -200.0;
 
-    Ok(phase)
+/// Convert complex data to dB magnitude.
+///
+/// db = 20 * log10(magnitude), clamped to at least [`DEFAULT_DB_FLOOR`]
+pub fn to_db<R, I>(real: &ArrayBase<R, Ix2>, imag: &ArrayBase<I, Ix2>) -> Result<Array2<f64>>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+{
+    to_db_with_floor(real, imag, DEFAULT_DB_FLOOR)
 }
 
-/// Convert complex data to dB magnitude.
+/// Convert complex data to dB magnitude, clamped to at least `floor` dB.
 ///
-/// db = 20 * log10(magnitude)
-pub fn to_db(real: &Array2<f64>, imag: &Array2<f64>) -> Result<Array2<f64>> {
-    let mag = to_magnitude(real, imag)?;
+/// The floor keeps silent bins, whose magnitude is 0, from becoming -∞.
+pub fn to_db_with_floor<R, I>(
+    real: &ArrayBase<R, Ix2>,
+    imag: &ArrayBase<I, Ix2>,
+    floor: f64,
+) -> Result<Array2<f64>>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+{
+    let mut mag = to_magnitude(real, imag)?;
 
-    // Avoid log(0) by clamping to a small value
-    let min_val = 1e-10;
-    Ok(mag.mapv(|x| 20.0 * x.max(min_val).log10()))
+    // Avoid log(0) by clamping to the floor's magnitude
+    let min_val = 10f64.powf(floor / 20.0);
+    mag.mapv_inplace(|x| 20.0 * x.max(min_val).log10());
+    Ok(mag)
 }
 
 /// Unwrap phase to remove discontinuities.
 ///
 /// Adjusts phase values to avoid jumps greater than π.
-pub fn unwrap_phase(phase: &Array2<f64>) -> Array2<f64> {
+pub fn unwrap_phase<S>(phase: &ArrayBase<S, Ix2>) -> Array2<f64>
+where
+    S: Data<Elem = f64>,
+{
     let (rows, cols) = phase.dim();
-    let mut unwrapped = phase.clone();
+    let mut unwrapped = phase.to_owned();
 
     let pi = std::f64::consts::PI;
     let two_pi = 2.0 * pi;
@@ -89,10 +154,14 @@ pub fn unwrap_phase(phase: &Array2<f64>) -> Array2<f64> {
 }
 
 /// Convert polar (magnitude, phase) to rectangular (real, imag).
-pub fn polar_to_rectangular(
-    mag: &Array2<f64>,
-    phase: &Array2<f64>,
-) -> Result<(Array2<f64>, Array2<f64>)> {
+pub fn polar_to_rectangular<M, P>(
+    mag: &ArrayBase<M, Ix2>,
+    phase: &ArrayBase<P, Ix2>,
+) -> Result<(Array2<f64>, Array2<f64>)>
+where
+    M: Data<Elem = f64>,
+    P: Data<Elem = f64>,
+{
     if mag.dim() != phase.dim() {
         return Err(Error::invalid_format(format!(
             "Magnitude and phase arrays have different shapes: {:?} vs {:?}",
@@ -107,11 +176,39 @@ pub fn polar_to_rectangular(
     Ok((real, imag))
 }
 
+/// Check that the real, imaginary and output arrays have the same shape.
+fn check_shapes<R, I, O>(
+    real: &ArrayBase<R, Ix2>,
+    imag: &ArrayBase<I, Ix2>,
+    out: &ArrayBase<O, Ix2>,
+) -> Result<()>
+where
+    R: Data<Elem = f64>,
+    I: Data<Elem = f64>,
+    O: Data<Elem = f64>,
+{
+    if real.dim() != imag.dim() {
+        return Err(Error::invalid_format(format!(
+            "Real and imaginary arrays have different shapes: {:?} vs {:?}",
+            real.dim(),
+            imag.dim()
+        )));
+    }
+    if out.dim() != real.dim() {
+        return Err(Error::invalid_format(format!(
+            "Output array has shape {:?}, expected {:?}",
+            out.dim(),
+            real.dim()
+        )));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
-    use ndarray::array;
+    use ndarray::{array, s};
 
     #[test]
     fn test_magnitude() {
@@ -126,6 +223,41 @@ mod tests {
         assert_relative_eq!(mag[[1, 1]], 1.0, epsilon = 1e-10);
     }
 
+    #[test]
+    fn test_into_views() {
+        let spectrum = array![[3.0, 0.0, 4.0, 1.0], [0.0, 1.0, 1.0, 0.0]];
+        let (real, imag) = (spectrum.slice(s![.., ..2]), spectrum.slice(s![.., 2..]));
+
+        let mut out = Array2::zeros((2, 4));
+        magnitude_into(&real, &imag, &mut out.slice_mut(s![.., ..2])).unwrap();
+        phase_into(&real, &imag, &mut out.slice_mut(s![.., 2..])).unwrap();
+
+        assert_relative_eq!(out[[0, 0]], 5.0, epsilon = 1e-10);
+        assert_relative_eq!(out[[1, 1]], 1.0, epsilon = 1e-10);
+        assert_relative_eq!(out[[0, 3]], std::f64::consts::FRAC_PI_2, epsilon = 1e-10);
+        assert_relative_eq!(out[[1, 2]], std::f64::consts::FRAC_PI_2, epsilon = 1e-10);
+        assert_eq!(to_magnitude(&real, &imag).unwrap(), out.slice(s![.., ..2]));
+
+        let mut wrong = Array2::zeros((2, 3));
+        assert!(magnitude_into(&real, &imag, &mut wrong).is_err());
+    }
+
+    #[test]
+    fn test_db() {
+        let real = array![[1.0, 0.0], [10.0, 0.0]];
+        let imag = array![[0.0, 0.0], [0.0, 0.1]];
+
+        let db = to_db(&real, &imag).unwrap();
+        assert_relative_eq!(db[[0, 0]], 0.0, epsilon = 1e-10);
+        assert_relative_eq!(db[[0, 1]], DEFAULT_DB_FLOOR, epsilon = 1e-10);
+        assert_relative_eq!(db[[1, 0]], 20.0, epsilon = 1e-10);
+
+        let floored = to_db_with_floor(&real, &imag, -12.0).unwrap();
+        assert_relative_eq!(floored[[0, 1]], -12.0, epsilon = 1e-10);
+        assert_relative_eq!(floored[[1, 1]], -12.0, epsilon = 1e-10);
+        assert_relative_eq!(floored[[1, 0]], 20.0, epsilon = 1e-10);
+    }
+
     #[test]
     fn test_phase() {
         let real = array![[1.0, -1.0], [0.0, 0.0]];
@@ -3,13 +3,20 @@
 //! This module provides [`MatToSdifConverter`] for converting MAT file
 //! data to SDIF format, and [`MatToSdifConfig`] for configuration options.
 
-use ndarray::Array1;
-use ndarray::Array2;
+use std::ops::Range;
+
+use ndarray::{Array1, Array2, Axis};
 
 use crate::error::{Error, Result};
+use crate::schemas;
 use crate::writer::SdifWriter;
+use super::complex::{
+    magnitude_into, phase_into, to_db_with_floor, unwrap_phase, DEFAULT_DB_FLOOR,
+};
 use super::data::MatData;
+use super::detect::{HeuristicDetector, VariableDetector};
 use super::file::MatFile;
+use super::time::{TimeValidation, TIME_EPSILON};
 
 /// Configuration for MAT to SDIF conversion.
 ///
@@ -49,8 +56,24 @@ pub struct MatToSdifConfig {
     /// How to handle complex data.
     pub complex_mode: ComplexMode,
 
+    /// Lowest level written by [`ComplexMode::Db`], in dB.
+    pub db_floor: f64,
+
+    /// Whether to unwrap phases across frames.
+    pub unwrap_phase: bool,
+
     /// Stream ID for output frames.
     pub stream_id: u32,
+
+    /// Expected value ranges of named columns.
+    pub column_ranges: Vec<(String, Range<f64>)>,
+
+    /// Whether out-of-range values fail the conversion instead of
+    /// producing warnings.
+    pub strict_ranges: bool,
+
+    /// What to do with frames at the same time as the previous one.
+    pub duplicate_times: DuplicateTimes,
 }
 
 /// How to handle complex numbers in MAT data.
@@ -67,6 +90,32 @@ pub enum ComplexMode {
 
     /// Keep real and imaginary as separate columns.
     RealImag,
+
+    /// Convert to magnitude in dB, down to
+    /// [`db_floor`](MatToSdifConfig::db_floor).
+    Db,
+}
+
+/// How to handle consecutive frames with the same time.
+///
+/// Times closer than 1e-10 s count as the same.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DuplicateTimes {
+    /// Write every frame as is.
+    #[default]
+    Keep,
+
+    /// Write one frame holding the rows of all frames at that time.
+    Merge,
+
+    /// Move each duplicate this many seconds after the frame before it.
+    ///
+    /// The offset should be much smaller than the hop size, or nudged
+    /// frames may overtake the following ones.
+    Offset(f64),
+
+    /// Fail the conversion.
+    Error,
 }
 
 impl Default for ComplexMode {
@@ -82,16 +131,16 @@ impl Default for MatToSdifConfig {
             data_variable: None,
             frame_type: "1TRC".to_string(),
             matrix_type: "1TRC".to_string(),
-            columns: vec![
-                "Index".to_string(),
-                "Frequency".to_string(),
-                "Amplitude".to_string(),
-                "Phase".to_string(),
-            ],
+            columns: schemas::TRC_COLUMNS.iter().map(|c| c.to_string()).collect(),
             max_partials: Some(1024),
             transpose: false,
             complex_mode: ComplexMode::default(),
+            db_floor: DEFAULT_DB_FLOOR,
+            unwrap_phase: false,
             stream_id: 0,
+            column_ranges: Vec::new(),
+            strict_ranges: false,
+            duplicate_times: DuplicateTimes::default(),
         }
     }
 }
@@ -156,11 +205,52 @@ impl MatToSdifConfig {
         self
     }
 
+    /// Set the lowest level written by [`ComplexMode::Db`], in dB.
+    ///
+    /// Silent bins would otherwise be -∞ dB; the default is -200 dB.
+    pub fn db_floor(mut self, floor: f64) -> Self {
+        self.db_floor = floor;
+        self
+    }
+
+    /// Set whether to unwrap phases across frames.
+    ///
+    /// Phases are taken from the `Phase` column of each row, or from the
+    /// phase half of the data in [`ComplexMode::MagnitudePhase`], and
+    /// unwrapped over time with [`unwrap_phase()`](super::unwrap_phase) so
+    /// they no longer jump by 2π between frames.
+    pub fn unwrap_phase(mut self, unwrap: bool) -> Self {
+        self.unwrap_phase = unwrap;
+        self
+    }
+
     /// Set the stream ID.
     pub fn stream_id(mut self, id: u32) -> Self {
         self.stream_id = id;
         self
     }
+
+    /// Expect the values of a column to lie in `range`.
+    ///
+    /// Catches unit mistakes such as frequencies in kHz or amplitudes in
+    /// dB before they reach the SDIF file. The range is half-open, like
+    /// any [`Range`], and NaN is always out of range.
+    pub fn column_range(mut self, column: impl Into<String>, range: Range<f64>) -> Self {
+        self.column_ranges.push((column.into(), range));
+        self
+    }
+
+    /// Set whether out-of-range values are errors rather than warnings.
+    pub fn strict_ranges(mut self, strict: bool) -> Self {
+        self.strict_ranges = strict;
+        self
+    }
+
+    /// Set how frames with duplicate times are handled.
+    pub fn duplicate_times(mut self, policy: DuplicateTimes) -> Self {
+        self.duplicate_times = policy;
+        self
+    }
 }
 
 /// Converter for MAT to SDIF conversion.
@@ -208,6 +298,9 @@ pub struct MatToSdifConverter<'a> {
 impl<'a> MatToSdifConverter<'a> {
     /// Create a new converter.
     ///
+    /// Variables the configuration doesn't name are found by
+    /// [`HeuristicDetector`].
+    ///
     /// # Arguments
     ///
     /// * `mat` - The loaded MAT file.
@@ -218,12 +311,33 @@ impl<'a> MatToSdifConverter<'a> {
     /// - [`Error::InvalidFormat`] if required variables are missing
     /// - [`Error::InvalidFormat`] if data shapes are incompatible
     pub fn new(mat: &'a MatFile, config: MatToSdifConfig) -> Result<Self> {
+        Self::new_with_detector(mat, config, &HeuristicDetector)
+    }
+
+    /// Create a new converter, finding unnamed variables with `detector`.
+    ///
+    /// # Errors
+    ///
+    /// - Any error from the detector
+    /// - [`Error::InvalidFormat`] if named variables are missing
+    /// - [`Error::InvalidFormat`] if data shapes are incompatible
+    pub fn new_with_detector(
+        mat: &'a MatFile,
+        config: MatToSdifConfig,
+        detector: &dyn VariableDetector,
+    ) -> Result<Self> {
         // Find time variable
-        let time_var = Self::find_time_variable(mat, &config)?;
+        let time_var = match config.time_variable {
+            Some(ref name) => mat.require(name)?,
+            None => detector.time_variable(mat)?,
+        };
         let times = time_var.to_array1()?;
 
         // Find data variable
-        let data_var = Self::find_data_variable(mat, &config)?;
+        let data_var = match config.data_variable {
+            Some(ref name) => mat.require(name)?,
+            None => detector.data_variable(mat)?,
+        };
         let mut data = data_var.to_array2()?;
 
         // Handle complex data
@@ -236,6 +350,12 @@ impl<'a> MatToSdifConverter<'a> {
             data = data.t().to_owned();
         }
 
+        if config.unwrap_phase {
+            let magnitude_phase =
+                data_var.is_complex() && config.complex_mode == ComplexMode::MagnitudePhase;
+            Self::unwrap_phase_columns(&mut data, &config, magnitude_phase)?;
+        }
+
         // Validate dimensions
         let num_frames = times.len();
         let (data_rows, _data_cols) = data.dim();
@@ -256,55 +376,32 @@ impl<'a> MatToSdifConverter<'a> {
         })
     }
 
-    /// Find the time variable.
-    fn find_time_variable<'m>(
-        mat: &'m MatFile,
+    /// Unwrap the phase columns of the data along the time axis.
+    fn unwrap_phase_columns(
+        data: &mut Array2<f64>,
         config: &MatToSdifConfig,
-    ) -> Result<&'m MatData> {
-        if let Some(ref name) = config.time_variable {
-            mat.require(name)
+        magnitude_phase: bool,
+    ) -> Result<()> {
+        let total = data.ncols();
+        let columns: Vec<usize> = if magnitude_phase {
+            // Phases make up the second half, after the magnitudes
+            (total / 2..total).collect()
         } else {
-            // Auto-detect
-            let time_vars = mat.find_time_vectors();
-            match time_vars.len() {
-                0 => Err(Error::invalid_format(
-                    "No time vector found. Specify --time-var explicitly.",
-                )),
-                1 => mat.require(time_vars[0]),
-                _ => Err(Error::invalid_format(format!(
-                    "Multiple possible time vectors found: {:?}. Specify --time-var explicitly.",
-                    time_vars
-                ))),
-            }
-        }
-    }
-
-    /// Find the data variable.
-    fn find_data_variable<'m>(
-        mat: &'m MatFile,
-        config: &MatToSdifConfig,
-    ) -> Result<&'m MatData> {
-        if let Some(ref name) = config.data_variable {
-            mat.require(name)
-        } else {
-            // Find non-time 2D variables
-            let candidates: Vec<_> = mat
+            let cols = config.columns.len();
+            let phase = config
+                .columns
                 .iter()
-                .filter(|(_, v)| v.is_2d() && !v.is_likely_time_vector())
-                .map(|(n, _)| n)
-                .collect();
-
-            match candidates.len() {
-                0 => Err(Error::invalid_format(
-                    "No suitable data variable found. Specify --data-var explicitly.",
-                )),
-                1 => mat.require(candidates[0]),
-                _ => Err(Error::invalid_format(format!(
-                    "Multiple possible data variables found: {:?}. Specify --data-var explicitly.",
-                    candidates
-                ))),
-            }
+                .position(|c| c == "Phase")
+                .ok_or_else(|| Error::invalid_format("Phase unwrapping needs a 'Phase' column"))?;
+            (phase..total).step_by(cols).collect()
+        };
+
+        let unwrapped = unwrap_phase(&data.select(Axis(1), &columns));
+        for (source, &column) in columns.iter().enumerate() {
+            data.column_mut(column).assign(&unwrapped.column(source));
         }
+
+        Ok(())
     }
 
     /// Handle complex data according to configuration.
@@ -313,17 +410,22 @@ impl<'a> MatToSdifConverter<'a> {
             ComplexMode::RealOnly => data_var.to_array2(),
             ComplexMode::Magnitude => data_var.magnitude(),
             ComplexMode::MagnitudePhase => {
-                let mag = data_var.magnitude()?;
-                let phase = data_var.phase()?;
-                // Concatenate magnitude and phase columns
-                let (rows, cols) = mag.dim();
+                let real = data_var.to_array2()?;
+                let imag = data_var.imag_to_array2()?;
+                // Magnitude columns followed by phase columns
+                let (rows, cols) = real.dim();
                 let mut combined = Array2::zeros((rows, cols * 2));
-                combined.slice_mut(ndarray::s![.., ..cols]).assign(&mag);
-                combined
-                    .slice_mut(ndarray::s![.., cols..])
-                    .assign(&phase);
+                let (mut mag, mut phase) =
+                    combined.multi_slice_mut((ndarray::s![.., ..cols], ndarray::s![.., cols..]));
+                magnitude_into(&real, &imag, &mut mag)?;
+                phase_into(&real, &imag, &mut phase)?;
                 Ok(combined)
             }
+            ComplexMode::Db => to_db_with_floor(
+                &data_var.to_array2()?,
+                &data_var.imag_to_array2()?,
+                config.db_floor,
+            ),
             ComplexMode::RealImag => {
                 let real = data_var.to_array2()?;
                 let imag = data_var.imag_to_array2()?;
@@ -356,54 +458,197 @@ impl<'a> MatToSdifConverter<'a> {
         (min, max)
     }
 
+    /// Check the frame times, reporting any problem with its location.
+    pub fn validate_times(&self) -> TimeValidation {
+        TimeValidation::check_values(&self.times.to_vec())
+    }
+
     /// Get the number of columns per frame.
     pub fn cols_per_frame(&self) -> usize {
         self.data.ncols()
     }
 
+    /// Check the data against the configured column ranges.
+    ///
+    /// Only the rows that [`write_to()`](Self::write_to) would write are
+    /// checked. Returns one warning per out-of-range value, naming the
+    /// frame index, time, row and column.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if a range names a column that is not
+    ///   configured
+    /// - [`Error::InvalidFormat`] if ranges are strict and a value is out
+    ///   of range
+    pub fn check_ranges(&self) -> Result<Vec<String>> {
+        let columns = &self.config.columns;
+        let checks = self
+            .config
+            .column_ranges
+            .iter()
+            .map(|(name, range)| {
+                let index = columns.iter().position(|c| c == name).ok_or_else(|| {
+                    Error::invalid_format(format!(
+                        "Range given for unknown column '{}' (columns: {})",
+                        name,
+                        columns.join(", ")
+                    ))
+                })?;
+                Ok((index, name, range))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut warnings = Vec::new();
+        if checks.is_empty() || columns.is_empty() {
+            return Ok(warnings);
+        }
+
+        let max_partials = self.config.max_partials.unwrap_or(usize::MAX);
+        for (frame, (time, row)) in self.iter().enumerate() {
+            let row = row.to_vec();
+            let partials = row.chunks_exact(columns.len()).take(max_partials);
+            for (partial, values) in partials.enumerate() {
+                for &(index, name, range) in &checks {
+                    let value = values[index];
+                    if !range.contains(&value) {
+                        warnings.push(format!(
+                            "Frame {} ({:.3}s), row {}: {} {} outside {}..{}",
+                            frame, time, partial, name, value, range.start, range.end
+                        ));
+                    }
+                }
+            }
+        }
+
+        if self.config.strict_ranges && !warnings.is_empty() {
+            return Err(Error::invalid_format(format!(
+                "{} values out of range, first: {}",
+                warnings.len(),
+                warnings[0]
+            )));
+        }
+
+        Ok(warnings)
+    }
+
     /// Write all frames to an SDIF writer.
     ///
+    /// With [strict ranges](MatToSdifConfig::strict_ranges), the data is
+    /// checked first and nothing is written if a value is out of range;
+    /// the same goes for duplicate times with [`DuplicateTimes::Error`].
+    /// Frames at the same time as the previous one are handled according
+    /// to [`MatToSdifConfig::duplicate_times`], so merging may write fewer
+    /// than [`num_frames()`](Self::num_frames) frames.
+    ///
     /// # Arguments
     ///
     /// * `writer` - The SDIF writer to write frames to.
     ///
     /// # Errors
     ///
-    /// Returns any errors from the underlying writer.
+    /// - Any error from [`check_ranges()`](Self::check_ranges) in strict
+    ///   mode
+    /// - [`Error::InvalidFormat`] if a time is duplicated and the policy
+    ///   is [`DuplicateTimes::Error`]
+    /// - Any error from the underlying writer
     pub fn write_to(&self, writer: &mut SdifWriter) -> Result<()> {
-        let max_partials = self.config.max_partials.unwrap_or(usize::MAX);
-
-        for (i, &time) in self.times.iter().enumerate() {
-            let row = self.data.row(i);
-            let row_data: Vec<f64> = row.iter().copied().collect();
-
-            // Calculate number of partials (rows in SDIF matrix)
-            let cols = self.config.columns.len();
-            let num_values = row_data.len();
+        self.write_to_with_progress(writer, |_| {})
+    }
 
-            if num_values % cols != 0 {
+    /// Write all frames to an SDIF writer, reporting progress.
+    ///
+    /// Like [`write_to()`](Self::write_to), calling `progress` with the
+    /// number of source frames done after each one, up to
+    /// [`num_frames()`](Self::num_frames).
+    ///
+    /// # Errors
+    ///
+    /// The same as [`write_to()`](Self::write_to).
+    pub fn write_to_with_progress<F>(&self, writer: &mut SdifWriter, mut progress: F) -> Result<()>
+    where
+        F: FnMut(usize),
+    {
+        if self.config.strict_ranges {
+            self.check_ranges()?;
+        }
+        if self.config.duplicate_times == DuplicateTimes::Error {
+            if let Some(&i) = self.validate_times().duplicates.first() {
                 return Err(Error::invalid_format(format!(
-                    "Data length {} is not divisible by column count {}",
-                    num_values, cols
+                    "Frame {} has the same time as the previous frame ({}s)",
+                    i, self.times[i]
                 )));
             }
+        }
+
+        // Rows of the frames at the current time, when merging
+        let mut merged: Option<(f64, Vec<f64>)> = None;
+        // Source and written time of the previous frame
+        let mut previous: Option<(f64, f64)> = None;
+
+        for (i, &time) in self.times.iter().enumerate() {
+            let values: Vec<f64> = self.data.row(i).iter().copied().collect();
+            let duplicate =
+                previous.is_some_and(|(source, _)| (time - source).abs() <= TIME_EPSILON);
+
+            let written = match (duplicate, self.config.duplicate_times) {
+                (true, DuplicateTimes::Offset(offset)) => {
+                    previous.map_or(time, |(_, written)| written + offset)
+                }
+                _ => time,
+            };
+            previous = Some((time, written));
+
+            if self.config.duplicate_times != DuplicateTimes::Merge {
+                self.write_frame(writer, written, &values)?;
+                progress(i + 1);
+                continue;
+            }
+            match merged {
+                Some((_, ref mut rows)) if duplicate => rows.extend(values),
+                _ => {
+                    if let Some((frame_time, rows)) = merged.replace((time, values)) {
+                        self.write_frame(writer, frame_time, &rows)?;
+                    }
+                }
+            }
+            progress(i + 1);
+        }
 
-            let num_partials = (num_values / cols).min(max_partials);
-            let limited_data = &row_data[..num_partials * cols];
-
-            writer.write_frame_one_matrix(
-                &self.config.frame_type,
-                time,
-                &self.config.matrix_type,
-                num_partials,
-                cols,
-                limited_data,
-            )?;
+        if let Some((frame_time, rows)) = merged {
+            self.write_frame(writer, frame_time, &rows)?;
         }
 
         Ok(())
     }
 
+    /// Write one frame, limiting it to the configured number of partials.
+    fn write_frame(&self, writer: &mut SdifWriter, time: f64, values: &[f64]) -> Result<()> {
+        let max_partials = self.config.max_partials.unwrap_or(usize::MAX);
+
+        // Calculate number of partials (rows in SDIF matrix)
+        let cols = self.config.columns.len();
+        let num_values = values.len();
+
+        if num_values % cols != 0 {
+            return Err(Error::invalid_format(format!(
+                "Data length {} is not divisible by column count {}",
+                num_values, cols
+            )));
+        }
+
+        let num_partials = (num_values / cols).min(max_partials);
+        let limited_data = &values[..num_partials * cols];
+
+        writer.write_frame_one_matrix(
+            &self.config.frame_type,
+            time,
+            &self.config.matrix_type,
+            num_partials,
+            cols,
+            limited_data,
+        )
+    }
+
     /// Get frame data for a specific time index.
     pub fn frame_data(&self, index: usize) -> Option<(&f64, ndarray::ArrayView1<f64>)> {
         if index < self.times.len() {
@@ -439,4 +684,52 @@ mod tests {
         assert_eq!(config.columns, vec!["Freq", "Amp"]);
         assert_eq!(config.max_partials, Some(512));
     }
+
+    #[test]
+    fn test_unwrap_phase_columns() {
+        let config = MatToSdifConfig::new()
+            .columns(&["Frequency", "Phase"])
+            .unwrap_phase(true);
+
+        // Two partials per frame, the second wrapping from +π to -π
+        let mut data = ndarray::array![[440.0, 0.0, 880.0, 3.0], [440.0, 0.5, 880.0, -3.0]];
+        MatToSdifConverter::unwrap_phase_columns(&mut data, &config, false).unwrap();
+        assert_eq!(data[[1, 1]], 0.5);
+        assert!((data[[1, 3]] - (2.0 * std::f64::consts::PI - 3.0)).abs() < 1e-12);
+        assert_eq!(data[[1, 2]], 880.0);
+
+        let no_phase = MatToSdifConfig::new().columns(&["Frequency", "Amplitude"]);
+        assert!(MatToSdifConverter::unwrap_phase_columns(&mut data, &no_phase, false).is_err());
+    }
+
+    #[test]
+    fn test_config_db_floor() {
+        assert_eq!(MatToSdifConfig::new().db_floor, -200.0);
+
+        let config = MatToSdifConfig::new()
+            .complex_mode(ComplexMode::Db)
+            .db_floor(-120.0);
+        assert_eq!(config.complex_mode, ComplexMode::Db);
+        assert_eq!(config.db_floor, -120.0);
+    }
+
+    #[test]
+    fn test_config_duplicate_times() {
+        assert_eq!(MatToSdifConfig::new().duplicate_times, DuplicateTimes::Keep);
+
+        let config = MatToSdifConfig::new().duplicate_times(DuplicateTimes::Offset(1e-6));
+        assert_eq!(config.duplicate_times, DuplicateTimes::Offset(1e-6));
+    }
+
+    #[test]
+    fn test_config_column_ranges() {
+        let config = MatToSdifConfig::new()
+            .column_range("Frequency", 20.0..20000.0)
+            .column_range("Amplitude", 0.0..f64::INFINITY)
+            .strict_ranges(true);
+
+        assert_eq!(config.column_ranges.len(), 2);
+        assert_eq!(config.column_ranges[0], ("Frequency".to_string(), 20.0..20000.0));
+        assert!(config.strict_ranges);
+    }
 }
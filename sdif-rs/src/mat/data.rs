@@ -286,15 +286,150 @@ impl MatData {
     pub fn imag_data(&self) -> Option<&[f64]> {
         self.imag_data.as_deref()
     }
+
+    /// Get summary statistics of the real part.
+    ///
+    /// `min`, `max` and `mean` only take finite values into account; NaNs
+    /// and infinities are counted separately. They are NaN when no value
+    /// is finite.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::MatFile;
+    /// # let mat = MatFile::open("data.mat")?;
+    /// let stats = mat.require("partials")?.stats();
+    /// if stats.nan_count > 0 {
+    ///     println!("{} of {} values are NaN", stats.nan_count, stats.count);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn stats(&self) -> ValueStats {
+        let mut stats = ValueStats {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            nan_count: 0,
+            inf_count: 0,
+            count: self.real_data.len(),
+        };
+
+        let mut sum = 0.0;
+        for &value in &self.real_data {
+            if value.is_nan() {
+                stats.nan_count += 1;
+            } else if value.is_infinite() {
+                stats.inf_count += 1;
+            } else {
+                stats.min = stats.min.min(value);
+                stats.max = stats.max.max(value);
+                sum += value;
+            }
+        }
+
+        let finite = stats.count - stats.nan_count - stats.inf_count;
+        if finite == 0 {
+            stats.min = f64::NAN;
+            stats.max = f64::NAN;
+            stats.mean = f64::NAN;
+        } else {
+            stats.mean = sum / finite as f64;
+        }
+
+        stats
+    }
+
+    /// Get the first `n` rows of the real part as a 2D array.
+    ///
+    /// Rows are as in [`to_array2()`](Self::to_array2), so 1D variables
+    /// give one value per row.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the variable has more than two
+    ///   dimensions
+    pub fn head(&self, n: usize) -> Result<Array2<f64>> {
+        let array = self.to_array2()?;
+        let rows = array.nrows().min(n);
+        Ok(array.slice(ndarray::s![..rows, ..]).to_owned())
+    }
+}
+
+/// Summary of the values of a variable, from [`MatData::stats()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueStats {
+    /// Smallest value, ignoring NaNs.
+    pub min: f64,
+
+    /// Largest value, ignoring NaNs.
+    pub max: f64,
+
+    /// Mean value, ignoring NaNs.
+    pub mean: f64,
+
+    /// Number of NaN values.
+    pub nan_count: usize,
+
+    /// Number of infinite values.
+    pub inf_count: usize,
+
+    /// Total number of values.
+    pub count: usize,
+}
+
+impl std::fmt::Display for ValueStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ValueStats {{ min: {}, max: {}, mean: {}, NaN: {}, inf: {}, count: {} }}",
+            self.min, self.max, self.mean, self.nan_count, self.inf_count, self.count
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn matrix(shape: &[usize], column_major: &[f64]) -> MatData {
+        MatData {
+            name: "test".to_string(),
+            shape: shape.to_vec(),
+            real_data: column_major.to_vec(),
+            imag_data: None,
+            dtype: "float64".to_string(),
+        }
+    }
+
     #[test]
     fn test_is_1d() {
         // These would need actual MatData instances to test properly
         // Integration tests will cover this with real MAT files
     }
+
+    #[test]
+    fn test_stats() {
+        let stats = matrix(&[2, 3], &[1.0, f64::NAN, -3.0, 5.0, f64::INFINITY, 0.0]).stats();
+        assert_eq!(stats.min, -3.0);
+        assert_eq!(stats.max, 5.0);
+        assert_eq!(stats.mean, 0.75);
+        assert_eq!(stats.nan_count, 1);
+        assert_eq!(stats.inf_count, 1);
+        assert_eq!(stats.count, 6);
+
+        let all_nan = matrix(&[1], &[f64::NAN]).stats();
+        assert!(all_nan.min.is_nan() && all_nan.mean.is_nan());
+    }
+
+    #[test]
+    fn test_head() {
+        // [[1, 2, 3], [4, 5, 6]] stored column by column
+        let data = matrix(&[2, 3], &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert_eq!(data.head(1).unwrap(), ndarray::array![[1.0, 2.0, 3.0]]);
+        assert_eq!(data.head(5).unwrap().nrows(), 2);
+
+        let vector = matrix(&[3], &[0.0, 0.1, 0.2]);
+        assert_eq!(vector.head(2).unwrap(), ndarray::array![[0.0], [0.1]]);
+        assert!(matrix(&[1, 1, 1], &[1.0]).head(3).is_err());
+    }
 }
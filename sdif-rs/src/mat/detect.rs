@@ -0,0 +1,102 @@
+//! Auto-detection of the time and data variables.
+//!
+//! When a [`MatToSdifConfig`](super::MatToSdifConfig) doesn't name the
+//! variables to convert, a [`VariableDetector`] picks them.
+//! [`HeuristicDetector`] is used by default; frontends for a known MAT
+//! layout can pass their own detector to
+//! [`MatToSdifConverter::new_with_detector()`](super::MatToSdifConverter::new_with_detector).
+
+use crate::error::{Error, Result};
+use super::data::MatData;
+use super::file::MatFile;
+
+/// Strategy for finding the time and data variables of a MAT file.
+///
+/// Detectors are only consulted for variables the configuration leaves
+/// unnamed.
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::{MatData, MatFile, MatToSdifConfig, MatToSdifConverter, Result, VariableDetector};
+///
+/// /// Our lab saves every analysis as `frameTimes` and `tracks`.
+/// struct LabLayout;
+///
+/// impl VariableDetector for LabLayout {
+///     fn time_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData> {
+///         mat.require("frameTimes")
+///     }
+///
+///     fn data_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData> {
+///         mat.require("tracks")
+///     }
+/// }
+///
+/// let mat = MatFile::open("session.mat")?;
+/// let converter =
+///     MatToSdifConverter::new_with_detector(&mat, MatToSdifConfig::new(), &LabLayout)?;
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+pub trait VariableDetector {
+    /// Find the variable holding the frame times.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Error::InvalidFormat`] when no variable, or more
+    /// than one, qualifies.
+    fn time_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData>;
+
+    /// Find the variable holding the frame data.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Error::InvalidFormat`] when no variable, or more
+    /// than one, qualifies.
+    fn data_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData>;
+}
+
+/// The default detector, based on variable names and shapes.
+///
+/// The time variable is the only one that
+/// [looks like a time vector](MatData::is_likely_time_vector), and the
+/// data variable the only other 2D variable. Ambiguity is an error
+/// asking for the variable to be named explicitly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicDetector;
+
+impl VariableDetector for HeuristicDetector {
+    fn time_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData> {
+        let time_vars = mat.find_time_vectors();
+        match time_vars.len() {
+            0 => Err(Error::invalid_format(
+                "No time vector found. Specify --time-var explicitly.",
+            )),
+            1 => mat.require(time_vars[0]),
+            _ => Err(Error::invalid_format(format!(
+                "Multiple possible time vectors found: {:?}. Specify --time-var explicitly.",
+                time_vars
+            ))),
+        }
+    }
+
+    fn data_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData> {
+        // Find non-time 2D variables
+        let candidates: Vec<_> = mat
+            .iter()
+            .filter(|(_, v)| v.is_2d() && !v.is_likely_time_vector())
+            .map(|(n, _)| n)
+            .collect();
+
+        match candidates.len() {
+            0 => Err(Error::invalid_format(
+                "No suitable data variable found. Specify --data-var explicitly.",
+            )),
+            1 => mat.require(candidates[0]),
+            _ => Err(Error::invalid_format(format!(
+                "Multiple possible data variables found: {:?}. Specify --data-var explicitly.",
+                candidates
+            ))),
+        }
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Read};
 use std::path::Path;
 
 use matfile::MatFile as RawMatFile;
@@ -94,8 +94,37 @@ impl MatFile {
             ))
         })?;
 
-        let reader = BufReader::new(file);
+        Self::parse(BufReader::new(file), path_str)
+    }
+
+    /// Parse a MAT file from any reader, such as standard input.
+    ///
+    /// The whole stream is read into memory before parsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Source of the MAT file bytes.
+    /// * `name` - Name used in place of a path in error messages.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the data is not a valid MAT file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::MatFile;
+    ///
+    /// let mat = MatFile::from_reader(std::io::stdin().lock(), "<stdin>")?;
+    /// println!("Loaded {} variables", mat.len());
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn from_reader(reader: impl Read, name: impl Into<String>) -> Result<Self> {
+        Self::parse(reader, name.into())
+    }
 
+    /// Parse MAT data and collect its numeric variables.
+    fn parse(reader: impl Read, path_str: String) -> Result<Self> {
         let mat_file = RawMatFile::parse(reader).map_err(|e| {
             Error::invalid_format(format!("Failed to parse MAT file '{}': {}", path_str, e))
         })?;
@@ -219,6 +248,15 @@ impl MatFile {
     ///
     /// Returns a formatted string describing each variable.
     pub fn describe(&self) -> String {
+        self.describe_with_preview(0)
+    }
+
+    /// Describe all variables, followed by the first `rows` rows of each.
+    ///
+    /// Besides shape and type, each variable gets the [statistics](MatData::stats)
+    /// of its real part, which are usually enough to tell time vectors,
+    /// frequencies and amplitudes apart.
+    pub fn describe_with_preview(&self, rows: usize) -> String {
         let mut lines = Vec::new();
         lines.push(format!("Variables in '{}':", self.path));
         lines.push(String::new());
@@ -230,23 +268,29 @@ impl MatFile {
         let max_name_len = names.iter().map(|n| n.len()).max().unwrap_or(4);
 
         lines.push(format!(
-            "  {:<width$}  {:>12}  {:>8}  {}",
+            "  {:<width$}  {:>12}  {:>8}  {:>10}  {:>10}  {:>10}  {:>5}  {:>5}  {}",
             "Name",
             "Shape",
             "Type",
+            "Min",
+            "Max",
+            "Mean",
+            "NaN",
+            "Inf",
             "Notes",
             width = max_name_len
         ));
         lines.push(format!(
-            "  {:-<width$}  {:->12}  {:->8}  -----",
-            "", "", "",
+            "  {:-<width$}  {:->12}  {:->8}  {:->10}  {:->10}  {:->10}  {:->5}  {:->5}  -----",
+            "", "", "", "", "", "", "", "",
             width = max_name_len
         ));
 
-        for name in names {
+        for name in &names {
             if let Some(data) = self.get(name) {
                 let shape_str = format!("{:?}", data.shape());
                 let type_str = if data.is_complex() { "complex" } else { "real" };
+                let stats = data.stats();
 
                 let mut notes = Vec::new();
                 if data.is_likely_time_vector() {
@@ -257,20 +301,50 @@ impl MatFile {
                 }
 
                 lines.push(format!(
-                    "  {:<width$}  {:>12}  {:>8}  {}",
+                    "  {:<width$}  {:>12}  {:>8}  {:>10}  {:>10}  {:>10}  {:>5}  {:>5}  {}",
                     name,
                     shape_str,
                     type_str,
+                    format_value(stats.min),
+                    format_value(stats.max),
+                    format_value(stats.mean),
+                    stats.nan_count,
+                    stats.inf_count,
                     notes.join(", "),
                     width = max_name_len
                 ));
             }
         }
 
+        if rows > 0 {
+            for name in &names {
+                // Arrays with more than two dimensions have no rows to show
+                if let Some(Ok(head)) = self.get(name).map(|data| data.head(rows)) {
+                    lines.push(String::new());
+                    lines.push(format!("  {}:", name));
+                    for (i, row) in head.rows().into_iter().enumerate() {
+                        let values: Vec<String> = row.iter().map(|&v| format_value(v)).collect();
+                        lines.push(format!("    [{}] {}", i, values.join(" ")));
+                    }
+                }
+            }
+        }
+
         lines.join("\n")
     }
 }
 
+/// Format a value compactly, switching to scientific notation for very
+/// large or small magnitudes.
+fn format_value(value: f64) -> String {
+    let magnitude = value.abs();
+    if value.is_finite() && value != 0.0 && !(1e-3..1e6).contains(&magnitude) {
+        format!("{:.3e}", value)
+    } else {
+        format!("{:.4}", value)
+    }
+}
+
 impl IntoIterator for MatFile {
     type Item = (String, MatData);
     type IntoIter = std::collections::hash_map::IntoIter<String, MatData>;
@@ -290,5 +364,22 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_from_reader_invalid() {
+        let result = MatFile::from_reader(&b"not a mat file"[..], "<stdin>");
+        match result {
+            Err(Error::InvalidFormat { reason }) => assert!(reason.contains("<stdin>")),
+            other => panic!("expected InvalidFormat, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(440.0), "440.0000");
+        assert_eq!(format_value(0.0), "0.0000");
+        assert_eq!(format_value(44100000.0), "4.410e7");
+        assert_eq!(format_value(f64::NAN), "NaN");
+    }
+
     // Additional tests require test MAT files
 }
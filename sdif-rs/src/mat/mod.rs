@@ -4,6 +4,9 @@
 //! their contents to SDIF format. It's designed for audio analysis
 //! workflows where MAT files contain time-series spectral data.
 //!
+//! Requires the `mat` feature, which also enables `ndarray`. Without it
+//! the crate doesn't depend on `matfile` or `ndarray` at all.
+//!
 //! # Overview
 //!
 //! The main types are:
@@ -12,6 +15,11 @@
 //! - [`MatData`] - Represents a single numeric variable
 //! - [`MatToSdifConfig`] - Configuration for MAT→SDIF conversion
 //! - [`MatToSdifConverter`] - Performs the actual conversion
+//! - [`VariableDetector`] - Picks the variables a configuration leaves unnamed
+//!
+//! These types are also re-exported at the crate root. The complex number
+//! helpers, such as [`to_magnitude()`] and [`unwrap_phase()`], are only
+//! available from this module.
 //!
 //! # Example
 //!
@@ -64,12 +72,17 @@
 mod complex;
 mod convert;
 mod data;
+mod detect;
 mod file;
 mod time;
 
 // Re-exports
-pub use complex::{polar_to_rectangular, to_db, to_magnitude, to_phase, unwrap_phase};
-pub use convert::{ComplexMode, MatToSdifConfig, MatToSdifConverter};
-pub use data::MatData;
+pub use complex::{
+    magnitude_into, phase_into, polar_to_rectangular, to_db, to_db_with_floor, to_magnitude,
+    to_phase, unwrap_phase, DEFAULT_DB_FLOOR,
+};
+pub use convert::{ComplexMode, DuplicateTimes, MatToSdifConfig, MatToSdifConverter};
+pub use data::{MatData, ValueStats};
+pub use detect::{HeuristicDetector, VariableDetector};
 pub use file::MatFile;
-pub use time::TimeStats;
+pub use time::{TimeStats, TimeValidation};
@@ -3,8 +3,19 @@
 //! This module provides methods for identifying time vectors in MAT files,
 //! which are needed to generate SDIF frame timestamps.
 
+use std::fmt;
+
 use super::data::MatData;
 
+/// Largest difference between times still considered equal.
+pub(super) const TIME_EPSILON: f64 = 1e-10;
+
+/// Relative deviation from the median hop beyond which a hop is irregular.
+const HOP_TOLERANCE: f64 = 0.01;
+
+/// Most locations listed per issue by [`TimeValidation::issues()`].
+const MAX_LISTED_LOCATIONS: usize = 5;
+
 /// Common names for time variables in audio analysis MAT files.
 const TIME_VARIABLE_NAMES: &[&str] = &[
     "time",
@@ -101,41 +112,12 @@ impl MatData {
 
     /// Check if values are monotonically increasing.
     pub fn is_monotonically_increasing(&self) -> bool {
-        let data = &self.real_data;
-
-        if data.len() < 2 {
-            return true;
-        }
-
-        // Allow small epsilon for floating point comparison
-        let eps = 1e-10;
-
-        for window in data.windows(2) {
-            if window[1] < window[0] - eps {
-                return false;
-            }
-        }
-
-        true
+        TimeValidation::check(self).is_monotonic()
     }
 
     /// Check if values are strictly increasing (no duplicates).
     pub fn is_strictly_increasing(&self) -> bool {
-        let data = &self.real_data;
-
-        if data.len() < 2 {
-            return true;
-        }
-
-        let eps = 1e-10;
-
-        for window in data.windows(2) {
-            if window[1] <= window[0] + eps {
-                return false;
-            }
-        }
-
-        true
+        TimeValidation::check(self).is_strictly_increasing()
     }
 
     /// Get time statistics (useful for validation).
@@ -215,10 +197,224 @@ impl std::fmt::Display for TimeStats {
     }
 }
 
+/// Detailed check of a time vector, with the location of each problem.
+///
+/// Locations are indices into the vector. Decreasing times, duplicates
+/// and irregular hops are reported at the second time of the offending
+/// pair.
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::{MatFile, TimeValidation};
+///
+/// let mat = MatFile::open("data.mat")?;
+/// let report = TimeValidation::check(mat.require("time")?);
+/// for issue in report.issues() {
+///     println!("time: {}", issue);
+/// }
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeValidation {
+    /// Number of time values.
+    pub len: usize,
+
+    /// Whether the variable is a vector, as [`MatData::is_1d()`].
+    pub is_vector: bool,
+
+    /// Indices of times earlier than the previous one.
+    pub decreasing: Vec<usize>,
+
+    /// Indices of times equal to the previous one.
+    pub duplicates: Vec<usize>,
+
+    /// Indices of negative times.
+    pub negative: Vec<usize>,
+
+    /// Indices of NaN or infinite times.
+    pub non_finite: Vec<usize>,
+
+    /// Median time between consecutive values, if there are at least two.
+    pub median_hop: Option<f64>,
+
+    /// Indices of times whose hop from the previous one differs from the
+    /// median hop by more than 1%.
+    pub irregular_hops: Vec<usize>,
+}
+
+impl TimeValidation {
+    /// Check the real part of a variable as a time vector.
+    pub fn check(data: &MatData) -> Self {
+        TimeValidation {
+            is_vector: data.is_1d(),
+            ..Self::check_values(data.real_data())
+        }
+    }
+
+    /// Check a sequence of times.
+    pub fn check_values(times: &[f64]) -> Self {
+        let mut report = TimeValidation {
+            len: times.len(),
+            is_vector: true,
+            ..Default::default()
+        };
+
+        for (i, &time) in times.iter().enumerate() {
+            if !time.is_finite() {
+                report.non_finite.push(i);
+            } else if time < 0.0 {
+                report.negative.push(i);
+            }
+        }
+
+        for (i, pair) in times.windows(2).enumerate() {
+            let hop = pair[1] - pair[0];
+            if hop < -TIME_EPSILON {
+                report.decreasing.push(i + 1);
+            } else if hop.abs() <= TIME_EPSILON {
+                report.duplicates.push(i + 1);
+            }
+        }
+
+        let mut hops: Vec<f64> = times
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .filter(|hop| hop.is_finite())
+            .collect();
+        if !hops.is_empty() {
+            hops.sort_by(f64::total_cmp);
+            let median = hops[hops.len() / 2];
+            report.median_hop = Some(median);
+            report.irregular_hops = times
+                .windows(2)
+                .enumerate()
+                .filter(|(_, pair)| {
+                    // Written so that NaN hops count as irregular
+                    let hop = pair[1] - pair[0];
+                    let regular = (hop - median).abs() <= median.abs() * HOP_TOLERANCE;
+                    !regular
+                })
+                .map(|(i, _)| i + 1)
+                .collect();
+        }
+
+        report
+    }
+
+    /// Check that no time is earlier than the previous one.
+    pub fn is_monotonic(&self) -> bool {
+        self.decreasing.is_empty()
+    }
+
+    /// Check that every time is later than the previous one.
+    pub fn is_strictly_increasing(&self) -> bool {
+        self.decreasing.is_empty() && self.duplicates.is_empty()
+    }
+
+    /// Check that all hops are close to the median hop.
+    pub fn is_regular(&self) -> bool {
+        self.irregular_hops.is_empty()
+    }
+
+    /// Check that the vector can be used as SDIF frame times: a finite,
+    /// non-negative, strictly increasing vector.
+    ///
+    /// Irregular hops are allowed, as SDIF frames need not be evenly
+    /// spaced.
+    pub fn is_valid(&self) -> bool {
+        self.is_vector
+            && self.is_strictly_increasing()
+            && self.negative.is_empty()
+            && self.non_finite.is_empty()
+    }
+
+    /// Describe each problem found, with its first locations.
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if !self.is_vector {
+            issues.push("not a vector".to_string());
+        }
+
+        let checks = [
+            (&self.non_finite, "NaN or infinite"),
+            (&self.negative, "negative"),
+            (&self.decreasing, "earlier than the previous time"),
+            (&self.duplicates, "equal to the previous time"),
+            (&self.irregular_hops, "irregular hop from the previous time"),
+        ];
+        for (indices, problem) in checks {
+            if !indices.is_empty() {
+                issues.push(format!(
+                    "{} time(s) {}, at {}",
+                    indices.len(),
+                    problem,
+                    locations(indices)
+                ));
+            }
+        }
+
+        issues
+    }
+}
+
+impl fmt::Display for TimeValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let issues = self.issues();
+        if issues.is_empty() {
+            write!(f, "{} times, no issues", self.len)
+        } else {
+            write!(f, "{} times: {}", self.len, issues.join("; "))
+        }
+    }
+}
+
+/// Format the first few indices of a list, e.g. `[3, 7, 12, ...]`.
+fn locations(indices: &[usize]) -> String {
+    let listed: Vec<String> = indices
+        .iter()
+        .take(MAX_LISTED_LOCATIONS)
+        .map(|i| i.to_string())
+        .collect();
+    if indices.len() > MAX_LISTED_LOCATIONS {
+        format!("[{}, ...]", listed.join(", "))
+    } else {
+        format!("[{}]", listed.join(", "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // Tests would require constructing MatData instances
-    // Integration tests will cover this with real MAT files
+    #[test]
+    fn test_time_validation_clean() {
+        let report = TimeValidation::check_values(&[0.0, 0.01, 0.02, 0.03]);
+        assert!(report.is_valid());
+        assert!(report.is_regular());
+        assert_eq!(report.median_hop, Some(0.01));
+        assert!(report.issues().is_empty());
+        assert_eq!(report.to_string(), "4 times, no issues");
+    }
+
+    #[test]
+    fn test_time_validation_problems() {
+        let times = [-0.01, 0.0, 0.01, 0.01, 0.005, 0.02, f64::NAN, 0.1];
+        let report = TimeValidation::check_values(&times);
+
+        assert_eq!(report.negative, [0]);
+        assert_eq!(report.duplicates, [3]);
+        assert_eq!(report.decreasing, [4]);
+        assert_eq!(report.non_finite, [6]);
+        assert!(report.irregular_hops.contains(&4));
+        assert!(!report.is_monotonic());
+        assert!(!report.is_valid());
+        assert_eq!(report.issues()[0], "1 time(s) NaN or infinite, at [6]");
+    }
+
+    #[test]
+    fn test_locations() {
+        assert_eq!(locations(&[1, 2]), "[1, 2]");
+        assert_eq!(locations(&[1, 2, 3, 4, 5, 6]), "[1, 2, 3, 4, 5, ...]");
+    }
 }
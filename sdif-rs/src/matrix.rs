@@ -4,17 +4,22 @@
 //! Each matrix has a signature, dimensions (rows x columns), and
 //! typed numeric data.
 
+use std::ffi::c_void;
 use std::marker::PhantomData;
 
+use indexmap::IndexMap;
 use sdif_sys::{
-    SdifFCurrDataType, SdifFCurrMatrixSignature, SdifFCurrNbCol,
-    SdifFCurrNbRow, SdifFReadMatrixHeader,
-    SdifFCurrOneRowData, SdifFReadOneRow, SdifFSkipMatrixData,
+    SdifDisableErrorOutput, SdifEnableErrorOutput, SdifFCurrDataType,
+    SdifFCurrMatrixSignature, SdifFCurrNbCol, SdifFCurrNbRow, SdifFCurrOneRowData,
+    SdifFReadMatrixHeader, SdifFReadOneRow, SdifFReadPadding, SdifFSkipMatrixData,
+    SdifPaddingCalculate,
 };
 
-use crate::data_type::DataType;
+use crate::data_type::{DataType, MatrixValue};
 use crate::error::{Error, Result};
 use crate::frame::Frame;
+use crate::frame_data::{column_names, decode, split_columns, OwnedMatrix};
+use crate::rows::{MatrixRow, RowLayout};
 use crate::signature::{signature_to_string, Signature};
 
 #[cfg(feature = "ndarray")]
@@ -31,6 +36,11 @@ use ndarray::{Array2, ShapeBuilder};
 ///
 /// - [`data_f64()`](Self::data_f64) - Get all data as `Vec<f64>` (row-major)
 /// - [`data_f32()`](Self::data_f32) - Get all data as `Vec<f32>` (row-major)
+/// - [`data_i32()`](Self::data_i32), [`data_u32()`](Self::data_u32) and
+///   [`data_as()`](Self::data_as) - Get integer data without loss
+/// - [`data_text()`](Self::data_text) - Get a text matrix as a `String`
+/// - [`into_columns()`](Self::into_columns) - Get one `Vec<f64>` per column
+/// - [`rows_as()`](Self::rows_as) - Get typed rows such as [`TrcRow`](crate::rows::TrcRow)
 /// - [`to_array_f64()`](Self::to_array_f64) - Get as `ndarray::Array2<f64>` (requires `ndarray` feature)
 ///
 /// # Example
@@ -71,6 +81,10 @@ pub struct Matrix<'a> {
     /// Data type of matrix elements.
     data_type: DataType,
 
+    /// Rows and columns to read, counted from 0, when a
+    /// [`Selection`](crate::selection::Selection) leaves some out.
+    selected: Option<(Vec<u32>, Vec<u32>)>,
+
     /// Whether data has been read.
     data_read: bool,
 
@@ -90,6 +104,7 @@ impl<'a> Matrix<'a> {
         let cols = unsafe { SdifFCurrNbCol(handle) };
         let raw_dtype = unsafe { SdifFCurrDataType(handle) };
         let data_type = DataType::from_raw(raw_dtype as u32);
+        let selected = frame.file().selected_cells(rows, cols);
 
         Matrix {
             frame,
@@ -97,29 +112,50 @@ impl<'a> Matrix<'a> {
             rows,
             cols,
             data_type,
+            selected,
             data_read: false,
             _phantom: PhantomData,
         }
     }
 
     /// Get the matrix type signature as a string (e.g., "1TRC").
+    ///
+    /// Signatures aliased with [`ReadOptions::alias()`](crate::ReadOptions::alias)
+    /// are reported as their alias.
     pub fn signature(&self) -> String {
-        signature_to_string(self.signature)
+        signature_to_string(self.canonical_signature_raw())
     }
 
-    /// Get the matrix type signature as a raw u32.
+    /// Get the matrix type signature as a raw u32, as stored in the file.
+    ///
+    /// Unlike [`signature()`](Self::signature), aliases are not applied.
     pub fn signature_raw(&self) -> Signature {
         self.signature
     }
 
+    /// Get the matrix type signature as a raw u32, with aliases applied.
+    pub fn canonical_signature_raw(&self) -> Signature {
+        self.frame.file().canonical_signature(self.signature)
+    }
+
     /// Get the number of rows in the matrix.
+    ///
+    /// Only selected rows are counted when the file was opened with a
+    /// [`Selection`](crate::selection::Selection).
     pub fn rows(&self) -> usize {
-        self.rows as usize
+        self.selected
+            .as_ref()
+            .map_or(self.rows as usize, |(rows, _)| rows.len())
     }
 
     /// Get the number of columns in the matrix.
+    ///
+    /// Only selected columns are counted when the file was opened with a
+    /// [`Selection`](crate::selection::Selection).
     pub fn cols(&self) -> usize {
-        self.cols as usize
+        self.selected
+            .as_ref()
+            .map_or(self.cols as usize, |(_, cols)| cols.len())
     }
 
     /// Get the total number of elements in the matrix.
@@ -137,6 +173,37 @@ impl<'a> Matrix<'a> {
         self.data_type
     }
 
+    /// Get the names of the columns declared for the matrix type.
+    ///
+    /// Names come from the file's type definitions, or from the
+    /// predefined types of the SDIF library for standard signatures. The
+    /// list is empty if the type isn't known, and may differ in length
+    /// from [`cols()`](Self::cols) if the matrix doesn't match its type.
+    ///
+    /// With a [`Selection`](crate::selection::Selection) of columns, only
+    /// the names of the selected columns are returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let file = SdifFile::open("input.sdif")?;
+    /// # let mut frame = file.frames().next().unwrap()?;
+    /// let matrix = frame.matrices().next().unwrap()?;
+    /// println!("{}", matrix.columns().join(", ")); // Index, Frequency, Amplitude, Phase
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn columns(&self) -> Vec<String> {
+        let columns = self.frame.file().matrix_columns(self.signature);
+        match &self.selected {
+            Some((_, cols)) => cols
+                .iter()
+                .filter_map(|&col| columns.get(col as usize).cloned())
+                .collect(),
+            None => columns,
+        }
+    }
+
     /// Get the matrix dimensions as a tuple (rows, cols).
     pub fn shape(&self) -> (usize, usize) {
         (self.rows(), self.cols())
@@ -163,49 +230,75 @@ impl<'a> Matrix<'a> {
     /// # let file = SdifFile::open("input.sdif")?;
     /// # let mut frame = file.frames().next().unwrap()?;
     /// # let matrix = frame.matrices().next().unwrap()?;
+    /// let cols = matrix.cols();
     /// let data = matrix.data_f64()?;
     ///
     /// // Access element at row 2, col 3
-    /// let cols = matrix.cols();
     /// let value = data[2 * cols + 3];
     /// # Ok::<(), sdif_rs::Error>(())
     /// ```
-    pub fn data_f64(mut self) -> Result<Vec<f64>> {
+    pub fn data_f64(self) -> Result<Vec<f64>> {
+        let mut data = Vec::new();
+        self.read_into(&mut data)?;
+        Ok(data)
+    }
+
+    /// Read matrix data as f64 values into an existing buffer.
+    ///
+    /// The buffer is cleared and filled with the values in row-major
+    /// order, like [`data_f64()`](Self::data_f64). Reusing one buffer
+    /// across matrices avoids an allocation per matrix when scanning many
+    /// frames.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if data was already read
+    /// - [`Error::DataTypeMismatch`] if the matrix is not `Float4` or `Float8`
+    /// - [`Error::ReadError`] if data couldn't be read
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// let file = SdifFile::open("input.sdif")?;
+    /// let mut buffer = Vec::new();
+    /// for frame in file.frames() {
+    ///     for matrix in frame?.matrices() {
+    ///         matrix?.read_into(&mut buffer)?;
+    ///         println!("{} values", buffer.len());
+    ///     }
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn read_into(mut self, buffer: &mut Vec<f64>) -> Result<()> {
         if self.data_read {
             return Err(Error::invalid_state("Matrix data already read"));
         }
+        self.check_float()?;
+        buffer.clear();
+        if self.selected.is_some() {
+            buffer.extend(decode(&self.read_bytes()?, self.data_type));
+            return Ok(());
+        }
         self.data_read = true;
 
-        let handle = self.frame.handle();
-        let total_elements = self.len();
-        let mut data = Vec::with_capacity(total_elements);
+        buffer.reserve(self.len());
 
-        // Read row by row
-        for _row in 0..self.rows {
-            let bytes_read = unsafe { SdifFReadOneRow(handle) };
-            if bytes_read <= 0 {
-                return Err(Error::read_error("Failed to read matrix row"));
-            }
+        let cols = self.cols();
 
-            // Get pointer to row data
-            let row_data = unsafe { SdifFCurrOneRowData(handle) };
-            if row_data.is_null() {
-                return Err(Error::null_pointer("Row data pointer"));
-            }
+        // Read row by row
+        for _row in 0..self.stored_rows() {
+            let row_data = self.read_row()?;
 
             // Copy data based on type
             match self.data_type {
                 DataType::Float8 => {
-                    let ptr = row_data as *const f64;
-                    for col in 0..self.cols as usize {
-                        data.push(unsafe { *ptr.add(col) });
-                    }
+                    let row = unsafe { std::slice::from_raw_parts(row_data as *const f64, cols) };
+                    buffer.extend_from_slice(row);
                 }
                 DataType::Float4 => {
-                    let ptr = row_data as *const f32;
-                    for col in 0..self.cols as usize {
-                        data.push(unsafe { *ptr.add(col) } as f64);
-                    }
+                    let row = unsafe { std::slice::from_raw_parts(row_data as *const f32, cols) };
+                    buffer.extend(row.iter().map(|&v| f64::from(v)));
                 }
                 _ => {
                     return Err(Error::type_mismatch("float", self.data_type.to_string()));
@@ -213,7 +306,97 @@ impl<'a> Matrix<'a> {
             }
         }
 
-        Ok(data)
+        self.read_padding();
+        Ok(())
+    }
+
+    /// Read matrix data as one vector of f64 values per column.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`data_f64()`](Self::data_f64).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let file = SdifFile::open("input.sdif")?;
+    /// # let mut frame = file.frames().next().unwrap()?;
+    /// # let matrix = frame.matrices().next().unwrap()?;
+    /// let columns = matrix.into_columns()?;
+    /// let frequencies = &columns[1];
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn into_columns(self) -> Result<Vec<Vec<f64>>> {
+        let (rows, cols) = self.shape();
+        let data = self.data_f64()?;
+        Ok(split_columns(&data, rows, cols))
+    }
+
+    /// Read matrix data as columns keyed by name, in column order.
+    ///
+    /// Names are the declared [`columns()`](Self::columns), or the
+    /// standard layout for the signature if the file declares none.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if not every column has a name
+    /// - Same as [`data_f64()`](Self::data_f64) otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let file = SdifFile::open("input.sdif")?;
+    /// # let mut frame = file.frames().next().unwrap()?;
+    /// # let matrix = frame.matrices().next().unwrap()?;
+    /// let columns = matrix.into_named_columns()?;
+    /// if let Some(frequencies) = columns.get("Frequency") {
+    ///     println!("{} partials", frequencies.len());
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn into_named_columns(self) -> Result<IndexMap<String, Vec<f64>>> {
+        let signature = self.signature();
+        let names = column_names(self.columns(), &signature, self.cols()).ok_or_else(|| {
+            Error::invalid_format(format!(
+                "Columns of matrix type '{}' are not named",
+                signature
+            ))
+        })?;
+        Ok(names.into_iter().zip(self.into_columns()?).collect())
+    }
+
+    /// Read the rows of a standard matrix type as structs.
+    ///
+    /// Columns are found by their declared names, see
+    /// [`rows`](crate::rows).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the matrix is not of type `T` or lacks
+    ///   a required column
+    /// - Same as [`data_f64()`](Self::data_f64) otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// use sdif_rs::rows::Fq0Row;
+    ///
+    /// # let file = SdifFile::open("input.sdif")?;
+    /// # let mut frame = file.frames().next().unwrap()?;
+    /// # let matrix = frame.matrices().next().unwrap()?;
+    /// for row in matrix.rows_as::<Fq0Row>()? {
+    ///     println!("{:.1} Hz ({:.2})", row.frequency, row.confidence);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn rows_as<T: MatrixRow>(self) -> Result<impl Iterator<Item = T>> {
+        let mut layout = RowLayout::<T>::new(&self.signature(), &self.columns(), self.cols())?;
+        let (rows, cols) = self.shape();
+        let data = self.data_f64()?;
+        Ok((0..rows).map(move |row| layout.read(&data[row * cols..(row + 1) * cols])))
     }
 
     /// Read matrix data as f32 values in row-major order.
@@ -224,35 +407,27 @@ impl<'a> Matrix<'a> {
         if self.data_read {
             return Err(Error::invalid_state("Matrix data already read"));
         }
+        self.check_float()?;
+        if self.selected.is_some() {
+            let data = decode(&self.read_bytes()?, self.data_type);
+            return Ok(data.into_iter().map(|v| v as f32).collect());
+        }
         self.data_read = true;
 
-        let handle = self.frame.handle();
-        let total_elements = self.len();
-        let mut data = Vec::with_capacity(total_elements);
-
-        for _row in 0..self.rows {
-            let bytes_read = unsafe { SdifFReadOneRow(handle) };
-            if bytes_read <= 0 {
-                return Err(Error::read_error("Failed to read matrix row"));
-            }
+        let cols = self.cols();
+        let mut data = Vec::with_capacity(self.len());
 
-            let row_data = unsafe { SdifFCurrOneRowData(handle) };
-            if row_data.is_null() {
-                return Err(Error::null_pointer("Row data pointer"));
-            }
+        for _row in 0..self.stored_rows() {
+            let row_data = self.read_row()?;
 
             match self.data_type {
                 DataType::Float4 => {
-                    let ptr = row_data as *const f32;
-                    for col in 0..self.cols as usize {
-                        data.push(unsafe { *ptr.add(col) });
-                    }
+                    let row = unsafe { std::slice::from_raw_parts(row_data as *const f32, cols) };
+                    data.extend_from_slice(row);
                 }
                 DataType::Float8 => {
-                    let ptr = row_data as *const f64;
-                    for col in 0..self.cols as usize {
-                        data.push(unsafe { *ptr.add(col) } as f32);
-                    }
+                    let row = unsafe { std::slice::from_raw_parts(row_data as *const f64, cols) };
+                    data.extend(row.iter().map(|&v| v as f32));
                 }
                 _ => {
                     return Err(Error::type_mismatch("float", self.data_type.to_string()));
@@ -260,9 +435,231 @@ impl<'a> Matrix<'a> {
             }
         }
 
+        self.read_padding();
         Ok(data)
     }
 
+    /// Read matrix data as values of type `T` in row-major order.
+    ///
+    /// Only data types that `T` holds without loss are read, see
+    /// [`MatrixValue`]; integer matrices can be read as `f64` too.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if data was already read
+    /// - [`Error::DataTypeMismatch`] if `T` can't hold every value of the
+    ///   matrix's data type
+    /// - [`Error::ReadError`] if data couldn't be read
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let file = SdifFile::open("input.sdif")?;
+    /// # let mut frame = file.frames().next().unwrap()?;
+    /// # let matrix = frame.matrices().next().unwrap()?;
+    /// let ids = matrix.data_as::<i64>()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn data_as<T: MatrixValue>(mut self) -> Result<Vec<T>> {
+        if !T::holds(self.data_type) {
+            return Err(Error::type_mismatch(
+                std::any::type_name::<T>(),
+                self.data_type.to_string(),
+            ));
+        }
+
+        let bytes = self.read_bytes()?;
+        Ok(decode(&bytes, self.data_type)
+            .into_iter()
+            .map(T::from_f64)
+            .collect())
+    }
+
+    /// Read integer matrix data as i32 values in row-major order.
+    ///
+    /// Same as [`data_as::<i32>()`](Self::data_as): `Int1`, `Int2`,
+    /// `Int4`, `UInt1` and `UInt2` matrices are read.
+    pub fn data_i32(self) -> Result<Vec<i32>> {
+        self.data_as()
+    }
+
+    /// Read unsigned integer matrix data as u32 values in row-major order.
+    ///
+    /// Same as [`data_as::<u32>()`](Self::data_as): `UInt1`, `UInt2` and
+    /// `UInt4` matrices are read.
+    pub fn data_u32(self) -> Result<Vec<u32>> {
+        self.data_as()
+    }
+
+    /// Read a text matrix as a string.
+    ///
+    /// The bytes of all rows are joined in row-major order, and trailing
+    /// NUL padding is removed.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if data was already read
+    /// - [`Error::DataTypeMismatch`] if the matrix is not `Text`
+    /// - [`Error::InvalidFormat`] if the text is not valid UTF-8
+    /// - [`Error::ReadError`] if data couldn't be read
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// let file = SdifFile::open("markers.sdif")?;
+    /// for frame in file.frames() {
+    ///     let mut frame = frame?;
+    ///     let time = frame.time();
+    ///     for matrix in frame.matrices() {
+    ///         let matrix = matrix?;
+    ///         if matrix.data_type() == sdif_rs::DataType::Text {
+    ///             println!("{:.3}s: {}", time, matrix.data_text()?);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn data_text(mut self) -> Result<String> {
+        if self.data_type != DataType::Text {
+            return Err(Error::type_mismatch("text", self.data_type.to_string()));
+        }
+
+        let mut bytes = self.read_bytes()?;
+        let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |last| last + 1);
+        bytes.truncate(len);
+        String::from_utf8(bytes)
+            .map_err(|e| Error::invalid_format(format!("Text matrix is not valid UTF-8: {}", e)))
+    }
+
+    /// Read the raw matrix data into an [`OwnedMatrix`].
+    ///
+    /// The values are copied as stored and decoded to f64 only when the
+    /// owned matrix is accessed. Unlike [`data_f64()`](Self::data_f64),
+    /// integer and text matrices are supported; the values of a text
+    /// matrix are its bytes.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if data was already read
+    /// - [`Error::DataTypeMismatch`] if the data type is unknown
+    /// - [`Error::ReadError`] if data couldn't be read
+    pub fn into_owned(mut self) -> Result<OwnedMatrix> {
+        if self.data_read {
+            return Err(Error::invalid_state("Matrix data already read"));
+        }
+        if self.data_type == DataType::Unknown {
+            return Err(Error::type_mismatch("numeric or text", self.data_type.to_string()));
+        }
+
+        let bytes = self.read_bytes()?;
+        let mut owned = OwnedMatrix::from_bytes(
+            self.signature(),
+            self.rows(),
+            self.cols(),
+            self.data_type,
+            bytes,
+        )?;
+        owned.columns = self.columns();
+        Ok(owned)
+    }
+
+    /// Read the data as stored, in native byte order.
+    ///
+    /// Rows and columns left out by the selection are dropped.
+    fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        if self.data_read {
+            return Err(Error::invalid_state("Matrix data already read"));
+        }
+        self.data_read = true;
+
+        let size = self.element_size();
+        let row_bytes = self.cols as usize * size;
+        let mut bytes = Vec::with_capacity(self.len() * size);
+
+        for row in 0..self.stored_rows() {
+            let row_data = self.read_row()?;
+            let data = unsafe { std::slice::from_raw_parts(row_data as *const u8, row_bytes) };
+            match &self.selected {
+                None => bytes.extend_from_slice(data),
+                Some((rows, cols)) => {
+                    if rows.binary_search(&row).is_ok() {
+                        for &col in cols {
+                            let start = col as usize * size;
+                            bytes.extend_from_slice(&data[start..start + size]);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.read_padding();
+        Ok(bytes)
+    }
+
+    /// Get the size in bytes of one element, one byte for text.
+    fn element_size(&self) -> usize {
+        match self.data_type {
+            DataType::Text => 1,
+            data_type => data_type.size_bytes(),
+        }
+    }
+
+    /// Get the number of elements stored in the file, selected or not.
+    fn stored_len(&self) -> usize {
+        self.rows as usize * self.cols as usize
+    }
+
+    /// Get the number of rows stored in the file.
+    ///
+    /// A matrix without columns has no data to read, however many rows its
+    /// header declares.
+    fn stored_rows(&self) -> u32 {
+        if self.cols == 0 {
+            0
+        } else {
+            self.rows
+        }
+    }
+
+    /// Read the next row and get a pointer to its data.
+    fn read_row(&self) -> Result<*mut c_void> {
+        let handle = self.frame.handle();
+        let bytes_read = unsafe { SdifFReadOneRow(handle) };
+        if bytes_read == 0 {
+            return Err(self.frame.read_error("Failed to read matrix row"));
+        }
+
+        let row_data = unsafe { SdifFCurrOneRowData(handle) };
+        if row_data.is_null() {
+            return Err(Error::null_pointer("Row data pointer"));
+        }
+        Ok(row_data)
+    }
+
+    /// Check that the matrix holds floating-point data.
+    ///
+    /// Done before reading so that an unsupported matrix is still skipped
+    /// cleanly when dropped.
+    fn check_float(&self) -> Result<()> {
+        match self.data_type {
+            DataType::Float4 | DataType::Float8 => Ok(()),
+            other => Err(Error::type_mismatch("float", other.to_string())),
+        }
+    }
+
+    /// Consume the padding that aligns matrix data to 8 bytes.
+    ///
+    /// Must be called after all rows have been read.
+    fn read_padding(&self) {
+        let data_bytes = self.stored_len() * self.element_size();
+        unsafe {
+            let padding = SdifPaddingCalculate(data_bytes as _);
+            SdifFReadPadding(self.frame.handle(), padding);
+        }
+    }
+
     /// Read matrix data as an ndarray Array2<f64>.
     ///
     /// Requires the `ndarray` feature.
@@ -319,9 +716,10 @@ impl<'a> Matrix<'a> {
         }
         self.data_read = true;
 
+        let data_bytes = self.stored_len() * self.element_size();
         let result = unsafe { SdifFSkipMatrixData(self.frame.handle()) };
-        if result < 0 {
-            Err(Error::read_error("Failed to skip matrix data"))
+        if result == 0 && data_bytes > 0 {
+            Err(self.frame.read_error("Failed to skip matrix data"))
         } else {
             Ok(())
         }
@@ -357,22 +755,53 @@ impl<'f, 'a: 'f> Iterator for MatrixIterator<'f, 'a> {
     type Item = Result<Matrix<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.read_matrix()? {
+                // Matrices outside the selection are skipped when dropped
+                Ok(_) if !self.frame.file().matrix_is_selected() => continue,
+                matrix => return Some(matrix),
+            }
+        }
+    }
+}
+
+impl<'f, 'a: 'f> MatrixIterator<'f, 'a> {
+    /// Read the next matrix header, whether or not the matrix is selected.
+    fn read_matrix(&mut self) -> Option<Result<Matrix<'a>>> {
         if !self.frame.has_more_matrices() {
             self.frame.mark_finished();
             return None;
         }
 
         let handle = self.frame.handle();
+        let file = self.frame.file();
 
-        // Read matrix header
-        let bytes_read = unsafe { SdifFReadMatrixHeader(handle) };
+        // Read matrix header. In lenient mode, undeclared types are handled
+        // below, so the library's own complaints are silenced.
+        let bytes_read = unsafe {
+            if file.is_lenient() {
+                SdifDisableErrorOutput();
+            }
+            let bytes_read = SdifFReadMatrixHeader(handle);
+            if file.is_lenient() {
+                SdifEnableErrorOutput();
+            }
+            bytes_read
+        };
 
-        if bytes_read <= 0 {
-            return Some(Err(Error::read_error("Failed to read matrix header")));
+        if bytes_read == 0 {
+            return Some(Err(self.frame.read_error("Failed to read matrix header")));
         }
 
         self.frame.advance_matrix();
 
+        let signature = unsafe { SdifFCurrMatrixSignature(handle) };
+        if let Err(e) = file.resolve_matrix_type(signature) {
+            // Keep the file position consistent before reporting
+            unsafe { SdifFSkipMatrixData(handle) };
+            return Some(Err(e));
+        }
+
         // SAFETY: We need to create a Matrix with the same lifetime as the frame.
         // This is safe because the iterator borrows frame mutably, preventing
         // other access, and Matrix will be dropped before the next iteration.
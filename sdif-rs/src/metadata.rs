@@ -0,0 +1,263 @@
+//! Conventional metadata keys recorded in NVT entries.
+//!
+//! Most SDIF tools describe a file with a handful of well-known NVT keys:
+//!
+//! ```text
+//! SampleRate    sample rate of the analysed sound, in Hz
+//! Creator       name of the program that wrote the file
+//! Date          when the file was written
+//! Soundfile     path of the analysed sound
+//! ```
+//!
+//! Files written by this crate also get a [`Writer`](WRITER) entry naming
+//! the crate and its version, such as `sdif-rs 0.5.0`, which
+//! [`SdifFile::written_by_sdif_rs()`](crate::SdifFile::written_by_sdif_rs)
+//! reads back. It can be left out with
+//! [`omit_writer()`](crate::SdifFileBuilder::omit_writer).
+//!
+//! [`SdifFile`](crate::SdifFile) reads them with typed accessors such as
+//! [`sample_rate()`](crate::SdifFile::sample_rate), and
+//! [`SdifFileBuilder`](crate::SdifFileBuilder) writes them with setters of
+//! the same names. All setters share one NVT table, so setting a key twice
+//! replaces its value.
+//!
+//! With the `chrono` feature, dates can also be read and written as
+//! [`chrono::DateTime`] values, and the builder can stamp the time a file
+//! is written with
+//! [`stamp_date()`](crate::SdifFileBuilder::stamp_date).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("partials.sdif")?;
+//! if let Some(rate) = file.sample_rate() {
+//!     println!("Analysed at {} Hz by {}", rate, file.creator().unwrap_or("unknown"));
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{Error, Result};
+
+/// NVT key of the sample rate.
+pub const SAMPLE_RATE: &str = "SampleRate";
+
+/// NVT key of the program that wrote the file.
+pub const CREATOR: &str = "Creator";
+
+/// NVT key of the date the file was written.
+pub const DATE: &str = "Date";
+
+/// NVT key of the analysed sound file.
+pub const SOUNDFILE: &str = "Soundfile";
+
+/// NVT key of the library that wrote the file, with its version.
+pub const WRITER: &str = "Writer";
+
+/// Name this crate records under [`WRITER`].
+const WRITER_NAME: &str = "sdif-rs";
+
+/// Formats accepted for dates without a UTC offset, tried in order.
+#[cfg(feature = "chrono")]
+const DATE_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// A library version, `major.minor.patch`.
+///
+/// Versions compare field by field, so `0.10.0` is newer than `0.9.3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    /// Major version.
+    pub major: u64,
+
+    /// Minor version.
+    pub minor: u64,
+
+    /// Patch version.
+    pub patch: u64,
+}
+
+impl Version {
+    /// Get the version of this crate.
+    pub fn current() -> Self {
+        Self::parse(env!("CARGO_PKG_VERSION")).expect("Crate version is major.minor.patch")
+    }
+
+    /// Parse `major.minor.patch`, ignoring any pre-release or build suffix.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let core = text.trim().split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Format the [`WRITER`] entry of this crate.
+pub(crate) fn format_writer() -> String {
+    format!("{} {}", WRITER_NAME, env!("CARGO_PKG_VERSION"))
+}
+
+/// Parse a [`WRITER`] entry, returning the version if it names this crate.
+pub(crate) fn parse_writer(value: &str) -> Option<Version> {
+    let (name, version) = value.trim().split_once(' ')?;
+    if name != WRITER_NAME {
+        return None;
+    }
+    Version::parse(version)
+}
+
+/// Find the value of `key` in the first NVT that has it.
+pub(crate) fn lookup<'a>(nvts: &'a [HashMap<String, String>], key: &str) -> Option<&'a str> {
+    nvts.iter()
+        .find_map(|nvt| nvt.get(key))
+        .map(|value| value.trim())
+}
+
+/// Parse a sample rate, ignoring an optional `Hz` suffix.
+///
+/// Returns `None` unless the rate is a positive, finite number.
+pub(crate) fn parse_sample_rate(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let number = value
+        .strip_suffix("Hz")
+        .or_else(|| value.strip_suffix("hz"))
+        .unwrap_or(value);
+
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|rate| *rate > 0.0 && rate.is_finite())
+}
+
+/// Format a sample rate for storing in an NVT.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the rate is not positive and finite
+pub(crate) fn format_sample_rate(rate: f64) -> Result<String> {
+    if !(rate > 0.0 && rate.is_finite()) {
+        return Err(Error::invalid_format(format!(
+            "Sample rate must be positive, got {}",
+            rate
+        )));
+    }
+    Ok(rate.to_string())
+}
+
+/// Parse a date in RFC 3339 or a common `YYYY-MM-DD[ HH:MM:SS]` layout.
+///
+/// Dates without a UTC offset are taken to be in UTC. A date without a
+/// time is read as midnight.
+#[cfg(feature = "chrono")]
+pub(crate) fn parse_date(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .map(|date| date.and_utc())
+}
+
+/// Format a date for storing in an NVT, as ISO 8601 in UTC.
+#[cfg(feature = "chrono")]
+pub(crate) fn format_date(date: chrono::DateTime<chrono::Utc>) -> String {
+    date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut first = HashMap::new();
+        first.insert("stream.f0".to_string(), "1".to_string());
+        let mut second = HashMap::new();
+        second.insert(CREATOR.to_string(), " AudioSculpt ".to_string());
+        let nvts = vec![first, second];
+
+        assert_eq!(lookup(&nvts, CREATOR), Some("AudioSculpt"));
+        assert_eq!(lookup(&nvts, DATE), None);
+    }
+
+    #[test]
+    fn test_parse_sample_rate() {
+        assert_eq!(parse_sample_rate("44100"), Some(44100.0));
+        assert_eq!(parse_sample_rate(" 48000.0 "), Some(48000.0));
+        assert_eq!(parse_sample_rate("22050 Hz"), Some(22050.0));
+        assert_eq!(parse_sample_rate("0"), None);
+        assert_eq!(parse_sample_rate("fast"), None);
+        assert_eq!(parse_sample_rate("inf"), None);
+    }
+
+    #[test]
+    fn test_format_sample_rate() {
+        assert_eq!(format_sample_rate(44100.0).unwrap(), "44100");
+        assert_eq!(format_sample_rate(22050.5).unwrap(), "22050.5");
+        assert!(format_sample_rate(-1.0).is_err());
+        assert!(format_sample_rate(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_version() {
+        let version = Version::parse("0.10.2").unwrap();
+        assert_eq!(version.to_string(), "0.10.2");
+        assert!(version > Version::parse("0.9.7").unwrap());
+        assert_eq!(Version::parse("1.2.3-beta.1+build"), Version::parse("1.2.3"));
+        assert_eq!(Version::parse("1.2"), None);
+        assert_eq!(Version::parse("1.2.3.4"), None);
+        assert_eq!(Version::parse("one.2.3"), None);
+    }
+
+    #[test]
+    fn test_writer_entry() {
+        assert_eq!(parse_writer(&format_writer()), Some(Version::current()));
+        assert_eq!(parse_writer("sdif-rs 0.4.1"), Version::parse("0.4.1"));
+        assert_eq!(parse_writer("AudioSculpt 3.0.0"), None);
+        assert_eq!(parse_writer("sdif-rs"), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_parse_date() {
+        use chrono::{TimeZone, Utc};
+
+        let noon = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(parse_date("2024-03-01T12:00:00"), Some(noon));
+        assert_eq!(parse_date("2024-03-01 12:00:00"), Some(noon));
+        assert_eq!(parse_date("2024-03-01T14:00:00+02:00"), Some(noon));
+        assert_eq!(
+            parse_date("2024-03-01"),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).single()
+        );
+        assert_eq!(parse_date("March 1st"), None);
+
+        assert_eq!(format_date(noon), "2024-03-01T12:00:00Z");
+        assert_eq!(parse_date(&format_date(noon)), Some(noon));
+    }
+}
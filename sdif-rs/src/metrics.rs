@@ -0,0 +1,127 @@
+//! Throughput metrics for services embedding the crate.
+//!
+//! With the `metrics` feature, reading and writing frames and converting
+//! files report to the [`metrics`](https://docs.rs/metrics) facade. Nothing
+//! is recorded until the application installs a recorder, such as
+//! `metrics-exporter-prometheus`. Without the feature the calls compile to
+//! nothing.
+//!
+//! | Name | Kind | Labels |
+//! |------|------|--------|
+//! | [`FRAMES_READ`] | counter | `signature` |
+//! | [`BYTES_READ`] | counter | `signature` |
+//! | [`FRAMES_WRITTEN`] | counter | `signature` |
+//! | [`BYTES_WRITTEN`] | counter | `signature` |
+//! | [`CONVERSION_DURATION`] | histogram | `conversion` |
+//!
+//! Frames read are counted as the frame iterators return them, so frames
+//! skipped by a filter or seek are not. Bytes read are the frame sizes
+//! from the frame headers, and frames without a size add nothing.
+//!
+//! The `conversion` label of [`CONVERSION_DURATION`] is one of the
+//! `CONVERSION_*` constants. Only conversions that succeed are recorded.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // In the application, with metrics-exporter-prometheus
+//! metrics_exporter_prometheus::PrometheusBuilder::new().install()?;
+//!
+//! sdif_rs::copy::copy_file("in.sdif", "out.sdif", &Default::default())?;
+//! // sdif_frames_read_total{signature="1TRC"} and friends are now exported
+//! ```
+
+use crate::signature::Signature;
+#[cfg(feature = "metrics")]
+use crate::signature::signature_to_string;
+
+/// Counter of frames read, labeled by frame signature.
+pub const FRAMES_READ: &str = "sdif_frames_read_total";
+
+/// Counter of bytes of frames read, labeled by frame signature.
+pub const BYTES_READ: &str = "sdif_bytes_read_total";
+
+/// Counter of frames written, labeled by frame signature.
+pub const FRAMES_WRITTEN: &str = "sdif_frames_written_total";
+
+/// Counter of bytes of frames written, labeled by frame signature.
+pub const BYTES_WRITTEN: &str = "sdif_bytes_written_total";
+
+/// Histogram of conversion durations in seconds, labeled by conversion.
+pub const CONVERSION_DURATION: &str = "sdif_conversion_duration_seconds";
+
+/// [`copy::copy_file()`](crate::copy::copy_file)
+pub const CONVERSION_COPY: &str = "copy";
+
+/// [`split::split_file()`](crate::split::split_file)
+pub const CONVERSION_SPLIT: &str = "split";
+
+/// [`repair::repair_file()`](crate::repair::repair_file)
+pub const CONVERSION_REPAIR: &str = "repair";
+
+/// [`csv::export()`](crate::csv::export)
+pub const CONVERSION_CSV_EXPORT: &str = "csv_export";
+
+/// [`csv::import()`](crate::csv::import)
+pub const CONVERSION_CSV_IMPORT: &str = "csv_import";
+
+/// [`sonic_visualiser::import()`](crate::sonic_visualiser::import)
+pub const CONVERSION_SONIC_VISUALISER_IMPORT: &str = "sonic_visualiser_import";
+
+/// [`praat::import()`](crate::praat::import)
+pub const CONVERSION_PRAAT_IMPORT: &str = "praat_import";
+
+/// Record a frame returned by a frame iterator.
+#[cfg(feature = "metrics")]
+pub(crate) fn frame_read(signature: Signature, byte_size: Option<u64>) {
+    let signature = signature_to_string(signature);
+    ::metrics::counter!(FRAMES_READ, "signature" => signature.clone()).increment(1);
+    if let Some(bytes) = byte_size {
+        ::metrics::counter!(BYTES_READ, "signature" => signature).increment(bytes);
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn frame_read(_signature: Signature, _byte_size: Option<u64>) {}
+
+/// Record a frame written, taking `bytes` in the file.
+#[cfg(feature = "metrics")]
+pub(crate) fn frame_written(signature: Signature, bytes: u64) {
+    let signature = signature_to_string(signature);
+    ::metrics::counter!(FRAMES_WRITTEN, "signature" => signature.clone()).increment(1);
+    ::metrics::counter!(BYTES_WRITTEN, "signature" => signature).increment(bytes);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn frame_written(_signature: Signature, _bytes: u64) {}
+
+/// Measures one conversion, recorded by [`ConversionTimer::finish()`].
+pub(crate) struct ConversionTimer {
+    #[cfg(feature = "metrics")]
+    conversion: &'static str,
+    #[cfg(feature = "metrics")]
+    start: std::time::Instant,
+}
+
+impl ConversionTimer {
+    /// Start timing a conversion, one of the `CONVERSION_*` constants.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn start(conversion: &'static str) -> Self {
+        ConversionTimer {
+            conversion,
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    pub(crate) fn start(_conversion: &'static str) -> Self {
+        ConversionTimer {}
+    }
+
+    /// Record the time since the conversion started.
+    pub(crate) fn finish(self) {
+        #[cfg(feature = "metrics")]
+        ::metrics::histogram!(CONVERSION_DURATION, "conversion" => self.conversion)
+            .record(self.start.elapsed().as_secs_f64());
+    }
+}
@@ -0,0 +1,393 @@
+//! Fundamental frequency estimation from audio.
+//!
+//! [`wav_to_sdif()`] reads a WAV file, estimates its pitch with the YIN
+//! algorithm (de Cheveigné & Kawahara, 2002), and writes the estimates as a
+//! 1FQ0 stream, so pitch SDIF files can be produced without external
+//! analysis tools. [`yin()`] runs the estimator on samples already in
+//! memory.
+//!
+//! Each frame gets one estimate. Its confidence is `1 - d'`, where `d'` is
+//! the cumulative mean normalized difference at the chosen period; frames
+//! where no period dips below the threshold are unvoiced and get frequency
+//! and confidence 0.
+//!
+//! Requires the `pitch` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::pitch::{self, PitchOptions};
+//!
+//! let options = PitchOptions::new().frequency_range(80.0, 800.0);
+//! let frames = pitch::wav_to_sdif("voice.wav", "voice.f0.sdif", &options)?;
+//! println!("Wrote {} pitch frames", frames);
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::schemas;
+use crate::source::read_mono;
+
+/// Frame/matrix signature of pitch estimates.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Name of the tool recorded in the output's history.
+const TOOL: &str = "sdif-rs-yin";
+
+/// Settings of the pitch estimator.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PitchOptions {
+    /// Time between estimates, in seconds.
+    pub hop: f64,
+
+    /// Length of the integration window, in seconds.
+    pub window: f64,
+
+    /// Lowest frequency to detect, in Hz.
+    pub min_frequency: f64,
+
+    /// Highest frequency to detect, in Hz.
+    pub max_frequency: f64,
+
+    /// Largest normalized difference accepted as a period (YIN's absolute
+    /// threshold). Lower values give fewer, more reliable estimates.
+    pub threshold: f64,
+}
+
+impl Default for PitchOptions {
+    fn default() -> Self {
+        PitchOptions {
+            hop: 0.01,
+            window: 0.025,
+            min_frequency: 50.0,
+            max_frequency: 1000.0,
+            threshold: 0.1,
+        }
+    }
+}
+
+impl PitchOptions {
+    /// Create the default options: 10 ms hop, 25 ms window, 50-1000 Hz,
+    /// threshold 0.1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time between estimates, in seconds.
+    pub fn hop(mut self, hop: f64) -> Self {
+        self.hop = hop;
+        self
+    }
+
+    /// Set the length of the integration window, in seconds.
+    pub fn window(mut self, window: f64) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set the range of frequencies to detect, in Hz.
+    pub fn frequency_range(mut self, min: f64, max: f64) -> Self {
+        self.min_frequency = min;
+        self.max_frequency = max;
+        self
+    }
+
+    /// Set the absolute threshold on the normalized difference.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Check the options against a sample rate.
+    fn validate(&self, sample_rate: f64) -> Result<()> {
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+
+        if !positive(sample_rate) {
+            return Err(Error::invalid_format(format!(
+                "Sample rate must be positive, got {}",
+                sample_rate
+            )));
+        }
+        if !positive(self.hop) || !positive(self.window) {
+            return Err(Error::invalid_format(format!(
+                "Hop and window must be positive, got {} and {}",
+                self.hop, self.window
+            )));
+        }
+        if !positive(self.min_frequency) || self.max_frequency <= self.min_frequency {
+            return Err(Error::invalid_format(format!(
+                "Invalid frequency range {}-{} Hz",
+                self.min_frequency, self.max_frequency
+            )));
+        }
+        if self.max_frequency >= sample_rate / 2.0 {
+            return Err(Error::invalid_format(format!(
+                "Highest frequency {} Hz is above the Nyquist frequency",
+                self.max_frequency
+            )));
+        }
+        if !(self.threshold > 0.0 && self.threshold < 1.0) {
+            return Err(Error::invalid_format(format!(
+                "Threshold must be between 0 and 1, got {}",
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// One pitch estimate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PitchEstimate {
+    /// Time of the centre of the analysis window, in seconds.
+    pub time: f64,
+
+    /// Fundamental frequency in Hz, or 0 if the frame is unvoiced.
+    pub frequency: f64,
+
+    /// Confidence between 0 and 1, or 0 if the frame is unvoiced.
+    pub confidence: f64,
+}
+
+impl PitchEstimate {
+    /// Check whether a pitch was found.
+    pub fn is_voiced(&self) -> bool {
+        self.frequency > 0.0
+    }
+}
+
+/// Estimate the pitch of mono samples with YIN.
+///
+/// Estimates are made every `options.hop` seconds for as long as a full
+/// window plus the longest period fits in the samples.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the options are invalid for `sample_rate`
+pub fn yin(samples: &[f32], sample_rate: f64, options: &PitchOptions) -> Result<Vec<PitchEstimate>> {
+    options.validate(sample_rate)?;
+
+    let window = (options.window * sample_rate).round().max(1.0) as usize;
+    let hop = (options.hop * sample_rate).round().max(1.0) as usize;
+    let min_lag = ((sample_rate / options.max_frequency).floor() as usize).max(2);
+    let max_lag = (sample_rate / options.min_frequency).ceil() as usize;
+
+    // Each frame holds the window plus one sample beyond the longest lag,
+    // for interpolating around it
+    let span = window + max_lag + 1;
+    let mut estimates = Vec::new();
+    let mut difference = vec![0.0; max_lag + 2];
+    let mut start = 0;
+
+    while start + span <= samples.len() {
+        let frame = &samples[start..start + span];
+        normalized_difference(frame, window, &mut difference);
+
+        let time = (start as f64 + window as f64 / 2.0) / sample_rate;
+        let estimate = match pick_period(&difference, min_lag, max_lag, options.threshold) {
+            Some(period) => PitchEstimate {
+                time,
+                frequency: sample_rate / period,
+                confidence: (1.0 - value_at(&difference, period)).clamp(0.0, 1.0),
+            },
+            None => PitchEstimate {
+                time,
+                frequency: 0.0,
+                confidence: 0.0,
+            },
+        };
+        estimates.push(estimate);
+        start += hop;
+    }
+
+    Ok(estimates)
+}
+
+/// Estimate the pitch of a WAV file.
+///
+/// Multichannel files are mixed down to mono first.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the file is not a valid WAV file or the
+///   options are invalid for its sample rate
+/// - [`Error::Io`] if the file can't be read
+pub fn track_wav(path: impl AsRef<Path>, options: &PitchOptions) -> Result<Vec<PitchEstimate>> {
+    let (samples, sample_rate) = read_mono(path.as_ref())?;
+    yin(&samples, sample_rate, options)
+}
+
+/// Estimate the pitch of a WAV file and write it as a 1FQ0 SDIF file.
+///
+/// Every estimate becomes a frame with a 1x2 matrix of frequency and
+/// confidence, unvoiced frames included. The output records the sample
+/// rate, the source audio (see [`crate::source`]) and the analysis
+/// settings in its history.
+///
+/// Returns the number of frames written.
+///
+/// # Errors
+///
+/// - Any error from [`track_wav()`]
+/// - Any error from creating or writing the output
+pub fn wav_to_sdif(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &PitchOptions,
+) -> Result<usize> {
+    let input = input.as_ref();
+    let (samples, sample_rate) = read_mono(input)?;
+    let estimates = yin(&samples, sample_rate, options)?;
+
+    let params = [
+        ("hop", options.hop.to_string()),
+        ("window", options.window.to_string()),
+        ("min_frequency", options.min_frequency.to_string()),
+        ("max_frequency", options.max_frequency.to_string()),
+        ("threshold", options.threshold.to_string()),
+    ];
+    let mut writer = SdifFile::builder()
+        .create(output)?
+        .sample_rate(sample_rate)?
+        .set_source_audio(input)?
+        .append_history(
+            TOOL,
+            env!("CARGO_PKG_VERSION"),
+            params.iter().map(|(name, value)| (*name, value.as_str())),
+        )?
+        .add_matrix_type(PITCH_SIGNATURE, schemas::FQ0_COLUMNS)?
+        .add_frame_type(PITCH_SIGNATURE, &["1FQ0 FundamentalFrequencyEstimate"])?
+        .build()?;
+
+    for estimate in &estimates {
+        writer.write_frame_one_matrix(
+            PITCH_SIGNATURE,
+            estimate.time,
+            PITCH_SIGNATURE,
+            1,
+            2,
+            &[estimate.frequency, estimate.confidence],
+        )?;
+    }
+    writer.close()?;
+
+    Ok(estimates.len())
+}
+
+/// Compute YIN's cumulative mean normalized difference function.
+///
+/// `difference[lag]` is filled for lags 0 to `difference.len() - 1`; the
+/// frame must hold `window` samples plus the largest lag.
+fn normalized_difference(frame: &[f32], window: usize, difference: &mut [f64]) {
+    difference[0] = 1.0;
+    let mut running_sum = 0.0;
+
+    for lag in 1..difference.len() {
+        let d: f64 = frame[..window]
+            .iter()
+            .zip(&frame[lag..lag + window])
+            .map(|(&a, &b)| {
+                let delta = f64::from(a) - f64::from(b);
+                delta * delta
+            })
+            .sum();
+
+        running_sum += d;
+        difference[lag] = if running_sum > 0.0 {
+            d * lag as f64 / running_sum
+        } else {
+            1.0
+        };
+    }
+}
+
+/// Pick the period from the normalized difference, in samples.
+///
+/// Takes the first lag below `threshold`, follows it down to its local
+/// minimum, and refines it with parabolic interpolation. Returns `None`
+/// if no lag in range dips below the threshold.
+fn pick_period(difference: &[f64], min_lag: usize, max_lag: usize, threshold: f64) -> Option<f64> {
+    let mut lag = (min_lag..=max_lag).find(|&lag| difference[lag] < threshold)?;
+    while lag < max_lag && difference[lag + 1] < difference[lag] {
+        lag += 1;
+    }
+
+    let (left, centre, right) = (difference[lag - 1], difference[lag], difference[lag + 1]);
+    let curvature = left - 2.0 * centre + right;
+    let offset = if curvature > 0.0 {
+        (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+
+    Some(lag as f64 + offset)
+}
+
+/// Interpolate the normalized difference at a fractional lag.
+fn value_at(difference: &[f64], lag: f64) -> f64 {
+    let index = lag.floor() as usize;
+    let fraction = lag - index as f64;
+    match difference.get(index + 1) {
+        Some(&next) => difference[index] * (1.0 - fraction) + next * fraction,
+        None => difference[index],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f64, sample_rate: f64, seconds: f64) -> Vec<f32> {
+        let n = (sample_rate * seconds) as usize;
+        (0..n)
+            .map(|i| (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn test_yin_sine() {
+        let samples = sine(220.0, 16000.0, 0.5);
+        let estimates = yin(&samples, 16000.0, &PitchOptions::new()).unwrap();
+
+        assert!(!estimates.is_empty());
+        for estimate in &estimates {
+            assert!(estimate.is_voiced());
+            assert!((estimate.frequency - 220.0).abs() < 0.5, "{:?}", estimate);
+            assert!(estimate.confidence > 0.9);
+        }
+        assert!((estimates[1].time - estimates[0].time - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_yin_silence_is_unvoiced() {
+        let samples = vec![0.0; 8000];
+        let estimates = yin(&samples, 16000.0, &PitchOptions::new()).unwrap();
+
+        assert!(!estimates.is_empty());
+        assert!(estimates.iter().all(|e| !e.is_voiced() && e.confidence == 0.0));
+    }
+
+    #[test]
+    fn test_yin_short_input() {
+        let estimates = yin(&[0.0; 10], 16000.0, &PitchOptions::new()).unwrap();
+        assert!(estimates.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(PitchOptions::new().validate(44100.0).is_ok());
+        assert!(PitchOptions::new().validate(0.0).is_err());
+        assert!(PitchOptions::new().hop(0.0).validate(44100.0).is_err());
+        assert!(PitchOptions::new()
+            .frequency_range(500.0, 100.0)
+            .validate(44100.0)
+            .is_err());
+        assert!(PitchOptions::new().validate(1500.0).is_err());
+        assert!(PitchOptions::new().threshold(1.5).validate(44100.0).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Real-time playback of SDIF frames.
+//!
+//! A [`Player`] hands out the frames of a file as their time comes,
+//! measured by a [`Clock`]. This is the timing loop needed to drive
+//! real-time resynthesis or stream frames over OSC:
+//!
+//! - Iterating a player blocks until each frame is due, so a plain `for`
+//!   loop plays the file at its own pace.
+//! - [`Player::poll()`] never blocks and only returns frames that are
+//!   already due, for callers that run their own loop, such as an audio
+//!   callback.
+//!
+//! By default the player follows the wall clock, starting when the first
+//! frame is requested. Any closure returning the current playback
+//! position in seconds can be used as the clock instead, e.g. to follow
+//! the position of an audio player.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::player::Player;
+//! use sdif_rs::{FrameFilter, SdifFile};
+//!
+//! let file = SdifFile::open("voice.sdif")?;
+//! let filter = FrameFilter::new().signature("1FQ0");
+//!
+//! // Play from 2 seconds on, in real time
+//! for frame in Player::with_filter(&file, filter, 2.0)? {
+//!     let mut frame = frame?;
+//!     let matrix = frame.matrices().next().expect("1FQ0 matrix")?;
+//!     println!("{:.3}s: {:?}", frame.time(), matrix.data_f64()?);
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::file::SdifFile;
+use crate::frame::{FilteredFrames, Frame, FrameFilter};
+
+/// Interval at which [`Clock::wait_until()`] checks the clock by default.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Source of the playback position for a [`Player`].
+///
+/// Closures returning the position in seconds implement this trait.
+pub trait Clock {
+    /// Get the current playback position in seconds.
+    fn now(&mut self) -> f64;
+
+    /// Block until the playback position reaches `time`.
+    ///
+    /// The default implementation checks [`now()`](Self::now) every
+    /// millisecond.
+    fn wait_until(&mut self, time: f64) {
+        while self.now() < time {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl<F: FnMut() -> f64> Clock for F {
+    fn now(&mut self) -> f64 {
+        self()
+    }
+}
+
+/// A [`Clock`] following the system's monotonic clock.
+///
+/// The clock starts the first time it is read, at the position given to
+/// [`new()`](Self::new).
+#[derive(Debug, Clone)]
+pub struct WallClock {
+    /// Playback position when the clock starts.
+    start: f64,
+
+    /// Playback seconds per wall-clock second.
+    speed: f64,
+
+    /// When the clock started, once it has been read.
+    origin: Option<Instant>,
+}
+
+impl WallClock {
+    /// Create a clock that starts at position `start`.
+    pub fn new(start: f64) -> Self {
+        WallClock {
+            start,
+            speed: 1.0,
+            origin: None,
+        }
+    }
+
+    /// Play `speed` times faster than real time (e.g. 0.5 for half speed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not positive and finite.
+    pub fn speed(mut self, speed: f64) -> Self {
+        assert!(
+            speed > 0.0 && speed.is_finite(),
+            "Playback speed must be positive and finite, got {}",
+            speed
+        );
+        self.speed = speed;
+        self
+    }
+}
+
+impl Clock for WallClock {
+    fn now(&mut self) -> f64 {
+        let origin = *self.origin.get_or_insert_with(Instant::now);
+        self.start + origin.elapsed().as_secs_f64() * self.speed
+    }
+
+    fn wait_until(&mut self, time: f64) {
+        let ahead = (time - self.now()) / self.speed;
+        if ahead > 0.0 {
+            // Times too far ahead to represent are waited for forever
+            thread::sleep(Duration::try_from_secs_f64(ahead).unwrap_or(Duration::MAX));
+        }
+    }
+}
+
+/// Frames of an SDIF file handed out as their time comes.
+///
+/// Frames are never dropped: if the caller falls behind, late frames are
+/// returned immediately, in order. See the [module documentation](self).
+pub struct Player<'a, C = WallClock> {
+    /// Next frame, read ahead but not played yet. Declared first so it is
+    /// dropped before the iterator.
+    pending: Option<Result<Frame<'a>>>,
+
+    /// Frames still to play.
+    frames: FilteredFrames<'a>,
+
+    /// Source of the playback position.
+    clock: C,
+}
+
+impl<'a> Player<'a> {
+    /// Create a player for every frame from `start` seconds on, following
+    /// the wall clock.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SdifFile::frames_from()`].
+    ///
+    /// # Panics
+    ///
+    /// Same as [`SdifFile::frames()`].
+    pub fn new(file: &'a SdifFile, start: f64) -> Result<Self> {
+        Self::with_filter(file, FrameFilter::new(), start)
+    }
+
+    /// Create a player for the frames that pass `filter` from `start`
+    /// seconds on, following the wall clock.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SdifFile::frames_from()`].
+    ///
+    /// # Panics
+    ///
+    /// Same as [`SdifFile::frames()`].
+    pub fn with_filter(file: &'a SdifFile, filter: FrameFilter, start: f64) -> Result<Self> {
+        Ok(Player {
+            pending: None,
+            frames: FilteredFrames::new(file.frames_from(start)?, filter),
+            clock: WallClock::new(start),
+        })
+    }
+}
+
+impl<'a, C: Clock> Player<'a, C> {
+    /// Follow `clock` instead of the current clock.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::cell::Cell;
+    /// use sdif_rs::player::Player;
+    /// use sdif_rs::SdifFile;
+    ///
+    /// // Position of the audio being played, updated by the audio engine
+    /// let position = Cell::new(0.0);
+    ///
+    /// let file = SdifFile::open("partials.sdif")?;
+    /// let mut player = Player::new(&file, 0.0)?.clock(|| position.get());
+    /// while let Some(frame) = player.poll() {
+    ///     println!("Due: {:.3}s", frame?.time());
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn clock<D: Clock>(self, clock: D) -> Player<'a, D> {
+        Player {
+            pending: self.pending,
+            frames: self.frames,
+            clock,
+        }
+    }
+
+    /// Get the next frame if it is due, without blocking.
+    ///
+    /// Returns `None` if the next frame is not due yet or if there are no
+    /// frames left; [`is_finished()`](Self::is_finished) tells the two
+    /// apart. The previously returned frame must be dropped first.
+    pub fn poll(&mut self) -> Option<Result<Frame<'a>>> {
+        let frame = match self.take_next()? {
+            Ok(frame) => frame,
+            Err(e) => return Some(Err(e)),
+        };
+
+        if frame.time() <= self.clock.now() {
+            Some(Ok(frame))
+        } else {
+            self.pending = Some(Ok(frame));
+            None
+        }
+    }
+
+    /// Get the time of the next frame, reading its header if needed.
+    ///
+    /// Returns `None` if there are no frames left, or if the next frame
+    /// can't be read; the error is then returned by the next call to
+    /// [`poll()`](Self::poll) or [`next()`](Iterator::next).
+    pub fn next_time(&mut self) -> Option<f64> {
+        self.read_ahead();
+        match &self.pending {
+            Some(Ok(frame)) => Some(frame.time()),
+            _ => None,
+        }
+    }
+
+    /// Check whether every frame has been played.
+    pub fn is_finished(&mut self) -> bool {
+        self.read_ahead();
+        self.pending.is_none()
+    }
+
+    /// Read the next frame into `pending` if it isn't there yet.
+    fn read_ahead(&mut self) {
+        if self.pending.is_none() {
+            self.pending = self.frames.next();
+        }
+    }
+
+    /// Take the frame read ahead, or read the next one.
+    fn take_next(&mut self) -> Option<Result<Frame<'a>>> {
+        self.pending.take().or_else(|| self.frames.next())
+    }
+}
+
+impl<'a, C: Clock> Iterator for Player<'a, C> {
+    type Item = Result<Frame<'a>>;
+
+    /// Wait until the next frame is due and return it.
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = match self.take_next()? {
+            Ok(frame) => frame,
+            Err(e) => return Some(Err(e)),
+        };
+
+        self.clock.wait_until(frame.time());
+        Some(Ok(frame))
+    }
+}
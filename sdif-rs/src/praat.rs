@@ -0,0 +1,368 @@
+//! Import of Praat text files.
+//!
+//! Praat is the most common source of pitch data in phonetics. It saves
+//! objects as text in a "long" format, with one `name = value` per line,
+//! or a "short" format with bare values; both are read here, in UTF-8 or
+//! Praat's UTF-16.
+//!
+//! Supported objects and the streams they become:
+//!
+//! | Praat object    | SDIF stream                                        |
+//! |-----------------|----------------------------------------------------|
+//! | `PitchTier`     | 1FQ0, one frame per point, confidence 1            |
+//! | `PointProcess`  | 1FQ0 from the period between consecutive pulses    |
+//! | `IntensityTier` | [energy](crate::features::ENERGY_SIGNATURE) frames |
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::praat;
+//!
+//! let frames = praat::import("speaker1.PitchTier", "speaker1.f0.sdif")?;
+//! println!("Imported {} pitch points", frames);
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use crate::breakpoints::Breakpoint;
+use crate::error::{Error, Result};
+use crate::features::{FeaturePoint, FeatureTrack, ENERGY_COLUMNS, ENERGY_SIGNATURE};
+use crate::file::SdifFile;
+use crate::metrics::{self, ConversionTimer};
+use crate::schemas;
+
+/// Frame/matrix signature of pitch data.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Longest period between pulses considered voiced, in seconds.
+///
+/// This is Praat's own default for voice analysis; longer gaps are pauses
+/// between voiced stretches.
+const MAX_PULSE_PERIOD: f64 = 0.02;
+
+/// A Praat object read from a text file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PraatObject {
+    /// Pitch targets, with values in Hz.
+    PitchTier {
+        /// Start of the time domain, in seconds.
+        xmin: f64,
+        /// End of the time domain, in seconds.
+        xmax: f64,
+        /// Points in time order.
+        points: Vec<Breakpoint>,
+    },
+
+    /// Intensity targets, with values in dB.
+    IntensityTier {
+        /// Start of the time domain, in seconds.
+        xmin: f64,
+        /// End of the time domain, in seconds.
+        xmax: f64,
+        /// Points in time order.
+        points: Vec<Breakpoint>,
+    },
+
+    /// Glottal pulse times.
+    PointProcess {
+        /// Start of the time domain, in seconds.
+        xmin: f64,
+        /// End of the time domain, in seconds.
+        xmax: f64,
+        /// Pulse times in seconds.
+        times: Vec<f64>,
+    },
+}
+
+impl PraatObject {
+    /// Parse a Praat text file's contents.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the text is not a Praat text file, the
+    ///   object class is not supported, or values are missing
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+        let file_type = lines.next().unwrap_or("");
+        if !file_type.starts_with("File type = \"ooTextFile") {
+            return Err(Error::invalid_format("Not a Praat text file"));
+        }
+
+        let class_line = lines.next().unwrap_or("");
+        let class = class_line
+            .split('"')
+            .nth(1)
+            .ok_or_else(|| Error::invalid_format("Praat file has no object class"))?;
+
+        let mut values = numbers(lines);
+        let mut next = |what: &str| {
+            values
+                .next()
+                .ok_or_else(|| Error::invalid_format(format!("Praat {} has no {}", class, what)))
+        };
+
+        let xmin = next("xmin")?;
+        let xmax = next("xmax")?;
+        let count = next("point count")?;
+        if !(count >= 0.0 && count.fract() == 0.0) {
+            return Err(Error::invalid_format(format!(
+                "Invalid point count in Praat {}: {}",
+                class, count
+            )));
+        }
+        let count = count as usize;
+
+        match class {
+            "PitchTier" | "IntensityTier" => {
+                let points = (0..count)
+                    .map(|_| {
+                        Ok(Breakpoint {
+                            time: next("point time")?,
+                            value: next("point value")?,
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(if class == "PitchTier" {
+                    PraatObject::PitchTier { xmin, xmax, points }
+                } else {
+                    PraatObject::IntensityTier { xmin, xmax, points }
+                })
+            }
+            "PointProcess" => {
+                let times = (0..count)
+                    .map(|_| next("pulse time"))
+                    .collect::<Result<_>>()?;
+                Ok(PraatObject::PointProcess { xmin, xmax, times })
+            }
+            _ => Err(Error::invalid_format(format!(
+                "Unsupported Praat object class: {}",
+                class
+            ))),
+        }
+    }
+
+    /// Read a Praat text file.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be read
+    /// - Any error from [`parse()`](Self::parse)
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&decode(&fs::read(path)?)?)
+    }
+
+    /// Get the pitch points of a PitchTier or PointProcess.
+    ///
+    /// A PointProcess gets a point at each pulse followed by another within
+    /// 20 ms, with the inverse of the period as its value. Returns `None`
+    /// for an IntensityTier.
+    pub fn pitch_points(&self) -> Option<Vec<Breakpoint>> {
+        match self {
+            PraatObject::PitchTier { points, .. } => Some(points.clone()),
+            PraatObject::PointProcess { times, .. } => Some(
+                times
+                    .windows(2)
+                    .filter(|pair| pair[1] > pair[0] && pair[1] - pair[0] <= MAX_PULSE_PERIOD)
+                    .map(|pair| Breakpoint {
+                        time: pair[0],
+                        value: 1.0 / (pair[1] - pair[0]),
+                    })
+                    .collect(),
+            ),
+            PraatObject::IntensityTier { .. } => None,
+        }
+    }
+
+    /// Write the object as an SDIF stream.
+    ///
+    /// Pitch is written as 1FQ0 frames with a 1x2 matrix of frequency and
+    /// confidence 1. Intensity is written as an energy
+    /// [`FeatureTrack`], converting dB to power with `10^(dB / 10)`.
+    ///
+    /// Returns the number of frames written.
+    ///
+    /// # Errors
+    ///
+    /// - Any error from creating or writing the file
+    pub fn write_sdif(&self, path: impl AsRef<Path>) -> Result<usize> {
+        if let PraatObject::IntensityTier { points, .. } = self {
+            let mut track = FeatureTrack::new(ENERGY_SIGNATURE, ENERGY_COLUMNS[0]);
+            track.points = points
+                .iter()
+                .map(|p| FeaturePoint {
+                    time: p.time,
+                    stream_id: 0,
+                    value: 10f64.powf(p.value / 10.0),
+                })
+                .collect();
+            track.write(path)?;
+            return Ok(track.points.len());
+        }
+
+        let points = self.pitch_points().unwrap_or_default();
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type(PITCH_SIGNATURE, schemas::FQ0_COLUMNS)?
+            .add_frame_type(PITCH_SIGNATURE, &["1FQ0 FundamentalFrequencyEstimate"])?
+            .build()?;
+        for point in &points {
+            writer.write_frame_one_matrix(
+                PITCH_SIGNATURE,
+                point.time,
+                PITCH_SIGNATURE,
+                1,
+                2,
+                &[point.value, 1.0],
+            )?;
+        }
+        writer.close()?;
+
+        Ok(points.len())
+    }
+}
+
+/// Convert a Praat text file to SDIF.
+///
+/// Returns the number of frames written. See the [module
+/// documentation](self) for how each object is converted.
+///
+/// # Errors
+///
+/// - Any error from [`PraatObject::read()`] or [`PraatObject::write_sdif()`]
+pub fn import(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<usize> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_PRAAT_IMPORT);
+    let frames = PraatObject::read(input)?.write_sdif(output)?;
+    timer.finish();
+    Ok(frames)
+}
+
+/// Iterate over the numbers in the body of a Praat text file.
+///
+/// Long format lines contribute the value after `=`, short format lines
+/// their whole text. Comments after `!` and lines without a number, such
+/// as `points [1]:`, are skipped.
+fn numbers<'a>(lines: impl Iterator<Item = &'a str> + 'a) -> impl Iterator<Item = f64> + 'a {
+    lines.filter_map(|line| {
+        let line = line.split('!').next().unwrap_or("");
+        let value = line.rsplit('=').next().unwrap_or(line);
+        value.trim().parse().ok()
+    })
+}
+
+/// Decode a Praat text file, which is UTF-16 with a byte order mark when
+/// it holds non-ASCII text and UTF-8 otherwise.
+fn decode(bytes: &[u8]) -> Result<String> {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units)
+            .map_err(|_| Error::invalid_format("Praat file is not valid UTF-16"))
+    };
+
+    match bytes {
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        _ => {
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| Error::invalid_format("Praat file is not valid UTF-8"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG_PITCH_TIER: &str = r#"File type = "ooTextFile"
+Object class = "PitchTier"
+
+xmin = 0
+xmax = 1.5
+points: size = 2
+points [1]:
+    number = 0.1
+    value = 220
+points [2]:
+    number = 0.6
+    value = 233.5
+"#;
+
+    const SHORT_INTENSITY_TIER: &str =
+        "File type = \"ooTextFile\"\n\"IntensityTier\"\n\n0\n1\n2\n0.25\n60\n0.75 ! comment\n70\n";
+
+    #[test]
+    fn test_parse_long_format() {
+        let object = PraatObject::parse(LONG_PITCH_TIER).unwrap();
+        assert_eq!(
+            object,
+            PraatObject::PitchTier {
+                xmin: 0.0,
+                xmax: 1.5,
+                points: vec![
+                    Breakpoint {
+                        time: 0.1,
+                        value: 220.0
+                    },
+                    Breakpoint {
+                        time: 0.6,
+                        value: 233.5
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_short_format() {
+        let object = PraatObject::parse(SHORT_INTENSITY_TIER).unwrap();
+        let PraatObject::IntensityTier { points, .. } = &object else {
+            panic!("Expected an IntensityTier, got {:?}", object);
+        };
+        assert_eq!(points.len(), 2);
+        assert_eq!(
+            points[1],
+            Breakpoint {
+                time: 0.75,
+                value: 70.0
+            }
+        );
+        assert_eq!(object.pitch_points(), None);
+    }
+
+    #[test]
+    fn test_point_process_pitch() {
+        let text = "File type = \"ooTextFile\"\nObject class = \"PointProcess\"\n\n0\n1\n4\n0.1\n0.105\n0.11\n0.5\n";
+        let object = PraatObject::parse(text).unwrap();
+
+        // The 0.39 s gap before the last pulse is a pause
+        let points = object.pitch_points().unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].time, 0.1);
+        assert!((points[0].value - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(PraatObject::parse("xmin = 0").is_err());
+        assert!(PraatObject::parse("File type = \"ooTextFile\"\n\"TextGrid\"\n0\n1\n0\n").is_err());
+        // Fewer points than declared
+        assert!(PraatObject::parse(&LONG_PITCH_TIER.replace("size = 2", "size = 3")).is_err());
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("File".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode(&bytes).unwrap(), "File");
+
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend("File".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(decode(&bytes).unwrap(), "File");
+    }
+}
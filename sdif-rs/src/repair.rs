@@ -0,0 +1,316 @@
+//! Salvaging the readable frames of damaged SDIF files.
+//!
+//! [`repair_file()`] walks the raw bytes of a file frame by frame instead
+//! of going through the C library, which gives up at the first damaged
+//! frame. Every frame whose header and matrices are consistent is copied
+//! through unchanged. When a frame doesn't check out, the damaged bytes are
+//! skipped one at a time until the next intact frame starts, so data after
+//! a damaged region is recovered too.
+//!
+//! NVTs and type declarations are stored as frames themselves, so they are
+//! salvaged the same way. Frames earlier than the last recovered frame are
+//! dropped, keeping frame times in order.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::repair;
+//!
+//! let summary = repair::repair_file("damaged.sdif", "repaired.sdif")?;
+//! println!("Recovered {} frames ({} bytes), dropped {} frames ({} bytes)",
+//!     summary.frames_recovered,
+//!     summary.bytes_recovered,
+//!     summary.frames_dropped,
+//!     summary.bytes_dropped
+//! );
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use sdif_sys::write;
+
+use crate::error::{Error, Result};
+use crate::metrics::{self, ConversionTimer};
+
+/// Size of the general header at the start of every SDIF file.
+const FILE_HEADER_SIZE: usize = 16;
+
+/// Size of a frame header: signature, size, time, stream ID and matrix count.
+const FRAME_HEADER_SIZE: usize = 24;
+
+/// Frame size value used by writers that don't know the size in advance.
+const UNKNOWN_FRAME_SIZE: u32 = 0xffff_ffff;
+
+/// Signatures of the frames holding NVTs, type declarations and stream IDs.
+const METADATA_SIGNATURES: [&[u8; 4]; 3] = [b"1NVT", b"1TYP", b"1IDS"];
+
+/// Outcome of a repair.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RepairSummary {
+    /// Number of data frames copied to the output. NVTs and type
+    /// declarations are not counted.
+    pub frames_recovered: usize,
+
+    /// Number of frames left out: frames out of order, and the first
+    /// frame of each damaged region if its header is intact. Other frames
+    /// inside a damaged region can't be recognized and are not counted.
+    pub frames_dropped: usize,
+
+    /// Size of the output in bytes.
+    pub bytes_recovered: u64,
+
+    /// Number of input bytes left out.
+    pub bytes_dropped: u64,
+
+    /// Byte ranges of the input that were left out.
+    pub damaged: Vec<Range<u64>>,
+}
+
+impl RepairSummary {
+    /// Check whether the input was copied whole.
+    pub fn is_intact(&self) -> bool {
+        self.bytes_dropped == 0
+    }
+
+    /// Record that `range` of the input was left out, merging it with the
+    /// previous range if they touch.
+    fn drop_bytes(&mut self, range: Range<usize>) {
+        let range = range.start as u64..range.end as u64;
+        self.bytes_dropped += range.end - range.start;
+        match self.damaged.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => self.damaged.push(range),
+        }
+    }
+}
+
+/// Copy the readable frames of `input` to a new file at `output`.
+///
+/// The whole input is read into memory.
+///
+/// # Errors
+///
+/// - [`Error::Io`] if the input can't be read or the output written
+/// - Same as [`repair_bytes()`] otherwise
+pub fn repair_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<RepairSummary> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_REPAIR);
+    let (repaired, summary) = repair_bytes(&fs::read(input)?)?;
+    fs::write(output, repaired)?;
+    timer.finish();
+    Ok(summary)
+}
+
+/// Copy the readable frames of the SDIF data in `data`.
+///
+/// Returns the repaired data and what was recovered.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the data doesn't start with an SDIF
+///   header
+pub fn repair_bytes(data: &[u8]) -> Result<(Vec<u8>, RepairSummary)> {
+    if data.len() < FILE_HEADER_SIZE || &data[..4] != b"SDIF" {
+        return Err(Error::invalid_format("Not an SDIF file: the header is missing or damaged"));
+    }
+
+    let mut repaired = data[..FILE_HEADER_SIZE].to_vec();
+    let mut summary = RepairSummary::default();
+    let mut last_time = f64::NEG_INFINITY;
+    let mut pos = FILE_HEADER_SIZE;
+    // Whether bytes are being skipped to find the next intact frame
+    let mut resyncing = false;
+
+    while pos < data.len() {
+        let Some(frame) = parse_frame(data, pos) else {
+            // Only the frame where the damage starts can be recognized
+            if !resyncing && is_plausible_header(data, pos) {
+                summary.frames_dropped += 1;
+            }
+            resyncing = true;
+            summary.drop_bytes(pos..pos + 1);
+            pos += 1;
+            continue;
+        };
+        resyncing = false;
+
+        if frame.time < last_time {
+            summary.frames_dropped += 1;
+            summary.drop_bytes(pos..frame.end);
+        } else {
+            repaired.extend_from_slice(&data[pos..frame.end]);
+            last_time = frame.time;
+            if !frame.is_metadata {
+                summary.frames_recovered += 1;
+            }
+        }
+        pos = frame.end;
+    }
+
+    summary.bytes_recovered = repaired.len() as u64;
+    Ok((repaired, summary))
+}
+
+/// An intact frame found in the data.
+struct FrameExtent {
+    /// Position just past the end of the frame.
+    end: usize,
+
+    /// Frame time in seconds.
+    time: f64,
+
+    /// Whether the frame holds NVTs or type declarations.
+    is_metadata: bool,
+}
+
+/// Read a big-endian u32 at `pos`.
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes(data[pos..pos + 4].try_into().expect("4 bytes"))
+}
+
+/// Check whether 4 bytes can be a frame or matrix signature.
+fn is_signature(bytes: &[u8]) -> bool {
+    bytes.iter().all(|b| b.is_ascii_alphanumeric())
+}
+
+/// Check whether a frame header seems to start at `pos`, whatever follows.
+fn is_plausible_header(data: &[u8], pos: usize) -> bool {
+    if pos + 8 > data.len() || !is_signature(&data[pos..pos + 4]) {
+        return false;
+    }
+    let size = be_u32(data, pos + 4);
+    size == UNKNOWN_FRAME_SIZE || (size >= 16 && size % 8 == 0)
+}
+
+/// Check the frame starting at `pos` and find where it ends.
+///
+/// Returns `None` unless the frame is complete and its matrices fill it
+/// exactly.
+fn parse_frame(data: &[u8], pos: usize) -> Option<FrameExtent> {
+    if pos + FRAME_HEADER_SIZE > data.len() || !is_plausible_header(data, pos) {
+        return None;
+    }
+
+    let size = be_u32(data, pos + 4);
+    let time = f64::from_be_bytes(data[pos + 8..pos + 16].try_into().expect("8 bytes"));
+    let num_matrices = be_u32(data, pos + 20);
+    if !time.is_finite() {
+        return None;
+    }
+
+    let limit = if size == UNKNOWN_FRAME_SIZE {
+        data.len()
+    } else {
+        let end = pos.checked_add(8 + size as usize)?;
+        if end > data.len() {
+            return None;
+        }
+        end
+    };
+
+    let mut end = pos + FRAME_HEADER_SIZE;
+    for _ in 0..num_matrices {
+        let header_end = end + write::MATRIX_HEADER_SIZE as usize;
+        if header_end > limit || !is_signature(&data[end..end + 4]) {
+            return None;
+        }
+
+        let element = write::data_type_size(be_u32(data, end + 4))?;
+        let (rows, cols) = (be_u32(data, end + 8) as usize, be_u32(data, end + 12) as usize);
+        let bytes = rows.checked_mul(cols)?.checked_mul(element)?;
+        end = header_end.checked_add(bytes)?.checked_add(write::padding(bytes))?;
+        if end > limit {
+            return None;
+        }
+    }
+
+    if size != UNKNOWN_FRAME_SIZE && end != limit {
+        return None;
+    }
+
+    Some(FrameExtent {
+        end,
+        time,
+        is_metadata: METADATA_SIGNATURES.iter().any(|sig| &data[pos..pos + 4] == *sig),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a frame holding one 1x2 f32 matrix.
+    fn frame(signature: &[u8; 4], time: f64) -> Vec<u8> {
+        let mut bytes = signature.to_vec();
+        bytes.extend_from_slice(&40u32.to_be_bytes());
+        bytes.extend_from_slice(&time.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&1u32.to_be_bytes());
+        bytes.extend_from_slice(signature);
+        for value in [0x0004u32, 1, 2] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes.extend_from_slice(&440f32.to_be_bytes());
+        bytes.extend_from_slice(&0.5f32.to_be_bytes());
+        bytes
+    }
+
+    fn header() -> Vec<u8> {
+        let mut bytes = b"SDIF".to_vec();
+        for value in [8u32, 3, 1] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_intact_data_is_unchanged() {
+        let mut data = header();
+        data.extend(frame(b"1FQ0", 0.0));
+        data.extend(frame(b"1FQ0", 0.1));
+
+        let (repaired, summary) = repair_bytes(&data).unwrap();
+        assert_eq!(repaired, data);
+        assert!(summary.is_intact());
+        assert_eq!(summary.frames_recovered, 2);
+        assert_eq!(summary.bytes_recovered, data.len() as u64);
+    }
+
+    #[test]
+    fn test_damaged_frames_are_skipped() {
+        let mut data = header();
+        data.extend(frame(b"1FQ0", 0.0));
+        let damaged = data.len();
+        let mut bad = frame(b"1FQ0", 0.1);
+        bad[31] = 0xff; // Unknown data type
+        data.extend(&bad);
+        data.extend([0; 4]); // Stray bytes
+        data.extend(frame(b"1FQ0", 0.2));
+        data.extend(frame(b"1FQ0", 0.15)); // Out of order
+        data.extend(&frame(b"1FQ0", 0.3)[..30]); // Truncated
+
+        let (repaired, summary) = repair_bytes(&data).unwrap();
+        assert_eq!(summary.frames_recovered, 2);
+        assert_eq!(summary.frames_dropped, 3);
+        assert_eq!(summary.bytes_recovered, repaired.len() as u64);
+        assert_eq!(summary.bytes_dropped, (data.len() - repaired.len()) as u64);
+        assert_eq!(summary.damaged[0], damaged as u64..(damaged + 52) as u64);
+        assert_eq!(summary.damaged.len(), 2);
+
+        let mut expected = header();
+        expected.extend(frame(b"1FQ0", 0.0));
+        expected.extend(frame(b"1FQ0", 0.2));
+        assert_eq!(repaired, expected);
+    }
+
+    #[test]
+    fn test_missing_header() {
+        assert!(matches!(
+            repair_bytes(b"not an SDIF file"),
+            Err(Error::InvalidFormat { .. })
+        ));
+    }
+}
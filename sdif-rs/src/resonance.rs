@@ -0,0 +1,512 @@
+//! Resonance model fitting from impulse responses.
+//!
+//! A struck or plucked object rings as a sum of exponentially decaying
+//! sinusoids, its modes. [`fit_resonances()`] estimates the frequency,
+//! amplitude, decay rate and phase of the strongest modes in a short
+//! recording of such a response, and [`wav_to_sdif()`] writes them as a
+//! 1RES matrix ready for modal synthesis.
+//!
+//! Modes are found as peaks in the spectrum of the start of the response.
+//! Each mode's amplitude envelope is then tracked over time, and a straight
+//! line fitted to its logarithm gives the decay rate and the amplitude at
+//! the onset. A mode `(f, a, d, φ)` models the signal
+//!
+//! ```text
+//! a · exp(-d · t) · cos(2π · f · t + φ)
+//! ```
+//!
+//! with `t` in seconds from the onset, which is taken to be the loudest
+//! sample.
+//!
+//! Requires the `resonance` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::resonance::{self, ResonanceOptions};
+//!
+//! let options = ResonanceOptions::new().max_modes(40);
+//! let modes = resonance::wav_to_sdif("bell.wav", "bell.res.sdif", &options)?;
+//! println!("Found {} modes", modes);
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::f64::consts::PI;
+use std::path::Path;
+
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::schemas;
+use crate::source::read_mono;
+
+/// Frame/matrix signature of resonances.
+const RESONANCE_SIGNATURE: &str = "1RES";
+
+/// Name of the tool recorded in the output's history.
+const TOOL: &str = "sdif-rs-resonance";
+
+/// Number of envelope frames per spectrum frame when tracking decay.
+const ENVELOPE_DIVISOR: usize = 4;
+
+/// Settings of the resonance fitting.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResonanceOptions {
+    /// Largest number of modes to return.
+    pub max_modes: usize,
+
+    /// Lowest mode frequency, in Hz.
+    pub min_frequency: f64,
+
+    /// Highest mode frequency, in Hz; `None` for the Nyquist frequency.
+    pub max_frequency: Option<f64>,
+
+    /// Length of the spectrum used to find modes, in samples. Must be a
+    /// power of two; longer spectra separate closer modes.
+    pub fft_size: usize,
+
+    /// Range below the strongest mode, in dB, in which modes are kept and
+    /// envelopes tracked.
+    pub dynamic_range_db: f64,
+}
+
+impl Default for ResonanceOptions {
+    fn default() -> Self {
+        ResonanceOptions {
+            max_modes: 20,
+            min_frequency: 20.0,
+            max_frequency: None,
+            fft_size: 8192,
+            dynamic_range_db: 60.0,
+        }
+    }
+}
+
+impl ResonanceOptions {
+    /// Create the default options: up to 20 modes above 20 Hz, an 8192
+    /// point spectrum and a 60 dB dynamic range.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the largest number of modes to return.
+    pub fn max_modes(mut self, modes: usize) -> Self {
+        self.max_modes = modes;
+        self
+    }
+
+    /// Set the range of mode frequencies, in Hz.
+    pub fn frequency_range(mut self, min: f64, max: f64) -> Self {
+        self.min_frequency = min;
+        self.max_frequency = Some(max);
+        self
+    }
+
+    /// Set the length of the spectrum used to find modes, in samples.
+    pub fn fft_size(mut self, size: usize) -> Self {
+        self.fft_size = size;
+        self
+    }
+
+    /// Set the range below the strongest mode in which modes are kept, in dB.
+    pub fn dynamic_range_db(mut self, range: f64) -> Self {
+        self.dynamic_range_db = range;
+        self
+    }
+
+    /// Check the options against a sample rate.
+    fn validate(&self, sample_rate: f64) -> Result<()> {
+        if !(sample_rate > 0.0 && sample_rate.is_finite()) {
+            return Err(Error::invalid_format(format!(
+                "Sample rate must be positive, got {}",
+                sample_rate
+            )));
+        }
+        if self.max_modes == 0 {
+            return Err(Error::invalid_format("At least one mode must be requested"));
+        }
+        if !self.fft_size.is_power_of_two() || self.fft_size < 16 * ENVELOPE_DIVISOR {
+            return Err(Error::invalid_format(format!(
+                "FFT size must be a power of two of at least {}, got {}",
+                16 * ENVELOPE_DIVISOR,
+                self.fft_size
+            )));
+        }
+        let max = self.max_frequency.unwrap_or(sample_rate / 2.0);
+        if !(self.min_frequency >= 0.0 && max > self.min_frequency && max <= sample_rate / 2.0) {
+            return Err(Error::invalid_format(format!(
+                "Invalid frequency range {}-{} Hz",
+                self.min_frequency, max
+            )));
+        }
+        if !(self.dynamic_range_db > 0.0 && self.dynamic_range_db.is_finite()) {
+            return Err(Error::invalid_format(format!(
+                "Dynamic range must be positive, got {}",
+                self.dynamic_range_db
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// One mode of a resonance model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resonance {
+    /// Frequency in Hz.
+    pub frequency: f64,
+
+    /// Amplitude at the onset, in sample units.
+    pub amplitude: f64,
+
+    /// Exponential decay rate, in 1/s.
+    pub decay_rate: f64,
+
+    /// Phase at the onset, in radians.
+    pub phase: f64,
+}
+
+impl Resonance {
+    /// Get the time the mode takes to decay by 60 dB, in seconds.
+    ///
+    /// Returns infinity for modes that don't decay.
+    pub fn t60(&self) -> f64 {
+        if self.decay_rate > 0.0 {
+            1000f64.ln() / self.decay_rate
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Estimate the modes of an impulse response.
+///
+/// Modes are returned strongest first. Responses too short to track a
+/// decay, or silent ones, give no modes.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the options are invalid for `sample_rate`
+pub fn fit_resonances(
+    samples: &[f32],
+    sample_rate: f64,
+    options: &ResonanceOptions,
+) -> Result<Vec<Resonance>> {
+    options.validate(sample_rate)?;
+
+    let onset = samples
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .map_or(0, |(i, _)| i);
+    let response: Vec<f64> = samples[onset..].iter().map(|&s| f64::from(s)).collect();
+
+    let peaks = find_peaks(&response, sample_rate, options);
+    let frame = options.fft_size / ENVELOPE_DIVISOR;
+
+    let mut modes: Vec<Resonance> = peaks
+        .into_iter()
+        .filter_map(|frequency| fit_mode(&response, sample_rate, frequency, frame, options))
+        .collect();
+    modes.sort_by(|a, b| b.amplitude.total_cmp(&a.amplitude));
+    Ok(modes)
+}
+
+/// Estimate the modes of an impulse response in a WAV file.
+///
+/// Multichannel files are mixed down to mono first.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the file is not a valid WAV file or the
+///   options are invalid for its sample rate
+/// - [`Error::Io`] if the file can't be read
+pub fn fit_wav(path: impl AsRef<Path>, options: &ResonanceOptions) -> Result<Vec<Resonance>> {
+    let (samples, sample_rate) = read_mono(path.as_ref())?;
+    fit_resonances(&samples, sample_rate, options)
+}
+
+/// Estimate the modes of a WAV file and write them as a 1RES SDIF file.
+///
+/// The modes are written as one frame at time 0 holding an N×4 matrix
+/// with the standard 1RES columns. The output records the sample rate,
+/// the source audio (see [`crate::source`]) and the fitting settings in
+/// its history.
+///
+/// Returns the number of modes written.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if no modes were found
+/// - Any error from [`fit_wav()`]
+/// - Any error from creating or writing the output
+pub fn wav_to_sdif(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &ResonanceOptions,
+) -> Result<usize> {
+    let input = input.as_ref();
+    let (samples, sample_rate) = read_mono(input)?;
+    let modes = fit_resonances(&samples, sample_rate, options)?;
+    if modes.is_empty() {
+        return Err(Error::invalid_format(format!(
+            "No resonances found in {}",
+            input.display()
+        )));
+    }
+
+    let params = [
+        ("max_modes", options.max_modes.to_string()),
+        ("min_frequency", options.min_frequency.to_string()),
+        (
+            "max_frequency",
+            options.max_frequency.unwrap_or(sample_rate / 2.0).to_string(),
+        ),
+        ("fft_size", options.fft_size.to_string()),
+        ("dynamic_range_db", options.dynamic_range_db.to_string()),
+    ];
+    let mut writer = SdifFile::builder()
+        .create(output)?
+        .sample_rate(sample_rate)?
+        .set_source_audio(input)?
+        .append_history(
+            TOOL,
+            env!("CARGO_PKG_VERSION"),
+            params.iter().map(|(name, value)| (*name, value.as_str())),
+        )?
+        .add_matrix_type(RESONANCE_SIGNATURE, schemas::RES_COLUMNS)?
+        .add_frame_type(RESONANCE_SIGNATURE, &["1RES Resonances"])?
+        .build()?;
+
+    let data: Vec<f64> = modes
+        .iter()
+        .flat_map(|m| [m.frequency, m.amplitude, m.decay_rate, m.phase])
+        .collect();
+    writer.write_frame_one_matrix(
+        RESONANCE_SIGNATURE,
+        0.0,
+        RESONANCE_SIGNATURE,
+        modes.len(),
+        schemas::RES_COLUMNS.len(),
+        &data,
+    )?;
+    writer.close()?;
+
+    Ok(modes.len())
+}
+
+/// Find the frequencies of the strongest spectral peaks.
+fn find_peaks(response: &[f64], sample_rate: f64, options: &ResonanceOptions) -> Vec<f64> {
+    let size = options.fft_size;
+    let window = hann(size);
+    let mut spectrum: Vec<Complex<f64>> = (0..size)
+        .map(|i| Complex::new(response.get(i).copied().unwrap_or(0.0) * window[i], 0.0))
+        .collect();
+    FftPlanner::new().plan_fft_forward(size).process(&mut spectrum);
+
+    let magnitude: Vec<f64> = spectrum[..=size / 2].iter().map(|c| c.norm()).collect();
+    let bin_width = sample_rate / size as f64;
+    let max_frequency = options.max_frequency.unwrap_or(sample_rate / 2.0);
+    let first = ((options.min_frequency / bin_width).ceil() as usize).max(1);
+    let last = ((max_frequency / bin_width).floor() as usize).min(magnitude.len() - 2);
+
+    let mut peaks: Vec<(usize, f64)> = (first..=last)
+        .filter(|&k| magnitude[k] > magnitude[k - 1] && magnitude[k] >= magnitude[k + 1])
+        .map(|k| (k, magnitude[k]))
+        .collect();
+    let Some(strongest) = peaks.iter().map(|&(_, m)| m).reduce(f64::max) else {
+        return Vec::new();
+    };
+
+    let floor = strongest * db_to_gain(-options.dynamic_range_db);
+    peaks.retain(|&(_, m)| m >= floor);
+    peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+    peaks.truncate(options.max_modes);
+
+    peaks
+        .into_iter()
+        .map(|(k, _)| (k as f64 + parabolic_offset(&magnitude, k)) * bin_width)
+        .collect()
+}
+
+/// Track one mode's envelope and fit its decay.
+///
+/// Returns `None` if fewer than two envelope frames are above the
+/// dynamic range floor.
+fn fit_mode(
+    response: &[f64],
+    sample_rate: f64,
+    frequency: f64,
+    frame: usize,
+    options: &ResonanceOptions,
+) -> Option<Resonance> {
+    let window = hann(frame);
+    let gain = 2.0 / window.iter().sum::<f64>();
+    let hop = frame / 2;
+    let omega = 2.0 * PI * frequency / sample_rate;
+
+    // Demodulate each frame at the mode frequency. Sample indices count
+    // from the onset, so the phase of the first frame is the onset phase.
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    let mut phase = 0.0;
+    let mut floor = 0.0;
+    let mut start = 0;
+    while start + frame <= response.len() {
+        let sum: Complex<f64> = (0..frame)
+            .map(|i| {
+                let n = (start + i) as f64;
+                Complex::from_polar(response[start + i] * window[i], -omega * n)
+            })
+            .sum();
+        let amplitude = sum.norm() * gain;
+
+        if points.is_empty() {
+            phase = sum.arg();
+            floor = amplitude * db_to_gain(-options.dynamic_range_db);
+        }
+        if amplitude <= floor || amplitude <= 0.0 {
+            break;
+        }
+
+        let time = (start as f64 + frame as f64 / 2.0) / sample_rate;
+        points.push((time, amplitude.ln()));
+        start += hop;
+    }
+
+    let (slope, intercept) = fit_line(&points)?;
+
+    Some(Resonance {
+        frequency,
+        amplitude: intercept.exp(),
+        decay_rate: (-slope).max(0.0),
+        phase,
+    })
+}
+
+/// Fit a least-squares line to points, returning (slope, intercept).
+fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+
+    let slope = covariance / variance;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Get the offset of a peak from bin `k` by parabolic interpolation of
+/// the log magnitude.
+fn parabolic_offset(magnitude: &[f64], k: usize) -> f64 {
+    let [left, centre, right] = [magnitude[k - 1], magnitude[k], magnitude[k + 1]]
+        .map(|m| m.max(f64::MIN_POSITIVE).ln());
+    let curvature = left - 2.0 * centre + right;
+    if curvature < 0.0 {
+        (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    }
+}
+
+/// Build a periodic Hann window.
+fn hann(size: usize) -> Vec<f64> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / size as f64).cos())
+        .collect()
+}
+
+/// Convert decibels to a gain factor.
+fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthesize a sum of decaying modes.
+    fn synthesize(modes: &[Resonance], sample_rate: f64, seconds: f64) -> Vec<f32> {
+        (0..(sample_rate * seconds) as usize)
+            .map(|i| {
+                let t = i as f64 / sample_rate;
+                modes
+                    .iter()
+                    .map(|m| {
+                        m.amplitude
+                            * (-m.decay_rate * t).exp()
+                            * (2.0 * PI * m.frequency * t + m.phase).cos()
+                    })
+                    .sum::<f64>() as f32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_two_modes() {
+        let truth = [
+            Resonance {
+                frequency: 440.0,
+                amplitude: 0.6,
+                decay_rate: 3.0,
+                phase: 0.0,
+            },
+            Resonance {
+                frequency: 1230.0,
+                amplitude: 0.3,
+                decay_rate: 8.0,
+                phase: 0.0,
+            },
+        ];
+        let samples = synthesize(&truth, 16000.0, 1.0);
+        let modes = fit_resonances(&samples, 16000.0, &ResonanceOptions::new().max_modes(2)).unwrap();
+
+        assert_eq!(modes.len(), 2);
+        for (fit, expected) in modes.iter().zip(&truth) {
+            assert!((fit.frequency - expected.frequency).abs() < 1.0, "{:?}", fit);
+            assert!((fit.amplitude - expected.amplitude).abs() < 0.05, "{:?}", fit);
+            assert!((fit.decay_rate - expected.decay_rate).abs() < 0.5, "{:?}", fit);
+        }
+    }
+
+    #[test]
+    fn test_silence_has_no_modes() {
+        let modes = fit_resonances(&[0.0; 16000], 16000.0, &ResonanceOptions::new()).unwrap();
+        assert!(modes.is_empty());
+    }
+
+    #[test]
+    fn test_fit_line() {
+        let points = [(0.0, 1.0), (1.0, -1.0), (2.0, -3.0)];
+        assert_eq!(fit_line(&points), Some((-2.0, 1.0)));
+        assert_eq!(fit_line(&points[..1]), None);
+    }
+
+    #[test]
+    fn test_t60() {
+        let mode = Resonance {
+            frequency: 100.0,
+            amplitude: 1.0,
+            decay_rate: 1000f64.ln(),
+            phase: 0.0,
+        };
+        assert!((mode.t60() - 1.0).abs() < 1e-12);
+        assert!(Resonance { decay_rate: 0.0, ..mode }.t60().is_infinite());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ResonanceOptions::new().validate(44100.0).is_ok());
+        assert!(ResonanceOptions::new().max_modes(0).validate(44100.0).is_err());
+        assert!(ResonanceOptions::new().fft_size(1000).validate(44100.0).is_err());
+        assert!(ResonanceOptions::new()
+            .frequency_range(100.0, 30000.0)
+            .validate(44100.0)
+            .is_err());
+    }
+}
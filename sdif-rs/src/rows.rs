@@ -0,0 +1,302 @@
+//! Typed rows of the standard matrix types.
+//!
+//! Instead of indexing into the row-major data of a matrix, the rows of a
+//! 1TRC, 1HRM, 1FQ0 or 1RES matrix can be read as structs with one field
+//! per column, using [`Matrix::rows_as()`](crate::Matrix::rows_as) or
+//! [`MatrixData::rows_as()`](crate::MatrixData::rows_as).
+//!
+//! Columns are found by name, so files that declare the columns of a type
+//! in another order are read correctly. Trailing columns that are
+//! commonly left out, like `Phase`, take a default value when missing.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::rows::TrcRow;
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("input.sdif")?;
+//! for frame in file.frames() {
+//!     let mut frame = frame?;
+//!     let time = frame.time();
+//!     for matrix in frame.matrices() {
+//!         let matrix = matrix?;
+//!         if matrix.signature() != "1TRC" {
+//!             continue;
+//!         }
+//!         for partial in matrix.rows_as::<TrcRow>()? {
+//!             println!("{:.3}s: #{} {:.1} Hz", time, partial.index, partial.frequency);
+//!         }
+//!     }
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::error::{Error, Result};
+use crate::schemas;
+
+/// A row of a matrix type, read column by column.
+///
+/// Implemented for the standard types in this module, and can be
+/// implemented for other matrix types to read them with
+/// [`Matrix::rows_as()`](crate::Matrix::rows_as).
+pub trait MatrixRow: Sized {
+    /// Signature of the matrix type.
+    const SIGNATURE: &'static str;
+
+    /// Names of the columns read, in the order given to
+    /// [`from_values()`](Self::from_values).
+    const COLUMNS: &'static [&'static str];
+
+    /// Value of a column that the matrix doesn't have.
+    ///
+    /// `None`, the default, makes the column required.
+    fn default_value(column: &str) -> Option<f64> {
+        let _ = column;
+        None
+    }
+
+    /// Build a row from the values of [`COLUMNS`](Self::COLUMNS).
+    fn from_values(values: &[f64]) -> Self;
+}
+
+/// A row of a 1TRC (sinusoidal tracks) matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrcRow {
+    /// Index of the partial track.
+    pub index: u32,
+
+    /// Frequency in Hz.
+    pub frequency: f64,
+
+    /// Linear amplitude.
+    pub amplitude: f64,
+
+    /// Phase in radians, 0 if the matrix has none.
+    pub phase: f64,
+}
+
+impl MatrixRow for TrcRow {
+    const SIGNATURE: &'static str = "1TRC";
+    const COLUMNS: &'static [&'static str] = schemas::TRC_COLUMNS;
+
+    fn default_value(column: &str) -> Option<f64> {
+        (column == "Phase").then_some(0.0)
+    }
+
+    fn from_values(values: &[f64]) -> Self {
+        TrcRow {
+            index: values[0] as u32,
+            frequency: values[1],
+            amplitude: values[2],
+            phase: values[3],
+        }
+    }
+}
+
+/// A row of a 1HRM (harmonic partials) matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HrmRow {
+    /// Harmonic number.
+    pub index: u32,
+
+    /// Frequency in Hz.
+    pub frequency: f64,
+
+    /// Linear amplitude.
+    pub amplitude: f64,
+
+    /// Phase in radians, 0 if the matrix has none.
+    pub phase: f64,
+}
+
+impl MatrixRow for HrmRow {
+    const SIGNATURE: &'static str = "1HRM";
+    const COLUMNS: &'static [&'static str] = schemas::HRM_COLUMNS;
+
+    fn default_value(column: &str) -> Option<f64> {
+        (column == "Phase").then_some(0.0)
+    }
+
+    fn from_values(values: &[f64]) -> Self {
+        HrmRow {
+            index: values[0] as u32,
+            frequency: values[1],
+            amplitude: values[2],
+            phase: values[3],
+        }
+    }
+}
+
+/// A row of a 1FQ0 (fundamental frequency) matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fq0Row {
+    /// Fundamental frequency in Hz, 0 or less when there is none.
+    pub frequency: f64,
+
+    /// Confidence of the estimate, 1 if the matrix has none.
+    pub confidence: f64,
+}
+
+impl MatrixRow for Fq0Row {
+    const SIGNATURE: &'static str = "1FQ0";
+    const COLUMNS: &'static [&'static str] = schemas::FQ0_COLUMNS;
+
+    fn default_value(column: &str) -> Option<f64> {
+        (column == "Confidence").then_some(1.0)
+    }
+
+    fn from_values(values: &[f64]) -> Self {
+        Fq0Row {
+            frequency: values[0],
+            confidence: values[1],
+        }
+    }
+}
+
+/// A row of a 1RES (resonance) matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResRow {
+    /// Frequency of the mode in Hz.
+    pub frequency: f64,
+
+    /// Linear amplitude.
+    pub amplitude: f64,
+
+    /// Decay rate in 1/s.
+    pub decay_rate: f64,
+
+    /// Initial phase in radians, 0 if the matrix has none.
+    pub phase: f64,
+}
+
+impl MatrixRow for ResRow {
+    const SIGNATURE: &'static str = "1RES";
+    const COLUMNS: &'static [&'static str] = schemas::RES_COLUMNS;
+
+    fn default_value(column: &str) -> Option<f64> {
+        (column == "Phase").then_some(0.0)
+    }
+
+    fn from_values(values: &[f64]) -> Self {
+        ResRow {
+            frequency: values[0],
+            amplitude: values[1],
+            decay_rate: values[2],
+            phase: values[3],
+        }
+    }
+}
+
+/// Where a column of a row type is read from.
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Column(usize),
+    Default(f64),
+}
+
+/// The positions of the columns of `T` in a matrix.
+#[derive(Debug)]
+pub(crate) struct RowLayout<T> {
+    sources: Vec<Source>,
+    values: Vec<f64>,
+    _row: PhantomData<T>,
+}
+
+impl<T: MatrixRow> RowLayout<T> {
+    /// Find the columns of `T` in a matrix.
+    ///
+    /// `declared` are the column names declared for the matrix type; the
+    /// standard layout is used if there are none.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidFormat`] if the matrix is not of type `T`, or lacks
+    /// a required column.
+    pub(crate) fn new(signature: &str, declared: &[String], cols: usize) -> Result<Self> {
+        if signature != T::SIGNATURE {
+            return Err(Error::invalid_format(format!(
+                "Expected a {} matrix, got {}",
+                T::SIGNATURE,
+                signature
+            )));
+        }
+
+        let position = |name: &str| {
+            if declared.is_empty() {
+                schemas::standard_columns(signature)?
+                    .iter()
+                    .position(|&c| c == name)
+            } else {
+                declared.iter().position(|c| c == name)
+            }
+            .filter(|&i| i < cols)
+        };
+        let sources = T::COLUMNS
+            .iter()
+            .map(|&name| match (position(name), T::default_value(name)) {
+                (Some(i), _) => Ok(Source::Column(i)),
+                (None, Some(value)) => Ok(Source::Default(value)),
+                (None, None) => Err(Error::invalid_format(format!(
+                    "{} matrix has no {} column",
+                    signature, name
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RowLayout {
+            values: Vec::with_capacity(sources.len()),
+            sources,
+            _row: PhantomData,
+        })
+    }
+
+    /// Read one row of the matrix.
+    pub(crate) fn read(&mut self, row: &[f64]) -> T {
+        self.values.clear();
+        self.values
+            .extend(self.sources.iter().map(|source| match *source {
+                Source::Column(i) => row[i],
+                Source::Default(value) => value,
+            }));
+        T::from_values(&self.values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_by_name() {
+        let declared = ["Frequency", "Index", "Amplitude"].map(String::from);
+        let mut layout = RowLayout::<TrcRow>::new("1TRC", &declared, 3).unwrap();
+        assert_eq!(
+            layout.read(&[440.0, 2.0, 0.5]),
+            TrcRow {
+                index: 2,
+                frequency: 440.0,
+                amplitude: 0.5,
+                phase: 0.0,
+            }
+        );
+
+        let mut layout = RowLayout::<Fq0Row>::new("1FQ0", &[], 1).unwrap();
+        assert_eq!(layout.read(&[220.0]).confidence, 1.0);
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        assert!(RowLayout::<TrcRow>::new("1HRM", &[], 4).is_err());
+        assert!(RowLayout::<ResRow>::new("1RES", &[], 2).is_err());
+
+        let declared = ["Index", "Frequency"].map(String::from);
+        assert!(RowLayout::<HrmRow>::new("1HRM", &declared, 2).is_err());
+    }
+}
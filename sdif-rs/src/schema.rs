@@ -0,0 +1,460 @@
+//! Descriptions of the matrix and frame types declared in an SDIF file.
+//!
+//! Files from unfamiliar tools often declare their own types in the
+//! `1TYP` chunk. [`SdifFile::describe_types()`](crate::SdifFile::describe_types)
+//! collects these declarations into a [`Schema`], which can be printed
+//! for humans or serialized to JSON (with the `json` feature).
+//!
+//! The same structure doubles as a template for new files: a schema
+//! document in JSON or TOML can configure a builder through
+//! [`SdifFileBuilder::from_schema()`](crate::SdifFileBuilder::from_schema),
+//! so a pipeline can ship its type declarations as data. A TOML schema
+//! looks like this:
+//!
+//! ```toml
+//! [nvt]
+//! creator = "my-pipeline"
+//!
+//! [[matrix_types]]
+//! signature = "1TRC"
+//! columns = ["Index", "Frequency", "Amplitude", "Phase"]
+//!
+//! [[frame_types]]
+//! signature = "1TRC"
+//! components = [{ signature = "1TRC", name = "SinusoidalTracks" }]
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("unknown-tool.sdif")?;
+//! let schema = file.describe_types()?;
+//!
+//! print!("{}", schema);
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::ffi::CStr;
+use std::fmt;
+#[cfg(any(feature = "json", feature = "toml"))]
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use sdif_sys::{
+    SdifCreateHashTableIterator, SdifFGetFrameTypesTable, SdifFGetMatrixTypesTable, SdifFileT,
+    SdifFrameTypeGetComponentName, SdifFrameTypeGetComponentSignature,
+    SdifFrameTypeGetNbComponents, SdifFrameTypeGetNthComponent, SdifFrameTypeT,
+    SdifHashTableIteratorGetNext, SdifHashTableIteratorIsNext, SdifKillHashTableIterator,
+    SdifMatrixTypeGetColumnName, SdifMatrixTypeGetNbColumns, SdifMatrixTypeT,
+};
+
+use crate::error::{Error, Result};
+use crate::signature::signature_to_string;
+
+/// Signatures of the header chunks (type definitions and NVTs), which the
+/// C library registers as types of their own when reading a file.
+const CHUNK_SIGNATURES: &[&str] = &["1TYP", "1NVT"];
+
+/// Description of a matrix type: its signature and column names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatrixTypeSchema {
+    /// Matrix signature (e.g., "1TRC").
+    pub signature: String,
+
+    /// Column names, in column order.
+    pub columns: Vec<String>,
+}
+
+/// A matrix component of a frame type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentSchema {
+    /// Signature of the component matrix.
+    pub signature: String,
+
+    /// Role name of the component within the frame.
+    pub name: String,
+}
+
+/// Description of a frame type: its signature and component matrices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameTypeSchema {
+    /// Frame signature (e.g., "1TRC").
+    pub signature: String,
+
+    /// Component matrices, in component order.
+    pub components: Vec<ComponentSchema>,
+}
+
+/// All type declarations of an SDIF file.
+///
+/// Types read from a file are sorted by signature so that the description
+/// is stable regardless of the order used by the C library's internal tables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schema {
+    /// Default NVT entries to write into new files.
+    ///
+    /// Empty for schemas read from a file with
+    /// [`SdifFile::describe_types()`](crate::SdifFile::describe_types).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "IndexMap::is_empty")
+    )]
+    pub nvt: IndexMap<String, String>,
+
+    /// Declared matrix types.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub matrix_types: Vec<MatrixTypeSchema>,
+
+    /// Declared frame types.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub frame_types: Vec<FrameTypeSchema>,
+}
+
+impl Schema {
+    /// Check if the schema declares no types at all.
+    pub fn is_empty(&self) -> bool {
+        self.matrix_types.is_empty() && self.frame_types.is_empty()
+    }
+
+    /// Find a matrix type by signature.
+    pub fn matrix_type(&self, signature: &str) -> Option<&MatrixTypeSchema> {
+        self.matrix_types.iter().find(|m| m.signature == signature)
+    }
+
+    /// Find a frame type by signature.
+    pub fn frame_type(&self, signature: &str) -> Option<&FrameTypeSchema> {
+        self.frame_types.iter().find(|f| f.signature == signature)
+    }
+
+    /// Keep only the types needed to write frames with the given signatures.
+    ///
+    /// The result declares those frame types, the matrix types of their
+    /// components, and matrix types sharing a signature with one of the
+    /// frames. NVT defaults are kept as they are.
+    pub fn subset(&self, frame_signatures: &[&str]) -> Schema {
+        let frame_types: Vec<FrameTypeSchema> = self
+            .frame_types
+            .iter()
+            .filter(|f| frame_signatures.contains(&f.signature.as_str()))
+            .cloned()
+            .collect();
+
+        let matrix_types = self
+            .matrix_types
+            .iter()
+            .filter(|m| {
+                frame_signatures.contains(&m.signature.as_str())
+                    || frame_types
+                        .iter()
+                        .flat_map(|f| &f.components)
+                        .any(|c| c.signature == m.signature)
+            })
+            .cloned()
+            .collect();
+
+        Schema {
+            nvt: self.nvt.clone(),
+            matrix_types,
+            frame_types,
+        }
+    }
+
+    /// Serialize the schema as pretty-printed JSON.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a schema from a JSON document.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Parse a schema from a TOML document.
+    ///
+    /// Requires the `toml` feature.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Load a schema document, choosing the format from the file extension.
+    ///
+    /// `.json` files require the `json` feature and `.toml` files require
+    /// the `toml` feature.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be read
+    /// - [`Error::InvalidFormat`] if the extension is not supported
+    /// - A parse error if the document is malformed
+    #[cfg(any(feature = "json", feature = "toml"))]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "json")]
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&text),
+            #[cfg(feature = "toml")]
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::from_toml(&text),
+            _ => Err(Error::invalid_format(format!(
+                "Unsupported schema format: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Collect the type declarations of an open file.
+    pub(crate) fn from_handle(handle: *mut SdifFileT) -> Result<Self> {
+        let mut schema = Schema::default();
+
+        unsafe {
+            let mtypes = SdifFGetMatrixTypesTable(handle);
+            if mtypes.is_null() {
+                return Err(Error::null_pointer("Matrix types table"));
+            }
+
+            let iter = SdifCreateHashTableIterator(mtypes);
+            while SdifHashTableIteratorIsNext(iter) != 0 {
+                let mtype = SdifHashTableIteratorGetNext(iter) as *mut SdifMatrixTypeT;
+                if !mtype.is_null() {
+                    schema.matrix_types.push(Self::describe_matrix_type(mtype));
+                }
+            }
+            SdifKillHashTableIterator(iter);
+
+            let ftypes = SdifFGetFrameTypesTable(handle);
+            if ftypes.is_null() {
+                return Err(Error::null_pointer("Frame types table"));
+            }
+
+            let iter = SdifCreateHashTableIterator(ftypes);
+            while SdifHashTableIteratorIsNext(iter) != 0 {
+                let ftype = SdifHashTableIteratorGetNext(iter) as *mut SdifFrameTypeT;
+                if !ftype.is_null() {
+                    schema.frame_types.push(Self::describe_frame_type(ftype));
+                }
+            }
+            SdifKillHashTableIterator(iter);
+        }
+
+        // The header chunks themselves are not declarations made by the file
+        schema
+            .matrix_types
+            .retain(|m| !CHUNK_SIGNATURES.contains(&m.signature.as_str()));
+        schema
+            .frame_types
+            .retain(|f| !CHUNK_SIGNATURES.contains(&f.signature.as_str()));
+
+        schema
+            .matrix_types
+            .sort_by(|a, b| a.signature.cmp(&b.signature));
+        schema
+            .frame_types
+            .sort_by(|a, b| a.signature.cmp(&b.signature));
+
+        Ok(schema)
+    }
+
+    /// Read signature and column names of a matrix type.
+    ///
+    /// # Safety
+    ///
+    /// `mtype` must point to a valid matrix type owned by the C library.
+    pub(crate) unsafe fn describe_matrix_type(mtype: *mut SdifMatrixTypeT) -> MatrixTypeSchema {
+        let num_columns = SdifMatrixTypeGetNbColumns(mtype);
+
+        // Column indices start at 1
+        let columns = (1..=num_columns)
+            .map(|i| c_str_or_empty(SdifMatrixTypeGetColumnName(mtype, i as _)))
+            .collect();
+
+        MatrixTypeSchema {
+            signature: signature_to_string((*mtype).Signature),
+            columns,
+        }
+    }
+
+    /// Read signature and components of a frame type.
+    ///
+    /// # Safety
+    ///
+    /// `ftype` must point to a valid frame type owned by the C library.
+    unsafe fn describe_frame_type(ftype: *mut SdifFrameTypeT) -> FrameTypeSchema {
+        let num_components = SdifFrameTypeGetNbComponents(ftype);
+
+        // Component numbers start at 1
+        let components = (1..=num_components)
+            .filter_map(|i| {
+                let comp = SdifFrameTypeGetNthComponent(ftype, i);
+                if comp.is_null() {
+                    return None;
+                }
+                Some(ComponentSchema {
+                    signature: signature_to_string(SdifFrameTypeGetComponentSignature(comp)),
+                    name: c_str_or_empty(SdifFrameTypeGetComponentName(comp)),
+                })
+            })
+            .collect();
+
+        FrameTypeSchema {
+            signature: signature_to_string((*ftype).Signature),
+            components,
+        }
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No type declarations");
+        }
+
+        if !self.matrix_types.is_empty() {
+            writeln!(f, "Matrix types:")?;
+            for mtype in &self.matrix_types {
+                writeln!(f, "  {}: {}", mtype.signature, mtype.columns.join(", "))?;
+            }
+        }
+
+        if !self.frame_types.is_empty() {
+            writeln!(f, "Frame types:")?;
+            for ftype in &self.frame_types {
+                writeln!(f, "  {}", ftype.signature)?;
+                for comp in &ftype.components {
+                    let columns = self
+                        .matrix_type(&comp.signature)
+                        .map(|m| format!(" ({})", m.columns.join(", ")))
+                        .unwrap_or_default();
+                    writeln!(f, "    {} {}{}", comp.signature, comp.name, columns)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Copy a C string owned by the library, or return an empty string for null.
+pub(crate) unsafe fn c_str_or_empty(ptr: *const std::ffi::c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Schema {
+        Schema {
+            nvt: IndexMap::new(),
+            matrix_types: vec![MatrixTypeSchema {
+                signature: "XTRC".to_string(),
+                columns: vec!["Index".to_string(), "Frequency".to_string()],
+            }],
+            frame_types: vec![FrameTypeSchema {
+                signature: "XTRC".to_string(),
+                components: vec![ComponentSchema {
+                    signature: "XTRC".to_string(),
+                    name: "Tracks".to_string(),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let schema = sample();
+        assert!(schema.matrix_type("XTRC").is_some());
+        assert!(schema.frame_type("1TRC").is_none());
+    }
+
+    #[test]
+    fn test_subset() {
+        let mut schema = sample();
+        schema.matrix_types.push(MatrixTypeSchema {
+            signature: "XFQ0".to_string(),
+            columns: vec!["Frequency".to_string()],
+        });
+        schema.matrix_types.push(MatrixTypeSchema {
+            signature: "XNOI".to_string(),
+            columns: vec!["Level".to_string()],
+        });
+        schema.frame_types.push(FrameTypeSchema {
+            signature: "XFQ0".to_string(),
+            components: vec![ComponentSchema {
+                signature: "XNOI".to_string(),
+                name: "Noise".to_string(),
+            }],
+        });
+
+        let subset = schema.subset(&["XFQ0"]);
+        assert!(subset.frame_type("XTRC").is_none());
+        assert!(subset.matrix_type("XTRC").is_none());
+        assert!(subset.frame_type("XFQ0").is_some());
+        assert!(subset.matrix_type("XFQ0").is_some());
+        assert!(subset.matrix_type("XNOI").is_some());
+
+        assert!(schema.subset(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_display() {
+        let text = sample().to_string();
+        assert!(text.contains("XTRC: Index, Frequency"));
+        assert!(text.contains("XTRC Tracks (Index, Frequency)"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_roundtrip() {
+        let schema = sample();
+        let parsed = Schema::from_json(&schema.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, schema);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml() {
+        let schema = Schema::from_toml(
+            r#"
+            [nvt]
+            creator = "tests"
+
+            [[matrix_types]]
+            signature = "XTRC"
+            columns = ["Index", "Frequency"]
+
+            [[frame_types]]
+            signature = "XTRC"
+            components = [{ signature = "XTRC", name = "Tracks" }]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(schema.nvt["creator"], "tests");
+        assert_eq!(schema.matrix_types, sample().matrix_types);
+        assert_eq!(schema.frame_types, sample().frame_types);
+    }
+
+    #[test]
+    fn test_display_empty() {
+        assert_eq!(Schema::default().to_string(), "No type declarations\n");
+    }
+}
@@ -0,0 +1,69 @@
+//! Column layouts of the standard SDIF matrix types.
+//!
+//! These tables are the single source of truth for default column names,
+//! used when declaring types, choosing conversion defaults, and checking
+//! that data has the expected shape.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::{schemas, SdifFile};
+//!
+//! let writer = SdifFile::builder()
+//!     .create("output.sdif")?
+//!     .add_matrix_type("1TRC", schemas::TRC_COLUMNS)?
+//!     .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+//!     .build()?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+/// Columns of 1TRC (sinusoidal tracks) matrices.
+pub const TRC_COLUMNS: &[&str] = &["Index", "Frequency", "Amplitude", "Phase"];
+
+/// Columns of 1HRM (harmonic partials) matrices.
+pub const HRM_COLUMNS: &[&str] = &["Index", "Frequency", "Amplitude", "Phase"];
+
+/// Columns of 1FQ0 (fundamental frequency) matrices.
+pub const FQ0_COLUMNS: &[&str] = &["Frequency", "Confidence"];
+
+/// Columns of 1RES (resonance) matrices.
+pub const RES_COLUMNS: &[&str] = &["Frequency", "Amplitude", "DecayRate", "Phase"];
+
+/// Columns of 1STF (short-time Fourier transform) matrices.
+pub const STF_COLUMNS: &[&str] = &["Real", "Imaginary"];
+
+/// Look up the standard column names for a matrix signature.
+///
+/// Returns `None` for signatures without a standard layout.
+///
+/// # Example
+///
+/// ```
+/// use sdif_rs::schemas;
+///
+/// assert_eq!(schemas::standard_columns("1FQ0"), Some(schemas::FQ0_COLUMNS));
+/// assert_eq!(schemas::standard_columns("XABC"), None);
+/// ```
+pub fn standard_columns(signature: &str) -> Option<&'static [&'static str]> {
+    match signature {
+        "1TRC" => Some(TRC_COLUMNS),
+        "1HRM" => Some(HRM_COLUMNS),
+        "1FQ0" => Some(FQ0_COLUMNS),
+        "1RES" => Some(RES_COLUMNS),
+        "1STF" => Some(STF_COLUMNS),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_columns() {
+        assert_eq!(standard_columns("1TRC"), Some(TRC_COLUMNS));
+        assert_eq!(standard_columns("1HRM").map(<[_]>::len), Some(4));
+        assert_eq!(standard_columns("1RES").unwrap()[2], "DecayRate");
+        assert_eq!(standard_columns("1trc"), None);
+    }
+}
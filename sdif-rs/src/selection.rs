@@ -0,0 +1,303 @@
+//! Selections of the data read from a file, in the SDIF library's syntax.
+//!
+//! The SDIF library accepts a selection appended to a file name after
+//! `::`, as in `voice.sdif::#0:1TRC@0.5-1.5`, and IRCAM tools read only
+//! the selected streams, frame and matrix types, times, rows and columns.
+//! A [`Selection`] builds such a string, and
+//! [`SdifFile::open_with_selection()`](crate::SdifFile::open_with_selection)
+//! opens a file with it:
+//!
+//! - Frames outside the selected streams, frame types or times are skipped
+//!   by [`SdifFile::frames()`](crate::SdifFile::frames).
+//! - Matrices of other types are skipped by
+//!   [`Frame::matrices()`](crate::Frame::matrices), so a frame may have
+//!   fewer matrices than [`Frame::num_matrices()`](crate::Frame::num_matrices).
+//! - Only the selected rows and columns of each matrix are read, and
+//!   [`Matrix::rows()`](crate::Matrix::rows) and
+//!   [`Matrix::cols()`](crate::Matrix::cols) count those.
+//!
+//! Each part lists values and inclusive ranges; a value matches if it is
+//! in any of them. Parts that are left empty select everything. Rows and
+//! columns are counted from 1, as in selection strings.
+//!
+//! The selection is applied by the library while reading, so it needs a
+//! library with the selection functions, see
+//! [`BackendInfo::selections`](crate::BackendInfo::selections).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::selection::Selection;
+//! use sdif_rs::SdifFile;
+//!
+//! // Frequencies of the partials between 0.5s and 1.5s
+//! let selection = Selection::new()
+//!     .frame_signature("1TRC")
+//!     .time_range(0.5..=1.5)
+//!     .column(2);
+//! assert_eq!(selection.to_string(), "::1TRC.2@0.5-1.5");
+//!
+//! let file = SdifFile::open_with_selection("voice.sdif", &selection)?;
+//! for frame in file.frames() {
+//!     for matrix in frame?.matrices() {
+//!         println!("{:?}", matrix?.data_f64()?);
+//!     }
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::error::{Error, Result};
+use crate::signature::string_to_signature;
+
+/// Characters that separate the parts of a selection string.
+const SEPARATORS: &[char] = &['#', ':', '/', '.', '_', '@', ',', '-', '+'];
+
+/// Streams, types, times, rows and columns to read from a file.
+///
+/// Empty by default, which selects everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection {
+    /// Stream IDs to read.
+    pub streams: Vec<RangeInclusive<u32>>,
+
+    /// Frame signatures to read.
+    pub frame_signatures: Vec<String>,
+
+    /// Matrix signatures to read.
+    pub matrix_signatures: Vec<String>,
+
+    /// Frame times to read, in seconds.
+    pub times: Vec<RangeInclusive<f64>>,
+
+    /// Matrix rows to read, counted from 1.
+    pub rows: Vec<RangeInclusive<u32>>,
+
+    /// Matrix columns to read, counted from 1.
+    pub columns: Vec<RangeInclusive<u32>>,
+}
+
+impl Selection {
+    /// Create a selection of everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select stream `id`.
+    pub fn stream(self, id: u32) -> Self {
+        self.streams(id..=id)
+    }
+
+    /// Select the streams with IDs in `ids`.
+    pub fn streams(mut self, ids: RangeInclusive<u32>) -> Self {
+        self.streams.push(ids);
+        self
+    }
+
+    /// Select frames with signature `signature`.
+    pub fn frame_signature(mut self, signature: &str) -> Self {
+        self.frame_signatures.push(signature.to_string());
+        self
+    }
+
+    /// Select matrices with signature `signature`.
+    pub fn matrix_signature(mut self, signature: &str) -> Self {
+        self.matrix_signatures.push(signature.to_string());
+        self
+    }
+
+    /// Select frames at exactly `time` seconds.
+    pub fn time(self, time: f64) -> Self {
+        self.time_range(time..=time)
+    }
+
+    /// Select frames with times in `range`, in seconds.
+    pub fn time_range(mut self, range: RangeInclusive<f64>) -> Self {
+        self.times.push(range);
+        self
+    }
+
+    /// Select matrix row `row`, counted from 1.
+    pub fn row(self, row: u32) -> Self {
+        self.rows(row..=row)
+    }
+
+    /// Select the matrix rows in `rows`, counted from 1.
+    pub fn rows(mut self, rows: RangeInclusive<u32>) -> Self {
+        self.rows.push(rows);
+        self
+    }
+
+    /// Select matrix column `column`, counted from 1.
+    pub fn column(self, column: u32) -> Self {
+        self.columns(column..=column)
+    }
+
+    /// Select the matrix columns in `columns`, counted from 1.
+    pub fn columns(mut self, columns: RangeInclusive<u32>) -> Self {
+        self.columns.push(columns);
+        self
+    }
+
+    /// Check whether the selection selects everything.
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+            && self.frame_signatures.is_empty()
+            && self.matrix_signatures.is_empty()
+            && self.times.is_empty()
+            && self.rows.is_empty()
+            && self.columns.is_empty()
+    }
+
+    /// Check that the selection can be written as a selection string.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidSignature`] if a signature is not 4 ASCII
+    ///   characters, or holds a separator such as `/` or `.`
+    /// - [`Error::InvalidFormat`] if a range is reversed, a time is
+    ///   negative or not finite, or a row or column is 0
+    pub fn validate(&self) -> Result<()> {
+        for signature in self.frame_signatures.iter().chain(&self.matrix_signatures) {
+            string_to_signature(signature)?;
+            if signature.contains(SEPARATORS) {
+                return Err(Error::invalid_signature(signature));
+            }
+        }
+
+        for range in &self.times {
+            let (start, end) = (*range.start(), *range.end());
+            if !(start.is_finite() && end.is_finite() && 0.0 <= start && start <= end) {
+                return Err(Error::invalid_format(format!(
+                    "Invalid selection time range: {}-{}",
+                    start, end
+                )));
+            }
+        }
+
+        let ranges = [
+            ("stream", &self.streams),
+            ("row", &self.rows),
+            ("column", &self.columns),
+        ];
+        for (part, ranges) in ranges {
+            for range in ranges {
+                if range.start() > range.end() || (part != "stream" && *range.start() == 0) {
+                    return Err(Error::invalid_format(format!(
+                        "Invalid selection {} range: {}-{}",
+                        part,
+                        range.start(),
+                        range.end()
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check whether rows or columns are selected.
+    pub(crate) fn selects_cells(&self) -> bool {
+        !(self.rows.is_empty() && self.columns.is_empty())
+    }
+}
+
+/// Formats the selection string, starting with `::`, or nothing for an
+/// empty selection.
+///
+/// The parts are written in the order stream, frame, matrix, column, row
+/// and time, which keeps the time's decimal point from being read as the
+/// column separator.
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        f.write_str("::")?;
+
+        write_part(f, "#", &self.streams)?;
+        if !self.frame_signatures.is_empty() {
+            // The frame separator can be left out right after `::`
+            let separator = if self.streams.is_empty() { "" } else { ":" };
+            write!(f, "{}{}", separator, self.frame_signatures.join(","))?;
+        }
+        if !self.matrix_signatures.is_empty() {
+            write!(f, "/{}", self.matrix_signatures.join(","))?;
+        }
+        write_part(f, ".", &self.columns)?;
+        write_part(f, "_", &self.rows)?;
+        write_part(f, "@", &self.times)
+    }
+}
+
+/// Write a list of values and ranges after `separator`, if there are any.
+fn write_part<T>(
+    f: &mut fmt::Formatter<'_>,
+    separator: &str,
+    ranges: &[RangeInclusive<T>],
+) -> fmt::Result
+where
+    T: PartialEq + fmt::Display,
+{
+    for (i, range) in ranges.iter().enumerate() {
+        f.write_str(if i == 0 { separator } else { "," })?;
+        if range.start() == range.end() {
+            write!(f, "{}", range.start())?;
+        } else {
+            write!(f, "{}-{}", range.start(), range.end())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_string() {
+        assert_eq!(Selection::new().to_string(), "");
+
+        let selection = Selection::new()
+            .stream(0)
+            .streams(2..=3)
+            .frame_signature("1TRC")
+            .matrix_signature("1TRC")
+            .columns(1..=2)
+            .row(4)
+            .time_range(0.5..=1.5);
+        assert_eq!(selection.to_string(), "::#0,2-3:1TRC/1TRC.1-2_4@0.5-1.5");
+
+        assert_eq!(Selection::new().time(2.0).to_string(), "::@2");
+        assert_eq!(
+            Selection::new().frame_signature("1FQ0").to_string(),
+            "::1FQ0"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Selection::new()
+            .stream(0)
+            .row(1)
+            .time(0.0)
+            .validate()
+            .is_ok());
+
+        assert!(matches!(
+            Selection::new().frame_signature("1TR").validate(),
+            Err(Error::InvalidSignature { .. })
+        ));
+        assert!(matches!(
+            Selection::new().matrix_signature("1/RC").validate(),
+            Err(Error::InvalidSignature { .. })
+        ));
+        assert!(Selection::new().time_range(2.0..=1.0).validate().is_err());
+        assert!(Selection::new().time(-1.0).validate().is_err());
+        assert!(Selection::new().row(0).validate().is_err());
+        let (start, end) = (3, 2);
+        assert!(Selection::new().columns(start..=end).validate().is_err());
+    }
+}
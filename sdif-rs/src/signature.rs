@@ -16,6 +16,9 @@
 //! let sig = string_to_signature("1TRC").unwrap();
 //! assert_eq!(signature_to_string(sig), "1TRC");
 //! ```
+//!
+//! Signatures known in advance can be written with the [`sig!`](crate::sig)
+//! macro instead, which checks them at compile time.
 
 use crate::error::{Error, Result};
 
@@ -107,6 +110,58 @@ pub const fn sig_const(s: &[u8; 4]) -> Signature {
         | (s[3] as u32)
 }
 
+/// Create a signature from a string at compile time.
+///
+/// Panics if the string is not 4 ASCII bytes, which is a compile error in
+/// a const context. Used by the [`sig!`](crate::sig) macro.
+#[doc(hidden)]
+pub const fn sig_const_str(s: &str) -> Signature {
+    let bytes = s.as_bytes();
+    if bytes.len() != 4 {
+        panic!("SDIF signatures must be exactly 4 bytes");
+    }
+
+    let mut i = 0;
+    while i < 4 {
+        if !bytes[i].is_ascii() {
+            panic!("SDIF signatures must be ASCII");
+        }
+        i += 1;
+    }
+
+    sig_const(&[bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Create a [`Signature`] constant, checked at compile time.
+///
+/// The literal must be exactly 4 ASCII bytes; anything else fails to
+/// compile instead of returning [`Error::InvalidSignature`] at runtime
+/// like [`string_to_signature()`].
+///
+/// # Example
+///
+/// ```
+/// use sdif_rs::{sig, signature_to_string, Signature};
+///
+/// const MARKERS: Signature = sig!("XMRK");
+///
+/// assert_eq!(sig!("1TRC"), 0x31545243);
+/// assert_eq!(signature_to_string(MARKERS), "XMRK");
+/// ```
+///
+/// Invalid signatures are rejected by the compiler:
+///
+/// ```compile_fail
+/// let sig = sdif_rs::sig!("1TRCX");
+/// ```
+#[macro_export]
+macro_rules! sig {
+    ($s:literal) => {{
+        const SIGNATURE: $crate::Signature = $crate::sig_const_str($s);
+        SIGNATURE
+    }};
+}
+
 /// Create a signature from a byte slice (runtime version).
 fn sig_const_from_slice(s: &[u8]) -> Signature {
     debug_assert_eq!(s.len(), 4);
@@ -172,6 +227,12 @@ mod tests {
         assert_eq!(sig_const(b"1TRC"), 0x31545243);
     }
 
+    #[test]
+    fn test_sig_macro() {
+        assert_eq!(crate::sig!("1TRC"), string_to_signature("1TRC").unwrap());
+        assert_eq!(sig_const_str("XMRK"), sig_const(b"XMRK"));
+    }
+
     #[test]
     fn test_known_signatures() {
         assert!(is_known_signature(crate::signatures::TRC));
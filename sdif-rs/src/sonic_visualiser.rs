@@ -0,0 +1,473 @@
+//! Import of Sonic Visualiser annotation layers.
+//!
+//! Sonic Visualiser exports annotation layers as CSV, one event per line,
+//! with columns depending on the layer type:
+//!
+//! | Layer         | Columns                           | Options                                 |
+//! |---------------|-----------------------------------|-----------------------------------------|
+//! | Time instants | `time,label`                      | [`CsvImportOptions::time_instants()`]   |
+//! | Time values   | `time,value,label`                | [`CsvImportOptions::default()`]         |
+//! | Regions       | `time,value,duration,label`       | [`CsvImportOptions::regions()`]         |
+//! | Notes         | `time,value,duration,level,label` | [`CsvImportOptions::notes()`]           |
+//!
+//! Other layouts can be read by setting the column indices directly.
+//!
+//! Events become either 1FQ0 frames, for pitch layers, or 1MRK marker
+//! frames holding a 1x3 matrix of [`MARKER_COLUMNS`]. Marker labels are
+//! stored in an NVT table with one entry per labelled marker,
+//!
+//! ```text
+//! marker.<id>    <label>
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::sonic_visualiser::{self, CsvImportOptions};
+//!
+//! let options = CsvImportOptions::time_instants();
+//! let count = sonic_visualiser::import("onsets.csv", "onsets.sdif", &options)?;
+//! println!("Imported {} markers", count);
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::metrics::{self, ConversionTimer};
+use crate::schemas;
+
+/// Signature of marker frames and matrices.
+pub const MARKER_SIGNATURE: &str = "1MRK";
+
+/// Columns of marker matrices.
+///
+/// `Id` numbers the markers from 0 in time order, `Duration` is 0 for
+/// instants, and `Value` is 0 when the layer has no values.
+pub const MARKER_COLUMNS: &[&str] = &["Id", "Duration", "Value"];
+
+/// Frame/matrix signature of pitch data.
+const PITCH_SIGNATURE: &str = "1FQ0";
+
+/// Prefix of all marker label NVT keys.
+const LABEL_PREFIX: &str = "marker.";
+
+/// The kind of stream annotations are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImportTarget {
+    /// 1MRK marker frames, with labels in an NVT table.
+    #[default]
+    Markers,
+
+    /// 1FQ0 frames, taking the frequency from the value column.
+    ///
+    /// Values of 0 or below, which Sonic Visualiser uses for unvoiced
+    /// stretches, are written with confidence 0.
+    Pitch,
+}
+
+/// Column mapping and output options for [`import()`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CsvImportOptions {
+    /// Column of event times, in seconds.
+    pub time_column: usize,
+
+    /// Column of event values, if any.
+    pub value_column: Option<usize>,
+
+    /// Column of event durations in seconds, if any.
+    pub duration_column: Option<usize>,
+
+    /// Column of event labels, if any.
+    pub label_column: Option<usize>,
+
+    /// Skip the first line as a header.
+    pub header: bool,
+
+    /// Field delimiter.
+    pub delimiter: char,
+
+    /// Kind of stream to write.
+    pub target: ImportTarget,
+}
+
+impl Default for CsvImportOptions {
+    fn default() -> Self {
+        Self {
+            time_column: 0,
+            value_column: Some(1),
+            duration_column: None,
+            label_column: Some(2),
+            header: false,
+            delimiter: ',',
+            target: ImportTarget::Markers,
+        }
+    }
+}
+
+impl CsvImportOptions {
+    /// Create options for a time-value layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create options for a time instants layer.
+    pub fn time_instants() -> Self {
+        Self {
+            value_column: None,
+            label_column: Some(1),
+            ..Self::default()
+        }
+    }
+
+    /// Create options for a regions layer.
+    pub fn regions() -> Self {
+        Self {
+            duration_column: Some(2),
+            label_column: Some(3),
+            ..Self::default()
+        }
+    }
+
+    /// Create options for a notes layer.
+    pub fn notes() -> Self {
+        Self {
+            duration_column: Some(2),
+            label_column: Some(4),
+            ..Self::default()
+        }
+    }
+
+    /// Set the column of event times.
+    pub fn time_column(mut self, column: usize) -> Self {
+        self.time_column = column;
+        self
+    }
+
+    /// Set the column of event values.
+    pub fn value_column(mut self, column: Option<usize>) -> Self {
+        self.value_column = column;
+        self
+    }
+
+    /// Set the column of event durations.
+    pub fn duration_column(mut self, column: Option<usize>) -> Self {
+        self.duration_column = column;
+        self
+    }
+
+    /// Set the column of event labels.
+    pub fn label_column(mut self, column: Option<usize>) -> Self {
+        self.label_column = column;
+        self
+    }
+
+    /// Set whether the first line is a header.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Set the field delimiter.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set the kind of stream to write.
+    pub fn target(mut self, target: ImportTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Check that the options are consistent.
+    fn validate(&self) -> Result<()> {
+        if self.target == ImportTarget::Pitch && self.value_column.is_none() {
+            return Err(Error::invalid_format("Pitch import needs a value column"));
+        }
+        if self.delimiter == '"' {
+            return Err(Error::invalid_format("CSV delimiter cannot be a quote"));
+        }
+        Ok(())
+    }
+
+    /// Serialize the options to pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Json`] if serialization fails
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// One event of an annotation layer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotation {
+    /// Start time in seconds.
+    pub time: f64,
+
+    /// Duration in seconds, 0 for instants.
+    pub duration: f64,
+
+    /// Value, if the layer has values.
+    pub value: Option<f64>,
+
+    /// Label, if present and not empty.
+    pub label: Option<String>,
+}
+
+/// Parse an annotation layer CSV export.
+///
+/// Events are returned sorted by time. Blank lines are skipped.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the options are inconsistent, or a line
+///   lacks a mapped column or has an invalid number in one
+pub fn parse_csv(text: &str, options: &CsvImportOptions) -> Result<Vec<Annotation>> {
+    options.validate()?;
+
+    let mut annotations = Vec::new();
+    let lines = text.lines().enumerate().skip(usize::from(options.header));
+
+    for (index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = split_fields(line, options.delimiter);
+        let field = |column: usize| {
+            fields.get(column).map(String::as_str).ok_or_else(|| {
+                Error::invalid_format(format!("Line {} has no column {}", index + 1, column))
+            })
+        };
+        let number = |column: usize| {
+            let text = field(column)?;
+            text.trim().parse::<f64>().map_err(|_| {
+                Error::invalid_format(format!(
+                    "Line {} column {}: invalid number {:?}",
+                    index + 1,
+                    column,
+                    text
+                ))
+            })
+        };
+
+        annotations.push(Annotation {
+            time: number(options.time_column)?,
+            duration: options
+                .duration_column
+                .map(number)
+                .transpose()?
+                .unwrap_or(0.0),
+            value: options.value_column.map(number).transpose()?,
+            label: match options.label_column {
+                // A trailing empty label is often left out entirely
+                Some(column) => fields
+                    .get(column)
+                    .filter(|label| !label.is_empty())
+                    .cloned(),
+                None => None,
+            },
+        });
+    }
+
+    annotations.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(annotations)
+}
+
+/// Read an annotation layer CSV export.
+///
+/// # Errors
+///
+/// - [`Error::Io`] if the file can't be read
+/// - Any error from [`parse_csv()`]
+pub fn read_csv(path: impl AsRef<Path>, options: &CsvImportOptions) -> Result<Vec<Annotation>> {
+    parse_csv(&fs::read_to_string(path)?, options)
+}
+
+/// Write annotations as an SDIF stream.
+///
+/// Returns the number of frames written.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the options are inconsistent, an event
+///   has no value for pitch output, or a label contains a null byte
+/// - Any error from creating or writing the file
+pub fn write_sdif(
+    annotations: &[Annotation],
+    path: impl AsRef<Path>,
+    options: &CsvImportOptions,
+) -> Result<usize> {
+    options.validate()?;
+
+    let mut writer = match options.target {
+        ImportTarget::Markers => {
+            let labels: Vec<(String, &str)> = annotations
+                .iter()
+                .enumerate()
+                .filter_map(|(id, a)| Some((label_key(id), a.label.as_deref()?)))
+                .collect();
+            SdifFile::builder()
+                .create(path)?
+                .add_nvt(labels.iter().map(|(key, label)| (key.as_str(), *label)))?
+                .add_matrix_type(MARKER_SIGNATURE, MARKER_COLUMNS)?
+                .add_frame_type(MARKER_SIGNATURE, &["1MRK Markers"])?
+                .build()?
+        }
+        ImportTarget::Pitch => SdifFile::builder()
+            .create(path)?
+            .add_matrix_type(PITCH_SIGNATURE, schemas::FQ0_COLUMNS)?
+            .add_frame_type(PITCH_SIGNATURE, &["1FQ0 FundamentalFrequencyEstimate"])?
+            .build()?,
+    };
+
+    for (id, annotation) in annotations.iter().enumerate() {
+        match options.target {
+            ImportTarget::Markers => writer.write_frame_one_matrix(
+                MARKER_SIGNATURE,
+                annotation.time,
+                MARKER_SIGNATURE,
+                1,
+                3,
+                &[
+                    id as f64,
+                    annotation.duration,
+                    annotation.value.unwrap_or(0.0),
+                ],
+            )?,
+            ImportTarget::Pitch => {
+                let frequency = annotation.value.ok_or_else(|| {
+                    Error::invalid_format(format!(
+                        "Pitch annotation at {}s has no value",
+                        annotation.time
+                    ))
+                })?;
+                let confidence = if frequency > 0.0 { 1.0 } else { 0.0 };
+                writer.write_frame_one_matrix(
+                    PITCH_SIGNATURE,
+                    annotation.time,
+                    PITCH_SIGNATURE,
+                    1,
+                    2,
+                    &[frequency, confidence],
+                )?
+            }
+        }
+    }
+    writer.close()?;
+
+    Ok(annotations.len())
+}
+
+/// Convert a Sonic Visualiser annotation layer CSV export to SDIF.
+///
+/// Returns the number of frames written.
+///
+/// # Errors
+///
+/// - Any error from [`read_csv()`] or [`write_sdif()`]
+pub fn import(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &CsvImportOptions,
+) -> Result<usize> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_SONIC_VISUALISER_IMPORT);
+    let annotations = read_csv(input, options)?;
+    let frames = write_sdif(&annotations, output, options)?;
+    timer.finish();
+    Ok(frames)
+}
+
+/// Get the NVT key holding the label of marker `id`.
+fn label_key(id: usize) -> String {
+    format!("{}{}", LABEL_PREFIX, id)
+}
+
+/// Split a CSV line into fields.
+///
+/// Fields may be quoted, with `""` standing for a quote inside quotes, as
+/// Sonic Visualiser does for labels containing the delimiter.
+pub(crate) fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_fields() {
+        assert_eq!(split_fields("0.5,440,a", ','), ["0.5", "440", "a"]);
+        assert_eq!(
+            split_fields("1,\"verse, \"\"A\"\"\",", ','),
+            ["1", "verse, \"A\"", ""]
+        );
+        assert_eq!(split_fields("1\t2", '\t'), ["1", "2"]);
+    }
+
+    #[test]
+    fn test_parse_layouts() {
+        let instants = parse_csv("0.5,onset\n0.25\n", &CsvImportOptions::time_instants()).unwrap();
+        assert_eq!(instants.len(), 2);
+        assert_eq!(instants[0].time, 0.25);
+        assert_eq!(instants[0].label, None);
+        assert_eq!(instants[1].label.as_deref(), Some("onset"));
+        assert_eq!(instants[1].value, None);
+
+        let regions = parse_csv("1.0,3,0.5,chorus\n", &CsvImportOptions::regions()).unwrap();
+        assert_eq!(
+            regions[0],
+            Annotation {
+                time: 1.0,
+                duration: 0.5,
+                value: Some(3.0),
+                label: Some("chorus".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_header_and_errors() {
+        let options = CsvImportOptions::new().header(true);
+        assert_eq!(
+            parse_csv("time,value,label\n0,1,x\n", &options)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let err = parse_csv("0,abc\n", &CsvImportOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("Line 1"));
+        assert!(parse_csv("0\n", &CsvImportOptions::new()).is_err());
+
+        let options = CsvImportOptions::time_instants().target(ImportTarget::Pitch);
+        assert!(parse_csv("0,a\n", &options).is_err());
+    }
+}
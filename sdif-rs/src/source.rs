@@ -0,0 +1,315 @@
+//! The audio file an analysis was computed from, recorded in NVT entries.
+//!
+//! [`SdifFileBuilder::set_source_audio()`](crate::SdifFileBuilder::set_source_audio)
+//! describes the analysed sound file with the NVT entries
+//!
+//! ```text
+//! source.file           file name of the sound
+//! source.sha256         SHA-256 of the file contents, in hex
+//! source.duration       duration in seconds (WAV files, `wav` feature)
+//! source.sample_rate    sample rate in Hz (WAV files, `wav` feature)
+//! ```
+//!
+//! and [`SdifFile::source_audio()`](crate::SdifFile::source_audio) reads
+//! them back, so an analysis stays traceable to the exact audio it came
+//! from even after the sound is renamed or moved.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("partials.sdif")?;
+//! if let Some(source) = file.source_audio() {
+//!     if !source.matches("sounds/voice.wav")? {
+//!         eprintln!("voice.wav changed since {} was analysed", source.file_name);
+//!     }
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+/// Prefix of all source audio NVT keys.
+const PREFIX: &str = "source.";
+
+/// Size of the buffer used when hashing files.
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Description of an analysed sound file.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceAudio {
+    /// File name of the sound, without its directory.
+    pub file_name: String,
+
+    /// SHA-256 of the file contents, as lowercase hex.
+    pub sha256: String,
+
+    /// Duration in seconds, if known.
+    pub duration: Option<f64>,
+
+    /// Sample rate in Hz, if known.
+    pub sample_rate: Option<f64>,
+}
+
+impl SourceAudio {
+    /// Describe a sound file.
+    ///
+    /// The file is read in full to compute its hash. With the `wav`
+    /// feature, the duration and sample rate of `.wav` files are read from
+    /// their header; other files leave them unset.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the path has no file name, or a
+    ///   `.wav` file has an invalid header
+    /// - [`Error::Io`] if the file can't be read
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                Error::invalid_format(format!("Path has no file name: {}", path.display()))
+            })?;
+
+        let (duration, sample_rate) = read_header(path)?;
+        Ok(SourceAudio {
+            file_name: file_name.to_string(),
+            sha256: hash_file(path)?,
+            duration,
+            sample_rate,
+        })
+    }
+
+    /// Check whether a file has the contents this description was made from.
+    ///
+    /// Only the hash is compared, so the file may have been renamed.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be read
+    pub fn matches(&self, path: impl AsRef<Path>) -> Result<bool> {
+        Ok(hash_file(path.as_ref())? == self.sha256)
+    }
+
+    /// Convert to NVT entries.
+    pub(crate) fn to_nvt_entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            (key("file"), self.file_name.clone()),
+            (key("sha256"), self.sha256.clone()),
+        ];
+        if let Some(duration) = self.duration {
+            entries.push((key("duration"), duration.to_string()));
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            entries.push((key("sample_rate"), sample_rate.to_string()));
+        }
+        entries
+    }
+}
+
+/// Get the NVT key of source audio field `field`.
+fn key(field: &str) -> String {
+    format!("{}{}", PREFIX, field)
+}
+
+/// Read the source audio description from a file's NVTs.
+///
+/// Returns `None` unless both the file name and hash are present. Numbers
+/// that don't parse are left unset.
+pub(crate) fn from_nvts(nvts: &[HashMap<String, String>]) -> Option<SourceAudio> {
+    let get = |field: &str| nvts.iter().find_map(|nvt| nvt.get(&key(field)));
+    let number = |field: &str| get(field).and_then(|value| value.trim().parse().ok());
+
+    Some(SourceAudio {
+        file_name: get("file")?.clone(),
+        sha256: get("sha256")?.clone(),
+        duration: number("duration"),
+        sample_rate: number("sample_rate"),
+    })
+}
+
+/// Compute the SHA-256 of a file's contents, as lowercase hex.
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Read the duration and sample rate of a WAV file from its header.
+///
+/// Other files, by extension, have neither.
+#[cfg(feature = "wav")]
+fn read_header(path: &Path) -> Result<(Option<f64>, Option<f64>)> {
+    let is_wav = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    if !is_wav {
+        return Ok((None, None));
+    }
+
+    let reader = hound::WavReader::open(path).map_err(|e| wav_error(path, e))?;
+    let sample_rate = f64::from(reader.spec().sample_rate);
+    Ok((Some(f64::from(reader.duration()) / sample_rate), Some(sample_rate)))
+}
+
+/// Convert an error reading or writing the WAV file at `path`.
+#[cfg(feature = "wav")]
+fn wav_error(path: &Path, error: hound::Error) -> Error {
+    match error {
+        hound::Error::IoError(e) => Error::Io(e),
+        e => Error::invalid_format(format!("Invalid WAV file {}: {}", path.display(), e)),
+    }
+}
+
+/// Read a WAV file as mono samples in [-1, 1], with its sample rate.
+///
+/// Channels are mixed down by averaging.
+#[cfg(any(feature = "pitch", feature = "resonance"))]
+pub(crate) fn read_mono(path: &Path) -> Result<(Vec<f32>, f64)> {
+    let mut reader = hound::WavReader::open(path).map_err(|e| wav_error(path, e))?;
+    let spec = reader.spec();
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| wav_error(path, e))?,
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|s| s as f32 * scale))
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e| wav_error(path, e))?
+        }
+    };
+
+    let channels = usize::from(spec.channels.max(1));
+    let samples = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok((samples, f64::from(spec.sample_rate)))
+}
+
+/// Write mono samples in [-1, 1] to a 16-bit WAV file.
+///
+/// Samples outside the range are clipped.
+#[cfg(feature = "synth")]
+pub(crate) fn write_mono(path: &Path, samples: &[f32], sample_rate: u32) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(|e| wav_error(path, e))?;
+    for &sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16;
+        writer.write_sample(sample).map_err(|e| wav_error(path, e))?;
+    }
+    writer.finalize().map_err(|e| wav_error(path, e))
+}
+
+/// Without the `wav` feature no headers are read.
+#[cfg(not(feature = "wav"))]
+fn read_header(_path: &Path) -> Result<(Option<f64>, Option<f64>)> {
+    Ok((None, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nvt_roundtrip() {
+        let source = SourceAudio {
+            file_name: "voice.wav".to_string(),
+            sha256: "ab".repeat(32),
+            duration: Some(2.5),
+            sample_rate: Some(44100.0),
+        };
+
+        let nvt: HashMap<String, String> = source.to_nvt_entries().into_iter().collect();
+        assert_eq!(nvt["source.file"], "voice.wav");
+        assert_eq!(nvt["source.sample_rate"], "44100");
+        assert_eq!(from_nvts(&[nvt]), Some(source));
+    }
+
+    #[test]
+    fn test_from_nvts_requires_hash() {
+        let nvt: HashMap<String, String> = [(key("file"), "voice.wav".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(from_nvts(&[nvt]), None);
+    }
+
+    #[test]
+    fn test_hash_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("abc.raw");
+        std::fs::write(&path, b"abc")?;
+
+        let source = SourceAudio::from_path(&path)?;
+        assert_eq!(source.file_name, "abc.raw");
+        assert_eq!(
+            source.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(source.duration, None);
+        assert!(source.matches(&path)?);
+
+        std::fs::write(&path, b"abd")?;
+        assert!(!source.matches(&path)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "wav")]
+    #[test]
+    fn test_wav_header() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("tone.WAV");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..4000 * 2 {
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let source = SourceAudio::from_path(&path)?;
+        assert_eq!(source.sample_rate, Some(8000.0));
+        assert_eq!(source.duration, Some(0.5));
+        Ok(())
+    }
+}
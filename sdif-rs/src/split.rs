@@ -0,0 +1,170 @@
+//! Splitting an SDIF file into one file per stream or per frame signature.
+//!
+//! Some consumers can only handle single-stream files. [`split_file()`]
+//! writes every frame of the input to an output file chosen by its frame
+//! signature or stream ID, e.g. `input.1TRC.sdif` and `input.1FQ0.sdif`.
+//! Each output carries the input's NVTs and the type declarations for the
+//! frames it holds.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::split::{self, SplitBy};
+//!
+//! for output in split::split_file("analysis.sdif", "out", SplitBy::Signature)? {
+//!     println!("{}: {} frames", output.path.display(), output.frames);
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+
+use crate::copy::copy_header;
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame::Frame;
+use crate::metrics::{self, ConversionTimer};
+use crate::streams;
+use crate::writer::SdifWriter;
+
+/// How frames are assigned to output files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SplitBy {
+    /// One file per frame signature, named `<stem>.<signature>.sdif`.
+    Signature,
+
+    /// One file per stream, named `<stem>.<stream>.sdif`, where `<stream>`
+    /// is the stream's name if the file names it and its ID otherwise.
+    Stream,
+}
+
+/// One file written by a split.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SplitOutput {
+    /// Signature, stream name, or stream ID the file was split on.
+    pub key: String,
+
+    /// Path of the written file.
+    pub path: PathBuf,
+
+    /// Number of frames written to the file.
+    pub frames: usize,
+}
+
+/// Split an SDIF file into one file per stream or per frame signature.
+///
+/// Output files are written to `output_dir`, named after the input file
+/// and the key they were split on, and listed in order of first
+/// appearance in the input. Stream IDs and frame times are kept as they
+/// are. Frames without matrices are left out.
+///
+/// The input is read twice: once to find the signatures in each output,
+/// and once to copy the frames.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the input path has no file name, or a
+///   frame signature to split on contains a path separator
+/// - Any error from opening or reading the input
+/// - Any error from creating or writing the outputs
+pub fn split_file(
+    input: impl AsRef<Path>,
+    output_dir: impl AsRef<Path>,
+    by: SplitBy,
+) -> Result<Vec<SplitOutput>> {
+    let timer = ConversionTimer::start(metrics::CONVERSION_SPLIT);
+    let input = input.as_ref();
+    let stem = input
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| {
+            Error::invalid_format(format!("Input path has no file name: {}", input.display()))
+        })?;
+
+    let file = SdifFile::open(input)?;
+    let stream_names: HashMap<u32, String> = streams::from_entries(file.nvts().iter().flatten())
+        .into_iter()
+        .map(|(name, id)| (id, name))
+        .collect();
+    let key_of = |frame: &Frame<'_>| match by {
+        SplitBy::Signature => frame.signature(),
+        SplitBy::Stream => stream_names
+            .get(&frame.stream_id())
+            .cloned()
+            .unwrap_or_else(|| frame.stream_id().to_string()),
+    };
+
+    // First pass: the frame signatures that end up in each output
+    let mut signatures: IndexMap<String, BTreeSet<String>> = IndexMap::new();
+    for frame in file.frames() {
+        let frame = frame?;
+        if frame.num_matrices() > 0 {
+            signatures
+                .entry(key_of(&frame))
+                .or_default()
+                .insert(frame.signature());
+        }
+    }
+
+    let schema = file.describe_types()?;
+    let mut outputs: IndexMap<String, (SplitOutput, SdifWriter)> = IndexMap::new();
+    for (key, sigs) in &signatures {
+        // Stream names are checked when read, but signatures can hold any
+        // character
+        if key.contains(['/', '\\']) {
+            return Err(Error::invalid_format(format!(
+                "Cannot name an output file after signature {:?}",
+                key
+            )));
+        }
+        let path = output_dir.as_ref().join(format!("{}.{}.sdif", stem, key));
+        let sigs: Vec<&str> = sigs.iter().map(String::as_str).collect();
+
+        let writer = copy_header(
+            &file,
+            &schema.subset(&sigs),
+            SdifFile::builder().create(&path)?,
+        )?
+        .build()?;
+
+        let output = SplitOutput {
+            key: key.clone(),
+            path,
+            frames: 0,
+        };
+        outputs.insert(key.clone(), (output, writer));
+    }
+
+    // Second pass: copy each frame to its output
+    let file = SdifFile::open(input)?;
+    for frame in file.frames() {
+        let frame = frame?;
+        let Some((output, writer)) = outputs.get_mut(&key_of(&frame)) else {
+            continue;
+        };
+
+        let frame = frame.into_data()?;
+        if frame.matrices.is_empty() {
+            continue;
+        }
+
+        writer.write_frame_data(&frame)?;
+        output.frames += 1;
+    }
+
+    let outputs = outputs
+        .into_values()
+        .map(|(output, writer)| {
+            writer.close()?;
+            Ok(output)
+        })
+        .collect::<Result<_>>()?;
+
+    timer.finish();
+    Ok(outputs)
+}
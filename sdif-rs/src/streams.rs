@@ -0,0 +1,97 @@
+//! Stream names recorded in NVT entries.
+//!
+//! SDIF identifies streams by integer IDs. Named streams map a name to an
+//! ID with one NVT entry per stream,
+//!
+//! ```text
+//! stream.<name>    <id>
+//! ```
+//!
+//! declared with [`SdifFileBuilder::named_stream()`](crate::SdifFileBuilder::named_stream)
+//! and resolved with [`SdifWriter::named_stream()`](crate::SdifWriter::named_stream)
+//! and [`SdifFile::stream_named()`](crate::SdifFile::stream_named).
+
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+
+/// Prefix of all stream name NVT keys.
+const PREFIX: &str = "stream.";
+
+/// Get the NVT key recording the ID of stream `name`.
+pub(crate) fn key(name: &str) -> String {
+    format!("{}{}", PREFIX, name)
+}
+
+/// Check that `name` can be stored in an NVT key.
+///
+/// Names also end up in file names (see [`split`](crate::split)), so path
+/// separators and the `.` and `..` directory names are rejected too.
+pub(crate) fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::invalid_format("Stream name cannot be empty"));
+    }
+    if name.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(Error::invalid_format(format!(
+            "Stream name cannot contain whitespace or control characters: {:?}",
+            name
+        )));
+    }
+    if name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(Error::invalid_format(format!(
+            "Stream name cannot be a path: {:?}",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Collect the stream names from NVT entries.
+///
+/// Entries whose value is not a valid stream ID, or whose name is not a
+/// valid stream name, are ignored.
+pub(crate) fn from_entries<'a>(
+    entries: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> HashMap<String, u32> {
+    entries
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(PREFIX)?;
+            validate_name(name).ok()?;
+            Some((name.to_string(), value.parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("partials").is_ok());
+        assert!(validate_name("f0.smoothed").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("two words").is_err());
+        assert!(validate_name("tab\tname").is_err());
+        assert!(validate_name("../escape").is_err());
+        assert!(validate_name("dir\\name").is_err());
+        assert!(validate_name("..").is_err());
+    }
+
+    #[test]
+    fn test_from_entries() {
+        let entries = [
+            (key("partials"), "1".to_string()),
+            (key("f0"), "2".to_string()),
+            (key("broken"), "x".to_string()),
+            (key("../../etc/passwd"), "4".to_string()),
+            ("creator".to_string(), "3".to_string()),
+        ];
+
+        let streams = from_entries(entries.iter().map(|(k, v)| (k, v)));
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams["partials"], 1);
+        assert_eq!(streams["f0"], 2);
+    }
+}
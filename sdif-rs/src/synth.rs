@@ -0,0 +1,401 @@
+//! Additive resynthesis of partials to audio.
+//!
+//! [`render()`] plays a [`TrackSet`] through a bank of sine oscillators,
+//! one per track, so an analysis can be auditioned without leaving Rust.
+//! Between the points of a track its frequency and amplitude change
+//! linearly, and the phase follows the frequency. Each track fades in
+//! before its birth and out after its death, so partials start and stop
+//! without clicks. A gap in a track longer than the frame hop is played as
+//! a death and a new birth, fading out and back in. [`render_file()`] and [`render_frames()`] read the 1TRC
+//! and 1HRM partials of a file or of some of its frames first, and
+//! [`write_wav()`] saves the result.
+//!
+//! Requires the `synth` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::synth::{self, SynthOptions};
+//! use sdif_rs::SdifFile;
+//!
+//! let options = SynthOptions::new().sample_rate(48000).normalize(0.9);
+//! let samples = synth::render_file(&SdifFile::open("voice.trc.sdif")?, &options)?;
+//! synth::write_wav("voice.resynth.wav", &samples, &options)?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::f64::consts::TAU;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::frame::Frame;
+use crate::source::write_mono;
+use crate::tracks::{TrackPoint, TrackSet};
+
+/// Spacing between two points of a track, in frame hops, above which the
+/// track is silent in between.
+const GAP_HOPS: f64 = 1.5;
+
+/// Settings of the oscillator bank.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SynthOptions {
+    /// Sample rate of the output, in Hz.
+    pub sample_rate: u32,
+
+    /// Length of the fade before birth and after death, in seconds.
+    pub fade: f64,
+
+    /// Factor applied to the amplitudes.
+    pub gain: f64,
+
+    /// Level of the loudest sample, replacing `gain` when set.
+    pub peak: Option<f64>,
+}
+
+impl Default for SynthOptions {
+    fn default() -> Self {
+        SynthOptions {
+            sample_rate: 44100,
+            fade: 0.01,
+            gain: 1.0,
+            peak: None,
+        }
+    }
+}
+
+impl SynthOptions {
+    /// Create the default options: 44.1 kHz, 10 ms fades, gain 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the sample rate of the output, in Hz.
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Set the length of the fades, in seconds.
+    pub fn fade(mut self, fade: f64) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    /// Set the factor applied to the amplitudes.
+    pub fn gain(mut self, gain: f64) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Scale the output so its loudest sample is at `peak`.
+    pub fn normalize(mut self, peak: f64) -> Self {
+        self.peak = Some(peak);
+        self
+    }
+
+    /// Check the options.
+    fn validate(&self) -> Result<()> {
+        if self.sample_rate == 0 {
+            return Err(Error::invalid_format("Sample rate must be positive, got 0"));
+        }
+        if !(self.fade >= 0.0 && self.fade.is_finite()) {
+            return Err(Error::invalid_format(format!(
+                "Fade must be 0 or more seconds, got {}",
+                self.fade
+            )));
+        }
+        if !self.gain.is_finite() || self.peak.is_some_and(|peak| !peak.is_finite()) {
+            return Err(Error::invalid_format(format!(
+                "Gain and peak must be finite, got {} and {:?}",
+                self.gain, self.peak
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Render tracks to mono samples.
+///
+/// The output starts at time 0 and ends with the last fade out. Parts of
+/// tracks before time 0 are left out, as are frequencies at or above the
+/// Nyquist frequency. Without fades, a track with a single point is
+/// silent, and empty tracks are always silent.
+///
+/// The frame hop is the smallest spacing between consecutive points of
+/// any track. Points more than 1.5 hops apart are not joined: the track
+/// fades out after the first and back in before the second.
+///
+/// # Errors
+///
+/// - [`Error::InvalidFormat`] if the options are invalid
+pub fn render(tracks: &TrackSet, options: &SynthOptions) -> Result<Vec<f32>> {
+    options.validate()?;
+
+    let sample_rate = f64::from(options.sample_rate);
+    let len = tracks
+        .iter()
+        .filter_map(|track| track.points.last())
+        .map(|last| sample_index(last.time + options.fade, sample_rate))
+        .max()
+        .unwrap_or(0);
+    let mut output = vec![0.0; len];
+
+    let max_gap = frame_hop(tracks) * GAP_HOPS;
+    for track in tracks {
+        let points = &track.points;
+        let mut start = 0;
+        for end in 1..=points.len() {
+            if end == points.len() || points[end].time - points[end - 1].time > max_gap {
+                render_points(&points[start..end], sample_rate, options.fade, &mut output);
+                start = end;
+            }
+        }
+    }
+
+    let scale = match options.peak {
+        Some(peak) => {
+            let loudest = output.iter().fold(0.0, |max: f64, s| max.max(s.abs()));
+            if loudest > 0.0 {
+                peak / loudest
+            } else {
+                1.0
+            }
+        }
+        None => options.gain,
+    };
+    Ok(output.iter().map(|&s| (s * scale) as f32).collect())
+}
+
+/// Render the 1TRC and 1HRM partials of a file to mono samples.
+///
+/// The file is read from its current position to the end.
+///
+/// # Errors
+///
+/// - Any error from [`TrackSet::from_partials()`]
+/// - Any error from [`render()`]
+pub fn render_file(file: &SdifFile, options: &SynthOptions) -> Result<Vec<f32>> {
+    render(&TrackSet::from_partials(file)?, options)
+}
+
+/// Render the 1TRC and 1HRM partials of some frames to mono samples.
+///
+/// The output still starts at time 0, so frames from later in a file are
+/// preceded by silence.
+///
+/// # Errors
+///
+/// - Any error from [`TrackSet::from_frames()`]
+/// - Any error from [`render()`]
+///
+/// # Example
+///
+/// ```no_run
+/// use sdif_rs::synth::{self, SynthOptions};
+/// use sdif_rs::{FrameFilter, SdifFile};
+///
+/// let file = SdifFile::open("mix.sdif")?;
+/// let voice = file.frames_filtered(FrameFilter::new().stream_id(2));
+/// let samples = synth::render_frames(voice, &SynthOptions::new())?;
+/// # Ok::<(), sdif_rs::Error>(())
+/// ```
+pub fn render_frames<'a>(
+    frames: impl IntoIterator<Item = Result<Frame<'a>>>,
+    options: &SynthOptions,
+) -> Result<Vec<f32>> {
+    render(&TrackSet::from_frames(frames)?, options)
+}
+
+/// Write rendered samples to a 16-bit mono WAV file at the sample rate of
+/// `options`.
+///
+/// Samples outside [-1, 1] are clipped; see
+/// [`SynthOptions::normalize()`] to avoid that.
+///
+/// # Errors
+///
+/// - [`Error::Io`] if the file can't be written
+pub fn write_wav(path: impl AsRef<Path>, samples: &[f32], options: &SynthOptions) -> Result<()> {
+    write_mono(path.as_ref(), samples, options.sample_rate)
+}
+
+/// Get the smallest spacing between consecutive points of any track, or
+/// infinity if no track has two points.
+fn frame_hop(tracks: &TrackSet) -> f64 {
+    tracks
+        .iter()
+        .flat_map(|track| track.points.windows(2))
+        .map(|pair| pair[1].time - pair[0].time)
+        .filter(|&spacing| spacing > 0.0)
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Add the points of one run of a track to the output.
+fn render_points(run: &[TrackPoint], sample_rate: f64, fade: f64, output: &mut [f64]) {
+    let (Some(&first), Some(&last)) = (run.first(), run.last()) else {
+        return;
+    };
+
+    // Points with silent ones before birth and after death for the fades
+    let mut points = Vec::with_capacity(run.len() + 2);
+    if fade > 0.0 {
+        points.push(TrackPoint {
+            time: first.time - fade,
+            amplitude: 0.0,
+            ..first
+        });
+    }
+    points.extend_from_slice(run);
+    if fade > 0.0 {
+        points.push(TrackPoint {
+            time: last.time + fade,
+            amplitude: 0.0,
+            ..last
+        });
+    }
+
+    // The frequency is constant during the fade in, so the phase at birth
+    // can be wound back to the first sample
+    let start = sample_index(points[0].time, sample_rate);
+    let mut phase = first.phase + TAU * first.frequency * (start as f64 / sample_rate - first.time);
+    let nyquist = sample_rate / 2.0;
+
+    for pair in points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let start = sample_index(from.time, sample_rate);
+        let end = sample_index(to.time, sample_rate).min(output.len());
+
+        for (n, sample) in output.iter_mut().enumerate().take(end).skip(start) {
+            let position = (n as f64 / sample_rate - from.time) / (to.time - from.time);
+            let frequency = from.frequency + (to.frequency - from.frequency) * position;
+            let amplitude = from.amplitude + (to.amplitude - from.amplitude) * position;
+            if frequency < nyquist {
+                *sample += amplitude * phase.sin();
+            }
+            phase += TAU * frequency / sample_rate;
+        }
+    }
+}
+
+/// Get the index of the first sample at or after `time`, 0 for negative
+/// times.
+fn sample_index(time: f64, sample_rate: f64) -> usize {
+    (time * sample_rate).ceil().max(0.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracks::Track;
+
+    fn track(index: u32, points: &[(f64, f64, f64)]) -> Track {
+        Track {
+            stream_id: 0,
+            signature: "1TRC".to_string(),
+            index,
+            points: points
+                .iter()
+                .map(|&(time, frequency, amplitude)| TrackPoint {
+                    time,
+                    frequency,
+                    amplitude,
+                    phase: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    fn rms(samples: &[f32]) -> f64 {
+        let sum: f64 = samples.iter().map(|&s| f64::from(s).powi(2)).sum();
+        (sum / samples.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn test_render_sine() {
+        let tracks = TrackSet {
+            tracks: vec![track(1, &[(0.0, 100.0, 0.5), (1.0, 100.0, 0.5)])],
+        };
+        let options = SynthOptions::new().sample_rate(1000).fade(0.0);
+        let samples = render(&tracks, &options).unwrap();
+        assert_eq!(samples.len(), 1000);
+
+        // 100 Hz at 1 kHz: a period of 10 samples starting at phase 0
+        assert!(samples[0].abs() < 1e-6);
+        assert!((samples[10] - samples[0]).abs() < 1e-3);
+        assert!((rms(&samples) - 0.5 / 2f64.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_render_interpolates_and_fades() {
+        let tracks = TrackSet {
+            tracks: vec![track(1, &[(0.5, 50.0, 0.0), (1.0, 50.0, 1.0)])],
+        };
+        let options = SynthOptions::new().sample_rate(1000).fade(0.1);
+        let samples = render(&tracks, &options).unwrap();
+        assert_eq!(samples.len(), 1100);
+
+        // Silent before the fade in, growing louder up to the death, then
+        // fading out
+        assert!(samples[..400].iter().all(|&s| s == 0.0));
+        assert!(rms(&samples[500..700]) < rms(&samples[800..1000]));
+        assert!(rms(&samples[1000..]) < rms(&samples[900..1000]));
+    }
+
+    #[test]
+    fn test_render_options() {
+        let tracks = TrackSet {
+            tracks: vec![
+                track(1, &[(0.0, 100.0, 1.0), (1.0, 100.0, 1.0)]),
+                track(2, &[(0.0, 600.0, 1.0), (1.0, 600.0, 1.0)]),
+            ],
+        };
+
+        // The second track is above the Nyquist frequency of 1 kHz
+        let options = SynthOptions::new().sample_rate(1000).fade(0.0).gain(0.5);
+        let samples = render(&tracks, &options).unwrap();
+        assert!((rms(&samples) - 0.5 / 2f64.sqrt()).abs() < 1e-3);
+
+        let samples = render(&tracks, &options.clone().normalize(0.25)).unwrap();
+        let loudest = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((loudest - 0.25).abs() < 1e-6);
+
+        assert!(render(&tracks, &SynthOptions::new().sample_rate(0)).is_err());
+        assert!(render(&tracks, &SynthOptions::new().fade(-1.0)).is_err());
+        assert!(render(&TrackSet::default(), &SynthOptions::new())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_render_empty_track() {
+        let tracks = TrackSet {
+            tracks: vec![track(1, &[]), track(2, &[(0.0, 100.0, 0.5), (1.0, 100.0, 0.5)])],
+        };
+        let options = SynthOptions::new().sample_rate(1000).fade(0.0);
+        let samples = render(&tracks, &options).unwrap();
+        assert_eq!(samples.len(), 1000);
+        assert!((rms(&samples) - 0.5 / 2f64.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_render_gap_fades_out_and_in() {
+        // Frames every 0.1s, with the partial missing from 0.3s to 0.6s
+        let points: Vec<(f64, f64, f64)> = [0.0, 0.1, 0.2, 0.7, 0.8, 0.9]
+            .iter()
+            .map(|&time| (time, 100.0, 1.0))
+            .collect();
+        let tracks = TrackSet {
+            tracks: vec![track(1, &points)],
+        };
+        let options = SynthOptions::new().sample_rate(1000).fade(0.05);
+        let samples = render(&tracks, &options).unwrap();
+        assert_eq!(samples.len(), 950);
+
+        // Silent in the gap, between the fade out and the fade in
+        assert!(samples[250..650].iter().all(|&s| s == 0.0));
+        assert!(rms(&samples[200..250]) < rms(&samples[100..200]));
+        assert!(rms(&samples[650..700]) < rms(&samples[700..800]));
+    }
+}
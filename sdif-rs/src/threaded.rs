@@ -0,0 +1,152 @@
+//! SDIF files that can be moved between threads.
+//!
+//! [`SdifFile`] is `!Send` because the C library keeps global state. With
+//! the `threaded` feature, [`OwnedSdifFile`] wraps a file so it can be
+//! handed to another thread: every call into the library made through it
+//! holds a process-wide lock, so files on different threads never run C
+//! code at the same time, while the Rust side of the work (decoding,
+//! analysis, output) runs in parallel.
+//!
+//! The feature builds the bundled library, whose state is shared by all
+//! threads. System libraries built with pthread support keep part of it
+//! per thread, and handles can't safely move between threads there.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::thread;
+//! use sdif_rs::SdifFile;
+//!
+//! let handles: Vec<_> = ["a.sdif", "b.sdif"]
+//!     .into_iter()
+//!     .map(|path| {
+//!         let mut file = SdifFile::open_owned(path)?;
+//!         Ok(thread::spawn(move || file.with(|file| file.frames().count())))
+//!     })
+//!     .collect::<sdif_rs::Result<_>>()?;
+//!
+//! for handle in handles {
+//!     println!("{} frames", handle.join().unwrap());
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::error::Result;
+use crate::file::{ReadOptions, SdifFile};
+
+/// Held while an [`OwnedSdifFile`] calls into the C library.
+static LIBRARY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Take the library lock.
+///
+/// A panic inside [`OwnedSdifFile::with()`] poisons the lock, but the
+/// library state it guards is still consistent, so the poison is ignored.
+fn lock_library() -> MutexGuard<'static, ()> {
+    LIBRARY_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// An [`SdifFile`] that is `Send` and can be moved to another thread.
+///
+/// Created by [`SdifFile::open_owned()`]. The file is used through
+/// [`with()`](Self::with), which holds the library lock for the duration
+/// of the closure.
+///
+/// Only calls made through `OwnedSdifFile`s take the lock. Don't use
+/// plain [`SdifFile`]s or [`SdifWriter`](crate::SdifWriter)s on other
+/// threads at the same time.
+#[derive(Debug)]
+pub struct OwnedSdifFile {
+    /// The wrapped file, dropped while holding the lock.
+    file: ManuallyDrop<SdifFile>,
+
+    /// Marker to keep OwnedSdifFile !Sync.
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+// SAFETY: the file's Rust state is owned and moves with it, and the
+// bundled C library keeps no per-thread state. Every call into the
+// library, including closing the file on drop, holds LIBRARY_LOCK.
+unsafe impl Send for OwnedSdifFile {}
+
+impl OwnedSdifFile {
+    /// Open an SDIF file for use from any thread.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SdifFile::open_with_options()`].
+    pub fn open_with_options(path: impl AsRef<Path>, options: ReadOptions) -> Result<Self> {
+        let _guard = lock_library();
+        let file = SdifFile::open_with_options(path, options)?;
+
+        Ok(OwnedSdifFile {
+            file: ManuallyDrop::new(file),
+            _not_sync: PhantomData,
+        })
+    }
+
+    /// Use the file while holding the library lock.
+    ///
+    /// Other threads using an `OwnedSdifFile` wait until `f` returns, so
+    /// keep work that doesn't need the file, such as processing the
+    /// frames read, outside of `f` where possible.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let mut file = SdifFile::open_owned("input.sdif")?;
+    /// let frames = std::thread::spawn(move || {
+    ///     file.with(|file| {
+    ///         file.frames()
+    ///             .map(|frame| frame?.into_data())
+    ///             .collect::<sdif_rs::Result<Vec<_>>>()
+    ///     })
+    /// })
+    /// .join()
+    /// .unwrap()?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn with<R>(&mut self, f: impl FnOnce(&SdifFile) -> R) -> R {
+        let _guard = lock_library();
+        f(&self.file)
+    }
+}
+
+impl Drop for OwnedSdifFile {
+    fn drop(&mut self) {
+        let _guard = lock_library();
+        // SAFETY: the file is never used again.
+        unsafe { ManuallyDrop::drop(&mut self.file) };
+    }
+}
+
+impl SdifFile {
+    /// Open an SDIF file that can be moved to another thread.
+    ///
+    /// See [`OwnedSdifFile`]. Requires the `threaded` feature.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`open()`](Self::open).
+    pub fn open_owned(path: impl AsRef<Path>) -> Result<OwnedSdifFile> {
+        OwnedSdifFile::open_with_options(path, ReadOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_file_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<OwnedSdifFile>();
+    }
+}
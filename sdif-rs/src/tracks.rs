@@ -0,0 +1,447 @@
+//! Sinusoidal tracks, one per partial.
+//!
+//! 1TRC files store partials frame by frame: each frame holds one row per
+//! partial alive at its time, told apart by the `Index` column. Most
+//! analysis wants the opposite view, each partial over time. A
+//! [`TrackSet`] reorganizes the rows into a [`Track`] per stream and
+//! index, with the partial's points in time order and its birth and death
+//! times. An index missing from a frame ends its track; if it comes back
+//! later, that starts a new track. Harmonic partials (1HRM) can be read
+//! the same way, as tracks of their own.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::tracks::TrackSet;
+//! use sdif_rs::SdifFile;
+//!
+//! let file = SdifFile::open("voice.trc.sdif")?;
+//! let tracks = TrackSet::from_file(&file)?;
+//! for track in tracks.iter().filter(|t| t.duration() > 0.1) {
+//!     println!(
+//!         "#{}: {:.3}s - {:.3}s, {:.1} Hz on average",
+//!         track.index,
+//!         track.birth(),
+//!         track.death(),
+//!         track.mean_frequency()
+//!     );
+//! }
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::Result;
+use crate::file::SdifFile;
+use crate::frame::Frame;
+use crate::rows::{HrmRow, TrcRow};
+
+/// Signature of sinusoidal track matrices.
+const TRACK_SIGNATURE: &str = "1TRC";
+
+/// Signature of harmonic partial matrices.
+const HARMONIC_SIGNATURE: &str = "1HRM";
+
+/// One point of a partial.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackPoint {
+    /// Time in seconds.
+    pub time: f64,
+
+    /// Frequency in Hz.
+    pub frequency: f64,
+
+    /// Linear amplitude.
+    pub amplitude: f64,
+
+    /// Phase in radians, 0 if the file has none.
+    pub phase: f64,
+}
+
+/// One partial over time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Track {
+    /// Stream ID of the frames the partial was read from.
+    pub stream_id: u32,
+
+    /// Signature of the matrices the partial was read from, `1TRC` or
+    /// `1HRM`.
+    pub signature: String,
+
+    /// Index of the partial in the `Index` column.
+    pub index: u32,
+
+    /// Points in time order, never empty.
+    pub points: Vec<TrackPoint>,
+}
+
+impl Track {
+    /// Get the time of the first point, in seconds.
+    pub fn birth(&self) -> f64 {
+        self.points[0].time
+    }
+
+    /// Get the time of the last point, in seconds.
+    pub fn death(&self) -> f64 {
+        self.points[self.points.len() - 1].time
+    }
+
+    /// Get the time between birth and death, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.death() - self.birth()
+    }
+
+    /// Get the mean frequency of the points, in Hz.
+    pub fn mean_frequency(&self) -> f64 {
+        self.points.iter().map(|p| p.frequency).sum::<f64>() / self.points.len() as f64
+    }
+
+    /// Get the largest amplitude of the points.
+    pub fn peak_amplitude(&self) -> f64 {
+        self.points.iter().map(|p| p.amplitude).fold(0.0, f64::max)
+    }
+}
+
+/// The partials of a file, by stream, signature and index.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackSet {
+    /// Tracks in stream ID, signature, index and birth order.
+    pub tracks: Vec<Track>,
+}
+
+/// Key of a partial: stream ID, matrix signature and index.
+type TrackKey = (u32, String, u32);
+
+/// Tracks being read, and the rows seen in the latest frame of each
+/// stream and signature.
+#[derive(Default)]
+struct TrackReader {
+    /// Tracks that may still get points.
+    open: BTreeMap<TrackKey, Vec<TrackPoint>>,
+
+    /// Tracks ended by a frame without their index.
+    closed: Vec<Track>,
+
+    /// Time of the latest frame and the indices it held, by stream ID and
+    /// signature.
+    latest: HashMap<(u32, String), (f64, HashSet<u32>)>,
+}
+
+impl TrackReader {
+    /// Start reading a `signature` matrix of a frame at `time`.
+    ///
+    /// A frame later than the latest one of its stream and signature ends
+    /// the tracks the latest frame didn't continue.
+    fn start_matrix(&mut self, stream_id: u32, signature: &str, time: f64) {
+        let stream = (stream_id, signature.to_string());
+        let (latest_time, seen) = self
+            .latest
+            .entry(stream.clone())
+            .or_insert_with(|| (time, HashSet::new()));
+
+        if time > *latest_time {
+            let ended: Vec<TrackKey> = self
+                .open
+                .range((stream_id, stream.1.clone(), 0)..=(stream_id, stream.1, u32::MAX))
+                .map(|(key, _)| key.clone())
+                .filter(|(_, _, index)| !seen.contains(index))
+                .collect();
+            for key in ended {
+                let points = self.open.remove(&key).expect("open track");
+                self.closed.push(track(key, points));
+            }
+            *latest_time = time;
+            seen.clear();
+        }
+    }
+
+    /// Add a row of the `signature` matrix started last for the stream.
+    fn add(&mut self, stream_id: u32, signature: &str, time: f64, row: TrcRow) {
+        if let Some((_, seen)) = self.latest.get_mut(&(stream_id, signature.to_string())) {
+            seen.insert(row.index);
+        }
+        let key = (stream_id, signature.to_string(), row.index);
+        push(self.open.entry(key).or_default(), time, row);
+    }
+
+    /// Collect all tracks, in stream ID, signature, index and birth order.
+    fn finish(self) -> TrackSet {
+        let mut tracks = self.closed;
+        tracks.extend(self.open.into_iter().map(|(key, points)| track(key, points)));
+        tracks.sort_by(|a, b| {
+            (a.stream_id, &a.signature, a.index)
+                .cmp(&(b.stream_id, &b.signature, b.index))
+                .then(a.birth().total_cmp(&b.birth()))
+        });
+        TrackSet { tracks }
+    }
+}
+
+impl TrackSet {
+    /// Read the partials of the 1TRC matrices of a file.
+    ///
+    /// Rows are grouped by the stream ID of their frame and their `Index`
+    /// column, and matrices without a `Phase` column give phases of 0.
+    /// Other matrices are ignored. A partial with two rows at the same time
+    /// keeps the first. A later 1TRC frame of the same stream without the
+    /// index ends the track, and a new track starts if the index comes
+    /// back.
+    ///
+    /// The file is read from its current position to the end.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`](crate::Error::InvalidFormat) if a 1TRC
+    ///   matrix lacks the `Index`, `Frequency` or `Amplitude` column
+    /// - Any error from reading the frames
+    pub fn from_file(file: &SdifFile) -> Result<Self> {
+        Self::read(file.frames(), false)
+    }
+
+    /// Read the partials of both the 1TRC and 1HRM matrices of a file.
+    ///
+    /// Like [`from_file()`](Self::from_file), with 1HRM rows grouped by
+    /// stream and `Index` column too. A 1TRC and a 1HRM partial with the
+    /// same index are different tracks.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`from_file()`](Self::from_file), for 1HRM matrices as
+    /// well.
+    pub fn from_partials(file: &SdifFile) -> Result<Self> {
+        Self::read(file.frames(), true)
+    }
+
+    /// Read the partials of the 1TRC and 1HRM matrices of some frames.
+    ///
+    /// Like [`from_partials()`](Self::from_partials), for frames chosen
+    /// by the caller, such as those of
+    /// [`SdifFile::frames_filtered()`](crate::SdifFile::frames_filtered)
+    /// or a time range.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`from_partials()`](Self::from_partials), and any error
+    /// from the iterator.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::tracks::TrackSet;
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let file = SdifFile::open("voice.trc.sdif")?;
+    /// let frames = file.frames_from(1.0)?.take_while(|frame| {
+    ///     frame.as_ref().map_or(true, |frame| frame.time() < 2.0)
+    /// });
+    /// let tracks = TrackSet::from_frames(frames)?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn from_frames<'a>(frames: impl IntoIterator<Item = Result<Frame<'a>>>) -> Result<Self> {
+        Self::read(frames, true)
+    }
+
+    /// Read the 1TRC matrices of frames, and the 1HRM ones if `harmonics`.
+    fn read<'a>(
+        frames: impl IntoIterator<Item = Result<Frame<'a>>>,
+        harmonics: bool,
+    ) -> Result<Self> {
+        let mut reader = TrackReader::default();
+
+        for frame in frames {
+            let mut frame = frame?;
+            let (time, stream_id) = (frame.time(), frame.stream_id());
+            for matrix in frame.matrices() {
+                let matrix = matrix?;
+                let signature = matrix.signature();
+                let harmonic = harmonics && signature == HARMONIC_SIGNATURE;
+                if signature == TRACK_SIGNATURE || harmonic {
+                    reader.start_matrix(stream_id, &signature, time);
+                }
+                if signature == TRACK_SIGNATURE {
+                    for row in matrix.rows_as::<TrcRow>()? {
+                        reader.add(stream_id, &signature, time, row);
+                    }
+                } else if harmonic {
+                    for row in matrix.rows_as::<HrmRow>()? {
+                        let row = TrcRow {
+                            index: row.index,
+                            frequency: row.frequency,
+                            amplitude: row.amplitude,
+                            phase: row.phase,
+                        };
+                        reader.add(stream_id, &signature, time, row);
+                    }
+                }
+            }
+        }
+
+        Ok(reader.finish())
+    }
+
+    /// Get the number of tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Check whether there are no tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Iterate over the tracks in stream ID, signature and index order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Track> {
+        self.tracks.iter()
+    }
+
+    /// Get the first track with index `index` in the `signature` matrices
+    /// of stream `stream_id`.
+    ///
+    /// An index that disappears and comes back has a track for each run;
+    /// [`get_all()`](Self::get_all) returns them all.
+    pub fn get(&self, stream_id: u32, signature: &str, index: u32) -> Option<&Track> {
+        self.get_all(stream_id, signature, index).first()
+    }
+
+    /// Get the tracks with index `index` in the `signature` matrices of
+    /// stream `stream_id`, in birth order.
+    pub fn get_all(&self, stream_id: u32, signature: &str, index: u32) -> &[Track] {
+        let key = (stream_id, signature, index);
+        let key_of = |track: &Track| (track.stream_id, track.signature.as_str(), track.index);
+        let start = self.tracks.partition_point(|track| key_of(track) < key);
+        let end = self.tracks.partition_point(|track| key_of(track) <= key);
+        &self.tracks[start..end]
+    }
+
+    /// Get the tracks alive at `time`, in seconds.
+    pub fn alive_at(&self, time: f64) -> impl Iterator<Item = &Track> {
+        self.tracks
+            .iter()
+            .filter(move |track| track.birth() <= time && time <= track.death())
+    }
+}
+
+impl<'a> IntoIterator for &'a TrackSet {
+    type Item = &'a Track;
+    type IntoIter = std::slice::Iter<'a, Track>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tracks.iter()
+    }
+}
+
+/// Build a track from its key and points.
+fn track((stream_id, signature, index): TrackKey, points: Vec<TrackPoint>) -> Track {
+    Track {
+        stream_id,
+        signature,
+        index,
+        points,
+    }
+}
+
+/// Add a row at `time` to a track's points, keeping times increasing.
+fn push(points: &mut Vec<TrackPoint>, time: f64, row: TrcRow) {
+    if points.last().is_some_and(|last| time <= last.time) {
+        return;
+    }
+    points.push(TrackPoint {
+        time,
+        frequency: row.frequency,
+        amplitude: row.amplitude,
+        phase: row.phase,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(index: u32, frequency: f64, amplitude: f64) -> TrcRow {
+        TrcRow {
+            index,
+            frequency,
+            amplitude,
+            phase: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_track_times() {
+        let mut points = Vec::new();
+        push(&mut points, 0.5, row(1, 440.0, 0.25));
+        push(&mut points, 0.5, row(1, 441.0, 0.5));
+        push(&mut points, 0.75, row(1, 442.0, 0.5));
+        push(&mut points, 1.0, row(1, 444.0, 0.125));
+        let track = Track {
+            stream_id: 0,
+            signature: TRACK_SIGNATURE.to_string(),
+            index: 1,
+            points,
+        };
+
+        assert_eq!(track.points.len(), 3);
+        assert_eq!(track.birth(), 0.5);
+        assert_eq!(track.death(), 1.0);
+        assert_eq!(track.duration(), 0.5);
+        assert_eq!(track.mean_frequency(), 442.0);
+        assert_eq!(track.peak_amplitude(), 0.5);
+    }
+
+    #[test]
+    fn test_track_set_lookup() {
+        let track = |index, birth, death| Track {
+            stream_id: 0,
+            signature: TRACK_SIGNATURE.to_string(),
+            index,
+            points: [birth, death]
+                .iter()
+                .map(|&time| TrackPoint {
+                    time,
+                    frequency: 440.0,
+                    amplitude: 0.5,
+                    phase: 0.0,
+                })
+                .collect(),
+        };
+        let tracks = TrackSet {
+            tracks: vec![track(1, 0.0, 1.0), track(3, 0.5, 2.0), track(7, 1.5, 2.0)],
+        };
+
+        assert_eq!(tracks.get(0, "1TRC", 3).map(|t| t.birth()), Some(0.5));
+        assert!(tracks.get(0, "1TRC", 2).is_none());
+        assert!(tracks.get(0, "1HRM", 3).is_none());
+        let alive: Vec<u32> = tracks.alive_at(1.0).map(|t| t.index).collect();
+        assert_eq!(alive, [1, 3]);
+    }
+
+    #[test]
+    fn test_index_gap_splits_track() {
+        let mut reader = TrackReader::default();
+        let mut matrix = |stream_id, time, rows: &[TrcRow]| {
+            reader.start_matrix(stream_id, TRACK_SIGNATURE, time);
+            for &row in rows {
+                reader.add(stream_id, TRACK_SIGNATURE, time, row);
+            }
+        };
+        matrix(0, 0.0, &[row(1, 440.0, 0.5), row(2, 880.0, 0.25)]);
+        // Index 1 is missing from the second frame
+        matrix(0, 0.01, &[row(2, 881.0, 0.25)]);
+        matrix(0, 0.02, &[row(1, 441.0, 0.5), row(2, 882.0, 0.25)]);
+        // Other streams don't end the tracks of stream 0
+        matrix(1, 0.03, &[row(5, 220.0, 0.5)]);
+        let tracks = reader.finish();
+
+        assert_eq!(tracks.len(), 4);
+        let runs = tracks.get_all(0, "1TRC", 1);
+        assert_eq!(runs.len(), 2);
+        assert_eq!((runs[0].birth(), runs[0].death()), (0.0, 0.0));
+        assert_eq!((runs[1].birth(), runs[1].death()), (0.02, 0.02));
+        assert_eq!(tracks.get(0, "1TRC", 1).map(|t| t.birth()), Some(0.0));
+        assert_eq!(tracks.get_all(0, "1TRC", 2).len(), 1);
+        assert_eq!(tracks.get(0, "1TRC", 2).map(|t| t.duration()), Some(0.02));
+    }
+}
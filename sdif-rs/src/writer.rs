@@ -3,18 +3,87 @@
 //! `SdifWriter` is obtained from `SdifFileBuilder::build()` and provides
 //! methods for writing frames to the file.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
 
+use sdif_sys::write::{self, MatrixElement};
 use sdif_sys::{
-    SdifFClose, SdifFWriteFrameAndOneMatrix, SdifFileT,
-    SdifDataTypeET_eFloat4, SdifDataTypeET_eFloat8,
+    SdifFClose, SdifFGetMatrixTypesTable, SdifFGetPos, SdifFileT, SdifGetMatrixType, SdiffPosT,
 };
 
+use crate::data_type::DataType;
 use crate::error::{Error, Result};
-use crate::frame_builder::FrameBuilder;
-use crate::signature::string_to_signature;
+use crate::frame_builder::{FrameBuilder, PendingMatrix};
+use crate::frame_data::{transpose, FrameData};
+use crate::metrics;
+use crate::schema::Schema;
+use crate::signature::{signature_to_string, string_to_signature, Signature};
+
+/// Regular time grid used to correct frame time jitter.
+#[derive(Debug, Clone, Copy)]
+struct TimeGrid {
+    /// Distance between grid points, in seconds.
+    hop: f64,
+    /// Maximum distance between a frame time and its grid point.
+    tolerance: f64,
+}
+
+/// What to do when a frame repeats the time, stream and signature of a
+/// frame already written.
+///
+/// Such frames usually come from a conversion bug, and some readers keep
+/// only one of them. Set with [`SdifWriter::check_duplicate_frames()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateFrames {
+    /// Write the frame and record a warning.
+    Warn,
+
+    /// Fail with [`Error::DuplicateFrame`] without writing the frame.
+    Reject,
+}
+
+/// Running totals of what a writer has written.
+///
+/// Returned by [`SdifWriter::stats()`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WriterStats {
+    /// Size of the file so far in bytes, including its header.
+    pub bytes_written: u64,
+
+    /// Number of frames written.
+    pub frames: usize,
+
+    /// Number of frames written, keyed by frame signature.
+    pub frames_by_signature: BTreeMap<String, usize>,
+
+    /// Number of matrix rows written, over all matrices.
+    pub rows: usize,
+
+    /// Earliest frame time written.
+    pub min_time: Option<f64>,
+
+    /// Latest frame time written.
+    pub max_time: Option<f64>,
+
+    /// Number of duplicate frames written, counted when enabled with
+    /// [`SdifWriter::check_duplicate_frames()`].
+    pub duplicate_frames: usize,
+}
+
+impl WriterStats {
+    /// Get the time spanned by the written frames.
+    pub fn duration(&self) -> f64 {
+        match (self.min_time, self.max_time) {
+            (Some(min), Some(max)) => max - min,
+            _ => 0.0,
+        }
+    }
+}
 
 /// Active writer for an SDIF file.
 ///
@@ -60,8 +129,37 @@ pub struct SdifWriter {
     /// Track the last written time for validation.
     last_time: Option<f64>,
 
-    /// Count of frames written.
-    frame_count: usize,
+    /// Totals of what has been written.
+    stats: WriterStats,
+
+    /// How duplicate frames are handled, if they are checked.
+    duplicate_check: Option<DuplicateFrames>,
+
+    /// Signature and stream of the frames written at `last_time`.
+    frames_at_last_time: HashSet<(Signature, u32)>,
+
+    /// Warnings about the frames written so far.
+    warnings: Vec<String>,
+
+    /// Grid that frame times are snapped to, if enabled.
+    time_grid: Option<TimeGrid>,
+
+    /// Data types used for f64 data, keyed by matrix signature.
+    data_types: HashMap<Signature, DataType>,
+
+    /// Whether matrix data is stored column by column.
+    column_major: bool,
+
+    /// Stream IDs declared on the builder, keyed by name.
+    streams: HashMap<String, u32>,
+
+    /// Whether the file was grown ahead of writing and must be truncated
+    /// to the written size on close.
+    preallocated: bool,
+
+    /// Whether the file is a temporary one, removed when the writer is
+    /// dropped.
+    temporary: bool,
 
     /// Marker to make SdifWriter !Send and !Sync.
     _not_send_sync: PhantomData<*const ()>,
@@ -69,15 +167,33 @@ pub struct SdifWriter {
 
 impl SdifWriter {
     /// Create a new writer (called internally by SdifFileBuilder).
-    pub(crate) fn new(handle: NonNull<SdifFileT>, path: PathBuf) -> Self {
-        SdifWriter {
+    pub(crate) fn new(
+        handle: NonNull<SdifFileT>,
+        path: PathBuf,
+        data_types: HashMap<Signature, DataType>,
+        column_major: bool,
+        streams: HashMap<String, u32>,
+    ) -> Self {
+        let mut writer = SdifWriter {
             handle,
             path,
             closed: false,
             last_time: None,
-            frame_count: 0,
+            stats: WriterStats::default(),
+            duplicate_check: None,
+            frames_at_last_time: HashSet::new(),
+            warnings: Vec::new(),
+            time_grid: None,
+            data_types,
+            column_major,
+            streams,
+            preallocated: false,
+            temporary: false,
             _not_send_sync: PhantomData,
-        }
+        };
+        // The header and ASCII chunks are already written
+        writer.stats.bytes_written = writer.position().unwrap_or(0);
+        writer
     }
 
     /// Get the file path.
@@ -87,7 +203,7 @@ impl SdifWriter {
 
     /// Get the number of frames written so far.
     pub fn frame_count(&self) -> usize {
-        self.frame_count
+        self.stats.frames
     }
 
     /// Get the last written timestamp.
@@ -95,11 +211,154 @@ impl SdifWriter {
         self.last_time
     }
 
+    /// Get statistics on what has been written so far.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let mut writer = SdifFile::builder().create("output.sdif")?.build()?;
+    /// writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[220.0, 0.9])?;
+    ///
+    /// let stats = writer.stats();
+    /// println!("{} bytes, {} frames", stats.bytes_written, stats.frames);
+    /// for (sig, count) in &stats.frames_by_signature {
+    ///     println!("  {}: {}", sig, count);
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn stats(&self) -> &WriterStats {
+        &self.stats
+    }
+
+    /// Get the warnings about the frames written so far.
+    ///
+    /// Each duplicate frame written while checking with
+    /// [`DuplicateFrames::Warn`] produces one warning.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Get the ID of a stream declared with
+    /// [`SdifFileBuilder::named_stream()`](crate::SdifFileBuilder::named_stream).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::UnknownStream`] if no stream with this name was declared
+    pub fn named_stream(&self, name: &str) -> Result<u32> {
+        self.streams
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::UnknownStream {
+                name: name.to_string(),
+            })
+    }
+
+    /// Check whether matrix data is stored in column-major order.
+    ///
+    /// See [`SdifFileBuilder::column_major()`](crate::SdifFileBuilder::column_major).
+    pub fn is_column_major(&self) -> bool {
+        self.column_major
+    }
+
+    /// Get the data type that f64 data is written as for a matrix signature.
+    ///
+    /// This is `Float8` unless a different default was set with
+    /// [`SdifFileBuilder::default_data_type()`](crate::SdifFileBuilder::default_data_type).
+    pub fn data_type_for(&self, matrix_sig: &str) -> DataType {
+        string_to_signature(matrix_sig)
+            .map(|sig| self.default_data_type(sig))
+            .unwrap_or_default()
+    }
+
+    /// Snap all subsequent frame times to a regular grid.
+    ///
+    /// Each frame time is rounded to the nearest multiple of `hop`. Times
+    /// derived from MAT time vectors often drift by a few ULPs from the
+    /// intended hop; snapping makes the written times exactly regular.
+    ///
+    /// # Arguments
+    ///
+    /// * `hop` - Grid spacing in seconds
+    /// * `tolerance` - Maximum allowed distance from the nearest grid point
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if `hop` is not positive or `tolerance`
+    ///   is negative
+    ///
+    /// Writing a frame whose time is further than `tolerance` from the grid
+    /// fails with [`Error::TimeOffGrid`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// # let mut writer = SdifFile::builder()
+    /// #     .create("output.sdif")?
+    /// #     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    /// #     .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+    /// #     .build()?;
+    /// writer.snap_times_to_grid(0.01, 1e-6)?;
+    ///
+    /// // Written at exactly 3 * 0.01
+    /// writer.write_frame_one_matrix("1FQ0", 0.030000000001, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn snap_times_to_grid(&mut self, hop: f64, tolerance: f64) -> Result<()> {
+        if !(hop.is_finite() && hop > 0.0) {
+            return Err(Error::invalid_format(format!(
+                "Grid hop must be positive, got {}",
+                hop
+            )));
+        }
+        if !(tolerance.is_finite() && tolerance >= 0.0) {
+            return Err(Error::invalid_format(format!(
+                "Grid tolerance must be non-negative, got {}",
+                tolerance
+            )));
+        }
+
+        self.time_grid = Some(TimeGrid { hop, tolerance });
+        Ok(())
+    }
+
+    /// Check subsequent frames for duplicates.
+    ///
+    /// A frame is a duplicate if a frame with the same time, stream ID and
+    /// signature was already written. Duplicates are counted in
+    /// [`WriterStats::duplicate_frames`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// use sdif_rs::DuplicateFrames;
+    ///
+    /// # let mut writer = SdifFile::builder()
+    /// #     .create("output.sdif")?
+    /// #     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    /// #     .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+    /// #     .build()?;
+    /// writer.check_duplicate_frames(DuplicateFrames::Reject);
+    ///
+    /// writer.write_frame_one_matrix("1FQ0", 0.01, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    /// assert!(writer.write_frame_one_matrix("1FQ0", 0.01, "1FQ0", 1, 2, &[441.0, 0.9]).is_err());
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn check_duplicate_frames(&mut self, action: DuplicateFrames) {
+        self.duplicate_check = Some(action);
+    }
+
     /// Write a frame containing a single matrix.
     ///
     /// This is a convenience method for the common case of one matrix per frame.
     /// For frames with multiple matrices, use [`new_frame()`](Self::new_frame).
     ///
+    /// The data is written as `Float8`, or as the default data type set for
+    /// `matrix_sig` with
+    /// [`SdifFileBuilder::default_data_type()`](crate::SdifFileBuilder::default_data_type).
+    ///
     /// # Arguments
     ///
     /// * `frame_sig` - Frame type signature (e.g., "1TRC")
@@ -142,6 +401,7 @@ impl SdifWriter {
         data: &[f64],
     ) -> Result<()> {
         self.check_not_closed()?;
+        let time = self.snap_time(time)?;
         self.validate_time(time)?;
 
         // Validate data size
@@ -153,21 +413,34 @@ impl SdifWriter {
         // Convert signatures
         let frame_sig_u32 = string_to_signature(frame_sig)?;
         let matrix_sig_u32 = string_to_signature(matrix_sig)?;
+        self.check_duplicate(time, frame_sig_u32, 0)?;
 
         unsafe {
-            self.write_frame_and_matrix_raw(
-                frame_sig_u32,
-                time,
-                0, // stream_id
-                matrix_sig_u32,
-                rows as u32,
-                cols as u32,
-                data,
-            )?;
+            if self.default_data_type(matrix_sig_u32) == DataType::Float4 {
+                let data: Vec<f32> = data.iter().map(|&v| v as f32).collect();
+                self.write_frame_and_matrix_raw(
+                    frame_sig_u32,
+                    time,
+                    0, // stream_id
+                    matrix_sig_u32,
+                    rows as u32,
+                    cols as u32,
+                    &data,
+                )?;
+            } else {
+                self.write_frame_and_matrix_raw(
+                    frame_sig_u32,
+                    time,
+                    0, // stream_id
+                    matrix_sig_u32,
+                    rows as u32,
+                    cols as u32,
+                    data,
+                )?;
+            }
         }
 
-        self.last_time = Some(time);
-        self.frame_count += 1;
+        self.record_frame_written(time, frame_sig_u32, 0, rows);
 
         Ok(())
     }
@@ -184,8 +457,40 @@ impl SdifWriter {
         rows: usize,
         cols: usize,
         data: &[f32],
+    ) -> Result<()> {
+        self.write_frame_one_matrix_of(frame_sig, time, matrix_sig, rows, cols, data)
+    }
+
+    /// Write a frame with one matrix containing i32 data.
+    ///
+    /// Similar to [`write_frame_one_matrix`](Self::write_frame_one_matrix)
+    /// but writes the matrix as `Int4`. Use
+    /// [`new_frame()`](Self::new_frame) for the other integer types and
+    /// text.
+    pub fn write_frame_one_matrix_i32(
+        &mut self,
+        frame_sig: &str,
+        time: f64,
+        matrix_sig: &str,
+        rows: usize,
+        cols: usize,
+        data: &[i32],
+    ) -> Result<()> {
+        self.write_frame_one_matrix_of(frame_sig, time, matrix_sig, rows, cols, data)
+    }
+
+    /// Write a frame with one matrix of data written as stored.
+    fn write_frame_one_matrix_of<T: MatrixElement>(
+        &mut self,
+        frame_sig: &str,
+        time: f64,
+        matrix_sig: &str,
+        rows: usize,
+        cols: usize,
+        data: &[T],
     ) -> Result<()> {
         self.check_not_closed()?;
+        let time = self.snap_time(time)?;
         self.validate_time(time)?;
 
         let expected_len = rows * cols;
@@ -195,9 +500,10 @@ impl SdifWriter {
 
         let frame_sig_u32 = string_to_signature(frame_sig)?;
         let matrix_sig_u32 = string_to_signature(matrix_sig)?;
+        self.check_duplicate(time, frame_sig_u32, 0)?;
 
         unsafe {
-            self.write_frame_and_matrix_raw_f32(
+            self.write_frame_and_matrix_raw(
                 frame_sig_u32,
                 time,
                 0,
@@ -208,12 +514,112 @@ impl SdifWriter {
             )?;
         }
 
-        self.last_time = Some(time);
-        self.frame_count += 1;
+        self.record_frame_written(time, frame_sig_u32, 0, rows);
 
         Ok(())
     }
 
+    /// Write a frame with one matrix given column by column.
+    ///
+    /// Each entry names a column of the matrix type `signature` and holds
+    /// its values, one per row. The columns are interleaved into row-major
+    /// order, in the order the matrix type declares them, and written as
+    /// with [`write_frame_one_matrix`](Self::write_frame_one_matrix) using
+    /// `signature` for both the frame and the matrix.
+    ///
+    /// Trailing columns of the type may be left out, as SDIF allows
+    /// matrices narrower than their type.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidFormat`] if the matrix type isn't declared, a
+    ///   column name is unknown or repeated, a column before the last one
+    ///   given is missing, or the columns have different lengths
+    /// - Same as [`write_frame_one_matrix`](Self::write_frame_one_matrix)
+    ///   otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::SdifFile;
+    /// let mut writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+    ///     .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+    ///     .build()?;
+    ///
+    /// let index = vec![1.0, 2.0];
+    /// let freqs = vec![440.0, 880.0];
+    /// let amps = vec![0.5, 0.3];
+    /// writer.write_frame_from_columns(
+    ///     "1TRC",
+    ///     0.0,
+    ///     &[("Index", &index), ("Frequency", &freqs), ("Amplitude", &amps)],
+    /// )?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn write_frame_from_columns<C: AsRef<[f64]>>(
+        &mut self,
+        signature: &str,
+        time: f64,
+        columns: &[(&str, C)],
+    ) -> Result<()> {
+        self.check_not_closed()?;
+
+        let sig = string_to_signature(signature)?;
+        let type_columns = self.matrix_columns(sig);
+        if type_columns.is_empty() {
+            return Err(Error::invalid_format(format!(
+                "Matrix type '{}' is not declared",
+                signature
+            )));
+        }
+
+        // Place each given column at its position in the type
+        let mut placed: Vec<Option<&[f64]>> = vec![None; type_columns.len()];
+        for (name, values) in columns {
+            let pos = type_columns.iter().position(|c| c == name).ok_or_else(|| {
+                Error::invalid_format(format!(
+                    "Matrix type '{}' has no column '{}'",
+                    signature, name
+                ))
+            })?;
+            if placed[pos].replace(values.as_ref()).is_some() {
+                return Err(Error::invalid_format(format!(
+                    "Column '{}' given twice",
+                    name
+                )));
+            }
+        }
+
+        let cols = placed
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |last| last + 1);
+        let placed = placed[..cols]
+            .iter()
+            .zip(&type_columns)
+            .map(|(values, name)| {
+                values.ok_or_else(|| Error::invalid_format(format!("Column '{}' is missing", name)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let rows = placed.first().map_or(0, |values| values.len());
+        if let Some((name, _)) = columns.iter().find(|(_, v)| v.as_ref().len() != rows) {
+            return Err(Error::invalid_format(format!(
+                "Column '{}' has a different length than the others ({} rows)",
+                name, rows
+            )));
+        }
+
+        let mut data = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            data.extend(placed.iter().map(|values| values[row]));
+        }
+
+        self.write_frame_one_matrix(signature, time, signature, rows, cols, &data)
+    }
+
     /// Start building a frame with multiple matrices.
     ///
     /// Returns a [`FrameBuilder`] that allows adding multiple matrices
@@ -251,13 +657,62 @@ impl SdifWriter {
         stream_id: u32,
     ) -> Result<FrameBuilder<'_>> {
         self.check_not_closed()?;
+        let time = self.snap_time(time)?;
         self.validate_time(time)?;
 
         let sig = string_to_signature(signature)?;
+        self.check_duplicate(time, sig, stream_id)?;
 
         Ok(FrameBuilder::new(self, sig, time, stream_id))
     }
 
+    /// Write an owned frame, for example one read from another file.
+    ///
+    /// Each matrix is written with its own data type.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if the frame has no matrices or the file
+    ///   is closed
+    /// - Any error from [`FrameBuilder::add_matrix_data()`]
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let input = SdifFile::open("input.sdif")?;
+    /// let mut writer = SdifFile::builder()
+    ///     .create("output.sdif")?
+    ///     .with_schema(&input.describe_types()?)?
+    ///     .build()?;
+    ///
+    /// for frame in input.frames() {
+    ///     writer.write_frame_data(&frame?.into_data()?)?;
+    /// }
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn write_frame_data(&mut self, frame: &FrameData) -> Result<()> {
+        if frame.matrices.is_empty() {
+            return Err(Error::invalid_state("Frame must have at least one matrix"));
+        }
+
+        // Validate every matrix before starting the frame
+        let matrices = frame
+            .matrices
+            .iter()
+            .map(PendingMatrix::from_data)
+            .collect::<Result<Vec<_>>>()?;
+
+        matrices
+            .into_iter()
+            .fold(
+                self.new_frame(&frame.signature, frame.time, frame.stream_id)?,
+                FrameBuilder::push_matrix,
+            )
+            .finish()
+    }
+
     /// Close the file and finalize writing.
     ///
     /// This must be called to ensure all data is flushed and the file
@@ -272,18 +727,74 @@ impl SdifWriter {
         self.do_close()
     }
 
+    /// Close the file and return its contents.
+    ///
+    /// Meant for writers created with
+    /// [`SdifFileBuilder::create_temporary()`](crate::SdifFileBuilder::create_temporary),
+    /// but works with any writer; a file written at a path is left in place.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be finalized or read back
+    pub fn into_bytes(mut self) -> Result<Vec<u8>> {
+        self.do_close()?;
+        Ok(fs::read(&self.path)?)
+    }
+
+    /// Close the file and copy its contents to `sink`, such as a socket.
+    ///
+    /// Returns the number of bytes copied. See
+    /// [`into_bytes()`](Self::into_bytes).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the file can't be finalized or read back, or
+    ///   `sink` can't be written
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::net::TcpStream;
+    /// use sdif_rs::SdifFile;
+    ///
+    /// let mut writer = SdifFile::builder()
+    ///     .create_temporary()?
+    ///     .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+    ///     .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+    ///     .build()?;
+    /// writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    ///
+    /// let mut socket = TcpStream::connect("127.0.0.1:9000")?;
+    /// writer.finish_into(&mut socket)?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn finish_into(mut self, mut sink: impl Write) -> Result<u64> {
+        self.do_close()?;
+        let copied = io::copy(&mut File::open(&self.path)?, &mut sink)?;
+        sink.flush()?;
+        Ok(copied)
+    }
+
     /// Internal close implementation.
     fn do_close(&mut self) -> Result<()> {
         if self.closed {
             return Ok(());
         }
 
+        let end = self
+            .preallocated
+            .then(|| self.position().unwrap_or(self.stats.bytes_written));
         self.closed = true;
 
         unsafe {
             SdifFClose(self.handle.as_ptr());
         }
 
+        // Drop the unused tail of the preallocated space
+        if let Some(end) = end {
+            OpenOptions::new().write(true).open(&self.path)?.set_len(end)?;
+        }
+
         Ok(())
     }
 
@@ -296,6 +807,25 @@ impl SdifWriter {
         }
     }
 
+    /// Snap a frame time to the time grid, if one is set.
+    fn snap_time(&self, time: f64) -> Result<f64> {
+        let grid = match self.time_grid {
+            Some(grid) => grid,
+            None => return Ok(time),
+        };
+
+        let nearest = (time / grid.hop).round() * grid.hop;
+        if (time - nearest).abs() > grid.tolerance {
+            return Err(Error::TimeOffGrid {
+                time,
+                nearest,
+                tolerance: grid.tolerance,
+            });
+        }
+
+        Ok(nearest)
+    }
+
     /// Validate that time is non-decreasing.
     fn validate_time(&self, time: f64) -> Result<()> {
         if let Some(last) = self.last_time {
@@ -309,52 +839,132 @@ impl SdifWriter {
         Ok(())
     }
 
+    /// Check whether a frame would repeat one already written.
+    fn is_duplicate(&self, time: f64, signature: Signature, stream_id: u32) -> bool {
+        self.last_time == Some(time) && self.frames_at_last_time.contains(&(signature, stream_id))
+    }
+
+    /// Reject a duplicate frame, if duplicates are rejected.
+    fn check_duplicate(&self, time: f64, signature: Signature, stream_id: u32) -> Result<()> {
+        if self.duplicate_check == Some(DuplicateFrames::Reject)
+            && self.is_duplicate(time, signature, stream_id)
+        {
+            return Err(Error::DuplicateFrame {
+                time,
+                stream_id,
+                signature: signature_to_string(signature),
+            });
+        }
+        Ok(())
+    }
+
     /// Get the raw file handle (for FrameBuilder).
     pub(crate) fn handle(&self) -> *mut SdifFileT {
         self.handle.as_ptr()
     }
 
-    /// Record that a frame was written (called by FrameBuilder).
-    pub(crate) fn record_frame_written(&mut self, time: f64) {
-        self.last_time = Some(time);
-        self.frame_count += 1;
+    /// Get the column names of a matrix type declared in the file.
+    fn matrix_columns(&self, signature: Signature) -> Vec<String> {
+        unsafe {
+            let mtypes = SdifFGetMatrixTypesTable(self.handle());
+            let mtype = if mtypes.is_null() {
+                std::ptr::null_mut()
+            } else {
+                SdifGetMatrixType(mtypes, signature)
+            };
+            if mtype.is_null() {
+                return Vec::new();
+            }
+            Schema::describe_matrix_type(mtype).columns
+        }
     }
 
-    /// Write a frame with one matrix using raw signatures (f64 data).
-    unsafe fn write_frame_and_matrix_raw(
-        &self,
-        frame_sig: u32,
+    /// Get the data type that f64 data is written as for a matrix signature.
+    pub(crate) fn default_data_type(&self, matrix_sig: Signature) -> DataType {
+        self.data_types.get(&matrix_sig).copied().unwrap_or_default()
+    }
+
+    /// Grow the file to hold `bytes` more bytes (best effort).
+    ///
+    /// The file is truncated to the size actually written when closed.
+    pub(crate) fn preallocate(&mut self, bytes: u64) {
+        let Ok(start) = self.position() else {
+            return;
+        };
+        if let Ok(file) = OpenOptions::new().write(true).open(&self.path) {
+            allocate(&file, start + bytes);
+            self.preallocated = true;
+        }
+    }
+
+    /// Remove the file when the writer is dropped.
+    pub(crate) fn remove_on_drop(&mut self) {
+        self.temporary = true;
+    }
+
+    /// Get the number of bytes written to the file so far.
+    pub(crate) fn position(&self) -> Result<u64> {
+        self.check_not_closed()?;
+
+        let mut pos: SdiffPosT = 0;
+        if unsafe { SdifFGetPos(self.handle.as_ptr(), &mut pos) } != 0 {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Failed to get file position",
+            )));
+        }
+
+        Ok(pos as u64)
+    }
+
+    /// Continue after frames already in the file, the last at `time`.
+    pub(crate) fn resume_after(&mut self, time: Option<f64>) {
+        self.last_time = time;
+    }
+
+    /// Record that a frame was written.
+    pub(crate) fn record_frame_written(
+        &mut self,
         time: f64,
+        frame_sig: Signature,
         stream_id: u32,
-        matrix_sig: u32,
-        rows: u32,
-        cols: u32,
-        data: &[f64],
-    ) -> Result<()> {
-        let bytes_written = SdifFWriteFrameAndOneMatrix(
-            self.handle.as_ptr(),
-            frame_sig,
-            stream_id,
-            time,
-            matrix_sig,
-            SdifDataTypeET_eFloat8,
-            rows,
-            cols,
-            data.as_ptr() as *mut libc::c_void,
-        );
+        rows: usize,
+    ) {
+        if self.duplicate_check.is_some() && self.is_duplicate(time, frame_sig, stream_id) {
+            self.stats.duplicate_frames += 1;
+            self.warnings.push(format!(
+                "Duplicate {} frame at {}s in stream {}",
+                signature_to_string(frame_sig),
+                time,
+                stream_id
+            ));
+        }
+        if self.last_time != Some(time) {
+            self.frames_at_last_time.clear();
+        }
+        self.frames_at_last_time.insert((frame_sig, stream_id));
+        self.last_time = Some(time);
 
-        if bytes_written == 0 {
-            Err(Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to write frame",
-            )))
-        } else {
-            Ok(())
+        let stats = &mut self.stats;
+        stats.frames += 1;
+        *stats
+            .frames_by_signature
+            .entry(signature_to_string(frame_sig))
+            .or_insert(0) += 1;
+        stats.rows += rows;
+        stats.min_time = Some(stats.min_time.map_or(time, |t| t.min(time)));
+        stats.max_time = Some(stats.max_time.map_or(time, |t| t.max(time)));
+
+        let previous = self.stats.bytes_written;
+        if let Ok(position) = self.position() {
+            self.stats.bytes_written = position;
         }
+        metrics::frame_written(frame_sig, self.stats.bytes_written.saturating_sub(previous));
     }
 
-    /// Write a frame with one matrix using raw signatures (f32 data).
-    unsafe fn write_frame_and_matrix_raw_f32(
+    /// Write a frame with one matrix using raw signatures.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn write_frame_and_matrix_raw<T: MatrixElement>(
         &self,
         frame_sig: u32,
         time: f64,
@@ -362,28 +972,28 @@ impl SdifWriter {
         matrix_sig: u32,
         rows: u32,
         cols: u32,
-        data: &[f32],
+        data: &[T],
     ) -> Result<()> {
-        let bytes_written = SdifFWriteFrameAndOneMatrix(
+        let column_major;
+        let data = if self.column_major {
+            column_major = transpose(data, rows as usize, cols as usize);
+            &column_major[..]
+        } else {
+            data
+        };
+
+        write::write_frame_and_one_matrix(
             self.handle.as_ptr(),
             frame_sig,
             stream_id,
             time,
             matrix_sig,
-            SdifDataTypeET_eFloat4,
+            T::DATA_TYPE,
             rows,
             cols,
-            data.as_ptr() as *mut libc::c_void,
-        );
-
-        if bytes_written == 0 {
-            Err(Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to write frame",
-            )))
-        } else {
-            Ok(())
-        }
+            write::bytes_of(data),
+        )?;
+        Ok(())
     }
 }
 
@@ -393,11 +1003,44 @@ impl Drop for SdifWriter {
             // Best-effort close, ignore errors
             let _ = self.do_close();
         }
+
+        if self.temporary {
+            let _ = fs::remove_file(&self.path);
+        }
     }
 }
 
 // PhantomData<*const ()> makes SdifWriter !Send and !Sync automatically
 
+/// Allocate disk space for the first `len` bytes of a file.
+///
+/// Failures are ignored: preallocation only reduces fragmentation.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn allocate(file: &File, len: u64) {
+    use std::ffi::c_int;
+    use std::os::unix::io::AsRawFd;
+
+    extern "C" {
+        // glibc and bionic have a 32-bit off_t on 32-bit targets, so use
+        // their 64-bit variant; musl and FreeBSD's off_t is always 64-bit
+        #[cfg_attr(
+            any(all(target_os = "linux", target_env = "gnu"), target_os = "android"),
+            link_name = "posix_fallocate64"
+        )]
+        fn posix_fallocate(fd: c_int, offset: i64, len: i64) -> c_int;
+    }
+
+    unsafe {
+        posix_fallocate(file.as_raw_fd(), 0, len as i64);
+    }
+}
+
+/// Allocate disk space for the first `len` bytes of a file.
+///
+/// Not supported on this platform; the file grows as it is written.
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn allocate(_file: &File, _len: u64) {}
+
 // ============================================================================
 // ndarray Integration
 // ============================================================================
@@ -488,4 +1131,15 @@ impl SdifWriter {
 #[cfg(test)]
 mod tests {
     // Most tests require actual file I/O - see integration tests
+    use super::*;
+
+    #[test]
+    fn test_stats_duration() {
+        let mut stats = WriterStats::default();
+        assert_eq!(stats.duration(), 0.0);
+
+        stats.min_time = Some(0.5);
+        stats.max_time = Some(2.0);
+        assert_eq!(stats.duration(), 1.5);
+    }
 }
@@ -0,0 +1,276 @@
+//! A pool of SDIF writers that can be shared between threads.
+//!
+//! [`SdifWriter`] is `!Send` because the C library keeps global state, so
+//! a service that receives frames on many threads can't hand writers
+//! around directly. [`WriterPool`] owns its writers on a dedicated worker
+//! thread and forwards each request to it, so callers on any thread can
+//! route frames to many files by output path.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_rs::WriterPool;
+//!
+//! let pool = WriterPool::new()?;
+//!
+//! pool.open("voice.sdif", |builder| {
+//!     builder
+//!         .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+//!         .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])
+//! })?;
+//!
+//! pool.with_writer("voice.sdif", |writer| {
+//!     writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])
+//! })?;
+//!
+//! pool.close("voice.sdif")?;
+//! # Ok::<(), sdif_rs::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+use crate::builder::{Config, SdifFileBuilder};
+use crate::error::{Error, Result};
+use crate::file::SdifFile;
+use crate::writer::SdifWriter;
+
+/// Writers owned by the worker thread, keyed by output path.
+type Writers = HashMap<PathBuf, SdifWriter>;
+
+/// A unit of work executed on the worker thread.
+type Job = Box<dyn FnOnce(&mut Writers) + Send>;
+
+/// A thread-safe collection of open SDIF writers keyed by output path.
+///
+/// All writers live on a single worker thread owned by the pool. Every
+/// method sends a request to that thread and waits for its result, so
+/// the pool itself is `Send + Sync` and can be shared with `Arc`.
+///
+/// Using one thread for all writers also serializes every call into the
+/// C library, which is not thread-safe. Avoid reading or writing other
+/// SDIF files on other threads while the pool is in use.
+///
+/// Dropping the pool closes every writer that is still open.
+pub struct WriterPool {
+    /// Channel to the worker thread. `None` once the pool is shut down.
+    sender: Mutex<Option<Sender<Job>>>,
+
+    /// Handle of the worker thread, joined on shutdown.
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WriterPool {
+    /// Create an empty pool and start its worker thread.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Io`] if the worker thread can't be spawned
+    pub fn new() -> Result<Self> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+
+        let worker = thread::Builder::new()
+            .name("sdif-writer-pool".to_string())
+            .spawn(move || {
+                let mut writers = Writers::new();
+                for job in receiver {
+                    job(&mut writers);
+                }
+                // Remaining writers are closed when `writers` is dropped.
+            })?;
+
+        Ok(WriterPool {
+            sender: Mutex::new(Some(sender)),
+            worker: Some(worker),
+        })
+    }
+
+    /// Create a new file in the pool.
+    ///
+    /// `configure` receives a builder for `path` on the worker thread and
+    /// adds NVTs and type definitions to it; the pool then writes the
+    /// header and keeps the writer open under `path`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if a writer for `path` is already open
+    /// - Any error returned by `configure` or while creating the file
+    pub fn open<F>(&self, path: impl Into<PathBuf>, configure: F) -> Result<()>
+    where
+        F: FnOnce(SdifFileBuilder<Config>) -> Result<SdifFileBuilder<Config>> + Send + 'static,
+    {
+        let path = path.into();
+
+        self.call(move |writers| {
+            if writers.contains_key(&path) {
+                return Err(Error::invalid_state("A writer for this path is already open"));
+            }
+
+            let writer = configure(SdifFile::builder().create(&path)?)?.build()?;
+            writers.insert(path, writer);
+            Ok(())
+        })
+    }
+
+    /// Run `f` with the writer open under `path`.
+    ///
+    /// `f` runs on the worker thread, so it must be `Send`; capture the
+    /// frame data by value.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if no writer is open for `path`
+    /// - Any error returned by `f`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sdif_rs::WriterPool;
+    /// # let pool = WriterPool::new()?;
+    /// let data = vec![1.0, 440.0, 0.5, 0.0];
+    /// pool.with_writer("output.sdif", move |writer| {
+    ///     writer.write_frame_one_matrix("1TRC", 0.0, "1TRC", 1, 4, &data)
+    /// })?;
+    /// # Ok::<(), sdif_rs::Error>(())
+    /// ```
+    pub fn with_writer<F, R>(&self, path: impl AsRef<Path>, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut SdifWriter) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+
+        self.call(move |writers| match writers.get_mut(&path) {
+            Some(writer) => f(writer),
+            None => Err(Error::invalid_state("No writer is open for this path")),
+        })
+    }
+
+    /// Close the writer open under `path` and remove it from the pool.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::InvalidState`] if no writer is open for `path`
+    pub fn close(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+
+        self.call(move |writers| match writers.remove(&path) {
+            Some(writer) => writer.close(),
+            None => Err(Error::invalid_state("No writer is open for this path")),
+        })
+    }
+
+    /// Check whether a writer is open under `path`.
+    pub fn contains(&self, path: impl AsRef<Path>) -> Result<bool> {
+        let path = path.as_ref().to_path_buf();
+        self.call(move |writers| Ok(writers.contains_key(&path)))
+    }
+
+    /// Get the paths of all open writers, sorted.
+    pub fn paths(&self) -> Result<Vec<PathBuf>> {
+        self.call(|writers| {
+            let mut paths: Vec<PathBuf> = writers.keys().cloned().collect();
+            paths.sort();
+            Ok(paths)
+        })
+    }
+
+    /// Close every open writer and stop the worker thread.
+    ///
+    /// Dropping the pool does the same, but ignores errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from closing a writer.
+    pub fn shutdown(mut self) -> Result<()> {
+        let result = self.call(|writers| {
+            let mut result = Ok(());
+            for (_, writer) in writers.drain() {
+                let closed = writer.close();
+                if result.is_ok() {
+                    result = closed;
+                }
+            }
+            result
+        });
+        self.stop();
+        result
+    }
+
+    /// Execute `f` on the worker thread and wait for its result.
+    fn call<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Writers) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let job: Job = Box::new(move |writers| {
+            let _ = reply_tx.send(f(writers));
+        });
+
+        {
+            let sender = self
+                .sender
+                .lock()
+                .map_err(|_| Error::invalid_state("Writer pool lock is poisoned"))?;
+            sender
+                .as_ref()
+                .ok_or_else(|| Error::invalid_state("Writer pool has been shut down"))?
+                .send(job)
+                .map_err(|_| Error::invalid_state("Writer pool worker has stopped"))?;
+        }
+
+        reply_rx
+            .recv()
+            .map_err(|_| Error::invalid_state("Writer pool worker has stopped"))?
+    }
+
+    /// Disconnect the worker and wait for it to close all writers.
+    fn stop(&mut self) {
+        if let Ok(mut sender) = self.sender.lock() {
+            sender.take();
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for WriterPool {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_pool_is_send_sync() {
+        assert_send_sync::<WriterPool>();
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let pool = WriterPool::new().unwrap();
+
+        assert!(!pool.contains("missing.sdif").unwrap());
+        assert!(pool.paths().unwrap().is_empty());
+
+        let result = pool.with_writer("missing.sdif", |w| Ok(w.frame_count()));
+        assert!(matches!(result, Err(Error::InvalidState { .. })));
+        assert!(matches!(pool.close("missing.sdif"), Err(Error::InvalidState { .. })));
+    }
+
+    #[test]
+    fn test_shutdown_empty_pool() {
+        let pool = WriterPool::new().unwrap();
+        assert!(pool.shutdown().is_ok());
+    }
+}
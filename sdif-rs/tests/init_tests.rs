@@ -0,0 +1,41 @@
+//! Integration tests for poisoned library initialization.
+//!
+//! The library is initialized once per process, so these tests live in
+//! their own test binary and run as a single test.
+
+use sdif_rs::init::{self, ALLOW_MISSING_TYPES_ENV, TYPES_ENV};
+use sdif_rs::{Error, SdifFile};
+use std::env;
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_missing_types_file_poisons_init() {
+    env::remove_var(ALLOW_MISSING_TYPES_ENV);
+    env::set_var(TYPES_ENV, "/nonexistent/SdifTypes.STYP");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out.sdif");
+
+    // Every open fails, not just the first one
+    for _ in 0..2 {
+        match SdifFile::builder().create(&path).unwrap().build() {
+            Err(Error::InitPoisoned { reason }) => {
+                assert!(reason.contains("/nonexistent/SdifTypes.STYP"));
+                assert!(reason.contains(ALLOW_MISSING_TYPES_ENV));
+            }
+            Err(e) => panic!("Expected InitPoisoned, got: {:?}", e),
+            Ok(_) => panic!("Expected InitPoisoned"),
+        }
+    }
+    assert!(init::is_initialized());
+    assert!(init::poison_reason().is_some());
+
+    // The override accepts the built-in types
+    env::set_var(ALLOW_MISSING_TYPES_ENV, "1");
+    let mut writer = SdifFile::builder().create(&path).unwrap().build().unwrap();
+    writer
+        .write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 1, &[440.0])
+        .unwrap();
+    writer.close().unwrap();
+    assert_eq!(SdifFile::open(&path).unwrap().frames().count(), 1);
+}
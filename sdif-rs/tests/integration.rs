@@ -122,8 +122,9 @@ fn test_read_matrices() {
             assert!(matrix.cols() > 0, "Matrix should have columns");
 
             // Read data
+            let len = matrix.rows() * matrix.cols();
             let data = matrix.data_f64().expect("Failed to read matrix data");
-            assert_eq!(data.len(), matrix.rows() * matrix.cols());
+            assert_eq!(data.len(), len);
         }
     }
 }
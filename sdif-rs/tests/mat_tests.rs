@@ -148,6 +148,66 @@ fn test_mat_to_sdif_conversion() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[ignore = "Requires test fixture: simple.mat"]
+fn test_custom_detector() -> Result<()> {
+    use sdif_rs::{Error, HeuristicDetector, MatData, VariableDetector};
+
+    /// Detector for a layout that never matches, to see it being asked.
+    struct Missing;
+
+    impl VariableDetector for Missing {
+        fn time_variable<'m>(&self, _mat: &'m MatFile) -> Result<&'m MatData> {
+            Err(Error::invalid_format("no time here"))
+        }
+
+        fn data_variable<'m>(&self, mat: &'m MatFile) -> Result<&'m MatData> {
+            HeuristicDetector.data_variable(mat)
+        }
+    }
+
+    let mat_path = mat_fixture("simple.mat");
+    if !mat_path.exists() {
+        return Ok(());
+    }
+    let mat = MatFile::open(&mat_path)?;
+
+    let result = MatToSdifConverter::new_with_detector(&mat, MatToSdifConfig::new(), &Missing);
+    assert!(matches!(result, Err(Error::InvalidFormat { reason }) if reason == "no time here"));
+
+    // Explicit names take precedence over the detector
+    let time = HeuristicDetector.time_variable(&mat)?.name().to_string();
+    let config = MatToSdifConfig::new().time_var(time);
+    assert!(MatToSdifConverter::new_with_detector(&mat, config, &Missing).is_ok());
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "Requires test fixture: simple.mat"]
+fn test_column_ranges() -> Result<()> {
+    let mat_path = mat_fixture("simple.mat");
+    if !mat_path.exists() {
+        return Ok(());
+    }
+    let mat = MatFile::open(&mat_path)?;
+
+    // Nothing fits in an empty range
+    let config = MatToSdifConfig::new().column_range("Frequency", 0.0..0.0);
+    let converter = MatToSdifConverter::new(&mat, config.clone())?;
+    let warnings = converter.check_ranges()?;
+    assert!(!warnings.is_empty());
+    assert!(warnings[0].starts_with("Frame 0 "));
+
+    let strict = MatToSdifConverter::new(&mat, config.strict_ranges(true))?;
+    assert!(strict.check_ranges().is_err());
+
+    let unknown = MatToSdifConfig::new().column_range("Pitch", 0.0..1.0);
+    assert!(MatToSdifConverter::new(&mat, unknown)?.check_ranges().is_err());
+
+    Ok(())
+}
+
 #[test]
 #[ignore = "Requires test fixture: complex.mat"]
 fn test_complex_magnitude() -> Result<()> {
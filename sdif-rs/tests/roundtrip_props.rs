@@ -0,0 +1,150 @@
+//! Property tests: random frames written and read back unchanged.
+//!
+//! Each case generates a sequence of frames with random signatures,
+//! stream IDs, times, matrix shapes and data types, writes them with
+//! [`SdifWriter::write_frame_data()`], and checks that reading the file
+//! returns the same frames and that the file has exactly the size the
+//! frame and matrix headers, data and padding call for.
+//!
+//! Only the data types the writer supports, `Float4` and `Float8`, are
+//! generated.
+
+use proptest::prelude::*;
+use sdif_rs::{DataType, FrameData, MatrixData, SdifFile};
+use tempfile::NamedTempFile;
+
+/// Matrix signatures used in generated frames.
+const MATRIX_SIGNATURES: &[&str] = &["XPA1", "XPB2", "XPC3"];
+
+/// Frame signatures used in generated frames.
+const FRAME_SIGNATURES: &[&str] = &["XPF1", "XPF2"];
+
+/// Generate a matrix of up to 39x7 values in one of the writable types.
+fn matrix() -> impl Strategy<Value = MatrixData> {
+    (
+        proptest::sample::select(MATRIX_SIGNATURES),
+        0usize..40,
+        0usize..8,
+        any::<bool>(),
+    )
+        .prop_flat_map(|(signature, rows, cols, float4)| {
+            let len = rows * cols;
+            let data = if float4 {
+                proptest::collection::vec(
+                    any::<f32>().prop_filter("finite", |v| v.is_finite()),
+                    len,
+                )
+                .prop_map(|values| values.into_iter().map(f64::from).collect())
+                .boxed()
+            } else {
+                proptest::collection::vec(
+                    any::<f64>().prop_filter("finite", |v| v.is_finite()),
+                    len,
+                )
+                .boxed()
+            };
+            data.prop_map(move |data| {
+                let mut matrix = MatrixData::new(signature, rows, cols, data);
+                if float4 {
+                    matrix.data_type = DataType::Float4;
+                }
+                matrix
+            })
+        })
+}
+
+/// Generate up to 12 frames with non-decreasing times.
+fn frames() -> impl Strategy<Value = Vec<FrameData>> {
+    proptest::collection::vec(
+        (
+            proptest::sample::select(FRAME_SIGNATURES),
+            0u32..4,
+            0.0f64..0.5,
+            proptest::collection::vec(matrix(), 1..4),
+        ),
+        1..12,
+    )
+    .prop_map(|frames| {
+        let mut time = 0.0;
+        frames
+            .into_iter()
+            .map(|(signature, stream_id, step, matrices)| {
+                time += step;
+                FrameData {
+                    matrices,
+                    ..FrameData::new(signature, time, stream_id)
+                }
+            })
+            .collect()
+    })
+}
+
+/// Size of a frame in the file, from the SDIF specification.
+fn frame_bytes(frame: &FrameData) -> u64 {
+    let matrices: u64 = frame
+        .matrices
+        .iter()
+        .map(|m| {
+            let element = if m.data_type == DataType::Float4 {
+                4
+            } else {
+                8
+            };
+            let data = (m.rows * m.cols * element) as u64;
+            16 + data + (8 - data % 8) % 8
+        })
+        .sum();
+    // Signature, size, time, stream ID and matrix count
+    24 + matrices
+}
+
+/// Write frames to a new file and return the size of its header.
+fn write(path: &std::path::Path, frames: &[FrameData]) -> sdif_rs::Result<u64> {
+    SdifFile::builder().create(path)?.build()?.close()?;
+    let header = std::fs::metadata(path)?.len();
+
+    let mut writer = SdifFile::builder().create(path)?.build()?;
+    for frame in frames {
+        writer.write_frame_data(frame)?;
+    }
+    writer.close()?;
+    Ok(header)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    #[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+    fn frames_round_trip(frames in frames()) {
+        let temp = NamedTempFile::new().expect("Failed to create temp file");
+        let header = write(temp.path(), &frames).unwrap();
+
+        let expected_size = header + frames.iter().map(frame_bytes).sum::<u64>();
+        prop_assert_eq!(std::fs::metadata(temp.path()).unwrap().len(), expected_size);
+
+        let file = SdifFile::open(temp.path()).unwrap();
+        let read: Vec<FrameData> = file
+            .frames()
+            .map(|frame| frame.and_then(|f| f.into_data()))
+            .collect::<sdif_rs::Result<_>>()
+            .unwrap();
+
+        prop_assert_eq!(read.len(), frames.len());
+        for (read, written) in read.iter().zip(&frames) {
+            prop_assert_eq!(&read.signature, &written.signature);
+            prop_assert_eq!(read.stream_id, written.stream_id);
+            prop_assert_eq!(read.time.to_bits(), written.time.to_bits());
+            prop_assert_eq!(read.matrices.len(), written.matrices.len());
+
+            for (read, written) in read.matrices.iter().zip(&written.matrices) {
+                prop_assert_eq!(&read.signature, &written.signature);
+                prop_assert_eq!(read.data_type, written.data_type);
+                prop_assert_eq!((read.rows, read.cols), (written.rows, written.cols));
+                let read_bits: Vec<u64> = read.data.iter().map(|v| v.to_bits()).collect();
+                let written_bits: Vec<u64> = written.data.iter().map(|v| v.to_bits()).collect();
+                prop_assert_eq!(read_bits, written_bits);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Integration tests for SDIF writing functionality.
 
-use sdif_rs::{SdifFile, Result, Error};
+use sdif_rs::{ReadOptions, SdifFile, Result, Error};
 use std::fs;
 use tempfile::NamedTempFile;
 
@@ -91,6 +91,155 @@ fn test_write_with_nvt() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_write_history() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_nvt([("creator", "sdif-rs-test")])?
+        .append_history("analyzer", "2.0", [("hop", "0.01")])?
+        .append_history("truncate", "1.0", [("max-frames", "10")])?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    writer.write_frame_one_matrix("1TRC", 0.0, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    writer.close()?;
+
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("history.0.tool\tanalyzer\n"));
+    assert!(text.contains("history.0.param.hop\t0.01\n"));
+    assert!(text.contains("history.1.tool\ttruncate\n"));
+
+    // And reads back in order
+    let history = SdifFile::open(path)?.history();
+    assert_eq!(history.len(), 2);
+    assert_eq!((history[0].tool.as_str(), history[0].version.as_str()), ("analyzer", "2.0"));
+    assert_eq!(history[0].params["hop"], "0.01");
+    assert_eq!(history[1].tool, "truncate");
+    assert_eq!(history[1].params["max-frames"], "10");
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_nvts_roundtrip() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_nvt([("creator", "sdif-rs-test"), ("window", "hann")])?
+        .sample_rate(44100.0)?
+        .append_history("analyzer", "2.0", [("hop", "0.01")])?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    writer.write_frame_one_matrix("1TRC", 0.0, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    assert!(!file.nvts().is_empty());
+    assert_eq!(file.nvt_get("creator"), Some("sdif-rs-test"));
+    assert_eq!(file.nvt_get("window"), Some("hann"));
+    assert_eq!(file.sample_rate(), Some(44100.0));
+
+    let history = file.history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].tool, "analyzer");
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_metadata_roundtrip() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .sample_rate(48000.0)?
+        .creator("first")?
+        .creator("my-analyzer")?
+        .date("2024-03-01T12:00:00Z")?
+        .soundfile("voice.wav")?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    writer.write_frame_one_matrix("1TRC", 0.0, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    assert_eq!(file.sample_rate(), Some(48000.0));
+    assert_eq!(file.creator(), Some("my-analyzer"));
+    assert_eq!(file.date(), Some("2024-03-01T12:00:00Z"));
+    assert_eq!(file.soundfile(), Some("voice.wav"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_source_audio_roundtrip() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let sound = dir.path().join("voice.raw");
+    fs::write(&sound, b"not really a sound")?;
+    let path = dir.path().join("voice.sdif");
+
+    let mut writer = SdifFile::builder()
+        .create(&path)?
+        .set_source_audio(&sound)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    writer.write_frame_one_matrix("1TRC", 0.0, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    writer.close()?;
+
+    let file = SdifFile::open(&path)?;
+    let source = file.source_audio().expect("source audio should be recorded");
+    assert_eq!(source.file_name, "voice.raw");
+    assert_eq!(file.soundfile(), Some("voice.raw"));
+    assert!(source.matches(&sound)?);
+
+    fs::write(&sound, b"a different sound")?;
+    assert!(!source.matches(&sound)?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_written_by_sdif_rs() -> Result<()> {
+    use sdif_rs::metadata::Version;
+
+    let write = |path: &std::path::Path, omit: bool| -> Result<()> {
+        let mut builder = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?;
+        if omit {
+            builder = builder.omit_writer();
+        }
+        let mut writer = builder.build()?;
+        writer.write_frame_one_matrix("1TRC", 0.0, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+        writer.close()
+    };
+
+    let temp = temp_sdif_path();
+    write(temp.path(), false)?;
+    assert_eq!(SdifFile::open(temp.path())?.written_by_sdif_rs(), Some(Version::current()));
+
+    write(temp.path(), true)?;
+    assert_eq!(SdifFile::open(temp.path())?.written_by_sdif_rs(), None);
+
+    Ok(())
+}
+
 #[test]
 fn test_write_f32_data() -> Result<()> {
     let temp = temp_sdif_path();
@@ -110,6 +259,63 @@ fn test_write_f32_data() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_default_data_type_per_signature() -> Result<()> {
+    use sdif_rs::DataType;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("XSPC", &["Magnitude"])?
+        .add_matrix_type("XMRK", &["Label"])?
+        .add_frame_type("XSPC", &["XSPC Spectrum", "XMRK Marker"])?
+        .default_data_type("XSPC", DataType::Float4)?
+        .build()?;
+
+    assert_eq!(writer.data_type_for("XSPC"), DataType::Float4);
+    assert_eq!(writer.data_type_for("XMRK"), DataType::Float8);
+
+    writer.write_frame_one_matrix("XSPC", 0.0, "XSPC", 3, 1, &[0.5, 0.25, 0.125])?;
+    writer
+        .new_frame("XSPC", 0.1, 0)?
+        .add_matrix("XSPC", 2, 1, &[1.0, 2.0])?
+        .add_matrix("XMRK", 1, 1, &[7.0])?
+        .finish()?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let mut types = Vec::new();
+    for frame in file.frames() {
+        let mut frame = frame?;
+        for matrix in frame.matrices() {
+            let matrix = matrix?;
+            types.push((matrix.signature(), matrix.data_type()));
+            matrix.skip()?;
+        }
+    }
+    assert_eq!(
+        types,
+        vec![
+            ("XSPC".to_string(), DataType::Float4),
+            ("XSPC".to_string(), DataType::Float4),
+            ("XMRK".to_string(), DataType::Float8),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_default_data_type_rejects_non_float() {
+    let result = SdifFile::builder()
+        .create("/tmp/test.sdif")
+        .and_then(|b| b.default_data_type("XMRK", sdif_rs::DataType::Int4));
+    assert!(matches!(result, Err(Error::DataTypeMismatch { .. })));
+}
+
 #[test]
 fn test_frame_builder_multiple_matrices() -> Result<()> {
     let temp = temp_sdif_path();
@@ -205,117 +411,2789 @@ fn test_data_length_validation() -> Result<()> {
     Ok(())
 }
 
-// Roundtrip test - write then read
 #[test]
 #[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
-fn test_write_then_read_roundtrip() -> Result<()> {
+fn test_write_frame_from_columns() -> Result<()> {
     let temp = temp_sdif_path();
     let path = temp.path();
 
-    // Original data
-    let original_data = vec![
-        1.0, 440.0, 0.5, 0.0,
-        2.0, 880.0, 0.3, 1.57,
-        3.0, 1320.0, 0.2, 3.14,
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+
+    let index = vec![1.0, 2.0];
+    let freqs = vec![440.0, 880.0];
+    let amps = vec![0.5, 0.3];
+
+    // Given out of order, and without the trailing Phase column
+    writer.write_frame_from_columns(
+        "1TRC",
+        0.0,
+        &[
+            ("Frequency", &freqs),
+            ("Index", &index),
+            ("Amplitude", &amps),
+        ],
+    )?;
+
+    let invalid: [&[(&str, &[f64])]; 4] = [
+        &[("Pitch", &freqs)],
+        &[("Frequency", &freqs)],
+        &[("Index", &index), ("Index", &index)],
+        &[("Index", &index), ("Frequency", &freqs[..1])],
     ];
-    let original_time = 0.123;
+    for columns in invalid {
+        let result = writer.write_frame_from_columns("1TRC", 0.1, columns);
+        assert!(
+            matches!(result, Err(Error::InvalidFormat { .. })),
+            "{:?}",
+            columns
+        );
+    }
+    let result = writer.write_frame_from_columns("XNEW", 0.1, &[("Index", &index)]);
+    assert!(matches!(result, Err(Error::InvalidFormat { .. })));
+    writer.close()?;
 
-    // Write
-    {
-        let mut writer = SdifFile::builder()
-            .create(path)?
-            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
-            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
-            .build()?;
+    let file = SdifFile::open(path)?;
+    let mut frames = file.frames();
+    let mut frame = frames.next().expect("frame")?;
+    let matrix = frame.matrices().next().expect("matrix")?;
+    assert_eq!((matrix.rows(), matrix.cols()), (2, 3));
+    assert_eq!(matrix.data_f64()?, [1.0, 440.0, 0.5, 2.0, 880.0, 0.3]);
+    drop(frame);
+    assert!(frames.next().is_none());
 
-        writer.write_frame_one_matrix("1TRC", original_time, "1TRC", 3, 4, &original_data)?;
-        writer.close()?;
-    }
+    Ok(())
+}
 
-    // Read back
-    {
-        let file = SdifFile::open(path)?;
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_matrix_columns() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_partials(path, 2)?;
 
-        let mut frame_count = 0;
-        for frame_result in file.frames() {
-            let mut frame = frame_result?;
-            frame_count += 1;
+    let file = SdifFile::open(path)?;
+    let mut frames = file.frames();
 
-            // Check time (with tolerance for floating point)
-            assert!((frame.time() - original_time).abs() < 1e-9);
-            assert_eq!(frame.signature(), "1TRC");
+    let mut frame = frames.next().expect("frame")?;
+    let columns = frame.matrices().next().expect("matrix")?.into_columns()?;
+    assert_eq!(columns, [[1.0, 2.0], [440.0, 880.0], [0.5, 0.25], [0.0, 0.0]]);
+    drop(frame);
 
-            for matrix_result in frame.matrices() {
-                let matrix = matrix_result?;
+    let mut frame = frames.next().expect("frame")?;
+    let named = frame.matrices().next().expect("matrix")?.into_named_columns()?;
+    assert_eq!(
+        named.keys().collect::<Vec<_>>(),
+        ["Index", "Frequency", "Amplitude", "Phase"]
+    );
+    assert_eq!(named["Frequency"], [440.0, 880.0]);
+    assert_eq!(named["Phase"], [0.0, 1.0]);
 
-                assert_eq!(matrix.signature(), "1TRC");
-                assert_eq!(matrix.rows(), 3);
-                assert_eq!(matrix.cols(), 4);
+    Ok(())
+}
 
-                let data = matrix.data_f64()?;
-                assert_eq!(data.len(), original_data.len());
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_typed_rows() -> Result<()> {
+    use sdif_rs::rows::{Fq0Row, TrcRow};
 
-                // Compare values
-                for (a, b) in data.iter().zip(original_data.iter()) {
-                    assert!((a - b).abs() < 1e-9, "Data mismatch: {} vs {}", a, b);
-                }
-            }
-        }
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_partials(path, 2)?;
 
-        assert_eq!(frame_count, 1);
+    let file = SdifFile::open(path)?;
+    let mut frames = file.frames();
+
+    let mut frame = frames.next().expect("frame")?;
+    let result = frame.matrices().next().expect("matrix")?.rows_as::<Fq0Row>();
+    assert!(matches!(result, Err(Error::InvalidFormat { .. })));
+    drop(frame);
+
+    let mut frame = frames.next().expect("frame")?;
+    let rows: Vec<TrcRow> = frame.matrices().next().expect("matrix")?.rows_as()?.collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!((rows[0].index, rows[0].frequency, rows[0].phase), (1, 440.0, 0.0));
+    assert_eq!((rows[1].index, rows[1].amplitude, rows[1].phase), (2, 0.25, 1.0));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_column_defs_round_trip() -> Result<()> {
+    use sdif_rs::columns::ColumnDef;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let columns = [
+        ColumnDef::new("Centroid").unit("Hz").description("Spectral centroid"),
+        ColumnDef::new("Flatness"),
+    ];
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type_with_defs("XDSC", &columns)?
+        .add_frame_type("XDSC", &["XDSC Descriptors"])?
+        .build()?;
+    writer.write_frame_one_matrix("XDSC", 0.0, "XDSC", 1, 2, &[1500.0, 0.2])?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    assert_eq!(file.column_defs("XDSC")?, columns);
+    assert!(file.column_defs("XNON")?.is_empty());
+    assert!(file.column_defs("TOOLONG").is_err());
+
+    let result = SdifFile::builder()
+        .create(temp_sdif_path().path())?
+        .add_matrix_type_with_defs("XDSC", &[ColumnDef::new("Mean Value").unit("dB")]);
+    assert!(matches!(result, Err(Error::InvalidFormat { .. })));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_frames_rev() -> Result<()> {
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 5)?;
+
+    let file = SdifFile::open(temp.path())?;
+    let mut frames = file.frames_rev()?;
+    assert_eq!(frames.size_hint(), (5, Some(5)));
+
+    let mut phases = Vec::new();
+    for frame in frames.by_ref() {
+        let mut frame = frame?;
+        let data = frame.matrices().next().expect("matrix")?.data_f64()?;
+        phases.push(data[7]);
     }
+    assert_eq!(phases, vec![4.0, 3.0, 2.0, 1.0, 0.0]);
+    assert!(frames.next().is_none());
+    drop(frames);
+
+    // A frame must be dropped before moving to the previous one
+    let mut frames = file.frames_rev()?;
+    let last = frames.next().expect("frame")?;
+    assert!((last.time() - 0.04).abs() < 1e-9);
+    assert!(frames.next().expect("error").is_err());
+    drop(last);
+    assert!((frames.next().expect("frame")?.time() - 0.03).abs() < 1e-9);
 
     Ok(())
 }
 
-#[cfg(feature = "ndarray")]
-mod ndarray_tests {
-    use super::*;
-    use ndarray::array;
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_frames_filtered() -> Result<()> {
+    use sdif_rs::FrameFilter;
 
-    #[test]
-    fn test_write_ndarray() -> Result<()> {
-        let temp = temp_sdif_path();
-        let path = temp.path();
+    let temp = temp_sdif_path();
+    let path = temp.path();
 
-        let mut writer = SdifFile::builder()
-            .create(path)?
-            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
-            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
-            .build()?;
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 Pitch"])?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    for i in 0..10 {
+        let time = i as f64 * 0.1;
+        writer.write_frame_one_matrix("1FQ0", time, "1FQ0", 1, 2, &[220.0, 0.9])?;
+        writer
+            .new_frame("1TRC", time, (i % 2) as u32)?
+            .add_matrix("1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?
+            .finish()?;
+    }
+    writer.close()?;
 
-        let data = array![
-            [1.0, 440.0, 0.5, 0.0],
-            [2.0, 880.0, 0.3, 1.57],
-        ];
+    let file = SdifFile::open(path)?;
+    file.scan_index()?;
+    let filter = FrameFilter::new()
+        .signature("1TRC")
+        .stream_id(1)
+        .time_range(0.25..0.75);
+    let mut times = Vec::new();
+    for frame in file.frames_filtered(filter) {
+        let mut frame = frame?;
+        assert_eq!((frame.signature().as_str(), frame.stream_id()), ("1TRC", 1));
+        assert_eq!(frame.matrices().next().expect("matrix")?.data_f64()?[1], 440.0);
+        times.push(frame.time());
+    }
+    assert_eq!(times.len(), 3);
+    assert!((times[0] - 0.3).abs() < 1e-9 && (times[2] - 0.7).abs() < 1e-9);
 
-        writer.write_frame_one_matrix_array("1TRC", 0.0, "1TRC", &data)?;
-        writer.close()?;
+    // With the index, iteration stopped at the 1FQ0 frame at 0.8s
+    let next = file.frames().next().expect("frame")?;
+    assert_eq!(next.signature(), "1TRC");
+    assert!((next.time() - 0.8).abs() < 1e-9);
 
-        Ok(())
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_frames_filtered_out_of_order() -> Result<()> {
+    use sdif_rs::FrameFilter;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 Pitch"])?
+        .build()?;
+    for time in [0.1, 0.2, 0.3, 0.5] {
+        writer.write_frame_one_matrix("1FQ0", time, "1FQ0", 1, 2, &[220.0, 0.9])?;
+    }
+    writer.close()?;
+
+    // The writer refuses decreasing times, so move the frame at 0.2s to
+    // 0.9s afterwards; the time follows the signature and size
+    let offset = SdifFile::open(path)?.scan_index()?.frames()[1].byte_offset as usize;
+    let mut bytes = fs::read(path)?;
+    bytes[offset + 8..offset + 16].copy_from_slice(&0.9f64.to_be_bytes());
+    fs::write(path, &bytes)?;
+
+    // Without an index, or with one showing the disorder, every frame is
+    // checked
+    let filter = FrameFilter::new().time_range(0.0..0.6);
+    for scan in [false, true] {
+        let file = SdifFile::open(path)?;
+        if scan {
+            file.scan_index()?;
+        }
+        let times = file
+            .frames_filtered(filter.clone())
+            .map(|frame| Ok(frame?.time()))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(times, [0.1, 0.3, 0.5]);
     }
 
-    #[test]
-    fn test_frame_builder_ndarray() -> Result<()> {
-        let temp = temp_sdif_path();
-        let path = temp.path();
+    Ok(())
+}
 
+#[test]
+fn test_deterministic_output_is_byte_identical() -> Result<()> {
+    fn write(path: &std::path::Path) -> Result<()> {
         let mut writer = SdifFile::builder()
             .create(path)?
+            .deterministic()
+            .add_nvt([
+                ("creator", "write_tests"),
+                ("source", "synthetic"),
+                ("sample_rate", "44100"),
+                ("window", "hann"),
+            ])?
             .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
             .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
             .build()?;
 
-        let data1 = array![[1.0, 440.0, 0.5, 0.0]];
-        let data2 = array![[2.0, 880.0, 0.3, 1.57]];
+        for i in 0..5 {
+            let data = vec![1.0, 440.0 + i as f64, 0.5, 0.0];
+            writer.write_frame_one_matrix("1TRC", i as f64 * 0.01, "1TRC", 1, 4, &data)?;
+        }
 
-        writer.new_frame("1TRC", 0.0, 0)?
-            .add_matrix_array("1TRC", &data1)?
+        writer.close()
+    }
+
+    let a = temp_sdif_path();
+    let b = temp_sdif_path();
+    write(a.path())?;
+    write(b.path())?;
+
+    assert_eq!(fs::read(a.path())?, fs::read(b.path())?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_describe_types_lists_declarations() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("XAMP", &["Gain", "Pan"])?
+        .add_frame_type("XAMP", &["XAMP Levels"])?
+        .build()?;
+    writer.write_frame_one_matrix("XAMP", 0.0, "XAMP", 1, 2, &[0.5, 0.0])?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let schema = file.describe_types()?;
+
+    let mtype = schema.matrix_type("XAMP").expect("XAMP matrix type");
+    assert_eq!(mtype.columns, ["Gain", "Pan"]);
+
+    let ftype = schema.frame_type("XAMP").expect("XAMP frame type");
+    assert_eq!(ftype.components.len(), 1);
+    assert_eq!(ftype.components[0].signature, "XAMP");
+    assert_eq!(ftype.components[0].name, "Levels");
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_duplicate_frames() -> Result<()> {
+    use sdif_rs::DuplicateFrames;
+
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    let data = [440.0, 0.9];
+
+    // Not checked unless enabled
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &data)?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &data)?;
+    assert_eq!(writer.stats().duplicate_frames, 0);
+
+    writer.check_duplicate_frames(DuplicateFrames::Warn);
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &data)?;
+    writer.new_frame("1FQ0", 0.0, 1)?.add_matrix("1FQ0", 1, 2, &data)?.finish()?;
+    writer.write_frame_one_matrix("1FQ0", 0.01, "1FQ0", 1, 2, &data)?;
+    assert_eq!(writer.stats().duplicate_frames, 1);
+    assert_eq!(writer.warnings().len(), 1);
+
+    writer.check_duplicate_frames(DuplicateFrames::Reject);
+    let result = writer.write_frame_one_matrix("1FQ0", 0.01, "1FQ0", 1, 2, &data);
+    assert!(matches!(result, Err(Error::DuplicateFrame { stream_id: 0, .. })));
+    writer.new_frame("1FQ0", 0.01, 1)?.add_matrix("1FQ0", 1, 2, &data)?.finish()?;
+    assert_eq!(writer.frame_count(), 6);
+    writer.close()?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_write_integer_and_text_matrices() -> Result<()> {
+    use sdif_rs::{DataType, MatrixData};
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("XINT", &["Value"])?
+        .add_matrix_type("1LAB", &["Label"])?
+        .add_frame_type("XINT", &["XINT Values", "1LAB Label"])?
+        .build()?;
+    writer.write_frame_one_matrix_i32("XINT", 0.0, "XINT", 3, 1, &[-7, 0, 100_000])?;
+    writer
+        .new_frame("XINT", 0.5, 0)?
+        .add_matrix_i16("XINT", 1, 2, &[-300, 300])?
+        .add_matrix_u8("XINT", 2, 1, &[0, 255])?
+        .add_matrix_text("1LAB", "chorus")?
+        .finish()?;
+    let mut converted = MatrixData::new("XINT", 1, 2, vec![-1.9, 70_000.0]);
+    converted.data_type = DataType::UInt2;
+    let mut frame = sdif_rs::FrameData::new("XINT", 1.0, 0);
+    frame.matrices.push(converted);
+    writer.write_frame_data(&frame)?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let mut frames = file.frames();
+
+    let matrix = frames.next().expect("frame")?.into_owned()?.matrices.remove(0);
+    assert_eq!(matrix.data_type, DataType::Int4);
+    assert_eq!(matrix.data(), &[-7.0, 0.0, 100_000.0]);
+
+    {
+        let mut frame = frames.next().expect("frame")?;
+        let mut matrices = frame.matrices();
+        let int2 = matrices.next().expect("matrix")?.into_owned()?;
+        assert_eq!((int2.data_type, int2.data()), (DataType::Int2, &[-300.0, 300.0][..]));
+        let uint1 = matrices.next().expect("matrix")?.into_owned()?;
+        assert_eq!((uint1.data_type, uint1.data()), (DataType::UInt1, &[0.0, 255.0][..]));
+        let text = matrices.next().expect("matrix")?;
+        assert_eq!((text.data_type(), text.shape()), (DataType::Text, (6, 1)));
+    }
+
+    let matrix = frames.next().expect("frame")?.into_owned()?.matrices.remove(0);
+    assert_eq!(matrix.data_type, DataType::UInt2);
+    assert_eq!(matrix.data(), &[0.0, 65_535.0]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_text_matrices_into_owned() -> Result<()> {
+    use sdif_rs::DataType;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1LAB", &["Label"])?
+        .add_frame_type("1LAB", &["1LAB Label"])?
+        .build()?;
+    writer.new_frame("1LAB", 1.5, 0)?.add_matrix_text("1LAB", "hi")?.finish()?;
+    writer.close()?;
+
+    let document = SdifFile::open(path)?.to_document()?;
+    let matrix = &document.frames[0].matrices[0];
+    assert_eq!((matrix.data_type, matrix.rows, matrix.cols), (DataType::Text, 2, 1));
+    assert_eq!(matrix.raw_bytes(), b"hi");
+    assert_eq!(matrix.data(), &[104.0, 105.0]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_integer_and_text_matrices() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("XINT", &["Value"])?
+        .add_matrix_type("1LAB", &["Label"])?
+        .add_frame_type("XINT", &["XINT Values", "1LAB Label"])?
+        .build()?;
+    writer
+        .new_frame("XINT", 0.0, 0)?
+        .add_matrix_i16("XINT", 2, 1, &[-3, 12])?
+        .add_matrix_u32("XINT", 1, 2, &[7, u32::MAX])?
+        .add_matrix_text("1LAB", "verse 1")?
+        .add_matrix_text("1LAB", "")?
+        .finish()?;
+    writer
+        .new_frame("XINT", 0.1, 0)?
+        .add_matrix_u32("XINT", 1, 1, &[1])?
+        .add_matrix_i16("XINT", 1, 1, &[5])?
+        .finish()?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let mut frames = file.frames();
+    {
+        let mut frame = frames.next().expect("frame")?;
+        let mut matrices = frame.matrices();
+        assert_eq!(matrices.next().expect("matrix")?.data_i32()?, vec![-3, 12]);
+        assert_eq!(matrices.next().expect("matrix")?.data_u32()?, vec![7, u32::MAX]);
+        assert_eq!(matrices.next().expect("matrix")?.data_text()?, "verse 1");
+        assert_eq!(matrices.next().expect("matrix")?.data_text()?, "");
+    }
+
+    // Conversions that could lose values are refused
+    let mut frame = frames.next().expect("frame")?;
+    let mut matrices = frame.matrices();
+    let result = matrices.next().expect("matrix")?.data_i32();
+    assert!(matches!(result, Err(Error::DataTypeMismatch { .. })));
+    assert_eq!(matrices.next().expect("matrix")?.data_as::<f64>()?, vec![5.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_snap_times_rejects_invalid_grid() -> Result<()> {
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder().create(temp.path())?.build()?;
+
+    assert!(writer.snap_times_to_grid(0.0, 1e-6).is_err());
+    assert!(writer.snap_times_to_grid(0.01, -1.0).is_err());
+    assert!(writer.snap_times_to_grid(f64::NAN, 1e-6).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_snap_times_off_grid() -> Result<()> {
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.snap_times_to_grid(0.01, 1e-6)?;
+
+    let result = writer.write_frame_one_matrix("1FQ0", 0.015, "1FQ0", 1, 2, &[440.0, 0.9]);
+    assert!(matches!(result, Err(Error::TimeOffGrid { .. })));
+    assert_eq!(writer.frame_count(), 0);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_snap_times_to_grid() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let hop = 0.01;
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.snap_times_to_grid(hop, 1e-6)?;
+
+    // Accumulating the hop drifts away from exact multiples
+    let mut t = 0.0;
+    for _ in 0..20 {
+        writer.write_frame_one_matrix("1FQ0", t, "1FQ0", 1, 2, &[440.0, 0.9])?;
+        t += hop;
+    }
+    writer
+        .new_frame("1FQ0", t + 1e-9, 0)?
+        .add_matrix("1FQ0", 1, 2, &[440.0, 0.9])?
+        .finish()?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    for (i, frame) in file.frames().enumerate() {
+        assert_eq!(frame?.time(), i as f64 * hop);
+    }
+
+    Ok(())
+}
+
+// Roundtrip test - write then read
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_write_then_read_roundtrip() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    // Original data
+    let original_data = vec![
+        1.0, 440.0, 0.5, 0.0,
+        2.0, 880.0, 0.3, 1.57,
+        3.0, 1320.0, 0.2, 3.14,
+    ];
+    let original_time = 0.123;
+
+    // Write
+    {
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+            .build()?;
+
+        writer.write_frame_one_matrix("1TRC", original_time, "1TRC", 3, 4, &original_data)?;
+        writer.close()?;
+    }
+
+    // Read back
+    {
+        let file = SdifFile::open(path)?;
+
+        let mut frame_count = 0;
+        for frame_result in file.frames() {
+            let mut frame = frame_result?;
+            frame_count += 1;
+
+            // Check time (with tolerance for floating point)
+            assert!((frame.time() - original_time).abs() < 1e-9);
+            assert_eq!(frame.signature(), "1TRC");
+
+            for matrix_result in frame.matrices() {
+                let matrix = matrix_result?;
+
+                assert_eq!(matrix.signature(), "1TRC");
+                assert_eq!(matrix.rows(), 3);
+                assert_eq!(matrix.cols(), 4);
+
+                let data = matrix.data_f64()?;
+                assert_eq!(data.len(), original_data.len());
+
+                // Compare values
+                for (a, b) in data.iter().zip(original_data.iter()) {
+                    assert!((a - b).abs() < 1e-9, "Data mismatch: {} vs {}", a, b);
+                }
+            }
+        }
+
+        assert_eq!(frame_count, 1);
+    }
+
+    Ok(())
+}
+
+/// Write a file with a custom type that is used but never declared.
+fn write_undeclared_types(path: &std::path::Path) -> Result<()> {
+    let mut writer = SdifFile::builder().create(path)?.build()?;
+    writer.write_frame_one_matrix("XAMP", 0.0, "XAMP", 1, 2, &[0.5, 0.25])?;
+    writer.write_frame_one_matrix("XAMP", 0.1, "XAMP", 1, 2, &[0.75, 0.125])?;
+    writer.close()
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_undeclared_types_lenient() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_undeclared_types(path)?;
+
+    let file = SdifFile::open(path)?;
+    let mut values = Vec::new();
+    for frame in file.frames() {
+        let mut frame = frame?;
+        assert_eq!(frame.signature(), "XAMP");
+        for matrix in frame.matrices() {
+            let matrix = matrix?;
+            assert_eq!(matrix.cols(), 2);
+            values.extend(matrix.data_f64()?);
+        }
+    }
+
+    assert_eq!(values, [0.5, 0.25, 0.75, 0.125]);
+    assert!(file.warnings().iter().any(|w| w.contains("XAMP")));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_undeclared_types_strict() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_undeclared_types(path)?;
+
+    let file = SdifFile::open_with_options(path, ReadOptions::new().strict(true))?;
+    let first = file.frames().next().expect("one frame");
+    assert!(matches!(first, Err(Error::InvalidFormat { .. })));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_aliased_signatures() -> Result<()> {
+    use sdif_rs::rows::TrcRow;
+    use sdif_rs::signature_to_string;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder().create(path)?.build()?;
+    let data = [1.0, 440.0, 0.5, 0.0, 2.0, 880.0, 0.25, 1.0];
+    writer.write_frame_one_matrix("ETRC", 0.0, "ETRC", 2, 4, &data)?;
+    writer.close()?;
+
+    let options = ReadOptions::new().strict(true).alias("ETRC", "1TRC");
+    let file = SdifFile::open_with_options(path, options)?;
+    let mut frame = file.frames().next().expect("frame")?;
+    assert_eq!(frame.signature(), "1TRC");
+    assert_eq!(signature_to_string(frame.signature_raw()), "ETRC");
+    assert_eq!(signature_to_string(frame.canonical_signature_raw()), "1TRC");
+
+    let matrix = frame.matrices().next().expect("matrix")?;
+    assert_eq!(matrix.signature(), "1TRC");
+    assert_eq!(signature_to_string(matrix.signature_raw()), "ETRC");
+    assert_eq!(signature_to_string(matrix.canonical_signature_raw()), "1TRC");
+    assert_eq!(matrix.columns(), ["Index", "Frequency", "Amplitude", "Phase"]);
+    let rows: Vec<TrcRow> = matrix.rows_as()?.collect();
+    assert_eq!((rows[1].index, rows[1].frequency), (2, 880.0));
+    drop(frame);
+
+    let index = file.scan_index()?;
+    assert_eq!(index.frames()[0].signature_str(), "ETRC");
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_multiple_frames_with_padding() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("XVAL", &["Value"])?
+        .add_matrix_type("XEXT", &["Extra"])?
+        .add_frame_type("XVAL", &["XVAL Values", "XEXT Extra"])?
+        .build()?;
+    for i in 0..3 {
+        let t = i as f64 * 0.5;
+        writer
+            .new_frame("XVAL", t, 0)?
+            .add_matrix_f32("XVAL", 3, 1, &[1.0, 2.0, t as f32])?
+            .add_matrix_f32("XEXT", 1, 1, &[t as f32])?
+            .finish()?;
+    }
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let mut times = Vec::new();
+    for frame in file.frames() {
+        let mut frame = frame?;
+        let t = frame.time() as f32;
+        times.push(frame.time());
+
+        let matrices: Vec<_> = frame
+            .matrices()
+            .map(|m| m.and_then(|m| m.data_f32()))
+            .collect::<Result<_>>()?;
+        assert_eq!(matrices, [vec![1.0, 2.0, t], vec![t]]);
+    }
+
+    assert_eq!(times, [0.0, 0.5, 1.0]);
+    assert!(file.warnings().is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_from_reader() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    writer.close()?;
+
+    let bytes = fs::read(path)?;
+    let file = SdifFile::from_reader(&bytes[..])?;
+
+    let mut frames = 0;
+    for frame in file.frames() {
+        let mut frame = frame?;
+        let matrix = frame.matrices().next().expect("one matrix")?;
+        assert_eq!(matrix.data_f64()?, [440.0, 0.9]);
+        frames += 1;
+    }
+    assert_eq!(frames, 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_from_bytes() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    writer.write_frame_one_matrix("1FQ0", 0.1, "1FQ0", 1, 2, &[445.0, 0.8])?;
+    writer.close()?;
+
+    let bytes = fs::read(path)?;
+    let file = SdifFile::from_bytes(&bytes)?;
+    assert_eq!(file.header()?.format_version, 3);
+    assert_eq!(file.frames().count(), 2);
+
+    // Seeking reopens the data
+    let file = SdifFile::from_bytes(&bytes)?;
+    file.seek_to_frame(1)?;
+    let mut frame = file.frames().next().expect("second frame")?;
+    assert_eq!(frame.time(), 0.1);
+    assert_eq!(frame.matrices().next().expect("one matrix")?.data_f64()?, [445.0, 0.8]);
+
+    assert!(SdifFile::from_bytes(b"not an SDIF file").is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_write_temporary() -> Result<()> {
+    let build = || {
+        let mut writer = SdifFile::builder()
+            .create_temporary()?
+            .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+            .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+            .build()?;
+        writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+        writer.write_frame_one_matrix("1FQ0", 0.1, "1FQ0", 1, 2, &[445.0, 0.8])?;
+        Ok::<_, Error>(writer)
+    };
+
+    let writer = build()?;
+    let temp_path = writer.path().to_path_buf();
+    let bytes = writer.into_bytes()?;
+    assert!(bytes.starts_with(b"SDIF"));
+    assert!(!temp_path.exists());
+
+    let file = SdifFile::from_bytes(&bytes)?;
+    assert_eq!(file.frames().count(), 2);
+
+    let mut sink = Vec::new();
+    assert_eq!(build()?.finish_into(&mut sink)?, bytes.len() as u64);
+    assert_eq!(sink.len(), bytes.len());
+
+    // Dropping the writer discards the data
+    let writer = build()?;
+    let temp_path = writer.path().to_path_buf();
+    drop(writer);
+    assert!(!temp_path.exists());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_writer_pool_from_threads() -> Result<()> {
+    use std::sync::Arc;
+    use sdif_rs::WriterPool;
+
+    let dir = tempfile::tempdir()?;
+    let paths: Vec<_> = (0..3).map(|i| dir.path().join(format!("stream{}.sdif", i))).collect();
+
+    let pool = Arc::new(WriterPool::new()?);
+    for path in &paths {
+        pool.open(path.clone(), |builder| {
+            builder
+                .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+                .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])
+        })?;
+    }
+    assert_eq!(pool.paths()?.len(), 3);
+
+    let handles: Vec<_> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let pool = Arc::clone(&pool);
+            let path = path.clone();
+            std::thread::spawn(move || -> Result<()> {
+                for n in 0..10 {
+                    let data = [100.0 * (i + 1) as f64, 1.0];
+                    pool.with_writer(&path, move |w| {
+                        w.write_frame_one_matrix("1FQ0", n as f64 * 0.01, "1FQ0", 1, 2, &data)
+                    })?;
+                }
+                Ok(())
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("writer thread panicked")?;
+    }
+
+    assert_eq!(pool.with_writer(&paths[0], |w| Ok(w.frame_count()))?, 10);
+    Arc::try_unwrap(pool).ok().expect("pool still shared").shutdown()?;
+
+    for (i, path) in paths.iter().enumerate() {
+        let file = SdifFile::open(path)?;
+        let mut frames = 0;
+        for frame in file.frames() {
+            let mut frame = frame?;
+            let matrix = frame.matrices().next().expect("one matrix")?;
+            assert_eq!(matrix.data_f64()?[0], 100.0 * (i + 1) as f64);
+            frames += 1;
+        }
+        assert_eq!(frames, 10);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_frame_offsets_and_seek() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    for i in 0..5 {
+        let rows = i + 1;
+        let data: Vec<f32> = (0..rows * 4).map(|v| v as f32).collect();
+        writer.write_frame_one_matrix_f32("1TRC", i as f64 * 0.1, "1TRC", rows, 4, &data)?;
+    }
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let mut index = Vec::new();
+    for frame in file.frames() {
+        let frame = frame?;
+        index.push((frame.time(), frame.byte_offset(), frame.byte_size().expect("known size")));
+    }
+    assert_eq!(index.len(), 5);
+
+    // Frames are contiguous and the last one ends at the end of the file
+    for pair in index.windows(2) {
+        assert_eq!(pair[0].1 + pair[0].2, pair[1].1);
+    }
+    let (_, last_offset, last_size) = index[4];
+    assert_eq!(last_offset + last_size, fs::metadata(path)?.len());
+
+    // Reopen and jump straight to the fourth frame
+    let file = SdifFile::open(path)?;
+    file.seek_to_offset(index[3].1)?;
+    let times: Vec<f64> = file.frames().map(|f| f.map(|f| f.time())).collect::<Result<_>>()?;
+    assert_eq!(times, [index[3].0, index[4].0]);
+
+    // Seeking back works after reaching the end
+    file.seek_to_offset(index[1].1)?;
+    let mut frame = file.frames().next().expect("frame")?;
+    assert_eq!(frame.time(), index[1].0);
+    assert_eq!(frame.matrices().next().expect("matrix")?.rows(), 2);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_seek_to_invalid_offset() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    writer.write_frame_one_matrix("1FQ0", 0.1, "1FQ0", 1, 2, &[441.0, 0.9])?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let offset = file.frames().next().expect("frame")?.byte_offset();
+
+    // Inside the first frame's time field
+    file.seek_to_offset(offset)?;
+    assert!(matches!(file.seek_to_offset(offset + 8), Err(Error::InvalidFormat { .. })));
+
+    // The reader is still positioned at the first frame
+    assert_eq!(file.frames().count(), 2);
+
+    let frames = file.frames();
+    assert!(matches!(file.seek_to_offset(offset), Err(Error::InvalidState { .. })));
+    drop(frames);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_seek_outside_frames() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_nvt([("creator", "sdif-rs-test")])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    writer.write_frame_one_matrix("1FQ0", 0.1, "1FQ0", 1, 2, &[441.0, 0.9])?;
+    writer.close()?;
+
+    let bytes = fs::read(path)?;
+    let chunk = |signature: &[u8]| {
+        bytes.windows(4).position(|w| w == signature).expect("chunk") as u64
+    };
+    let file_len = bytes.len() as u64;
+
+    let file = SdifFile::open(path)?;
+    let invalid =
+        |offset: u64| matches!(file.seek_to_offset(offset), Err(Error::InvalidFormat { .. }));
+
+    // The "SDIF" header
+    assert!(invalid(0));
+    // The ASCII chunks before the first frame
+    assert!(invalid(chunk(b"1NVT")));
+    assert!(invalid(chunk(b"1TYP")));
+    // The end of the file and past it
+    assert!(invalid(file_len));
+    assert!(invalid(file_len + 100));
+
+    // The reader is still positioned at the first frame
+    assert_eq!(file.frames().count(), 2);
+
+    // Frames after the last one can still be asked for
+    assert_eq!(file.frames_from(1.0)?.count(), 0);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_sidecar_index() -> Result<()> {
+    use sdif_rs::Index;
+
+    fn write(path: &std::path::Path, frames: usize) -> Result<()> {
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+            .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+            .build()?;
+        for i in 0..frames {
+            writer.write_frame_one_matrix("1FQ0", i as f64 * 0.5, "1FQ0", 1, 2, &[440.0, 0.9])?;
+        }
+        writer.close()
+    }
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("pitch.sdif");
+    write(&path, 4)?;
+
+    // No sidecar yet
+    assert!(SdifFile::open(&path)?.index().is_none());
+
+    let index = Index::build(&path)?;
+    assert_eq!(index.len(), 4);
+    index.save(Index::sidecar_path(&path))?;
+
+    // The sidecar is picked up and can be used to seek
+    let file = SdifFile::open(&path)?;
+    let loaded = file.index().expect("sidecar index");
+    assert_eq!(loaded, &index);
+
+    let target = loaded.first_at_or_after(1.2).expect("frame at 1.5s");
+    file.seek_to_offset(target.byte_offset)?;
+    assert_eq!(file.frames().next().expect("frame")?.time(), 1.5);
+
+    // Disabled through the read options
+    let options = ReadOptions::new().sidecar_index(false);
+    assert!(SdifFile::open_with_options(&path, options)?.index().is_none());
+
+    // Rewriting the SDIF file makes the sidecar stale
+    write(&path, 6)?;
+    assert!(SdifFile::open(&path)?.index().is_none());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_scan_index_and_seek() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    for i in 0..5 {
+        let time = i as f64 * 0.5;
+        writer.write_frame_one_matrix("1FQ0", time, "1FQ0", 1, 2, &[440.0 + time, 0.9])?;
+    }
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    assert!(file.index().is_none());
+
+    let index = file.scan_index()?;
+    assert_eq!(index.len(), 5);
+    assert_eq!(index.frames()[3].time, 1.5);
+    assert_eq!(index.frames()[3].signature_str(), "1FQ0");
+    assert!(std::ptr::eq(file.index().expect("scanned index"), index));
+
+    // Scanning doesn't move the reader
+    assert_eq!(file.frames().count(), 5);
+
+    file.seek_to_frame(2)?;
+    assert_eq!(file.frames().next().expect("frame")?.time(), 1.0);
+    let err = file.seek_to_frame(5).unwrap_err();
+    assert!(matches!(err, Error::FrameOutOfRange { index: 5, len: 5 }));
+    assert!(err.is_usage());
+
+    let frames = file.frames_from(1.2)?;
+    let times: Vec<f64> = frames.map(|f| f.map(|f| f.time())).collect::<Result<_>>()?;
+    assert_eq!(times, [1.5, 2.0]);
+    assert_eq!(file.frames_from(0.0)?.count(), 5);
+    assert_eq!(file.frames_from(10.0)?.count(), 0);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_frame_at() -> Result<()> {
+    use sdif_rs::Rounding;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_partials(path, 5)?;
+
+    let file = SdifFile::open(path)?;
+    let at = |time, rounding| -> Result<Option<(f64, bool)>> {
+        Ok(file.frame_at(time, rounding)?.map(|(frame, exact)| (frame.time(), exact)))
+    };
+
+    assert_eq!(at(0.02, Rounding::Nearest)?, Some((0.02, true)));
+    assert_eq!(at(0.025, Rounding::Before)?, Some((0.02, false)));
+    assert_eq!(at(0.025, Rounding::After)?, Some((0.03, false)));
+    assert_eq!(at(0.028, Rounding::Nearest)?, Some((0.03, false)));
+    assert_eq!(at(-1.0, Rounding::Before)?, None);
+    assert_eq!(at(1.0, Rounding::After)?, None);
+    assert_eq!(at(1.0, Rounding::Nearest)?, Some((0.04, false)));
+    assert_eq!(file.scan_index()?.nearest_frame(0.011).map(|f| f.time), Some(0.01));
+
+    // Reading continues after the returned frame
+    let (frame, _) = file.frame_at(0.01, Rounding::Before)?.expect("frame");
+    assert!(matches!(
+        file.frame_at(0.0, Rounding::Before),
+        Err(Error::InvalidState { .. })
+    ));
+    drop(frame);
+    assert_eq!(file.frames().next().expect("frame")?.time(), 0.02);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_player() -> Result<()> {
+    use std::cell::Cell;
+    use std::time::Instant;
+    use sdif_rs::player::{Player, WallClock};
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_partials(path, 5)?;
+    let file = SdifFile::open(path)?;
+
+    // Caller-driven clock
+    let position = Cell::new(0.0);
+    let mut player = Player::new(&file, 0.01)?.clock(|| position.get());
+    assert!(player.poll().is_none());
+    assert_eq!(player.next_time(), Some(0.01));
+    assert!(!player.is_finished());
+
+    position.set(0.025);
+    let mut times = Vec::new();
+    while let Some(frame) = player.poll() {
+        times.push(frame?.time());
+    }
+    assert_eq!(times, [0.01, 0.02]);
+
+    position.set(1.0);
+    assert_eq!(player.poll().expect("due frame")?.time(), 0.03);
+    assert_eq!(player.count(), 1);
+
+    // Wall clock, twice as fast: 0.04s of frames take about 0.02s
+    let started = Instant::now();
+    let player = Player::new(&file, 0.0)?.clock(WallClock::new(0.0).speed(2.0));
+    let times: Vec<f64> = player.map(|f| f.map(|f| f.time())).collect::<Result<_>>()?;
+    assert_eq!(times, [0.0, 0.01, 0.02, 0.03, 0.04]);
+    assert!(started.elapsed().as_secs_f64() >= 0.02);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "positive and finite")]
+fn test_wall_clock_rejects_zero_speed() {
+    sdif_rs::player::WallClock::new(0.0).speed(0.0);
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_verify_padding() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    // 1x3 f32 matrices are followed by 4 bytes of padding
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("XPAD", &["A", "B", "C"])?
+        .add_frame_type("XPAD", &["XPAD Padded"])?
+        .build()?;
+    for i in 0..3 {
+        writer.write_frame_one_matrix_f32("XPAD", i as f64, "XPAD", 1, 3, &[1.0, 2.0, 3.0])?;
+    }
+    writer.close()?;
+
+    let options = ReadOptions::new().verify_padding(true);
+    let file = SdifFile::open_with_options(path, options.clone())?;
+    assert_eq!(file.frames().count(), 3);
+    assert!(file.warnings().is_empty());
+    let offset = file.scan_index()?.frames()[1].byte_offset;
+    drop(file);
+
+    // Frame header, matrix header and data come before the padding
+    let mut bytes = fs::read(path)?;
+    bytes[offset as usize + 24 + 16 + 12] = b' ';
+    fs::write(path, &bytes)?;
+
+    let file = SdifFile::open_with_options(path, options.clone())?;
+    assert_eq!(file.frames().count(), 3);
+    let warnings = file.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("at 1.000000s"), "{}", warnings[0]);
+
+    let file = SdifFile::open_with_options(path, options.strict(true))?;
+    let results: Vec<bool> = file.frames().map(|f| f.is_ok()).collect();
+    assert_eq!(results, [true, false, true]);
+
+    // Not checked by default
+    let file = SdifFile::open(path)?;
+    assert_eq!(file.frames().count(), 3);
+    assert!(file.warnings().is_empty());
+
+    Ok(())
+}
+
+/// Write `frames` 1TRC frames of two f32 partials (72 bytes each in the file).
+fn write_partials(path: &std::path::Path, frames: usize) -> Result<()> {
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    for i in 0..frames {
+        let data = [1.0, 440.0, 0.5, 0.0, 2.0, 880.0, 0.25, i as f32];
+        writer.write_frame_one_matrix_f32("1TRC", i as f64 * 0.01, "1TRC", 2, 4, &data)?;
+    }
+    writer.close()
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_append_to_existing_file() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    write_partials(path, 3)?;
+
+    let mut writer = SdifFile::builder().append(path)?;
+    assert!(writer
+        .write_frame_one_matrix("1TRC", 0.01, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])
+        .is_err());
+    writer.write_frame_one_matrix("1TRC", 0.02, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    writer.write_frame_one_matrix("1TRC", 0.5, "1TRC", 1, 4, &[3.0, 660.0, 0.1, 0.0])?;
+    assert_eq!(writer.frame_count(), 2);
+    writer.close()?;
+
+    let file = SdifFile::open_with_options(path, ReadOptions::new().strict(true))?;
+    let frames = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[2].matrices[0].data[7], 2.0);
+    assert_eq!(frames[4].time, 0.5);
+    assert_eq!(frames[4].matrices[0].data, vec![3.0, 660.0, 0.1, 0.0]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_copy_whole_file() -> Result<()> {
+    use sdif_rs::copy::{self, CopyOptions};
+
+    let input = temp_sdif_path();
+    let output = temp_sdif_path();
+    write_partials(input.path(), 5)?;
+
+    let summary = copy::copy_file(input.path(), output.path(), &CopyOptions::new())?;
+    assert_eq!(summary.frames_copied, 5);
+    assert!(!summary.is_truncated());
+    assert_eq!(summary.bytes_written, fs::metadata(input.path())?.len());
+    assert_eq!(fs::read(input.path())?, fs::read(output.path())?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_copy_max_frames() -> Result<()> {
+    use sdif_rs::copy::{self, CopyOptions};
+
+    let input = temp_sdif_path();
+    let output = temp_sdif_path();
+    write_partials(input.path(), 10)?;
+
+    let summary = copy::copy_file(input.path(), output.path(), &CopyOptions::new().max_frames(3))?;
+    assert_eq!(summary.frames_copied, 3);
+    assert_eq!(summary.frames_dropped, 7);
+
+    let file = SdifFile::open(output.path())?;
+    let mut times = Vec::new();
+    for frame in file.frames() {
+        let mut frame = frame?;
+        times.push(frame.time());
+        let matrix = frame.matrices().next().expect("matrix")?;
+        assert_eq!(matrix.data_type(), sdif_rs::DataType::Float4);
+    }
+    assert_eq!(times, vec![0.0, 0.01, 0.02]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_copy_max_bytes() -> Result<()> {
+    use sdif_rs::copy::{self, CopyOptions};
+
+    let input = temp_sdif_path();
+    let output = temp_sdif_path();
+    write_partials(input.path(), 10)?;
+
+    let input_len = fs::metadata(input.path())?.len();
+    let budget = input_len - 72 * 4 - 1;
+
+    let summary = copy::copy_file(input.path(), output.path(), &CopyOptions::new().max_bytes(budget))?;
+    assert_eq!(summary.frames_copied, 5);
+    assert_eq!(summary.frames_dropped, 5);
+    assert_eq!(summary.bytes_written, fs::metadata(output.path())?.len());
+    assert!(summary.bytes_written <= budget);
+
+    assert_eq!(SdifFile::open(output.path())?.frames().count(), 5);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_copy_time_range() -> Result<()> {
+    use sdif_rs::copy::{self, CopyOptions};
+
+    let input = temp_sdif_path();
+    let output = temp_sdif_path();
+    write_partials(input.path(), 10)?;
+
+    let options = CopyOptions::new().time_range(0.025..0.055);
+    let summary = copy::copy_file(input.path(), output.path(), &options)?;
+    assert_eq!(summary.frames_copied, 3);
+    assert_eq!(summary.frames_out_of_range, 7);
+    assert!(!summary.is_truncated());
+
+    let file = SdifFile::open(output.path())?;
+    let times = file
+        .frames()
+        .map(|frame| frame.map(|frame| frame.time()))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(times, vec![3.0 * 0.01, 4.0 * 0.01, 5.0 * 0.01]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_group_by_time() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+
+    // F0 and partials as separate streams at the same instants
+    for (i, time) in [0.0, 0.01, 0.02].into_iter().enumerate() {
+        let f0 = 220.0 + i as f64;
+        writer.new_frame("1FQ0", time, 1)?
+            .add_matrix("1FQ0", 1, 2, &[f0, 0.9])?
+            .finish()?;
+        writer.new_frame("1TRC", time + 1e-12, 0)?
+            .add_matrix_f32("1TRC", 2, 4, &[1.0, f0 as f32, 0.5, 0.0, 2.0, 2.0 * f0 as f32, 0.25, 0.0])?
+            .finish()?;
+    }
+    writer.write_frame_one_matrix("1TRC", 0.5, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let slices = file
+        .frames()
+        .group_by_time(1e-9)
+        .collect::<Result<Vec<_>>>()?;
+
+    assert_eq!(slices.len(), 4);
+    assert_eq!(slices[1].time, 0.01);
+    assert_eq!(slices[1].frames.len(), 2);
+    assert_eq!(slices[1].frame("1FQ0").expect("F0 frame").stream_id, 1);
+    assert_eq!(slices[1].matrix("1FQ0").and_then(|m| m.get(0, 0)), Some(221.0));
+
+    let partials = slices[2].matrix("1TRC").expect("partials");
+    assert_eq!(partials.data_type, sdif_rs::DataType::Float4);
+    assert_eq!(partials.row(1), Some(&[2.0, 444.0, 0.25, 0.0][..]));
+
+    assert_eq!(slices[3].frames.len(), 1);
+    assert_eq!(slices[3].time, 0.5);
+
+    Ok(())
+}
+
+#[test]
+fn test_filter_matrix_data() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .add_matrix_type("1LAB", &["Label"])?
+        .add_frame_type("1LAB", &["1LAB Label"])?
+        .add_frame_type("XLAB", &["1LAB Label", "1FQ0 PitchEstimate"])?
+        .build()?;
+
+    for (i, confidence) in [0.9, 0.1, 0.8, 0.0].into_iter().enumerate() {
+        let time = i as f64 * 0.01;
+        writer.write_frame_one_matrix("1FQ0", time, "1FQ0", 1, 2, &[220.0 + i as f64, confidence])?;
+        writer.write_frame_one_matrix("1TRC", time, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    }
+    // Text matrices don't stop the filter, before or after the tested one
+    writer.new_frame("1LAB", 0.04, 0)?.add_matrix_text("1LAB", "end")?.finish()?;
+    writer
+        .new_frame("XLAB", 0.05, 0)?
+        .add_matrix_text("1LAB", "hi")?
+        .add_matrix("1FQ0", 1, 2, &[230.0, 0.7])?
+        .finish()?;
+    writer.new_frame("1FQ0", 0.06, 0)?.add_matrix("1FQ0", 0, 2, &[])?.finish()?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    let voiced = file
+        .frames()
+        .filter_matrix_data("1FQ0", |rows| rows.first().is_some_and(|r| r[1] > 0.5))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Partial frames have no 1FQ0 matrix to test and are skipped as well
+    let times: Vec<f64> = voiced.iter().map(|frame| frame.time).collect();
+    assert_eq!(times, [0.0, 0.02, 0.05]);
+    assert_eq!(voiced[1].matrix("1FQ0").and_then(|m| m.get(0, 0)), Some(222.0));
+    assert_eq!(voiced[2].matrices.len(), 2);
+    assert_eq!(voiced[2].matrix("1LAB").and_then(|m| m.get(0, 0)), Some(f64::from(b'h')));
+
+    Ok(())
+}
+
+#[cfg(feature = "ndarray")]
+mod ndarray_tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_write_ndarray() -> Result<()> {
+        let temp = temp_sdif_path();
+        let path = temp.path();
+
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+            .build()?;
+
+        let data = array![
+            [1.0, 440.0, 0.5, 0.0],
+            [2.0, 880.0, 0.3, 1.57],
+        ];
+
+        writer.write_frame_one_matrix_array("1TRC", 0.0, "1TRC", &data)?;
+        writer.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_builder_ndarray() -> Result<()> {
+        let temp = temp_sdif_path();
+        let path = temp.path();
+
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+            .build()?;
+
+        let data1 = array![[1.0, 440.0, 0.5, 0.0]];
+        let data2 = array![[2.0, 880.0, 0.3, 1.57]];
+
+        writer.new_frame("1TRC", 0.0, 0)?
+            .add_matrix_array("1TRC", &data1)?
             .add_matrix_array("1TRC", &data2)?
             .finish()?;
 
-        writer.close()?;
+        writer.close()?;
+
+        Ok(())
+    }
+}
+
+/// Write 1FQ0 frames at the given times, with the frequency rising 100 Hz per second.
+fn write_f0_track(path: &std::path::Path, times: &[f64]) -> Result<()> {
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    for &time in times {
+        writer.write_frame_one_matrix("1FQ0", time, "1FQ0", 1, 2, &[200.0 + 100.0 * time, 1.0])?;
+    }
+    writer.close()
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_find_gaps() -> Result<()> {
+    use sdif_rs::analysis::{self, GapKind};
+
+    let temp = temp_sdif_path();
+    write_f0_track(temp.path(), &[0.0, 0.01, 0.04, 0.05, 0.057, 0.067])?;
+
+    let gaps = analysis::find_gaps(&SdifFile::open(temp.path())?, 0.01)?;
+    assert_eq!(gaps.len(), 2);
+
+    assert_eq!(gaps[0].kind, GapKind::Missing { frames: 2 });
+    assert_eq!((gaps[0].start, gaps[0].end), (0.01, 0.04));
+    assert_eq!(gaps[0].signature, "1FQ0");
+
+    assert_eq!(gaps[1].kind, GapKind::Irregular);
+    assert_eq!(gaps[1].start, 0.05);
+
+    assert!(analysis::find_gaps(&SdifFile::open(temp.path())?, 0.0).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_copy_fill_gaps() -> Result<()> {
+    use sdif_rs::copy::{self, CopyOptions, FillMode};
+
+    let input = temp_sdif_path();
+    write_f0_track(input.path(), &[0.0, 0.01, 0.04, 0.05])?;
+
+    let output = temp_sdif_path();
+    let options = CopyOptions::new().fill_gaps(0.01, FillMode::Interpolate);
+    let summary = copy::copy_file(input.path(), output.path(), &options)?;
+    assert_eq!(summary.frames_copied, 4);
+    assert_eq!(summary.frames_filled, 2);
+
+    let file = SdifFile::open(output.path())?;
+    let frames = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<Vec<_>>>()?;
+    let times: Vec<f64> = frames.iter().map(|f| (f.time * 1000.0).round()).collect();
+    assert_eq!(times, vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0]);
+
+    let f0 = frames[2].matrix("1FQ0").expect("filled matrix");
+    assert!((f0.get(0, 0).unwrap() - 202.0).abs() < 1e-9);
+
+    // Empty fills keep the columns but have no rows
+    let options = CopyOptions::new().fill_gaps(0.01, FillMode::Empty);
+    let summary = copy::copy_file(input.path(), output.path(), &options)?;
+    assert_eq!(summary.frames_filled, 2);
+
+    let file = SdifFile::open(output.path())?;
+    let filled = file.frames().nth(2).expect("filled frame")?.into_data()?;
+    assert_eq!(filled.time, 0.02);
+    assert_eq!((filled.matrices[0].rows, filled.matrices[0].cols), (0, 2));
+
+    // Filled frames count toward the frame limit
+    let options = CopyOptions::new()
+        .max_frames(3)
+        .fill_gaps(0.01, FillMode::Empty);
+    let summary = copy::copy_file(input.path(), output.path(), &options)?;
+    assert_eq!((summary.frames_copied, summary.frames_filled), (2, 1));
+    assert_eq!(summary.frames_dropped, 2);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_copy_fill_gaps_large_jump() -> Result<()> {
+    use sdif_rs::copy::{self, CopyOptions, FillMode};
+
+    let input = temp_sdif_path();
+    write_f0_track(input.path(), &[0.0, 0.01, 1e9])?;
+
+    // A corrupt time would need 1e11 filled frames
+    let output = temp_sdif_path();
+    let options = CopyOptions::new().fill_gaps(0.01, FillMode::Empty);
+    let err = copy::copy_file(input.path(), output.path(), &options).unwrap_err();
+    assert!(err.to_string().contains("can be filled"));
+
+    // A large gap within the cap is filled lazily, up to the limits
+    let input = temp_sdif_path();
+    write_f0_track(input.path(), &[0.0, 1000.0])?;
+    let options = CopyOptions::new()
+        .max_frames(10)
+        .fill_gaps(0.01, FillMode::Empty);
+    let summary = copy::copy_file(input.path(), output.path(), &options)?;
+    assert_eq!((summary.frames_copied, summary.frames_filled), (1, 9));
+    assert_eq!(summary.frames_dropped, 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_writer_stats() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+
+    let header_bytes = writer.stats().bytes_written;
+    assert!(header_bytes > 0);
+    assert_eq!(writer.stats().frames, 0);
+    assert_eq!(writer.stats().min_time, None);
+
+    writer.write_frame_one_matrix("1TRC", 0.5, "1TRC", 2, 4, &[0.0; 8])?;
+    writer.new_frame("1FQ0", 0.75, 1)?
+        .add_matrix("1FQ0", 1, 2, &[220.0, 0.9])?
+        .finish()?;
+    writer.write_frame_one_matrix_f32("1TRC", 1.0, "1TRC", 3, 4, &[0.0; 12])?;
+
+    let stats = writer.stats().clone();
+    assert_eq!(stats.frames, 3);
+    assert_eq!(stats.frames_by_signature["1TRC"], 2);
+    assert_eq!(stats.frames_by_signature["1FQ0"], 1);
+    assert_eq!(stats.rows, 6);
+    assert_eq!((stats.min_time, stats.max_time), (Some(0.5), Some(1.0)));
+    assert_eq!(stats.duration(), 0.5);
+    assert!(stats.bytes_written > header_bytes);
+
+    writer.close()?;
+    assert_eq!(stats.bytes_written, fs::metadata(path)?.len());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_column_major_roundtrip() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .column_major()
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    assert!(writer.is_column_major());
+
+    let values = [220.0, 0.9, 221.0, 0.8, 222.0, 0.7];
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 3, 2, &values)?;
+    writer.new_frame("1FQ0", 0.01, 0)?
+        .add_matrix("1FQ0", 3, 2, &values)?
+        .finish()?;
+    writer.close()?;
+
+    let file = SdifFile::open(path)?;
+    for frame in file.frames() {
+        let frame = frame?.into_data()?;
+        let stored = frame.matrix("1FQ0").expect("F0 matrix");
+
+        assert_eq!((stored.rows, stored.cols), (3, 2));
+        assert_eq!(stored.data, vec![220.0, 221.0, 222.0, 0.9, 0.8, 0.7]);
+        assert_eq!(stored.interpret_column_major().data, values.to_vec());
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_named_streams() -> Result<()> {
+    let temp = temp_sdif_path();
+    let path = temp.path();
+
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .named_stream("f0")?
+        .named_stream("f0-smoothed")?
+        .named_stream("f0")?
+        .build()?;
+
+    assert_eq!(writer.named_stream("f0")?, 1);
+    assert_eq!(writer.named_stream("f0-smoothed")?, 2);
+    assert!(matches!(
+        writer.named_stream("partials"),
+        Err(Error::UnknownStream { .. })
+    ));
+
+    let smoothed = writer.named_stream("f0-smoothed")?;
+    writer.new_frame("1FQ0", 0.0, smoothed)?
+        .add_matrix("1FQ0", 1, 2, &[220.0, 0.9])?
+        .finish()?;
+    writer.close()?;
+
+    // The name table is stored as NVT entries
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("stream.f0\t1\n"));
+    assert!(text.contains("stream.f0-smoothed\t2\n"));
+
+    let file = SdifFile::open(path)?;
+    assert_eq!(file.stream_named("f0"), Some(1));
+    assert_eq!(file.stream_named("f0-smoothed"), Some(2));
+    assert_eq!(file.stream_named("partials"), None);
+    for frame in file.frames() {
+        assert_eq!(frame?.stream_id(), smoothed);
+    }
+
+    assert!(SdifFile::builder().create(path)?.named_stream("two words").is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_split_file() -> Result<()> {
+    use sdif_rs::split::{self, SplitBy};
+
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("analysis.sdif");
+
+    let mut writer = SdifFile::builder()
+        .create(&input)?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    for i in 0..3 {
+        let time = i as f64 * 0.01;
+        writer.write_frame_one_matrix("1TRC", time, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+        writer.new_frame("1FQ0", time, 1)?
+            .add_matrix("1FQ0", 1, 2, &[440.0, 0.9])?
+            .finish()?;
+    }
+    writer.close()?;
+
+    let outputs = split::split_file(&input, dir.path(), SplitBy::Signature)?;
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0].key, "1TRC");
+    assert_eq!(outputs[0].path, dir.path().join("analysis.1TRC.sdif"));
+    assert_eq!(outputs[1].frames, 3);
+
+    let f0 = SdifFile::open(&outputs[1].path)?;
+    let types = f0.describe_types()?;
+    assert!(types.frame_type("1FQ0").is_some());
+    assert!(types.matrix_type("1TRC").is_none());
+    for frame in f0.frames() {
+        let frame = frame?;
+        assert_eq!((frame.signature(), frame.stream_id()), ("1FQ0".to_string(), 1));
+    }
+
+    let outputs = split::split_file(&input, dir.path(), SplitBy::Stream)?;
+    let keys: Vec<&str> = outputs.iter().map(|o| o.key.as_str()).collect();
+    assert_eq!(keys, vec!["0", "1"]);
+    assert_eq!(SdifFile::open(dir.path().join("analysis.0.sdif"))?.frames().count(), 3);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_split_ignores_path_stream_names() -> Result<()> {
+    use sdif_rs::split::{self, SplitBy};
+
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("analysis.sdif");
+
+    // A stream name that would write outside the output directory
+    let mut writer = SdifFile::builder()
+        .create(&input)?
+        .add_nvt([("stream.../../escaped", "0")])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[440.0, 0.9])?;
+    writer.close()?;
+
+    let outputs = split::split_file(&input, dir.path(), SplitBy::Stream)?;
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].path, dir.path().join("analysis.0.sdif"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_summary_frame_rate_per_stream() -> Result<()> {
+    use sdif_rs::corpus::FileSummary;
+
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+        .build()?;
+    for i in 0..5 {
+        let time = i as f64 * 0.01;
+        writer.write_frame_one_matrix("1FQ0", time, "1FQ0", 1, 2, &[220.0, 1.0])?;
+        writer.write_frame_one_matrix("1TRC", time, "1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?;
+    }
+    writer.close()?;
+
+    // Interleaved streams at 100 Hz each don't add up to 200 Hz
+    let summary = FileSummary::from_file(&SdifFile::open(temp.path())?, temp.path())?;
+    assert_eq!(summary.num_frames, 10);
+    assert!((summary.frame_rate.expect("frame rate") - 100.0).abs() < 1e-6);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_summary_skips_non_float_pitch() -> Result<()> {
+    use sdif_rs::corpus::FileSummary;
+
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 1, 2, &[220.0, 1.0])?;
+    writer.write_frame_one_matrix_i32("1FQ0", 0.01, "1FQ0", 1, 2, &[880, 1])?;
+    writer.write_frame_one_matrix("1FQ0", 0.02, "1FQ0", 1, 2, &[240.0, 1.0])?;
+    writer.close()?;
+
+    // The integer matrix is neither read nor counted
+    let summary = FileSummary::from_file(&SdifFile::open(temp.path())?, temp.path())?;
+    assert_eq!(summary.num_frames, 3);
+    let pitch = summary.pitch_range.expect("pitch range");
+    assert_eq!((pitch.min, pitch.max), (220.0, 240.0));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_windowed_stats() -> Result<()> {
+    use sdif_rs::analysis;
+
+    let temp = temp_sdif_path();
+    write_f0_track(temp.path(), &[0.0, 0.05, 0.25])?;
+
+    let file = SdifFile::open(temp.path())?;
+    let series = analysis::windowed_stats(&file, 0.1, |w| (w.frames.len(), w.mean("1FQ0", 0)))?;
+
+    assert_eq!(series.len(), 3);
+    assert_eq!(series[0].value.0, 2);
+    assert!((series[0].value.1.unwrap() - 202.5).abs() < 1e-9);
+    assert_eq!(series[1].value, (0, None));
+    assert!((series[1].start - 0.1).abs() < 1e-12);
+    assert_eq!(series[2].value.0, 1);
+
+    // A long run of empty windows is passed as one window
+    let temp = temp_sdif_path();
+    write_f0_track(temp.path(), &[0.0, 1e6])?;
+    let file = SdifFile::open(temp.path())?;
+    let series = analysis::windowed_stats(&file, 0.001, |w| w.frames.len())?;
+    assert_eq!(series.len(), 3);
+    assert_eq!(series[1].value, 0);
+    assert!((series[1].end - 1e6).abs() < 1e-3);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_energy_track() -> Result<()> {
+    use sdif_rs::features;
+
+    let input = temp_sdif_path();
+    write_partials(input.path(), 3)?;
+
+    let track = features::energy_track(&SdifFile::open(input.path())?)?;
+    assert_eq!(track.points.len(), 3);
+    assert!((track.points[1].time - 0.01).abs() < 1e-12);
+    assert!((track.points[1].value - (0.25 + 0.0625)).abs() < 1e-9);
+
+    let output = temp_sdif_path();
+    track.write(output.path())?;
+
+    let file = SdifFile::open(output.path())?;
+    assert!(file.describe_types()?.matrix_type("1NRG").is_some());
+    let frames = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[2].matrix("1NRG").expect("energy").data, vec![0.3125]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_inharmonicity() -> Result<()> {
+    use sdif_rs::features;
+
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("1HRM", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1HRM", &["1HRM HarmonicPartials"])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+
+    // No F0 yet: skipped
+    writer.write_frame_one_matrix("1HRM", 0.0, "1HRM", 1, 4, &[1.0, 100.0, 1.0, 0.0])?;
+    // Harmonic partials, F0 after the partials at the same time
+    writer.write_frame_one_matrix("1HRM", 0.01, "1HRM", 2, 4, &[1.0, 100.0, 1.0, 0.0, 2.0, 200.0, 0.5, 0.0])?;
+    writer.new_frame("1FQ0", 0.01, 1)?
+        .add_matrix("1FQ0", 1, 2, &[100.0, 1.0])?
+        .finish()?;
+    // Stretched partials, reusing the last F0
+    writer.write_frame_one_matrix("1HRM", 0.02, "1HRM", 2, 4, &[1.0, 101.0, 1.0, 0.0, 2.0, 206.0, 0.5, 0.0])?;
+    writer.close()?;
+
+    let track = features::inharmonicity(&SdifFile::open(temp.path())?)?;
+    assert_eq!(track.points.len(), 2);
+    assert_eq!(track.points[0].value, 0.0);
+    assert!((track.points[1].time - 0.02).abs() < 1e-12);
+    assert!((track.points[1].value - (0.01 + 0.03) / 2.0).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_append_descriptors() -> Result<()> {
+    use sdif_rs::descriptors::{self, DescriptorOptions};
+
+    let input = temp_sdif_path();
+    write_partials(input.path(), 3)?;
+
+    let output = temp_sdif_path();
+    let written = descriptors::append_descriptors(input.path(), output.path(), &DescriptorOptions::new())?;
+    assert_eq!(written, 3);
+
+    let file = SdifFile::open(output.path())?;
+    assert!(file.describe_types()?.frame_type("1DSC").is_some());
+
+    let frames = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(frames.len(), 6);
+    assert_eq!(frames[0].signature, "1TRC");
+    assert_eq!(frames[1].signature, "1DSC");
+    assert_eq!(frames[1].stream_id, 1);
+    assert_eq!(frames[1].time, frames[0].time);
+
+    // Partials at 440 Hz (0.5) and 880 Hz (0.25)
+    let dsc = frames[1].matrix("1DSC").expect("descriptor matrix");
+    assert!((dsc.get(0, 0).unwrap() - (440.0 * 0.5 + 880.0 * 0.25) / 0.75).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_owned_frames_decode_lazily() -> Result<()> {
+    let path = temp_sdif_path();
+    write_partials(path.path(), 3)?;
+
+    let file = SdifFile::open(path.path())?;
+    let frames = file
+        .frames()
+        .map(|frame| frame?.into_owned())
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(frames.len(), 3);
+
+    let matrix = frames[2].matrix("1TRC").unwrap();
+    assert_eq!(matrix.data_type, sdif_rs::DataType::Float4);
+    assert_eq!((matrix.rows, matrix.cols), (2, 4));
+    assert_eq!(matrix.raw_bytes().len(), 32);
+    assert!(!matrix.is_decoded());
+
+    assert_eq!(matrix.get(1, 1), Some(880.0));
+    assert_eq!(matrix.get(1, 3), Some(2.0));
+    assert!(matrix.is_decoded());
+    assert!(!frames[0].matrices[0].is_decoded());
+
+    assert_eq!(frames[1].to_data().matrices[0].row(0), Some(&[1.0, 440.0, 0.5, 0.0][..]));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_read_into_reuses_buffer() -> Result<()> {
+    let path = temp_sdif_path();
+    write_partials(path.path(), 3)?;
+
+    let file = SdifFile::open(path.path())?;
+    let mut buffer = Vec::new();
+    let mut last = Vec::new();
+    for frame in file.frames() {
+        for matrix in frame?.matrices() {
+            matrix?.read_into(&mut buffer)?;
+            assert_eq!(buffer.len(), 8);
+            last = buffer.clone();
+        }
+    }
+    assert_eq!(last, vec![1.0, 440.0, 0.5, 0.0, 2.0, 880.0, 0.25, 2.0]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_add_matrix_ref() -> Result<()> {
+    let path = temp_sdif_path();
+    let partials = [1.0, 440.0, 0.5, 0.0, 2.0, 880.0, 0.25, 1.5];
+    let f0 = [440.0, 0.9];
+
+    let mut writer = SdifFile::builder()
+        .create(path.path())?
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks", "1FQ0 Fundamental"])?
+        .build()?;
+    writer
+        .new_frame("1TRC", 0.0, 0)?
+        .add_matrix_ref("1TRC", 2, 4, &partials)?
+        .add_matrix_ref("1FQ0", 1, 2, &f0)?
+        .finish()?;
+    assert!(writer
+        .new_frame("1TRC", 0.1, 0)?
+        .add_matrix_ref("1TRC", 3, 4, &partials)
+        .is_err());
+    writer.close()?;
+
+    let file = SdifFile::open(path.path())?;
+    let frame = file.frames().next().unwrap()?.into_data()?;
+    assert_eq!(frame.matrices[0].data, partials.to_vec());
+    assert_eq!(frame.matrix("1FQ0").unwrap().data, f0.to_vec());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_reserve_frames_truncates_on_close() -> Result<()> {
+    let expected = temp_sdif_path();
+    write_partials(expected.path(), 3)?;
 
+    let write_reserved = |path: &std::path::Path, close: bool| -> Result<()> {
+        let mut writer = SdifFile::builder()
+            .create(path)?
+            .reserve_frames(1000, 4096)
+            .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])?
+            .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])?
+            .build()?;
+        for i in 0..3 {
+            let data = [1.0, 440.0, 0.5, 0.0, 2.0, 880.0, 0.25, i as f32];
+            writer.write_frame_one_matrix_f32("1TRC", i as f64 * 0.01, "1TRC", 2, 4, &data)?;
+        }
+        if close {
+            writer.close()?;
+        }
         Ok(())
+    };
+
+    // Closed explicitly and dropped without closing
+    for close in [true, false] {
+        let path = temp_sdif_path();
+        write_reserved(path.path(), close)?;
+        assert_eq!(fs::read(path.path())?, fs::read(expected.path())?);
+        assert_eq!(SdifFile::open(path.path())?.frames().count(), 3);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_matrix_columns() -> Result<()> {
+    let path = temp_sdif_path();
+    write_partials(path.path(), 2)?;
+    let expected = ["Index", "Frequency", "Amplitude", "Phase"];
+
+    let file = SdifFile::open(path.path())?;
+    {
+        let mut frames = file.frames();
+        let mut frame = frames.next().unwrap()?;
+        let matrix = frame.matrices().next().unwrap()?;
+        assert_eq!(matrix.columns(), expected);
+    }
+
+    let file = SdifFile::open(path.path())?;
+    let frame = file.frames().next().unwrap()?.into_data()?;
+    let matrix = &frame.matrices[0];
+    assert_eq!(matrix.columns, expected);
+    assert_eq!(matrix.column_index("Amplitude"), Some(2));
+
+    #[cfg(feature = "json")]
+    {
+        let json = serde_json::to_string(matrix)?;
+        assert!(json.contains(r#""columns":["Index","Frequency","Amplitude","Phase"]"#));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "json")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_document_json_round_trip() -> Result<()> {
+    use sdif_rs::document::SdifDocument;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_nvt([("creator", "test")])?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .add_matrix_type("XIDX", &["Index"])?
+        .add_frame_type("XIDX", &["XIDX Indices"])?
+        .build()?;
+    writer.write_frame_one_matrix_f32("1FQ0", 0.0, "1FQ0", 1, 2, &[440.5, 0.9])?;
+    writer.write_frame_one_matrix_i32("XIDX", 0.5, "XIDX", 3, 1, &[-1, 0, i32::MAX])?;
+    writer.close()?;
+
+    let document = SdifFile::open(path)?.to_document()?;
+    assert_eq!(document.frames.len(), 2);
+    assert_eq!(document.metadata.nvts[0]["creator"], "test");
+
+    let json = serde_json::to_string(&document)?;
+    let parsed: SdifDocument = serde_json::from_str(&json)?;
+    assert_eq!(parsed, document);
+
+    let restored = temp_sdif_path();
+    assert_eq!(parsed.write_sdif(restored.path())?, 2);
+    let reread = SdifFile::open(restored.path())?.to_document()?;
+    assert_eq!(reread.frames, document.frames);
+    assert_eq!(reread.metadata.matrix_types, document.metadata.matrix_types);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "json")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_to_json() -> Result<()> {
+    use sdif_rs::document::SdifDocument;
+
+    let temp = temp_sdif_path();
+    let path = temp.path();
+    let mut writer = SdifFile::builder()
+        .create(path)?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    let values = [440.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+    writer.write_frame_one_matrix("1FQ0", 0.0, "1FQ0", 2, 2, &values)?;
+    writer.close()?;
+
+    let json = SdifFile::open(path)?.to_json()?;
+    assert!(json.contains(r#""stream_id": 0"#));
+    assert!(json.contains(r#""-inf""#));
+
+    let parsed: SdifDocument = serde_json::from_str(&json)?;
+    let data = parsed.frames[0].matrices[0].data();
+    assert_eq!(data[0], 440.0);
+    assert!(data[1].is_nan());
+    assert_eq!(&data[2..], &values[2..]);
+
+    // Other tools may write null for values JSON can't hold
+    let nulled = json.replace(r#""NaN""#, "null");
+    let parsed: SdifDocument = serde_json::from_str(&nulled)?;
+    assert!(parsed.frames[0].matrices[0].data()[1].is_nan());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "threaded")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_owned_files_on_threads() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let paths: Vec<_> = (1..=4)
+        .map(|n| dir.path().join(format!("{}.sdif", n)))
+        .collect();
+    for (n, path) in paths.iter().enumerate() {
+        write_partials(path, (n + 1) * 10)?;
+    }
+
+    let workers: Vec<_> = paths
+        .iter()
+        .map(|path| {
+            let mut file = SdifFile::open_owned(path)?;
+            Ok(std::thread::spawn(move || {
+                file.with(|file| {
+                    file.frames()
+                        .map(|frame| frame?.into_data())
+                        .collect::<Result<Vec<_>>>()
+                })
+            }))
+        })
+        .collect::<Result<_>>()?;
+
+    for (n, worker) in workers.into_iter().enumerate() {
+        let frames = worker.join().expect("worker panicked")?;
+        assert_eq!(frames.len(), (n + 1) * 10);
+        assert_eq!(frames[3].matrices[0].get(0, 1), Some(440.0));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pitch")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_wav_to_pitch_sdif() -> Result<()> {
+    use sdif_rs::pitch::{self, PitchOptions};
+
+    let dir = tempfile::tempdir()?;
+    let wav = dir.path().join("tone.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&wav, spec).unwrap();
+    for i in 0..8000 {
+        let phase = 2.0 * std::f64::consts::PI * 330.0 * i as f64 / 16000.0;
+        writer.write_sample((phase.sin() * 16000.0) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let output = dir.path().join("tone.f0.sdif");
+    let written = pitch::wav_to_sdif(&wav, &output, &PitchOptions::new())?;
+    assert!(written > 0);
+
+    let file = SdifFile::open(&output)?;
+    let mut frames = 0;
+    for frame in file.frames() {
+        let frame = frame?.into_data()?;
+        assert_eq!(frame.signature, "1FQ0");
+        let f0 = frame.matrices[0].get(0, 0).unwrap();
+        assert!((f0 - 330.0).abs() < 1.0, "{}", f0);
+        frames += 1;
+    }
+    assert_eq!(frames, written);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "resonance")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_wav_to_resonance_sdif() -> Result<()> {
+    use sdif_rs::resonance::{self, ResonanceOptions};
+
+    let dir = tempfile::tempdir()?;
+    let wav = dir.path().join("bell.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(&wav, spec).unwrap();
+    for i in 0..16000 {
+        let t = i as f64 / 16000.0;
+        let sample = 0.5 * (-4.0 * t).exp() * (2.0 * std::f64::consts::PI * 523.0 * t).cos();
+        writer.write_sample(sample as f32).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let output = dir.path().join("bell.res.sdif");
+    let modes = resonance::wav_to_sdif(&wav, &output, &ResonanceOptions::new().max_modes(1))?;
+    assert_eq!(modes, 1);
+
+    let file = SdifFile::open(&output)?;
+    let frames: Vec<_> = file.frames().map(|f| f?.into_data()).collect::<Result<_>>()?;
+    assert_eq!(frames.len(), 1);
+    let matrix = &frames[0].matrices[0];
+    assert_eq!((matrix.signature.as_str(), matrix.rows, matrix.cols), ("1RES", 1, 4));
+    assert!((matrix.get(0, 0).unwrap() - 523.0).abs() < 1.0);
+    assert!((matrix.get(0, 2).unwrap() - 4.0).abs() < 0.5);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "synth")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_render_partials_to_wav() -> Result<()> {
+    use sdif_rs::synth::{self, SynthOptions};
+
+    let dir = tempfile::tempdir()?;
+    let sdif = dir.path().join("partials.sdif");
+    write_partials(&sdif, 3)?;
+
+    let options = SynthOptions::new().sample_rate(8000).normalize(0.5);
+    let samples = synth::render_file(&SdifFile::open(&sdif)?, &options)?;
+    // Last frame at 0.02s plus the 10 ms fade out
+    assert_eq!(samples.len(), 240);
+
+    let wav = dir.path().join("partials.wav");
+    synth::write_wav(&wav, &samples, &options)?;
+    let mut reader = hound::WavReader::open(&wav).unwrap();
+    assert_eq!(reader.spec().sample_rate, 8000);
+    assert_eq!(reader.spec().channels, 1);
+    let written: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+    assert_eq!(written.len(), 240);
+    let loudest = written.iter().map(|s| s.unsigned_abs()).max().unwrap();
+    assert!((i32::from(loudest) - 16384).abs() <= 1, "{}", loudest);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_breakpoint_export() -> Result<()> {
+    use sdif_rs::breakpoints;
+
+    let temp = temp_sdif_path();
+    write_f0_track(temp.path(), &[0.0, 0.5, 1.0])?;
+    let pitch = breakpoints::pitch(&SdifFile::open(temp.path())?)?;
+    assert_eq!(pitch.to_text(), "0 200\n0.5 250\n1 300\n");
+    assert_eq!(pitch.to_csound_table(1, 16)?, "f 1 0 17 -27 0 200 8 250 16 300\n");
+
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 3)?;
+    let partials = breakpoints::partials(&SdifFile::open(temp.path())?, "Frequency")?;
+    assert_eq!(partials.len(), 2);
+    assert_eq!(partials[&2].points.len(), 3);
+    assert!(partials[&2].points.iter().all(|p| p.value == 880.0));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_track_set() -> Result<()> {
+    use sdif_rs::tracks::TrackSet;
+
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 3)?;
+    let mut writer = SdifFile::builder().append(temp.path())?;
+    writer.write_frame_one_matrix("1TRC", 0.05, "1TRC", 1, 4, &[3.0, 660.0, 0.1, 0.0])?;
+    writer.close()?;
+
+    let tracks = TrackSet::from_file(&SdifFile::open(temp.path())?)?;
+    let indices: Vec<u32> = tracks.iter().map(|t| t.index).collect();
+    assert_eq!(indices, [1, 2, 3]);
+
+    let track = tracks.get(0, "1TRC", 2).unwrap();
+    assert_eq!(track.points.len(), 3);
+    assert_eq!((track.birth(), track.death()), (0.0, 0.02));
+    assert_eq!(track.points[2].phase, 2.0);
+    assert_eq!(track.mean_frequency(), 880.0);
+
+    let track = tracks.get(0, "1TRC", 3).unwrap();
+    assert_eq!((track.birth(), track.duration()), (0.05, 0.0));
+    assert_eq!(tracks.alive_at(0.05).count(), 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "synth")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_track_set_keys() -> Result<()> {
+    use sdif_rs::synth::{self, SynthOptions};
+    use sdif_rs::tracks::TrackSet;
+    use sdif_rs::FrameFilter;
+
+    // Partial 1 in 1TRC and 1HRM matrices of stream 0, and in stream 1
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks", "1HRM HarmonicPartials"])?
+        .build()?;
+    for i in 0..2 {
+        let time = i as f64 * 0.5;
+        writer.new_frame("1TRC", time, 0)?
+            .add_matrix("1TRC", 1, 4, &[1.0, 440.0, 0.5, 0.0])?
+            .add_matrix("1HRM", 1, 4, &[1.0, 220.0, 0.25, 0.0])?
+            .finish()?;
+        writer.new_frame("1TRC", time, 1)?
+            .add_matrix("1TRC", 1, 4, &[1.0, 110.0, 0.1, 0.0])?
+            .finish()?;
+    }
+    writer.close()?;
+
+    let file = SdifFile::open(temp.path())?;
+    let tracks = TrackSet::from_partials(&file)?;
+    let keys: Vec<(u32, &str, u32)> = tracks
+        .iter()
+        .map(|t| (t.stream_id, t.signature.as_str(), t.index))
+        .collect();
+    assert_eq!(keys, [(0, "1HRM", 1), (0, "1TRC", 1), (1, "1TRC", 1)]);
+    assert!(tracks.iter().all(|t| t.points.len() == 2));
+    assert_eq!(tracks.get(0, "1HRM", 1).unwrap().mean_frequency(), 220.0);
+    assert_eq!(tracks.get(1, "1TRC", 1).unwrap().mean_frequency(), 110.0);
+
+    // Only the partials of stream 1
+    let file = SdifFile::open(temp.path())?;
+    let tracks = TrackSet::from_frames(file.frames_filtered(FrameFilter::new().stream_id(1)))?;
+    assert_eq!(tracks.len(), 1);
+
+    let file = SdifFile::open(temp.path())?;
+    let options = SynthOptions::new().sample_rate(1000).fade(0.0);
+    let samples =
+        synth::render_frames(file.frames_filtered(FrameFilter::new().stream_id(1)), &options)?;
+    assert_eq!(samples.len(), 500);
+    assert!(samples.iter().any(|&s| s.abs() > 0.05));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_praat_import() -> Result<()> {
+    use sdif_rs::{breakpoints, praat};
+
+    let pitch_tier = NamedTempFile::new().expect("Failed to create temp file");
+    fs::write(
+        pitch_tier.path(),
+        "File type = \"ooTextFile\"\nObject class = \"PitchTier\"\n\n\
+         xmin = 0\nxmax = 1\npoints: size = 2\n\
+         points [1]:\n    number = 0.25\n    value = 180\n\
+         points [2]:\n    number = 0.75\n    value = 210\n",
+    )?;
+
+    let temp = temp_sdif_path();
+    assert_eq!(praat::import(pitch_tier.path(), temp.path())?, 2);
+    let pitch = breakpoints::pitch(&SdifFile::open(temp.path())?)?;
+    assert_eq!(pitch.to_text(), "0.25 180\n0.75 210\n");
+
+    let intensity_tier = NamedTempFile::new().expect("Failed to create temp file");
+    fs::write(
+        intensity_tier.path(),
+        "File type = \"ooTextFile\"\n\"IntensityTier\"\n\n0\n1\n1\n0.5\n60\n",
+    )?;
+
+    let temp = temp_sdif_path();
+    assert_eq!(praat::import(intensity_tier.path(), temp.path())?, 1);
+    let file = SdifFile::open(temp.path())?;
+    let frame = file.frames().next().expect("Expected a frame")?.into_data()?;
+    assert_eq!(frame.signature, "1NRG");
+    assert!((frame.matrices[0].data[0] - 1e6).abs() < 1e-3);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_sonic_visualiser_import() -> Result<()> {
+    use sdif_rs::breakpoints;
+    use sdif_rs::sonic_visualiser::{self, CsvImportOptions, ImportTarget};
+
+    let csv = NamedTempFile::new().expect("Failed to create temp file");
+    fs::write(csv.path(), "0.5,2,0.25,\"verse, A\"\n0.1,1,0.4,intro\n")?;
+
+    let temp = temp_sdif_path();
+    let count = sonic_visualiser::import(csv.path(), temp.path(), &CsvImportOptions::regions())?;
+    assert_eq!(count, 2);
+    let file = SdifFile::open(temp.path())?;
+    let frames: Vec<_> = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<_>>()?;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].signature, "1MRK");
+    assert_eq!(frames[0].time, 0.1);
+    assert_eq!(frames[1].matrices[0].data, [1.0, 0.25, 2.0]);
+
+    fs::write(csv.path(), "0.0,220\n0.01,0\n0.02,230\n")?;
+    let options = CsvImportOptions::new()
+        .label_column(None)
+        .target(ImportTarget::Pitch);
+    let temp = temp_sdif_path();
+    sonic_visualiser::import(csv.path(), temp.path(), &options)?;
+    let pitch = breakpoints::pitch(&SdifFile::open(temp.path())?)?;
+    assert_eq!(pitch.to_text(), "0 220\n0.01 0\n0.02 230\n");
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_csv_round_trip() -> Result<()> {
+    use sdif_rs::csv::{self, CsvExportOptions, CsvImportOptions};
+
+    let input = temp_sdif_path();
+    write_partials(input.path(), 2)?;
+
+    let text = csv::to_csv(
+        &SdifFile::open(input.path())?,
+        &CsvExportOptions::new("1TRC").stream_column(true),
+    )?;
+    assert_eq!(
+        text,
+        "time,stream,Index,Frequency,Amplitude,Phase\n\
+         0,0,1,440,0.5,0\n\
+         0,0,2,880,0.25,0\n\
+         0.01,0,1,440,0.5,0\n\
+         0.01,0,2,880,0.25,1\n"
+    );
+
+    // Only the columns the matrices have, not all of the predefined ones
+    let f0 = temp_sdif_path();
+    let mut writer = SdifFile::builder().create(f0.path())?.build()?;
+    writer.write_frame_one_matrix("1FQ0", 0.5, "1FQ0", 1, 2, &[220.0, 0.75])?;
+    writer.close()?;
+    let f0_text = csv::to_csv(&SdifFile::open(f0.path())?, &CsvExportOptions::new("1FQ0"))?;
+    assert_eq!(f0_text, "time,Frequency,Confidence\n0.5,220,0.75\n");
+
+    let csv_file = NamedTempFile::new().expect("Failed to create temp file");
+    fs::write(csv_file.path(), &text)?;
+    let output = temp_sdif_path();
+    let options = CsvImportOptions::new("XPRT")
+        .map_column("Frequency", "Frequency")
+        .map_column("Amplitude", "Amplitude");
+    assert_eq!(csv::import(csv_file.path(), output.path(), &options)?, 2);
+
+    let file = SdifFile::open(output.path())?;
+    let frames: Vec<_> = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<_>>()?;
+    assert_eq!((frames[1].signature.as_str(), frames[1].time), ("XPRT", 0.01));
+    let matrix = &frames[1].matrices[0];
+    assert_eq!(matrix.columns, ["Frequency", "Amplitude"]);
+    assert_eq!(matrix.data, [440.0, 0.5, 880.0, 0.25]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_voicing_markers() -> Result<()> {
+    use sdif_rs::f0::{self, F0Track};
+
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("1FQ0", &["Frequency", "Confidence"])?
+        .add_frame_type("1FQ0", &["1FQ0 PitchEstimate"])?
+        .build()?;
+    for (i, confidence) in [0.1, 0.9, 0.9, 0.9, 0.2].into_iter().enumerate() {
+        writer.write_frame_one_matrix("1FQ0", i as f64 * 0.1, "1FQ0", 1, 2, &[220.0, confidence])?;
     }
+    writer.close()?;
+
+    let track = F0Track::from_file(&SdifFile::open(temp.path())?)?;
+    assert_eq!(track.points.len(), 5);
+    let segments = f0::segment_voicing(&track, 0.5, 0.05);
+    assert_eq!(segments.iter().map(|s| s.voiced).collect::<Vec<_>>(), [false, true, false]);
+
+    let markers = temp_sdif_path();
+    assert_eq!(f0::write_markers(&segments, markers.path())?, 3);
+    let file = SdifFile::open(markers.path())?;
+    let frames: Vec<_> = file
+        .frames()
+        .map(|frame| frame?.into_data())
+        .collect::<Result<_>>()?;
+    assert_eq!(frames[1].signature, "1MRK");
+    assert_eq!(frames[1].time, 0.1);
+    assert_eq!(frames[1].matrices[0].data[2], 1.0);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_dump_round_trip() -> Result<()> {
+    use sdif_rs::dump::Dump;
+
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 3)?;
+    let text = Dump::from_file(&SdifFile::open(temp.path())?)?.to_string();
+    assert!(text.contains("frame 1TRC 0 0.02\n  matrix 1TRC float32 2x4\n    1 440 0.5 0\n"));
+
+    let restored = temp_sdif_path();
+    assert_eq!(Dump::parse(&text)?.write_sdif(restored.path())?, 3);
+    let round_trip = Dump::from_file(&SdifFile::open(restored.path())?)?.to_string();
+    assert_eq!(round_trip, text);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_dump_int_and_text_matrices() -> Result<()> {
+    use sdif_rs::dump::Dump;
+
+    let temp = temp_sdif_path();
+    let mut writer = SdifFile::builder()
+        .create(temp.path())?
+        .add_matrix_type("XINT", &["Value"])?
+        .add_matrix_type("1LAB", &["Label"])?
+        .add_frame_type("XINT", &["XINT Values", "1LAB Label"])?
+        .build()?;
+    writer
+        .new_frame("XINT", 0.5, 0)?
+        .add_matrix_i32("XINT", 2, 1, &[-7, 2_000_000_000])?
+        .add_matrix_text("1LAB", " verse\t1 ")?
+        .finish()?;
+    writer.close()?;
+
+    let text = Dump::from_file(&SdifFile::open(temp.path())?)?.to_string();
+    assert!(text.contains("  matrix XINT int32 2x1\n    -7\n    2000000000\n"));
+    assert!(text.contains("    |\\sverse\\t1\\s\n"));
+
+    let restored = temp_sdif_path();
+    Dump::parse(&text)?.write_sdif(restored.path())?;
+    let round_trip = Dump::from_file(&SdifFile::open(restored.path())?)?.to_string();
+    assert_eq!(round_trip, text);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_open_with_selection() -> Result<()> {
+    use sdif_rs::selection::Selection;
+
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 5)?;
+
+    // Frequency and last column of the second partial, from 2 frames
+    let selection = Selection::new()
+        .frame_signature("1TRC")
+        .time_range(0.015..=0.035)
+        .row(2)
+        .column(2)
+        .column(4);
+    let file = SdifFile::open_with_selection(temp.path(), &selection)?;
+    let mut frames = Vec::new();
+    for frame in file.frames() {
+        let mut frame = frame?;
+        let time = frame.time();
+        let matrix = frame.matrices().next().unwrap()?;
+        assert_eq!(matrix.shape(), (1, 2));
+        frames.push((time, matrix.data_f64()?));
+    }
+    assert_eq!(frames, [(0.02, vec![880.0, 2.0]), (0.03, vec![880.0, 3.0])]);
+
+    // Other streams and matrix types select nothing
+    let file = SdifFile::open_with_selection(temp.path(), &Selection::new().stream(1))?;
+    assert_eq!(file.frames().count(), 0);
+    let selection = Selection::new().matrix_signature("1FQ0");
+    let file = SdifFile::open_with_selection(temp.path(), &selection)?;
+    let frame = file.frames().next().unwrap()?.into_data()?;
+    assert!(frame.matrices.is_empty());
+
+    assert!(SdifFile::open_with_selection(temp.path(), &Selection::new().row(0)).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_frames_rev_with_selection() -> Result<()> {
+    use sdif_rs::selection::Selection;
+
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 5)?;
+
+    let selection = Selection::new().time_range(0.005..=0.025);
+    let file = SdifFile::open_with_selection(temp.path(), &selection)?;
+    let times = file
+        .frames_rev()?
+        .map(|frame| frame.map(|frame| frame.time()))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(times, [0.02, 0.01]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_selected_column_names() -> Result<()> {
+    use sdif_rs::selection::Selection;
+
+    let temp = temp_sdif_path();
+    write_partials(temp.path(), 1)?;
+
+    let selection = Selection::new().column(2).column(4);
+    let file = SdifFile::open_with_selection(temp.path(), &selection)?;
+    let mut frame = file.frames().next().unwrap()?;
+    let matrix = frame.matrices().next().unwrap()?;
+    assert_eq!(matrix.columns(), ["Frequency", "Phase"]);
+
+    let columns = matrix.into_named_columns()?;
+    let names: Vec<&str> = columns.keys().map(|name| name.as_str()).collect();
+    assert_eq!(names, ["Frequency", "Phase"]);
+    assert_eq!(columns["Frequency"], [440.0, 880.0]);
+    assert_eq!(columns["Phase"], [0.0, 0.0]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+#[cfg_attr(sdif_stub_bindings, ignore = "Requires actual SDIF library")]
+fn test_metrics() -> Result<()> {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use sdif_rs::copy::{self, CopyOptions};
+    use sdif_rs::metrics as m;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let input = temp_sdif_path();
+    let output = temp_sdif_path();
+    metrics::with_local_recorder(&recorder, || -> Result<()> {
+        write_partials(input.path(), 3)?;
+        copy::copy_file(input.path(), output.path(), &CopyOptions::new())?;
+        Ok(())
+    })?;
+
+    let values: Vec<_> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let labels: Vec<_> = key.key().labels().map(|l| l.value().to_string()).collect();
+            (key.key().name().to_string(), labels, value)
+        })
+        .collect();
+    let counter = |name: &str| {
+        values.iter().find_map(|(n, labels, value)| match value {
+            DebugValue::Counter(count) if n == name && labels == &["1TRC"] => Some(*count),
+            _ => None,
+        })
+    };
+
+    assert_eq!(counter(m::FRAMES_WRITTEN), Some(6));
+    assert_eq!(counter(m::FRAMES_READ), Some(3));
+    // Each frame is a 24 byte header and a 2x4 float32 matrix
+    assert_eq!(counter(m::BYTES_READ), Some(3 * 72));
+    assert_eq!(counter(m::BYTES_WRITTEN), Some(6 * 72));
+    assert!(values.iter().any(|(name, labels, value)| name == m::CONVERSION_DURATION
+        && labels == &[m::CONVERSION_COPY]
+        && matches!(value, DebugValue::Histogram(samples) if samples.len() == 1)));
+
+    Ok(())
 }
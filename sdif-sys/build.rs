@@ -1,9 +1,17 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Directory containing the SDIF library, overriding pkg-config
+const LIB_DIR_ENV: &str = "SDIF_LIB_DIR";
+/// Directory containing `sdif.h`, overriding pkg-config
+const INCLUDE_DIR_ENV: &str = "SDIF_INCLUDE_DIR";
+/// `1` to link the system library statically, `0` to link it dynamically
+const STATIC_ENV: &str = "SDIF_STATIC";
 
 fn main() {
     println!("cargo:rerun-if-changed=wrapper.h");
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(sdif_stub_bindings)");
 
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
 
@@ -22,6 +30,23 @@ fn main() {
         return;
     }
 
+    if env::var("CARGO_FEATURE_RUNTIME_LOADING").is_ok() {
+        // The library is opened when first used, so only its headers are
+        // needed here and nothing is linked
+        match try_headers() {
+            Some(include_path) => {
+                emit_library_info(&include_path);
+                generate_bindings(&include_path, &out_dir, true);
+            }
+            None => {
+                println!("cargo:warning=SDIF headers not found - generating stub bindings");
+                println!("cargo:rustc-cfg=sdif_stub_bindings");
+                generate_stub_bindings(&out_dir);
+            }
+        }
+        return;
+    }
+
     let (include_path, lib_path) = if use_bundled {
         match try_build_bundled(&out_dir) {
             Some(paths) => paths,
@@ -33,6 +58,8 @@ fn main() {
                 return;
             }
         }
+    } else if let Some(paths) = try_env_override() {
+        paths
     } else {
         match try_pkg_config() {
             Some(paths) => paths,
@@ -56,47 +83,265 @@ fn main() {
     };
 
     // Generate bindings
-    generate_bindings(&include_path, &out_dir);
+    emit_library_info(&include_path);
+    generate_bindings(&include_path, &out_dir, false);
 
     // Output linking directives
     if let Some(lib_path) = lib_path {
         println!("cargo:rustc-link-search=native={}", lib_path.display());
     }
 
-    if use_bundled || env::var("CARGO_FEATURE_STATIC").is_ok() {
+    // The bundled build only produces a static library
+    if use_bundled || static_override() == Some(true) {
         println!("cargo:rustc-link-lib=static=sdif");
     } else {
         println!("cargo:rustc-link-lib=sdif");
     }
 }
 
+/// Use the library location given by `SDIF_LIB_DIR` and
+/// `SDIF_INCLUDE_DIR`, if either is set.
+///
+/// The headers default to the `include` directory next to the library
+/// directory, and without `SDIF_LIB_DIR` the linker's search path is used.
+///
+/// # Panics
+///
+/// Panics if a directory given doesn't exist or has no `sdif.h`, since
+/// falling back to another install would hide the mistake.
+fn try_env_override() -> Option<(PathBuf, Option<PathBuf>)> {
+    let lib_dir = env_path(LIB_DIR_ENV);
+    let include_dir = env_path(INCLUDE_DIR_ENV);
+    if lib_dir.is_none() && include_dir.is_none() {
+        return None;
+    }
+
+    if let Some(lib_dir) = &lib_dir {
+        if !lib_dir.is_dir() {
+            panic!("{} is not a directory: {}", LIB_DIR_ENV, lib_dir.display());
+        }
+    }
+    let include_dir = include_dir
+        .or_else(|| Some(lib_dir.as_ref()?.parent()?.join("include")))
+        .filter(|dir| dir.join("sdif.h").is_file())
+        .unwrap_or_else(|| {
+            panic!(
+                "No sdif.h found; set {} to the directory containing it",
+                INCLUDE_DIR_ENV
+            )
+        });
+
+    println!(
+        "cargo:info=Using SDIF headers from {}",
+        include_dir.display()
+    );
+    emit_default_types_file(&include_dir);
+    Some((include_dir, lib_dir))
+}
+
+/// Record the types file an installed library loads by default
+///
+/// `make install` builds the library with `$(datadir)/SdifTypes.STYP` as
+/// its default, which is in the `share` directory next to the headers'
+/// `include` directory.
+fn emit_default_types_file(include_dir: &Path) {
+    if let Some(prefix) = include_dir.parent() {
+        let types_file = prefix.join("share").join("SdifTypes.STYP");
+        println!(
+            "cargo:rustc-env=SDIF_DEFAULT_TYPES_FILE={}",
+            types_file.display()
+        );
+    }
+}
+
+/// Read a directory from the environment, ignoring empty values
+fn env_path(name: &str) -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed={}", name);
+    env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Get the static or dynamic linking choice made with `SDIF_STATIC`
+fn static_override() -> Option<bool> {
+    println!("cargo:rerun-if-env-changed={}", STATIC_ENV);
+    match env::var(STATIC_ENV).ok()?.as_str() {
+        "1" | "true" | "yes" => Some(true),
+        "0" | "false" | "no" => Some(false),
+        other => panic!("{} must be 1 or 0, got '{}'", STATIC_ENV, other),
+    }
+}
+
 /// Try to find SDIF using pkg-config
 fn try_pkg_config() -> Option<(PathBuf, Option<PathBuf>)> {
+    let mut config = pkg_config::Config::new();
+    if let Some(statik) = static_override() {
+        config.statik(statik);
+    }
+
     // Try pkg-config first
-    match pkg_config::Config::new()
-        .atleast_version("3.0")
-        .probe("sdif")
-    {
+    match config.atleast_version("3.0").probe("sdif") {
         Ok(lib) => {
-            let include_path = lib.include_paths
-                .first()
-                .cloned()
-                .unwrap_or_else(|| PathBuf::from("/usr/include"));
-
+            let include_path = pkg_config_include(&lib)?;
             let lib_path = lib.link_paths.first().cloned();
+            emit_default_types_file(&include_path);
 
             println!("cargo:info=Found SDIF via pkg-config");
             Some((include_path, lib_path))
         }
         Err(e) => {
             println!("cargo:warning=pkg-config error: {}", e);
+            if is_cross_compiling() {
+                warn_cross_compiling();
+            }
+            None
+        }
+    }
+}
+
+/// Find the SDIF headers without building or linking the library
+fn try_headers() -> Option<PathBuf> {
+    if let Some(include_dir) = env_path(INCLUDE_DIR_ENV) {
+        emit_default_types_file(&include_dir);
+        return Some(include_dir);
+    }
+
+    if let Ok(lib) = pkg_config::Config::new()
+        .atleast_version("3.0")
+        .cargo_metadata(false)
+        .probe("sdif")
+    {
+        let include_dir = pkg_config_include(&lib)?;
+        emit_default_types_file(&include_dir);
+        return Some(include_dir);
+    }
+
+    let include_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("sdif")
+        .join("include");
+    include_dir.exists().then_some(include_dir)
+}
+
+/// Get the include directory of a library found by pkg-config.
+///
+/// pkg-config leaves out directories the compiler searches by default, and
+/// for those the target's `/usr/include` is used. That is the host's own
+/// only when not cross compiling; otherwise it has to come from the
+/// sysroot.
+fn pkg_config_include(lib: &pkg_config::Library) -> Option<PathBuf> {
+    if let Some(include_path) = lib.include_paths.first() {
+        return Some(include_path.clone());
+    }
+
+    match target_sysroot() {
+        Some(sysroot) => Some(sysroot.join("usr").join("include")),
+        None if is_cross_compiling() => {
+            println!("cargo:warning=pkg-config found SDIF but no include directory for it");
+            warn_cross_compiling();
             None
         }
+        None => Some(PathBuf::from("/usr/include")),
     }
 }
 
+/// Whether the crate is being built for another platform than the host
+fn is_cross_compiling() -> bool {
+    env::var("TARGET").ok() != env::var("HOST").ok()
+}
+
+/// Get the target's sysroot, from `PKG_CONFIG_SYSROOT_DIR` or `SYSROOT`.
+///
+/// Like pkg-config itself, target-specific variables such as
+/// `PKG_CONFIG_SYSROOT_DIR_aarch64-unknown-linux-gnu` or
+/// `TARGET_PKG_CONFIG_SYSROOT_DIR` take precedence over the plain one.
+fn target_sysroot() -> Option<PathBuf> {
+    let target = env::var("TARGET").unwrap_or_default();
+    let kind = if is_cross_compiling() {
+        "TARGET"
+    } else {
+        "HOST"
+    };
+    let names = [
+        format!("PKG_CONFIG_SYSROOT_DIR_{}", target),
+        format!("PKG_CONFIG_SYSROOT_DIR_{}", target.replace('-', "_")),
+        format!("{}_PKG_CONFIG_SYSROOT_DIR", kind),
+        "PKG_CONFIG_SYSROOT_DIR".to_string(),
+        "SYSROOT".to_string(),
+    ];
+
+    let mut sysroot = None;
+    for name in &names {
+        println!("cargo:rerun-if-env-changed={}", name);
+        if sysroot.is_none() {
+            sysroot = env::var_os(name).filter(|value| !value.is_empty());
+        }
+    }
+    sysroot.map(PathBuf::from)
+}
+
+/// Explain how to find a cross-compiled SDIF library
+fn warn_cross_compiling() {
+    let target = env::var("TARGET").unwrap_or_default();
+    println!(
+        "cargo:warning=Cross compiling for {}: set PKG_CONFIG_SYSROOT_DIR (or \
+         PKG_CONFIG_SYSROOT_DIR_{}) to the target's sysroot and PKG_CONFIG_PATH to \
+         its pkgconfig directory, or enable the bundled feature",
+        target, target
+    );
+}
+
+/// Pass the library version and optional parts found in its headers to
+/// the crate as `SDIF_LIBRARY_VERSION` and `SDIF_LIBRARY_FEATURES`
+fn emit_library_info(include_path: &Path) {
+    let read = |name: &str| {
+        let path = include_path.join(name);
+        println!("cargo:rerun-if-changed={}", path.display());
+        // Not all headers are UTF-8
+        let bytes = std::fs::read(path).unwrap_or_default();
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+
+    let version_header = read("sdif_version.h");
+    let define = |name: &str| {
+        version_header.lines().find_map(|line| {
+            let mut words = line.split_whitespace();
+            (words.next() == Some("#define") && words.next() == Some(name))
+                .then(|| words.next())
+                .flatten()
+                .and_then(|value| value.parse::<u32>().ok())
+        })
+    };
+    if let (Some(major), Some(minor), Some(release)) = (
+        define("SDIF_VERSION_MAJOR"),
+        define("SDIF_VERSION_MINOR"),
+        define("SDIF_VERSION_RELEASE"),
+    ) {
+        println!(
+            "cargo:rustc-env=SDIF_LIBRARY_VERSION={}.{}.{}",
+            major, minor, release
+        );
+    }
+
+    // Optional parts, each detected by one of its functions
+    let header = read("sdif.h");
+    let features: Vec<&str> = [
+        ("selections", "SdifCreateSelection"),
+        ("text-conversion", "SdifToText"),
+    ]
+    .iter()
+    .filter(|(_, function)| {
+        header.contains(&format!("{} ", function)) || header.contains(&format!("{}(", function))
+    })
+    .map(|(feature, _)| *feature)
+    .collect();
+    println!(
+        "cargo:rustc-env=SDIF_LIBRARY_FEATURES={}",
+        features.join(",")
+    );
+}
+
 /// Try to build SDIF from bundled source
-fn try_build_bundled(out_dir: &PathBuf) -> Option<(PathBuf, Option<PathBuf>)> {
+fn try_build_bundled(out_dir: &Path) -> Option<(PathBuf, Option<PathBuf>)> {
     println!("cargo:info=Attempting to build SDIF from bundled source");
 
     let sdif_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("sdif");
@@ -152,39 +397,75 @@ fn try_build_bundled(out_dir: &PathBuf) -> Option<(PathBuf, Option<PathBuf>)> {
         .opt_level(2)
         .define("HAVE_STDINT_H", "1");  // Modern C compilers have stdint.h
 
-    // Platform-specific settings
-    if cfg!(target_os = "windows") {
-        build.define("WIN32", None);
+    // Platform-specific settings, for the target rather than the build host
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    if target_os == "windows" {
+        // Without SDIF_IS_STATIC the headers declare every function
+        // dllimport, and calls between the library's own objects don't link
+        build.define("WIN32", None).define("SDIF_IS_STATIC", None);
+    }
+    if target_env == "msvc" {
+        // The sources use the POSIX names (strdup, fileno) and plain CRT
+        // functions that MSVC deprecates
+        build
+            .define("_CRT_SECURE_NO_WARNINGS", None)
+            .define("_CRT_NONSTDC_NO_DEPRECATE", None);
     }
 
     // Endianness settings - SDIF library needs these for modern architectures
     // ARM64, x86_64, and most modern architectures are little-endian
-    if cfg!(target_endian = "little") {
+    if env::var("CARGO_CFG_TARGET_ENDIAN").as_deref() == Ok("little") {
         build.define("HOST_ENDIAN_LITTLE", "1");
     } else {
         build.define("HOST_ENDIAN_BIG", "1");
         build.define("WORDS_BIGENDIAN", "1");
     }
 
-    // Set SDIFTYPES path if needed
+    // Make the SDIFTYPES path at build time the library's default types
+    // file, used when the variable is unset at runtime
+    println!("cargo:rerun-if-env-changed=SDIFTYPES");
     if let Ok(types_path) = env::var("SDIFTYPES") {
-        build.define("SDIFTYPES_FILE", Some(types_path.as_str()));
+        let quoted = format!("\"{}\"", types_path);
+        build.define("_SdifTypesFileName", Some(quoted.as_str()));
+        println!("cargo:rustc-env=SDIF_DEFAULT_TYPES_FILE={}", types_path);
     }
 
-    build.compile("sdif");
+    if let Err(e) = build.try_compile("sdif") {
+        if is_cross_compiling() {
+            let target = env::var("TARGET").unwrap_or_default();
+            panic!(
+                "Failed to compile the bundled SDIF library for {}: {}\n\
+                 Install a C cross compiler for the target and set CC_{} to it",
+                target,
+                e,
+                target.replace('-', "_")
+            );
+        }
+        panic!("Failed to compile the bundled SDIF library: {}", e);
+    }
 
     // Mark source files for rebuild tracking
     for file in &c_files {
         println!("cargo:rerun-if-changed={}", file.display());
     }
 
-    Some((include_dir, Some(out_dir.clone())))
+    Some((include_dir, Some(out_dir.to_path_buf())))
 }
 
 /// Generate Rust bindings using bindgen
-fn generate_bindings(include_path: &PathBuf, out_dir: &PathBuf) {
-    let bindings = bindgen::Builder::default()
+///
+/// With `runtime_loading`, functions are generated as members of a
+/// `SdifLibrary` struct loaded with libloading, plus free functions
+/// forwarding to it.
+fn generate_bindings(include_path: &Path, out_dir: &Path, runtime_loading: bool) {
+    let mut builder = bindgen::Builder::default()
         .header("wrapper.h")
+        .clang_args(
+            target_sysroot()
+                .filter(|_| is_cross_compiling())
+                .map(|sysroot| format!("--sysroot={}", sysroot.display())),
+        )
         .clang_arg(format!("-I{}", include_path.display()))
 
         // Allowlist SDIF types and functions
@@ -200,6 +481,7 @@ fn generate_bindings(include_path: &PathBuf, out_dir: &PathBuf) {
         .allowlist_type("SdifFileModeE")
         .allowlist_var("eReadFile")
         .allowlist_var("eWriteFile")
+        .allowlist_var("eReadWriteFile")
         .allowlist_var("eUnknownFileMode")
         .allowlist_var("ePredefinedTypes")
         .allowlist_var("eModeMask")
@@ -229,24 +511,124 @@ fn generate_bindings(include_path: &PathBuf, out_dir: &PathBuf) {
         .derive_partialeq(true)
 
         // Other options
+        .ctypes_prefix("core::ffi")
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
         .generate_comments(true)
-        .layout_tests(true)
+        .layout_tests(true);
 
+    if runtime_loading {
+        builder = builder
+            .dynamic_library_name("SdifLibrary")
+            .dynamic_link_require_all(false);
+    }
+
+    let mut bindings = builder
         .generate()
-        .expect("Failed to generate bindings");
+        .expect("Failed to generate bindings")
+        .to_string();
+
+    if runtime_loading {
+        bindings.push_str(&runtime_shims(&bindings));
+    }
 
     let bindings_path = out_dir.join("bindings.rs");
-    bindings
-        .write_to_file(&bindings_path)
+    std::fs::write(&bindings_path, bindings)
         .expect("Failed to write bindings");
 
     println!("cargo:info=Generated bindings at {:?}", bindings_path);
 }
 
+/// Generate a free function for each method of the `SdifLibrary` struct,
+/// calling it on the library loaded at runtime
+///
+/// bindgen generates methods like
+/// `pub unsafe fn SdifFOpen(&self, name: ..., mode: ...) -> ... { ... }`;
+/// the free functions take the same parameters without `&self`, so callers
+/// don't change between linked and runtime-loaded builds.
+fn runtime_shims(bindings: &str) -> String {
+    let mut shims = String::from("\n// Free functions forwarding to the runtime-loaded library\n");
+
+    let Some(start) = bindings.find("impl SdifLibrary") else {
+        return shims;
+    };
+    let block_start = start + bindings[start..].find('{').unwrap();
+    let block = &bindings[block_start + 1..block_start + closing(&bindings[block_start..], '{', '}')];
+
+    let mut rest = block;
+    while let Some(pos) = rest.find("pub unsafe fn ") {
+        rest = &rest[pos + "pub unsafe fn ".len()..];
+        let open = rest.find('(').unwrap();
+        let close = open + closing(&rest[open..], '(', ')');
+        let body = close + rest[close..].find('{').unwrap();
+
+        let name = rest[..open].trim();
+        let params: Vec<&str> = split_params(&rest[open + 1..close]);
+        let output = rest[close + 1..body].trim();
+        rest = &rest[body..];
+
+        // Constructors such as `new` don't take `&self`
+        if params.first().map(|p| p.replace(' ', "")) != Some("&self".to_string()) {
+            continue;
+        }
+        let params = &params[1..];
+        let args: Vec<&str> = params
+            .iter()
+            .map(|p| p.split(':').next().unwrap().trim())
+            .collect();
+
+        shims.push_str(&format!(
+            "pub unsafe fn {}({}) {} {{ crate::runtime::library().{}({}) }}\n",
+            name,
+            params.join(", "),
+            output,
+            name,
+            args.join(", ")
+        ));
+    }
+
+    shims
+}
+
+/// Find the offset of the bracket closing the one at the start of `text`
+fn closing(text: &str, open: char, close: char) -> usize {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return i;
+            }
+        }
+    }
+    panic!("Unbalanced '{}' in generated bindings", open);
+}
+
+/// Split a parameter list at the commas outside of nested parentheses
+fn split_params(params: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(params[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(params[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
 /// Generate stub bindings when SDIF library is not available
 /// This allows the crate to compile for publishing, but the functions won't be usable
-fn generate_stub_bindings(out_dir: &PathBuf) {
+fn generate_stub_bindings(out_dir: &Path) {
     let stub_bindings = r#"
 // Stub bindings generated because SDIF library was not available at build time.
 // To use this crate, you must:
@@ -255,23 +637,76 @@ fn generate_stub_bindings(out_dir: &PathBuf) {
 //
 // See the README.md for detailed instructions.
 
-use std::os::raw::{c_char, c_int, c_void, c_double, c_float};
+use core::ffi::{c_char, c_int, c_void, c_double, c_float};
 
-// Opaque types (matching real SDIF library structure)
+// File handle (only the fields read by sdif-rs are exposed)
 #[repr(C)]
 pub struct SdifFileT {
+    pub CurrFramH: *mut SdifFrameHeaderT,
+    _private: [u8; 0],
+}
+
+// Frame header of the frame being read or written
+#[repr(C)]
+pub struct SdifFrameHeaderS {
+    pub Signature: SdifSignature,
+    pub Size: u32,
+    pub NbMatrix: u32,
+    pub NumID: u32,
+    pub Time: SdifFloat8,
+}
+pub type SdifFrameHeaderT = SdifFrameHeaderS;
+
+// Type definition structs (only the leading signature field is exposed)
+#[repr(C)]
+pub struct SdifMatrixTypeT {
+    pub Signature: SdifSignature,
     _private: [u8; 0],
 }
 
+#[repr(C)]
+pub struct SdifFrameTypeT {
+    pub Signature: SdifSignature,
+    _private: [u8; 0],
+}
+
+// Name-value tables read from a file
+#[repr(C)]
+pub struct SdifListT {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct SdifNameValuesLT {
+    pub NVTList: *mut SdifListT,
+    pub CurrNVT: *mut SdifNameValueTableT,
+    pub HashSize: u32,
+}
+
+#[repr(C)]
+pub struct SdifNameValueTableT {
+    pub NVHT: *mut c_void,
+    pub NumTable: u32,
+    pub StreamID: u32,
+}
+
+#[repr(C)]
+pub struct SdifNameValueT {
+    pub Name: *mut c_char,
+    pub Value: *mut c_char,
+}
+
 // Type aliases
 pub type SdifSignature = u32;
 pub type SdifFloat8 = c_double;
 pub type SdifFloat4 = c_float;
+pub type SdiffPosT = i64;
 
 // File mode enum
 pub type SdifFileModeET = u32;
 pub const SdifFileModeET_eReadFile: u32 = 1;
 pub const SdifFileModeET_eWriteFile: u32 = 2;
+pub const SdifFileModeET_eReadWriteFile: u32 = 3;
 pub const SdifFileModeET_ePredefinedTypes: u32 = 4;
 pub const SdifFileModeET_eModeMask: u32 = 7;
 
@@ -304,7 +739,11 @@ extern "C" {
     pub fn SdifFCurrTime(file: *mut SdifFileT) -> c_double;
     pub fn SdifFCurrFrameSignature(file: *mut SdifFileT) -> SdifSignature;
     pub fn SdifFCurrNbMatrix(file: *mut SdifFileT) -> u32;
-    pub fn SdifFGetSignature(file: *mut SdifFileT) -> u32;
+    pub fn SdifFCurrID(file: *mut SdifFileT) -> u32;
+    pub fn SdifFCurrSignature(file: *mut SdifFileT) -> SdifSignature;
+    pub fn SdifFGetSignature(file: *mut SdifFileT, nb_char_read: *mut usize) -> c_int;
+    pub fn SdifFGetPos(file: *mut SdifFileT, pos: *mut SdiffPosT) -> c_int;
+    pub fn SdifFSetPos(file: *mut SdifFileT, pos: *mut SdiffPosT) -> c_int;
 
     // Matrix reading functions
     pub fn SdifFReadMatrixHeader(file: *mut SdifFileT) -> isize;
@@ -316,6 +755,21 @@ extern "C" {
     pub fn SdifFReadOneRow(file: *mut SdifFileT) -> isize;
     pub fn SdifFCurrOneRowData(file: *mut SdifFileT) -> *mut c_void;
     pub fn SdifFReadMatrixData(file: *mut SdifFileT) -> isize;
+    pub fn SdifFSkipMatrix(file: *mut SdifFileT) -> usize;
+    pub fn SdifFReadPadding(file: *mut SdifFileT, padding: usize) -> usize;
+    pub fn SdifPaddingCalculate(nb_bytes: usize) -> usize;
+    pub fn SdifFCurrOneRow(file: *mut SdifFileT) -> *mut c_void;  // Returns SdifOneRowT*
+    pub fn SdifReInitOneRow(one_row: *mut c_void, data_type: SdifDataTypeET, nb_data: u32) -> *mut c_void;
+
+    // Selection functions
+    pub fn SdifFCurrFrameIsSelected(file: *mut SdifFileT) -> c_int;
+    pub fn SdifFCurrMatrixIsSelected(file: *mut SdifFileT) -> c_int;
+    pub fn SdifFRowIsSelected(file: *mut SdifFileT, row: c_int) -> c_int;
+    pub fn SdifFColumnIsSelected(file: *mut SdifFileT, col: c_int) -> c_int;
+
+    // Error reporting
+    pub fn SdifEnableErrorOutput();
+    pub fn SdifDisableErrorOutput();
 
     // Writing functions - General
     pub fn SdifFWriteGeneralHeader(file: *mut SdifFileT) -> usize;
@@ -362,13 +816,21 @@ extern "C" {
     pub fn SdifSignatureToString(sig: SdifSignature) -> *const c_char;
 
     // NVT functions
-    pub fn SdifFNameValueList(file: *mut SdifFileT) -> *mut c_void;  // Returns SdifNameValuesLT*
-    pub fn SdifNameValuesLNewTable(nvt_list: *mut c_void, stream_id: u32) -> *mut c_void;
+    pub fn SdifFNameValueList(file: *mut SdifFileT) -> *mut SdifNameValuesLT;
+    pub fn SdifNameValuesLNewTable(
+        nvt_list: *mut SdifNameValuesLT,
+        stream_id: u32,
+    ) -> *mut SdifNameValuesLT;
     pub fn SdifNameValuesLPutCurrNVT(
-        nvt_list: *mut c_void,
+        nvt_list: *mut SdifNameValuesLT,
         name: *const c_char,
         value: *const c_char,
-    );
+    ) -> *mut SdifNameValueT;
+
+    // List iteration
+    pub fn SdifListInitLoop(list: *mut SdifListT) -> c_int;
+    pub fn SdifListIsNext(list: *mut SdifListT) -> c_int;
+    pub fn SdifListGetNext(list: *mut SdifListT) -> *mut c_void;
 
     // Matrix type definition functions
     pub fn SdifFGetMatrixTypesTable(file: *mut SdifFileT) -> *mut c_void;  // Returns SdifHashTableT*
@@ -400,6 +862,22 @@ extern "C" {
         table: *mut c_void,
         ftype: *mut c_void,
     );
+
+    // Type lookup functions
+    pub fn SdifGetMatrixType(table: *mut c_void, signature: SdifSignature) -> *mut SdifMatrixTypeT;
+    pub fn SdifTestFrameType(file: *mut SdifFileT, signature: SdifSignature) -> *mut SdifFrameTypeT;
+
+    // Type introspection functions
+    pub fn SdifCreateHashTableIterator(table: *mut c_void) -> *mut c_void;
+    pub fn SdifKillHashTableIterator(iter: *mut c_void);
+    pub fn SdifHashTableIteratorIsNext(iter: *mut c_void) -> c_int;
+    pub fn SdifHashTableIteratorGetNext(iter: *mut c_void) -> *mut c_void;
+    pub fn SdifMatrixTypeGetNbColumns(mtype: *mut SdifMatrixTypeT) -> u32;
+    pub fn SdifMatrixTypeGetColumnName(mtype: *mut SdifMatrixTypeT, index: c_int) -> *const c_char;
+    pub fn SdifFrameTypeGetNbComponents(ftype: *mut SdifFrameTypeT) -> u32;
+    pub fn SdifFrameTypeGetNthComponent(ftype: *mut SdifFrameTypeT, num: u32) -> *mut c_void;  // Returns SdifComponentT*
+    pub fn SdifFrameTypeGetComponentSignature(component: *mut c_void) -> SdifSignature;
+    pub fn SdifFrameTypeGetComponentName(component: *mut c_void) -> *mut c_char;
 }
 
 #[cfg(test)]
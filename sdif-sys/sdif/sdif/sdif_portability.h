@@ -74,7 +74,9 @@
 
 #include "sdif_version.h"
 
-#if defined( _WIN32) || defined(WIN32)
+/* MSVC has had a standard snprintf since Visual Studio 2015, and its
+   stdio.h refuses a snprintf macro; _snprintf doesn't nul-terminate */
+#if (defined( _WIN32) || defined(WIN32)) && defined(_MSC_VER) && _MSC_VER < 1900
 #   include <stdio.h>
 #   define snprintf _snprintf
 #endif
@@ -50,6 +50,24 @@
 //!
 //! - `bundled`: Compile SDIF from bundled source instead of linking to system library
 //! - `static`: Force static linking (implies `bundled` on most systems)
+//! - `runtime-loading`: Load the SDIF shared library when first used instead
+//!   of linking it, see [`check_library()`]
+//!
+//! The `SDIF_LIB_DIR`, `SDIF_INCLUDE_DIR` and `SDIF_STATIC` environment
+//! variables point the build at an install pkg-config doesn't know about;
+//! see the README.
+//!
+//! ## Writing Matrices
+//!
+//! The [`write`] module wraps the frame and matrix write functions with
+//! length checks on the matrix data, so callers don't compute data sizes
+//! and padding themselves.
+//!
+//! ## Library Version
+//!
+//! [`library_version()`], [`supports_selections()`] and
+//! [`supports_text_conversion()`] describe the library the bindings were
+//! built against, so callers can adapt to older system installs.
 
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
@@ -60,6 +78,109 @@
 // Include the generated bindings
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+#[cfg(all(feature = "runtime-loading", not(sdif_stub_bindings)))]
+mod runtime;
+
+pub mod write;
+
+#[cfg(all(feature = "runtime-loading", not(sdif_stub_bindings)))]
+pub use runtime::LIBRARY_ENV;
+
+/// Check whether the SDIF library can be called.
+///
+/// A linked library always can. With the `runtime-loading` feature the
+/// shared library is loaded on the first call, from the path in the
+/// `SDIF_LIBRARY` environment variable or the platform's usual library
+/// name, and the error explains why it couldn't be loaded. Stub builds
+/// have no library at all.
+pub fn check_library() -> Result<(), &'static str> {
+    #[cfg(sdif_stub_bindings)]
+    return Err("sdif-sys was built without the SDIF library");
+
+    #[cfg(all(feature = "runtime-loading", not(sdif_stub_bindings)))]
+    return runtime::load().map(|_| ());
+
+    #[cfg(not(any(feature = "runtime-loading", sdif_stub_bindings)))]
+    Ok(())
+}
+
+/// Check whether the SDIF library can be called, see [`check_library()`].
+pub fn is_available() -> bool {
+    check_library().is_ok()
+}
+
+/// Whether the library is loaded at runtime rather than linked.
+pub const RUNTIME_LOADING: bool = cfg!(all(feature = "runtime-loading", not(sdif_stub_bindings)));
+
+/// Version of the SDIF library the bindings were generated for, such as
+/// `"3.11.7"`.
+///
+/// Taken from `sdif_version.h` at build time, so `None` for stub builds and
+/// for headers too old to have one. With the `runtime-loading` feature the
+/// library actually loaded may be a different release.
+pub fn library_version() -> Option<&'static str> {
+    option_env!("SDIF_LIBRARY_VERSION")
+}
+
+/// [`library_version()`] as a single comparable number,
+/// `major * 10000 + minor * 100 + release`.
+pub fn library_version_number() -> Option<u32> {
+    let mut parts = library_version()?
+        .split('.')
+        .map(|part| part.parse::<u32>().ok());
+    let (major, minor, release) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(major * 10000 + minor * 100 + release)
+}
+
+/// The standard predefined types file, `SdifTypes.STYP`, from the bundled
+/// SDIF source.
+///
+/// Installs normally point the library at a copy of it with the
+/// `SDIFTYPES` environment variable.
+pub const STANDARD_TYPES: &str = include_str!("../sdif/data/SdifTypes.STYP");
+
+/// The types file the library loads by default, when the `SDIFTYPES`
+/// environment variable is unset and the current directory has no
+/// `SdifTypes.STYP`.
+///
+/// For an installed library this is `share/SdifTypes.STYP` under its
+/// prefix, where `make install` puts it; for the bundled build it is the
+/// `SDIFTYPES` path at build time. `None` if the library has no default
+/// outside the current directory.
+pub fn default_types_file() -> Option<&'static str> {
+    option_env!("SDIF_DEFAULT_TYPES_FILE")
+}
+
+/// Whether the library has the selection functions (`SdifCreateSelection`
+/// and friends).
+pub fn supports_selections() -> bool {
+    has_feature("selections", b"SdifCreateSelection\0")
+}
+
+/// Whether the library has the SDIF/text conversion functions
+/// (`SdifToText` and `SdifTextToSdif`).
+pub fn supports_text_conversion() -> bool {
+    has_feature("text-conversion", b"SdifToText\0")
+}
+
+/// Check that the headers declared a feature and, when the library is
+/// loaded at runtime, that the loaded library exports `symbol`.
+fn has_feature(feature: &str, symbol: &[u8]) -> bool {
+    let declared = option_env!("SDIF_LIBRARY_FEATURES")
+        .unwrap_or_default()
+        .split(',')
+        .any(|name| name == feature);
+
+    #[cfg(all(feature = "runtime-loading", not(sdif_stub_bindings)))]
+    return declared && runtime::has_symbol(symbol);
+
+    #[cfg(not(all(feature = "runtime-loading", not(sdif_stub_bindings))))]
+    {
+        let _ = symbol;
+        declared
+    }
+}
+
 // ============================================================================
 // Additional Constants and Type Aliases
 // ============================================================================
@@ -76,6 +197,8 @@ include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 pub use SdifFileModeE_eReadFile as SdifFileModeET_eReadFile;
 #[cfg(not(sdif_stub_bindings))]
 pub use SdifFileModeE_eWriteFile as SdifFileModeET_eWriteFile;
+#[cfg(not(sdif_stub_bindings))]
+pub use SdifFileModeE_eReadWriteFile as SdifFileModeET_eReadWriteFile;
 
 #[cfg(not(sdif_stub_bindings))]
 pub use SdifDataTypeE_eFloat4 as SdifDataTypeET_eFloat4;
@@ -176,6 +299,19 @@ const fn signature_from_str_const(s: &[u8; 4]) -> SdifSignature {
 // Tests
 // ============================================================================
 
+/// Lock held by every unit test that calls into the C library.
+///
+/// The library keeps global state, and `test_init_and_kill` frees it while
+/// other tests may be using it.
+#[cfg(test)]
+pub(crate) static FFI_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Take [`FFI_LOCK`], even if a test panicked while holding it.
+#[cfg(test)]
+pub(crate) fn lock_ffi() -> std::sync::MutexGuard<'static, ()> {
+    FFI_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +342,7 @@ mod tests {
     fn test_init_and_kill() {
         // This test verifies that the C library can be initialized and cleaned up
         // without crashing. It's a basic smoke test.
+        let _lock = lock_ffi();
         unsafe {
             SdifGenInit(ptr::null());
             SdifGenKill();
@@ -225,6 +362,7 @@ mod tests {
         // Verify data type constants match expected sizes
         // eFloat4 = 4 bytes (f32), eFloat8 = 8 bytes (f64)
         // Note: The actual values may vary by SDIF version; adjust as needed
+        let _lock = lock_ffi();
         unsafe {
             let size_f4 = SdifSizeofDataType(SdifDataTypeET_eFloat4);
             let size_f8 = SdifSizeofDataType(SdifDataTypeET_eFloat8);
@@ -0,0 +1,83 @@
+//! Loading of the SDIF shared library at runtime.
+//!
+//! With the `runtime-loading` feature the bindings are members of
+//! [`SdifLibrary`], and the free functions generated next to it forward to
+//! the library loaded here on first use.
+
+use std::env;
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+use crate::SdifLibrary;
+
+/// Environment variable with the path of the SDIF shared library to load.
+pub const LIBRARY_ENV: &str = "SDIF_LIBRARY";
+
+/// Names tried when [`LIBRARY_ENV`] isn't set.
+#[cfg(target_os = "windows")]
+const LIBRARY_NAMES: &[&str] = &["sdif.dll", "libsdif.dll"];
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &["libsdif.dylib"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const LIBRARY_NAMES: &[&str] = &["libsdif.so", "libsdif.so.3"];
+
+/// The library, or why it couldn't be loaded.
+static LIBRARY: OnceLock<Result<SdifLibrary, String>> = OnceLock::new();
+
+/// Load the library, or return the reason it failed.
+///
+/// Only the first call tries to load it; later calls return the same
+/// result.
+pub(crate) fn load() -> Result<&'static SdifLibrary, &'static str> {
+    LIBRARY.get_or_init(open).as_ref().map_err(String::as_str)
+}
+
+/// Get the loaded library for a forwarding function.
+///
+/// # Panics
+///
+/// Panics if the library can't be loaded. Callers are expected to check
+/// [`check_library()`](crate::check_library) before their first call.
+pub(crate) fn library() -> &'static SdifLibrary {
+    match load() {
+        Ok(library) => library,
+        Err(reason) => panic!("{}", reason),
+    }
+}
+
+/// Check whether the loaded library exports `symbol`, a nul-terminated
+/// name.
+///
+/// Functions missing from older releases are only an error when called,
+/// so this lets callers test for them first.
+pub(crate) fn has_symbol(symbol: &[u8]) -> bool {
+    match load() {
+        // SAFETY: the symbol is only looked up, never called
+        Ok(library) => unsafe { library.__library.get::<*const ()>(symbol).is_ok() },
+        Err(_) => false,
+    }
+}
+
+/// Open the first library that loads.
+fn open() -> Result<SdifLibrary, String> {
+    let names: Vec<OsString> = match env::var_os(LIBRARY_ENV) {
+        Some(path) => vec![path],
+        None => LIBRARY_NAMES.iter().map(OsString::from).collect(),
+    };
+
+    let mut errors = Vec::new();
+    for name in &names {
+        // SAFETY: loading runs the library's initialisers, and libsdif has
+        // none beyond the C runtime's
+        match unsafe { SdifLibrary::new(name) } {
+            Ok(library) => return Ok(library),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
+    Err(format!(
+        "SDIF shared library not installed ({}; set {} to its path)",
+        errors.join("; "),
+        LIBRARY_ENV
+    ))
+}
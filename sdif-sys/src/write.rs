@@ -0,0 +1,460 @@
+//! Size-checked wrappers over the frame and matrix write functions.
+//!
+//! The C write functions take an untyped data pointer and trust it to hold
+//! `rows * cols` elements of the matrix data type. The wrappers here take
+//! the data as bytes, check its length against the dimensions and data
+//! type, and compute the frame sizes and padding the C library expects, so
+//! callers only have to keep the file handle valid.
+//!
+//! [`bytes_of()`] turns a slice of numbers into the bytes to write, in
+//! native byte order as the C library expects.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sdif_sys::write::{self, MatrixElement};
+//! use sdif_sys::*;
+//!
+//! # unsafe fn example(file: *mut SdifFileT) -> Result<(), write::WriteError> {
+//! let data = [440.0f64, 0.9];
+//! write::write_frame_and_one_matrix(
+//!     file,
+//!     SIG_1FQ0,
+//!     0,
+//!     0.5,
+//!     SIG_1FQ0,
+//!     f64::DATA_TYPE,
+//!     1,
+//!     2,
+//!     write::bytes_of(&data),
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::ffi::c_void;
+use std::fmt;
+
+use crate::{
+    SdifDataTypeET, SdifFSetCurrFrameHeader, SdifFSetCurrMatrixHeader, SdifFWriteFrameAndOneMatrix,
+    SdifFWriteFrameHeader, SdifFWriteMatrixData, SdifFWriteMatrixHeader, SdifFWritePadding,
+    SdifFileT, SdifSignature,
+};
+
+/// Size counted in a frame header's size field for the header itself:
+/// time (8), stream ID (4) and matrix count (4).
+pub const FRAME_HEADER_SIZE: u32 = 16;
+
+/// Size of a matrix header: signature, data type, rows and columns.
+pub const MATRIX_HEADER_SIZE: u32 = 16;
+
+/// Error from a size-checked write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteError {
+    /// The data type code isn't one the library can write.
+    UnknownDataType(SdifDataTypeET),
+
+    /// The data doesn't have `rows * cols` elements of the data type.
+    SizeMismatch {
+        /// Expected length in bytes.
+        expected: usize,
+        /// Actual length in bytes.
+        actual: usize,
+    },
+
+    /// The frame or matrix is too large for the 32-bit size fields.
+    TooLarge,
+
+    /// The library wrote nothing.
+    Failed(&'static str),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::UnknownDataType(data_type) => {
+                write!(f, "Unknown SDIF data type 0x{:04x}", data_type)
+            }
+            WriteError::SizeMismatch { expected, actual } => {
+                write!(f, "Matrix data has {} bytes, expected {}", actual, expected)
+            }
+            WriteError::TooLarge => f.write_str("Frame is too large for SDIF size fields"),
+            WriteError::Failed(what) => write!(f, "Failed to write {}", what),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+/// A number type that can be written as matrix data.
+///
+/// Implemented for the primitive types matching the SDIF data types.
+pub trait MatrixElement: Copy + sealed::Sealed {
+    /// SDIF data type code of this type.
+    const DATA_TYPE: SdifDataTypeET;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+// Codes of the `SdifDataTypeE` enum in sdif.h
+macro_rules! matrix_element {
+    ($($ty:ty => $code:expr),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $ty {}
+            impl MatrixElement for $ty {
+                const DATA_TYPE: SdifDataTypeET = $code;
+            }
+        )*
+    };
+}
+
+matrix_element! {
+    f32 => 0x0004,
+    f64 => 0x0008,
+    i8 => 0x0101,
+    i16 => 0x0102,
+    i32 => 0x0104,
+    i64 => 0x0108,
+    u8 => 0x0201,
+    u16 => 0x0202,
+    u32 => 0x0204,
+    u64 => 0x0208,
+}
+
+/// View matrix elements as the bytes to pass to the write functions.
+pub fn bytes_of<T: MatrixElement>(data: &[T]) -> &[u8] {
+    // SAFETY: the element types are plain numbers without padding, and the
+    // byte length is that of the slice.
+    unsafe { std::slice::from_raw_parts(data.as_ptr().cast::<u8>(), std::mem::size_of_val(data)) }
+}
+
+/// Get the size in bytes of one element of `data_type`.
+///
+/// Returns `None` for codes the library can't write, including the
+/// float codes of SDIF versions before 3.
+pub fn data_type_size(data_type: SdifDataTypeET) -> Option<usize> {
+    match data_type as u32 {
+        // Text, then the float, signed and unsigned types; the low byte
+        // of the code is the element size
+        0x0301 | 0x0004 | 0x0008 | 0x0101 | 0x0102 | 0x0104 | 0x0108 | 0x0201 | 0x0202 | 0x0204
+        | 0x0208 => Some((data_type as u32 & 0xff) as usize),
+        _ => None,
+    }
+}
+
+/// Get the number of padding bytes after `bytes` of matrix data.
+///
+/// Matrices are padded to a multiple of 8 bytes.
+pub const fn padding(bytes: usize) -> usize {
+    (8 - bytes % 8) % 8
+}
+
+/// Get the length in bytes of the data of a `rows` × `cols` matrix.
+///
+/// # Errors
+///
+/// - [`WriteError::UnknownDataType`] if `data_type` is unknown
+/// - [`WriteError::TooLarge`] if the length overflows
+pub fn matrix_data_size(
+    data_type: SdifDataTypeET,
+    rows: u32,
+    cols: u32,
+) -> Result<usize, WriteError> {
+    let element = data_type_size(data_type).ok_or(WriteError::UnknownDataType(data_type))?;
+    (rows as usize)
+        .checked_mul(cols as usize)
+        .and_then(|n| n.checked_mul(element))
+        .ok_or(WriteError::TooLarge)
+}
+
+/// Get the size a matrix takes in a frame: header, data and padding.
+///
+/// # Errors
+///
+/// Same as [`matrix_data_size()`], and [`WriteError::TooLarge`] if the
+/// size doesn't fit in a frame.
+pub fn matrix_size(data_type: SdifDataTypeET, rows: u32, cols: u32) -> Result<u32, WriteError> {
+    let data = matrix_data_size(data_type, rows, cols)?;
+    let size = data
+        .checked_add(padding(data))
+        .and_then(|n| n.checked_add(MATRIX_HEADER_SIZE as usize))
+        .ok_or(WriteError::TooLarge)?;
+    u32::try_from(size).map_err(|_| WriteError::TooLarge)
+}
+
+/// Get the size to store in the header of a frame holding `matrices`,
+/// each given as (data type, rows, columns).
+///
+/// # Errors
+///
+/// Same as [`matrix_size()`].
+pub fn frame_size(
+    matrices: impl IntoIterator<Item = (SdifDataTypeET, u32, u32)>,
+) -> Result<u32, WriteError> {
+    matrices
+        .into_iter()
+        .try_fold(FRAME_HEADER_SIZE, |size, (data_type, rows, cols)| {
+            size.checked_add(matrix_size(data_type, rows, cols)?)
+                .ok_or(WriteError::TooLarge)
+        })
+}
+
+/// Write a frame holding a single matrix.
+///
+/// `data` holds the matrix elements in row-major order and native byte
+/// order. Returns the number of bytes written.
+///
+/// # Errors
+///
+/// - [`WriteError::UnknownDataType`] or [`WriteError::SizeMismatch`] if
+///   `data` doesn't match the data type and dimensions; nothing is written
+/// - [`WriteError::TooLarge`] if the frame doesn't fit the size fields
+/// - [`WriteError::Failed`] if the library wrote nothing
+///
+/// # Safety
+///
+/// `file` must be a valid handle opened for writing, with its header and
+/// ASCII chunks already written.
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn write_frame_and_one_matrix(
+    file: *mut SdifFileT,
+    frame_signature: SdifSignature,
+    stream_id: u32,
+    time: f64,
+    matrix_signature: SdifSignature,
+    data_type: SdifDataTypeET,
+    rows: u32,
+    cols: u32,
+    data: &[u8],
+) -> Result<usize, WriteError> {
+    check_data(data_type, rows, cols, data)?;
+    frame_size([(data_type, rows, cols)])?;
+
+    let written = with_aligned(data_type, data, |ptr| {
+        SdifFWriteFrameAndOneMatrix(
+            file,
+            frame_signature,
+            stream_id,
+            time,
+            matrix_signature,
+            data_type,
+            rows,
+            cols,
+            ptr,
+        )
+    });
+    written_or(written as usize, "frame")
+}
+
+/// Write the header of a frame whose matrices are written next with
+/// [`write_matrix()`].
+///
+/// `size` is the frame size from [`frame_size()`]. Returns the number of
+/// bytes written.
+///
+/// # Errors
+///
+/// - [`WriteError::Failed`] if the library wrote nothing
+///
+/// # Safety
+///
+/// Same as [`write_frame_and_one_matrix()`].
+pub unsafe fn write_frame_header(
+    file: *mut SdifFileT,
+    signature: SdifSignature,
+    stream_id: u32,
+    time: f64,
+    matrices: u32,
+    size: u32,
+) -> Result<usize, WriteError> {
+    SdifFSetCurrFrameHeader(file, signature, size, matrices, stream_id, time);
+    written_or(SdifFWriteFrameHeader(file) as usize, "frame header")
+}
+
+/// Write one matrix of a frame: header, data and padding.
+///
+/// `data` holds the matrix elements in row-major order and native byte
+/// order. Returns the number of bytes written.
+///
+/// # Errors
+///
+/// - [`WriteError::UnknownDataType`] or [`WriteError::SizeMismatch`] if
+///   `data` doesn't match the data type and dimensions; nothing is written
+/// - [`WriteError::Failed`] if the library wrote nothing
+///
+/// # Safety
+///
+/// `file` must be a valid handle opened for writing, inside a frame
+/// started with [`write_frame_header()`].
+pub unsafe fn write_matrix(
+    file: *mut SdifFileT,
+    signature: SdifSignature,
+    data_type: SdifDataTypeET,
+    rows: u32,
+    cols: u32,
+    data: &[u8],
+) -> Result<usize, WriteError> {
+    check_data(data_type, rows, cols, data)?;
+
+    SdifFSetCurrMatrixHeader(file, signature, data_type, rows, cols);
+    let header = written_or(SdifFWriteMatrixHeader(file) as usize, "matrix header")?;
+
+    // Empty matrices, such as frames without partials, have no data
+    if data.is_empty() {
+        return Ok(header);
+    }
+
+    let written = with_aligned(data_type, data, |ptr| SdifFWriteMatrixData(file, ptr));
+    let written = written_or(written as usize, "matrix data")?;
+    SdifFWritePadding(file, padding(written) as _);
+
+    Ok(header + written + padding(written))
+}
+
+/// Check that `data` holds a `rows` × `cols` matrix of `data_type`.
+fn check_data(
+    data_type: SdifDataTypeET,
+    rows: u32,
+    cols: u32,
+    data: &[u8],
+) -> Result<(), WriteError> {
+    let expected = matrix_data_size(data_type, rows, cols)?;
+    if data.len() != expected {
+        return Err(WriteError::SizeMismatch {
+            expected,
+            actual: data.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Call `f` with a pointer to `data` aligned for `data_type`.
+///
+/// The library reads the data through typed pointers, so misaligned bytes
+/// are copied first.
+fn with_aligned<R>(data_type: SdifDataTypeET, data: &[u8], f: impl FnOnce(*mut c_void) -> R) -> R {
+    let align = data_type_size(data_type).unwrap_or(1);
+    if data.as_ptr().align_offset(align) == 0 {
+        return f(data.as_ptr() as *mut c_void);
+    }
+
+    let mut aligned = vec![0u64; (data.len() + 7) / 8];
+    // SAFETY: the buffer has at least `data.len()` bytes.
+    unsafe {
+        std::ptr::copy_nonoverlapping(data.as_ptr(), aligned.as_mut_ptr().cast::<u8>(), data.len());
+    }
+    f(aligned.as_mut_ptr().cast::<c_void>())
+}
+
+/// Turn a byte count of 0 into an error.
+fn written_or(bytes: usize, what: &'static str) -> Result<usize, WriteError> {
+    if bytes == 0 {
+        Err(WriteError::Failed(what))
+    } else {
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        SdifDataTypeET_eFloat4, SdifDataTypeET_eFloat8, SdifFClose, SdifFOpen,
+        SdifFWriteAllASCIIChunks, SdifFWriteGeneralHeader, SdifFileModeET_eWriteFile, SdifGenInit,
+        lock_ffi, SIG_1FQ0, SIG_1TRC,
+    };
+    use std::ffi::CString;
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(f32::DATA_TYPE, SdifDataTypeET_eFloat4);
+        assert_eq!(f64::DATA_TYPE, SdifDataTypeET_eFloat8);
+        assert_eq!(data_type_size(u16::DATA_TYPE), Some(2));
+        assert_eq!(data_type_size(0x0020), None);
+
+        assert_eq!(matrix_size(f64::DATA_TYPE, 1, 2), Ok(32));
+        assert_eq!(matrix_size(f32::DATA_TYPE, 1, 3), Ok(32));
+        assert_eq!(matrix_size(f32::DATA_TYPE, 0, 4), Ok(16));
+        assert_eq!(
+            frame_size([(f64::DATA_TYPE, 1, 2), (f32::DATA_TYPE, 2, 4)]),
+            Ok(16 + 32 + 48)
+        );
+        assert_eq!(
+            matrix_size(f64::DATA_TYPE, u32::MAX, u32::MAX),
+            Err(WriteError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_write_checks_sizes() {
+        let _lock = lock_ffi();
+        let path = std::env::temp_dir().join(format!("sdif-sys-write-{}.sdif", std::process::id()));
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            SdifGenInit(std::ptr::null());
+            let file = SdifFOpen(c_path.as_ptr(), SdifFileModeET_eWriteFile);
+            assert!(!file.is_null());
+            SdifFWriteGeneralHeader(file);
+            SdifFWriteAllASCIIChunks(file);
+
+            let short = bytes_of(&[440.0f64]);
+            assert_eq!(
+                write_frame_and_one_matrix(
+                    file,
+                    SIG_1FQ0,
+                    0,
+                    0.0,
+                    SIG_1FQ0,
+                    f64::DATA_TYPE,
+                    1,
+                    2,
+                    short
+                ),
+                Err(WriteError::SizeMismatch {
+                    expected: 16,
+                    actual: 8
+                })
+            );
+
+            // Misaligned data is copied before being written
+            let mut buffer = vec![0u8; 17];
+            buffer[1..].copy_from_slice(bytes_of(&[440.0f64, 0.9]));
+            let written = write_frame_and_one_matrix(
+                file,
+                SIG_1FQ0,
+                0,
+                0.5,
+                SIG_1FQ0,
+                f64::DATA_TYPE,
+                1,
+                2,
+                &buffer[1..],
+            );
+            assert_eq!(written, Ok(8 + 16 + 32));
+
+            let size = frame_size([(f32::DATA_TYPE, 1, 3)]).unwrap();
+            assert!(write_frame_header(file, SIG_1TRC, 1, 1.0, 1, size).is_ok());
+            let data = bytes_of(&[1.0f32, 2.0, 3.0]);
+            assert_eq!(
+                write_matrix(file, SIG_1TRC, f32::DATA_TYPE, 1, 3, data),
+                Ok(32)
+            );
+            SdifFClose(file);
+        }
+
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // The last two frames, big-endian on disk
+        let frames = &bytes[bytes.len() - 112..];
+        assert_eq!(&frames[..4], b"1FQ0");
+        assert_eq!(&frames[4..8], &48u32.to_be_bytes());
+        assert_eq!(&frames[40..48], &440.0f64.to_be_bytes());
+        assert_eq!(&frames[56..60], b"1TRC");
+        assert_eq!(&frames[60..64], &48u32.to_be_bytes());
+        assert_eq!(&frames[104..108], &3.0f32.to_be_bytes());
+    }
+}
@@ -0,0 +1,181 @@
+//! Command-line argument definitions using clap derive macros.
+
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Longest variable name MATLAB accepts.
+const MAX_NAME_LEN: usize = 63;
+
+/// Convert SDIF files to MATLAB/Octave .mat files.
+///
+/// sdif2mat reads the frames of one type from an SDIF file and writes a
+/// time vector and a data matrix with one row per frame, the layout
+/// mat2sdif reads back.
+#[derive(Parser, Debug)]
+#[command(name = "sdif2mat")]
+#[command(author, version, about, long_about = None)]
+#[command(after_help = EXAMPLES)]
+pub struct Args {
+    /// Input .sdif file (use `-` to read from standard input)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output .mat file
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    // ========================================================================
+    // Frame Selection
+    // ========================================================================
+    /// SDIF frame type signature of the frames to convert (4 characters)
+    #[arg(short = 'f', long, value_name = "SIG", default_value = "1TRC")]
+    pub frame_type: String,
+
+    /// SDIF matrix type signature to read from each frame (4 characters)
+    ///
+    /// Defaults to the frame type.
+    #[arg(short = 'm', long, value_name = "SIG")]
+    pub matrix_type: Option<String>,
+
+    /// Only convert frames of this stream ID
+    ///
+    /// By default frames of every stream are converted.
+    #[arg(long, value_name = "ID")]
+    pub stream: Option<u32>,
+
+    // ========================================================================
+    // MAT Layout
+    // ========================================================================
+    /// Name of the time vector variable
+    #[arg(short = 't', long = "time-var", value_name = "NAME", default_value = "time")]
+    pub time_var: String,
+
+    /// Name of the data matrix variable
+    #[arg(short = 'd', long = "data-var", value_name = "NAME", default_value = "data")]
+    pub data_var: String,
+
+    /// Transpose the data matrix (one column per frame)
+    ///
+    /// Use this to match files converted with `mat2sdif --transpose`.
+    #[arg(long)]
+    pub transpose: bool,
+
+    // ========================================================================
+    // Output Control
+    // ========================================================================
+    /// Suppress all non-error output
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Force overwrite of existing output file
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl Args {
+    /// Validate argument combinations.
+    pub fn validate(&self) -> Result<(), String> {
+        // Validate signature lengths
+        if self.frame_type.len() != 4 {
+            return Err(format!(
+                "Frame type must be exactly 4 characters, got '{}'",
+                self.frame_type
+            ));
+        }
+
+        if self.matrix_type().len() != 4 {
+            return Err(format!(
+                "Matrix type must be exactly 4 characters, got '{}'",
+                self.matrix_type()
+            ));
+        }
+
+        for name in [&self.time_var, &self.data_var] {
+            if !is_variable_name(name) {
+                return Err(format!("Invalid MATLAB variable name: '{}'", name));
+            }
+        }
+        if self.time_var == self.data_var {
+            return Err("Time and data variables must have different names".to_string());
+        }
+
+        // Check input file exists
+        if !self.input_is_stdin() && !self.input.exists() {
+            return Err(format!(
+                "Input file not found: {}",
+                self.input.display()
+            ));
+        }
+
+        // Check output doesn't exist (unless --force)
+        if self.output.exists() && !self.force {
+            return Err(format!(
+                "Output file already exists: {} (use --force to overwrite)",
+                self.output.display()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Get the matrix type to read, defaulting to the frame type.
+    pub fn matrix_type(&self) -> &str {
+        self.matrix_type.as_deref().unwrap_or(&self.frame_type)
+    }
+
+    /// Check whether the input should be read from standard input.
+    pub fn input_is_stdin(&self) -> bool {
+        self.input.as_os_str() == "-"
+    }
+
+    /// Get the input name for messages.
+    pub fn input_name(&self) -> String {
+        if self.input_is_stdin() {
+            "<stdin>".to_string()
+        } else {
+            self.input.display().to_string()
+        }
+    }
+}
+
+/// Check whether `name` is a valid MATLAB variable name.
+fn is_variable_name(name: &str) -> bool {
+    name.len() <= MAX_NAME_LEN
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Example usage shown in --help.
+const EXAMPLES: &str = r#"
+EXAMPLES:
+    # Convert sinusoidal tracks (1TRC frames)
+    sdif2mat analysis.sdif tracks.mat
+
+    # Convert F0 data with custom variable names
+    sdif2mat f0.sdif pitch.mat -f 1FQ0 -t t -d f0
+
+    # Only convert the frames of stream 2
+    sdif2mat --stream 2 analysis.sdif tracks.mat
+
+    # One column per frame, as read by mat2sdif --transpose
+    sdif2mat --transpose analysis.sdif tracks.mat
+
+    # Read the SDIF file from a pipeline
+    fetch-analysis | sdif2mat - tracks.mat
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_variable_name() {
+        assert!(is_variable_name("time"));
+        assert!(is_variable_name("f0_track2"));
+        assert!(!is_variable_name(""));
+        assert!(!is_variable_name("2d"));
+        assert!(!is_variable_name("_data"));
+        assert!(!is_variable_name("my-data"));
+        assert!(!is_variable_name(&"x".repeat(64)));
+    }
+}
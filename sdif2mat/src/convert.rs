@@ -0,0 +1,183 @@
+//! Main conversion command.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+use anyhow::{bail, Context, Result};
+
+use sdif_rs::{FrameFilter, SdifFile};
+
+use crate::cli::Args;
+use crate::mat_writer::MatWriter;
+
+/// Run the conversion.
+pub fn run(args: &Args) -> Result<()> {
+    let file = open_input(args)?;
+    let frames = Frames::collect(&file, args)
+        .with_context(|| format!("Failed to read SDIF file: {}", args.input_name()))?;
+
+    if frames.times.is_empty() {
+        bail!(
+            "No {} frames with {} matrices found in {}",
+            args.frame_type,
+            args.matrix_type(),
+            args.input_name()
+        );
+    }
+
+    let (rows, cols, data) = frames.data_matrix(args.transpose);
+    write_mat(args, &frames.times, rows, cols, &data)
+        .with_context(|| format!("Failed to write MAT file: {}", args.output.display()))?;
+
+    if !args.quiet {
+        println!(
+            "Converted {} frames ({:.3}s to {:.3}s) to {}: {} {}x1, {} {}x{}",
+            frames.times.len(),
+            frames.times[0],
+            frames.times[frames.times.len() - 1],
+            args.output.display(),
+            args.time_var,
+            frames.times.len(),
+            args.data_var,
+            rows,
+            cols
+        );
+    }
+
+    Ok(())
+}
+
+/// Open the input file, reading standard input for `-`.
+fn open_input(args: &Args) -> Result<SdifFile> {
+    if args.input_is_stdin() {
+        SdifFile::from_reader(io::stdin().lock())
+    } else {
+        SdifFile::open(&args.input)
+    }
+    .with_context(|| format!("Failed to open SDIF file: {}", args.input_name()))
+}
+
+/// Write the time vector and data matrix to the output file.
+fn write_mat(args: &Args, times: &[f64], rows: usize, cols: usize, data: &[f64]) -> Result<()> {
+    let out = BufWriter::new(File::create(&args.output)?);
+    let description = format!("Created by sdif2mat from {}", args.input_name());
+
+    let mut writer = MatWriter::new(out, &description)?;
+    writer.write_matrix(&args.time_var, times.len(), 1, times)?;
+    writer.write_matrix(&args.data_var, rows, cols, data)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Times and values of the frames being converted.
+struct Frames {
+    /// Frame times in file order.
+    times: Vec<f64>,
+
+    /// Values of each frame's matrix in row-major order, empty for frames
+    /// without the matrix.
+    values: Vec<Vec<f64>>,
+}
+
+impl Frames {
+    /// Read the frames selected by the arguments.
+    ///
+    /// Every matrix must have the same number of columns, so each frame
+    /// fits in one row of the data matrix.
+    fn collect(file: &SdifFile, args: &Args) -> Result<Self> {
+        let mut filter = FrameFilter::new().signature(&args.frame_type);
+        if let Some(stream) = args.stream {
+            filter = filter.stream_id(stream);
+        }
+
+        let mut frames = Frames {
+            times: Vec::new(),
+            values: Vec::new(),
+        };
+        // Column count of the first non-empty matrix
+        let mut columns: Option<usize> = None;
+
+        for frame in file.frames_filtered(filter) {
+            let mut frame = frame?;
+            let time = frame.time();
+            let mut values = Vec::new();
+
+            for matrix in frame.matrices() {
+                let matrix = matrix?;
+                if matrix.signature() != args.matrix_type() {
+                    continue;
+                }
+                if matrix.rows() > 0 {
+                    let cols = *columns.get_or_insert(matrix.cols());
+                    if matrix.cols() != cols {
+                        bail!(
+                            "Frame at {}s has {} columns, earlier frames have {}",
+                            time,
+                            matrix.cols(),
+                            cols
+                        );
+                    }
+                }
+                values = matrix.data_f64()?;
+                break;
+            }
+
+            frames.times.push(time);
+            frames.values.push(values);
+        }
+
+        Ok(frames)
+    }
+
+    /// Lay out the values as a column-major matrix with one row per frame,
+    /// or one column per frame when transposed.
+    ///
+    /// Frames with fewer matrix rows than the largest are padded with NaN.
+    /// Returns the number of rows and columns, and the data.
+    fn data_matrix(&self, transpose: bool) -> (usize, usize, Vec<f64>) {
+        let num_frames = self.values.len();
+        let width = self.values.iter().map(Vec::len).max().unwrap_or(0);
+        let mut data = vec![f64::NAN; num_frames * width];
+
+        for (frame, values) in self.values.iter().enumerate() {
+            for (i, &value) in values.iter().enumerate() {
+                let index = if transpose {
+                    frame * width + i
+                } else {
+                    i * num_frames + frame
+                };
+                data[index] = value;
+            }
+        }
+
+        if transpose {
+            (width, num_frames, data)
+        } else {
+            (num_frames, width, data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_matrix() {
+        let frames = Frames {
+            times: vec![0.0, 0.1],
+            values: vec![vec![1.0, 2.0, 3.0, 4.0], vec![5.0, 6.0]],
+        };
+
+        let (rows, cols, data) = frames.data_matrix(false);
+        assert_eq!((rows, cols), (2, 4));
+        assert_eq!(&data[..4], &[1.0, 5.0, 2.0, 6.0]);
+        assert_eq!((data[4], data[6]), (3.0, 4.0));
+        assert!(data[5].is_nan() && data[7].is_nan());
+
+        let (rows, cols, data) = frames.data_matrix(true);
+        assert_eq!((rows, cols), (4, 2));
+        assert_eq!(&data[..6], &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert!(data[6].is_nan() && data[7].is_nan());
+    }
+}
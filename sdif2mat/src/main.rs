@@ -0,0 +1,32 @@
+//! sdif2mat - Convert SDIF files to MATLAB/Octave .mat files.
+//!
+//! This tool is the reverse of mat2sdif: it collects the frames of one
+//! type from an SDIF file and writes their times and data as two numeric
+//! arrays in a Level 5 MAT file.
+
+mod cli;
+mod convert;
+mod mat_writer;
+
+use anyhow::Result;
+use clap::Parser;
+
+use cli::Args;
+
+fn main() {
+    let args = Args::parse();
+
+    if let Err(e) = run(args) {
+        eprintln!("error: {}", e);
+        for cause in e.chain().skip(1) {
+            eprintln!("  caused by: {}", cause);
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Validate the arguments and run the conversion.
+fn run(args: Args) -> Result<()> {
+    args.validate().map_err(|e| anyhow::anyhow!("{}", e))?;
+    convert::run(&args)
+}
@@ -0,0 +1,122 @@
+//! Minimal Level 5 MAT file writer.
+//!
+//! Only real double matrices are written, uncompressed and little-endian,
+//! which MATLAB and Octave both read. See the MathWorks "MAT-File Format"
+//! reference for the layout: a 128-byte header followed by one `miMATRIX`
+//! element per variable.
+
+use std::io::{self, Write};
+
+/// Size of the descriptive text at the start of the header.
+const HEADER_TEXT_SIZE: usize = 116;
+
+/// Level 5 format version.
+const VERSION: u16 = 0x0100;
+
+// Data element types
+const MI_INT8: u32 = 1;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_DOUBLE: u32 = 9;
+const MI_MATRIX: u32 = 14;
+
+/// Array class of double matrices.
+const MX_DOUBLE_CLASS: u32 = 6;
+
+/// Writes variables to a MAT file.
+pub struct MatWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> MatWriter<W> {
+    /// Write the file header, with `description` in its text.
+    pub fn new(mut out: W, description: &str) -> io::Result<Self> {
+        let mut text = format!("MATLAB 5.0 MAT-file, {}", description).into_bytes();
+        text.truncate(HEADER_TEXT_SIZE);
+        text.resize(HEADER_TEXT_SIZE, b' ');
+
+        out.write_all(&text)?;
+        // No subsystem data
+        out.write_all(&[0; 8])?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        // Endian indicator, reads "MI" on big-endian machines
+        out.write_all(b"IM")?;
+
+        Ok(MatWriter { out })
+    }
+
+    /// Write a `rows` x `cols` matrix, with `data` in column-major order.
+    pub fn write_matrix(
+        &mut self,
+        name: &str,
+        rows: usize,
+        cols: usize,
+        data: &[f64],
+    ) -> io::Result<()> {
+        debug_assert_eq!(data.len(), rows * cols);
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "Matrix too large for MAT");
+        let rows = i32::try_from(rows).map_err(|_| too_large())?;
+        let cols = i32::try_from(cols).map_err(|_| too_large())?;
+
+        let mut body = Vec::with_capacity(64 + data.len() * 8);
+        push_element(&mut body, MI_UINT32, &[MX_DOUBLE_CLASS, 0].map(u32::to_le_bytes).concat());
+        push_element(&mut body, MI_INT32, &[rows, cols].map(i32::to_le_bytes).concat());
+        push_element(&mut body, MI_INT8, name.as_bytes());
+        let values: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        push_element(&mut body, MI_DOUBLE, &values);
+
+        let size = u32::try_from(body.len()).map_err(|_| too_large())?;
+        self.out.write_all(&MI_MATRIX.to_le_bytes())?;
+        self.out.write_all(&size.to_le_bytes())?;
+        self.out.write_all(&body)
+    }
+
+    /// Flush the output and return it.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Append a data element: its type, size and data padded to 8 bytes.
+fn push_element(out: &mut Vec<u8>, data_type: u32, data: &[u8]) {
+    out.extend_from_slice(&data_type.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    out.resize(out.len() + (8 - data.len() % 8) % 8, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn le_u32(bytes: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_header() {
+        let bytes = MatWriter::new(Vec::new(), "test").unwrap().finish().unwrap();
+        assert_eq!(bytes.len(), 128);
+        assert!(bytes.starts_with(b"MATLAB 5.0 MAT-file, test  "));
+        assert_eq!(&bytes[124..], &[0x00, 0x01, b'I', b'M']);
+    }
+
+    #[test]
+    fn test_write_matrix() {
+        let mut writer = MatWriter::new(Vec::new(), "test").unwrap();
+        writer.write_matrix("x", 2, 1, &[1.0, 2.0]).unwrap();
+        let bytes = writer.finish().unwrap();
+        let matrix = &bytes[128..];
+
+        assert_eq!(le_u32(matrix, 0), MI_MATRIX);
+        // Flags, dimensions and name elements, then 2 doubles
+        assert_eq!(le_u32(matrix, 4), 16 + 16 + 16 + 8 + 16);
+        assert_eq!(le_u32(matrix, 16), MX_DOUBLE_CLASS);
+        assert_eq!((le_u32(matrix, 32), le_u32(matrix, 36)), (2, 1));
+        assert_eq!(&matrix[48..49], b"x");
+        assert_eq!(le_u32(matrix, 56), MI_DOUBLE);
+        assert_eq!(&matrix[64..72], &1.0f64.to_le_bytes());
+        assert_eq!(matrix.len(), 8 + 72);
+    }
+}
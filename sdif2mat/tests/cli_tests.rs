@@ -0,0 +1,202 @@
+//! Integration tests for sdif2mat CLI.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use sdif_rs::SdifFile;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Get the sdif2mat command.
+fn sdif2mat() -> Command {
+    Command::cargo_bin("sdif2mat").unwrap()
+}
+
+/// Write 3 1TRC frames on stream 0 and one on stream 1 to `dir`.
+///
+/// Frame `i` holds `i + 1` partials, with frequencies 100, 200, ...
+fn partials_sdif(dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("partials.sdif");
+    let mut writer = SdifFile::builder()
+        .create(&path)
+        .unwrap()
+        .add_matrix_type("1TRC", &["Index", "Frequency", "Amplitude", "Phase"])
+        .unwrap()
+        .add_frame_type("1TRC", &["1TRC SinusoidalTracks"])
+        .unwrap()
+        .build()
+        .unwrap();
+
+    for i in 0..3 {
+        let data: Vec<f64> = (0..=i)
+            .flat_map(|p| [p as f64, (p + 1) as f64 * 100.0, 0.5, 0.0])
+            .collect();
+        writer
+            .write_frame_one_matrix("1TRC", i as f64 * 0.01, "1TRC", i + 1, 4, &data)
+            .unwrap();
+    }
+    writer
+        .new_frame("1TRC", 0.03, 1)
+        .unwrap()
+        .add_matrix("1TRC", 1, 4, &[0.0, 50.0, 0.1, 0.0])
+        .unwrap()
+        .finish()
+        .unwrap();
+    writer.close().unwrap();
+    path
+}
+
+/// Read the variables of a MAT file written by sdif2mat.
+///
+/// Returns each variable's name, dimensions and column-major data.
+fn read_mat(path: &Path) -> Vec<(String, usize, usize, Vec<f64>)> {
+    let bytes = fs::read(path).unwrap();
+    assert!(bytes.starts_with(b"MATLAB 5.0 MAT-file"));
+    let u32_at = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+    let mut variables = Vec::new();
+    let mut pos = 128;
+    while pos < bytes.len() {
+        let end = pos + 8 + u32_at(pos + 4) as usize;
+        let (rows, cols) = (u32_at(pos + 32) as usize, u32_at(pos + 36) as usize);
+        let name_len = u32_at(pos + 44) as usize;
+        let name = String::from_utf8(bytes[pos + 48..pos + 48 + name_len].to_vec()).unwrap();
+
+        let values = pos + 48 + (name_len + 7) / 8 * 8;
+        let data = bytes[values + 8..values + 8 + rows * cols * 8]
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        variables.push((name, rows, cols, data));
+        pos = end;
+    }
+    variables
+}
+
+// ============================================================================
+// Basic CLI Tests
+// ============================================================================
+
+#[test]
+fn test_help() {
+    sdif2mat()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Convert SDIF files"))
+        .stdout(predicate::str::contains("--frame-type"))
+        .stdout(predicate::str::contains("--stream"))
+        .stdout(predicate::str::contains("--transpose"))
+        .stdout(predicate::str::contains("EXAMPLES"));
+}
+
+#[test]
+fn test_nonexistent_input() {
+    sdif2mat()
+        .arg("/nonexistent/file.sdif")
+        .arg("output.mat")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
+
+#[test]
+fn test_invalid_variable_name() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("in.sdif");
+    fs::write(&input, "dummy").unwrap();
+
+    sdif2mat()
+        .arg(&input)
+        .arg(dir.path().join("out.mat"))
+        .args(["--time-var", "2t"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid MATLAB variable name"));
+}
+
+// ============================================================================
+// Conversion Tests
+// ============================================================================
+
+#[test]
+fn test_convert() {
+    let dir = TempDir::new().unwrap();
+    let output = dir.path().join("out.mat");
+
+    sdif2mat()
+        .arg(partials_sdif(&dir))
+        .arg(&output)
+        .args(["--stream", "0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Converted 3 frames"));
+
+    let variables = read_mat(&output);
+    assert_eq!(variables.len(), 2);
+
+    let (name, rows, cols, times) = &variables[0];
+    assert_eq!((name.as_str(), *rows, *cols), ("time", 3, 1));
+    assert_eq!(times, &[0.0, 0.01, 0.02]);
+
+    // One row per frame, the shorter ones padded with NaN
+    let (name, rows, cols, data) = &variables[1];
+    assert_eq!((name.as_str(), *rows, *cols), ("data", 3, 12));
+    assert_eq!(&data[3..6], &[100.0, 100.0, 100.0]);
+    assert!(data[15].is_nan());
+    assert_eq!(&data[16..18], &[200.0, 200.0]);
+    assert!(data[27].is_nan() && data[28].is_nan());
+    assert_eq!(data[29], 300.0);
+}
+
+#[test]
+fn test_convert_transposed() {
+    let dir = TempDir::new().unwrap();
+    let output = dir.path().join("out.mat");
+
+    sdif2mat()
+        .arg(partials_sdif(&dir))
+        .arg(&output)
+        .args(["--transpose", "--quiet", "-t", "t", "-d", "tracks"])
+        .assert()
+        .success()
+        .stdout("");
+
+    let variables = read_mat(&output);
+    let (name, _, _, times) = &variables[0];
+    assert_eq!(name, "t");
+    assert_eq!(times, &[0.0, 0.01, 0.02, 0.03]);
+
+    // One column per frame, the stream 1 frame last
+    let (name, rows, cols, data) = &variables[1];
+    assert_eq!((name.as_str(), *rows, *cols), ("tracks", 12, 4));
+    assert_eq!(&data[36..40], &[0.0, 50.0, 0.1, 0.0]);
+}
+
+#[test]
+fn test_no_matching_frames() {
+    let dir = TempDir::new().unwrap();
+    sdif2mat()
+        .arg(partials_sdif(&dir))
+        .arg(dir.path().join("out.mat"))
+        .args(["--frame-type", "1FQ0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No 1FQ0 frames"));
+}
+
+#[test]
+fn test_refuses_overwrite() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("in.sdif");
+    let output = dir.path().join("out.mat");
+    fs::write(&input, "dummy").unwrap();
+    fs::write(&output, "existing").unwrap();
+
+    sdif2mat()
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+}